@@ -0,0 +1,107 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use jj_cli::commit_templater::CommitTemplateLanguage;
+use jj_cli::formatter::PlainTextFormatter;
+use jj_cli::template_builder;
+use jj_cli::template_parser::TemplateAliasesMap;
+use jj_lib::id_prefix::IdPrefixContext;
+use jj_lib::mailmap::Mailmap;
+use jj_lib::op_store::WorkspaceId;
+use jj_lib::repo_path::RepoPathUiConverter;
+use jj_lib::revset::{RevsetAliasesMap, RevsetParseContext};
+use testutils::{CommitGraphBuilder, TestRepo};
+
+const TEMPLATES: &[(&str, &str)] = &[
+    ("commit_id", "commit_id ++ \"\\n\""),
+    (
+        "log_line",
+        r#"
+        separate(" ",
+          change_id.shortest(8),
+          author.email(),
+          committer.timestamp(),
+          branches,
+          commit_id.short(),
+          if(empty, "(empty)"),
+          description.first_line(),
+        ) ++ "\n"
+        "#,
+    ),
+];
+
+/// Renders templates for every commit of a linear history.
+fn bench_render_commits(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_render_commits");
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let mut tx = test_repo.repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.mut_repo());
+    let mut commits = vec![graph_builder.initial_commit()];
+    for _ in 1..1_000 {
+        let commit = graph_builder.commit_with_parents(&[commits.last().unwrap()]);
+        commits.push(commit);
+    }
+    let repo = tx.commit("create commits").unwrap();
+
+    let path_converter = RepoPathUiConverter::Fs {
+        cwd: repo.repo_path().to_owned(),
+        base: repo.repo_path().to_owned(),
+    };
+    let workspace_id = WorkspaceId::default();
+    let aliases_map = RevsetAliasesMap::new();
+    let mailmap = Mailmap::empty();
+    let revset_parse_context = RevsetParseContext {
+        aliases_map: &aliases_map,
+        user_email: settings.user_email(),
+        mailmap: &mailmap,
+        workspace: None,
+    };
+    let id_prefix_context = IdPrefixContext::default();
+    let language = CommitTemplateLanguage::new(
+        repo.as_ref(),
+        &path_converter,
+        &workspace_id,
+        revset_parse_context,
+        &id_prefix_context,
+        None,
+    );
+    for (name, template_text) in TEMPLATES {
+        let template = template_builder::parse(
+            &language,
+            template_text,
+            &TemplateAliasesMap::new(),
+            CommitTemplateLanguage::wrap_commit,
+        )
+        .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new(*name, commits.len()),
+            &commits,
+            |b, commits| {
+                b.iter(|| {
+                    let mut output = Vec::new();
+                    let mut formatter = PlainTextFormatter::new(&mut output);
+                    for commit in commits {
+                        template.format(commit, &mut formatter).unwrap();
+                    }
+                    output
+                })
+            },
+        );
+    }
+}
+
+criterion_group!(benches, bench_render_commits);
+criterion_main!(benches);
@@ -25,7 +25,7 @@ use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::SystemTime;
-use std::{fs, str};
+use std::{fs, iter, str};
 
 use clap::builder::{
     MapValueParser, NonEmptyStringValueParser, TypedValueParser, ValueParserFactory,
@@ -36,11 +36,15 @@ use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use jj_lib::backend::{ChangeId, CommitId, MergedTreeId};
 use jj_lib::commit::Commit;
+use jj_lib::commit_builder::CommitBuilder;
+use jj_lib::fileset::{self, FilesetExpression};
+use jj_lib::fingerprint_cache::FileFingerprintCache;
 use jj_lib::git_backend::GitBackend;
 use jj_lib::gitignore::{GitIgnoreError, GitIgnoreFile};
 use jj_lib::hex_util::to_reverse_hex;
 use jj_lib::id_prefix::IdPrefixContext;
-use jj_lib::matchers::{EverythingMatcher, Matcher, PrefixMatcher};
+use jj_lib::mailmap::Mailmap;
+use jj_lib::matchers::{EverythingMatcher, Matcher};
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::{OpStoreError, OperationId, WorkspaceId};
@@ -50,7 +54,7 @@ use jj_lib::repo::{
     CheckOutCommitError, EditCommitError, MutableRepo, ReadonlyRepo, Repo, RepoLoader,
     StoreFactories, StoreLoadError,
 };
-use jj_lib::repo_path::{FsPathParseError, RepoPath, RepoPathBuf};
+use jj_lib::repo_path::{FsPathParseError, RepoPath, RepoPathBuf, RepoPathUiConverter};
 use jj_lib::revset::{
     Revset, RevsetAliasesMap, RevsetCommitRef, RevsetExpression, RevsetFilterPredicate,
     RevsetIteratorExt, RevsetParseContext, RevsetParseError, RevsetWorkspaceContext,
@@ -67,7 +71,7 @@ use jj_lib::working_copy::{
 use jj_lib::workspace::{
     default_working_copy_factories, LockedWorkspace, Workspace, WorkspaceLoadError, WorkspaceLoader,
 };
-use jj_lib::{dag_walk, file_util, git, op_heads_store, op_walk, revset};
+use jj_lib::{dag_walk, git, graph, op_heads_store, op_walk, revset};
 use once_cell::unsync::OnceCell;
 use tracing::instrument;
 use tracing_chrome::ChromeLayerBuilder;
@@ -83,6 +87,7 @@ use crate::config::{
     new_config_path, AnnotatedValue, CommandNameAndArgs, ConfigSource, LayeredConfigs,
 };
 use crate::formatter::{FormatRecorder, Formatter, PlainTextFormatter};
+use crate::generic_templater::GenericTemplateLanguage;
 use crate::git_util::{
     is_colocated_git_workspace, print_failed_git_export, print_git_import_stats,
 };
@@ -90,9 +95,9 @@ use crate::merge_tools::{DiffEditor, MergeEditor, MergeToolConfigError};
 use crate::operation_templater::OperationTemplateLanguageExtension;
 use crate::template_builder::TemplateLanguage;
 use crate::template_parser::TemplateAliasesMap;
-use crate::templater::{PropertyPlaceholder, TemplateRenderer};
+use crate::templater::{PropertyPlaceholder, TemplatePropertyExt as _, TemplateRenderer};
 use crate::ui::{ColorChoice, Ui};
-use crate::{revset_util, template_builder, text_util};
+use crate::{description_util, revset_util, template_builder, text_util};
 
 #[derive(Clone)]
 struct ChromeTracingFlushGuard {
@@ -194,6 +199,7 @@ pub struct CommandHelper {
     maybe_workspace_loader: Result<WorkspaceLoader, CommandError>,
     store_factories: StoreFactories,
     working_copy_factories: HashMap<String, Box<dyn WorkingCopyFactory>>,
+    mailmap: OnceCell<Mailmap>,
 }
 
 impl CommandHelper {
@@ -253,12 +259,19 @@ impl CommandHelper {
         wrap_self: impl Fn(PropertyPlaceholder<C>) -> L::Property,
     ) -> Result<TemplateRenderer<'a, C>, CommandError> {
         let aliases = self.load_template_aliases(ui)?;
-        Ok(template_builder::parse(
-            language,
-            template_text,
-            &aliases,
-            wrap_self,
-        )?)
+        let template = template_builder::parse(language, template_text, &aliases, wrap_self)?;
+        Ok(template.with_strict(self.global_args.template_strict))
+    }
+
+    /// Loads the `.mailmap` file at the workspace root. The file is read once
+    /// per command.
+    fn load_mailmap(&self, workspace_root: &Path) -> Result<&Mailmap, CommandError> {
+        self.mailmap.get_or_try_init(|| {
+            let mailmap_path = workspace_root.join(".mailmap");
+            Mailmap::load(&mailmap_path).map_err(|err| {
+                user_error_with_message(format!("Failed to read {}", mailmap_path.display()), err)
+            })
+        })
     }
 
     pub fn operation_template_extension(&self) -> Option<&dyn OperationTemplateLanguageExtension> {
@@ -345,7 +358,7 @@ impl CommandHelper {
                         }
                     }
                     Ok(tx
-                        .write("resolve concurrent operations")
+                        .write("resolve concurrent operations")?
                         .leave_unpublished()
                         .operation()
                         .clone())
@@ -367,6 +380,20 @@ impl CommandHelper {
     ) -> Result<WorkspaceCommandHelper, CommandError> {
         WorkspaceCommandHelper::new(ui, self, workspace, repo)
     }
+
+    /// Creates a helper for a workspace created by this command. Unlike
+    /// `for_loaded_repo()`, the working copy may be updated even if the command
+    /// was run with `--at-op`, so the `repo` should be loaded at head.
+    pub fn for_new_workspace(
+        &self,
+        ui: &mut Ui,
+        workspace: Workspace,
+        repo: Arc<ReadonlyRepo>,
+    ) -> Result<WorkspaceCommandHelper, CommandError> {
+        let mut workspace_command = WorkspaceCommandHelper::new(ui, self, workspace, repo)?;
+        workspace_command.may_update_working_copy = !self.global_args.ignore_working_copy;
+        Ok(workspace_command)
+    }
 }
 
 /// A ReadonlyRepo along with user-config-dependent derived data. The derived
@@ -389,10 +416,15 @@ impl ReadonlyUserRepo {
     }
 }
 
+/// How many times a transaction is merged with operations published while it
+/// was being committed before giving up and leaving divergent operations.
+const MAX_CONCURRENT_OPERATION_ATTEMPTS: usize = 3;
+
 /// Provides utilities for writing a command that works on a [`Workspace`]
 /// (which most commands do).
 pub struct WorkspaceCommandHelper {
     cwd: PathBuf,
+    path_converter: RepoPathUiConverter,
     string_args: Vec<String>,
     global_args: GlobalArgs,
     settings: UserSettings,
@@ -401,7 +433,9 @@ pub struct WorkspaceCommandHelper {
     // TODO: Parsed template can be cached if it doesn't capture 'repo lifetime
     commit_summary_template_text: String,
     commit_template_extension: Option<Arc<dyn CommitTemplateLanguageExtension>>,
+    op_description_template: TemplateRenderer<'static, OpDescriptionContext>,
     revset_aliases_map: RevsetAliasesMap,
+    mailmap: Mailmap,
     template_aliases_map: TemplateAliasesMap,
     may_update_working_copy: bool,
     working_copy_shared_with_git: bool,
@@ -419,12 +453,25 @@ impl WorkspaceCommandHelper {
         let commit_summary_template_text =
             settings.config().get_string("templates.commit_summary")?;
         let revset_aliases_map = revset_util::load_revset_aliases(ui, &command.layered_configs)?;
+        let mailmap = command.load_mailmap(workspace.workspace_root())?.clone();
         let template_aliases_map = command.load_template_aliases(ui)?;
+        let op_description_template = template_builder::parse(
+            &op_description_template_language(),
+            &settings.config().get_string("templates.op_description")?,
+            &template_aliases_map,
+            GenericTemplateLanguage::wrap_self,
+        )?
+        .with_strict(command.global_args.template_strict);
         let loaded_at_head = command.global_args.at_operation == "@";
         let may_update_working_copy = loaded_at_head && !command.global_args.ignore_working_copy;
         let working_copy_shared_with_git = is_colocated_git_workspace(&workspace, &repo);
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: command.cwd.clone(),
+            base: workspace.workspace_root().clone(),
+        };
         let helper = Self {
             cwd: command.cwd.clone(),
+            path_converter,
             string_args: command.string_args.clone(),
             global_args: command.global_args.clone(),
             settings,
@@ -432,7 +479,9 @@ impl WorkspaceCommandHelper {
             user_repo: ReadonlyUserRepo::new(repo),
             commit_summary_template_text,
             commit_template_extension: command.commit_template_extension.clone(),
+            op_description_template,
             revset_aliases_map,
+            mailmap,
             template_aliases_map,
             may_update_working_copy,
             working_copy_shared_with_git,
@@ -463,23 +512,39 @@ impl WorkspaceCommandHelper {
         }
     }
 
-    /// Snapshot the working copy if allowed, and import Git refs if the working
-    /// copy is collocated with Git.
+    /// Snapshot the working copy if allowed and enabled by `snapshot.auto`,
+    /// and import Git refs if the working copy is collocated with Git.
     #[instrument(skip_all)]
     pub fn maybe_snapshot(&mut self, ui: &mut Ui) -> Result<(), CommandError> {
         if self.may_update_working_copy {
-            if self.working_copy_shared_with_git {
-                self.import_git_head(ui)?;
-            }
-            // Because the Git refs (except HEAD) aren't imported yet, the ref
-            // pointing to the new working-copy commit might not be exported.
-            // In that situation, the ref would be conflicted anyway, so export
-            // failure is okay.
+            let auto_snapshot = self.settings.auto_snapshot()?;
+            self.import_git_and_snapshot(ui, auto_snapshot)?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot the working copy even if `snapshot.auto` is disabled, and
+    /// import Git refs if the working copy is collocated with Git.
+    #[instrument(skip_all)]
+    pub fn snapshot(&mut self, ui: &mut Ui) -> Result<(), CommandError> {
+        self.check_working_copy_writable()?;
+        self.import_git_and_snapshot(ui, true)
+    }
+
+    fn import_git_and_snapshot(&mut self, ui: &mut Ui, snapshot: bool) -> Result<(), CommandError> {
+        if self.working_copy_shared_with_git {
+            self.import_git_head(ui)?;
+        }
+        // Because the Git refs (except HEAD) aren't imported yet, the ref
+        // pointing to the new working-copy commit might not be exported.
+        // In that situation, the ref would be conflicted anyway, so export
+        // failure is okay.
+        if snapshot {
             self.snapshot_working_copy(ui)?;
-            // import_git_refs() can rebase the working-copy commit.
-            if self.working_copy_shared_with_git {
-                self.import_git_refs(ui)?;
-            }
+        }
+        // import_git_refs() can rebase the working-copy commit.
+        if self.working_copy_shared_with_git {
+            self.import_git_refs(ui)?;
         }
         Ok(())
     }
@@ -526,7 +591,7 @@ impl WorkspaceCommandHelper {
             // state to it without updating working copy files.
             locked_ws.locked_wc().reset(&new_git_head_commit)?;
             tx.mut_repo().rebase_descendants(&self.settings)?;
-            self.user_repo = ReadonlyUserRepo::new(tx.commit("import git head"));
+            self.user_repo = ReadonlyUserRepo::new(tx.commit("import git head")?);
             locked_ws.finish(self.user_repo.repo.op_id().clone())?;
             if old_git_head.is_present() {
                 writeln!(
@@ -553,7 +618,7 @@ impl WorkspaceCommandHelper {
     /// the working copy parent if the repository is colocated.
     #[instrument(skip_all)]
     fn import_git_refs(&mut self, ui: &mut Ui) -> Result<(), CommandError> {
-        let git_settings = self.settings.git_settings();
+        let git_settings = self.settings.git_settings()?;
         let mut tx = self.start_transaction();
         // Automated import shouldn't fail because of reserved remote name.
         let stats = git::import_some_refs(tx.mut_repo(), &git_settings, |ref_name| {
@@ -581,6 +646,10 @@ impl WorkspaceCommandHelper {
         Ok(())
     }
 
+    pub fn settings(&self) -> &UserSettings {
+        &self.settings
+    }
+
     pub fn repo(&self) -> &Arc<ReadonlyRepo> {
         &self.user_repo.repo
     }
@@ -631,10 +700,12 @@ impl WorkspaceCommandHelper {
     }
 
     pub fn format_file_path(&self, file: &RepoPath) -> String {
-        file_util::relative_path(&self.cwd, &file.to_fs_path(self.workspace_root()))
-            .to_str()
-            .unwrap()
-            .to_owned()
+        self.path_converter.format_file_path(file)
+    }
+
+    /// Converter of repo paths to the paths presented to the user.
+    pub fn path_converter(&self) -> &RepoPathUiConverter {
+        &self.path_converter
     }
 
     /// Parses a path relative to cwd into a RepoPath, which is relative to the
@@ -647,12 +718,11 @@ impl WorkspaceCommandHelper {
         if values.is_empty() {
             Ok(Box::new(EverythingMatcher))
         } else {
-            // TODO: Add support for globs and other formats
-            let paths: Vec<_> = values
+            let expressions: Vec<_> = values
                 .iter()
-                .map(|v| self.parse_file_path(v))
+                .map(|v| fileset::parse_pattern(v, &self.path_converter))
                 .try_collect()?;
-            Ok(Box::new(PrefixMatcher::new(paths)))
+            Ok(FilesetExpression::union_all(expressions).to_matcher())
         }
     }
 
@@ -887,6 +957,7 @@ It resolved to these revisions:
         RevsetParseContext {
             aliases_map: &self.revset_aliases_map,
             user_email: self.settings.user_email(),
+            mailmap: &self.mailmap,
             workspace: Some(workspace_context),
         }
     }
@@ -927,12 +998,8 @@ It resolved to these revisions:
         wrap_self: impl Fn(PropertyPlaceholder<C>) -> L::Property,
     ) -> Result<TemplateRenderer<'a, C>, CommandError> {
         let aliases = &self.template_aliases_map;
-        Ok(template_builder::parse(
-            language,
-            template_text,
-            aliases,
-            wrap_self,
-        )?)
+        let template = template_builder::parse(language, template_text, aliases, wrap_self)?;
+        Ok(template.with_strict(self.global_args.template_strict))
     }
 
     /// Parses commit template into evaluation tree.
@@ -952,6 +1019,7 @@ It resolved to these revisions:
     pub fn commit_template_language(&self) -> Result<CommitTemplateLanguage<'_>, CommandError> {
         Ok(CommitTemplateLanguage::new(
             self.repo().as_ref(),
+            &self.path_converter,
             self.workspace_id(),
             self.revset_parse_context(),
             self.id_prefix_context()?,
@@ -961,8 +1029,11 @@ It resolved to these revisions:
 
     /// Template for one-line summary of a commit.
     pub fn commit_summary_template(&self) -> TemplateRenderer<'_, Commit> {
+        // Summary lines are embedded in other messages, which shouldn't be
+        // aborted by --template-strict.
         self.parse_commit_template(&self.commit_summary_template_text)
             .expect("parse error should be confined by WorkspaceCommandHelper::new()")
+            .with_strict(false)
     }
 
     /// Returns one-line summary of the given `commit`.
@@ -1023,6 +1094,25 @@ It resolved to these revisions:
 
     #[instrument(skip_all)]
     fn snapshot_working_copy(&mut self, ui: &mut Ui) -> Result<(), CommandError> {
+        self.snapshot_working_copy_with_description(ui, None)
+    }
+
+    /// Snapshots the working copy, and records the changes as an operation
+    /// with the given `description` instead of a snapshot operation.
+    pub fn snapshot_with_description(
+        &mut self,
+        ui: &mut Ui,
+        description: &str,
+    ) -> Result<(), CommandError> {
+        self.check_working_copy_writable()?;
+        self.snapshot_working_copy_with_description(ui, Some(description))
+    }
+
+    fn snapshot_working_copy_with_description(
+        &mut self,
+        ui: &mut Ui,
+        description: Option<&str>,
+    ) -> Result<(), CommandError> {
         let workspace_id = self.workspace_id().to_owned();
         let get_wc_commit = |repo: &ReadonlyRepo| -> Result<Option<_>, _> {
             repo.view()
@@ -1037,6 +1127,11 @@ It resolved to these revisions:
             return Ok(());
         };
         let base_ignores = self.base_ignores()?;
+        let fingerprint_cache = if self.settings.use_fingerprint_cache()? {
+            Some(FileFingerprintCache::load(self.workspace.repo_path()))
+        } else {
+            None
+        };
 
         // Compare working-copy tree and operation with repo's, and reload as needed.
         let mut locked_ws = self.workspace.start_working_copy_mutation()?;
@@ -1090,12 +1185,22 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
             fsmonitor_kind: self.settings.fsmonitor_kind()?,
             progress: progress.as_ref().map(|x| x as _),
             max_new_file_size: self.settings.max_new_file_size()?,
+            ignore_executable_bit: self.settings.ignore_executable_bit()?,
+            fingerprint_cache: fingerprint_cache.as_ref(),
         })?;
         drop(progress);
+        if let Some(cache) = &fingerprint_cache {
+            if let Err(err) = cache.save() {
+                writeln!(
+                    ui.warning_default(),
+                    "Failed to save fingerprint cache: {err}"
+                )?;
+            }
+        }
         if new_tree_id != *wc_commit.tree_id() {
             let mut tx =
                 start_repo_transaction(&self.user_repo.repo, &self.settings, &self.string_args);
-            tx.set_is_snapshot(true);
+            tx.set_is_snapshot(description.is_none());
             let mut_repo = tx.mut_repo();
             let commit = mut_repo
                 .rewrite_commit(&self.settings, &wc_commit)
@@ -1117,12 +1222,41 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
                 print_failed_git_export(ui, &failed_branches)?;
             }
 
-            self.user_repo = ReadonlyUserRepo::new(tx.commit("snapshot working copy"));
+            let description = description.unwrap_or("snapshot working copy");
+            self.user_repo = ReadonlyUserRepo::new(tx.commit(description)?);
         }
         locked_ws.finish(self.user_repo.repo.op_id().clone())?;
         Ok(())
     }
 
+    /// Fails if the working copy has changes which weren't recorded because
+    /// `snapshot.auto` is disabled, as checking out another tree would
+    /// overwrite them. The snapshot taken to find out isn't recorded.
+    fn check_no_unrecorded_changes(&mut self, ui: &mut Ui) -> Result<(), CommandError> {
+        let base_ignores = self.base_ignores()?;
+        let mut locked_ws = self.workspace.start_working_copy_mutation()?;
+        let old_tree_id = locked_ws.locked_wc().old_tree_id().clone();
+        let progress = crate::progress::snapshot_progress(ui);
+        let new_tree_id = locked_ws.locked_wc().snapshot(SnapshotOptions {
+            base_ignores,
+            fsmonitor_kind: self.settings.fsmonitor_kind()?,
+            progress: progress.as_ref().map(|x| x as _),
+            max_new_file_size: self.settings.max_new_file_size()?,
+            ignore_executable_bit: self.settings.ignore_executable_bit()?,
+            fingerprint_cache: None,
+        })?;
+        drop(progress);
+        // The lock is released without finishing, which discards the snapshot.
+        drop(locked_ws);
+        if new_tree_id != old_tree_id {
+            return Err(user_error_with_hint(
+                "The working copy has unrecorded changes, which would be overwritten.",
+                "`snapshot.auto` is disabled. Run `jj snapshot` to record the changes first.",
+            ));
+        }
+        Ok(())
+    }
+
     fn update_working_copy(
         &mut self,
         ui: &mut Ui,
@@ -1160,6 +1294,28 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
         WorkspaceCommandTransaction { helper: self, tx }
     }
 
+    /// Renders the `templates.op_description` template for an operation whose
+    /// built-in description is `description`. The built-in description is
+    /// used if the template renders to an empty string.
+    fn render_op_description(&self, description: String) -> Result<String, CommandError> {
+        let context = OpDescriptionContext {
+            description,
+            args: iter::once("jj".to_owned())
+                .chain(self.string_args.iter().skip(1).cloned())
+                .collect(),
+            workspace: self.workspace_id().as_str().to_owned(),
+        };
+        let mut output = Vec::new();
+        self.op_description_template
+            .format(&context, &mut PlainTextFormatter::new(&mut output))?;
+        let rendered = String::from_utf8_lossy(&output);
+        if rendered.trim().is_empty() {
+            Ok(context.description)
+        } else {
+            Ok(rendered.into_owned())
+        }
+    }
+
     fn finish_transaction(
         &mut self,
         ui: &mut Ui,
@@ -1174,13 +1330,37 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
         if num_rebased > 0 {
             writeln!(ui.stderr(), "Rebased {num_rebased} descendant commits")?;
         }
+        // Operations run with --at-op are expected to diverge from the head.
+        let mut merged_ops = vec![];
+        if self.global_args.at_operation == "@" && self.settings.merge_concurrent_operations()? {
+            merged_ops =
+                tx.merge_concurrent_operations(&self.settings, MAX_CONCURRENT_OPERATION_ATTEMPTS)?;
+            if !merged_ops.is_empty() {
+                writeln!(
+                    ui.stderr(),
+                    "Concurrent modification detected, merged {} operations into this one.",
+                    merged_ops.len()
+                )?;
+            }
+        }
 
         let old_repo = tx.base_repo().clone();
 
-        let maybe_old_wc_commit = old_repo
-            .view()
-            .get_wc_commit_id(self.workspace_id())
-            .map(|commit_id| tx.base_repo().store().get_commit(commit_id))
+        let base_wc_commit_id = old_repo.view().get_wc_commit_id(self.workspace_id());
+        let mut old_wc_commit_id = base_wc_commit_id.cloned();
+        if self.may_update_working_copy {
+            // If one of the merged operations checked out another commit, the
+            // files on disk are that commit's, not the base operation's. The
+            // last such operation is the one which updated them.
+            for op in &merged_ops {
+                let wc_commit_id = op.view()?.get_wc_commit_id(self.workspace_id()).cloned();
+                if wc_commit_id.as_ref() != base_wc_commit_id {
+                    old_wc_commit_id = wc_commit_id;
+                }
+            }
+        }
+        let maybe_old_wc_commit = old_wc_commit_id
+            .map(|commit_id| tx.base_repo().store().get_commit(&commit_id))
             .transpose()?;
         let maybe_new_wc_commit = tx
             .repo()
@@ -1188,6 +1368,16 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
             .get_wc_commit_id(self.workspace_id())
             .map(|commit_id| tx.repo().store().get_commit(commit_id))
             .transpose()?;
+        // Without automatic snapshots, the files on disk may differ from the
+        // recorded tree. Don't overwrite them by checking out another tree.
+        if self.may_update_working_copy && !self.settings.auto_snapshot()? {
+            if let Some(new_wc_commit) = &maybe_new_wc_commit {
+                let old_tree_id = maybe_old_wc_commit.as_ref().map(|commit| commit.tree_id());
+                if old_tree_id != Some(new_wc_commit.tree_id()) {
+                    self.check_no_unrecorded_changes(ui)?;
+                }
+            }
+        }
         if self.working_copy_shared_with_git {
             let git_repo = self.git_backend().unwrap().open_git_repo()?;
             if let Some(wc_commit) = &maybe_new_wc_commit {
@@ -1196,7 +1386,8 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
             let failed_branches = git::export_refs(tx.mut_repo())?;
             print_failed_git_export(ui, &failed_branches)?;
         }
-        self.user_repo = ReadonlyUserRepo::new(tx.commit(description));
+        let description = self.render_op_description(description.into())?;
+        self.user_repo = ReadonlyUserRepo::new(tx.commit(description)?);
         self.report_repo_changes(ui, &old_repo)?;
 
         if self.may_update_working_copy {
@@ -1327,7 +1518,9 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
                 .iter()
                 .commits(new_repo.store())
                 .try_collect()?;
-            if !root_conflict_commits.is_empty() {
+            if !root_conflict_commits.is_empty()
+                && !ui.messages().is_hint_disabled("resolve-conflicts")
+            {
                 fmt.push_label("hint")?;
                 if added_conflict_commits.len() == 1 {
                     writeln!(fmt, "To resolve the conflicts, start by updating to it:",)?;
@@ -1347,9 +1540,13 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
                 }
                 writeln!(
                     fmt,
-                    r#"Then use `jj resolve`, or edit the conflict markers in the file directly.
+                    "{}",
+                    ui.messages().get(
+                        "resolve-conflicts",
+                        r#"Then use `jj resolve`, or edit the conflict markers in the file directly.
 Once the conflicts are resolved, you may want inspect the result with `jj diff`.
 Then run `jj squash` to move the resolution into the conflicted commit."#,
+                    ),
                 )?;
                 fmt.pop_label()?;
             }
@@ -1400,6 +1597,23 @@ impl WorkspaceCommandTransaction<'_> {
         self.tx.mut_repo().edit(workspace_id, commit)
     }
 
+    /// Writes the commit built by `build_commit()` with the trailers configured
+    /// by `commit.trailers` appended to its description. The trailers are
+    /// rendered against the commit being written.
+    pub fn write_commit_with_trailers(
+        &mut self,
+        build_commit: impl for<'r> FnOnce(&'r mut MutableRepo) -> CommitBuilder<'r>,
+    ) -> Result<Commit, CommandError> {
+        let helper: &WorkspaceCommandHelper = self.helper;
+        let commit_builder = build_commit(self.tx.mut_repo());
+        let description = description_util::add_configured_trailers(
+            &helper.settings,
+            helper,
+            &commit_builder.unwritten_commit(),
+        )?;
+        Ok(commit_builder.set_description(description).write()?)
+    }
+
     pub fn format_commit_summary(&self, commit: &Commit) -> String {
         let mut output = Vec::new();
         self.write_commit_summary(&mut PlainTextFormatter::new(&mut output), commit)
@@ -1416,6 +1630,7 @@ impl WorkspaceCommandTransaction<'_> {
         let id_prefix_context = IdPrefixContext::default();
         let language = CommitTemplateLanguage::new(
             self.tx.repo(),
+            &self.helper.path_converter,
             self.helper.workspace_id(),
             self.helper.revset_parse_context(),
             &id_prefix_context,
@@ -1436,6 +1651,82 @@ impl WorkspaceCommandTransaction<'_> {
         self.helper.finish_transaction(ui, self.tx, description)
     }
 
+    /// Reports what finishing the transaction would do without committing it.
+    ///
+    /// Descendants of rewritten commits are rebased in memory, and the new
+    /// commits which would become conflicted or empty are listed. Neither the
+    /// operation log nor the working copy is updated.
+    pub fn finish_dry_run(mut self, ui: &mut Ui) -> Result<(), CommandError> {
+        if !self.tx.mut_repo().has_changes() {
+            writeln!(ui.stderr(), "Nothing changed.")?;
+            return Ok(());
+        }
+        self.tx
+            .mut_repo()
+            .rebase_descendants(&self.helper.settings)?;
+        let repo = self.tx.repo();
+        let base_heads = self
+            .base_repo()
+            .view()
+            .heads()
+            .iter()
+            .cloned()
+            .collect_vec();
+        let new_heads = repo.view().heads().iter().cloned().collect_vec();
+        let new_commits: Vec<Commit> = graph::range(repo, &base_heads, &new_heads)?
+            .commits(repo.store())
+            .try_collect()?;
+        let mut newly_conflicted = vec![];
+        let mut newly_empty = vec![];
+        for commit in &new_commits {
+            let predecessors = commit.predecessors();
+            // Commits created from scratch, such as a new working-copy commit,
+            // didn't become conflicted or empty.
+            if predecessors.is_empty() {
+                continue;
+            }
+            if commit.has_conflict()? {
+                let mut was_conflicted = false;
+                for predecessor in &predecessors {
+                    was_conflicted |= predecessor.has_conflict()?;
+                }
+                if !was_conflicted {
+                    newly_conflicted.push(commit);
+                }
+            }
+            // Merge commits are never considered empty, like with `--empty=drop`.
+            if commit.parent_ids().len() == 1 && commit.is_empty(repo)? {
+                let mut was_empty = false;
+                for predecessor in &predecessors {
+                    was_empty |= predecessor.is_empty(repo)?;
+                }
+                if !was_empty {
+                    newly_empty.push(commit);
+                }
+            }
+        }
+
+        let mut formatter = ui.stderr_formatter();
+        let formatter = formatter.as_mut();
+        writeln!(formatter, "Would create {} new commits", new_commits.len())?;
+        for (heading, commits) in [
+            ("These commits would become conflicted:", &newly_conflicted),
+            ("These commits would become empty:", &newly_empty),
+        ] {
+            if commits.is_empty() {
+                continue;
+            }
+            writeln!(formatter, "{heading}")?;
+            for commit in commits {
+                write!(formatter, "  ")?;
+                self.write_commit_summary(formatter, commit)?;
+                writeln!(formatter)?;
+            }
+        }
+        writeln!(formatter, "Dry run, nothing was changed.")?;
+        Ok(())
+    }
+
     /// Returns the wrapped [`Transaction`] for circumstances where
     /// finer-grained control is needed. The caller becomes responsible for
     /// finishing the `Transaction`, including rebasing descendants and updating
@@ -1445,6 +1736,32 @@ impl WorkspaceCommandTransaction<'_> {
     }
 }
 
+/// Values available to the `templates.op_description` template.
+#[derive(Clone, Debug)]
+struct OpDescriptionContext {
+    description: String,
+    args: Vec<String>,
+    workspace: String,
+}
+
+fn op_description_template_language() -> GenericTemplateLanguage<'static, OpDescriptionContext> {
+    type L = GenericTemplateLanguage<'static, OpDescriptionContext>;
+    let mut language = L::new();
+    language.add_keyword("description", |self_property| {
+        let out_property = self_property.map(|context| context.description);
+        Ok(L::wrap_string(out_property))
+    });
+    language.add_keyword("args", |self_property| {
+        let out_property = self_property.map(|context| context.args);
+        Ok(L::wrap_string_list(out_property))
+    });
+    language.add_keyword("workspace", |self_property| {
+        let out_property = self_property.map(|context| context.workspace);
+        Ok(L::wrap_string(out_property))
+    });
+    language
+}
+
 fn find_workspace_dir(cwd: &Path) -> &Path {
     cwd.ancestors()
         .find(|path| path.join(".jj").is_dir())
@@ -1486,6 +1803,7 @@ jj git init --colocate",
             err @ SignInitError::UnknownBackend(_),
         )) => user_error(err),
         WorkspaceLoadError::StoreLoadError(err) => internal_error(err),
+        WorkspaceLoadError::WorkingCopyState(err) => internal_error(err),
         WorkspaceLoadError::NonUnicodePath | WorkspaceLoadError::Path(_) => user_error(err),
     }
 }
@@ -1496,23 +1814,33 @@ pub fn start_repo_transaction(
     string_args: &[String],
 ) -> Transaction {
     let mut tx = repo.start_transaction(settings);
+    tx.set_tag("args".to_string(), format_args_tag(string_args));
+    tx
+}
+
+/// Formats the command line to be recorded in the "args" tag. The first
+/// argument is replaced with `jj`.
+fn format_args_tag(string_args: &[String]) -> String {
     // TODO: Either do better shell-escaping here or store the values in some list
     // type (which we currently don't have).
     let shell_escape = |arg: &String| {
-        if arg.as_bytes().iter().all(|b| {
-            matches!(b,
-                b'A'..=b'Z'
-                | b'a'..=b'z'
-                | b'0'..=b'9'
-                | b','
-                | b'-'
-                | b'.'
-                | b'/'
-                | b':'
-                | b'@'
-                | b'_'
-            )
-        }) {
+        // Empty arguments are quoted so they survive being split again.
+        if !arg.is_empty()
+            && arg.as_bytes().iter().all(|b| {
+                matches!(b,
+                    b'A'..=b'Z'
+                    | b'a'..=b'z'
+                    | b'0'..=b'9'
+                    | b','
+                    | b'-'
+                    | b'.'
+                    | b'/'
+                    | b':'
+                    | b'@'
+                    | b'_'
+                )
+            })
+        {
             arg.clone()
         } else {
             format!("'{}'", arg.replace('\'', "\\'"))
@@ -1520,8 +1848,35 @@ pub fn start_repo_transaction(
     };
     let mut quoted_strings = vec!["jj".to_string()];
     quoted_strings.extend(string_args.iter().skip(1).map(shell_escape));
-    tx.set_tag("args".to_string(), quoted_strings.join(" "));
-    tx
+    quoted_strings.join(" ")
+}
+
+/// Splits the command line recorded in the "args" tag into arguments.
+///
+/// Arguments are separated by spaces, and quoted by `'` if they contain other
+/// characters than the safe ones. Quotes inside quoted arguments are escaped by
+/// `\`.
+pub fn split_args_tag(args: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = args.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_quotes => match chars.next() {
+                Some('\'') => current.push('\''),
+                Some(c) => current.extend(['\\', c]),
+                None => current.push('\\'),
+            },
+            '\'' => in_quotes = !in_quotes,
+            ' ' if !in_quotes => result.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    if !args.is_empty() {
+        result.push(current);
+    }
+    result
 }
 
 /// Whether the working copy is stale or not.
@@ -1601,13 +1956,18 @@ pub fn print_checkout_stats(
              working copy.",
             stats.skipped_files
         )?;
-        writeln!(
-            ui.hint_default(),
-            "Inspect the changes compared to the intended target with `jj diff --from {}`.
-Discard the conflicting changes with `jj restore --from {}`.",
-            short_commit_hash(new_commit.id()),
-            short_commit_hash(new_commit.id())
-        )?;
+        let hint_id = "skipped-conflicting-updates";
+        if !ui.messages().is_hint_disabled(hint_id) {
+            let commit_hash = short_commit_hash(new_commit.id());
+            let message = ui.messages().format(
+                hint_id,
+                "Inspect the changes compared to the intended target with `jj diff --from \
+                 {commit}`.
+Discard the conflicting changes with `jj restore --from {commit}`.",
+                &[("commit", commit_hash.as_str())],
+            );
+            writeln!(ui.hint_default(), "{message}")?;
+        }
     }
     Ok(())
 }
@@ -1638,11 +1998,15 @@ pub fn print_trackable_remote_branches(ui: &Ui, view: &View) -> io::Result<()> {
         writeln!(formatter.labeled("branch"), "{full_name}")?;
     }
     drop(formatter);
-    writeln!(
-        ui.hint_default(),
-        "Run `jj branch track {names}` to keep local branches updated on future pulls.",
-        names = remote_branch_names.join(" "),
-    )?;
+    if !ui.messages().is_hint_disabled("track-remote-branches") {
+        let names = remote_branch_names.join(" ");
+        let message = ui.messages().format(
+            "track-remote-branches",
+            "Run `jj branch track {names}` to keep local branches updated on future pulls.",
+            &[("names", names.as_str())],
+        );
+        writeln!(ui.hint_default(), "{message}")?;
+    }
     Ok(())
 }
 
@@ -2025,6 +2389,21 @@ impl fmt::Display for RemoteBranchName {
     }
 }
 
+impl FromStr for RemoteBranchName {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        // TODO: maybe reuse revset parser to handle branch/remote name containing @
+        let (branch, remote) = src
+            .rsplit_once('@')
+            .ok_or_else(|| "remote branch must be specified in branch@remote form".to_owned())?;
+        Ok(RemoteBranchName {
+            branch: branch.to_owned(),
+            remote: remote.to_owned(),
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct RemoteBranchNamePattern {
     pub branch: StringPattern,
@@ -2127,6 +2506,22 @@ pub struct GlobalArgs {
     /// do that, but it is possible.
     #[arg(long, visible_alias = "at-op", global = true, default_value = "@")]
     pub at_operation: String,
+    /// Give up waiting for locks held by other processes after this many
+    /// seconds
+    ///
+    /// Other `jj` processes may hold the working-copy or operation locks. By
+    /// default, Jujutsu waits for them to be released. With this option, it
+    /// fails instead once the timeout has passed, and reports which process
+    /// holds the lock.
+    #[arg(long, value_name = "SECONDS", global = true)]
+    pub wait_for_lock: Option<u64>,
+    /// Abort if a template property fails to evaluate
+    ///
+    /// By default, a property that fails to evaluate (for example, because a
+    /// commit can't be read) is rendered as an inline error placeholder, and
+    /// the rest of the output is printed as usual.
+    #[arg(long, global = true)]
+    pub template_strict: bool,
     /// Enable debug logging
     #[arg(long, global = true)]
     pub debug: bool,
@@ -2312,6 +2707,11 @@ fn handle_early_args(
     if args.no_pager.unwrap_or_default() {
         args.config_toml.push(r#"ui.paginate="never""#.to_owned());
     }
+    // Stores read the lock timeout from the settings when the repo is loaded.
+    if let Some(seconds) = early_matches.get_one::<u64>("wait_for_lock") {
+        args.config_toml
+            .push(format!("core.lock-timeout={seconds}"));
+    }
     if !args.config_toml.is_empty() {
         layered_configs.parse_config_args(&args.config_toml)?;
         ui.reset(&layered_configs.merge())?;
@@ -2575,6 +2975,7 @@ impl CliRunner {
             maybe_workspace_loader,
             store_factories: self.store_factories.unwrap_or_default(),
             working_copy_factories,
+            mailmap: OnceCell::new(),
         };
         for start_hook_fn in self.start_hook_fns {
             start_hook_fn(ui, &command_helper)?;
@@ -2636,3 +3037,41 @@ fn format_template_aliases_hint(template_aliases: &TemplateAliasesMap) -> String
     );
     hint
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_args_tag() {
+        assert_eq!(split_args_tag(""), Vec::<String>::new());
+        assert_eq!(split_args_tag("jj log"), ["jj", "log"]);
+        assert_eq!(
+            split_args_tag("jj log -r 'foo | bar'"),
+            ["jj", "log", "-r", "foo | bar"]
+        );
+        assert_eq!(
+            split_args_tag(r"jj describe -m 'it\'s'"),
+            ["jj", "describe", "-m", "it's"]
+        );
+        assert_eq!(split_args_tag(r"jj '\n' ''"), ["jj", r"\n", ""]);
+    }
+
+    #[test]
+    fn test_args_tag_round_trip() {
+        let args = [
+            "/usr/bin/jj",
+            "describe",
+            "-m",
+            "it's a 'quoted' \\ text",
+            "",
+            "-r@-",
+        ]
+        .map(|arg| arg.to_owned());
+        let tag = format_args_tag(&args);
+        assert_eq!(tag, r"jj describe -m 'it\'s a \'quoted\' \ text' '' -r@-");
+        let mut split_args = split_args_tag(&tag);
+        split_args[0] = args[0].clone();
+        assert_eq!(split_args, args);
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::io::Write as _;
 use std::process::ExitCode;
 use std::sync::Arc;
@@ -19,9 +20,11 @@ use std::{error, io, iter, str};
 
 use itertools::Itertools as _;
 use jj_lib::backend::BackendError;
+use jj_lib::fileset::FilesetParseError;
 use jj_lib::git::{GitConfigParseError, GitExportError, GitImportError, GitRemoteManagementError};
 use jj_lib::gitignore::GitIgnoreError;
-use jj_lib::op_heads_store::OpHeadResolutionError;
+use jj_lib::lock::FileLockError;
+use jj_lib::op_heads_store::{OpHeadResolutionError, OpHeadsStoreError};
 use jj_lib::op_store::OpStoreError;
 use jj_lib::op_walk::OpsetEvaluationError;
 use jj_lib::repo::{CheckOutCommitError, EditCommitError, RepoLoaderError, RewriteRootCommit};
@@ -30,6 +33,7 @@ use jj_lib::revset::{
     RevsetEvaluationError, RevsetParseError, RevsetParseErrorKind, RevsetResolutionError,
 };
 use jj_lib::signing::SignInitError;
+use jj_lib::transaction::TransactionCommitError;
 use jj_lib::tree::TreeMergeError;
 use jj_lib::working_copy::{ResetError, SnapshotError, WorkingCopyStateError};
 use jj_lib::workspace::WorkspaceInitError;
@@ -38,6 +42,7 @@ use thiserror::Error;
 use crate::merge_tools::{
     ConflictResolveError, DiffEditError, DiffGenerateError, MergeToolConfigError,
 };
+use crate::messages::MessageCatalog;
 use crate::revset_util::{self, UserRevsetEvaluationError};
 use crate::template_parser::{TemplateParseError, TemplateParseErrorKind};
 use crate::ui::Ui;
@@ -57,6 +62,9 @@ pub struct CommandError {
     pub kind: CommandErrorKind,
     pub error: Arc<dyn error::Error + Send + Sync>,
     pub hints: Vec<String>,
+    /// Ids to look up hints in the message catalog, keyed by the index of the
+    /// hint in `hints`. Hints without id can't be customized or disabled.
+    pub hint_ids: HashMap<usize, &'static str>,
 }
 
 impl CommandError {
@@ -68,6 +76,7 @@ impl CommandError {
             kind,
             error: Arc::from(err.into()),
             hints: vec![],
+            hint_ids: HashMap::new(),
         }
     }
 
@@ -77,6 +86,14 @@ impl CommandError {
         self
     }
 
+    /// Returns error with the given `hint` attached. The hint can be
+    /// customized or disabled by `id`.
+    pub fn hinted_with_id(mut self, id: &'static str, hint: impl Into<String>) -> Self {
+        self.hint_ids.insert(self.hints.len(), id);
+        self.add_hint(hint);
+        self
+    }
+
     /// Appends `hint` to the error.
     pub fn add_hint(&mut self, hint: impl Into<String>) {
         self.hints.push(hint.into());
@@ -86,6 +103,20 @@ impl CommandError {
     pub fn extend_hints(&mut self, hints: impl IntoIterator<Item = String>) {
         self.hints.extend(hints);
     }
+
+    /// Returns the hints to print, with the ones which have an id replaced or
+    /// removed as configured by `messages`.
+    fn configured_hints(&self, messages: &MessageCatalog) -> Vec<String> {
+        self.hints
+            .iter()
+            .enumerate()
+            .filter_map(|(i, hint)| match self.hint_ids.get(&i) {
+                Some(id) if messages.is_hint_disabled(id) => None,
+                Some(id) => Some(messages.get(id, hint).to_owned()),
+                None => Some(hint.clone()),
+            })
+            .collect()
+    }
 }
 
 /// Wraps error with user-visible message.
@@ -244,6 +275,26 @@ impl From<OpHeadResolutionError> for CommandError {
             OpHeadResolutionError::NoHeads => {
                 internal_error_with_message("Corrupt repository", err)
             }
+            OpHeadResolutionError::OpHeadsStore(err) => err.into(),
+        }
+    }
+}
+
+impl From<OpHeadsStoreError> for CommandError {
+    fn from(err: OpHeadsStoreError) -> Self {
+        if is_lock_timeout(&err) {
+            user_error(err)
+        } else {
+            internal_error(err)
+        }
+    }
+}
+
+impl From<TransactionCommitError> for CommandError {
+    fn from(err: TransactionCommitError) -> Self {
+        match err {
+            TransactionCommitError::Backend(err) => err.into(),
+            TransactionCommitError::OpHeadsStore(err) => err.into(),
         }
     }
 }
@@ -262,7 +313,8 @@ impl From<SnapshotError> for CommandError {
     fn from(err: SnapshotError) -> Self {
         match err {
             SnapshotError::NewFileTooLarge { .. } => {
-                user_error_with_message("Failed to snapshot the working copy", err).hinted(
+                user_error_with_message("Failed to snapshot the working copy", err).hinted_with_id(
+                    "snapshot-file-too-large",
                     r#"Increase the value of the `snapshot.max-new-file-size` config option if you
 want this file to be snapshotted. Otherwise add it to your `.gitignore` file."#,
                 )
@@ -459,6 +511,15 @@ impl From<FsPathParseError> for CommandError {
     }
 }
 
+impl From<FilesetParseError> for CommandError {
+    fn from(err: FilesetParseError) -> Self {
+        match err {
+            FilesetParseError::InvalidPath(err) => err.into(),
+            err => user_error_with_message("Failed to parse fileset", err),
+        }
+    }
+}
+
 impl From<clap::Error> for CommandError {
     fn from(err: clap::Error) -> Self {
         cli_error(err)
@@ -473,10 +534,25 @@ impl From<GitConfigParseError> for CommandError {
 
 impl From<WorkingCopyStateError> for CommandError {
     fn from(err: WorkingCopyStateError) -> Self {
-        internal_error_with_message("Failed to access working copy state", err)
+        if is_lock_timeout(&err) {
+            user_error_with_message("Failed to access working copy state", err)
+        } else {
+            internal_error_with_message("Failed to access working copy state", err)
+        }
     }
 }
 
+/// Returns true if `err` was caused by giving up on a lock held by another
+/// process, which the user can resolve by waiting or retrying.
+fn is_lock_timeout(err: &(dyn error::Error + 'static)) -> bool {
+    iter::successors(Some(err), |err| err.source()).any(|err| {
+        matches!(
+            err.downcast_ref::<FileLockError>(),
+            Some(FileLockError::Timeout { .. } | FileLockError::Stale { .. })
+        )
+    })
+}
+
 impl From<GitIgnoreError> for CommandError {
     fn from(err: GitIgnoreError) -> Self {
         user_error_with_message("Failed to process .gitignore.", err)
@@ -497,7 +573,7 @@ fn try_handle_command_result(
         return Ok(ExitCode::SUCCESS);
     };
     let err = &cmd_err.error;
-    let hints = &cmd_err.hints;
+    let hints = &cmd_err.configured_hints(ui.messages());
     match cmd_err.kind {
         CommandErrorKind::User => {
             print_error(ui, "Error: ", err, hints)?;
@@ -505,10 +581,16 @@ fn try_handle_command_result(
         }
         CommandErrorKind::Config => {
             print_error(ui, "Config error: ", err, hints)?;
-            writeln!(
-                ui.hint_no_heading(),
-                "For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md."
-            )?;
+            if !ui.messages().is_hint_disabled("config-help") {
+                writeln!(
+                    ui.hint_no_heading(),
+                    "{}",
+                    ui.messages().get(
+                        "config-help",
+                        "For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md."
+                    )
+                )?;
+            }
             Ok(ExitCode::from(1))
         }
         CommandErrorKind::Cli => {
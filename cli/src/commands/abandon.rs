@@ -40,6 +40,12 @@ pub(crate) struct AbandonArgs {
     /// Do not print every abandoned commit on a separate line
     #[arg(long, short)]
     summary: bool,
+    /// Only abandon the revisions that are empty
+    ///
+    /// Merge commits are considered empty if they don't change the merged
+    /// tree of their parents.
+    #[arg(long)]
+    empty: bool,
     /// Ignored (but lets you pass `-r` for consistency with other commands)
     #[arg(short = 'r', hide = true)]
     unused_revision: bool,
@@ -56,7 +62,20 @@ pub(crate) fn cmd_abandon(
         let repo = workspace_command.repo();
         let expression = workspace_command.parse_union_revsets(&args.revisions)?;
         let revset = workspace_command.evaluate_revset(expression)?;
-        revset.iter().commits(repo.store()).try_collect()?
+        let mut commits: Vec<_> = revset.iter().commits(repo.store()).try_collect()?;
+        if args.empty {
+            let root_commit_id = repo.store().root_commit_id();
+            commits = commits
+                .into_iter()
+                .filter(|commit| commit.id() != root_commit_id)
+                .filter_map(|commit| match commit.is_empty(repo.as_ref()) {
+                    Ok(true) => Some(Ok(commit)),
+                    Ok(false) => None,
+                    Err(err) => Some(Err(err)),
+                })
+                .try_collect()?;
+        }
+        commits
     };
     if to_abandon.is_empty() {
         writeln!(ui.stderr(), "No revisions to abandon.")?;
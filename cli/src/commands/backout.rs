@@ -12,25 +12,40 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write;
+
+use itertools::Itertools as _;
 use jj_lib::object_id::ObjectId;
-use jj_lib::rewrite::back_out_commit;
+use jj_lib::repo::Repo;
+use jj_lib::revset::RevsetIteratorExt as _;
+use jj_lib::rewrite::{back_out_commits, back_out_commits_as_stack};
 use tracing::instrument;
 
 use crate::cli_util::{CommandHelper, RevisionArg};
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
-/// Apply the reverse of a revision on top of another revision
+/// Apply the reverse of revisions on top of another revision
+///
+/// If multiple revisions are given, their combined changes are reversed in a
+/// single commit. With `--stack`, one commit is created for each revision
+/// instead, mirroring the original stack in reverse order.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct BackoutArgs {
-    /// The revision to apply the reverse of
+    /// The revision(s) to apply the reverse of
     #[arg(long, short, default_value = "@")]
-    revision: RevisionArg,
+    revision: Vec<RevisionArg>,
     /// The revision to apply the reverse changes on top of
     // TODO: It seems better to default this to `@-`. Maybe the working
     // copy should be rebased on top?
     #[arg(long, short, default_value = "@")]
     destination: Vec<RevisionArg>,
+    /// Create a separate backout commit for each revision
+    ///
+    /// The newest revision is backed out first, so the new commits form a
+    /// stack in the reverse order of the original ones.
+    #[arg(long)]
+    stack: bool,
 }
 
 #[instrument(skip_all)]
@@ -40,23 +55,39 @@ pub(crate) fn cmd_backout(
     args: &BackoutArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
-    let commit_to_back_out = workspace_command.resolve_single_rev(&args.revision)?;
+    // Children come before their parents in the revset order, which is the
+    // order the changes have to be reversed in.
+    let to_back_out: Vec<_> = {
+        let repo = workspace_command.repo();
+        let expression = workspace_command.parse_union_revsets(&args.revision)?;
+        let revset = workspace_command.evaluate_revset(expression)?;
+        revset.iter().commits(repo.store()).try_collect()?
+    };
+    if to_back_out.is_empty() {
+        writeln!(ui.stderr(), "No revisions to back out.")?;
+        return Ok(());
+    }
     let mut parents = vec![];
     for revision_str in &args.destination {
         let destination = workspace_command.resolve_single_rev(revision_str)?;
         parents.push(destination);
     }
     let mut tx = workspace_command.start_transaction();
-    back_out_commit(
-        command.settings(),
-        tx.mut_repo(),
-        &commit_to_back_out,
-        &parents,
-    )?;
-    tx.finish(
-        ui,
-        format!("back out commit {}", commit_to_back_out.id().hex()),
-    )?;
+    if args.stack {
+        back_out_commits_as_stack(command.settings(), tx.mut_repo(), &to_back_out, &parents)?;
+    } else {
+        back_out_commits(command.settings(), tx.mut_repo(), &to_back_out, &parents)?;
+    }
+    let transaction_description = if to_back_out.len() == 1 {
+        format!("back out commit {}", to_back_out[0].id().hex())
+    } else {
+        format!(
+            "back out commit {} and {} more",
+            to_back_out[0].id().hex(),
+            to_back_out.len() - 1
+        )
+    };
+    tx.finish(ui, transaction_description)?;
 
     Ok(())
 }
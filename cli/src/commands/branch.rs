@@ -12,25 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::Reverse;
 use std::collections::HashSet;
 use std::fmt;
 use std::io::Write as _;
+use std::str::FromStr;
 
 use clap::builder::NonEmptyStringValueParser;
 use itertools::Itertools;
 use jj_lib::backend::CommitId;
-use jj_lib::git;
 use jj_lib::object_id::ObjectId;
-use jj_lib::op_store::{RefTarget, RemoteRef};
+use jj_lib::op_store::{BranchTarget, RefTarget, RemoteRef, RemoteRefState};
 use jj_lib::repo::Repo;
 use jj_lib::revset::{self, RevsetExpression};
 use jj_lib::str_util::StringPattern;
 use jj_lib::view::View;
+use jj_lib::{git, graph};
 
 use crate::cli_util::{
-    parse_string_pattern, CommandHelper, RemoteBranchName, RemoteBranchNamePattern, RevisionArg,
+    parse_string_pattern, short_change_hash, CommandHelper, RemoteBranchName,
+    RemoteBranchNamePattern, RevisionArg,
 };
-use crate::command_error::{user_error, user_error_with_hint, CommandError};
+use crate::command_error::{
+    config_error_with_message, user_error, user_error_with_hint, CommandError,
+};
+use crate::commit_templater::{CommitTemplateLanguage, RefName};
 use crate::formatter::Formatter;
 use crate::ui::Ui;
 
@@ -50,6 +56,7 @@ pub enum BranchCommand {
     List(BranchListArgs),
     #[command(visible_alias("r"))]
     Rename(BranchRenameArgs),
+    Retarget(BranchRetargetArgs),
     #[command(visible_alias("s"))]
     Set(BranchSetArgs),
     #[command(visible_alias("t"))]
@@ -64,6 +71,14 @@ pub struct BranchCreateArgs {
     #[arg(long, short)]
     revision: Option<RevisionArg>,
 
+    /// Make the branches follow the change of the target revision
+    ///
+    /// When the change is rewritten, the branches are moved to its new commit,
+    /// even if the rewrite would otherwise move them elsewhere (e.g. to the
+    /// second part of `jj split`).
+    #[arg(long)]
+    at_change: bool,
+
     /// The branches to create.
     #[arg(required = true, value_parser=NonEmptyStringValueParser::new())]
     names: Vec<String>,
@@ -126,6 +141,68 @@ pub struct BranchListArgs {
     /// wouldn't have a local target.
     #[arg(long, short)]
     revisions: Vec<RevisionArg>,
+
+    /// Sort branches by the given key
+    ///
+    /// The key is one of `name`, `committer-date` (of the branch target), or
+    /// `ahead` (the number of local commits not on the tracked remote
+    /// branches), optionally followed by `:desc` to reverse the order.
+    /// Branches with equal keys are listed by name. The default can be set by
+    /// the `ui.branch-list-sort` config.
+    #[arg(long, value_name = "KEY[:desc]", value_parser = BranchSortKey::from_str)]
+    sort: Option<BranchSortKey>,
+
+    /// Render each branch using the given template
+    ///
+    /// The template is evaluated for every listed local and remote branch,
+    /// e.g. `name ++ " ahead " ++ self.ahead_by("origin") ++ "\n"`. No hints
+    /// are printed in this mode.
+    ///
+    /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    #[arg(long, short = 'T')]
+    template: Option<String>,
+}
+
+/// How `jj branch list` orders branches.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct BranchSortKey {
+    field: BranchSortField,
+    descending: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum BranchSortField {
+    Name,
+    CommitterDate,
+    Ahead,
+}
+
+impl FromStr for BranchSortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, order) = s.split_once(':').unwrap_or((s, "asc"));
+        let field = match field {
+            "name" => BranchSortField::Name,
+            "committer-date" => BranchSortField::CommitterDate,
+            "ahead" => BranchSortField::Ahead,
+            _ => {
+                return Err(format!(
+                    "invalid sort key {field:?} (expected name, committer-date, or ahead)"
+                ))
+            }
+        };
+        let descending = match order {
+            "asc" => false,
+            "desc" => true,
+            _ => {
+                return Err(format!(
+                    "invalid sort order {order:?} (expected asc or desc)"
+                ))
+            }
+        };
+        Ok(BranchSortKey { field, descending })
+    }
 }
 
 /// Forget everything about a branch, including its local and remote
@@ -161,6 +238,26 @@ pub struct BranchRenameArgs {
     pub new: String,
 }
 
+/// Make a local branch track a different remote branch
+///
+/// This is useful after a branch was renamed on the remote, e.g. from `master`
+/// to `main`. The local branch keeps its target. Since a local branch can only
+/// track remote branches of the same name, it is renamed to the name of the
+/// remote branch if they differ. The remote branches it tracked before are
+/// untracked, but not forgotten.
+///
+/// Unlike `jj branch track`, the remote target isn't merged into the local
+/// branch. If they have diverged, the divergence is reported instead.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BranchRetargetArgs {
+    /// The local branch to retarget
+    pub name: String,
+
+    /// The remote branch to track instead
+    #[arg(long, value_name = "BRANCH@REMOTE")]
+    pub to_remote_branch: RemoteBranchName,
+}
+
 /// Update an existing branch to point to a certain commit.
 #[derive(clap::Args, Clone, Debug)]
 pub struct BranchSetArgs {
@@ -172,6 +269,13 @@ pub struct BranchSetArgs {
     #[arg(long, short = 'B')]
     pub allow_backwards: bool,
 
+    /// Make the branches follow the change of the target revision
+    ///
+    /// Without this flag, any existing pins are removed, and the branches
+    /// follow the target commit as it's rewritten.
+    #[arg(long)]
+    pub at_change: bool,
+
     /// The branches to update.
     #[arg(required = true)]
     pub names: Vec<String>,
@@ -191,8 +295,12 @@ pub struct BranchTrackArgs {
     /// https://github.com/martinvonz/jj/blob/main/docs/revsets.md#string-patterns.
     ///
     /// Examples: branch@remote, glob:main@*, glob:jjfan-*@upstream
-    #[arg(required = true, value_name = "BRANCH@REMOTE")]
+    #[arg(required_unless_present = "all_remotes", value_name = "BRANCH@REMOTE")]
     pub names: Vec<RemoteBranchNamePattern>,
+
+    /// Track all remote branches which aren't tracked yet
+    #[arg(long, visible_alias = "all", conflicts_with = "names")]
+    pub all_remotes: bool,
 }
 
 /// Stop tracking given remote branches
@@ -227,6 +335,7 @@ pub fn cmd_branch(
     match subcommand {
         BranchCommand::Create(sub_args) => cmd_branch_create(ui, command, sub_args),
         BranchCommand::Rename(sub_args) => cmd_branch_rename(ui, command, sub_args),
+        BranchCommand::Retarget(sub_args) => cmd_branch_retarget(ui, command, sub_args),
         BranchCommand::Set(sub_args) => cmd_branch_set(ui, command, sub_args),
         BranchCommand::Delete(sub_args) => cmd_branch_delete(ui, command, sub_args),
         BranchCommand::Forget(sub_args) => cmd_branch_forget(ui, command, sub_args),
@@ -268,6 +377,10 @@ fn cmd_branch_create(
     for branch_name in branch_names {
         tx.mut_repo()
             .set_local_branch_target(branch_name, RefTarget::normal(target_commit.id().clone()));
+        if args.at_change {
+            tx.mut_repo()
+                .set_branch_pin(branch_name, Some(target_commit.change_id().clone()));
+        }
     }
     tx.finish(
         ui,
@@ -298,9 +411,11 @@ fn cmd_branch_rename(
         return Err(user_error(format!("Branch already exists: {new_branch}")));
     }
 
+    let pin = view.get_branch_pin(old_branch).cloned();
     let mut tx = workspace_command.start_transaction();
     tx.mut_repo()
         .set_local_branch_target(new_branch, ref_target);
+    tx.mut_repo().set_branch_pin(new_branch, pin);
     tx.mut_repo()
         .set_local_branch_target(old_branch, RefTarget::absent());
     tx.finish(
@@ -335,6 +450,98 @@ fn cmd_branch_rename(
     Ok(())
 }
 
+fn cmd_branch_retarget(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BranchRetargetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let view = workspace_command.repo().view();
+    let old_branch = &args.name;
+    let ref_target = view.get_local_branch(old_branch).clone();
+    if ref_target.is_absent() {
+        return Err(user_error(format!("No such branch: {old_branch}")));
+    }
+
+    let new_name = &args.to_remote_branch;
+    let new_branch = &new_name.branch;
+    if new_name.remote == git::REMOTE_NAME_FOR_LOCAL_GIT_REPO {
+        return Err(user_error(format!(
+            "Git-tracking branch cannot be retargeted to: {new_name}"
+        )));
+    }
+    let new_remote_ref = view.get_remote_branch(new_branch, &new_name.remote).clone();
+    if new_remote_ref.is_absent() {
+        return Err(user_error(format!("No such remote branch: {new_name}")));
+    }
+    if new_branch != old_branch && view.get_local_branch(new_branch).is_present() {
+        return Err(user_error(format!("Branch already exists: {new_branch}")));
+    }
+    if new_branch == old_branch && new_remote_ref.is_tracking() {
+        writeln!(
+            ui.warning_no_heading(),
+            "Remote branch already tracked: {new_name}"
+        )?;
+        return Ok(());
+    }
+
+    // The @git branch follows the local branch, so it isn't untracked.
+    let old_remotes = view
+        .remote_branches_matching(
+            &StringPattern::exact(old_branch),
+            &StringPattern::everything(),
+        )
+        .filter(|((_, remote), remote_ref)| {
+            *remote != git::REMOTE_NAME_FOR_LOCAL_GIT_REPO && remote_ref.is_tracking()
+        })
+        .map(|((_, remote), _)| remote.to_owned())
+        .collect_vec();
+
+    let pin = view.get_branch_pin(old_branch).cloned();
+    let mut tx = workspace_command.start_transaction();
+    for remote in &old_remotes {
+        tx.mut_repo().untrack_remote_branch(old_branch, remote);
+    }
+    if new_branch != old_branch {
+        tx.mut_repo()
+            .set_local_branch_target(new_branch, ref_target.clone());
+        tx.mut_repo().set_branch_pin(new_branch, pin);
+        tx.mut_repo()
+            .set_local_branch_target(old_branch, RefTarget::absent());
+    }
+    // Don't merge the remote target as track_remote_branch() would do. The
+    // local branch would otherwise become conflicted if they had diverged.
+    tx.mut_repo().set_remote_branch(
+        new_branch,
+        &new_name.remote,
+        RemoteRef {
+            target: new_remote_ref.target.clone(),
+            state: RemoteRefState::Tracking,
+        },
+    );
+    tx.finish(
+        ui,
+        format!(
+            "retarget {} to track remote {}",
+            make_branch_term(&[old_branch]),
+            make_branch_term(&[new_name]),
+        ),
+    )?;
+
+    if new_branch != old_branch {
+        writeln!(ui.stderr(), "Renamed branch {old_branch} to {new_branch}.")?;
+    }
+    let repo = workspace_command.repo();
+    match describe_ahead_behind(repo.as_ref(), &ref_target, &new_remote_ref.target)? {
+        Some(message) => writeln!(
+            ui.stderr(),
+            "Started tracking {new_name}, which is {message}."
+        )?,
+        None => writeln!(ui.stderr(), "Started tracking {new_name}.")?,
+    }
+    Ok(())
+}
+
 fn cmd_branch_set(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -380,6 +587,8 @@ fn cmd_branch_set(
     for branch_name in branch_names {
         tx.mut_repo()
             .set_local_branch_target(branch_name, RefTarget::normal(target_commit.id().clone()));
+        let pin = args.at_change.then(|| target_commit.change_id().clone());
+        tx.mut_repo().set_branch_pin(branch_name, pin);
     }
     tx.finish(
         ui,
@@ -500,7 +709,12 @@ fn cmd_branch_delete(
     }
     tx.finish(ui, format!("delete {}", make_branch_term(&names)))?;
     if names.len() > 1 {
-        writeln!(ui.stderr(), "Deleted {} branches.", names.len())?;
+        writeln!(
+            ui.stderr(),
+            "Deleted {} branches: {}.",
+            names.len(),
+            names.iter().join(", ")
+        )?;
     }
     Ok(())
 }
@@ -526,7 +740,12 @@ fn cmd_branch_forget(
     }
     tx.finish(ui, format!("forget {}", make_branch_term(&names)))?;
     if names.len() > 1 {
-        writeln!(ui.stderr(), "Forgot {} branches.", names.len())?;
+        writeln!(
+            ui.stderr(),
+            "Forgot {} branches: {}.",
+            names.len(),
+            names.iter().join(", ")
+        )?;
     }
     Ok(())
 }
@@ -539,6 +758,16 @@ fn cmd_branch_track(
     let mut workspace_command = command.workspace_helper(ui)?;
     let view = workspace_command.repo().view();
     let mut names = Vec::new();
+    if args.all_remotes {
+        names.extend(
+            view.all_remote_branches()
+                .filter(|(_, remote_ref)| !remote_ref.is_tracking())
+                .map(|((branch, remote), _)| RemoteBranchName {
+                    branch: branch.to_owned(),
+                    remote: remote.to_owned(),
+                }),
+        );
+    }
     for (name, remote_ref) in find_remote_branches(view, &args.names)? {
         if remote_ref.is_tracking() {
             writeln!(
@@ -558,8 +787,9 @@ fn cmd_branch_track(
     if names.len() > 1 {
         writeln!(
             ui.stderr(),
-            "Started tracking {} remote branches.",
-            names.len()
+            "Started tracking {} remote branches: {}.",
+            names.len(),
+            names.iter().join(", ")
         )?;
     }
     Ok(())
@@ -678,20 +908,38 @@ fn cmd_branch_list(
             Ok(())
         };
 
+    let language = workspace_command.commit_template_language()?;
+    let template = args
+        .template
+        .as_ref()
+        .map(|text| {
+            workspace_command.parse_template(&language, text, CommitTemplateLanguage::wrap_ref_name)
+        })
+        .transpose()?;
+
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
 
-    let branches_to_list = view.branches().filter(|(name, target)| {
-        branch_names_to_list
-            .as_ref()
-            .map_or(true, |branch_names| branch_names.contains(name))
-            && (!args.conflicted || target.local_target.has_conflict())
-    });
+    let sort_key = match args.sort {
+        Some(sort_key) => sort_key,
+        None => branch_list_sort_key(command.settings().config())?,
+    };
+    let branches_to_list = view
+        .branches()
+        .filter(|(name, target)| {
+            branch_names_to_list
+                .as_ref()
+                .map_or(true, |branch_names| branch_names.contains(name))
+                && (!args.conflicted || target.local_target.has_conflict())
+        })
+        .collect_vec();
+    let branches_to_list = sort_branches(repo.as_ref(), branches_to_list, sort_key)?;
     for (name, branch_target) in branches_to_list {
-        let (mut tracking_remote_refs, untracked_remote_refs) = branch_target
-            .remote_refs
-            .into_iter()
+        let remote_refs = branch_target.remote_refs;
+        let (mut tracking_remote_refs, untracked_remote_refs) = remote_refs
+            .iter()
+            .copied()
             .partition::<Vec<_>, _>(|&(_, remote_ref)| remote_ref.is_tracking());
 
         if args.tracked {
@@ -699,10 +947,46 @@ fn cmd_branch_list(
                 .retain(|&(remote, _)| remote != git::REMOTE_NAME_FOR_LOCAL_GIT_REPO);
         }
 
+        if let Some(template) = &template {
+            let local_target = branch_target.local_target;
+            if !args.tracked && local_target.is_present() || !tracking_remote_refs.is_empty() {
+                let ref_name = RefName::local_branch(
+                    name,
+                    local_target,
+                    remote_refs.iter().map(|&(_, remote_ref)| remote_ref),
+                );
+                template.format(&ref_name, formatter)?;
+            }
+            for &(remote, remote_ref) in &tracking_remote_refs {
+                let synced = remote_ref.target == *local_target;
+                if !args.all && !args.tracked && synced {
+                    continue;
+                }
+                let ref_name = RefName::remote_branch(name, remote, remote_ref, local_target);
+                template.format(&ref_name, formatter)?;
+            }
+            if args.all {
+                for &(remote, remote_ref) in &untracked_remote_refs {
+                    let ref_name = RefName::remote_branch(name, remote, remote_ref, local_target);
+                    template.format(&ref_name, formatter)?;
+                }
+            }
+            continue;
+        }
+
         if !args.tracked && branch_target.local_target.is_present()
             || !tracking_remote_refs.is_empty()
         {
             write!(formatter.labeled("branch"), "{name}")?;
+            if let Some(change_id) = view.get_branch_pin(name) {
+                write!(formatter, " (pinned to change ")?;
+                write!(
+                    formatter.labeled("change_id"),
+                    "{}",
+                    short_change_hash(change_id)
+                )?;
+                write!(formatter, ")")?;
+            }
             if branch_target.local_target.is_present() {
                 print_branch_target(formatter, branch_target.local_target)?;
             } else {
@@ -720,36 +1004,10 @@ fn cmd_branch_list(
             write!(formatter.labeled("branch"), "@{remote}")?;
             let local_target = branch_target.local_target;
             if local_target.is_present() && !synced {
-                let remote_added_ids = remote_ref.target.added_ids().cloned().collect_vec();
-                let local_added_ids = local_target.added_ids().cloned().collect_vec();
-                let (remote_ahead_lower, remote_ahead_upper) =
-                    revset::walk_revs(repo.as_ref(), &remote_added_ids, &local_added_ids)?
-                        .count_estimate();
-                let (local_ahead_lower, local_ahead_upper) =
-                    revset::walk_revs(repo.as_ref(), &local_added_ids, &remote_added_ids)?
-                        .count_estimate();
-                let remote_ahead_message = match remote_ahead_upper {
-                    Some(0) => None,
-                    Some(upper) if upper == remote_ahead_lower => {
-                        Some(format!("ahead by {remote_ahead_lower} commits"))
-                    }
-                    _ => Some(format!("ahead by at least {remote_ahead_lower} commits")),
-                };
-                let local_ahead_message = match local_ahead_upper {
-                    Some(0) => None,
-                    Some(upper) if upper == local_ahead_lower => {
-                        Some(format!("behind by {local_ahead_lower} commits"))
-                    }
-                    _ => Some(format!("behind by at least {local_ahead_lower} commits")),
-                };
-                match (remote_ahead_message, local_ahead_message) {
-                    (Some(rm), Some(lm)) => {
-                        write!(formatter, " ({rm}, {lm})")?;
-                    }
-                    (Some(m), None) | (None, Some(m)) => {
-                        write!(formatter, " ({m})")?;
-                    }
-                    (None, None) => { /* do nothing */ }
+                if let Some(message) =
+                    describe_ahead_behind(repo.as_ref(), local_target, &remote_ref.target)?
+                {
+                    write!(formatter, " ({message})")?;
                 }
             }
             print_branch_target(formatter, &remote_ref.target)?;
@@ -784,3 +1042,130 @@ fn cmd_branch_list(
 
     Ok(())
 }
+
+fn branch_list_sort_key(config: &config::Config) -> Result<BranchSortKey, CommandError> {
+    const KEY: &str = "ui.branch-list-sort";
+    let value = match config.get_string(KEY) {
+        Ok(value) => value,
+        Err(config::ConfigError::NotFound(_)) => {
+            return Ok(BranchSortKey {
+                field: BranchSortField::Name,
+                descending: false,
+            })
+        }
+        Err(err) => return Err(config_error_with_message(format!("Invalid `{KEY}`"), err)),
+    };
+    value
+        .parse()
+        .map_err(|err: String| config_error_with_message(format!("Invalid `{KEY}`"), err))
+}
+
+/// Sorts `branches`, which must be in name order, by `sort_key`. The sort is
+/// stable, so branches with equal keys stay in name order.
+fn sort_branches<'a>(
+    repo: &dyn Repo,
+    mut branches: Vec<(&'a str, BranchTarget<'a>)>,
+    sort_key: BranchSortKey,
+) -> Result<Vec<(&'a str, BranchTarget<'a>)>, CommandError> {
+    let value_fn = match sort_key.field {
+        BranchSortField::Name => {
+            if sort_key.descending {
+                branches.reverse();
+            }
+            return Ok(branches);
+        }
+        BranchSortField::CommitterDate => branch_committer_timestamp,
+        BranchSortField::Ahead => branch_ahead_count,
+    };
+    let mut keyed_branches: Vec<_> = branches
+        .into_iter()
+        .map(|branch| Ok::<_, CommandError>((value_fn(repo, &branch.1)?, branch)))
+        .try_collect()?;
+    if sort_key.descending {
+        keyed_branches.sort_by_key(|&(value, _)| Reverse(value));
+    } else {
+        keyed_branches.sort_by_key(|&(value, _)| value);
+    }
+    Ok(keyed_branches
+        .into_iter()
+        .map(|(_, branch)| branch)
+        .collect())
+}
+
+/// Returns the newest committer timestamp among the local targets, or among
+/// the remote targets if the local branch is deleted.
+fn branch_committer_timestamp(
+    repo: &dyn Repo,
+    branch_target: &BranchTarget,
+) -> Result<i64, CommandError> {
+    let ids = if branch_target.local_target.is_present() {
+        branch_target.local_target.added_ids().collect_vec()
+    } else {
+        branch_target
+            .remote_refs
+            .iter()
+            .flat_map(|(_, remote_ref)| remote_ref.target.added_ids())
+            .collect_vec()
+    };
+    let mut newest = i64::MIN;
+    for id in ids {
+        let commit = repo.store().get_commit(id)?;
+        newest = newest.max(commit.committer().timestamp.timestamp.0);
+    }
+    Ok(newest)
+}
+
+/// Returns the largest number of local commits that aren't on one of the
+/// tracked remote branches.
+fn branch_ahead_count(repo: &dyn Repo, branch_target: &BranchTarget) -> Result<i64, CommandError> {
+    let local_added_ids = branch_target
+        .local_target
+        .added_ids()
+        .cloned()
+        .collect_vec();
+    let mut ahead = 0;
+    for &(remote, remote_ref) in &branch_target.remote_refs {
+        if !remote_ref.is_tracking() || remote == git::REMOTE_NAME_FOR_LOCAL_GIT_REPO {
+            continue;
+        }
+        let remote_added_ids = remote_ref.target.added_ids().cloned().collect_vec();
+        let count = graph::range(repo, &remote_added_ids, &local_added_ids)?.count();
+        ahead = ahead.max(count as i64);
+    }
+    Ok(ahead)
+}
+
+/// Describes how far the remote target is ahead of and behind the local
+/// target. Returns `None` if neither is ahead of the other.
+fn describe_ahead_behind(
+    repo: &dyn Repo,
+    local_target: &RefTarget,
+    remote_target: &RefTarget,
+) -> Result<Option<String>, CommandError> {
+    let remote_added_ids = remote_target.added_ids().cloned().collect_vec();
+    let local_added_ids = local_target.added_ids().cloned().collect_vec();
+    let (remote_ahead_lower, remote_ahead_upper) =
+        revset::walk_revs(repo, &remote_added_ids, &local_added_ids)?.count_estimate();
+    let (local_ahead_lower, local_ahead_upper) =
+        revset::walk_revs(repo, &local_added_ids, &remote_added_ids)?.count_estimate();
+    let remote_ahead_message = match remote_ahead_upper {
+        Some(0) => None,
+        Some(upper) if upper == remote_ahead_lower => {
+            Some(format!("ahead by {remote_ahead_lower} commits"))
+        }
+        _ => Some(format!("ahead by at least {remote_ahead_lower} commits")),
+    };
+    let local_ahead_message = match local_ahead_upper {
+        Some(0) => None,
+        Some(upper) if upper == local_ahead_lower => {
+            Some(format!("behind by {local_ahead_lower} commits"))
+        }
+        _ => Some(format!("behind by at least {local_ahead_lower} commits")),
+    };
+    let message = match (remote_ahead_message, local_ahead_message) {
+        (Some(rm), Some(lm)) => Some(format!("{rm}, {lm}")),
+        (Some(m), None) | (None, Some(m)) => Some(m),
+        (None, None) => None,
+    };
+    Ok(message)
+}
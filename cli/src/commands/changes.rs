@@ -0,0 +1,83 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use indexmap::IndexMap;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo as _;
+use jj_lib::revset::RevsetIteratorExt as _;
+use tracing::instrument;
+
+use crate::cli_util::{CommandHelper, RevisionArg};
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// List the changes you are working on and their state
+///
+/// Each mutable change is listed with the workspaces it is checked out in, the
+/// remote branches it has been pushed to, and whether it is conflicted,
+/// divergent, or empty. The commits of a divergent change are listed together.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ChangesArgs {
+    /// Which revisions to list [default: `immutable_heads()..`]
+    #[arg(long, short)]
+    revisions: Vec<RevisionArg>,
+    /// Render each commit using the given template
+    ///
+    /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    #[arg(long, short = 'T')]
+    template: Option<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_changes(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ChangesArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let revset_expression = if args.revisions.is_empty() {
+        workspace_command.parse_revset("immutable_heads()..")?
+    } else {
+        workspace_command.parse_union_revsets(&args.revisions)?
+    };
+    let repo = workspace_command.repo();
+    let commits: Vec<Commit> = workspace_command
+        .evaluate_revset(revset_expression)?
+        .iter()
+        .commits(repo.store())
+        .try_collect()?;
+    let mut changes: IndexMap<_, Vec<Commit>> = IndexMap::new();
+    for commit in commits {
+        changes
+            .entry(commit.change_id().clone())
+            .or_default()
+            .push(commit);
+    }
+
+    let template_string = match &args.template {
+        Some(value) => value.to_string(),
+        None => command
+            .settings()
+            .config()
+            .get_string("templates.changes")?,
+    };
+    let template = workspace_command.parse_commit_template(&template_string)?;
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for commit in changes.values().flatten() {
+        template.format(commit, formatter.as_mut())?;
+    }
+    Ok(())
+}
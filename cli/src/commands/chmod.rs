@@ -28,8 +28,8 @@ enum ChmodMode {
     // We use short names for enum values so that errors say that the possible values are `n, x`.
     #[value(name = "n", alias("normal"))]
     Normal,
-    /// Make a path executable (alias: executable)
-    #[value(name = "x", alias("executable"))]
+    /// Make a path executable (aliases: executable, +x)
+    #[value(name = "x", aliases(["executable", "+x"]))]
     Executable,
 }
 
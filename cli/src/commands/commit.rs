@@ -20,7 +20,8 @@ use tracing::instrument;
 use crate::cli_util::CommandHelper;
 use crate::command_error::{user_error, CommandError};
 use crate::description_util::{
-    description_template_for_commit, edit_description, join_message_paragraphs,
+    add_signoff_trailer, description_template_for_commit, edit_description,
+    join_message_paragraphs, DescriptionTemplateOptions,
 };
 use crate::ui::Ui;
 
@@ -37,6 +38,11 @@ pub(crate) struct CommitArgs {
     /// The change description to use (don't open editor)
     #[arg(long = "message", short, value_name = "MESSAGE")]
     message_paragraphs: Vec<String>,
+    /// Add a `Signed-off-by` trailer for the configured user
+    ///
+    /// This can be enabled by default with the `commit.signoff` setting.
+    #[arg(long, short)]
+    signoff: bool,
     /// Put these paths in the first commit
     #[arg(value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
@@ -86,26 +92,30 @@ new working-copy commit.
 
     let template = description_template_for_commit(
         ui,
-        command.settings(),
         tx.base_workspace_helper(),
         "",
         commit.description(),
         &base_tree,
         &middle_tree,
+        &DescriptionTemplateOptions {
+            signoff: args.signoff,
+        },
     )?;
 
     let description = if !args.message_paragraphs.is_empty() {
-        join_message_paragraphs(&args.message_paragraphs)
+        let description = join_message_paragraphs(&args.message_paragraphs);
+        add_signoff_trailer(command.settings(), description, args.signoff)?
     } else {
+        // The sign-off is added before editing so that it can be removed
         edit_description(tx.base_repo(), &template, command.settings())?
     };
 
-    let new_commit = tx
-        .mut_repo()
-        .rewrite_commit(command.settings(), &commit)
-        .set_tree_id(tree_id)
-        .set_description(description)
-        .write()?;
+    let new_commit = tx.write_commit_with_trailers(|mut_repo| {
+        mut_repo
+            .rewrite_commit(command.settings(), &commit)
+            .set_tree_id(tree_id)
+            .set_description(description)
+    })?;
     let workspace_ids = tx
         .mut_repo()
         .view()
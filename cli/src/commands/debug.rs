@@ -14,48 +14,82 @@
 
 use std::any::Any;
 use std::fmt::Debug;
-use std::io::Write as _;
+use std::io::{IsTerminal as _, Read as _, Write as _};
+use std::time::Duration;
+use std::{env, io, process, thread};
 
 use clap::Subcommand;
-use jj_lib::backend::TreeId;
+use itertools::Itertools as _;
+use jj_lib::backend::{
+    self, CommitId, ConflictId, FileId, MergedTreeId, Signature, SymlinkId, TreeId, TreeValue,
+};
 use jj_lib::default_index::{AsCompositeIndex as _, DefaultIndexStore, DefaultReadonlyIndex};
+use jj_lib::journal::JournalReader;
+use jj_lib::local_backend::LocalBackend;
+use jj_lib::local_pack::RepackScope;
 use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merge::Merge;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::revset::RevsetIteratorExt as _;
+use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::store::CacheStats;
 use jj_lib::working_copy::WorkingCopy;
 use jj_lib::{op_walk, revset};
 
 use crate::cli_util::{CommandHelper, RevisionArg};
-use crate::command_error::{internal_error, user_error, CommandError};
+use crate::command_error::{
+    internal_error, internal_error_with_message, user_error, user_error_with_message, CommandError,
+};
+use crate::commit_templater::CommitTemplateLanguage;
+use crate::operation_templater::OperationTemplateLanguage;
+use crate::template_builder::sorted_fn_names;
+use crate::template_parser::{TemplateAliasesMap, TemplateParseErrorKind};
 use crate::ui::Ui;
-use crate::{revset_util, template_parser};
+use crate::{revset_util, template_builder, template_parser};
 
 /// Low-level commands not intended for users
 #[derive(Subcommand, Clone, Debug)]
 #[command(hide = true)]
 pub enum DebugCommand {
     Revset(DebugRevsetArgs),
+    RevsetFunctions(DebugRevsetFunctionsArgs),
     #[command(name = "workingcopy")]
     WorkingCopy(DebugWorkingCopyArgs),
     Template(DebugTemplateArgs),
+    TemplateFunctions(DebugTemplateFunctionsArgs),
     Index(DebugIndexArgs),
     #[command(name = "reindex")]
     ReIndex(DebugReIndexArgs),
+    Repack(DebugRepackArgs),
     #[command(visible_alias = "view")]
     Operation(DebugOperationArgs),
     Tree(DebugTreeArgs),
+    Object(DebugObjectArgs),
     #[command(subcommand)]
     Watchman(DebugWatchmanSubcommand),
+    CacheStats(DebugCacheStatsArgs),
+    Watch(DebugWatchArgs),
+    Journal(DebugJournalArgs),
 }
 
 /// Evaluate revset to full commit IDs
 #[derive(clap::Args, Clone, Debug)]
 pub struct DebugRevsetArgs {
     revision: String,
+    /// Show the tree after each optimization step, and the subexpressions
+    /// which are evaluated only once
+    #[arg(long)]
+    explain: bool,
 }
 
+/// List revset functions with the number of arguments they accept
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugRevsetFunctionsArgs {}
+
 /// Show information about the working copy state
 #[derive(clap::Args, Clone, Debug)]
 pub struct DebugWorkingCopyArgs {}
@@ -66,6 +100,13 @@ pub struct DebugTemplateArgs {
     template: String,
 }
 
+/// List template functions and methods per type with the number of arguments
+/// they accept
+///
+/// Methods added by extensions of a custom build are included.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugTemplateFunctionsArgs {}
+
 /// Show commit index stats
 #[derive(clap::Args, Clone, Debug)]
 pub struct DebugIndexArgs {}
@@ -74,6 +115,10 @@ pub struct DebugIndexArgs {}
 #[derive(clap::Args, Clone, Debug)]
 pub struct DebugReIndexArgs {}
 
+/// Move the objects of the local (non-Git) backend into a single pack file
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugRepackArgs {}
+
 /// Show information about an operation and its view
 #[derive(clap::Args, Clone, Debug)]
 pub struct DebugOperationArgs {
@@ -105,9 +150,81 @@ pub struct DebugTreeArgs {
     #[arg(long, requires = "id")]
     dir: Option<String>,
     paths: Vec<String>,
+    /// Print each entry as a JSON object per line, with the terms of
+    /// conflicted entries listed separately
+    #[arg(long)]
+    json: bool,
     // TODO: Add an option to include trees that are ancestors of the matched paths
 }
 
+/// Print an object read from the backend by its id
+///
+/// File contents and symlink targets are printed as is. Other objects are
+/// printed in debug format, or as JSON with `--json`.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugObjectArgs {
+    #[arg(value_enum)]
+    kind: DebugObjectKind,
+    id: String,
+    /// The path of the object, for backends which store objects by path
+    #[arg(long)]
+    path: Option<String>,
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum DebugObjectKind {
+    Commit,
+    Tree,
+    File,
+    Symlink,
+    /// A conflict in the legacy format, where conflicts are stored as tree
+    /// entries
+    Conflict,
+}
+
+/// Show hit rates of the commit and tree caches
+///
+/// The given revisions are read along with their diffs against their parents,
+/// which is roughly the work `jj log --summary` does.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugCacheStatsArgs {
+    #[arg(long, short = 'r', default_value = "@")]
+    revisions: Vec<RevisionArg>,
+}
+
+/// Re-run a command whenever the repo or the working copy changes
+///
+/// The working copy is snapshotted (using the filesystem monitor if
+/// configured) at each interval, and the command is run again if it created a
+/// new operation or if another process did. The command should be read-only,
+/// e.g. `jj debug watch -- log -r 'stack(@)'`. Stop watching with Ctrl-C.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugWatchArgs {
+    /// Seconds to wait between checks for changes
+    #[arg(long, default_value_t = 1)]
+    interval: u64,
+    /// Stop after running the command this many times
+    #[arg(long)]
+    count: Option<usize>,
+    /// The jj command to run, without the leading `jj`
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    command: Vec<String>,
+}
+
+/// Print the journal of published operations
+///
+/// Each line is a JSON object with the operation id, the branches, tags, and
+/// Git refs changed by the operation, and the working-copy commits after it.
+/// Tools can read `.jj/repo/journal` directly instead.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugJournalArgs {
+    /// Keep printing entries as new operations are published
+    #[arg(long, short)]
+    follow: bool,
+}
+
 #[derive(Subcommand, Clone, Debug)]
 pub enum DebugWatchmanSubcommand {
     QueryClock,
@@ -122,13 +239,20 @@ pub fn cmd_debug(
 ) -> Result<(), CommandError> {
     match subcommand {
         DebugCommand::Revset(args) => cmd_debug_revset(ui, command, args),
+        DebugCommand::RevsetFunctions(args) => cmd_debug_revset_functions(ui, command, args),
         DebugCommand::WorkingCopy(args) => cmd_debug_working_copy(ui, command, args),
         DebugCommand::Template(args) => cmd_debug_template(ui, command, args),
+        DebugCommand::TemplateFunctions(args) => cmd_debug_template_functions(ui, command, args),
         DebugCommand::Index(args) => cmd_debug_index(ui, command, args),
         DebugCommand::ReIndex(args) => cmd_debug_reindex(ui, command, args),
+        DebugCommand::Repack(args) => cmd_debug_repack(ui, command, args),
         DebugCommand::Operation(args) => cmd_debug_operation(ui, command, args),
         DebugCommand::Tree(args) => cmd_debug_tree(ui, command, args),
+        DebugCommand::Object(args) => cmd_debug_object(ui, command, args),
         DebugCommand::Watchman(args) => cmd_debug_watchman(ui, command, args),
+        DebugCommand::CacheStats(args) => cmd_debug_cache_stats(ui, command, args),
+        DebugCommand::Watch(args) => cmd_debug_watch(ui, command, args),
+        DebugCommand::Journal(args) => cmd_debug_journal(ui, command, args),
     }
 }
 
@@ -146,7 +270,14 @@ fn cmd_debug_revset(
     writeln!(ui.stdout(), "{expression:#?}")?;
     writeln!(ui.stdout())?;
 
-    let expression = revset::optimize(expression);
+    let (expression, steps) = revset::optimize_explained(expression);
+    if args.explain {
+        for (name, step_expression) in &steps {
+            writeln!(ui.stdout(), "-- Optimized by {name}:")?;
+            writeln!(ui.stdout(), "{step_expression:#?}")?;
+            writeln!(ui.stdout())?;
+        }
+    }
     writeln!(ui.stdout(), "-- Optimized:")?;
     writeln!(ui.stdout(), "{expression:#?}")?;
     writeln!(ui.stdout())?;
@@ -158,6 +289,14 @@ fn cmd_debug_revset(
     writeln!(ui.stdout(), "{expression:#?}")?;
     writeln!(ui.stdout())?;
 
+    if args.explain {
+        writeln!(ui.stdout(), "-- Evaluated once:")?;
+        for group in revset::common_subexpressions(&expression) {
+            writeln!(ui.stdout(), "{:#?}", group[0])?;
+        }
+        writeln!(ui.stdout())?;
+    }
+
     let revset = expression.evaluate(repo)?;
     writeln!(ui.stdout(), "-- Evaluated:")?;
     writeln!(ui.stdout(), "{revset:#?}")?;
@@ -170,6 +309,31 @@ fn cmd_debug_revset(
     Ok(())
 }
 
+fn cmd_debug_revset_functions(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DebugRevsetFunctionsArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let workspace_ctx = workspace_command.revset_parse_context();
+    for name in revset::builtin_function_names() {
+        let arity = describe_arity(|arg_count| {
+            let text = format!("{name}({})", vec!["x"; arg_count].join(", "));
+            match revset::parse(&text, &workspace_ctx) {
+                Ok(_) => Ok(()),
+                Err(err) => match err.kind() {
+                    revset::RevsetParseErrorKind::InvalidFunctionArgumentCount {
+                        min, max, ..
+                    } => Err(Some((*min, *max))),
+                    _ => Err(None),
+                },
+            }
+        });
+        writeln!(ui.stdout(), "{name}: {arity}")?;
+    }
+    Ok(())
+}
+
 fn cmd_debug_working_copy(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -202,6 +366,131 @@ fn cmd_debug_template(
     Ok(())
 }
 
+fn cmd_debug_template_functions(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DebugTemplateFunctionsArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let commit_language = workspace_command.commit_template_language()?;
+    let operation_language = OperationTemplateLanguage::new(
+        repo.op_store().root_operation_id(),
+        Some(repo.op_id()),
+        command.operation_template_extension(),
+    );
+    // User aliases could shadow the functions to be probed.
+    let aliases_map = TemplateAliasesMap::new();
+    let probe_commit_template = |text: &str| {
+        template_builder::parse(
+            &commit_language,
+            text,
+            &aliases_map,
+            CommitTemplateLanguage::wrap_commit,
+        )
+        .map(|_| ())
+    };
+    let probe_operation_template = |text: &str| {
+        template_builder::parse(
+            &operation_language,
+            text,
+            &aliases_map,
+            OperationTemplateLanguage::wrap_operation,
+        )
+        .map(|_| ())
+    };
+    let probe_arity = |receiver: Option<(&str, &str)>, name: &str, use_operation: bool| {
+        describe_arity(|arg_count| {
+            let args = vec![r#""x""#; arg_count].join(", ");
+            let text = match receiver {
+                Some((prefix, suffix)) => format!("{prefix}{name}({args}){suffix}"),
+                None => format!("{name}({args})"),
+            };
+            let result = if use_operation {
+                probe_operation_template(&text)
+            } else {
+                probe_commit_template(&text)
+            };
+            result.map_err(|err| match err.kind() {
+                TemplateParseErrorKind::InvalidArgumentCount { min, max, .. } => Some((*min, *max)),
+                _ => None,
+            })
+        })
+    };
+
+    writeln!(ui.stdout(), "Template functions:")?;
+    for name in sorted_fn_names(&commit_language.build_fn_table().core.functions) {
+        let arity = probe_arity(None, name, false);
+        writeln!(ui.stdout(), "  {name}: {arity}")?;
+    }
+    let mut method_names = commit_language.build_fn_table().method_names();
+    for (type_name, names) in operation_language.build_fn_table().method_names() {
+        if !method_names.iter().any(|(name, _)| *name == type_name) {
+            method_names.push((type_name, names));
+        }
+    }
+    for (type_name, names) in method_names {
+        if names.is_empty() {
+            continue;
+        }
+        // Build an expression of the type from the commit or operation keywords.
+        let receiver = match type_name {
+            "String" => Some((("description.", ""), false)),
+            "Integer" => Some((("description.len().", ""), false)),
+            "Signature" => Some((("author.", ""), false)),
+            "Timestamp" => Some((("author.timestamp().", ""), false)),
+            "TimestampRange" => Some((("time.", ""), true)),
+            "Commit" => Some((("self.", ""), false)),
+            "RefName" => Some((("branches.map(|r| r.", ")"), false)),
+            "CommitOrChangeId" => Some((("commit_id.", ""), false)),
+            "ShortestIdPrefix" => Some((("commit_id.shortest().", ""), false)),
+            "Workspace" => Some((("current_workspace().", ""), false)),
+            "Operation" => Some((("self.", ""), true)),
+            "OperationId" => Some((("id.", ""), true)),
+            _ => None,
+        };
+        writeln!(ui.stdout(), "{type_name} methods:")?;
+        for name in names {
+            let arity = match receiver {
+                Some((receiver, use_operation)) => probe_arity(Some(receiver), name, use_operation),
+                None => "unknown".to_owned(),
+            };
+            writeln!(ui.stdout(), "  {name}: {arity}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Describes the number of arguments a function accepts, such as "1 to 2
+/// arguments".
+///
+/// The `probe` callback builds a call with the given number of arguments, and
+/// returns the accepted `(min, max)` counts if the call fails with an
+/// argument-count error. The argument count is checked before the argument
+/// types, so the count is found by calling with no arguments, and then with
+/// many arguments.
+fn describe_arity(probe: impl Fn(usize) -> Result<(), Option<(usize, Option<usize>)>>) -> String {
+    let arity_from = |result: &Result<(), Option<(usize, Option<usize>)>>| match *result {
+        Err(Some((min, Some(max)))) if min == max => Some(format!("{min} arguments")),
+        Err(Some((min, Some(max)))) => Some(format!("{min} to {max} arguments")),
+        Err(Some((1, None))) => Some("at least 1 argument".to_owned()),
+        Err(Some((min, None))) => Some(format!("at least {min} arguments")),
+        Ok(()) | Err(None) => None,
+    };
+    let no_args = probe(0);
+    if let Some(arity) = arity_from(&no_args) {
+        return arity;
+    }
+    let many_args = probe(8);
+    if let Some(arity) = arity_from(&many_args) {
+        arity
+    } else if no_args.is_ok() && many_args.is_ok() {
+        "any number of arguments".to_owned()
+    } else {
+        "unknown".to_owned()
+    }
+}
+
 fn cmd_debug_index(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -272,6 +561,31 @@ fn cmd_debug_reindex(
     Ok(())
 }
 
+fn cmd_debug_repack(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DebugRepackArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    let store = workspace.repo_loader().store();
+    let Some(local_backend) = store.backend_impl().downcast_ref::<LocalBackend>() else {
+        return Err(user_error(
+            "Only repositories using the local backend can be repacked",
+        ));
+    };
+    let stats = local_backend.repack(RepackScope::All)?;
+    writeln!(ui.stdout(), "Number of objects: {}", stats.num_objects)?;
+    writeln!(ui.stdout(), "Number of deltas: {}", stats.num_deltas)?;
+    writeln!(ui.stdout(), "Pack size: {} bytes", stats.pack_size)?;
+    writeln!(
+        ui.stdout(),
+        "Removed {} loose objects and {} old packs",
+        stats.num_loose_removed,
+        stats.num_packs_removed
+    )?;
+    Ok(())
+}
+
 fn cmd_debug_operation(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -319,13 +633,282 @@ fn cmd_debug_tree(
     };
     let matcher = workspace_command.matcher_from_values(&args.paths)?;
     for (path, value) in tree.entries_matching(matcher.as_ref()) {
-        let ui_path = workspace_command.format_file_path(&path);
-        writeln!(ui.stdout(), "{ui_path}: {value:?}")?;
+        if args.json {
+            let entry = serde_json::json!({
+                "path": path.as_internal_file_string(),
+                "value": merge_to_json(&value, |term| optional_tree_value_to_json(term.as_ref())),
+            });
+            writeln!(ui.stdout(), "{entry}")?;
+        } else {
+            let ui_path = workspace_command.format_file_path(&path);
+            writeln!(ui.stdout(), "{ui_path}: {value:?}")?;
+        }
     }
 
     Ok(())
 }
 
+fn cmd_debug_object(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugObjectArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let store = workspace_command.repo().store();
+    let path = if let Some(path_str) = &args.path {
+        workspace_command.parse_file_path(path_str)?
+    } else {
+        RepoPathBuf::root()
+    };
+    let invalid_id = || user_error(format!("Invalid {} id", args.kind.name()));
+    match args.kind {
+        DebugObjectKind::Commit => {
+            let id = CommitId::try_from_hex(&args.id).map_err(|_| invalid_id())?;
+            let commit = store.get_commit(&id)?;
+            if args.json {
+                let json = commit_to_json(commit.store_commit());
+                writeln!(ui.stdout(), "{json:#}")?;
+            } else {
+                writeln!(ui.stdout(), "{:#?}", commit.store_commit())?;
+            }
+        }
+        DebugObjectKind::Tree => {
+            let id = TreeId::try_from_hex(&args.id).map_err(|_| invalid_id())?;
+            let tree = store.get_tree(&path, &id)?;
+            if args.json {
+                let entries: serde_json::Map<_, _> = tree
+                    .entries_non_recursive()
+                    .map(|entry| {
+                        let name = entry.name().as_str().to_owned();
+                        (name, tree_value_to_json(entry.value()))
+                    })
+                    .collect();
+                let json = serde_json::json!({ "entries": entries });
+                writeln!(ui.stdout(), "{json:#}")?;
+            } else {
+                for entry in tree.entries_non_recursive() {
+                    let name = entry.name().as_str();
+                    writeln!(ui.stdout(), "{name}: {:?}", entry.value())?;
+                }
+            }
+        }
+        DebugObjectKind::File => {
+            let id = FileId::try_from_hex(&args.id).map_err(|_| invalid_id())?;
+            let mut content = vec![];
+            store
+                .read_file(&path, &id)?
+                .read_to_end(&mut content)
+                .map_err(|err| internal_error_with_message("Failed to read file", err))?;
+            if args.json {
+                let json = bytes_to_json("content", content);
+                writeln!(ui.stdout(), "{json:#}")?;
+            } else {
+                ui.stdout().write_all(&content)?;
+            }
+        }
+        DebugObjectKind::Symlink => {
+            let id = SymlinkId::try_from_hex(&args.id).map_err(|_| invalid_id())?;
+            let target = store.read_symlink(&path, &id)?;
+            if args.json {
+                let json = serde_json::json!({ "target": target });
+                writeln!(ui.stdout(), "{json:#}")?;
+            } else {
+                writeln!(ui.stdout(), "{target}")?;
+            }
+        }
+        DebugObjectKind::Conflict => {
+            let id = ConflictId::try_from_hex(&args.id).map_err(|_| invalid_id())?;
+            let conflict = store.read_conflict(&path, &id)?;
+            if args.json {
+                let json =
+                    merge_to_json(&conflict, |term| optional_tree_value_to_json(term.as_ref()));
+                writeln!(ui.stdout(), "{json:#}")?;
+            } else {
+                writeln!(ui.stdout(), "{conflict:#?}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl DebugObjectKind {
+    fn name(self) -> &'static str {
+        match self {
+            DebugObjectKind::Commit => "commit",
+            DebugObjectKind::Tree => "tree",
+            DebugObjectKind::File => "file",
+            DebugObjectKind::Symlink => "symlink",
+            DebugObjectKind::Conflict => "conflict",
+        }
+    }
+}
+
+fn merge_to_json<T>(
+    merge: &Merge<T>,
+    term_to_json: impl Fn(&T) -> serde_json::Value,
+) -> serde_json::Value {
+    serde_json::json!({
+        "removes": merge.removes().map(&term_to_json).collect_vec(),
+        "adds": merge.adds().map(&term_to_json).collect_vec(),
+    })
+}
+
+fn optional_tree_value_to_json(value: Option<&TreeValue>) -> serde_json::Value {
+    value.map_or(serde_json::Value::Null, tree_value_to_json)
+}
+
+fn tree_value_to_json(value: &TreeValue) -> serde_json::Value {
+    match value {
+        TreeValue::File { id, executable } => serde_json::json!({
+            "type": "file",
+            "id": id.hex(),
+            "executable": executable,
+        }),
+        TreeValue::Symlink(id) => serde_json::json!({ "type": "symlink", "id": id.hex() }),
+        TreeValue::Tree(id) => serde_json::json!({ "type": "tree", "id": id.hex() }),
+        TreeValue::GitSubmodule(id) => {
+            serde_json::json!({ "type": "git_submodule", "id": id.hex() })
+        }
+        TreeValue::Conflict(id) => serde_json::json!({ "type": "conflict", "id": id.hex() }),
+    }
+}
+
+fn commit_to_json(commit: &backend::Commit) -> serde_json::Value {
+    let signature_to_json = |signature: &Signature| {
+        serde_json::json!({
+            "name": signature.name,
+            "email": signature.email,
+            "timestamp": signature.timestamp.timestamp.0,
+            "tz_offset": signature.timestamp.tz_offset,
+        })
+    };
+    let root_tree = match &commit.root_tree {
+        MergedTreeId::Legacy(id) => serde_json::json!({ "legacy": id.hex() }),
+        MergedTreeId::Merge(ids) => merge_to_json(ids, |id| id.hex().into()),
+    };
+    serde_json::json!({
+        "parents": commit.parents.iter().map(|id| id.hex()).collect_vec(),
+        "predecessors": commit.predecessors.iter().map(|id| id.hex()).collect_vec(),
+        "root_tree": root_tree,
+        "change_id": commit.change_id.reverse_hex(),
+        "description": commit.description,
+        "author": signature_to_json(&commit.author),
+        "committer": signature_to_json(&commit.committer),
+        "extra": commit.extra,
+        "secure_sig": commit
+            .secure_sig
+            .as_ref()
+            .map(|sig| bytes_to_json("sig", sig.sig.clone())),
+    })
+}
+
+/// Returns an object with the `bytes` as a string keyed by `name`, or as hex
+/// keyed by `name` with a `_hex` suffix if they aren't valid UTF-8.
+fn bytes_to_json(name: &str, bytes: Vec<u8>) -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+    match String::from_utf8(bytes) {
+        Ok(text) => map.insert(name.to_owned(), text.into()),
+        Err(err) => map.insert(format!("{name}_hex"), hex::encode(err.into_bytes()).into()),
+    };
+    map.into()
+}
+
+fn cmd_debug_cache_stats(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugCacheStatsArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let revset_expression = workspace_command.parse_union_revsets(&args.revisions)?;
+    let revset = workspace_command.evaluate_revset(revset_expression)?;
+    for commit in revset.iter().commits(repo.store()) {
+        let commit = commit?;
+        let parent_tree = merge_commit_trees(repo.as_ref(), &commit.parents())?;
+        parent_tree.diff_summary(&commit.tree()?, &EverythingMatcher)?;
+    }
+    let stats = repo.store().cache_stats();
+    write_cache_stats(ui, "Commit cache", &stats.commits)?;
+    write_cache_stats(ui, "Tree cache", &stats.trees)?;
+    Ok(())
+}
+
+fn write_cache_stats(ui: &Ui, name: &str, stats: &CacheStats) -> io::Result<()> {
+    writeln!(ui.stdout(), "{name}:")?;
+    if let Some(capacity) = stats.capacity {
+        writeln!(ui.stdout(), "  Capacity: {capacity}")?;
+    } else {
+        writeln!(ui.stdout(), "  Capacity: unlimited")?;
+    }
+    writeln!(ui.stdout(), "  Entries: {}", stats.len)?;
+    writeln!(ui.stdout(), "  Hits: {}", stats.hits)?;
+    writeln!(ui.stdout(), "  Misses: {}", stats.misses)?;
+    if let Some(hit_rate) = stats.hit_rate() {
+        writeln!(ui.stdout(), "  Hit rate: {:.1}%", hit_rate * 100.0)?;
+    }
+    Ok(())
+}
+
+fn cmd_debug_watch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugWatchArgs,
+) -> Result<(), CommandError> {
+    let jj_path = env::current_exe().map_err(|err| {
+        user_error_with_message("Failed to find the path of the jj executable", err)
+    })?;
+    let mut last_op_id = None;
+    let mut run_count = 0;
+    loop {
+        // Snapshotting creates a new operation if the working copy changed.
+        let workspace_command = command.workspace_helper(ui)?;
+        let op_id = workspace_command.repo().op_id().clone();
+        if last_op_id.as_ref() != Some(&op_id) {
+            if io::stdout().is_terminal() {
+                // Clear the screen and move the cursor to the top left.
+                write!(ui.stdout(), "\x1b[2J\x1b[H")?;
+                ui.stdout().flush()?;
+            }
+            let status = process::Command::new(&jj_path)
+                .arg("--repository")
+                .arg(workspace_command.workspace_root())
+                .args(&args.command)
+                .status()
+                .map_err(|err| user_error_with_message("Failed to run jj", err))?;
+            if !status.success() {
+                writeln!(ui.warning_default(), "Command exited with {status}")?;
+            }
+            // The command may have snapshotted the working copy by itself.
+            let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+            last_op_id = Some(workspace_command.repo().op_id().clone());
+            run_count += 1;
+            if args.count.is_some_and(|count| run_count >= count) {
+                return Ok(());
+            }
+        }
+        thread::sleep(Duration::from_secs(args.interval));
+    }
+}
+
+fn cmd_debug_journal(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugJournalArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    let mut reader = JournalReader::open(workspace.repo_path())?;
+    loop {
+        while let Some(entry) = reader.next_entry().map_err(internal_error)? {
+            writeln!(ui.stdout(), "{}", entry.to_json())?;
+        }
+        if !args.follow {
+            return Ok(());
+        }
+        ui.stdout().flush()?;
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
 #[cfg(feature = "watchman")]
 fn cmd_debug_watchman(
     ui: &mut Ui,
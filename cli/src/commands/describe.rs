@@ -20,7 +20,8 @@ use tracing::instrument;
 use crate::cli_util::{CommandHelper, RevisionArg};
 use crate::command_error::CommandError;
 use crate::description_util::{
-    description_template_for_describe, edit_description, join_message_paragraphs,
+    add_signoff_trailer, description_template_for_describe, edit_description,
+    join_message_paragraphs, DescriptionTemplateOptions,
 };
 use crate::ui::Ui;
 
@@ -48,6 +49,11 @@ pub(crate) struct DescribeArgs {
     /// This is mainly useful in combination with e.g. `--reset-author`.
     #[arg(long)]
     no_edit: bool,
+    /// Add a `Signed-off-by` trailer for the configured user
+    ///
+    /// This can be enabled by default with the `commit.signoff` setting.
+    #[arg(long, short)]
+    signoff: bool,
     /// Reset the author to the configured user
     ///
     /// This resets the author name, email, and timestamp.
@@ -72,29 +78,40 @@ pub(crate) fn cmd_describe(
     let description = if args.stdin {
         let mut buffer = String::new();
         io::stdin().read_to_string(&mut buffer).unwrap();
-        buffer
+        add_signoff_trailer(command.settings(), buffer, args.signoff)?
     } else if !args.message_paragraphs.is_empty() {
-        join_message_paragraphs(&args.message_paragraphs)
+        let description = join_message_paragraphs(&args.message_paragraphs);
+        add_signoff_trailer(command.settings(), description, args.signoff)?
     } else if args.no_edit {
-        commit.description().to_owned()
+        let description = commit.description().to_owned();
+        add_signoff_trailer(command.settings(), description, args.signoff)?
     } else {
-        let template =
-            description_template_for_describe(ui, command.settings(), &workspace_command, &commit)?;
+        // The sign-off is added before editing so that it can be removed
+        let template = description_template_for_describe(
+            ui,
+            command.settings(),
+            &workspace_command,
+            &commit,
+            &DescriptionTemplateOptions {
+                signoff: args.signoff,
+            },
+        )?;
         edit_description(workspace_command.repo(), &template, command.settings())?
     };
     if description == *commit.description() && !args.reset_author {
         writeln!(ui.stderr(), "Nothing changed.")?;
     } else {
         let mut tx = workspace_command.start_transaction();
-        let mut commit_builder = tx
-            .mut_repo()
-            .rewrite_commit(command.settings(), &commit)
-            .set_description(description);
-        if args.reset_author {
-            let new_author = commit_builder.committer().clone();
-            commit_builder = commit_builder.set_author(new_author);
-        }
-        commit_builder.write()?;
+        tx.write_commit_with_trailers(|mut_repo| {
+            let mut commit_builder = mut_repo
+                .rewrite_commit(command.settings(), &commit)
+                .set_description(description);
+            if args.reset_author {
+                let new_author = commit_builder.committer().clone();
+                commit_builder = commit_builder.set_author(new_author);
+            }
+            commit_builder
+        })?;
         tx.finish(ui, format!("describe commit {}", commit.id().hex()))?;
     }
     Ok(())
@@ -12,11 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use jj_lib::rewrite::merge_commit_trees;
+use std::collections::HashSet;
+
+use itertools::Itertools as _;
+use jj_lib::backend::ChangeId;
+use jj_lib::commit::Commit;
+use jj_lib::dag_walk;
+use jj_lib::operation::Operation;
+use jj_lib::repo::{ReadonlyRepo, Repo as _};
+use jj_lib::rewrite::{merge_commit_trees, rebase_to_dest_parent};
 use tracing::instrument;
 
-use crate::cli_util::{CommandHelper, RevisionArg};
-use crate::command_error::CommandError;
+use crate::cli_util::{short_change_hash, short_operation_hash, CommandHelper, RevisionArg};
+use crate::command_error::{user_error, CommandError};
 use crate::diff_util::{diff_formats_for, show_diff, DiffFormatArgs};
 use crate::ui::Ui;
 
@@ -31,6 +39,10 @@ use crate::ui::Ui;
 /// given revisions. If either is left out, it defaults to the working-copy
 /// commit. For example, `jj diff --from main` shows the changes from "main"
 /// (perhaps a branch name) to the working-copy commit.
+///
+/// With the `--change` and `--from-op` options, shows how the given change was
+/// modified between the two operations, even if the old version of the change
+/// is now hidden. Like `jj interdiff`, changes from the parents are excluded.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct DiffArgs {
     /// Show changes in this revision, compared to its parent(s)
@@ -46,6 +58,15 @@ pub(crate) struct DiffArgs {
     /// Show changes to this revision
     #[arg(long, conflicts_with = "revision")]
     to: Option<RevisionArg>,
+    /// Show how this change was modified between `--from-op` and `--to-op`
+    #[arg(long, conflicts_with_all = ["revision", "from", "to"], requires = "from_op")]
+    change: Option<RevisionArg>,
+    /// Operation to compare the `--change` from
+    #[arg(long, requires = "change")]
+    from_op: Option<String>,
+    /// Operation to compare the `--change` to [default: @]
+    #[arg(long, requires = "change")]
+    to_op: Option<String>,
     /// Restrict the diff to these paths
     #[arg(value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
@@ -62,7 +83,21 @@ pub(crate) fn cmd_diff(
     let workspace_command = command.workspace_helper(ui)?;
     let from_tree;
     let to_tree;
-    if args.from.is_some() || args.to.is_some() {
+    if let (Some(change), Some(from_op)) = (&args.change, &args.from_op) {
+        let change_id = workspace_command
+            .resolve_single_rev(change)?
+            .change_id()
+            .clone();
+        let repo_loader = workspace_command.repo().loader();
+        let from_op = workspace_command.resolve_single_op(from_op)?;
+        let to_op = workspace_command.resolve_single_op(args.to_op.as_deref().unwrap_or("@"))?;
+        let from_repo = repo_loader.load_at(&from_op)?;
+        let to_repo = repo_loader.load_at(&to_op)?;
+        let to = resolve_change_at_op(&to_repo, &to_op, &change_id, None)?;
+        let from = resolve_change_at_op(&from_repo, &from_op, &change_id, Some(&to))?;
+        from_tree = rebase_to_dest_parent(workspace_command.repo().as_ref(), &from, &to)?;
+        to_tree = to.tree()?;
+    } else if args.from.is_some() || args.to.is_some() {
         let from = workspace_command.resolve_single_rev(args.from.as_deref().unwrap_or("@"))?;
         from_tree = from.tree()?;
         let to = workspace_command.resolve_single_rev(args.to.as_deref().unwrap_or("@"))?;
@@ -88,3 +123,51 @@ pub(crate) fn cmd_diff(
     )?;
     Ok(())
 }
+
+/// Finds the visible commit of the change in the repo loaded at `op`. If the
+/// change is divergent there, picks the commit `successor` was rewritten from.
+fn resolve_change_at_op(
+    repo: &ReadonlyRepo,
+    op: &Operation,
+    change_id: &ChangeId,
+    successor: Option<&Commit>,
+) -> Result<Commit, CommandError> {
+    let candidates: HashSet<_> = repo
+        .resolve_change_id(change_id)
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    if let Some(successor) = successor {
+        let store = repo.store();
+        let predecessors = dag_walk::dfs_ok(
+            [Ok(successor.clone())],
+            |commit: &Commit| commit.id().clone(),
+            |commit| {
+                commit
+                    .predecessor_ids()
+                    .iter()
+                    .map(|id| store.get_commit(id))
+                    .collect_vec()
+            },
+        );
+        for commit in predecessors {
+            let commit = commit?;
+            if candidates.contains(commit.id()) {
+                return Ok(commit);
+            }
+        }
+    }
+    match candidates.into_iter().collect::<Vec<_>>().as_slice() {
+        [id] => Ok(repo.store().get_commit(id)?),
+        [] => Err(user_error(format!(
+            "Change {} doesn't exist at operation {}",
+            short_change_hash(change_id),
+            short_operation_hash(op.id())
+        ))),
+        _ => Err(user_error(format!(
+            "Change {} is divergent at operation {}",
+            short_change_hash(change_id),
+            short_operation_hash(op.id())
+        ))),
+    }
+}
@@ -20,7 +20,7 @@ use jj_lib::commit::Commit;
 use jj_lib::repo::Repo;
 use tracing::instrument;
 
-use crate::cli_util::{short_commit_hash, CommandHelper, RevisionArg};
+use crate::cli_util::{CommandHelper, RevisionArg};
 use crate::command_error::{user_error, CommandError};
 use crate::ui::Ui;
 
@@ -79,7 +79,11 @@ pub(crate) fn cmd_duplicate(
     }
 
     for (old_id, new_commit) in &duplicated_old_to_new {
-        write!(ui.stderr(), "Duplicated {} as ", short_commit_hash(old_id))?;
+        let old_commit = tx.base_repo().store().get_commit(old_id)?;
+        write!(ui.stderr(), "Duplicated ")?;
+        tx.base_workspace_helper()
+            .write_commit_summary(ui.stderr_formatter().as_mut(), &old_commit)?;
+        write!(ui.stderr(), " as ")?;
         tx.write_commit_summary(ui.stderr_formatter().as_mut(), new_commit)?;
         writeln!(ui.stderr())?;
     }
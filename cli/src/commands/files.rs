@@ -12,25 +12,127 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io;
 use std::io::Write;
+use std::sync::Arc;
 
+use itertools::Itertools as _;
+use jj_lib::backend::TreeValue;
+use jj_lib::commit::Commit;
+use jj_lib::graph;
+use jj_lib::merge::MergedTreeValue;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::repo_path::RepoPathBuf;
 use tracing::instrument;
 
 use crate::cli_util::{CommandHelper, RevisionArg};
 use crate::command_error::CommandError;
+use crate::generic_templater::GenericTemplateLanguage;
+use crate::template_builder::TemplateLanguage as _;
+use crate::templater::{TemplatePropertyError, TemplatePropertyExt as _};
 use crate::ui::Ui;
 
 /// List files in a revision
+///
+/// Paths may be glob patterns such as `'**/Cargo.toml'`. If `--revision` is
+/// repeated, the files in each revision are listed in turn.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct FilesArgs {
-    /// The revision to list files in
+    /// The revision(s) to list files in
     #[arg(long, short, default_value = "@")]
-    revision: RevisionArg,
+    revision: Vec<RevisionArg>,
+    /// Render each file using the given template
+    ///
+    /// The following keywords are defined:
+    ///
+    /// * `path: String`: Path to the file, relative to the current directory.
+    /// * `commit_id: String`: Full id of the revision the file was found in.
+    /// * `mode: String`: One of "file", "executable", "symlink",
+    ///   "git-submodule", or "conflict".
+    /// * `size: Integer`: Size of the file contents or the symlink target in
+    ///   bytes. Conflicted files and submodules have size 0.
+    /// * `last_modified_commit_id: String`: Full id of the closest ancestor of
+    ///   the revision which modified the file.
+    ///
+    /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    #[arg(long, short = 'T', verbatim_doc_comment)]
+    template: Option<String>,
     /// Only list files matching these prefixes (instead of all files)
     #[arg(value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
 }
 
+/// File found in a revision, which can be rendered by `jj files -T`.
+#[derive(Clone)]
+struct FileEntry {
+    commit: Commit,
+    path: RepoPathBuf,
+    ui_path: String,
+    value: MergedTreeValue,
+}
+
+fn file_entry_mode(value: &MergedTreeValue) -> &'static str {
+    match value.as_resolved() {
+        Some(Some(TreeValue::File {
+            executable: false, ..
+        })) => "file",
+        Some(Some(TreeValue::File {
+            executable: true, ..
+        })) => "executable",
+        Some(Some(TreeValue::Symlink(_))) => "symlink",
+        Some(Some(TreeValue::GitSubmodule(_))) => "git-submodule",
+        // Trees and absent entries aren't emitted by entries_matching().
+        _ => "conflict",
+    }
+}
+
+fn file_entry_size(entry: &FileEntry) -> Result<u64, TemplatePropertyError> {
+    let store = entry.commit.store();
+    match entry.value.as_resolved() {
+        Some(Some(TreeValue::File { id, .. })) => {
+            let mut reader = store.read_file(&entry.path, id)?;
+            Ok(io::copy(&mut reader, &mut io::sink())?)
+        }
+        Some(Some(TreeValue::Symlink(id))) => {
+            let target = store.read_symlink(&entry.path, id)?;
+            Ok(target.len().try_into()?)
+        }
+        _ => Ok(0),
+    }
+}
+
+fn file_template_language(repo: Arc<ReadonlyRepo>) -> GenericTemplateLanguage<'static, FileEntry> {
+    type L = GenericTemplateLanguage<'static, FileEntry>;
+    let mut language = L::new();
+    language.add_keyword("path", |self_property| {
+        let out_property = self_property.map(|entry| entry.ui_path);
+        Ok(L::wrap_string(out_property))
+    });
+    language.add_keyword("commit_id", |self_property| {
+        let out_property = self_property.map(|entry| entry.commit.id().hex());
+        Ok(L::wrap_string(out_property))
+    });
+    language.add_keyword("mode", |self_property| {
+        let out_property = self_property.map(|entry| file_entry_mode(&entry.value).to_owned());
+        Ok(L::wrap_string(out_property))
+    });
+    language.add_keyword("size", |self_property| {
+        let out_property =
+            self_property.and_then(|entry| Ok(i64::try_from(file_entry_size(&entry)?)?));
+        Ok(L::wrap_integer(out_property))
+    });
+    language.add_keyword("last_modified_commit_id", move |self_property| {
+        let repo = repo.clone();
+        let out_property = self_property.and_then(move |entry| {
+            let id = graph::last_modifying_commit(repo.as_ref(), entry.commit.id(), &entry.path)?;
+            Ok(id.map(|id| id.hex()).unwrap_or_default())
+        });
+        Ok(L::wrap_string(out_property))
+    });
+    language
+}
+
 #[instrument(skip_all)]
 pub(crate) fn cmd_files(
     ui: &mut Ui,
@@ -38,16 +140,38 @@ pub(crate) fn cmd_files(
     args: &FilesArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
-    let commit = workspace_command.resolve_single_rev(&args.revision)?;
-    let tree = commit.tree()?;
+    let commits: Vec<_> = args
+        .revision
+        .iter()
+        .map(|revision_str| workspace_command.resolve_single_rev(revision_str))
+        .try_collect()?;
     let matcher = workspace_command.matcher_from_values(&args.paths)?;
+    let template = args
+        .template
+        .as_ref()
+        .map(|text| {
+            let language = file_template_language(workspace_command.repo().clone());
+            workspace_command.parse_template(&language, text, GenericTemplateLanguage::wrap_self)
+        })
+        .transpose()?;
     ui.request_pager();
-    for (name, _value) in tree.entries_matching(matcher.as_ref()) {
-        writeln!(
-            ui.stdout(),
-            "{}",
-            &workspace_command.format_file_path(&name)
-        )?;
+    let mut formatter = ui.stdout_formatter();
+    for commit in commits {
+        let tree = commit.tree()?;
+        for (path, value) in tree.entries_matching(matcher.as_ref()) {
+            let ui_path = workspace_command.format_file_path(&path);
+            if let Some(template) = &template {
+                let entry = FileEntry {
+                    commit: commit.clone(),
+                    path,
+                    ui_path,
+                    value,
+                };
+                template.format(&entry, formatter.as_mut())?;
+            } else {
+                writeln!(formatter, "{ui_path}")?;
+            }
+        }
     }
     Ok(())
 }
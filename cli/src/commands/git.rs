@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
@@ -21,17 +21,18 @@ use std::{fmt, fs, io};
 
 use clap::{ArgGroup, Subcommand};
 use itertools::Itertools;
-use jj_lib::backend::TreeValue;
+use jj_lib::backend::{CommitId, TreeValue};
 use jj_lib::file_util;
 use jj_lib::git::{
     self, parse_gitmodules, GitBranchPushTargets, GitFetchError, GitFetchStats, GitPushError,
+    RejectedRefUpdate,
 };
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::RefTarget;
 use jj_lib::refs::{
     classify_branch_push_action, BranchPushAction, BranchPushUpdate, LocalAndRemoteRef,
 };
-use jj_lib::repo::{ReadonlyRepo, Repo};
+use jj_lib::repo::{MutableRepo, ReadonlyRepo, Repo};
 use jj_lib::repo_path::RepoPath;
 use jj_lib::revset::{self, RevsetExpression, RevsetIteratorExt as _};
 use jj_lib::settings::{ConfigResultExt as _, UserSettings};
@@ -39,6 +40,7 @@ use jj_lib::str_util::StringPattern;
 use jj_lib::view::View;
 use jj_lib::workspace::Workspace;
 use maplit::hashset;
+use unicode_width::UnicodeWidthStr as _;
 
 use crate::cli_util::{
     parse_string_pattern, print_trackable_remote_branches, short_change_hash, short_commit_hash,
@@ -52,6 +54,7 @@ use crate::git_util::{
     get_git_repo, is_colocated_git_workspace, print_failed_git_export, print_git_import_stats,
     with_remote_git_callbacks, GitSidebandProgressMessageWriter,
 };
+use crate::messages;
 use crate::ui::Ui;
 
 /// Commands for working with the underlying Git repo
@@ -146,6 +149,14 @@ pub struct GitInitArgs {
     /// This option is mutually exclusive with `--colocate`.
     #[arg(long, conflicts_with = "colocate", value_hint = clap::ValueHint::DirPath)]
     git_repo: Option<String>,
+
+    /// Create a commit for each entry of the Git stash
+    ///
+    /// This only applies when the backing Git repo already exists. Each commit
+    /// is created on top of the commit the changes were stashed on. The
+    /// stashed index and untracked files aren't imported.
+    #[arg(long)]
+    import_stash: bool,
 }
 
 /// Fetch from a Git remote
@@ -158,6 +169,9 @@ pub struct GitFetchArgs {
     ///
     /// By default, the specified name matches exactly. Use `glob:` prefix to
     /// expand `*` as a glob. The other wildcard characters aren't supported.
+    ///
+    /// Can be repeated. All matching branches are fetched at once, and only
+    /// the matching remote-tracking branches are imported.
     #[arg(long, short, default_value = "glob:*", value_parser = parse_string_pattern)]
     branch: Vec<StringPattern>,
     /// The remote to fetch from (only named remotes are supported, can be
@@ -167,6 +181,11 @@ pub struct GitFetchArgs {
     /// Fetch from all remotes
     #[arg(long, conflicts_with = "remotes")]
     all_remotes: bool,
+    /// Keep remote-tracking branches that were deleted on the remote
+    ///
+    /// This overrides the `git.fetch-prune` config.
+    #[arg(long)]
+    no_prune: bool,
 }
 
 /// Create a new repo backed by a clone of a Git repo
@@ -195,9 +214,13 @@ pub struct GitCloneArgs {
 #[command(group(ArgGroup::new("specific").args(&["branch", "change", "revisions"]).multiple(true)))]
 #[command(group(ArgGroup::new("what").args(&["all", "deleted", "tracked"]).conflicts_with("specific")))]
 pub struct GitPushArgs {
-    /// The remote to push to (only named remotes are supported)
-    #[arg(long)]
-    remote: Option<String>,
+    /// The remote to push to (only named remotes are supported, can be
+    /// repeated)
+    ///
+    /// The branches are pushed to each remote in order. If a push fails, the
+    /// remaining remotes are skipped.
+    #[arg(long = "remote", value_name = "REMOTE")]
+    remotes: Vec<String>,
     /// Push only this branch, or branches matching a pattern (can be repeated)
     ///
     /// By default, the specified name matches exactly. Use `glob:` prefix to
@@ -377,6 +400,7 @@ pub fn git_init(
     workspace_root: &Path,
     colocate: bool,
     git_repo: Option<&str>,
+    import_stash: bool,
 ) -> Result<(), CommandError> {
     #[derive(Clone, Debug)]
     enum GitInitMode {
@@ -426,7 +450,7 @@ pub fn git_init(
             // Import refs first so all the reachable commits are indexed in
             // chronological order.
             let colocated = is_colocated_git_workspace(&workspace, &repo);
-            let repo = init_git_refs(ui, command, repo, colocated)?;
+            let repo = init_git_refs(ui, command, repo, colocated, import_stash)?;
             let mut workspace_command = command.for_loaded_repo(ui, workspace, repo)?;
             maybe_add_gitignore(&workspace_command)?;
             workspace_command.maybe_snapshot(ui)?;
@@ -453,6 +477,9 @@ pub fn git_init(
 /// Imports branches and tags from the underlying Git repo, exports changes if
 /// the repo is colocated.
 ///
+/// Commits which are only reachable from the Git reflogs are imported as hidden
+/// commits, and the Git stash entries are imported as commits if requested.
+///
 /// This is similar to `WorkspaceCommandHelper::import_git_refs()`, but never
 /// moves the Git HEAD to the working copy parent.
 fn init_git_refs(
@@ -460,10 +487,11 @@ fn init_git_refs(
     command: &CommandHelper,
     repo: Arc<ReadonlyRepo>,
     colocated: bool,
+    import_stash: bool,
 ) -> Result<Arc<ReadonlyRepo>, CommandError> {
     let mut tx = start_repo_transaction(&repo, command.settings(), command.string_args());
     // There should be no old refs to abandon, but enforce it.
-    let mut git_settings = command.settings().git_settings();
+    let mut git_settings = command.settings().git_settings()?;
     git_settings.abandon_unreachable_commits = false;
     let stats = git::import_some_refs(
         tx.mut_repo(),
@@ -471,17 +499,39 @@ fn init_git_refs(
         // Initial import shouldn't fail because of reserved remote name.
         |ref_name| !git::is_reserved_git_remote_ref(ref_name),
     )?;
-    if !tx.mut_repo().has_changes() {
+    let git_repo = get_git_repo(repo.store())?;
+    let reflog_commit_ids = git::import_reflog(tx.mut_repo(), &git_repo)?;
+    let stash_commits = if import_stash {
+        git::import_stash(tx.mut_repo(), &git_repo, command.settings())?
+    } else {
+        vec![]
+    };
+    // Hidden commits are recorded in the index only.
+    if !tx.mut_repo().has_changes() && reflog_commit_ids.is_empty() {
         return Ok(repo);
     }
     print_git_import_stats(ui, tx.repo(), &stats, false)?;
+    if !reflog_commit_ids.is_empty() {
+        writeln!(
+            ui.stderr(),
+            "Imported {} hidden commits from Git reflogs.",
+            reflog_commit_ids.len()
+        )?;
+    }
+    if !stash_commits.is_empty() {
+        writeln!(
+            ui.stderr(),
+            "Imported {} Git stash entries as commits.",
+            stash_commits.len()
+        )?;
+    }
     if colocated {
         // If git.auto-local-branch = true, local branches could be created for
         // the imported remote branches.
         let failed_branches = git::export_refs(tx.mut_repo())?;
         print_failed_git_export(ui, &failed_branches)?;
     }
-    let repo = tx.commit("import git refs");
+    let repo = tx.commit("import git refs")?;
     writeln!(
         ui.stderr(),
         "Done importing changes from the underlying Git repo."
@@ -506,6 +556,7 @@ fn cmd_git_init(
         &wc_path,
         args.colocate,
         args.git_repo.as_deref(),
+        args.import_stash,
     )?;
 
     let relative_wc_path = file_util::relative_path(cwd, &wc_path);
@@ -533,6 +584,10 @@ fn cmd_git_fetch(
     } else {
         args.remotes.clone()
     };
+    let mut git_settings = command.settings().git_settings()?;
+    if args.no_prune {
+        git_settings.fetch_prune = false;
+    }
     let mut tx = workspace_command.start_transaction();
     for remote in &remotes {
         let stats = with_remote_git_callbacks(ui, None, |cb| {
@@ -542,7 +597,7 @@ fn cmd_git_fetch(
                 remote,
                 &args.branch,
                 cb,
-                &command.settings().git_settings(),
+                &git_settings,
             )
         })
         .map_err(|err| match err {
@@ -757,6 +812,7 @@ fn do_git_clone(
     git_repo.remote(remote_name, source).unwrap();
     let mut fetch_tx = workspace_command.start_transaction();
 
+    let git_settings = command.settings().git_settings()?;
     let stats = with_remote_git_callbacks(ui, None, |cb| {
         git::fetch(
             fetch_tx.mut_repo(),
@@ -764,7 +820,7 @@ fn do_git_clone(
             remote_name,
             &[StringPattern::everything()],
             cb,
-            &command.settings().git_settings(),
+            &git_settings,
         )
     })
     .map_err(|err| match err {
@@ -790,72 +846,168 @@ fn cmd_git_push(
     let mut workspace_command = command.workspace_helper(ui)?;
     let git_repo = get_git_repo(workspace_command.repo().store())?;
 
-    let remote = if let Some(name) = &args.remote {
-        name.clone()
+    let remotes = if args.remotes.is_empty() {
+        get_default_push_remotes(ui, command.settings(), &git_repo)?
     } else {
-        get_default_push_remote(ui, command.settings(), &git_repo)?
+        args.remotes.iter().unique().cloned().collect()
     };
 
     let repo = workspace_command.repo().clone();
     let mut tx = workspace_command.start_transaction();
-    let tx_description;
+    let change_branch_names = if args.all || args.tracked || args.deleted {
+        vec![]
+    } else {
+        // Process --change branches first because matching branches can be moved.
+        update_change_branches(
+            ui,
+            &mut tx,
+            &args.change,
+            &command.settings().push_branch_prefix(),
+        )?
+    };
+    let mut remote_updates = vec![];
+    for remote in &remotes {
+        let branch_updates =
+            find_branch_updates(ui, &tx, &repo, args, remote, &change_branch_names)?;
+        if branch_updates.is_empty() {
+            if remotes.len() > 1 {
+                writeln!(ui.stderr(), "Nothing changed on {remote}.")?;
+            }
+            continue;
+        }
+        remote_updates.push((remote.as_str(), branch_updates));
+    }
+    if remote_updates.is_empty() {
+        if remotes.len() == 1 {
+            writeln!(ui.stderr(), "Nothing changed.")?;
+        }
+        return Ok(());
+    }
+    let branches_term = if args.all {
+        "all branches".to_owned()
+    } else if args.tracked {
+        "all tracked branches".to_owned()
+    } else if args.deleted {
+        "all deleted branches".to_owned()
+    } else {
+        make_branch_term(
+            &remote_updates
+                .iter()
+                .flat_map(|(_, branch_updates)| branch_updates)
+                .map(|(branch, _)| branch.as_str())
+                .unique()
+                .collect_vec(),
+        )
+    };
+    let make_tx_description = |remotes: &[&str]| match remotes {
+        [remote] => format!("push {branches_term} to git remote {remote}"),
+        remotes => format!(
+            "push {branches_term} to git remotes {}",
+            remotes.iter().join(", ")
+        ),
+    };
+
+    let mut remote_targets = vec![];
+    for (remote, branch_updates) in remote_updates {
+        let force_pushed_branches = check_branch_updates_to_push(&repo, remote, &branch_updates)?;
+        print_branch_updates(ui, remote, &branch_updates, &force_pushed_branches)?;
+        let targets = GitBranchPushTargets {
+            branch_updates,
+            force_pushed_branches,
+        };
+        remote_targets.push((remote, targets));
+    }
+
+    if args.dry_run {
+        writeln!(ui.stderr(), "Dry-run requested, not pushing.")?;
+        return Ok(());
+    }
+
+    let remote_names = remote_targets
+        .iter()
+        .map(|(remote, _)| *remote)
+        .collect_vec();
+    for (i, (remote, targets)) in remote_targets.iter().enumerate() {
+        if let Err(mut err) = push_to_remote(ui, tx.mut_repo(), &git_repo, remote, targets) {
+            if i > 0 {
+                // Record the remote-tracking branches updated by the
+                // successful pushes.
+                tx.finish(ui, make_tx_description(&remote_names[..i]))?;
+            }
+            if remotes.len() > 1 {
+                err.add_hint(format!(
+                    "Failed to push to {remote}. Branches were not pushed to: {}",
+                    remote_names[i..].iter().join(", ")
+                ));
+            }
+            return Err(err);
+        }
+        if remotes.len() > 1 {
+            writeln!(ui.stderr(), "Pushed to {remote}.")?;
+        }
+        print_forge_urls(ui, command.settings(), remote, &targets.branch_updates)?;
+    }
+    tx.finish(ui, make_tx_description(&remote_names))?;
+    Ok(())
+}
+
+/// Collects the branches to push to the `remote`, and how they should be
+/// updated.
+fn find_branch_updates(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    repo: &ReadonlyRepo,
+    args: &GitPushArgs,
+    remote: &str,
+    change_branch_names: &[String],
+) -> Result<Vec<(String, BranchPushUpdate)>, CommandError> {
     let mut branch_updates = vec![];
     if args.all {
-        for (branch_name, targets) in repo.view().local_remote_branches(&remote) {
-            match classify_branch_update(branch_name, &remote, targets) {
+        for (branch_name, targets) in repo.view().local_remote_branches(remote) {
+            match classify_branch_update(branch_name, remote, targets) {
                 Ok(Some(update)) => branch_updates.push((branch_name.to_owned(), update)),
                 Ok(None) => {}
                 Err(reason) => reason.print(ui)?,
             }
         }
-        tx_description = format!("push all branches to git remote {remote}");
     } else if args.tracked {
-        for (branch_name, targets) in repo.view().local_remote_branches(&remote) {
+        for (branch_name, targets) in repo.view().local_remote_branches(remote) {
             if !targets.remote_ref.is_tracking() {
                 continue;
             }
-            match classify_branch_update(branch_name, &remote, targets) {
+            match classify_branch_update(branch_name, remote, targets) {
                 Ok(Some(update)) => branch_updates.push((branch_name.to_owned(), update)),
                 Ok(None) => {}
                 Err(reason) => reason.print(ui)?,
             }
         }
-        tx_description = format!("push all tracked branches to git remote {remote}");
     } else if args.deleted {
-        for (branch_name, targets) in repo.view().local_remote_branches(&remote) {
+        for (branch_name, targets) in repo.view().local_remote_branches(remote) {
             if targets.local_target.is_present() {
                 continue;
             }
-            match classify_branch_update(branch_name, &remote, targets) {
+            match classify_branch_update(branch_name, remote, targets) {
                 Ok(Some(update)) => branch_updates.push((branch_name.to_owned(), update)),
                 Ok(None) => {}
                 Err(reason) => reason.print(ui)?,
             }
         }
-        tx_description = format!("push all deleted branches to git remote {remote}");
     } else {
         let mut seen_branches: HashSet<&str> = HashSet::new();
 
-        // Process --change branches first because matching branches can be moved.
-        let change_branch_names = update_change_branches(
-            ui,
-            &mut tx,
-            &args.change,
-            &command.settings().push_branch_prefix(),
-        )?;
         let change_branches = change_branch_names.iter().map(|branch_name| {
             let targets = LocalAndRemoteRef {
                 local_target: tx.repo().view().get_local_branch(branch_name),
-                remote_ref: tx.repo().view().get_remote_branch(branch_name, &remote),
+                remote_ref: tx.repo().view().get_remote_branch(branch_name, remote),
             };
             (branch_name.as_ref(), targets)
         });
-        let branches_by_name = find_branches_to_push(repo.view(), &args.branch, &remote)?;
+        let branches_by_name = find_branches_to_push(repo.view(), &args.branch, remote)?;
         for (branch_name, targets) in change_branches.chain(branches_by_name.iter().copied()) {
             if !seen_branches.insert(branch_name) {
                 continue;
             }
-            match classify_branch_update(branch_name, &remote, targets) {
+            match classify_branch_update(branch_name, remote, targets) {
                 Ok(Some(update)) => branch_updates.push((branch_name.to_owned(), update)),
                 Ok(None) => writeln!(
                     ui.stderr(),
@@ -870,7 +1022,7 @@ fn cmd_git_push(
         let branches_targeted = find_branches_targeted_by_revisions(
             ui,
             tx.base_workspace_helper(),
-            &remote,
+            remote,
             &args.revisions,
             use_default_revset,
         )?;
@@ -878,32 +1030,26 @@ fn cmd_git_push(
             if !seen_branches.insert(branch_name) {
                 continue;
             }
-            match classify_branch_update(branch_name, &remote, targets) {
+            match classify_branch_update(branch_name, remote, targets) {
                 Ok(Some(update)) => branch_updates.push((branch_name.to_owned(), update)),
                 Ok(None) => {}
                 Err(reason) => reason.print(ui)?,
             }
         }
-
-        tx_description = format!(
-            "push {} to git remote {}",
-            make_branch_term(
-                &branch_updates
-                    .iter()
-                    .map(|(branch, _)| branch.as_str())
-                    .collect_vec()
-            ),
-            &remote
-        );
-    }
-    if branch_updates.is_empty() {
-        writeln!(ui.stderr(), "Nothing changed.")?;
-        return Ok(());
     }
+    Ok(branch_updates)
+}
 
+/// Checks that the commits to push to the `remote` are complete, and returns
+/// the branches which need to be force-pushed.
+fn check_branch_updates_to_push(
+    repo: &ReadonlyRepo,
+    remote: &str,
+    branch_updates: &[(String, BranchPushUpdate)],
+) -> Result<HashSet<String>, CommandError> {
     let mut new_heads = vec![];
     let mut force_pushed_branches = hashset! {};
-    for (branch_name, update) in &branch_updates {
+    for (branch_name, update) in branch_updates {
         if let Some(new_target) = &update.new_target {
             new_heads.push(new_target.clone());
             let force = match &update.old_target {
@@ -920,14 +1066,14 @@ fn cmd_git_push(
     // already been pushed.
     let mut old_heads = repo
         .view()
-        .remote_branches(&remote)
+        .remote_branches(remote)
         .flat_map(|(_, old_head)| old_head.target.added_ids())
         .cloned()
         .collect_vec();
     if old_heads.is_empty() {
         old_heads.push(repo.store().root_commit_id().clone());
     }
-    for commit in revset::walk_revs(repo.as_ref(), &new_heads, &old_heads)?
+    for commit in revset::walk_revs(repo, &new_heads, &old_heads)?
         .iter()
         .commits(repo.store())
     {
@@ -958,84 +1104,179 @@ fn cmd_git_push(
             )));
         }
     }
+    Ok(force_pushed_branches)
+}
 
-    writeln!(ui.stderr(), "Branch changes to push to {}:", &remote)?;
-    for (branch_name, update) in &branch_updates {
-        match (&update.old_target, &update.new_target) {
-            (Some(old_target), Some(new_target)) => {
-                if force_pushed_branches.contains(branch_name) {
-                    writeln!(
-                        ui.stderr(),
-                        "  Force branch {branch_name} from {} to {}",
-                        short_commit_hash(old_target),
-                        short_commit_hash(new_target)
-                    )?;
-                } else {
-                    writeln!(
-                        ui.stderr(),
-                        "  Move branch {branch_name} from {} to {}",
-                        short_commit_hash(old_target),
-                        short_commit_hash(new_target)
-                    )?;
-                }
-            }
-            (Some(old_target), None) => {
-                writeln!(
-                    ui.stderr(),
-                    "  Delete branch {branch_name} from {}",
-                    short_commit_hash(old_target)
-                )?;
-            }
-            (None, Some(new_target)) => {
-                writeln!(
-                    ui.stderr(),
-                    "  Add branch {branch_name} to {}",
-                    short_commit_hash(new_target)
-                )?;
-            }
-            (None, None) => {
-                panic!("Not pushing any change to branch {branch_name}");
-            }
+fn print_branch_updates(
+    ui: &Ui,
+    remote: &str,
+    branch_updates: &[(String, BranchPushUpdate)],
+    force_pushed_branches: &HashSet<String>,
+) -> io::Result<()> {
+    let format_target = |target: &Option<CommitId>| {
+        target
+            .as_ref()
+            .map_or_else(|| "(none)".to_owned(), short_commit_hash)
+    };
+    let rows = branch_updates
+        .iter()
+        .map(|(branch_name, update)| {
+            let action = match (&update.old_target, &update.new_target) {
+                (Some(_), Some(_)) if force_pushed_branches.contains(branch_name) => "force update",
+                (Some(_), Some(_)) => "update",
+                (Some(_), None) => "delete",
+                (None, Some(_)) => "create",
+                (None, None) => panic!("Not pushing any change to branch {branch_name}"),
+            };
+            let old = format_target(&update.old_target);
+            let new = format_target(&update.new_target);
+            (action, branch_name.as_str(), old, new)
+        })
+        .collect_vec();
+    let column_width = |width: fn(&(&str, &str, String, String)) -> usize| {
+        rows.iter().map(width).max().unwrap_or(0)
+    };
+    let action_width = column_width(|(action, ..)| action.len());
+    let name_width = column_width(|(_, name, ..)| name.width());
+    let old_width = column_width(|(_, _, old, _)| old.len());
+
+    let mut formatter = ui.stderr_formatter();
+    writeln!(formatter, "Branch changes to push to {remote}:")?;
+    for (action, branch_name, old, new) in &rows {
+        let name_pad = name_width - branch_name.width();
+        write!(formatter, "  {action:<action_width$}  ")?;
+        write!(formatter.labeled("branch"), "{branch_name}")?;
+        writeln!(formatter, "{:name_pad$}  {old:<old_width$} -> {new}", "")?;
+    }
+    Ok(())
+}
+
+/// Prints which branches were pushed to the `remote` and which were rejected,
+/// with the reasons reported by the remote.
+fn print_push_results(
+    ui: &Ui,
+    remote: &str,
+    targets: &GitBranchPushTargets,
+    rejected_updates: &[RejectedRefUpdate],
+) -> io::Result<()> {
+    let name_width = targets
+        .branch_updates
+        .iter()
+        .map(|(branch_name, _)| branch_name.width())
+        .max()
+        .unwrap_or(0);
+    let mut formatter = ui.stderr_formatter();
+    writeln!(formatter, "Push results for {remote}:")?;
+    for (branch_name, _) in &targets.branch_updates {
+        let qualified_name = format!("refs/heads/{branch_name}");
+        let rejected_update = rejected_updates
+            .iter()
+            .find(|update| update.qualified_name == qualified_name);
+        let name_pad = name_width - branch_name.width();
+        write!(formatter, "  ")?;
+        write!(formatter.labeled("branch"), "{branch_name}")?;
+        write!(formatter, "{:name_pad$}  ", "")?;
+        match rejected_update {
+            None => writeln!(formatter, "pushed")?,
+            Some(RejectedRefUpdate {
+                reason: Some(reason),
+                ..
+            }) => writeln!(formatter, "rejected: {reason}")?,
+            Some(_) => writeln!(formatter, "rejected")?,
         }
     }
+    Ok(())
+}
 
-    if args.dry_run {
-        writeln!(ui.stderr(), "Dry-run requested, not pushing.")?;
+/// Prints links to the forge (e.g. pull-request creation pages) configured
+/// for the `remote` by `git.forge-urls`.
+fn print_forge_urls(
+    ui: &Ui,
+    settings: &UserSettings,
+    remote: &str,
+    branch_updates: &[(String, BranchPushUpdate)],
+) -> Result<(), CommandError> {
+    let Some(url_template) = settings
+        .config()
+        .get::<HashMap<String, String>>("git.forge-urls")
+        .optional()?
+        .and_then(|mut urls| urls.remove(remote))
+    else {
         return Ok(());
+    };
+    for (branch_name, update) in branch_updates {
+        if update.new_target.is_none() {
+            continue;
+        }
+        let url = messages::substitute_placeholders(
+            &url_template,
+            &[
+                ("branch", percent_encode(branch_name).as_str()),
+                ("remote", percent_encode(remote).as_str()),
+            ],
+        );
+        writeln!(ui.hint_default(), "{branch_name}@{remote}: {url}")?;
     }
+    Ok(())
+}
 
-    let targets = GitBranchPushTargets {
-        branch_updates,
-        force_pushed_branches,
-    };
+/// Percent-encodes the `text` so it can be used as a URL path segment or
+/// query value. Only the unreserved characters of RFC 3986 are kept as is.
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                char::from(b).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
+fn push_to_remote(
+    ui: &Ui,
+    mut_repo: &mut MutableRepo,
+    git_repo: &git2::Repository,
+    remote: &str,
+    targets: &GitBranchPushTargets,
+) -> Result<(), CommandError> {
     let mut writer = GitSidebandProgressMessageWriter::new(ui);
     let mut sideband_progress_callback = |progress_message: &[u8]| {
         _ = writer.write(ui, progress_message);
     };
-    with_remote_git_callbacks(ui, Some(&mut sideband_progress_callback), |cb| {
-        git::push_branches(tx.mut_repo(), &git_repo, &remote, &targets, cb)
-    })
-    .map_err(|err| match err {
+    let result = with_remote_git_callbacks(ui, Some(&mut sideband_progress_callback), |cb| {
+        git::push_branches(mut_repo, git_repo, remote, targets, cb)
+    });
+    if let Err(GitPushError::RefUpdateRejected(rejected_updates)) = &result {
+        print_push_results(ui, remote, targets, rejected_updates)?;
+    }
+    result.map_err(|err| match err {
         GitPushError::InternalGitError(err) => map_git_error(err),
-        GitPushError::NotFastForward => user_error_with_hint(
+        GitPushError::NotFastForward => user_error(
             "The push conflicts with changes made on the remote (it is not fast-forwardable).",
+        )
+        .hinted_with_id(
+            "push-not-fast-forward",
             "Try fetching from the remote, then make the branch point to where you want it to be, \
              and push again.",
         ),
         _ => user_error(err),
     })?;
     writer.flush(ui)?;
-    tx.finish(ui, tx_description)?;
     Ok(())
 }
 
-fn get_default_push_remote(
+fn get_default_push_remotes(
     ui: &Ui,
     settings: &UserSettings,
     git_repo: &git2::Repository,
-) -> Result<String, CommandError> {
-    if let Some(remote) = settings.config().get_string("git.push").optional()? {
-        Ok(remote)
+) -> Result<Vec<String>, CommandError> {
+    const KEY: &str = "git.push";
+    if let Ok(remotes) = settings.config().get(KEY) {
+        Ok(remotes)
+    } else if let Some(remote) = settings.config().get_string(KEY).optional()? {
+        Ok(vec![remote])
     } else if let Some(remote) = get_single_remote(git_repo)? {
         // similar to get_default_fetch_remotes
         if remote != DEFAULT_REMOTE {
@@ -1044,9 +1285,9 @@ fn get_default_push_remote(
                 "Pushing to the only existing remote: {remote}"
             )?;
         }
-        Ok(remote)
+        Ok(vec![remote])
     } else {
-        Ok(DEFAULT_REMOTE.to_owned())
+        Ok(vec![DEFAULT_REMOTE.to_owned()])
     }
 }
 
@@ -1240,7 +1481,7 @@ fn cmd_git_import(
     // In non-colocated repo, HEAD@git will never be moved internally by jj.
     // That's why cmd_git_export() doesn't export the HEAD ref.
     git::import_head(tx.mut_repo())?;
-    let stats = git::import_refs(tx.mut_repo(), &command.settings().git_settings())?;
+    let stats = git::import_refs(tx.mut_repo(), &command.settings().git_settings()?)?;
     print_git_import_stats(ui, tx.repo(), &stats, true)?;
     tx.finish(ui, "import git refs")?;
     Ok(())
@@ -59,8 +59,16 @@ pub(crate) fn cmd_init(
     // Preserve existing behaviour where `jj init` is not able to create
     // a colocated repo.
     let colocate = false;
+    let import_stash = false;
     if args.git || args.git_repo.is_some() {
-        git::git_init(ui, command, &wc_path, colocate, args.git_repo.as_deref())?;
+        git::git_init(
+            ui,
+            command,
+            &wc_path,
+            colocate,
+            args.git_repo.as_deref(),
+            import_stash,
+        )?;
         writeln!(
             ui.warning_default(),
             "`--git` and `--git-repo` are deprecated.
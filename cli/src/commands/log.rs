@@ -12,8 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io;
+
 use itertools::Itertools;
-use jj_lib::backend::CommitId;
+use jj_lib::backend::{BackendError, CommitId};
 use jj_lib::repo::Repo;
 use jj_lib::revset::{self, RevsetExpression, RevsetFilterPredicate, RevsetIteratorExt};
 use jj_lib::revset_graph::{
@@ -25,7 +27,9 @@ use crate::cli_util::{format_template, CommandHelper, LogContentFormat, Revision
 use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::diff_util::{self, DiffFormatArgs};
+use crate::formatter::Formatter;
 use crate::graphlog::{get_graphlog, Edge};
+use crate::templater::{Template as _, TemplatePropertyError};
 use crate::ui::Ui;
 
 /// Show revision history
@@ -107,6 +111,7 @@ pub(crate) fn cmd_log(
         .config()
         .get_bool("ui.log-synthetic-elided-nodes")?;
     let with_content_format = LogContentFormat::new(ui, command.settings())?;
+    let strict = command.global_args().template_strict;
 
     let template;
     let node_template;
@@ -172,28 +177,41 @@ pub(crate) fn cmd_log(
                 }
                 let mut buffer = vec![];
                 let key = (commit_id, false);
-                let commit = store.get_commit(&key.0)?;
-                with_content_format.write_graph_text(
-                    ui.new_formatter(&mut buffer).as_mut(),
-                    |formatter| template.format(&commit, formatter),
-                    || graph.width(&key, &graphlog_edges),
-                )?;
+                let commit = match store.get_commit(&key.0) {
+                    Ok(commit) => Some(commit),
+                    Err(err) if !strict => {
+                        with_content_format.write_graph_text(
+                            ui.new_formatter(&mut buffer).as_mut(),
+                            |formatter| write_commit_error(formatter, err),
+                            || graph.width(&key, &graphlog_edges),
+                        )?;
+                        None
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+                if let Some(commit) = &commit {
+                    with_content_format.write_graph_text(
+                        ui.new_formatter(&mut buffer).as_mut(),
+                        |formatter| template.format(commit, formatter),
+                        || graph.width(&key, &graphlog_edges),
+                    )?;
+                }
                 if !buffer.ends_with(b"\n") {
                     buffer.push(b'\n');
                 }
-                if !diff_formats.is_empty() {
+                if let Some(commit) = commit.as_ref().filter(|_| !diff_formats.is_empty()) {
                     let mut formatter = ui.new_formatter(&mut buffer);
                     diff_util::show_patch(
                         ui,
                         formatter.as_mut(),
                         &workspace_command,
-                        &commit,
+                        commit,
                         matcher.as_ref(),
                         &diff_formats,
                     )?;
                 }
 
-                let node_symbol = format_template(ui, &Some(commit), &node_template);
+                let node_symbol = format_template(ui, &commit, &node_template);
                 graph.add_node(
                     &key,
                     &graphlog_edges,
@@ -226,7 +244,15 @@ pub(crate) fn cmd_log(
                 Box::new(revset.iter())
             };
             for commit_or_error in iter.commits(store).take(args.limit.unwrap_or(usize::MAX)) {
-                let commit = commit_or_error?;
+                let commit = match commit_or_error {
+                    Ok(commit) => commit,
+                    Err(err) if !strict => {
+                        with_content_format
+                            .write(formatter, |formatter| write_commit_error(formatter, err))?;
+                        continue;
+                    }
+                    Err(err) => return Err(err.into()),
+                };
                 with_content_format
                     .write(formatter, |formatter| template.format(&commit, formatter))?;
                 if !diff_formats.is_empty() {
@@ -267,3 +293,9 @@ pub(crate) fn cmd_log(
 
     Ok(())
 }
+
+/// Prints the error in place of the commit which couldn't be loaded.
+fn write_commit_error(formatter: &mut dyn Formatter, err: BackendError) -> io::Result<()> {
+    TemplatePropertyError::from(err).format(formatter)?;
+    writeln!(formatter)
+}
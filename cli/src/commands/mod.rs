@@ -18,6 +18,7 @@ mod backout;
 mod bench;
 mod branch;
 mod cat;
+mod changes;
 mod checkout;
 mod chmod;
 mod commit;
@@ -38,7 +39,7 @@ mod r#move;
 mod new;
 mod next;
 mod obslog;
-mod operation;
+pub(crate) mod operation;
 mod prev;
 mod rebase;
 mod resolve;
@@ -46,6 +47,7 @@ mod restore;
 mod root;
 mod run;
 mod show;
+mod snapshot;
 mod sparse;
 mod split;
 mod squash;
@@ -77,6 +79,7 @@ enum Command {
     Branch(branch::BranchCommand),
     #[command(alias = "print")]
     Cat(cat::CatArgs),
+    Changes(changes::ChangesArgs),
     #[command(hide = true)]
     Checkout(checkout::CheckoutArgs),
     Chmod(chmod::ChmodArgs),
@@ -128,6 +131,7 @@ enum Command {
     // TODO: Flesh out.
     Run(run::RunArgs),
     Show(show::ShowArgs),
+    Snapshot(snapshot::SnapshotArgs),
     #[command(subcommand)]
     Sparse(sparse::SparseArgs),
     Split(split::SplitArgs),
@@ -168,8 +172,10 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::Untrack(sub_args) => untrack::cmd_untrack(ui, command_helper, sub_args),
         Command::Files(sub_args) => files::cmd_files(ui, command_helper, sub_args),
         Command::Cat(sub_args) => cat::cmd_cat(ui, command_helper, sub_args),
+        Command::Changes(sub_args) => changes::cmd_changes(ui, command_helper, sub_args),
         Command::Diff(sub_args) => diff::cmd_diff(ui, command_helper, sub_args),
         Command::Show(sub_args) => show::cmd_show(ui, command_helper, sub_args),
+        Command::Snapshot(sub_args) => snapshot::cmd_snapshot(ui, command_helper, sub_args),
         Command::Status(sub_args) => status::cmd_status(ui, command_helper, sub_args),
         Command::Log(sub_args) => log::cmd_log(ui, command_helper, sub_args),
         Command::Interdiff(sub_args) => interdiff::cmd_interdiff(ui, command_helper, sub_args),
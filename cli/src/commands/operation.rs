@@ -12,16 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::io::Write as _;
 use std::slice;
 
 use clap::Subcommand;
 use itertools::Itertools as _;
 use jj_lib::object_id::ObjectId;
-use jj_lib::op_store::OperationId;
+use jj_lib::op_store::{OpStoreError, OperationId};
 use jj_lib::op_walk;
 use jj_lib::operation::Operation;
 use jj_lib::repo::Repo;
+use jj_lib::workspace::Workspace;
 
 use crate::cli_util::{format_template, short_operation_hash, CommandHelper, LogContentFormat};
 use crate::command_error::{user_error, user_error_with_hint, CommandError};
@@ -37,6 +39,7 @@ use crate::ui::Ui;
 pub enum OperationCommand {
     Abandon(OperationAbandonArgs),
     Log(OperationLogArgs),
+    Reattach(OperationReattachArgs),
     Undo(OperationUndoArgs),
     Restore(OperationRestoreArgs),
 }
@@ -50,6 +53,18 @@ pub struct OperationLogArgs {
     /// Don't show the graph, show a flat list of operations
     #[arg(long)]
     no_graph: bool,
+    /// Hide operations that only snapshotted the working copy
+    ///
+    /// The graph edges skip over the hidden operations.
+    #[arg(long)]
+    no_snapshot: bool,
+    /// Show operations that are no longer reachable from the current operation
+    ///
+    /// These are operations abandoned by `jj op abandon`, or left behind when
+    /// the operation heads were lost. They can be restored by `jj op
+    /// reattach` until they are garbage collected by `jj util gc`.
+    #[arg(long, conflicts_with = "no_snapshot")]
+    deleted: bool,
     /// Render each operation using the given template
     ///
     /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
@@ -113,6 +128,19 @@ pub struct OperationAbandonArgs {
     operation: String,
 }
 
+/// Make unreachable operations visible again
+///
+/// Use `jj op log --deleted` to find operations that were abandoned or whose
+/// operation heads were lost. The reattached operations are merged with the
+/// current operation by the next command, as if they had been run
+/// concurrently.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationReattachArgs {
+    /// The operations to reattach
+    #[arg(required = true)]
+    operations: Vec<String>,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
 enum UndoWhatToRestore {
     /// The jj repo state and local branches
@@ -184,18 +212,54 @@ fn cmd_op_log(
         )?;
     }
 
+    let deleted_ops = if args.deleted {
+        Some(op_walk::find_unreachable_operations(
+            repo_loader.op_store(),
+            &head_ops,
+        )?)
+    } else {
+        None
+    };
+    let deleted_op_ids: HashSet<OperationId> = deleted_ops
+        .iter()
+        .flatten()
+        .map(|op| op.id().clone())
+        .collect();
+
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
-    let iter = op_walk::walk_ancestors(&head_ops).take(args.limit.unwrap_or(usize::MAX));
+    let iter: Box<dyn Iterator<Item = Result<Operation, OpStoreError>>> = match deleted_ops {
+        Some(ops) => Box::new(ops.into_iter().map(Ok)),
+        None => Box::new(op_walk::walk_ancestors(&head_ops)),
+    };
+    let iter = iter
+        .filter_ok(|op| !(args.no_snapshot && op.metadata().is_snapshot))
+        .take(args.limit.unwrap_or(usize::MAX));
     if !args.no_graph {
         let mut graph = get_graphlog(command.settings(), formatter.raw());
         for op in iter {
             let op = op?;
-            let mut edges = vec![];
-            for id in op.parent_ids() {
-                edges.push(Edge::Direct(id.clone()));
-            }
+            let edges = if args.no_snapshot {
+                non_snapshot_parent_edges(&op)?
+            } else if args.deleted {
+                // Parents which are still reachable aren't shown.
+                op.parent_ids()
+                    .iter()
+                    .map(|id| {
+                        if deleted_op_ids.contains(id) {
+                            Edge::Direct(id.clone())
+                        } else {
+                            Edge::Missing
+                        }
+                    })
+                    .collect()
+            } else {
+                op.parent_ids()
+                    .iter()
+                    .map(|id| Edge::Direct(id.clone()))
+                    .collect()
+            };
             let mut buffer = vec![];
             with_content_format.write_graph_text(
                 ui.new_formatter(&mut buffer).as_mut(),
@@ -227,6 +291,29 @@ fn cmd_op_log(
     Ok(())
 }
 
+/// Returns edges to the nearest ancestors which aren't snapshot operations.
+fn non_snapshot_parent_edges(op: &Operation) -> Result<Vec<Edge<OperationId>>, OpStoreError> {
+    let mut edges = vec![];
+    let mut visited = HashSet::new();
+    let mut to_visit: Vec<(Operation, bool)> =
+        op.parents().map_ok(|op| (op, true)).try_collect()?;
+    to_visit.reverse();
+    while let Some((op, direct)) = to_visit.pop() {
+        if !visited.insert(op.id().clone()) {
+            continue;
+        }
+        if op.metadata().is_snapshot {
+            let parents: Vec<_> = op.parents().try_collect()?;
+            to_visit.extend(parents.into_iter().rev().map(|op| (op, false)));
+        } else if direct {
+            edges.push(Edge::Direct(op.id().clone()));
+        } else {
+            edges.push(Edge::Indirect(op.id().clone()));
+        }
+    }
+    Ok(edges)
+}
+
 /// Restore only the portions of the view specified by the `what` argument
 fn view_with_desired_portions_restored(
     view_being_restored: &jj_lib::op_store::View,
@@ -251,9 +338,14 @@ fn view_with_desired_portions_restored(
         git_refs: current_view.git_refs.clone(),
         git_head: current_view.git_head.clone(),
         wc_commit_ids: repo_source.wc_commit_ids.clone(),
+        pinned_branches: repo_source.pinned_branches.clone(),
     }
 }
 
+/// Description of the operation created by `jj op undo`, which is followed by
+/// the id of the undone operation.
+pub(crate) const UNDO_DESCRIPTION_PREFIX: &str = "undo operation ";
+
 pub fn cmd_op_undo(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -280,7 +372,10 @@ pub fn cmd_op_undo(
         &args.what,
     );
     tx.mut_repo().set_view(new_view);
-    tx.finish(ui, format!("undo operation {}", bad_op.id().hex()))?;
+    tx.finish(
+        ui,
+        format!("{UNDO_DESCRIPTION_PREFIX}{}", bad_op.id().hex()),
+    )?;
 
     Ok(())
 }
@@ -373,7 +468,66 @@ fn cmd_op_abandon(
         stats.unreachable_count,
         stats.rewritten_count,
     )?;
-    repo_loader
+    reset_head_operation(ui, command, &mut workspace, &current_head_op, new_head_id)
+}
+
+fn cmd_op_reattach(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationReattachArgs,
+) -> Result<(), CommandError> {
+    // Don't load the repo so that this command can be used to recover from
+    // lost operation heads.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op_heads_store = repo_loader.op_heads_store();
+    let ops: Vec<Operation> = args
+        .operations
+        .iter()
+        .map(|op_str| op_walk::resolve_op_for_load(repo_loader, op_str))
+        .try_collect()?;
+    let current_head_ops =
+        op_walk::get_current_head_ops(repo_loader.op_store(), op_heads_store.as_ref())?;
+    let reachable_op_ids: HashSet<OperationId> = op_walk::walk_ancestors(&current_head_ops)
+        .map_ok(|op| op.id().clone())
+        .try_collect()?;
+
+    let mut num_reattached = 0;
+    for op in ops.iter().unique_by(|op| op.id()) {
+        if reachable_op_ids.contains(op.id()) {
+            writeln!(
+                ui.warning_default(),
+                "Operation {} is already reachable from the current operation",
+                short_operation_hash(op.id()),
+            )?;
+            continue;
+        }
+        op_heads_store.update_op_heads(&[], op.id());
+        num_reattached += 1;
+    }
+    if num_reattached == 0 {
+        writeln!(ui.stderr(), "Nothing changed.")?;
+    } else {
+        writeln!(
+            ui.stderr(),
+            "Reattached {num_reattached} operations. They will be merged with the current \
+             operation by the next command.",
+        )?;
+    }
+    Ok(())
+}
+
+/// Replaces the `current_head_op` with the rewritten `new_head_id`, and remaps
+/// the operation id of the current workspace.
+pub(crate) fn reset_head_operation(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    workspace: &mut Workspace,
+    current_head_op: &Operation,
+    new_head_id: OperationId,
+) -> Result<(), CommandError> {
+    workspace
+        .repo_loader()
         .op_heads_store()
         .update_op_heads(slice::from_ref(current_head_op.id()), &new_head_id);
     // Remap the operation id of the current workspace. If there were any
@@ -403,6 +557,7 @@ pub fn cmd_operation(
     match subcommand {
         OperationCommand::Abandon(args) => cmd_op_abandon(ui, command, args),
         OperationCommand::Log(args) => cmd_op_log(ui, command, args),
+        OperationCommand::Reattach(args) => cmd_op_reattach(ui, command, args),
         OperationCommand::Restore(args) => cmd_op_restore(ui, command, args),
         OperationCommand::Undo(args) => cmd_op_undo(ui, command, args),
     }
@@ -24,15 +24,18 @@ use jj_lib::commit::Commit;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::{ReadonlyRepo, Repo};
 use jj_lib::revset::{RevsetExpression, RevsetIteratorExt};
-use jj_lib::rewrite::{rebase_commit, rebase_commit_with_options, EmptyBehaviour, RebaseOptions};
-use jj_lib::settings::UserSettings;
+use jj_lib::rewrite::{
+    rebase_commit, rebase_commit_with_options, EmptyBehaviour, RebaseOptions, RebasedCommit,
+};
+use jj_lib::settings::{ConfigResultExt as _, UserSettings};
 use tracing::instrument;
 
 use crate::cli_util::{
-    self, resolve_multiple_nonempty_revsets_default_single, short_commit_hash, CommandHelper,
-    RevisionArg, WorkspaceCommandHelper,
+    self, resolve_multiple_nonempty_revsets_default_single, short_change_hash, short_commit_hash,
+    CommandHelper, RevisionArg, WorkspaceCommandHelper, WorkspaceCommandTransaction,
 };
-use crate::command_error::{user_error, CommandError};
+use crate::command_error::{user_error, user_error_with_hint, CommandError};
+use crate::commands::workspace::resolve_other_workspace_wc_commit;
 use crate::ui::Ui;
 
 /// Move revisions to different parent(s)
@@ -155,8 +158,14 @@ pub(crate) struct RebaseArgs {
     revision: Option<RevisionArg>,
     /// The revision(s) to rebase onto (can be repeated to create a merge
     /// commit)
-    #[arg(long, short, required = true)]
+    #[arg(long, short, required_unless_present = "onto_workspace")]
     destination: Vec<RevisionArg>,
+    /// Rebase onto the working-copy commit of another workspace
+    ///
+    /// This is like `-d <NAME>@`, but fails if the workspace doesn't exist or
+    /// is the current workspace.
+    #[arg(long, value_name = "NAME", conflicts_with = "destination")]
+    onto_workspace: Option<String>,
 
     /// If true, when rebasing would produce an empty commit, the commit is
     /// abandoned. It will not be abandoned if it was already empty before the
@@ -165,11 +174,45 @@ pub(crate) struct RebaseArgs {
     #[arg(long, conflicts_with = "revision")]
     skip_empty: bool,
 
+    /// What to do with commits that become empty when rebased [default:
+    /// `rebase.empty` config, or `keep`]
+    ///
+    /// Merge commits with multiple non-empty parents are always kept.
+    #[arg(long, value_enum, conflicts_with_all = ["revision", "skip_empty"])]
+    empty: Option<EmptyMode>,
+
+    /// Leave commits in place if rebasing them would create conflicts
+    ///
+    /// If a source commit or any of its descendants would become conflicted,
+    /// the source and its descendants are left in place, and the other sources
+    /// are rebased. The commits which would become conflicted are reported.
+    #[arg(long, conflicts_with = "revision")]
+    stop_on_conflict: bool,
+
+    /// Only report which commits would become conflicted or empty
+    ///
+    /// The rebase is computed in memory, but the resulting commits are not
+    /// recorded in the operation log and the working copy is left untouched.
+    #[arg(long)]
+    dry_run: bool,
+
     /// Deprecated. Please prefix the revset with `all:` instead.
     #[arg(long, short = 'L', hide = true)]
     allow_large_revsets: bool,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum EmptyMode {
+    /// Keep the commits
+    Keep,
+    /// Abandon the commits, unless they were already empty before the rebase
+    Drop,
+    /// List the commits that became empty and ask whether to abandon them.
+    /// Fails if no terminal is available to ask.
+    Ask,
+}
+
 #[instrument(skip_all)]
 pub(crate) fn cmd_rebase(
     ui: &mut Ui,
@@ -183,17 +226,41 @@ Please use `jj rebase -d 'all:x|y'` instead of `jj rebase --allow-large-revsets
         ));
     }
 
+    let empty_mode = if args.revision.is_some() {
+        EmptyMode::Keep
+    } else if args.skip_empty {
+        EmptyMode::Drop
+    } else if let Some(mode) = args.empty {
+        mode
+    } else {
+        command
+            .settings()
+            .config()
+            .get("rebase.empty")
+            .optional()?
+            .unwrap_or(EmptyMode::Keep)
+    };
     let rebase_options = RebaseOptions {
-        empty: match args.skip_empty {
-            true => EmptyBehaviour::AbandonNewlyEmpty,
-            false => EmptyBehaviour::Keep,
+        empty: match empty_mode {
+            EmptyMode::Drop => EmptyBehaviour::AbandonNewlyEmpty,
+            EmptyMode::Keep | EmptyMode::Ask => EmptyBehaviour::Keep,
         },
         simplify_ancestor_merge: false,
+        stop_on_conflict: args.stop_on_conflict,
+    };
+    let command_options = RebaseCommandOptions {
+        rebase_options,
+        ask_empty: empty_mode == EmptyMode::Ask,
+        dry_run: args.dry_run,
     };
     let mut workspace_command = command.workspace_helper(ui)?;
-    let new_parents = cli_util::resolve_all_revs(&workspace_command, &args.destination)?
-        .into_iter()
-        .collect_vec();
+    let new_parents = if let Some(name) = &args.onto_workspace {
+        vec![resolve_other_workspace_wc_commit(&workspace_command, name)?]
+    } else {
+        cli_util::resolve_all_revs(&workspace_command, &args.destination)?
+            .into_iter()
+            .collect_vec()
+    };
     if let Some(rev_str) = &args.revision {
         assert_eq!(
             // In principle, `-r --skip-empty` could mean to abandon the `-r`
@@ -206,7 +273,7 @@ Please use `jj rebase -d 'all:x|y'` instead of `jj rebase --allow-large-revsets
             // emptied. But it would also make sense for the descendants of the
             // `--before` commit to be abandoned if emptied. A commit can easily
             // be in both categories.
-            rebase_options.empty,
+            command_options.rebase_options.empty,
             EmptyBehaviour::Keep,
             "clap should forbid `-r --skip-empty`"
         );
@@ -216,6 +283,7 @@ Please use `jj rebase -d 'all:x|y'` instead of `jj rebase --allow-large-revsets
             &mut workspace_command,
             &new_parents,
             rev_str,
+            args.dry_run,
         )?;
     } else if !args.source.is_empty() {
         let source_commits =
@@ -226,7 +294,7 @@ Please use `jj rebase -d 'all:x|y'` instead of `jj rebase --allow-large-revsets
             &mut workspace_command,
             &new_parents,
             &source_commits,
-            rebase_options,
+            &command_options,
         )?;
     } else {
         let branch_commits = if args.branch.is_empty() {
@@ -240,19 +308,26 @@ Please use `jj rebase -d 'all:x|y'` instead of `jj rebase --allow-large-revsets
             &mut workspace_command,
             &new_parents,
             &branch_commits,
-            rebase_options,
+            &command_options,
         )?;
     }
     Ok(())
 }
 
+/// Options of `jj rebase -s`/`-b` in addition to the library `RebaseOptions`.
+struct RebaseCommandOptions {
+    rebase_options: RebaseOptions,
+    ask_empty: bool,
+    dry_run: bool,
+}
+
 fn rebase_branch(
     ui: &mut Ui,
     settings: &UserSettings,
     workspace_command: &mut WorkspaceCommandHelper,
     new_parents: &[Commit],
     branch_commits: &IndexSet<Commit>,
-    rebase_options: RebaseOptions,
+    command_options: &RebaseCommandOptions,
 ) -> Result<(), CommandError> {
     let parent_ids = new_parents
         .iter()
@@ -277,7 +352,7 @@ fn rebase_branch(
         workspace_command,
         new_parents,
         &root_commits,
-        rebase_options,
+        command_options,
     )
 }
 
@@ -287,28 +362,73 @@ fn rebase_descendants(
     workspace_command: &mut WorkspaceCommandHelper,
     new_parents: &[Commit],
     old_commits: &IndexSet<Commit>,
-    rebase_options: RebaseOptions,
+    command_options: &RebaseCommandOptions,
 ) -> Result<(), CommandError> {
+    let RebaseCommandOptions {
+        rebase_options,
+        ask_empty,
+        dry_run,
+    } = command_options;
     workspace_command.check_rewritable(old_commits)?;
     for old_commit in old_commits.iter() {
         check_rebase_destinations(workspace_command.repo(), new_parents, old_commit)?;
     }
-    let mut tx = workspace_command.start_transaction();
-    // `rebase_descendants` takes care of sorting in reverse topological order, so
-    // no need to do it here.
-    for old_commit in old_commits {
-        rebase_commit_with_options(
-            settings,
-            tx.mut_repo(),
-            old_commit,
-            new_parents,
-            &rebase_options,
-        )?;
+    // With `--stop-on-conflict`, sources which would become conflicted, or
+    // whose descendants would, are left in place. Their ancestors can't be
+    // rewritten either, or they would become divergent, so the rebase is
+    // retried with the other sources.
+    let mut sources = old_commits.iter().collect_vec();
+    let mut stopped_ids = vec![];
+    let (mut tx, num_rebased) = loop {
+        let mut tx = workspace_command.start_transaction();
+        let mut new_stopped_ids = vec![];
+        // `rebase_descendants` takes care of sorting in reverse topological order, so
+        // no need to do it here.
+        for old_commit in &sources {
+            let rebased_commit = rebase_commit_with_options(
+                settings,
+                tx.mut_repo(),
+                old_commit,
+                new_parents,
+                rebase_options,
+            )?;
+            if let RebasedCommit::Stopped = rebased_commit {
+                new_stopped_ids.push(old_commit.id().clone());
+            }
+        }
+        let (num_rebased_descendants, stopped) = tx
+            .mut_repo()
+            .rebase_descendants_with_options_return_stopped(settings, rebase_options.clone())?;
+        new_stopped_ids.extend(stopped.into_keys());
+        if new_stopped_ids.is_empty() {
+            break (tx, sources.len() + num_rebased_descendants);
+        }
+        drop(tx);
+        let stopped_ancestors = RevsetExpression::commits(new_stopped_ids.clone())
+            .ancestors()
+            .evaluate_programmatic(workspace_command.repo().as_ref())?;
+        let is_stopped_ancestor = stopped_ancestors.containing_fn();
+        sources.retain(|commit| !is_stopped_ancestor(commit.id()));
+        stopped_ids.extend(new_stopped_ids);
+    };
+    if *dry_run {
+        return tx.finish_dry_run(ui);
+    }
+    if *ask_empty {
+        abandon_newly_empty_commits(ui, settings, &mut tx)?;
     }
-    let num_rebased = old_commits.len()
-        + tx.mut_repo()
-            .rebase_descendants_with_options(settings, rebase_options)?;
     writeln!(ui.stderr(), "Rebased {num_rebased} commits")?;
+    if !stopped_ids.is_empty() {
+        let left_commits = old_commits
+            .iter()
+            .filter(|commit| !sources.contains(commit))
+            .collect_vec();
+        print_stopped_on_conflict(ui, &tx, &stopped_ids, &left_commits, new_parents)?;
+        if sources.is_empty() {
+            // Everything was left in place, so there's no operation to record.
+            return Ok(());
+        }
+    }
     let tx_message = if old_commits.len() == 1 {
         format!(
             "rebase commit {} and descendants",
@@ -321,12 +441,114 @@ fn rebase_descendants(
     Ok(())
 }
 
+/// Lists the commits rewritten in the transaction which became empty, and
+/// abandons them if the user agrees.
+fn abandon_newly_empty_commits(
+    ui: &mut Ui,
+    settings: &UserSettings,
+    tx: &mut WorkspaceCommandTransaction,
+) -> Result<(), CommandError> {
+    let base_heads = tx.base_repo().view().heads().iter().cloned().collect_vec();
+    let new_heads = tx.repo().view().heads().iter().cloned().collect_vec();
+    let new_commits: Vec<Commit> = RevsetExpression::commits(base_heads)
+        .range(&RevsetExpression::commits(new_heads))
+        .evaluate_programmatic(tx.repo())
+        .unwrap()
+        .iter()
+        .commits(tx.repo().store())
+        .try_collect()?;
+    let mut newly_empty = vec![];
+    for commit in new_commits {
+        // Merge commits are kept like with `--empty=drop`.
+        if commit.parent_ids().len() != 1 || !commit.is_empty(tx.repo())? {
+            continue;
+        }
+        let mut was_empty = false;
+        for predecessor in commit.predecessors() {
+            was_empty |= predecessor.is_empty(tx.repo())?;
+        }
+        if !was_empty {
+            newly_empty.push(commit);
+        }
+    }
+    if newly_empty.is_empty() {
+        return Ok(());
+    }
+    if !Ui::can_prompt() {
+        return Err(user_error_with_hint(
+            format!("Rebasing would leave {} commits empty", newly_empty.len()),
+            "Use `--empty=keep` or `--empty=drop` to choose what to do with them.",
+        ));
+    }
+    {
+        let mut formatter = ui.stderr_formatter();
+        writeln!(formatter, "These commits became empty:")?;
+        for commit in &newly_empty {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+    }
+    if !ui.prompt_yes_no("Abandon them?", Some(false))? {
+        return Ok(());
+    }
+    for commit in &newly_empty {
+        tx.mut_repo().record_abandoned_commit(commit.id().clone());
+    }
+    tx.mut_repo().rebase_descendants(settings)?;
+    writeln!(ui.stderr(), "Abandoned {} commits", newly_empty.len())?;
+    Ok(())
+}
+
+fn print_stopped_on_conflict(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    stopped_ids: &[CommitId],
+    left_commits: &[&Commit],
+    new_parents: &[Commit],
+) -> Result<(), CommandError> {
+    let store = tx.repo().store();
+    let mut formatter = ui.stderr_formatter();
+    let formatter = formatter.as_mut();
+    writeln!(
+        formatter,
+        "Rebasing would create conflicts in these commits:"
+    )?;
+    for id in stopped_ids.iter().sorted().dedup() {
+        write!(formatter, "  ")?;
+        tx.write_commit_summary(formatter, &store.get_commit(id)?)?;
+        writeln!(formatter)?;
+    }
+    writeln!(
+        formatter,
+        "Left these commits and their descendants in place:"
+    )?;
+    for commit in left_commits {
+        write!(formatter, "  ")?;
+        tx.write_commit_summary(formatter, commit)?;
+        writeln!(formatter)?;
+    }
+    formatter.push_label("hint")?;
+    writeln!(formatter, "To rebase them anyway, run:")?;
+    write!(formatter, "  jj rebase")?;
+    for commit in left_commits {
+        write!(formatter, " -s {}", short_change_hash(commit.change_id()))?;
+    }
+    for parent in new_parents {
+        write!(formatter, " -d {}", short_change_hash(parent.change_id()))?;
+    }
+    writeln!(formatter)?;
+    formatter.pop_label()?;
+    Ok(())
+}
+
 fn rebase_revision(
     ui: &mut Ui,
     settings: &UserSettings,
     workspace_command: &mut WorkspaceCommandHelper,
     new_parents: &[Commit],
     rev_str: &str,
+    dry_run: bool,
 ) -> Result<(), CommandError> {
     let old_commit = workspace_command.resolve_single_rev(rev_str)?;
     workspace_command.check_rewritable([&old_commit])?;
@@ -438,6 +660,9 @@ fn rebase_revision(
     rebase_commit(settings, tx.mut_repo(), &old_commit, &new_parents)?;
     debug_assert_eq!(tx.mut_repo().rebase_descendants(settings)?, 0);
 
+    if dry_run {
+        return tx.finish_dry_run(ui);
+    }
     if num_rebased_descendants > 0 {
         writeln!(
             ui.stderr(),
@@ -0,0 +1,53 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+
+use jj_lib::repo::Repo;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Record changes in the working copy
+///
+/// Most commands snapshot the working copy automatically, so this is only
+/// needed if automatic snapshotting is disabled by `snapshot.auto = false`.
+/// For details, see
+/// https://github.com/martinvonz/jj/blob/main/docs/config.md#explicit-snapshots.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct SnapshotArgs {}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_snapshot(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    SnapshotArgs {}: &SnapshotArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let old_op_id = workspace_command.repo().op_id().clone();
+    workspace_command.snapshot(ui)?;
+    if *workspace_command.repo().op_id() == old_op_id {
+        writeln!(ui.stderr(), "Nothing changed.")?;
+    } else if let Some(wc_commit_id) = workspace_command.get_wc_commit_id() {
+        let wc_commit = workspace_command.repo().store().get_commit(wc_commit_id)?;
+        write!(ui.stderr(), "Working copy now at: ")?;
+        ui.stderr_formatter().with_label("working_copy", |fmt| {
+            workspace_command.write_commit_summary(fmt, &wc_commit)
+        })?;
+        writeln!(ui.stderr())?;
+    }
+    Ok(())
+}
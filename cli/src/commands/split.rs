@@ -11,16 +11,24 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::collections::HashSet;
 use std::io::Write;
+use std::slice;
 
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
-use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::revset::{RevsetExpression, RevsetIteratorExt as _};
+use jj_lib::rewrite::{merge_commit_trees, restore_tree};
 use tracing::instrument;
 
-use crate::cli_util::{CommandHelper, RevisionArg};
-use crate::command_error::CommandError;
-use crate::description_util::{description_template_for_commit, edit_description};
+use crate::cli_util::{CommandHelper, RevisionArg, WorkspaceCommandTransaction};
+use crate::command_error::{user_error_with_hint, CommandError};
+use crate::description_util::{
+    description_template_for_commit, edit_description, DescriptionTemplateOptions,
+};
 use crate::ui::Ui;
 
 /// Split a revision in two
@@ -35,6 +43,10 @@ use crate::ui::Ui;
 /// change description for each commit. If the change did not have a
 /// description, the second part will not get a description, and you will be
 /// asked for a description only for the first part.
+///
+/// With `--parallel`, the parts become siblings on the parents of the original
+/// revision, and its children are rebased onto all of the parts. The changes
+/// can then be split into more than two parts by `--group`.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct SplitArgs {
     /// Interactively choose which parts to split. This is the default if no
@@ -47,6 +59,25 @@ pub(crate) struct SplitArgs {
     /// The revision to split
     #[arg(long, short, default_value = "@")]
     revision: RevisionArg,
+    /// Split the revision into parallel revisions instead of a parent and
+    /// child
+    ///
+    /// The split fails if the parts depend on each other, i.e. if applying
+    /// them separately onto the parents would produce conflicts.
+    #[arg(long, short)]
+    parallel: bool,
+    /// Put the paths matching this pattern in a revision of their own (can be
+    /// repeated)
+    ///
+    /// A path matching several groups goes to the first of them. Changes not
+    /// matching any group are put in the last revision.
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        requires = "parallel",
+        conflicts_with_all = ["interactive", "tool", "paths"],
+    )]
+    group: Vec<String>,
     /// Put these paths in the first commit
     #[arg(value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
@@ -62,16 +93,39 @@ pub(crate) fn cmd_split(
     let commit = workspace_command.resolve_single_rev(&args.revision)?;
     workspace_command.check_rewritable([&commit])?;
     let matcher = workspace_command.matcher_from_values(&args.paths)?;
+    let group_matchers: Vec<_> = args
+        .group
+        .iter()
+        .map(|pattern| workspace_command.matcher_from_values(slice::from_ref(pattern)))
+        .try_collect()?;
     let diff_selector = workspace_command.diff_selector(
         ui,
         args.tool.as_deref(),
-        args.interactive || args.paths.is_empty(),
+        args.interactive || (args.paths.is_empty() && args.group.is_empty()),
     )?;
     let mut tx = workspace_command.start_transaction();
     let end_tree = commit.tree()?;
     let base_tree = merge_commit_trees(tx.repo(), &commit.parents())?;
-    let instructions = format!(
-        "\
+
+    // The trees of the parts, in the order they are created in.
+    let mut part_trees = vec![];
+    if group_matchers.is_empty() {
+        let instructions = if args.parallel {
+            format!(
+                "\
+You are splitting a commit into two parallel commits: {}
+
+The diff initially shows the changes in the commit you're splitting.
+
+Adjust the right side until it shows the contents you want for the first
+commit. The remainder will be in the second commit, next to the first one.
+If you don't make any changes, then the operation will be aborted.
+",
+                tx.format_commit_summary(&commit)
+            )
+        } else {
+            format!(
+                "\
 You are splitting a commit in two: {}
 
 The diff initially shows the changes in the commit you're splitting.
@@ -80,83 +134,197 @@ Adjust the right side until it shows the contents you want for the first
 (parent) commit. The remainder will be in the second commit. If you
 don't make any changes, then the operation will be aborted.
 ",
-        tx.format_commit_summary(&commit)
-    );
+                tx.format_commit_summary(&commit)
+            )
+        };
 
-    // Prompt the user to select the changes they want for the first commit.
-    let selected_tree_id =
-        diff_selector.select(&base_tree, &end_tree, matcher.as_ref(), Some(&instructions))?;
-    if &selected_tree_id == commit.tree_id() && diff_selector.is_interactive() {
-        // The user selected everything from the original commit.
-        writeln!(ui.stderr(), "Nothing changed.")?;
-        return Ok(());
+        // Prompt the user to select the changes they want for the first commit.
+        let selected_tree_id =
+            diff_selector.select(&base_tree, &end_tree, matcher.as_ref(), Some(&instructions))?;
+        if &selected_tree_id == commit.tree_id() && diff_selector.is_interactive() {
+            // The user selected everything from the original commit.
+            writeln!(ui.stderr(), "Nothing changed.")?;
+            return Ok(());
+        }
+        if selected_tree_id == base_tree.id() {
+            // The user selected nothing, so the first commit will be empty.
+            writeln!(
+                ui.warning_no_heading(),
+                "The given paths do not match any file: {}",
+                args.paths.join(" ")
+            )?;
+        }
+        let selected_tree = tx.repo().store().get_root_tree(&selected_tree_id)?;
+        let remaining_tree = if args.parallel {
+            // Take the selected changes out of the original tree. This is where
+            // the parts may turn out to depend on each other.
+            end_tree.merge(&selected_tree, &base_tree)?
+        } else {
+            end_tree.clone()
+        };
+        part_trees.push(selected_tree);
+        part_trees.push(remaining_tree);
+    } else {
+        let mut remaining_tree = end_tree.clone();
+        for (pattern, matcher) in args.group.iter().zip(&group_matchers) {
+            let group_tree_id = restore_tree(&remaining_tree, &base_tree, matcher.as_ref())?;
+            if group_tree_id == base_tree.id() {
+                writeln!(
+                    ui.warning_no_heading(),
+                    "The group does not match any changed file: {pattern}"
+                )?;
+            }
+            let remaining_tree_id = restore_tree(&base_tree, &remaining_tree, matcher.as_ref())?;
+            remaining_tree = tx.repo().store().get_root_tree(&remaining_tree_id)?;
+            part_trees.push(tx.repo().store().get_root_tree(&group_tree_id)?);
+        }
+        part_trees.push(remaining_tree);
     }
-    if selected_tree_id == base_tree.id() {
-        // The user selected nothing, so the first commit will be empty.
-        writeln!(
-            ui.warning_no_heading(),
-            "The given paths do not match any file: {}",
-            args.paths.join(" ")
-        )?;
+    if args.parallel {
+        check_parts_are_independent(&tx, &base_tree, &end_tree, &part_trees)?;
     }
 
-    // Create the first commit, which includes the changes selected by the user.
-    let selected_tree = tx.repo().store().get_root_tree(&selected_tree_id)?;
-    let first_template = description_template_for_commit(
-        ui,
-        command.settings(),
-        tx.base_workspace_helper(),
-        "Enter commit description for the first part (parent).",
-        commit.description(),
-        &base_tree,
-        &selected_tree,
-    )?;
-    let first_description = edit_description(tx.base_repo(), &first_template, command.settings())?;
-    let first_commit = tx
-        .mut_repo()
-        .rewrite_commit(command.settings(), &commit)
-        .set_tree_id(selected_tree_id)
-        .set_description(first_description)
-        .write()?;
-
-    // Create the second commit, which includes everything the user didn't select.
-    let second_description = if commit.description().is_empty() {
-        // If there was no description before, don't ask for one for the second commit.
-        "".to_string()
-    } else {
-        let second_template = description_template_for_commit(
-            ui,
-            command.settings(),
-            tx.base_workspace_helper(),
-            "Enter commit description for the second part (child).",
-            commit.description(),
-            &selected_tree,
-            &end_tree,
-        )?;
-        edit_description(tx.base_repo(), &second_template, command.settings())?
-    };
-    let second_commit = tx
-        .mut_repo()
-        .rewrite_commit(command.settings(), &commit)
-        .set_parents(vec![first_commit.id().clone()])
-        .set_tree_id(commit.tree_id().clone())
-        .generate_new_change_id()
-        .set_description(second_description)
-        .write()?;
+    let part_count = part_trees.len();
+    let mut parts: Vec<Commit> = vec![];
+    for (index, part_tree) in part_trees.iter().enumerate() {
+        let (part_name, parent_tree) = if args.parallel {
+            (format!("part {} of {part_count}", index + 1), &base_tree)
+        } else if index == 0 {
+            ("the first part (parent)".to_owned(), &base_tree)
+        } else {
+            ("the second part (child)".to_owned(), &part_trees[0])
+        };
+        let description = if index > 0 && commit.description().is_empty() {
+            // If there was no description before, don't ask for one for the
+            // other commits.
+            "".to_string()
+        } else {
+            let template = description_template_for_commit(
+                ui,
+                tx.base_workspace_helper(),
+                &format!("Enter commit description for {part_name}."),
+                commit.description(),
+                parent_tree,
+                part_tree,
+                &DescriptionTemplateOptions::default(),
+            )?;
+            edit_description(tx.base_repo(), &template, command.settings())?
+        };
+        let mut_repo = tx.mut_repo();
+        let part = if index == 0 {
+            // The first part keeps the change id of the original commit.
+            mut_repo
+                .rewrite_commit(command.settings(), &commit)
+                .set_tree_id(part_tree.id())
+                .set_description(description)
+                .write()?
+        } else {
+            let parent_ids = if args.parallel {
+                commit.parent_ids().to_vec()
+            } else {
+                vec![parts[0].id().clone()]
+            };
+            mut_repo
+                .rewrite_commit(command.settings(), &commit)
+                .set_parents(parent_ids)
+                .set_tree_id(part_tree.id())
+                .generate_new_change_id()
+                .set_description(description)
+                .write()?
+        };
+        parts.push(part);
+    }
+    let last_part = parts.last().unwrap();
 
-    // We want only the `second_commit` to inherit `commit`'s branches and
+    let mut num_rebased = 0;
+    if args.parallel {
+        // Make the children merges of all the parts, so that their contents
+        // don't change.
+        let part_ids = parts.iter().map(|part| part.id().clone()).collect_vec();
+        let children: Vec<_> = RevsetExpression::commit(commit.id().clone())
+            .children()
+            .evaluate_programmatic(tx.base_repo().as_ref())?
+            .iter()
+            .commits(tx.base_repo().store())
+            .try_collect()?;
+        for child in &children {
+            let parent_ids = child
+                .parent_ids()
+                .iter()
+                .flat_map(|id| {
+                    if id == commit.id() {
+                        part_ids.clone()
+                    } else {
+                        vec![id.clone()]
+                    }
+                })
+                .collect_vec();
+            let new_child = tx
+                .mut_repo()
+                .rewrite_commit(command.settings(), child)
+                .set_parents(parent_ids)
+                .write()?;
+            tx.mut_repo()
+                .set_rewritten_commit(child.id().clone(), new_child.id().clone());
+        }
+        num_rebased += children.len();
+    }
+    // We want only the last part to inherit `commit`'s branches and
     // descendants.
     tx.mut_repo()
-        .set_rewritten_commit(commit.id().clone(), second_commit.id().clone());
-    let num_rebased = tx.mut_repo().rebase_descendants(command.settings())?;
+        .set_rewritten_commit(commit.id().clone(), last_part.id().clone());
+    num_rebased += tx.mut_repo().rebase_descendants(command.settings())?;
     if num_rebased > 0 {
         writeln!(ui.stderr(), "Rebased {num_rebased} descendant commits")?;
     }
-    write!(ui.stderr(), "First part: ")?;
-    tx.write_commit_summary(ui.stderr_formatter().as_mut(), &first_commit)?;
-    write!(ui.stderr(), "\nSecond part: ")?;
-    tx.write_commit_summary(ui.stderr_formatter().as_mut(), &second_commit)?;
-    writeln!(ui.stderr())?;
+    for (index, part) in parts.iter().enumerate() {
+        if args.parallel {
+            write!(ui.stderr(), "Part {}: ", index + 1)?;
+        } else if index == 0 {
+            write!(ui.stderr(), "First part: ")?;
+        } else {
+            write!(ui.stderr(), "Second part: ")?;
+        }
+        tx.write_commit_summary(ui.stderr_formatter().as_mut(), part)?;
+        writeln!(ui.stderr())?;
+    }
     tx.finish(ui, format!("split commit {}", commit.id().hex()))?;
     Ok(())
 }
+
+/// Fails if the changes in `part_trees` don't add up to `end_tree` when
+/// applied separately onto `base_tree`.
+fn check_parts_are_independent(
+    tx: &WorkspaceCommandTransaction,
+    base_tree: &MergedTree,
+    end_tree: &MergedTree,
+    part_trees: &[MergedTree],
+) -> Result<(), CommandError> {
+    let mut combined_tree = part_trees[0].clone();
+    for part_tree in &part_trees[1..] {
+        combined_tree = combined_tree.merge(base_tree, part_tree)?;
+    }
+    let existing_conflicts: HashSet<_> = end_tree.conflicts().map(|(path, _)| path).collect();
+    let new_conflicts = part_trees
+        .iter()
+        .chain([&combined_tree])
+        .flat_map(|tree| tree.conflicts().map(|(path, _)| path))
+        .filter(|path| !existing_conflicts.contains(path))
+        .sorted()
+        .dedup()
+        .collect_vec();
+    if new_conflicts.is_empty() {
+        return Ok(());
+    }
+    let ui_paths = new_conflicts
+        .iter()
+        .map(|path| tx.base_workspace_helper().format_file_path(path))
+        .join(", ");
+    Err(user_error_with_hint(
+        "The changes can't be split into parallel commits because they depend on each other",
+        format!(
+            "These files would have conflicts: {ui_paths}. Select whole files for each part, or \
+             split without --parallel."
+        ),
+    ))
+}
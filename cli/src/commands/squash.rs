@@ -70,6 +70,12 @@ pub(crate) struct SquashArgs {
     /// Move only changes to these paths (instead of all paths)
     #[arg(conflicts_with_all = ["interactive", "tool"], value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
+    /// Only report which commits would become conflicted or empty
+    ///
+    /// The squash is computed in memory, but the resulting commits are not
+    /// recorded in the operation log and the working copy is left untouched.
+    #[arg(long)]
+    dry_run: bool,
 }
 
 #[instrument(skip_all)]
@@ -122,6 +128,9 @@ pub(crate) fn cmd_squash(
         args.revision.is_none() && args.from.is_none() && args.into.is_none(),
         &args.paths,
     )?;
+    if args.dry_run {
+        return tx.finish_dry_run(ui);
+    }
     tx.finish(ui, tx_description)?;
     Ok(())
 }
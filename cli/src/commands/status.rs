@@ -13,6 +13,8 @@
 // limitations under the License.
 
 use itertools::Itertools;
+use jj_lib::backend::TreeValue;
+use jj_lib::file_util::check_symlink_support;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::repo::Repo;
 use jj_lib::rewrite::merge_commit_trees;
@@ -20,7 +22,7 @@ use tracing::instrument;
 
 use super::resolve;
 use crate::cli_util::CommandHelper;
-use crate::command_error::CommandError;
+use crate::command_error::{user_error, CommandError};
 use crate::diff_util;
 use crate::ui::Ui;
 
@@ -34,13 +36,21 @@ use crate::ui::Ui;
 ///  * Conflicted branches (see https://github.com/martinvonz/jj/blob/main/docs/branches.md)
 #[derive(clap::Args, Clone, Debug)]
 #[command(visible_alias = "st")]
-pub(crate) struct StatusArgs {}
+pub(crate) struct StatusArgs {
+    /// Render the working-copy commit using the given template instead
+    ///
+    /// Useful keywords include `changes`, `conflicted_files`, and `parents`.
+    /// For the syntax, see
+    /// https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    #[arg(long, short = 'T')]
+    template: Option<String>,
+}
 
 #[instrument(skip_all)]
 pub(crate) fn cmd_status(
     ui: &mut Ui,
     command: &CommandHelper,
-    _args: &StatusArgs,
+    args: &StatusArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo();
@@ -48,6 +58,15 @@ pub(crate) fn cmd_status(
         .get_wc_commit_id()
         .map(|id| repo.store().get_commit(id))
         .transpose()?;
+    if let Some(template_text) = &args.template {
+        let template = workspace_command.parse_commit_template(template_text)?;
+        let wc_commit =
+            maybe_wc_commit.ok_or_else(|| user_error("This command requires a working copy"))?;
+        ui.request_pager();
+        let mut formatter = ui.stdout_formatter();
+        template.format(&wc_commit, formatter.as_mut())?;
+        return Ok(());
+    }
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
@@ -75,6 +94,27 @@ pub(crate) fn cmd_status(
             resolve::print_conflicted_paths(&conflicts, formatter, &workspace_command)?
         }
 
+        // Symlinks are materialized as regular files if the platform (e.g.
+        // Windows without Developer Mode) can't create them. Finding symlinks
+        // walks the whole tree, so skip it if the user opted into regular files.
+        if command.settings().symlinks_enabled()?
+            && !check_symlink_support().unwrap_or(false)
+            && tree
+                .entries()
+                .any(|(_, value)| matches!(value.as_normal(), Some(TreeValue::Symlink(_))))
+        {
+            writeln!(
+                ui.warning_default(),
+                "Symlinks in the working copy are checked out as regular files containing the \
+                 link target, because this platform doesn't support creating symlinks."
+            )?;
+            writeln!(
+                ui.hint_default(),
+                "On Windows, enable Developer Mode to check out real symlinks, or set \
+                 `core.symlinks = false` to silence this warning."
+            )?;
+        }
+
         let template = workspace_command.commit_summary_template();
         write!(formatter, "Working copy : ")?;
         formatter.with_label("working_copy", |fmt| template.format(wc_commit, fmt))?;
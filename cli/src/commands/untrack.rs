@@ -70,6 +70,8 @@ pub(crate) fn cmd_untrack(
         fsmonitor_kind: command.settings().fsmonitor_kind()?,
         progress: None,
         max_new_file_size: command.settings().max_new_file_size()?,
+        ignore_executable_bit: command.settings().ignore_executable_bit()?,
+        fingerprint_cache: None,
     })?;
     if wc_tree_id != *new_commit.tree_id() {
         let wc_tree = store.get_root_tree(&wc_tree_id)?;
@@ -102,7 +104,7 @@ Make sure they're ignored, then try again.",
     if num_rebased > 0 {
         writeln!(ui.stderr(), "Rebased {num_rebased} descendant commits")?;
     }
-    let repo = tx.commit("untrack paths");
+    let repo = tx.commit("untrack paths")?;
     locked_ws.finish(repo.op_id().clone())?;
     Ok(())
 }
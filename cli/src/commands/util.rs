@@ -12,25 +12,43 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::io::Write;
-use std::slice;
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
+use std::{process, slice};
 
 use clap::{Command, Subcommand};
+use itertools::Itertools as _;
+use jj_lib::backend::ChangeId;
+use jj_lib::default_index::{AsCompositeIndex as _, DefaultReadonlyIndex};
+use jj_lib::fingerprint_cache::FileFingerprintCache;
+use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::OperationId;
 use jj_lib::repo::Repo;
+use jj_lib::revset::{RevsetExpression, RevsetFilterPredicate};
+use jj_lib::{journal, op_walk, revset};
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
-use crate::command_error::{user_error, CommandError};
+use crate::command_error::{internal_error, user_error, user_error_with_message, CommandError};
+use crate::commands::operation;
+use crate::commit_templater::CommitTemplateBuildFnTable;
+use crate::operation_templater::OperationTemplateBuildFnTable;
+use crate::template_builder::sorted_fn_names;
 use crate::ui::Ui;
 
 /// Infrequently used commands such as for generating shell completions
 #[derive(Subcommand, Clone, Debug)]
 pub(crate) enum UtilCommand {
     Completion(UtilCompletionArgs),
+    Exec(UtilExecArgs),
     Gc(UtilGcArgs),
     Mangen(UtilMangenArgs),
     MarkdownHelp(UtilMarkdownHelp),
+    Stats(UtilStatsArgs),
     ConfigSchema(UtilConfigSchemaArgs),
 }
 
@@ -69,6 +87,33 @@ pub(crate) struct UtilCompletionArgs {
     zsh: bool,
 }
 
+/// Run a program with the repo context in its environment
+///
+/// The program is run in the current directory with the following environment
+/// variables set:
+///
+/// - `JJ_ROOT`: the workspace root directory
+/// - `JJ_CHANGE`: the change ID of the working-copy commit
+/// - `JJ_COMMIT`: the commit ID of the working-copy commit
+/// - `JJ_OP`: the ID of the current operation
+///
+/// The working copy is snapshotted before the program is run.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct UtilExecArgs {
+    /// Record the changes made by the program to the working copy as an
+    /// operation with this description
+    #[arg(long, short)]
+    description: Option<String>,
+    /// The program to run
+    command: String,
+    /// Arguments to pass to the program
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+/// Number of journal entries kept by `jj util gc`.
+const JOURNAL_ENTRIES_TO_KEEP: usize = 1000;
+
 /// Run backend-dependent garbage collection.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct UtilGcArgs {
@@ -82,16 +127,45 @@ pub(crate) struct UtilGcArgs {
     /// release.
     #[arg(long)]
     expire: Option<String>,
+    /// Fold consecutive snapshot operations into one before collecting garbage
+    ///
+    /// Each run of operations that only snapshotted the working copy is
+    /// replaced by its last operation. The folded operations will be pruned
+    /// once they expire.
+    #[arg(long)]
+    compact_snapshots: bool,
 }
 
 /// Print a ROFF (manpage)
 #[derive(clap::Args, Clone, Debug)]
-pub(crate) struct UtilMangenArgs {}
+pub(crate) struct UtilMangenArgs {
+    /// Write a manpage for each subcommand to this directory instead
+    #[arg(long, value_hint = clap::ValueHint::DirPath)]
+    output_dir: Option<PathBuf>,
+}
 
 /// Print the CLI help for all subcommands in Markdown
+///
+/// The output also lists the builtin template and revset functions.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct UtilMarkdownHelp {}
 
+/// Print statistics about the repo
+///
+/// Reports the number of visible and hidden commits, conflicted commits,
+/// divergent changes, operations, the number of commits in each index segment,
+/// and the number and largest of the files in the working copy. This can be
+/// useful to compare the repo before and after `jj util gc`.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct UtilStatsArgs {
+    /// Number of largest files to report
+    #[arg(long, default_value_t = 10)]
+    largest_files: usize,
+    /// Print the statistics as a JSON object
+    #[arg(long)]
+    json: bool,
+}
+
 /// Print the JSON schema for the jj TOML config format.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct UtilConfigSchemaArgs {}
@@ -115,9 +189,11 @@ pub(crate) fn cmd_util(
 ) -> Result<(), CommandError> {
     match subcommand {
         UtilCommand::Completion(args) => cmd_util_completion(ui, command, args),
+        UtilCommand::Exec(args) => cmd_util_exec(ui, command, args),
         UtilCommand::Gc(args) => cmd_util_gc(ui, command, args),
         UtilCommand::Mangen(args) => cmd_util_mangen(ui, command, args),
         UtilCommand::MarkdownHelp(args) => cmd_util_markdownhelp(ui, command, args),
+        UtilCommand::Stats(args) => cmd_util_stats(ui, command, args),
         UtilCommand::ConfigSchema(args) => cmd_util_config_schema(ui, command, args),
     }
 }
@@ -167,6 +243,47 @@ fn cmd_util_completion(
     Ok(())
 }
 
+fn cmd_util_exec(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &UtilExecArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    if args.description.is_some() {
+        workspace_command.check_working_copy_writable()?;
+    }
+    let repo = workspace_command.repo();
+    let mut cmd = process::Command::new(&args.command);
+    cmd.args(&args.args)
+        .env("JJ_ROOT", workspace_command.workspace_root())
+        .env("JJ_OP", repo.op_id().hex());
+    if let Some(wc_commit_id) = workspace_command.get_wc_commit_id() {
+        let wc_commit = repo.store().get_commit(wc_commit_id)?;
+        cmd.env("JJ_CHANGE", wc_commit.change_id().reverse_hex())
+            .env("JJ_COMMIT", wc_commit_id.hex());
+    }
+    tracing::info!(?cmd, "Invoking the program:");
+    let exit_status = cmd.status().map_err(|err| {
+        user_error_with_message(format!("Failed to run program '{}'", args.command), err)
+    })?;
+    if !exit_status.success() {
+        let code = exit_status
+            .code()
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "<unknown>".to_string());
+        return Err(user_error(format!(
+            "Program '{}' exited with a non-zero code. Exit code: {code}.",
+            args.command
+        )));
+    }
+    if let Some(description) = &args.description {
+        // The program may have run jj commands, so reload the repo at head.
+        let mut workspace_command = command.workspace_helper_no_snapshot(ui)?;
+        workspace_command.snapshot_with_description(ui, description)?;
+    }
+    Ok(())
+}
+
 fn cmd_util_gc(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -182,20 +299,61 @@ fn cmd_util_gc(
         Some("now") => SystemTime::now() - Duration::ZERO,
         _ => return Err(user_error("--expire only accepts 'now'")),
     };
+    if args.compact_snapshots {
+        compact_snapshot_operations(ui, command)?;
+    }
     let workspace_command = command.workspace_helper(ui)?;
 
     let repo = workspace_command.repo();
     repo.op_store()
         .gc(slice::from_ref(repo.op_id()), keep_newer)?;
     repo.store().gc(repo.index(), keep_newer)?;
+    // The cached file ids may refer to contents which were just removed.
+    FileFingerprintCache::clear(command.workspace_loader()?.repo_path())?;
+    {
+        // Keep concurrent operations from appending to the journal while it's
+        // being replaced.
+        let _lock = repo.op_heads_store().lock().map_err(internal_error)?;
+        journal::truncate(repo.repo_path(), JOURNAL_ENTRIES_TO_KEEP)?;
+    }
     Ok(())
 }
 
+fn compact_snapshot_operations(ui: &mut Ui, command: &CommandHelper) -> Result<(), CommandError> {
+    let mut workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let current_head_op = op_walk::resolve_op_for_load(repo_loader, "@")?;
+    let stats = op_walk::compact_snapshots(
+        repo_loader.op_store().as_ref(),
+        slice::from_ref(&current_head_op),
+    )?;
+    if stats.unreachable_count == 0 {
+        return Ok(());
+    }
+    let [new_head_id]: [OperationId; 1] = stats.new_head_ids.try_into().unwrap();
+    writeln!(
+        ui.stderr(),
+        "Folded {} snapshot operations and reparented {} descendant operations.",
+        stats.unreachable_count,
+        stats.rewritten_count,
+    )?;
+    operation::reset_head_operation(ui, command, &mut workspace, &current_head_op, new_head_id)
+}
+
 fn cmd_util_mangen(
     ui: &mut Ui,
     command: &CommandHelper,
-    _args: &UtilMangenArgs,
+    args: &UtilMangenArgs,
 ) -> Result<(), CommandError> {
+    if let Some(output_dir) = &args.output_dir {
+        clap_mangen::generate_to(command.app().clone(), output_dir).map_err(|err| {
+            user_error_with_message(
+                format!("Failed to write manpages to {}", output_dir.display()),
+                err,
+            )
+        })?;
+        return Ok(());
+    }
     let mut buf = vec![];
     let man = clap_mangen::Man::new(command.app().clone());
     man.render(&mut buf)?;
@@ -210,8 +368,157 @@ fn cmd_util_markdownhelp(
 ) -> Result<(), CommandError> {
     // If we ever need more flexibility, the code of `clap_markdown` is simple and
     // readable. We could reimplement the parts we need without trouble.
-    let markdown = clap_markdown::help_markdown_command(command.app()).into_bytes();
-    ui.stdout_formatter().write_all(&markdown)?;
+    let mut markdown = clap_markdown::help_markdown_command(command.app());
+    let reference = functions_reference_markdown();
+    match markdown.rfind("<hr/>") {
+        Some(footer_pos) => markdown.insert_str(footer_pos, &reference),
+        None => markdown.push_str(&reference),
+    }
+    ui.stdout_formatter().write_all(markdown.as_bytes())?;
+    Ok(())
+}
+
+/// Lists the builtin template and revset functions in Markdown.
+fn functions_reference_markdown() -> String {
+    let commit_table = CommitTemplateBuildFnTable::builtin();
+    let operation_table = OperationTemplateBuildFnTable::builtin();
+    let mut method_names = commit_table.method_names();
+    for (type_name, names) in operation_table.method_names() {
+        if !method_names.iter().any(|(name, _)| *name == type_name) {
+            method_names.push((type_name, names));
+        }
+    }
+    let format_names = |names: &[&str]| names.iter().map(|name| format!("`{name}`")).join(", ");
+
+    let mut out = String::new();
+    writeln!(out, "## Template functions\n").unwrap();
+    let function_names = sorted_fn_names(&commit_table.core.functions);
+    writeln!(out, "{}\n\n", format_names(&function_names)).unwrap();
+    writeln!(out, "## Template methods\n").unwrap();
+    for (type_name, names) in &method_names {
+        if !names.is_empty() {
+            writeln!(out, "* **{type_name}:** {}", format_names(names)).unwrap();
+        }
+    }
+    writeln!(out, "\n\n## Revset functions\n").unwrap();
+    let revset_names = revset::builtin_function_names();
+    writeln!(out, "{}\n\n\n", format_names(&revset_names)).unwrap();
+    out
+}
+
+#[instrument(skip_all)]
+fn cmd_util_stats(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &UtilStatsArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+
+    let all_revset = RevsetExpression::all().evaluate_programmatic(repo.as_ref())?;
+    let mut visible_commits = 0;
+    let mut commit_counts_by_change: HashMap<ChangeId, usize> = HashMap::new();
+    for (_, change_id) in all_revset.commit_change_ids() {
+        visible_commits += 1;
+        *commit_counts_by_change.entry(change_id).or_default() += 1;
+    }
+    let divergent_changes = commit_counts_by_change
+        .values()
+        .filter(|&&count| count > 1)
+        .count();
+    let conflicted_commits = RevsetExpression::filter(RevsetFilterPredicate::HasConflict)
+        .evaluate_programmatic(repo.as_ref())?
+        .iter()
+        .count();
+    let operations = op_walk::walk_ancestors(slice::from_ref(repo.operation()))
+        .process_results(|ops| ops.count())?;
+
+    // Other index implementations don't expose their segments.
+    let index_stats = repo
+        .readonly_index()
+        .as_any()
+        .downcast_ref::<DefaultReadonlyIndex>()
+        .map(|index| index.as_composite().stats());
+    let hidden_commits = index_stats
+        .as_ref()
+        .map(|stats| stats.num_commits as usize - visible_commits);
+    let index_level_commits = index_stats.as_ref().map(|stats| {
+        stats
+            .levels
+            .iter()
+            .map(|level| level.num_commits)
+            .collect_vec()
+    });
+
+    // The file sizes recorded by the local working copy are used to avoid
+    // reading the contents. Other working copies don't expose them.
+    let local_working_copy = workspace_command
+        .working_copy()
+        .as_any()
+        .downcast_ref::<LocalWorkingCopy>();
+    let (working_copy_files, largest_files) = match local_working_copy {
+        Some(working_copy) => {
+            let mut files = working_copy
+                .file_states()?
+                .iter()
+                .map(|(path, state)| (state.size, path.to_owned()))
+                .collect_vec();
+            let num_files = files.len();
+            files.sort_by(|(size1, path1), (size2, path2)| size2.cmp(size1).then(path1.cmp(path2)));
+            files.truncate(args.largest_files);
+            (Some(num_files), files)
+        }
+        None => (None, vec![]),
+    };
+
+    if args.json {
+        let json = serde_json::json!({
+            "visible_commits": visible_commits,
+            "hidden_commits": hidden_commits,
+            "conflicted_commits": conflicted_commits,
+            "divergent_changes": divergent_changes,
+            "operations": operations,
+            "index_levels": index_level_commits,
+            "working_copy_files": working_copy_files,
+            "largest_files": largest_files
+                .iter()
+                .map(|(size, path)| serde_json::json!({
+                    "path": path.as_internal_file_string(),
+                    "size": size,
+                }))
+                .collect_vec(),
+        });
+        writeln!(ui.stdout(), "{json:#}")?;
+        return Ok(());
+    }
+
+    let mut formatter = ui.stdout_formatter();
+    writeln!(formatter, "Visible commits: {visible_commits}")?;
+    if let Some(hidden_commits) = hidden_commits {
+        writeln!(formatter, "Hidden commits: {hidden_commits}")?;
+    }
+    writeln!(formatter, "Conflicted commits: {conflicted_commits}")?;
+    writeln!(formatter, "Divergent changes: {divergent_changes}")?;
+    writeln!(formatter, "Operations: {operations}")?;
+    if let Some(level_commits) = &index_level_commits {
+        writeln!(formatter, "Index levels:")?;
+        for (i, num_commits) in level_commits.iter().enumerate() {
+            writeln!(formatter, "  Level {i}: {num_commits} commits")?;
+        }
+    }
+    if let Some(working_copy_files) = working_copy_files {
+        writeln!(formatter, "Working-copy files: {working_copy_files}")?;
+    }
+    if !largest_files.is_empty() {
+        writeln!(formatter, "Largest files:")?;
+        for (size, path) in &largest_files {
+            writeln!(
+                formatter,
+                "  {size} {}",
+                workspace_command.format_file_path(path)
+            )?;
+        }
+    }
     Ok(())
 }
 
@@ -19,6 +19,7 @@ use std::sync::Arc;
 
 use clap::Subcommand;
 use itertools::Itertools;
+use jj_lib::commit::Commit;
 use jj_lib::file_util;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::{OpStoreError, WorkspaceId};
@@ -29,7 +30,7 @@ use jj_lib::workspace::Workspace;
 use tracing::instrument;
 
 use crate::cli_util::{
-    self, check_stale_working_copy, print_checkout_stats, short_commit_hash, CommandHelper,
+    self, check_stale_working_copy, print_checkout_stats, short_operation_hash, CommandHelper,
     RevisionArg, WorkingCopyFreshness, WorkspaceCommandHelper,
 };
 use crate::command_error::{internal_error_with_message, user_error, CommandError};
@@ -51,6 +52,7 @@ pub(crate) enum WorkspaceCommand {
     Add(WorkspaceAddArgs),
     Forget(WorkspaceForgetArgs),
     List(WorkspaceListArgs),
+    MoveChange(WorkspaceMoveChangeArgs),
     Root(WorkspaceRootArgs),
     UpdateStale(WorkspaceUpdateStaleArgs),
 }
@@ -58,6 +60,12 @@ pub(crate) enum WorkspaceCommand {
 /// Add a workspace
 ///
 /// Sparse patterns will be copied over from the current workspace.
+///
+/// With `--at-op`, the working-copy commit of the current workspace is
+/// recreated as it was at the given operation, with the same parents,
+/// contents, and description. This lets you look at what you had before an
+/// operation, e.g. before `jj undo`, without restoring the whole repo with `jj
+/// op restore`. The new workspace is still added at the current operation.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct WorkspaceAddArgs {
     /// Where to create the new workspace
@@ -99,6 +107,18 @@ pub(crate) struct WorkspaceForgetArgs {
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct WorkspaceListArgs {}
 
+/// Move the working-copy change to another workspace
+///
+/// The other workspace starts editing the current working-copy commit, and
+/// this workspace gets a new, empty working-copy commit on top of the same
+/// parents. The other workspace becomes stale; run `jj workspace update-stale`
+/// there to update its files.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct WorkspaceMoveChangeArgs {
+    /// Name of the workspace to move the change to
+    workspace: String,
+}
+
 /// Show the current workspace root directory
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct WorkspaceRootArgs {}
@@ -120,6 +140,7 @@ pub(crate) fn cmd_workspace(
         WorkspaceCommand::Add(args) => cmd_workspace_add(ui, command, args),
         WorkspaceCommand::Forget(args) => cmd_workspace_forget(ui, command, args),
         WorkspaceCommand::List(args) => cmd_workspace_list(ui, command, args),
+        WorkspaceCommand::MoveChange(args) => cmd_workspace_move_change(ui, command, args),
         WorkspaceCommand::Root(args) => cmd_workspace_root(ui, command, args),
         WorkspaceCommand::UpdateStale(args) => cmd_workspace_update_stale(ui, command, args),
     }
@@ -132,6 +153,29 @@ fn cmd_workspace_add(
     args: &WorkspaceAddArgs,
 ) -> Result<(), CommandError> {
     let old_workspace_command = command.workspace_helper(ui)?;
+    // With --at-op, the current workspace is loaded at that operation. Take
+    // the working-copy commit from there, but add the new workspace at head.
+    let (repo, old_wc_commit_at_op) = if command.global_args().at_operation == "@" {
+        (old_workspace_command.repo().clone(), None)
+    } else {
+        if !args.revision.is_empty() {
+            return Err(user_error("--revision cannot be used with --at-op"));
+        }
+        let op_repo = old_workspace_command.repo();
+        let wc_commit_id = op_repo
+            .view()
+            .get_wc_commit_id(old_workspace_command.workspace_id())
+            .ok_or_else(|| {
+                user_error(format!(
+                    "Workspace {} had no working-copy commit at operation {}",
+                    old_workspace_command.workspace_id().as_str(),
+                    short_operation_hash(op_repo.op_id())
+                ))
+            })?;
+        let old_wc_commit = op_repo.store().get_commit(wc_commit_id)?;
+        let head_repo = op_repo.loader().load_at_head(command.settings())?;
+        (head_repo, Some(old_wc_commit))
+    };
     let destination_path = command.cwd().join(&args.destination);
     if destination_path.exists() {
         return Err(user_error("Workspace already exists"));
@@ -149,7 +193,6 @@ fn cmd_workspace_add(
             .to_string()
     };
     let workspace_id = WorkspaceId::new(name.clone());
-    let repo = old_workspace_command.repo();
     if repo.view().get_wc_commit_id(&workspace_id).is_some() {
         return Err(user_error(format!(
             "Workspace named '{name}' already exists"
@@ -160,7 +203,7 @@ fn cmd_workspace_add(
     let (new_workspace, repo) = Workspace::init_workspace_with_existing_repo(
         command.settings(),
         &destination_path,
-        repo,
+        &repo,
         working_copy_factory,
         workspace_id,
     )?;
@@ -172,7 +215,7 @@ fn cmd_workspace_add(
     )?;
 
     // Copy sparse patterns from workspace where the command was run
-    let mut new_workspace_command = WorkspaceCommandHelper::new(ui, command, new_workspace, repo)?;
+    let mut new_workspace_command = command.for_new_workspace(ui, new_workspace, repo)?;
     let (mut locked_ws, _wc_commit) = new_workspace_command.start_working_copy_mutation()?;
     let sparse_patterns = old_workspace_command
         .working_copy()
@@ -187,9 +230,12 @@ fn cmd_workspace_add(
 
     let mut tx = new_workspace_command.start_transaction();
 
-    // If no parent revisions are specified, create a working-copy commit based
-    // on the parent of the current working-copy commit.
-    let parents = if args.revision.is_empty() {
+    // If --at-op is specified, recreate the working-copy commit as of that
+    // operation. If no parent revisions are specified, create a working-copy
+    // commit based on the parent of the current working-copy commit.
+    let parents = if let Some(old_wc_commit) = &old_wc_commit_at_op {
+        old_wc_commit.parents()
+    } else if args.revision.is_empty() {
         // Check out parents of the current workspace's working-copy commit, or the
         // root if there is no working-copy commit in the current workspace.
         if let Some(old_wc_commit_id) = tx
@@ -207,12 +253,19 @@ fn cmd_workspace_add(
             .collect_vec()
     };
 
-    let tree = merge_commit_trees(tx.repo(), &parents)?;
+    let tree = if let Some(old_wc_commit) = &old_wc_commit_at_op {
+        old_wc_commit.tree()?
+    } else {
+        merge_commit_trees(tx.repo(), &parents)?
+    };
     let parent_ids = parents.iter().map(|c| c.id().clone()).collect_vec();
-    let new_wc_commit = tx
+    let mut commit_builder = tx
         .mut_repo()
-        .new_commit(command.settings(), parent_ids, tree.id())
-        .write()?;
+        .new_commit(command.settings(), parent_ids, tree.id());
+    if let Some(old_wc_commit) = &old_wc_commit_at_op {
+        commit_builder = commit_builder.set_description(old_wc_commit.description());
+    }
+    let new_wc_commit = commit_builder.write()?;
 
     tx.edit(&new_wc_commit)?;
     tx.finish(
@@ -301,6 +354,84 @@ fn cmd_workspace_root(
     Ok(())
 }
 
+#[instrument(skip_all)]
+fn cmd_workspace_move_change(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &WorkspaceMoveChangeArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit = resolve_other_workspace_wc_commit(&workspace_command, &args.workspace)?;
+    let wc_commit = workspace_command.resolve_single_rev("@")?;
+    if target_commit.id() == wc_commit.id() {
+        return Err(user_error(format!(
+            "Workspace {} is already editing the working-copy commit",
+            args.workspace
+        )));
+    }
+    workspace_command.check_rewritable([&wc_commit])?;
+
+    let mut tx = workspace_command.start_transaction();
+    let parents = wc_commit.parents();
+    let merged_tree = merge_commit_trees(tx.repo(), &parents)?;
+    let new_wc_commit = tx
+        .mut_repo()
+        .new_commit(
+            command.settings(),
+            wc_commit.parent_ids().to_vec(),
+            merged_tree.id(),
+        )
+        .write()?;
+    // The moved commit must not be abandoned as the commit we're leaving.
+    let workspace_id = tx.base_workspace_helper().workspace_id().clone();
+    tx.mut_repo()
+        .set_wc_commit(workspace_id, new_wc_commit.id().clone())?;
+    tx.mut_repo()
+        .edit(WorkspaceId::new(args.workspace.clone()), &wc_commit)?;
+    tx.finish(
+        ui,
+        format!(
+            "move working-copy commit {} to workspace {}",
+            wc_commit.id().hex(),
+            args.workspace
+        ),
+    )?;
+    // The other workspace's files can't be checked here, so it may have had
+    // unrecorded changes or have already been stale.
+    writeln!(
+        ui.warning_default(),
+        "The working copy of workspace {} is now stale",
+        args.workspace
+    )?;
+    writeln!(
+        ui.hint_default(),
+        "Run `jj workspace update-stale` in workspace {} before making changes there. Changes it \
+         hasn't recorded yet will be snapshotted into its previous working-copy commit.",
+        args.workspace
+    )?;
+    Ok(())
+}
+
+/// Looks up the working-copy commit of the workspace `name`, which must exist
+/// and must not be the current workspace.
+pub(crate) fn resolve_other_workspace_wc_commit(
+    workspace_command: &WorkspaceCommandHelper,
+    name: &str,
+) -> Result<Commit, CommandError> {
+    let workspace_id = WorkspaceId::new(name.to_owned());
+    if &workspace_id == workspace_command.workspace_id() {
+        return Err(user_error(format!(
+            "Workspace {name} is the current workspace"
+        )));
+    }
+    let repo = workspace_command.repo();
+    let commit_id = repo
+        .view()
+        .get_wc_commit_id(&workspace_id)
+        .ok_or_else(|| user_error(format!("No such workspace: {name}")))?;
+    Ok(repo.store().get_commit(commit_id)?)
+}
+
 fn create_and_check_out_recovery_commit(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -322,16 +453,15 @@ fn create_and_check_out_recovery_commit(
         )
         .write()?;
     mut_repo.set_wc_commit(workspace_id, new_commit.id().clone())?;
-    let repo = tx.commit("recovery commit");
+    let repo = tx.commit("recovery commit")?;
 
     locked_workspace.locked_wc().recover(&new_commit)?;
     locked_workspace.finish(repo.op_id().clone())?;
 
-    writeln!(
-        ui.stderr(),
-        "Created and checked out recovery commit {}",
-        short_commit_hash(new_commit.id())
-    )?;
+    let workspace_command = command.for_loaded_repo(ui, command.load_workspace()?, repo.clone())?;
+    write!(ui.stderr(), "Created and checked out recovery commit ")?;
+    workspace_command.write_commit_summary(ui.stderr_formatter().as_mut(), &new_commit)?;
+    writeln!(ui.stderr())?;
 
     Ok(repo)
 }
@@ -13,33 +13,48 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::cmp::max;
 use std::collections::HashMap;
-use std::io;
+use std::path::Path;
 use std::rc::Rc;
+use std::{io, slice};
 
 use itertools::Itertools as _;
-use jj_lib::backend::{ChangeId, CommitId};
+use jj_lib::backend::{BackendError, BackendResult, ChangeId, CommitId, Signature};
 use jj_lib::commit::Commit;
 use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::hex_util::to_reverse_hex;
 use jj_lib::id_prefix::IdPrefixContext;
+use jj_lib::matchers::{EverythingMatcher, Matcher};
+use jj_lib::merge::MergedTreeValue;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
-use jj_lib::op_store::{RefTarget, WorkspaceId};
+use jj_lib::op_store::{RefTarget, RemoteRef, WorkspaceId};
 use jj_lib::repo::Repo;
-use jj_lib::revset::{Revset, RevsetParseContext};
-use jj_lib::{git, rewrite};
+use jj_lib::repo_path::{RepoPathBuf, RepoPathUiConverter};
+use jj_lib::revset::{self, Revset, RevsetEvaluationError, RevsetExpression, RevsetParseContext};
+use jj_lib::revset_graph::{RevsetGraphEdge, RevsetGraphEdgeType};
+use jj_lib::signing::{SigStatus, Verification};
+use jj_lib::str_util::StringPattern;
+use jj_lib::tree::TreeMergeError;
+use jj_lib::{fileset, footer, git, graph, rewrite};
 use once_cell::unsync::OnceCell;
 
+use crate::diff_util::{self, DiffStats};
 use crate::formatter::Formatter;
 use crate::template_builder::{
-    self, merge_fn_map, BuildContext, CoreTemplateBuildFnTable, CoreTemplatePropertyKind,
-    IntoTemplateProperty, TemplateBuildMethodFnMap, TemplateLanguage,
+    self, merge_fn_map, sorted_fn_names, BuildContext, CoreTemplateBuildFnTable,
+    CoreTemplatePropertyKind, IntoTemplateProperty, TemplateBuildFunctionFnMap,
+    TemplateBuildMethodFnMap, TemplateLanguage,
+};
+use crate::template_parser::{
+    self, ExpressionNode, FunctionCallNode, TemplateParseError, TemplateParseResult,
 };
-use crate::template_parser::{self, FunctionCallNode, TemplateParseError, TemplateParseResult};
 use crate::templater::{
-    self, IntoTemplate, PlainTextFormattedProperty, Template, TemplateProperty,
-    TemplatePropertyError, TemplatePropertyExt as _,
+    self, FormattablePropertyTemplate, IntoJson, IntoTemplate, Literal, PlainTextFormattedProperty,
+    Template, TemplateProperty, TemplatePropertyError, TemplatePropertyExt as _, ToJson,
 };
 use crate::{revset_util, text_util};
 
@@ -51,6 +66,7 @@ pub trait CommitTemplateLanguageExtension {
 
 pub struct CommitTemplateLanguage<'repo> {
     repo: &'repo dyn Repo,
+    path_converter: &'repo RepoPathUiConverter,
     workspace_id: WorkspaceId,
     // RevsetParseContext doesn't borrow a repo, but we'll need 'repo lifetime
     // anyway to capture it to evaluate dynamically-constructed user expression
@@ -61,6 +77,7 @@ pub struct CommitTemplateLanguage<'repo> {
     id_prefix_context: &'repo IdPrefixContext,
     build_fn_table: CommitTemplateBuildFnTable<'repo>,
     keyword_cache: CommitKeywordCache,
+    build_cache: CommitBuildCache<'repo>,
     cache_extensions: ExtensionsMap,
 }
 
@@ -69,6 +86,7 @@ impl<'repo> CommitTemplateLanguage<'repo> {
     /// evaluation tree.
     pub fn new(
         repo: &'repo dyn Repo,
+        path_converter: &'repo RepoPathUiConverter,
         workspace_id: &WorkspaceId,
         revset_parse_context: RevsetParseContext<'repo>,
         id_prefix_context: &'repo IdPrefixContext,
@@ -86,11 +104,13 @@ impl<'repo> CommitTemplateLanguage<'repo> {
 
         CommitTemplateLanguage {
             repo,
+            path_converter,
             workspace_id: workspace_id.clone(),
             revset_parse_context,
             id_prefix_context,
             build_fn_table,
             keyword_cache: CommitKeywordCache::default(),
+            build_cache: CommitBuildCache::default(),
             cache_extensions,
         }
     }
@@ -177,6 +197,88 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method("ShortestIdPrefix", table, function)?;
                 build(self, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::RepoPath(property) => {
+                let table = &self.build_fn_table.repo_path_methods;
+                let build = template_parser::lookup_method("RepoPath", table, function)?;
+                build(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::RepoPathOpt(property) => {
+                let table = &self.build_fn_table.repo_path_methods;
+                let build = template_parser::lookup_method("RepoPath", table, function)?;
+                let inner_property = property.and_then(|opt| {
+                    opt.ok_or_else(|| TemplatePropertyError("No RepoPath available".into()))
+                });
+                build(self, build_ctx, Box::new(inner_property), function)
+            }
+            CommitTemplatePropertyKind::RepoPathList(property) => {
+                // TODO: migrate to table?
+                template_builder::build_formattable_list_method(
+                    self,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_repo_path,
+                )
+            }
+            CommitTemplatePropertyKind::Workspace(property) => {
+                let table = &self.build_fn_table.workspace_methods;
+                let build = template_parser::lookup_method("Workspace", table, function)?;
+                build(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::WorkspaceList(property) => {
+                // TODO: migrate to table?
+                template_builder::build_formattable_list_method(
+                    self,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_workspace,
+                )
+            }
+            CommitTemplatePropertyKind::FileChange(property) => {
+                let table = &self.build_fn_table.file_change_methods;
+                let build = template_parser::lookup_method("FileChange", table, function)?;
+                build(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::FileChangeList(property) => {
+                // TODO: migrate to table?
+                template_builder::build_formattable_list_method(
+                    self,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_file_change,
+                )
+            }
+            CommitTemplatePropertyKind::Trailer(property) => {
+                let table = &self.build_fn_table.trailer_methods;
+                let build = template_parser::lookup_method("Trailer", table, function)?;
+                build(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                if function.name == "contains_key" {
+                    build_trailer_list_contains_key(self, build_ctx, property, function)
+                } else {
+                    template_builder::build_formattable_list_method(
+                        self,
+                        build_ctx,
+                        property,
+                        function,
+                        Self::wrap_trailer,
+                    )
+                }
+            }
+            CommitTemplatePropertyKind::TreeDiff(property) => {
+                let table = &self.build_fn_table.tree_diff_methods;
+                let build = template_parser::lookup_method("TreeDiff", table, function)?;
+                build(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::CryptographicSignature(property) => {
+                let table = &self.build_fn_table.cryptographic_signature_methods;
+                let build =
+                    template_parser::lookup_method("CryptographicSignature", table, function)?;
+                build(self, build_ctx, property, function)
+            }
         }
     }
 }
@@ -188,6 +290,10 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         self.repo
     }
 
+    pub fn path_converter(&self) -> &'repo RepoPathUiConverter {
+        self.path_converter
+    }
+
     pub fn workspace_id(&self) -> &WorkspaceId {
         &self.workspace_id
     }
@@ -196,6 +302,11 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         &self.keyword_cache
     }
 
+    /// Function table including the methods added by the extension.
+    pub fn build_fn_table(&self) -> &CommitTemplateBuildFnTable<'repo> {
+        &self.build_fn_table
+    }
+
     pub fn cache_extension<T: Any>(&self) -> Option<&T> {
         self.cache_extensions.get::<T>()
     }
@@ -247,6 +358,72 @@ impl<'repo> CommitTemplateLanguage<'repo> {
     ) -> CommitTemplatePropertyKind<'repo> {
         CommitTemplatePropertyKind::ShortestIdPrefix(Box::new(property))
     }
+
+    pub fn wrap_repo_path(
+        property: impl TemplateProperty<Output = RepoPathBuf> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::RepoPath(Box::new(property))
+    }
+
+    pub fn wrap_repo_path_opt(
+        property: impl TemplateProperty<Output = Option<RepoPathBuf>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::RepoPathOpt(Box::new(property))
+    }
+
+    pub fn wrap_repo_path_list(
+        property: impl TemplateProperty<Output = Vec<RepoPathBuf>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::RepoPathList(Box::new(property))
+    }
+
+    pub fn wrap_workspace(
+        property: impl TemplateProperty<Output = WorkspaceRef> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::Workspace(Box::new(property))
+    }
+
+    pub fn wrap_workspace_list(
+        property: impl TemplateProperty<Output = Vec<WorkspaceRef>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::WorkspaceList(Box::new(property))
+    }
+
+    pub fn wrap_file_change(
+        property: impl TemplateProperty<Output = FileChange> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::FileChange(Box::new(property))
+    }
+
+    pub fn wrap_file_change_list(
+        property: impl TemplateProperty<Output = Vec<FileChange>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::FileChangeList(Box::new(property))
+    }
+
+    pub fn wrap_trailer(
+        property: impl TemplateProperty<Output = Trailer> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::Trailer(Box::new(property))
+    }
+
+    pub fn wrap_trailer_list(
+        property: impl TemplateProperty<Output = Vec<Trailer>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TrailerList(Box::new(property))
+    }
+
+    pub fn wrap_tree_diff(
+        property: impl TemplateProperty<Output = TreeDiff> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TreeDiff(Box::new(property))
+    }
+
+    pub fn wrap_cryptographic_signature(
+        property: impl TemplateProperty<Output = CryptographicSignature> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::CryptographicSignature(Box::new(property))
+    }
 }
 
 pub enum CommitTemplatePropertyKind<'repo> {
@@ -259,6 +436,17 @@ pub enum CommitTemplatePropertyKind<'repo> {
     RefNameList(Box<dyn TemplateProperty<Output = Vec<RefName>> + 'repo>),
     CommitOrChangeId(Box<dyn TemplateProperty<Output = CommitOrChangeId> + 'repo>),
     ShortestIdPrefix(Box<dyn TemplateProperty<Output = ShortestIdPrefix> + 'repo>),
+    RepoPath(Box<dyn TemplateProperty<Output = RepoPathBuf> + 'repo>),
+    RepoPathOpt(Box<dyn TemplateProperty<Output = Option<RepoPathBuf>> + 'repo>),
+    RepoPathList(Box<dyn TemplateProperty<Output = Vec<RepoPathBuf>> + 'repo>),
+    Workspace(Box<dyn TemplateProperty<Output = WorkspaceRef> + 'repo>),
+    WorkspaceList(Box<dyn TemplateProperty<Output = Vec<WorkspaceRef>> + 'repo>),
+    FileChange(Box<dyn TemplateProperty<Output = FileChange> + 'repo>),
+    FileChangeList(Box<dyn TemplateProperty<Output = Vec<FileChange>> + 'repo>),
+    Trailer(Box<dyn TemplateProperty<Output = Trailer> + 'repo>),
+    TrailerList(Box<dyn TemplateProperty<Output = Vec<Trailer>> + 'repo>),
+    TreeDiff(Box<dyn TemplateProperty<Output = TreeDiff> + 'repo>),
+    CryptographicSignature(Box<dyn TemplateProperty<Output = CryptographicSignature> + 'repo>),
 }
 
 impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
@@ -281,6 +469,27 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             }
             CommitTemplatePropertyKind::CommitOrChangeId(_) => None,
             CommitTemplatePropertyKind::ShortestIdPrefix(_) => None,
+            CommitTemplatePropertyKind::RepoPath(_) => None,
+            CommitTemplatePropertyKind::RepoPathOpt(property) => {
+                Some(Box::new(property.map(|opt| opt.is_some())))
+            }
+            CommitTemplatePropertyKind::RepoPathList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
+            CommitTemplatePropertyKind::Workspace(_) => None,
+            CommitTemplatePropertyKind::WorkspaceList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
+            CommitTemplatePropertyKind::FileChange(_) => None,
+            CommitTemplatePropertyKind::FileChangeList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
+            CommitTemplatePropertyKind::Trailer(_) => None,
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
+            CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::CryptographicSignature(_) => None,
         }
     }
 
@@ -316,10 +525,123 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::ShortestIdPrefix(property) => {
                 Some(property.into_template())
             }
+            CommitTemplatePropertyKind::RepoPath(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::RepoPathOpt(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::RepoPathList(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::Workspace(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::WorkspaceList(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::FileChange(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::FileChangeList(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::Trailer(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::TrailerList(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::CryptographicSignature(_) => None,
+        }
+    }
+
+    fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'repo>> {
+        match self {
+            CommitTemplatePropertyKind::Core(property) => property.try_into_json(),
+            CommitTemplatePropertyKind::Commit(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::CommitOpt(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::CommitList(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RefName(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RefNameOpt(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RefNameList(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::CommitOrChangeId(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::ShortestIdPrefix(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RepoPath(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RepoPathOpt(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RepoPathList(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::Workspace(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::WorkspaceList(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::FileChange(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::FileChangeList(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::Trailer(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::TrailerList(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::CryptographicSignature(_) => None,
+        }
+    }
+
+    fn with_error_name(self, name: &str) -> Self {
+        match self {
+            CommitTemplatePropertyKind::Core(property) => {
+                CommitTemplatePropertyKind::Core(property.with_error_name(name))
+            }
+            CommitTemplatePropertyKind::Commit(property) => {
+                CommitTemplatePropertyKind::Commit(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::CommitOpt(property) => {
+                CommitTemplatePropertyKind::CommitOpt(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::CommitList(property) => {
+                CommitTemplatePropertyKind::CommitList(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::RefName(property) => {
+                CommitTemplatePropertyKind::RefName(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::RefNameOpt(property) => {
+                CommitTemplatePropertyKind::RefNameOpt(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::RefNameList(property) => {
+                CommitTemplatePropertyKind::RefNameList(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::CommitOrChangeId(property) => {
+                CommitTemplatePropertyKind::CommitOrChangeId(Box::new(
+                    property.with_error_name(name),
+                ))
+            }
+            CommitTemplatePropertyKind::ShortestIdPrefix(property) => {
+                CommitTemplatePropertyKind::ShortestIdPrefix(Box::new(
+                    property.with_error_name(name),
+                ))
+            }
+            CommitTemplatePropertyKind::RepoPath(property) => {
+                CommitTemplatePropertyKind::RepoPath(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::RepoPathOpt(property) => {
+                CommitTemplatePropertyKind::RepoPathOpt(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::RepoPathList(property) => {
+                CommitTemplatePropertyKind::RepoPathList(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::Workspace(property) => {
+                CommitTemplatePropertyKind::Workspace(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::WorkspaceList(property) => {
+                CommitTemplatePropertyKind::WorkspaceList(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::FileChange(property) => {
+                CommitTemplatePropertyKind::FileChange(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::FileChangeList(property) => {
+                CommitTemplatePropertyKind::FileChangeList(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::Trailer(property) => {
+                CommitTemplatePropertyKind::Trailer(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                CommitTemplatePropertyKind::TrailerList(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::TreeDiff(property) => {
+                CommitTemplatePropertyKind::TreeDiff(Box::new(property.with_error_name(name)))
+            }
+            CommitTemplatePropertyKind::CryptographicSignature(property) => {
+                CommitTemplatePropertyKind::CryptographicSignature(Box::new(
+                    property.with_error_name(name),
+                ))
+            }
         }
     }
 }
 
+/// Table of functions that translate global function call node.
+pub type CommitTemplateBuildFunctionFnMap<'repo> =
+    TemplateBuildFunctionFnMap<'repo, CommitTemplateLanguage<'repo>>;
+
 /// Table of functions that translate method call node of self type `T`.
 pub type CommitTemplateBuildMethodFnMap<'repo, T> =
     TemplateBuildMethodFnMap<'repo, CommitTemplateLanguage<'repo>, T>;
@@ -331,17 +653,33 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub ref_name_methods: CommitTemplateBuildMethodFnMap<'repo, RefName>,
     pub commit_or_change_id_methods: CommitTemplateBuildMethodFnMap<'repo, CommitOrChangeId>,
     pub shortest_id_prefix_methods: CommitTemplateBuildMethodFnMap<'repo, ShortestIdPrefix>,
+    pub repo_path_methods: CommitTemplateBuildMethodFnMap<'repo, RepoPathBuf>,
+    pub workspace_methods: CommitTemplateBuildMethodFnMap<'repo, WorkspaceRef>,
+    pub file_change_methods: CommitTemplateBuildMethodFnMap<'repo, FileChange>,
+    pub trailer_methods: CommitTemplateBuildMethodFnMap<'repo, Trailer>,
+    pub tree_diff_methods: CommitTemplateBuildMethodFnMap<'repo, TreeDiff>,
+    pub cryptographic_signature_methods:
+        CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature>,
 }
 
 impl<'repo> CommitTemplateBuildFnTable<'repo> {
     /// Creates new symbol table containing the builtin methods.
-    fn builtin() -> Self {
+    pub fn builtin() -> Self {
+        let mut core = CoreTemplateBuildFnTable::builtin();
+        merge_fn_map(&mut core.functions, builtin_commit_functions());
+        merge_fn_map(&mut core.signature_methods, builtin_signature_methods());
         CommitTemplateBuildFnTable {
-            core: CoreTemplateBuildFnTable::builtin(),
+            core,
             commit_methods: builtin_commit_methods(),
             ref_name_methods: builtin_ref_name_methods(),
             commit_or_change_id_methods: builtin_commit_or_change_id_methods(),
             shortest_id_prefix_methods: builtin_shortest_id_prefix_methods(),
+            repo_path_methods: builtin_repo_path_methods(),
+            workspace_methods: builtin_workspace_methods(),
+            file_change_methods: builtin_file_change_methods(),
+            trailer_methods: builtin_trailer_methods(),
+            tree_diff_methods: builtin_tree_diff_methods(),
+            cryptographic_signature_methods: builtin_cryptographic_signature_methods(),
         }
     }
 
@@ -352,9 +690,42 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             ref_name_methods: HashMap::new(),
             commit_or_change_id_methods: HashMap::new(),
             shortest_id_prefix_methods: HashMap::new(),
+            repo_path_methods: HashMap::new(),
+            workspace_methods: HashMap::new(),
+            file_change_methods: HashMap::new(),
+            trailer_methods: HashMap::new(),
+            tree_diff_methods: HashMap::new(),
+            cryptographic_signature_methods: HashMap::new(),
         }
     }
 
+    /// Returns the method names registered for each type.
+    pub fn method_names(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        let mut names = self.core.method_names();
+        names.extend([
+            ("Commit", sorted_fn_names(&self.commit_methods)),
+            ("RefName", sorted_fn_names(&self.ref_name_methods)),
+            (
+                "CommitOrChangeId",
+                sorted_fn_names(&self.commit_or_change_id_methods),
+            ),
+            (
+                "ShortestIdPrefix",
+                sorted_fn_names(&self.shortest_id_prefix_methods),
+            ),
+            ("RepoPath", sorted_fn_names(&self.repo_path_methods)),
+            ("Workspace", sorted_fn_names(&self.workspace_methods)),
+            ("FileChange", sorted_fn_names(&self.file_change_methods)),
+            ("Trailer", sorted_fn_names(&self.trailer_methods)),
+            ("TreeDiff", sorted_fn_names(&self.tree_diff_methods)),
+            (
+                "CryptographicSignature",
+                sorted_fn_names(&self.cryptographic_signature_methods),
+            ),
+        ]);
+        names
+    }
+
     fn merge(&mut self, extension: CommitTemplateBuildFnTable<'repo>) {
         let CommitTemplateBuildFnTable {
             core,
@@ -362,6 +733,12 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             ref_name_methods,
             commit_or_change_id_methods,
             shortest_id_prefix_methods,
+            repo_path_methods,
+            workspace_methods,
+            file_change_methods,
+            trailer_methods,
+            tree_diff_methods,
+            cryptographic_signature_methods,
         } = extension;
 
         self.core.merge(core);
@@ -375,6 +752,15 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             &mut self.shortest_id_prefix_methods,
             shortest_id_prefix_methods,
         );
+        merge_fn_map(&mut self.repo_path_methods, repo_path_methods);
+        merge_fn_map(&mut self.workspace_methods, workspace_methods);
+        merge_fn_map(&mut self.file_change_methods, file_change_methods);
+        merge_fn_map(&mut self.trailer_methods, trailer_methods);
+        merge_fn_map(&mut self.tree_diff_methods, tree_diff_methods);
+        merge_fn_map(
+            &mut self.cryptographic_signature_methods,
+            cryptographic_signature_methods,
+        );
     }
 }
 
@@ -403,6 +789,50 @@ impl CommitKeywordCache {
     }
 }
 
+impl ToJson for Commit {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let parent_ids: Vec<_> = self.parent_ids().iter().map(|id| id.hex()).collect();
+        Ok(serde_json::json!({
+            "author": self.author().to_json()?,
+            "change_id": CommitOrChangeId::Change(self.change_id().clone()).hex(),
+            "commit_id": self.id().hex(),
+            "committer": self.committer().to_json()?,
+            "description": self.description(),
+            "parents": parent_ids,
+        }))
+    }
+}
+
+/// Tests whether a commit is a member of an evaluated revset.
+type IsMemberFn<'repo> = dyn Fn(&CommitId) -> bool + 'repo;
+
+/// Values which depend only on the repo, computed while building the template.
+///
+/// They are shared by all properties referring to the same value, so that e.g.
+/// the immutable set is evaluated once per template even if `immutable` appears
+/// in several places.
+#[derive(Default)]
+struct CommitBuildCache<'repo> {
+    is_immutable_fn: OnceCell<Rc<IsMemberFn<'repo>>>,
+    // Keyed by the text of the revset argument.
+    descendant_counts: RefCell<HashMap<String, Rc<DescendantCounts<'repo>>>>,
+    ancestor_distances: RefCell<HashMap<String, Rc<AncestorDistances<'repo>>>>,
+}
+
+/// Looks up `key` in the `cache`, or inserts the value built by `f`.
+fn get_or_try_insert_cached<T: ?Sized>(
+    cache: &RefCell<HashMap<String, Rc<T>>>,
+    key: &str,
+    f: impl FnOnce() -> TemplateParseResult<Rc<T>>,
+) -> TemplateParseResult<Rc<T>> {
+    if let Some(value) = cache.borrow().get(key) {
+        return Ok(value.clone());
+    }
+    let value = f()?;
+    cache.borrow_mut().insert(key.to_owned(), value.clone());
+    Ok(value)
+}
+
 fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Commit> {
     type L<'repo> = CommitTemplateLanguage<'repo>;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -417,6 +847,34 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_string(out_property))
         },
     );
+    map.insert(
+        "gerrit_change_id",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|commit| {
+                footer::find_change_id(commit.description())
+                    .map(Cow::into_owned)
+                    .unwrap_or_default()
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "signature",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|commit| CryptographicSignature { commit });
+            Ok(L::wrap_cryptographic_signature(out_property))
+        },
+    );
+    map.insert(
+        "trailers",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|commit| Trailer::parse_all(commit.description()));
+            Ok(L::wrap_trailer_list(out_property))
+        },
+    );
     map.insert(
         "change_id",
         |_language, _build_ctx, self_property, function| {
@@ -468,6 +926,15 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_string(out_property))
         },
     );
+    map.insert(
+        "workspaces",
+        |language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let repo = language.repo;
+            let out_property = self_property.map(|commit| extract_workspaces(repo, &commit));
+            Ok(L::wrap_workspace_list(out_property))
+        },
+    );
     map.insert(
         "current_working_copy",
         |language, _build_ctx, self_property, function| {
@@ -578,12 +1045,65 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         "immutable",
         |language, _build_ctx, self_property, function| {
             template_parser::expect_no_arguments(function)?;
-            let revset = evaluate_immutable_revset(language, function.name_span)?;
-            let is_immutable = revset.containing_fn();
+            let is_immutable = language
+                .build_cache
+                .is_immutable_fn
+                .get_or_try_init(|| {
+                    let revset = evaluate_immutable_revset(language, function.name_span)?;
+                    Ok::<_, TemplateParseError>(Rc::from(revset.containing_fn()))
+                })?
+                .clone();
             let out_property = self_property.map(move |commit| is_immutable(commit.id()));
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "num_descendants",
+        |language, _build_ctx, self_property, function| {
+            let [within_node] = template_parser::expect_exact_arguments(function)?;
+            let counts = get_or_try_insert_cached(
+                &language.build_cache.descendant_counts,
+                &expect_revset_text(within_node)?,
+                || {
+                    let within = evaluate_revset_argument(language, within_node)?;
+                    Ok(Rc::new(DescendantCounts::new(within.as_ref())))
+                },
+            )?;
+            let repo = language.repo;
+            let out_property = self_property.and_then(move |commit| {
+                let count = counts.get(repo, commit.id())?;
+                Ok(i64::try_from(count)?)
+            });
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "distance_to",
+        |language, _build_ctx, self_property, function| {
+            let [target_node] = template_parser::expect_exact_arguments(function)?;
+            let distances = get_or_try_insert_cached(
+                &language.build_cache.ancestor_distances,
+                &expect_revset_text(target_node)?,
+                || {
+                    let target = evaluate_revset_argument(language, target_node)?;
+                    let distances = AncestorDistances::new(language.repo, target.as_ref())
+                        .map_err(|err| {
+                            TemplateParseError::unexpected_expression(
+                                err.to_string(),
+                                target_node.span,
+                            )
+                        })?;
+                    Ok(Rc::new(distances))
+                },
+            )?;
+            let repo = language.repo;
+            let out_property = self_property.and_then(move |commit| {
+                let distance = distances.get(repo, commit.id())?;
+                Ok(i64::try_from(distance)?)
+            });
+            Ok(L::wrap_integer(out_property))
+        },
+    );
     map.insert(
         "conflict",
         |_language, _build_ctx, self_property, function| {
@@ -595,15 +1115,81 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
     map.insert("empty", |language, _build_ctx, self_property, function| {
         template_parser::expect_no_arguments(function)?;
         let repo = language.repo;
-        let out_property = self_property.and_then(|commit| {
-            if let [parent] = &commit.parents()[..] {
-                return Ok(parent.tree_id() == commit.tree_id());
-            }
-            let parent_tree = rewrite::merge_commit_trees(repo, &commit.parents())?;
-            Ok(*commit.tree_id() == parent_tree.id())
-        });
+        let out_property = self_property.and_then(|commit| Ok(commit.is_empty(repo)?));
         Ok(L::wrap_boolean(out_property))
     });
+    map.insert(
+        "matches",
+        |language, _build_ctx, self_property, function| {
+            let [fileset_node] = template_parser::expect_exact_arguments(function)?;
+            let matcher = parse_fileset_argument(language, fileset_node)?;
+            let repo = language.repo;
+            let out_property = self_property.and_then(move |commit| {
+                let mut paths = changed_paths(repo, &commit, matcher.as_ref())?;
+                Ok(paths.next().transpose()?.is_some())
+            });
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map.insert("files", |language, _build_ctx, self_property, function| {
+        let ([], [fileset_node]) = template_parser::expect_arguments(function)?;
+        let matcher = parse_optional_fileset_argument(language, fileset_node)?;
+        let repo = language.repo;
+        let out_property = self_property.and_then(move |commit| {
+            let paths: Vec<_> = changed_paths(repo, &commit, matcher.as_ref())?.try_collect()?;
+            Ok(paths)
+        });
+        Ok(L::wrap_repo_path_list(out_property))
+    });
+    map.insert(
+        "changes",
+        |language, _build_ctx, self_property, function| {
+            let ([], [fileset_node]) = template_parser::expect_arguments(function)?;
+            let matcher = parse_optional_fileset_argument(language, fileset_node)?;
+            let repo = language.repo;
+            let out_property = self_property.and_then(move |commit| {
+                let from_tree = rewrite::merge_commit_trees(repo, &commit.parents())?;
+                let to_tree = commit.tree()?;
+                let changes: Vec<_> = from_tree
+                    .diff(&to_tree, matcher.as_ref())
+                    .map(|(path, diff)| {
+                        let (before, after) = diff?;
+                        Ok::<_, BackendError>(FileChange::new(path, &before, &after))
+                    })
+                    .try_collect()?;
+                Ok(changes)
+            });
+            Ok(L::wrap_file_change_list(out_property))
+        },
+    );
+    map.insert("diff", |language, _build_ctx, self_property, function| {
+        let ([], [fileset_node]) = template_parser::expect_arguments(function)?;
+        let matcher: Rc<dyn Matcher> =
+            parse_optional_fileset_argument(language, fileset_node)?.into();
+        let repo = language.repo;
+        let out_property = self_property
+            .and_then(move |commit| Ok(TreeDiff::from_commit(repo, &commit, matcher.clone())?));
+        Ok(L::wrap_tree_diff(out_property))
+    });
+    map.insert(
+        "conflicted_files",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.and_then(|commit| {
+                let paths: Vec<_> = commit.tree()?.conflicts().map(|(path, _)| path).collect();
+                Ok(paths)
+            });
+            Ok(L::wrap_repo_path_list(out_property))
+        },
+    );
+    map.insert("extra", |language, build_ctx, self_property, function| {
+        let [key_node] = template_parser::expect_exact_arguments(function)?;
+        let key_property =
+            template_builder::expect_plain_text_expression(language, build_ctx, key_node)?;
+        let out_property = (self_property, key_property)
+            .map(|(commit, key)| commit.extra().get(&key).cloned().unwrap_or_default());
+        Ok(L::wrap_string(out_property))
+    });
     map.insert("root", |language, _build_ctx, self_property, function| {
         template_parser::expect_no_arguments(function)?;
         let repo = language.repo;
@@ -628,6 +1214,18 @@ fn extract_working_copies(repo: &dyn Repo, commit: &Commit) -> String {
     names.join(" ")
 }
 
+fn extract_workspaces(repo: &dyn Repo, commit: &Commit) -> Vec<WorkspaceRef> {
+    repo.view()
+        .wc_commit_ids()
+        .iter()
+        .filter(|(_, wc_commit_id)| *wc_commit_id == commit.id())
+        .map(|(workspace_id, _)| WorkspaceRef {
+            id: workspace_id.clone(),
+        })
+        .sorted()
+        .collect()
+}
+
 fn evaluate_immutable_revset<'repo>(
     language: &CommitTemplateLanguage<'repo>,
     span: pest::Span<'_>,
@@ -646,59 +1244,738 @@ fn evaluate_immutable_revset<'repo>(
     Ok(revset)
 }
 
-/// Branch or tag name with metadata.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct RefName {
-    /// Local name.
-    name: String,
-    /// Remote name if this is a remote or Git-tracking ref.
-    remote: Option<String>,
-    /// Ref target has conflicts.
-    conflict: bool,
-    /// Local ref is synchronized with all tracking remotes, or tracking remote
-    /// ref is synchronized with the local.
-    synced: bool,
+/// Extracts the text of the string literal `node`, which is used as the key of
+/// the cached revset evaluation results.
+fn expect_revset_text(node: &ExpressionNode) -> TemplateParseResult<String> {
+    template_parser::expect_string_literal_with(node, |text, _span| Ok(text.to_owned()))
 }
 
-impl RefName {
-    fn is_local(&self) -> bool {
-        self.remote.is_none()
-    }
+/// Evaluates the string literal `node` as a user revset expression.
+fn evaluate_revset_argument<'repo>(
+    language: &CommitTemplateLanguage<'repo>,
+    node: &ExpressionNode,
+) -> TemplateParseResult<Box<dyn Revset + 'repo>> {
+    let repo = language.repo;
+    template_parser::expect_string_literal_with(node, |text, span| {
+        let expression = revset::parse(text, &language.revset_parse_context).map_err(|err| {
+            TemplateParseError::unexpected_expression(revset_util::format_parse_error(&err), span)
+        })?;
+        let symbol_resolver =
+            revset_util::default_symbol_resolver(repo, language.id_prefix_context);
+        revset_util::evaluate(repo, &symbol_resolver, expression)
+            .map_err(|err| TemplateParseError::unexpected_expression(err.to_string(), span))
+    })
+}
 
-    fn is_remote(&self) -> bool {
-        self.remote.is_some()
-    }
+/// Parses the string literal `node` as a fileset expression.
+fn parse_fileset_argument(
+    language: &CommitTemplateLanguage,
+    node: &ExpressionNode,
+) -> TemplateParseResult<Box<dyn Matcher>> {
+    template_parser::expect_string_literal_with(node, |text, span| {
+        let expression = fileset::parse(text, language.path_converter)
+            .map_err(|err| TemplateParseError::unexpected_expression(err.to_string(), span))?;
+        Ok(expression.to_matcher())
+    })
 }
 
-impl Template for RefName {
-    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
-        write!(formatter.labeled("name"), "{}", self.name)?;
-        if let Some(remote) = &self.remote {
-            write!(formatter, "@")?;
-            write!(formatter.labeled("remote"), "{remote}")?;
-        }
-        // Don't show both conflict and unsynced sigils as conflicted ref wouldn't
-        // be pushed.
-        if self.conflict {
-            write!(formatter, "??")?;
-        } else if self.is_local() && !self.synced {
-            write!(formatter, "*")?;
-        }
-        Ok(())
+/// Parses the fileset argument if specified, or matches all files otherwise.
+fn parse_optional_fileset_argument(
+    language: &CommitTemplateLanguage,
+    node: Option<&ExpressionNode>,
+) -> TemplateParseResult<Box<dyn Matcher>> {
+    match node {
+        Some(node) => parse_fileset_argument(language, node),
+        None => Ok(Box::new(EverythingMatcher)),
     }
 }
 
-impl Template for Vec<RefName> {
-    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
-        templater::format_joined(formatter, self, " ")
-    }
+/// Iterates paths changed by the `commit` compared to its parents.
+fn changed_paths<'a>(
+    repo: &dyn Repo,
+    commit: &Commit,
+    matcher: &'a dyn Matcher,
+) -> Result<impl Iterator<Item = BackendResult<RepoPathBuf>> + 'a, TemplatePropertyError> {
+    let from_tree = rewrite::merge_commit_trees(repo, &commit.parents())?;
+    let to_tree = commit.tree()?;
+    Ok(from_tree
+        .diff(&to_tree, matcher)
+        .map(|(path, diff)| diff.map(|_| path)))
 }
 
-fn builtin_ref_name_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, RefName> {
-    type L<'repo> = CommitTemplateLanguage<'repo>;
-    // Not using maplit::hashmap!{} or custom declarative macro here because
-    // code completion inside macro is quite restricted.
-    let mut map = CommitTemplateBuildMethodFnMap::<RefName>::new();
+/// Numbers of descendants of commits within a set of commits.
+///
+/// The counts for the members of the set are calculated incrementally by
+/// walking the set in topological order, as far as needed to reach the
+/// requested commit. Other commits are looked up individually.
+struct DescendantCounts<'repo> {
+    is_member: Box<IsMemberFn<'repo>>,
+    walk: RefCell<DescendantCountsWalk<'repo>>,
+    other_counts: RefCell<HashMap<CommitId, usize>>,
+}
+
+/// State of the topological walk over the members of the set.
+struct DescendantCountsWalk<'repo> {
+    /// Members of the set not yet visited (children first), and their edges.
+    graph_iter: Box<dyn Iterator<Item = (CommitId, Vec<RevsetGraphEdge>)> + 'repo>,
+    /// Number of visited members, which is the bit position of the next member.
+    next_pos: usize,
+    /// Bit sets of the visited descendants of the members not yet visited.
+    pending: HashMap<CommitId, Vec<u64>>,
+    counts: HashMap<CommitId, usize>,
+}
+
+impl<'repo> DescendantCounts<'repo> {
+    fn new(within: &(dyn Revset + 'repo)) -> Self {
+        let walk = DescendantCountsWalk {
+            graph_iter: within.iter_graph(),
+            next_pos: 0,
+            pending: HashMap::new(),
+            counts: HashMap::new(),
+        };
+        DescendantCounts {
+            is_member: within.containing_fn(),
+            walk: RefCell::new(walk),
+            other_counts: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Number of descendants of `id` in the set, excluding `id` itself.
+    fn get(&self, repo: &dyn Repo, id: &CommitId) -> Result<usize, RevsetEvaluationError> {
+        if (self.is_member)(id) {
+            if let Some(count) = self.walk.borrow_mut().walk_to(id) {
+                return Ok(count);
+            }
+        }
+        if let Some(&count) = self.other_counts.borrow().get(id) {
+            return Ok(count);
+        }
+        let count = RevsetExpression::commit(id.clone())
+            .descendants()
+            .evaluate_programmatic(repo)?
+            .iter()
+            .filter(|descendant_id| descendant_id != id && (self.is_member)(descendant_id))
+            .count();
+        self.other_counts.borrow_mut().insert(id.clone(), count);
+        Ok(count)
+    }
+}
+
+impl DescendantCountsWalk<'_> {
+    /// Visits members until `id` is counted. Returns `None` if `id` isn't
+    /// emitted by the walk.
+    fn walk_to(&mut self, id: &CommitId) -> Option<usize> {
+        while !self.counts.contains_key(id) {
+            let (member_id, edges) = self.graph_iter.next()?;
+            self.visit(member_id, edges);
+        }
+        self.counts.get(id).copied()
+    }
+
+    /// Counts descendants of the member by its bit set, which is complete since
+    /// children precede parents, and propagates the bits to the parents.
+    fn visit(&mut self, id: CommitId, edges: Vec<RevsetGraphEdge>) {
+        let pos = self.next_pos;
+        self.next_pos += 1;
+        let mut bits = self.pending.remove(&id).unwrap_or_default();
+        let count = bits.iter().map(|word| word.count_ones() as usize).sum();
+        self.counts.insert(id, count);
+        bits.resize(pos / 64 + 1, 0);
+        bits[pos / 64] |= 1 << (pos % 64);
+        for edge in edges {
+            if edge.edge_type == RevsetGraphEdgeType::Missing {
+                continue;
+            }
+            let parent_bits = self.pending.entry(edge.target).or_default();
+            if parent_bits.len() < bits.len() {
+                parent_bits.resize(bits.len(), 0);
+            }
+            for (dest, src) in parent_bits.iter_mut().zip(&bits) {
+                *dest |= src;
+            }
+        }
+    }
+}
+
+/// Numbers of commits between target commits and other commits, i.e. the
+/// size of `target..commit`.
+///
+/// The distance of a commit with a single parent is derived from the parent's
+/// distance, so walking a stack of commits costs linear time.
+struct AncestorDistances<'repo> {
+    target_ids: Vec<CommitId>,
+    is_target_ancestor: Box<IsMemberFn<'repo>>,
+    distances: RefCell<HashMap<CommitId, usize>>,
+}
+
+impl<'repo> AncestorDistances<'repo> {
+    fn new(repo: &'repo dyn Repo, target: &dyn Revset) -> Result<Self, RevsetEvaluationError> {
+        let target_ids = target.iter().collect_vec();
+        let target_ancestors = RevsetExpression::commits(target_ids.clone())
+            .ancestors()
+            .evaluate_programmatic(repo)?;
+        Ok(AncestorDistances {
+            target_ids,
+            is_target_ancestor: target_ancestors.containing_fn(),
+            distances: RefCell::new(HashMap::new()),
+        })
+    }
+
+    fn get(&self, repo: &dyn Repo, id: &CommitId) -> Result<usize, TemplatePropertyError> {
+        let mut chain = vec![];
+        let mut id = id.clone();
+        let mut distance = loop {
+            if let Some(&distance) = self.distances.borrow().get(&id) {
+                break distance;
+            }
+            if (self.is_target_ancestor)(&id) {
+                break 0;
+            }
+            let commit = repo.store().get_commit(&id)?;
+            if let [parent_id] = commit.parent_ids() {
+                chain.push(id);
+                id = parent_id.clone();
+                continue;
+            }
+            // Merge commits can share ancestors through their parents.
+            let distance = RevsetExpression::commits(self.target_ids.clone())
+                .range(&RevsetExpression::commit(id.clone()))
+                .evaluate_programmatic(repo)?
+                .iter()
+                .count();
+            break distance;
+        };
+        let mut distances = self.distances.borrow_mut();
+        distances.insert(id, distance);
+        for id in chain.into_iter().rev() {
+            distance += 1;
+            distances.insert(id, distance);
+        }
+        Ok(distance)
+    }
+}
+
+/// Signature methods that depend on the commit template environment.
+fn builtin_signature_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Signature> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<Signature>::new();
+    map.insert(
+        "matches_user_config",
+        |language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            // Compare emails the same way as the mine() revset does.
+            let context = &language.revset_parse_context;
+            let patterns = context
+                .mailmap
+                .aliases_of_email(&context.user_email)
+                .into_iter()
+                .map(StringPattern::ExactI)
+                .collect_vec();
+            let out_property = self_property.map(move |signature| {
+                patterns
+                    .iter()
+                    .any(|pattern| pattern.matches(&signature.email))
+            });
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map
+}
+
+fn builtin_commit_functions<'repo>() -> CommitTemplateBuildFunctionFnMap<'repo> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildFunctionFnMap::new();
+    map.insert("current_workspace", |language, _build_ctx, function| {
+        template_parser::expect_no_arguments(function)?;
+        let workspace = WorkspaceRef {
+            id: language.workspace_id.clone(),
+        };
+        Ok(L::wrap_workspace(Literal(workspace)))
+    });
+    map
+}
+
+/// Workspace in which a commit is checked out.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct WorkspaceRef {
+    id: WorkspaceId,
+}
+
+impl Template for WorkspaceRef {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(formatter, "{}@", self.id.as_str())
+    }
+}
+
+impl ToJson for WorkspaceRef {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({ "name": self.id.as_str() }))
+    }
+}
+
+impl Template for Vec<WorkspaceRef> {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(formatter, self, " ")
+    }
+}
+
+fn builtin_workspace_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, WorkspaceRef> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<WorkspaceRef>::new();
+    map.insert("name", |_language, _build_ctx, self_property, function| {
+        template_parser::expect_no_arguments(function)?;
+        let out_property = self_property.map(|workspace| workspace.id.as_str().to_owned());
+        Ok(L::wrap_string(out_property))
+    });
+    map
+}
+
+/// File changed by a commit, compared to its parents.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileChange {
+    path: RepoPathBuf,
+    status: FileChangeStatus,
+    conflict: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FileChangeStatus {
+    Added,
+    Modified,
+    Removed,
+}
+
+impl FileChange {
+    fn new(path: RepoPathBuf, before: &MergedTreeValue, after: &MergedTreeValue) -> Self {
+        let status = if before.is_absent() {
+            FileChangeStatus::Added
+        } else if after.is_absent() {
+            FileChangeStatus::Removed
+        } else {
+            FileChangeStatus::Modified
+        };
+        FileChange {
+            path,
+            status,
+            conflict: !after.is_resolved(),
+        }
+    }
+
+    fn status_name(&self) -> &'static str {
+        match self.status {
+            FileChangeStatus::Added => "added",
+            FileChangeStatus::Modified => "modified",
+            FileChangeStatus::Removed => "removed",
+        }
+    }
+
+    fn status_char(&self) -> char {
+        match self.status {
+            FileChangeStatus::Added => 'A',
+            FileChangeStatus::Modified => 'M',
+            FileChangeStatus::Removed => 'D',
+        }
+    }
+}
+
+impl Template for FileChange {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(
+            formatter,
+            "{} {}",
+            self.status_char(),
+            self.path.as_internal_file_string()
+        )
+    }
+}
+
+impl Template for Vec<FileChange> {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(formatter, self, " ")
+    }
+}
+
+impl ToJson for FileChange {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "conflict": self.conflict,
+            "path": self.path.as_internal_file_string(),
+            "status": self.status_name(),
+        }))
+    }
+}
+
+fn builtin_file_change_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, FileChange> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<FileChange>::new();
+    map.insert("path", |_language, _build_ctx, self_property, function| {
+        template_parser::expect_no_arguments(function)?;
+        let out_property = self_property.map(|change| change.path);
+        Ok(L::wrap_repo_path(out_property))
+    });
+    map.insert(
+        "status",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|change| change.status_name().to_owned());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "status_char",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|change| change.status_char().to_string());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "conflict",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|change| change.conflict);
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map
+}
+
+/// `Key: value` line in the footer of a commit description.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trailer {
+    key: String,
+    value: String,
+}
+
+impl Trailer {
+    fn parse_all(description: &str) -> Vec<Self> {
+        footer::parse_footers(description)
+            .into_iter()
+            .map(|entry| Trailer {
+                key: entry.key.to_owned(),
+                value: entry.value.into_owned(),
+            })
+            .collect()
+    }
+}
+
+impl Template for Trailer {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(formatter, "{}: {}", self.key, self.value)
+    }
+}
+
+impl Template for Vec<Trailer> {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(formatter, self, "\n")
+    }
+}
+
+impl ToJson for Trailer {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "key": self.key,
+            "value": self.value,
+        }))
+    }
+}
+
+fn builtin_trailer_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Trailer> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<Trailer>::new();
+    map.insert("key", |_language, _build_ctx, self_property, function| {
+        template_parser::expect_no_arguments(function)?;
+        let out_property = self_property.map(|trailer| trailer.key);
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert("value", |_language, _build_ctx, self_property, function| {
+        template_parser::expect_no_arguments(function)?;
+        let out_property = self_property.map(|trailer| trailer.value);
+        Ok(L::wrap_string(out_property))
+    });
+    map
+}
+
+fn build_trailer_list_contains_key<'repo>(
+    language: &CommitTemplateLanguage<'repo>,
+    build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Output = Vec<Trailer>> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let [key_node] = template_parser::expect_exact_arguments(function)?;
+    let key_property =
+        template_builder::expect_plain_text_expression(language, build_ctx, key_node)?;
+    let out_property = (self_property, key_property).map(|(trailers, key)| {
+        trailers
+            .iter()
+            .any(|trailer| trailer.key.eq_ignore_ascii_case(&key))
+    });
+    Ok(CommitTemplateLanguage::wrap_boolean(out_property))
+}
+
+/// Changes made by a commit compared to its auto-merged parents.
+pub struct TreeDiff {
+    from_tree: MergedTree,
+    to_tree: MergedTree,
+    matcher: Rc<dyn Matcher>,
+}
+
+impl TreeDiff {
+    fn from_commit(
+        repo: &dyn Repo,
+        commit: &Commit,
+        matcher: Rc<dyn Matcher>,
+    ) -> Result<Self, TreeMergeError> {
+        Ok(TreeDiff {
+            from_tree: rewrite::merge_commit_trees(repo, &commit.parents())?,
+            to_tree: commit.tree()?,
+            matcher,
+        })
+    }
+
+    fn stats(&self) -> Result<DiffStats, TemplatePropertyError> {
+        let tree_diff = self
+            .from_tree
+            .diff_stream(&self.to_tree, self.matcher.as_ref());
+        let stats =
+            diff_util::get_diff_stats(self.to_tree.store(), tree_diff).map_err(|err| err.error)?;
+        Ok(stats)
+    }
+}
+
+impl Template for DiffStats {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(
+            formatter,
+            "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+            self.files_changed,
+            if self.files_changed == 1 { "" } else { "s" },
+            self.insertions,
+            if self.insertions == 1 { "" } else { "s" },
+            self.deletions,
+            if self.deletions == 1 { "" } else { "s" },
+        )
+    }
+}
+
+fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, TreeDiff> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<TreeDiff>::new();
+    map.insert("stat", |_language, _build_ctx, self_property, function| {
+        template_parser::expect_no_arguments(function)?;
+        let out_property = self_property.and_then(|diff| diff.stats());
+        Ok(L::wrap_template(Box::new(
+            FormattablePropertyTemplate::new(out_property),
+        )))
+    });
+    map.insert(
+        "files_changed",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property =
+                self_property.and_then(|diff| Ok(i64::try_from(diff.stats()?.files_changed)?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "insertions",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property =
+                self_property.and_then(|diff| Ok(i64::try_from(diff.stats()?.insertions)?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "deletions",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property =
+                self_property.and_then(|diff| Ok(i64::try_from(diff.stats()?.deletions)?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map
+}
+
+/// Signature of a commit made with a signing backend such as GPG or SSH.
+///
+/// The signature is verified lazily, when one of the methods other than
+/// `is_signed()` is evaluated.
+#[derive(Clone, Debug)]
+pub struct CryptographicSignature {
+    commit: Commit,
+}
+
+impl CryptographicSignature {
+    fn verification(&self) -> Result<Option<Verification>, TemplatePropertyError> {
+        Ok(self.commit.verification()?)
+    }
+}
+
+fn builtin_cryptographic_signature_methods<'repo>(
+) -> CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<CryptographicSignature>::new();
+    map.insert(
+        "is_signed",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|signature| signature.commit.is_signed());
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map.insert(
+        "status",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.and_then(|signature| {
+                let status = match signature.verification()? {
+                    None => "none",
+                    Some(verification) => match verification.status {
+                        SigStatus::Good => "good",
+                        SigStatus::Unknown => "unknown",
+                        SigStatus::Bad => "bad",
+                    },
+                };
+                Ok(status.to_owned())
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert("key", |_language, _build_ctx, self_property, function| {
+        template_parser::expect_no_arguments(function)?;
+        let out_property = self_property.and_then(|signature| {
+            let key = signature.verification()?.and_then(|v| v.key);
+            Ok(key.unwrap_or_default())
+        });
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "display",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.and_then(|signature| {
+                let display = signature.verification()?.and_then(|v| v.display);
+                Ok(display.unwrap_or_default())
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map
+}
+
+/// Branch or tag name with metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefName {
+    /// Local name.
+    name: String,
+    /// Remote name if this is a remote or Git-tracking ref.
+    remote: Option<String>,
+    /// Ref target has conflicts.
+    conflict: bool,
+    /// Local ref is synchronized with all tracking remotes, or tracking remote
+    /// ref is synchronized with the local.
+    synced: bool,
+}
+
+impl RefName {
+    /// Creates local branch name. The name is synced if the branch points to
+    /// the same target as all of its tracking remote branches.
+    pub fn local_branch<'a>(
+        name: impl Into<String>,
+        target: &RefTarget,
+        remote_refs: impl IntoIterator<Item = &'a RemoteRef>,
+    ) -> Self {
+        RefName {
+            name: name.into(),
+            remote: None,
+            conflict: target.has_conflict(),
+            synced: remote_refs
+                .into_iter()
+                .all(|remote_ref| !remote_ref.is_tracking() || remote_ref.target == *target),
+        }
+    }
+
+    /// Creates remote branch name. The name is synced if the remote branch is
+    /// tracked and points to the same target as the local branch.
+    pub fn remote_branch(
+        name: impl Into<String>,
+        remote_name: impl Into<String>,
+        remote_ref: &RemoteRef,
+        local_target: &RefTarget,
+    ) -> Self {
+        RefName {
+            name: name.into(),
+            remote: Some(remote_name.into()),
+            conflict: remote_ref.target.has_conflict(),
+            synced: remote_ref.is_tracking() && remote_ref.target == *local_target,
+        }
+    }
+
+    fn is_local(&self) -> bool {
+        self.remote.is_none()
+    }
+
+    fn is_remote(&self) -> bool {
+        self.remote.is_some()
+    }
+}
+
+impl Template for RefName {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(formatter.labeled("name"), "{}", self.name)?;
+        if let Some(remote) = &self.remote {
+            write!(formatter, "@")?;
+            write!(formatter.labeled("remote"), "{remote}")?;
+        }
+        // Don't show both conflict and unsynced sigils as conflicted ref wouldn't
+        // be pushed.
+        if self.conflict {
+            write!(formatter, "??")?;
+        } else if self.is_local() && !self.synced {
+            write!(formatter, "*")?;
+        }
+        Ok(())
+    }
+}
+
+impl Template for Vec<RefName> {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(formatter, self, " ")
+    }
+}
+
+impl ToJson for RefName {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "conflict": self.conflict,
+            "name": self.name,
+            "remote": self.remote,
+            "synced": self.synced,
+        }))
+    }
+}
+
+fn builtin_ref_name_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, RefName> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<RefName>::new();
     map.insert("name", |_language, _build_ctx, self_property, function| {
         template_parser::expect_no_arguments(function)?;
         let out_property = self_property.map(|ref_name| ref_name.name);
@@ -712,9 +1989,82 @@ fn builtin_ref_name_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Re
             Ok(L::wrap_string(out_property))
         },
     );
+    map.insert(
+        "ahead_by",
+        |language, build_ctx, self_property, function| {
+            let [remote_node] = template_parser::expect_exact_arguments(function)?;
+            let remote_property =
+                template_builder::expect_plain_text_expression(language, build_ctx, remote_node)?;
+            let repo = language.repo;
+            let out_property = (self_property, remote_property).and_then(|(ref_name, remote)| {
+                let (ahead, _) = ref_name_ahead_behind(repo, &ref_name, &remote)?;
+                Ok(i64::try_from(ahead)?)
+            });
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "behind_by",
+        |language, build_ctx, self_property, function| {
+            let [remote_node] = template_parser::expect_exact_arguments(function)?;
+            let remote_property =
+                template_builder::expect_plain_text_expression(language, build_ctx, remote_node)?;
+            let repo = language.repo;
+            let out_property = (self_property, remote_property).and_then(|(ref_name, remote)| {
+                let (_, behind) = ref_name_ahead_behind(repo, &ref_name, &remote)?;
+                Ok(i64::try_from(behind)?)
+            });
+            Ok(L::wrap_integer(out_property))
+        },
+    );
     map
 }
 
+/// Counts the commits of the branch which aren't on the branch of the same
+/// name at `remote`, and the other way around. Fails if either branch is
+/// absent or conflicted.
+fn ref_name_ahead_behind(
+    repo: &dyn Repo,
+    ref_name: &RefName,
+    remote: &str,
+) -> Result<(usize, usize), TemplatePropertyError> {
+    let view = repo.view();
+    let target = match &ref_name.remote {
+        None => view.get_local_branch(&ref_name.name),
+        Some(ref_remote) => &view.get_remote_branch(&ref_name.name, ref_remote).target,
+    };
+    let remote_target = &view.get_remote_branch(&ref_name.name, remote).target;
+    let symbol = match &ref_name.remote {
+        None => ref_name.name.clone(),
+        Some(ref_remote) => format!("{}@{ref_remote}", ref_name.name),
+    };
+    let id = expect_normal_target(target, &symbol)?;
+    let remote_symbol = format!("{}@{remote}", ref_name.name);
+    let remote_id = expect_normal_target(remote_target, &remote_symbol)?;
+    Ok(graph::ahead_behind(
+        repo,
+        slice::from_ref(id),
+        slice::from_ref(remote_id),
+    )?)
+}
+
+fn expect_normal_target<'a>(
+    target: &'a RefTarget,
+    symbol: &str,
+) -> Result<&'a CommitId, TemplatePropertyError> {
+    if target.is_absent() {
+        Err(TemplatePropertyError(
+            format!("Branch {symbol} doesn't exist").into(),
+        ))
+    } else if let Some(id) = target.as_normal() {
+        Ok(id)
+    } else {
+        Err(TemplatePropertyError(
+            format!("Branch {symbol} is conflicted").into(),
+        ))
+    }
+}
+
 /// Cache for reverse lookup refs.
 #[derive(Clone, Debug, Default)]
 pub struct RefNamesIndex {
@@ -744,23 +2094,16 @@ fn build_branches_index(repo: &dyn Repo) -> RefNamesIndex {
         let local_target = branch_target.local_target;
         let remote_refs = branch_target.remote_refs;
         if local_target.is_present() {
-            let ref_name = RefName {
-                name: branch_name.to_owned(),
-                remote: None,
-                conflict: local_target.has_conflict(),
-                synced: remote_refs.iter().all(|&(_, remote_ref)| {
-                    !remote_ref.is_tracking() || remote_ref.target == *local_target
-                }),
-            };
+            let ref_name = RefName::local_branch(
+                branch_name,
+                local_target,
+                remote_refs.iter().map(|&(_, remote_ref)| remote_ref),
+            );
             index.insert(local_target.added_ids(), ref_name);
         }
         for &(remote_name, remote_ref) in &remote_refs {
-            let ref_name = RefName {
-                name: branch_name.to_owned(),
-                remote: Some(remote_name.to_owned()),
-                conflict: remote_ref.target.has_conflict(),
-                synced: remote_ref.is_tracking() && remote_ref.target == *local_target,
-            };
+            let ref_name =
+                RefName::remote_branch(branch_name, remote_name, remote_ref, local_target);
             index.insert(remote_ref.target.added_ids(), ref_name);
         }
     }
@@ -844,6 +2187,12 @@ impl Template for CommitOrChangeId {
     }
 }
 
+impl ToJson for CommitOrChangeId {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.hex().into())
+    }
+}
+
 fn builtin_commit_or_change_id_methods<'repo>(
 ) -> CommitTemplateBuildMethodFnMap<'repo, CommitOrChangeId> {
     type L<'repo> = CommitTemplateLanguage<'repo>;
@@ -888,6 +2237,15 @@ impl Template for ShortestIdPrefix {
     }
 }
 
+impl ToJson for ShortestIdPrefix {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "prefix": self.prefix,
+            "rest": self.rest,
+        }))
+    }
+}
+
 impl ShortestIdPrefix {
     fn to_upper(&self) -> Self {
         Self {
@@ -934,3 +2292,74 @@ fn builtin_shortest_id_prefix_methods<'repo>(
     });
     map
 }
+
+impl Template for RepoPathBuf {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(formatter, "{}", self.as_internal_file_string())
+    }
+}
+
+impl ToJson for RepoPathBuf {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.as_internal_file_string().into())
+    }
+}
+
+impl Template for Vec<RepoPathBuf> {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(formatter, self, " ")
+    }
+}
+
+fn builtin_repo_path_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, RepoPathBuf> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<RepoPathBuf>::new();
+    map.insert(
+        "display",
+        |language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let path_converter = language.path_converter;
+            let out_property = self_property.map(|path| path_converter.format_file_path(&path));
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "parent",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|path| path.parent().map(|path| path.to_owned()));
+            Ok(L::wrap_repo_path_opt(out_property))
+        },
+    );
+    map.insert(
+        "extension",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|path| {
+                let Some((_, name)) = path.split() else {
+                    return String::new();
+                };
+                let extension = Path::new(name.as_str()).extension();
+                extension.map_or(String::new(), |ext| ext.to_str().unwrap().to_owned())
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "starts_with",
+        |language, build_ctx, self_property, function| {
+            let [prefix_node] = template_parser::expect_exact_arguments(function)?;
+            let prefix_property =
+                template_builder::expect_plain_text_expression(language, build_ctx, prefix_node)?;
+            let out_property = (self_property, prefix_property).and_then(|(path, prefix)| {
+                // The prefix is a repo-relative path in the same form as printed.
+                let prefix = RepoPathBuf::from_relative_path(prefix)?;
+                Ok(path.starts_with(&prefix))
+            });
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map
+}
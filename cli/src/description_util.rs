@@ -1,12 +1,13 @@
 use itertools::Itertools;
 use jj_lib::commit::Commit;
+use jj_lib::footer;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::repo::ReadonlyRepo;
-use jj_lib::settings::UserSettings;
+use jj_lib::settings::{ConfigResultExt as _, UserSettings};
 
 use crate::cli_util::{edit_temp_file, WorkspaceCommandHelper};
-use crate::command_error::CommandError;
+use crate::command_error::{user_error, CommandError};
 use crate::diff_util::{self, DiffFormat};
 use crate::formatter::PlainTextFormatter;
 use crate::text_util;
@@ -90,11 +91,125 @@ pub fn join_message_paragraphs(paragraphs: &[String]) -> String {
         .join("\n")
 }
 
+/// Returns the description of the `commit` with the trailers configured by
+/// `commit.trailers` appended. The `commit` is usually the one about to be
+/// written by `WorkspaceCommandTransaction::write_commit_with_trailers()`.
+///
+/// Each trailer is a commit template that should render to a `Key: value`
+/// line. Trailers that render to an empty string or are already in the footer
+/// are skipped, so describing a commit again doesn't duplicate them. Empty
+/// descriptions are left alone.
+pub fn add_configured_trailers(
+    settings: &UserSettings,
+    workspace_command: &WorkspaceCommandHelper,
+    commit: &Commit,
+) -> Result<String, CommandError> {
+    let templates: Vec<String> = settings
+        .config()
+        .get("commit.trailers")
+        .optional()?
+        .unwrap_or_default();
+    let mut description = commit.description().to_owned();
+    if templates.is_empty() || description.trim().is_empty() {
+        return Ok(description);
+    }
+    for template_text in &templates {
+        let template = workspace_command.parse_commit_template(template_text)?;
+        let mut output = Vec::new();
+        template.format(commit, &mut PlainTextFormatter::new(&mut output))?;
+        let line = String::from_utf8_lossy(&output);
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some(entry) = footer::parse_footer_line(line) else {
+            return Err(user_error(format!(
+                "Trailer template `{template_text}` rendered to `{line}`, which isn't a `Key: \
+                 value` line"
+            )));
+        };
+        if footer::parse_footers(&description).contains(&entry) {
+            continue;
+        }
+        description = footer::add_footer_line(&description, entry.key, &entry.value);
+    }
+    Ok(description)
+}
+
+/// Appends a `Signed-off-by` trailer for the configured user to the
+/// `description` if `signoff` is set or `commit.signoff` is enabled.
+///
+/// Like `git commit --signoff`, the trailer isn't added if the footer already
+/// has it. Empty descriptions are left alone.
+pub fn add_signoff_trailer(
+    settings: &UserSettings,
+    description: String,
+    signoff: bool,
+) -> Result<String, CommandError> {
+    let Some(value) = signoff_value(settings, signoff)? else {
+        return Ok(description);
+    };
+    if description.trim().is_empty() {
+        return Ok(description);
+    }
+    Ok(add_signoff_line(description, &value))
+}
+
+/// Adds the `Signed-off-by` trailer to the `description` that is about to be
+/// edited, so the user can see it and remove it in the editor.
+///
+/// Unlike [`add_signoff_trailer()`], the trailer is added to an empty
+/// description too, after blank lines for the message like `git commit
+/// --signoff` does.
+fn add_signoff_to_template(
+    settings: &UserSettings,
+    description: String,
+    signoff: bool,
+) -> Result<String, CommandError> {
+    let Some(value) = signoff_value(settings, signoff)? else {
+        return Ok(description);
+    };
+    if description.trim().is_empty() {
+        return Ok(format!("\n\nSigned-off-by: {value}\n"));
+    }
+    Ok(add_signoff_line(description, &value))
+}
+
+/// Returns the `Signed-off-by` value for the configured user if `signoff` is
+/// set or `commit.signoff` is enabled.
+fn signoff_value(settings: &UserSettings, signoff: bool) -> Result<Option<String>, CommandError> {
+    let enabled = signoff
+        || settings
+            .config()
+            .get_bool("commit.signoff")
+            .optional()?
+            .unwrap_or(false);
+    Ok(enabled.then(|| format!("{} <{}>", settings.user_name(), settings.user_email())))
+}
+
+fn add_signoff_line(description: String, value: &str) -> String {
+    if footer::parse_footers(&description)
+        .iter()
+        .any(|entry| entry.key.eq_ignore_ascii_case("Signed-off-by") && entry.value == value)
+    {
+        return description;
+    }
+    footer::add_footer_line(&description, "Signed-off-by", value)
+}
+
+/// Options for the description template to be edited.
+#[derive(Clone, Debug, Default)]
+pub struct DescriptionTemplateOptions {
+    /// Add a `Signed-off-by` trailer, which can then be removed in the editor.
+    pub signoff: bool,
+}
+
 pub fn description_template_for_describe(
     ui: &Ui,
     settings: &UserSettings,
     workspace_command: &WorkspaceCommandHelper,
     commit: &Commit,
+    options: &DescriptionTemplateOptions,
 ) -> Result<String, CommandError> {
     let mut diff_summary_bytes = Vec::new();
     diff_util::show_patch(
@@ -110,6 +225,7 @@ pub fn description_template_for_describe(
     } else {
         commit.description().to_owned()
     };
+    let description = add_signoff_to_template(settings, description, options.signoff)?;
     if diff_summary_bytes.is_empty() {
         Ok(description)
     } else {
@@ -119,12 +235,12 @@ pub fn description_template_for_describe(
 
 pub fn description_template_for_commit(
     ui: &Ui,
-    settings: &UserSettings,
     workspace_command: &WorkspaceCommandHelper,
     intro: &str,
     overall_commit_description: &str,
     from_tree: &MergedTree,
     to_tree: &MergedTree,
+    options: &DescriptionTemplateOptions,
 ) -> Result<String, CommandError> {
     let mut diff_summary_bytes = Vec::new();
     diff_util::show_diff(
@@ -136,15 +252,21 @@ pub fn description_template_for_commit(
         &EverythingMatcher,
         &[DiffFormat::Summary],
     )?;
+    let settings = workspace_command.settings();
     let mut template_chunks = Vec::new();
     if !intro.is_empty() {
         template_chunks.push(format!("JJ: {intro}\n"));
     }
-    template_chunks.push(if overall_commit_description.is_empty() {
+    let description = if overall_commit_description.is_empty() {
         settings.default_description()
     } else {
         overall_commit_description.to_owned()
-    });
+    };
+    template_chunks.push(add_signoff_to_template(
+        settings,
+        description,
+        options.signoff,
+    )?);
     if !diff_summary_bytes.is_empty() {
         template_chunks.push("\n".to_owned());
         template_chunks.push(diff_summary_to_description(&diff_summary_bytes));
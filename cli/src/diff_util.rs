@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::cmp::max;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::io;
 use std::ops::Range;
 
@@ -22,7 +22,7 @@ use itertools::Itertools;
 use jj_lib::backend::{BackendResult, TreeValue};
 use jj_lib::commit::Commit;
 use jj_lib::conflicts::{materialize_tree_value, MaterializedTreeValue};
-use jj_lib::diff::{Diff, DiffHunk};
+use jj_lib::diff::{Diff, DiffAlgorithm, DiffHunk};
 use jj_lib::files::DiffLine;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
@@ -49,8 +49,9 @@ const DEFAULT_CONTEXT_LINES: usize = 3;
 
 #[derive(clap::Args, Clone, Debug)]
 #[command(next_help_heading = "Diff Formatting Options")]
-#[command(group(clap::ArgGroup::new("short-format").args(&["summary", "stat", "types"])))]
+#[command(group(clap::ArgGroup::new("short-format").args(&["summary", "stat", "dirstat", "types"])))]
 #[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "tool"])))]
+#[command(group(clap::ArgGroup::new("diff-algorithm").args(&["histogram", "patience", "myers"])))]
 pub struct DiffFormatArgs {
     /// For each path, show only whether it was modified, added, or deleted
     #[arg(long, short)]
@@ -58,6 +59,15 @@ pub struct DiffFormatArgs {
     /// Show a histogram of the changes
     #[arg(long)]
     pub stat: bool,
+    /// Show the percentage of changed lines in each directory
+    ///
+    /// Changes are attributed to the directory containing the file, or to its
+    /// ancestor at `--dirstat-depth` if the file is nested deeper than that.
+    #[arg(long)]
+    pub dirstat: bool,
+    /// Maximum depth of directories shown by `--dirstat`
+    #[arg(long, value_name = "DEPTH", requires = "dirstat")]
+    dirstat_depth: Option<usize>,
     /// For each path, show only its type before and after
     ///
     /// The diff is shown as two letters. The first letter indicates the type
@@ -79,15 +89,36 @@ pub struct DiffFormatArgs {
     /// Number of lines of context to show
     #[arg(long)]
     context: Option<usize>,
+    /// Use the histogram diff algorithm (default)
+    #[arg(long)]
+    histogram: bool,
+    /// Use the patience diff algorithm
+    ///
+    /// Lines which appear exactly once on both sides are matched up first,
+    /// which tends to produce more readable diffs for reordered code.
+    #[arg(long)]
+    patience: bool,
+    /// Use the Myers diff algorithm, which produces a minimal diff
+    #[arg(long)]
+    myers: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DiffFormat {
     Summary,
     Stat,
+    DirStat {
+        depth: Option<usize>,
+    },
     Types,
-    Git { context: usize },
-    ColorWords { context: usize },
+    Git {
+        context: usize,
+        algorithm: DiffAlgorithm,
+    },
+    ColorWords {
+        context: usize,
+        algorithm: DiffAlgorithm,
+    },
     Tool(Box<ExternalMergeTool>),
 }
 
@@ -96,9 +127,14 @@ pub fn diff_formats_for(
     settings: &UserSettings,
     args: &DiffFormatArgs,
 ) -> Result<Vec<DiffFormat>, config::ConfigError> {
-    let formats = diff_formats_from_args(settings, args)?;
+    let algorithm = diff_algorithm_for(settings, args)?;
+    let formats = diff_formats_from_args(settings, args, algorithm)?;
     if formats.is_empty() {
-        Ok(vec![default_diff_format(settings, args.context)?])
+        Ok(vec![default_diff_format(
+            settings,
+            args.context,
+            algorithm,
+        )?])
     } else {
         Ok(formats)
     }
@@ -111,10 +147,11 @@ pub fn diff_formats_for_log(
     args: &DiffFormatArgs,
     patch: bool,
 ) -> Result<Vec<DiffFormat>, config::ConfigError> {
-    let mut formats = diff_formats_from_args(settings, args)?;
+    let algorithm = diff_algorithm_for(settings, args)?;
+    let mut formats = diff_formats_from_args(settings, args, algorithm)?;
     // --patch implies default if no format other than --summary is specified
     if patch && matches!(formats.as_slice(), [] | [DiffFormat::Summary]) {
-        formats.push(default_diff_format(settings, args.context)?);
+        formats.push(default_diff_format(settings, args.context, algorithm)?);
         formats.dedup();
     }
     Ok(formats)
@@ -123,6 +160,7 @@ pub fn diff_formats_for_log(
 fn diff_formats_from_args(
     settings: &UserSettings,
     args: &DiffFormatArgs,
+    algorithm: DiffAlgorithm,
 ) -> Result<Vec<DiffFormat>, config::ConfigError> {
     let mut formats = [
         (args.summary, DiffFormat::Summary),
@@ -131,15 +169,23 @@ fn diff_formats_from_args(
             args.git,
             DiffFormat::Git {
                 context: args.context.unwrap_or(DEFAULT_CONTEXT_LINES),
+                algorithm,
             },
         ),
         (
             args.color_words,
             DiffFormat::ColorWords {
                 context: args.context.unwrap_or(DEFAULT_CONTEXT_LINES),
+                algorithm,
             },
         ),
         (args.stat, DiffFormat::Stat),
+        (
+            args.dirstat,
+            DiffFormat::DirStat {
+                depth: args.dirstat_depth,
+            },
+        ),
     ]
     .into_iter()
     .filter_map(|(arg, format)| arg.then_some(format))
@@ -152,9 +198,38 @@ fn diff_formats_from_args(
     Ok(formats)
 }
 
+fn diff_algorithm_for(
+    settings: &UserSettings,
+    args: &DiffFormatArgs,
+) -> Result<DiffAlgorithm, config::ConfigError> {
+    if args.histogram {
+        return Ok(DiffAlgorithm::Histogram);
+    } else if args.patience {
+        return Ok(DiffAlgorithm::Patience);
+    } else if args.myers {
+        return Ok(DiffAlgorithm::Myers);
+    }
+    let Some(name) = settings
+        .config()
+        .get_string("ui.diff.algorithm")
+        .optional()?
+    else {
+        return Ok(DiffAlgorithm::default());
+    };
+    match name.as_ref() {
+        "histogram" => Ok(DiffAlgorithm::Histogram),
+        "patience" => Ok(DiffAlgorithm::Patience),
+        "myers" => Ok(DiffAlgorithm::Myers),
+        _ => Err(config::ConfigError::Message(format!(
+            "invalid diff algorithm: {name}"
+        ))),
+    }
+}
+
 fn default_diff_format(
     settings: &UserSettings,
     num_context_lines: Option<usize>,
+    algorithm: DiffAlgorithm,
 ) -> Result<DiffFormat, config::ConfigError> {
     let config = settings.config();
     if let Some(args) = config.get("ui.diff.tool").optional()? {
@@ -179,11 +254,14 @@ fn default_diff_format(
         "types" => Ok(DiffFormat::Types),
         "git" => Ok(DiffFormat::Git {
             context: num_context_lines.unwrap_or(DEFAULT_CONTEXT_LINES),
+            algorithm,
         }),
         "color-words" => Ok(DiffFormat::ColorWords {
             context: num_context_lines.unwrap_or(DEFAULT_CONTEXT_LINES),
+            algorithm,
         }),
         "stat" => Ok(DiffFormat::Stat),
+        "dirstat" => Ok(DiffFormat::DirStat { depth: None }),
         _ => Err(config::ConfigError::Message(format!(
             "invalid diff format: {name}"
         ))),
@@ -209,17 +287,33 @@ pub fn show_diff(
                 let tree_diff = from_tree.diff_stream(to_tree, matcher);
                 show_diff_stat(ui, formatter, workspace_command, tree_diff)?;
             }
+            DiffFormat::DirStat { depth } => {
+                let tree_diff = from_tree.diff_stream(to_tree, matcher);
+                show_diff_dirstat(formatter, workspace_command, *depth, tree_diff)?;
+            }
             DiffFormat::Types => {
                 let tree_diff = from_tree.diff_stream(to_tree, matcher);
                 show_types(formatter, workspace_command, tree_diff)?;
             }
-            DiffFormat::Git { context } => {
+            DiffFormat::Git { context, algorithm } => {
                 let tree_diff = from_tree.diff_stream(to_tree, matcher);
-                show_git_diff(formatter, workspace_command, *context, tree_diff)?;
+                show_git_diff(
+                    formatter,
+                    workspace_command,
+                    *context,
+                    *algorithm,
+                    tree_diff,
+                )?;
             }
-            DiffFormat::ColorWords { context } => {
+            DiffFormat::ColorWords { context, algorithm } => {
                 let tree_diff = from_tree.diff_stream(to_tree, matcher);
-                show_color_words_diff(formatter, workspace_command, *context, tree_diff)?;
+                show_color_words_diff(
+                    formatter,
+                    workspace_command,
+                    *context,
+                    *algorithm,
+                    tree_diff,
+                )?;
             }
             DiffFormat::Tool(tool) => {
                 merge_tools::generate_diff(ui, formatter.raw(), from_tree, to_tree, matcher, tool)?;
@@ -255,6 +349,7 @@ fn show_color_words_diff_hunks(
     left: &[u8],
     right: &[u8],
     num_context_lines: usize,
+    algorithm: DiffAlgorithm,
     formatter: &mut dyn Formatter,
 ) -> io::Result<()> {
     const SKIPPED_CONTEXT_LINE: &str = "    ...\n";
@@ -263,7 +358,7 @@ fn show_color_words_diff_hunks(
     let mut skipped_context = false;
     // Are the lines in `context` to be printed before the next modified line?
     let mut context_before = true;
-    for diff_line in files::diff(left, right) {
+    for diff_line in files::diff_with_algorithm(left, right, algorithm) {
         if diff_line.is_unmodified() {
             context.push_back(diff_line.clone());
             let mut start_skipping_context = false;
@@ -453,6 +548,7 @@ pub fn show_color_words_diff(
     formatter: &mut dyn Formatter,
     workspace_command: &WorkspaceCommandHelper,
     num_context_lines: usize,
+    algorithm: DiffAlgorithm,
     tree_diff: TreeDiffStream,
 ) -> Result<(), CommandError> {
     formatter.push_label("diff")?;
@@ -477,6 +573,7 @@ pub fn show_color_words_diff(
                         &[],
                         &right_content.contents,
                         num_context_lines,
+                        algorithm,
                         formatter,
                     )?;
                 }
@@ -538,6 +635,7 @@ pub fn show_color_words_diff(
                         &left_content.contents,
                         &right_content.contents,
                         num_context_lines,
+                        algorithm,
                         formatter,
                     )?;
                 }
@@ -557,6 +655,7 @@ pub fn show_color_words_diff(
                         &left_content.contents,
                         &[],
                         num_context_lines,
+                        algorithm,
                         formatter,
                     )?;
                 }
@@ -649,6 +748,7 @@ fn unified_diff_hunks<'content>(
     left_content: &'content [u8],
     right_content: &'content [u8],
     num_context_lines: usize,
+    algorithm: DiffAlgorithm,
 ) -> Vec<UnifiedDiffHunk<'content>> {
     let mut hunks = vec![];
     let mut current_hunk = UnifiedDiffHunk {
@@ -657,7 +757,11 @@ fn unified_diff_hunks<'content>(
         lines: vec![],
     };
     let mut show_context_after = false;
-    let diff = Diff::for_tokenizer(&[left_content, right_content], &diff::find_line_ranges);
+    let diff = Diff::for_tokenizer_with_algorithm(
+        &[left_content, right_content],
+        &diff::find_line_ranges,
+        algorithm,
+    );
     for hunk in diff.hunks() {
         match hunk {
             DiffHunk::Matching(content) => {
@@ -730,8 +834,9 @@ fn show_unified_diff_hunks(
     left_content: &[u8],
     right_content: &[u8],
     num_context_lines: usize,
+    algorithm: DiffAlgorithm,
 ) -> Result<(), CommandError> {
-    for hunk in unified_diff_hunks(left_content, right_content, num_context_lines) {
+    for hunk in unified_diff_hunks(left_content, right_content, num_context_lines, algorithm) {
         writeln!(
             formatter.labeled("hunk_header"),
             "@@ -{},{} +{},{} @@",
@@ -797,6 +902,7 @@ pub fn show_git_diff(
     formatter: &mut dyn Formatter,
     workspace_command: &WorkspaceCommandHelper,
     num_context_lines: usize,
+    algorithm: DiffAlgorithm,
     tree_diff: TreeDiffStream,
 ) -> Result<(), CommandError> {
     formatter.push_label("diff")?;
@@ -815,7 +921,13 @@ pub fn show_git_diff(
                     writeln!(formatter, "--- /dev/null")?;
                     writeln!(formatter, "+++ b/{path_string}")
                 })?;
-                show_unified_diff_hunks(formatter, &[], &right_part.content, num_context_lines)?;
+                show_unified_diff_hunks(
+                    formatter,
+                    &[],
+                    &right_part.content,
+                    num_context_lines,
+                    algorithm,
+                )?;
             } else if right_value.is_present() {
                 let left_part = git_diff_part(&path, left_value)?;
                 let right_part = git_diff_part(&path, right_value)?;
@@ -845,6 +957,7 @@ pub fn show_git_diff(
                     &left_part.content,
                     &right_part.content,
                     num_context_lines,
+                    algorithm,
                 )?;
             } else {
                 let left_part = git_diff_part(&path, left_value)?;
@@ -855,7 +968,13 @@ pub fn show_git_diff(
                     writeln!(formatter, "--- a/{path_string}")?;
                     writeln!(formatter, "+++ /dev/null")
                 })?;
-                show_unified_diff_hunks(formatter, &left_part.content, &[], num_context_lines)?;
+                show_unified_diff_hunks(
+                    formatter,
+                    &left_part.content,
+                    &[],
+                    num_context_lines,
+                    algorithm,
+                )?;
             }
         }
         Ok::<(), CommandError>(())
@@ -912,10 +1031,25 @@ fn get_diff_stat(
     left_content: &FileContent,
     right_content: &FileContent,
 ) -> DiffStat {
+    let (added, removed) = count_changed_lines(left_content, right_content);
+    DiffStat {
+        path,
+        added,
+        removed,
+    }
+}
+
+/// Returns the number of added and removed lines.
+fn count_changed_lines(left_content: &FileContent, right_content: &FileContent) -> (usize, usize) {
     // TODO: this matches git's behavior, which is to count the number of newlines
     // in the file. but that behavior seems unhelpful; no one really cares how
     // many `0xa0` characters are in an image.
-    let hunks = unified_diff_hunks(&left_content.contents, &right_content.contents, 0);
+    let hunks = unified_diff_hunks(
+        &left_content.contents,
+        &right_content.contents,
+        0,
+        DiffAlgorithm::default(),
+    );
     let mut added = 0;
     let mut removed = 0;
     for hunk in hunks {
@@ -927,11 +1061,35 @@ fn get_diff_stat(
             }
         }
     }
-    DiffStat {
-        path,
-        added,
-        removed,
+    (added, removed)
+}
+
+/// Numbers of changed files and lines in a diff.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// Counts the files and lines changed by the `tree_diff`.
+pub fn get_diff_stats(store: &Store, tree_diff: TreeDiffStream) -> Result<DiffStats, CommandError> {
+    let mut stats = DiffStats::default();
+    let mut diff_stream = materialized_diff_stream(store, tree_diff);
+    async {
+        while let Some((repo_path, diff)) = diff_stream.next().await {
+            let (left, right) = diff?;
+            let left_content = diff_content(&repo_path, left)?;
+            let right_content = diff_content(&repo_path, right)?;
+            let (added, removed) = count_changed_lines(&left_content, &right_content);
+            stats.files_changed += 1;
+            stats.insertions += added;
+            stats.deletions += removed;
+        }
+        Ok::<(), CommandError>(())
     }
+    .block_on()?;
+    Ok(stats)
 }
 
 pub fn show_diff_stat(
@@ -1011,6 +1169,60 @@ pub fn show_diff_stat(
     Ok(())
 }
 
+/// Shows the share of changed lines per directory, like `git diff --dirstat`.
+///
+/// Each file's changes are attributed to its parent directory, truncated to
+/// `depth` components if specified.
+pub fn show_diff_dirstat(
+    formatter: &mut dyn Formatter,
+    workspace_command: &WorkspaceCommandHelper,
+    depth: Option<usize>,
+    tree_diff: TreeDiffStream,
+) -> Result<(), CommandError> {
+    let mut changes_by_dir: BTreeMap<RepoPathBuf, usize> = BTreeMap::new();
+    let mut diff_stream = materialized_diff_stream(workspace_command.repo().store(), tree_diff);
+    async {
+        while let Some((repo_path, diff)) = diff_stream.next().await {
+            let (left, right) = diff?;
+            let left_content = diff_content(&repo_path, left)?;
+            let right_content = diff_content(&repo_path, right)?;
+            let (added, removed) = count_changed_lines(&left_content, &right_content);
+            let mut dir = repo_path.parent().unwrap_or(RepoPath::root());
+            if let Some(depth) = depth {
+                while dir.components().count() > depth {
+                    dir = dir.parent().unwrap();
+                }
+            }
+            *changes_by_dir.entry(dir.to_owned()).or_default() += added + removed;
+        }
+        Ok::<(), CommandError>(())
+    }
+    .block_on()?;
+
+    let total: usize = changes_by_dir.values().sum();
+    if total == 0 {
+        return Ok(());
+    }
+    formatter.with_label("diff", |formatter| {
+        for (dir, changes) in &changes_by_dir {
+            if *changes == 0 {
+                continue;
+            }
+            let percentage = *changes as f64 * 100.0 / total as f64;
+            // Parent directories are already formatted with a trailing separator.
+            let path = workspace_command.format_file_path(dir);
+            writeln!(
+                formatter,
+                "{percentage:5.1}% {}{}",
+                path.trim_end_matches(std::path::MAIN_SEPARATOR),
+                std::path::MAIN_SEPARATOR,
+            )?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
 pub fn show_types(
     formatter: &mut dyn Formatter,
     workspace_command: &WorkspaceCommandHelper,
@@ -32,6 +32,22 @@ pub trait Formatter: Write {
     fn push_label(&mut self, label: &str) -> io::Result<()>;
 
     fn pop_label(&mut self) -> io::Result<()>;
+
+    /// Makes the text written until the matching `pop_hyperlink()` a link to
+    /// the `url`. Formatters which don't support hyperlinks ignore it.
+    fn push_hyperlink(&mut self, _url: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Returns true if template property errors should abort rendering instead
+    /// of being printed inline.
+    fn is_strict(&self) -> bool {
+        false
+    }
 }
 
 impl dyn Formatter + '_ {
@@ -132,7 +148,7 @@ pub struct FormatterFactory {
 enum FormatterFactoryKind {
     PlainText,
     Sanitized,
-    Color { rules: Arc<Rules> },
+    Color { rules: Arc<Rules>, hyperlinks: bool },
 }
 
 impl FormatterFactory {
@@ -143,7 +159,8 @@ impl FormatterFactory {
     ) -> Result<Self, config::ConfigError> {
         let kind = if color {
             let rules = Arc::new(rules_from_config(config)?);
-            FormatterFactoryKind::Color { rules }
+            let hyperlinks = config.get_bool("ui.hyperlinks").unwrap_or(true);
+            FormatterFactoryKind::Color { rules, hyperlinks }
         } else if sanitized {
             FormatterFactoryKind::Sanitized
         } else {
@@ -159,8 +176,8 @@ impl FormatterFactory {
         match &self.kind {
             FormatterFactoryKind::PlainText => Box::new(PlainTextFormatter::new(output)),
             FormatterFactoryKind::Sanitized => Box::new(SanitizingFormatter::new(output)),
-            FormatterFactoryKind::Color { rules } => {
-                Box::new(ColorFormatter::new(output, rules.clone()))
+            FormatterFactoryKind::Color { rules, hyperlinks } => {
+                Box::new(ColorFormatter::new(output, rules.clone()).with_hyperlinks(*hyperlinks))
             }
         }
     }
@@ -262,6 +279,10 @@ pub struct ColorFormatter<W: Write> {
     cached_styles: HashMap<Vec<String>, Style>,
     /// The style we last wrote to the output.
     current_style: Style,
+    /// Whether to emit OSC 8 hyperlinks.
+    hyperlinks: bool,
+    /// The stack of currently applied hyperlink URLs. The last one is active.
+    hyperlink_urls: Vec<String>,
 }
 
 impl<W: Write> ColorFormatter<W> {
@@ -272,9 +293,17 @@ impl<W: Write> ColorFormatter<W> {
             labels: vec![],
             cached_styles: HashMap::new(),
             current_style: Style::default(),
+            hyperlinks: false,
+            hyperlink_urls: vec![],
         }
     }
 
+    /// Enables or disables OSC 8 hyperlinks.
+    pub fn with_hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.hyperlinks = hyperlinks;
+        self
+    }
+
     pub fn for_config(output: W, config: &config::Config) -> Result<Self, config::ConfigError> {
         let rules = rules_from_config(config)?;
         Ok(Self::new(output, Arc::new(rules)))
@@ -359,6 +388,12 @@ impl<W: Write> ColorFormatter<W> {
     }
 }
 
+/// Writes OSC 8 escape sequence which starts a hyperlink to the `url`, or ends
+/// the current hyperlink if the `url` is empty.
+fn write_hyperlink_escape(output: &mut impl Write, url: &str) -> io::Result<()> {
+    write!(output, "\x1b]8;;{url}\x1b\\")
+}
+
 fn rules_from_config(config: &config::Config) -> Result<Rules, config::ConfigError> {
     let mut result = vec![];
     let table = config.get_table("colors")?;
@@ -511,6 +546,32 @@ impl<W: Write> Formatter for ColorFormatter<W> {
         }
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        if !self.hyperlinks {
+            return Ok(());
+        }
+        // Control characters could terminate the escape sequence early, so such
+        // URLs are linked as empty, which leaves the text unlinked.
+        let url = if url.chars().any(|c| c.is_control()) {
+            ""
+        } else {
+            url
+        };
+        write_hyperlink_escape(&mut self.output, url)?;
+        self.hyperlink_urls.push(url.to_owned());
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        if !self.hyperlinks {
+            return Ok(());
+        }
+        self.hyperlink_urls.pop();
+        // Hyperlinks can't be nested, so the outer link is restarted if any.
+        let outer_url = self.hyperlink_urls.last().map_or("", |url| url.as_str());
+        write_hyperlink_escape(&mut self.output, outer_url)
+    }
 }
 
 impl<W: Write> Drop for ColorFormatter<W> {
@@ -519,6 +580,10 @@ impl<W: Write> Drop for ColorFormatter<W> {
         // because of an error), let's still try to reset any currently active style.
         self.labels.clear();
         self.write_new_style().ok();
+        if !self.hyperlink_urls.is_empty() {
+            self.hyperlink_urls.clear();
+            write_hyperlink_escape(&mut self.output, "").ok();
+        }
     }
 }
 
@@ -539,6 +604,8 @@ pub struct FormatRecorder {
 enum LabelOp {
     PushLabel(String),
     PopLabel,
+    PushHyperlink(String),
+    PopHyperlink,
 }
 
 impl FormatRecorder {
@@ -578,6 +645,8 @@ impl FormatRecorder {
             match op {
                 LabelOp::PushLabel(label) => formatter.push_label(label)?,
                 LabelOp::PopLabel => formatter.pop_label()?,
+                LabelOp::PushHyperlink(url) => formatter.push_hyperlink(url)?,
+                LabelOp::PopHyperlink => formatter.pop_hyperlink()?,
             }
         }
         flush_data(formatter, self.data.len())
@@ -609,6 +678,16 @@ impl Formatter for FormatRecorder {
         self.push_label_op(LabelOp::PopLabel);
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        self.push_label_op(LabelOp::PushHyperlink(url.to_owned()));
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.push_label_op(LabelOp::PopHyperlink);
+        Ok(())
+    }
 }
 
 fn write_sanitized(output: &mut impl Write, buf: &[u8]) -> Result<(), Error> {
@@ -1095,6 +1174,34 @@ mod tests {
         insta::assert_snapshot!(String::from_utf8(output).unwrap(), @"[38;5;2m inside [39m");
     }
 
+    #[test]
+    fn test_color_formatter_hyperlinks() {
+        let config = config_from_string(r#" colors.inside = "green" "#);
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = ColorFormatter::for_config(&mut output, &config)
+            .unwrap()
+            .with_hyperlinks(true);
+        formatter.push_hyperlink("https://example.com/").unwrap();
+        formatter.push_label("inside").unwrap();
+        write!(formatter, "link").unwrap();
+        formatter.pop_label().unwrap();
+        formatter.pop_hyperlink().unwrap();
+        write!(formatter, " after").unwrap();
+        drop(formatter);
+        insta::assert_snapshot!(
+            String::from_utf8(output).unwrap(),
+            @r"]8;;https://example.com/\[38;5;2mlink[39m]8;;\ after");
+
+        // Disabled by default
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = ColorFormatter::for_config(&mut output, &config).unwrap();
+        formatter.push_hyperlink("https://example.com/").unwrap();
+        write!(formatter, "link").unwrap();
+        formatter.pop_hyperlink().unwrap();
+        drop(formatter);
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @"link");
+    }
+
     #[test]
     fn test_heading_labeled_writer() {
         let config = config_from_string(
@@ -1166,5 +1273,20 @@ mod tests {
         insta::assert_snapshot!(
             String::from_utf8(output).unwrap(),
             @"<< outer1 >>[38;5;1m<< inner1  inner2 >>[39m<< outer2 >>");
+
+        // Hyperlinks should be replayed as well.
+        let mut recorder = FormatRecorder::new();
+        recorder.push_hyperlink("https://example.com/").unwrap();
+        write!(recorder, "link").unwrap();
+        recorder.pop_hyperlink().unwrap();
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = ColorFormatter::for_config(&mut output, &config)
+            .unwrap()
+            .with_hyperlinks(true);
+        recorder.replay(&mut formatter).unwrap();
+        drop(formatter);
+        insta::assert_snapshot!(
+            String::from_utf8(output).unwrap(),
+            @r"]8;;https://example.com/\link]8;;\");
     }
 }
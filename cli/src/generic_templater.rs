@@ -19,7 +19,7 @@ use crate::template_builder::{
     TemplateLanguage,
 };
 use crate::template_parser::{self, FunctionCallNode, TemplateParseResult};
-use crate::templater::{Template, TemplateProperty};
+use crate::templater::{Template, TemplateProperty, TemplatePropertyExt as _};
 
 /// General-purpose template language for basic value types.
 ///
@@ -151,6 +151,24 @@ impl<'a, C: 'a> IntoTemplateProperty<'a> for GenericTemplatePropertyKind<'a, C>
             GenericTemplatePropertyKind::Self_(_) => None,
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        match self {
+            GenericTemplatePropertyKind::Core(property) => property.try_into_json(),
+            GenericTemplatePropertyKind::Self_(_) => None,
+        }
+    }
+
+    fn with_error_name(self, name: &str) -> Self {
+        match self {
+            GenericTemplatePropertyKind::Core(property) => {
+                GenericTemplatePropertyKind::Core(property.with_error_name(name))
+            }
+            GenericTemplatePropertyKind::Self_(property) => {
+                GenericTemplatePropertyKind::Self_(Box::new(property.with_error_name(name)))
+            }
+        }
+    }
 }
 
 /// Function that translates keyword (or 0-ary method call node of the self type
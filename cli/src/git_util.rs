@@ -29,6 +29,7 @@ use jj_lib::store::Store;
 use jj_lib::workspace::Workspace;
 use unicode_width::UnicodeWidthStr;
 
+use crate::cli_util::short_commit_hash;
 use crate::command_error::{user_error, CommandError};
 use crate::formatter::Formatter;
 use crate::progress::Progress;
@@ -281,6 +282,22 @@ pub fn print_git_import_stats(
         }
     }
 
+    if !stats.reassociated_commits.is_empty() {
+        writeln!(
+            ui.stderr(),
+            "Replaced {} local commits with their rewritten versions from the remote:",
+            stats.reassociated_commits.len()
+        )?;
+        for (old_id, new_id) in &stats.reassociated_commits {
+            writeln!(
+                ui.stderr(),
+                "  {} -> {}",
+                short_commit_hash(old_id),
+                short_commit_hash(new_id)
+            )?;
+        }
+    }
+
     if !stats.abandoned_commits.is_empty() {
         writeln!(
             ui.stderr(),
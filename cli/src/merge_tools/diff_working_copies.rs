@@ -282,6 +282,8 @@ diff editing in mind and be a little inaccurate.
             fsmonitor_kind: FsmonitorKind::None,
             progress: None,
             max_new_file_size: u64::MAX,
+            ignore_executable_bit: false,
+            fingerprint_cache: None,
         })?;
         Ok(output_tree_state.current_tree_id().clone())
     }
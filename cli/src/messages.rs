@@ -0,0 +1,136 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Catalog of user-facing messages which can be replaced by config.
+//!
+//! Messages that can be customized are identified by a stable id such as
+//! `resolve-conflicts`. The `messages.<id>` config table replaces the
+//! built-in text, and `ui.hints.disable` suppresses hints by id.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::command_error::{config_error_with_message, CommandError};
+
+/// Messages configured by the `messages` table and `ui.hints.disable`.
+#[derive(Clone, Debug, Default)]
+pub struct MessageCatalog {
+    overrides: HashMap<String, String>,
+    disabled_hints: HashSet<String>,
+}
+
+impl MessageCatalog {
+    pub fn from_config(config: &config::Config) -> Result<Self, CommandError> {
+        let overrides = match config.get::<HashMap<String, String>>("messages") {
+            Ok(overrides) => overrides,
+            Err(config::ConfigError::NotFound(_)) => HashMap::new(),
+            Err(err) => return Err(config_error_with_message("Invalid `messages`", err)),
+        };
+        let disabled_hints = match config.get::<Vec<String>>("ui.hints.disable") {
+            Ok(ids) => ids.into_iter().collect(),
+            Err(config::ConfigError::NotFound(_)) => HashSet::new(),
+            Err(err) => return Err(config_error_with_message("Invalid `ui.hints.disable`", err)),
+        };
+        Ok(MessageCatalog {
+            overrides,
+            disabled_hints,
+        })
+    }
+
+    /// Returns true if the hint identified by `id` should not be printed.
+    pub fn is_hint_disabled(&self, id: &str) -> bool {
+        self.disabled_hints.contains(id)
+    }
+
+    /// Looks up the message identified by `id`, falling back to `default`.
+    pub fn get<'a>(&'a self, id: &str, default: &'a str) -> &'a str {
+        self.overrides.get(id).map_or(default, |s| s.as_str())
+    }
+
+    /// Looks up the message identified by `id`, and substitutes `{name}`
+    /// placeholders with the given `args`.
+    ///
+    /// Placeholders which have no corresponding argument are left as is.
+    pub fn format(&self, id: &str, default: &str, args: &[(&str, &str)]) -> String {
+        substitute_placeholders(self.get(id, default), args)
+    }
+}
+
+/// Substitutes `{name}` placeholders in `template` with the given `args`.
+pub fn substitute_placeholders(template: &str, args: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let value = tail.find('}').and_then(|end| {
+            let name = &tail[1..end];
+            let (_, value) = args.iter().find(|(arg_name, _)| *arg_name == name)?;
+            Some((value, end))
+        });
+        if let Some((value, end)) = value {
+            output.push_str(value);
+            rest = &tail[end + 1..];
+        } else {
+            output.push('{');
+            rest = &tail[1..];
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute_placeholders() {
+        let args = [("names", "a b"), ("id", "xyz")];
+        assert_eq!(substitute_placeholders("", &args), "");
+        assert_eq!(substitute_placeholders("no args", &args), "no args");
+        assert_eq!(
+            substitute_placeholders("track {names} at {id}", &args),
+            "track a b at xyz"
+        );
+        assert_eq!(
+            substitute_placeholders("{unknown} {names}", &args),
+            "{unknown} a b"
+        );
+        assert_eq!(substitute_placeholders("{names", &args), "{names");
+        assert_eq!(substitute_placeholders("{{id}}", &args), "{xyz}");
+    }
+
+    #[test]
+    fn test_catalog_from_config() {
+        let config = config::Config::builder()
+            .set_override("messages.resolve-conflicts", "Ask for help in #vcs.")
+            .unwrap()
+            .set_override("ui.hints.disable", vec!["config-help"])
+            .unwrap()
+            .build()
+            .unwrap();
+        let catalog = MessageCatalog::from_config(&config).unwrap();
+        assert_eq!(
+            catalog.get("resolve-conflicts", "default"),
+            "Ask for help in #vcs."
+        );
+        assert_eq!(catalog.get("other", "default"), "default");
+        assert!(catalog.is_hint_disabled("config-help"));
+        assert!(!catalog.is_hint_disabled("resolve-conflicts"));
+
+        let catalog = MessageCatalog::from_config(&config::Config::default()).unwrap();
+        assert_eq!(catalog.get("resolve-conflicts", "default"), "default");
+        assert!(!catalog.is_hint_disabled("config-help"));
+    }
+}
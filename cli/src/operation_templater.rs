@@ -19,18 +19,22 @@ use std::io;
 use itertools::Itertools as _;
 use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::object_id::ObjectId;
-use jj_lib::op_store::OperationId;
+use jj_lib::op_store::{self, OperationId};
 use jj_lib::operation::Operation;
+use jj_lib::refs;
+use jj_lib::view::View;
 
+use crate::cli_util;
+use crate::commands::operation::UNDO_DESCRIPTION_PREFIX;
 use crate::formatter::Formatter;
 use crate::template_builder::{
-    self, merge_fn_map, BuildContext, CoreTemplateBuildFnTable, CoreTemplatePropertyKind,
-    IntoTemplateProperty, TemplateBuildMethodFnMap, TemplateLanguage,
+    self, merge_fn_map, sorted_fn_names, BuildContext, CoreTemplateBuildFnTable,
+    CoreTemplatePropertyKind, IntoTemplateProperty, TemplateBuildMethodFnMap, TemplateLanguage,
 };
 use crate::template_parser::{self, FunctionCallNode, TemplateParseResult};
 use crate::templater::{
-    IntoTemplate, PlainTextFormattedProperty, Template, TemplateProperty, TemplatePropertyExt as _,
-    TimestampRange,
+    IntoJson, IntoTemplate, PlainTextFormattedProperty, Template, TemplateProperty,
+    TemplatePropertyError, TemplatePropertyExt as _, TimestampRange, ToJson,
 };
 
 pub trait OperationTemplateLanguageExtension {
@@ -101,6 +105,16 @@ impl TemplateLanguage<'static> for OperationTemplateLanguage {
                 let build = template_parser::lookup_method("Operation", table, function)?;
                 build(self, build_ctx, property, function)
             }
+            OperationTemplatePropertyKind::OperationList(property) => {
+                // TODO: migrate to table?
+                template_builder::build_unformattable_list_method(
+                    self,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_operation,
+                )
+            }
             OperationTemplatePropertyKind::OperationId(property) => {
                 let table = &self.build_fn_table.operation_id_methods;
                 let build = template_parser::lookup_method("OperationId", table, function)?;
@@ -111,6 +125,11 @@ impl TemplateLanguage<'static> for OperationTemplateLanguage {
 }
 
 impl OperationTemplateLanguage {
+    /// Function table including the methods added by the extension.
+    pub fn build_fn_table(&self) -> &OperationTemplateBuildFnTable {
+        &self.build_fn_table
+    }
+
     pub fn cache_extension<T: Any>(&self) -> Option<&T> {
         self.cache_extensions.get::<T>()
     }
@@ -121,6 +140,12 @@ impl OperationTemplateLanguage {
         OperationTemplatePropertyKind::Operation(Box::new(property))
     }
 
+    pub fn wrap_operation_list(
+        property: impl TemplateProperty<Output = Vec<Operation>> + 'static,
+    ) -> OperationTemplatePropertyKind {
+        OperationTemplatePropertyKind::OperationList(Box::new(property))
+    }
+
     pub fn wrap_operation_id(
         property: impl TemplateProperty<Output = OperationId> + 'static,
     ) -> OperationTemplatePropertyKind {
@@ -131,6 +156,7 @@ impl OperationTemplateLanguage {
 pub enum OperationTemplatePropertyKind {
     Core(CoreTemplatePropertyKind<'static>),
     Operation(Box<dyn TemplateProperty<Output = Operation>>),
+    OperationList(Box<dyn TemplateProperty<Output = Vec<Operation>>>),
     OperationId(Box<dyn TemplateProperty<Output = OperationId>>),
 }
 
@@ -139,6 +165,9 @@ impl IntoTemplateProperty<'static> for OperationTemplatePropertyKind {
         match self {
             OperationTemplatePropertyKind::Core(property) => property.try_into_boolean(),
             OperationTemplatePropertyKind::Operation(_) => None,
+            OperationTemplatePropertyKind::OperationList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
             OperationTemplatePropertyKind::OperationId(_) => None,
         }
     }
@@ -164,9 +193,38 @@ impl IntoTemplateProperty<'static> for OperationTemplatePropertyKind {
         match self {
             OperationTemplatePropertyKind::Core(property) => property.try_into_template(),
             OperationTemplatePropertyKind::Operation(_) => None,
+            OperationTemplatePropertyKind::OperationList(_) => None,
             OperationTemplatePropertyKind::OperationId(property) => Some(property.into_template()),
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value>>> {
+        match self {
+            OperationTemplatePropertyKind::Core(property) => property.try_into_json(),
+            OperationTemplatePropertyKind::Operation(property) => Some(property.into_json()),
+            OperationTemplatePropertyKind::OperationList(property) => Some(property.into_json()),
+            OperationTemplatePropertyKind::OperationId(property) => Some(property.into_json()),
+        }
+    }
+
+    fn with_error_name(self, name: &str) -> Self {
+        match self {
+            OperationTemplatePropertyKind::Core(property) => {
+                OperationTemplatePropertyKind::Core(property.with_error_name(name))
+            }
+            OperationTemplatePropertyKind::Operation(property) => {
+                OperationTemplatePropertyKind::Operation(Box::new(property.with_error_name(name)))
+            }
+            OperationTemplatePropertyKind::OperationList(property) => {
+                OperationTemplatePropertyKind::OperationList(Box::new(
+                    property.with_error_name(name),
+                ))
+            }
+            OperationTemplatePropertyKind::OperationId(property) => {
+                OperationTemplatePropertyKind::OperationId(Box::new(property.with_error_name(name)))
+            }
+        }
+    }
 }
 
 /// Table of functions that translate method call node of self type `T`.
@@ -182,7 +240,7 @@ pub struct OperationTemplateBuildFnTable {
 
 impl OperationTemplateBuildFnTable {
     /// Creates new symbol table containing the builtin methods.
-    fn builtin() -> Self {
+    pub fn builtin() -> Self {
         OperationTemplateBuildFnTable {
             core: CoreTemplateBuildFnTable::builtin(),
             operation_methods: builtin_operation_methods(),
@@ -198,6 +256,16 @@ impl OperationTemplateBuildFnTable {
         }
     }
 
+    /// Returns the method names registered for each type.
+    pub fn method_names(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        let mut names = self.core.method_names();
+        names.extend([
+            ("Operation", sorted_fn_names(&self.operation_methods)),
+            ("OperationId", sorted_fn_names(&self.operation_id_methods)),
+        ]);
+        names
+    }
+
     fn merge(&mut self, other: OperationTemplateBuildFnTable) {
         let OperationTemplateBuildFnTable {
             core,
@@ -211,6 +279,27 @@ impl OperationTemplateBuildFnTable {
     }
 }
 
+impl ToJson for Operation {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let metadata = self.metadata();
+        let time = TimestampRange {
+            start: metadata.start_time.clone(),
+            end: metadata.end_time.clone(),
+        };
+        let parent_ids: Vec<_> = self.parent_ids().iter().map(|id| id.hex()).collect();
+        Ok(serde_json::json!({
+            "description": metadata.description,
+            "hostname": metadata.hostname,
+            "id": self.id().hex(),
+            "is_snapshot": metadata.is_snapshot,
+            "parents": parent_ids,
+            "tags": metadata.tags,
+            "time": time.to_json()?,
+            "username": metadata.username,
+        }))
+    }
+}
+
 fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
     type L = OperationTemplateLanguage;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -238,17 +327,49 @@ fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
         let out_property = self_property.map(|op| op.id().clone());
         Ok(L::wrap_operation_id(out_property))
     });
+    map.insert(
+        "is_snapshot",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|op| op.metadata().is_snapshot);
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map.insert(
+        "is_undo",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|op| {
+                op.metadata()
+                    .description
+                    .starts_with(UNDO_DESCRIPTION_PREFIX)
+            });
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map.insert(
+        "command",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|op| {
+                op.metadata()
+                    .tags
+                    .get("args")
+                    .map_or(vec![], |args| cli_util::split_args_tag(args))
+            });
+            Ok(L::wrap_string_list(out_property))
+        },
+    );
     map.insert("tags", |_language, _build_ctx, self_property, function| {
         template_parser::expect_no_arguments(function)?;
         let out_property = self_property.map(|op| {
-            // TODO: introduce map type
             op.metadata()
                 .tags
                 .iter()
-                .map(|(key, value)| format!("{key}: {value}"))
-                .join("\n")
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
         });
-        Ok(L::wrap_string(out_property))
+        Ok(L::wrap_string_map(out_property))
     });
     map.insert("time", |_language, _build_ctx, self_property, function| {
         template_parser::expect_no_arguments(function)?;
@@ -272,15 +393,84 @@ fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
         let out_property = self_property.map(move |op| op.id() == &root_op_id);
         Ok(L::wrap_boolean(out_property))
     });
+    map.insert(
+        "parents",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.and_then(|op| {
+                let parents: Vec<_> = op.parents().try_collect()?;
+                Ok(parents)
+            });
+            Ok(L::wrap_operation_list(out_property))
+        },
+    );
+    map.insert(
+        "changed_branches",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.and_then(|op| {
+                let (parent_view, view) = views_before_and_after(&op)?;
+                Ok(changed_branch_names(&parent_view, &view))
+            });
+            Ok(L::wrap_string_list(out_property))
+        },
+    );
+    map.insert(
+        "changed_refs_count",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.and_then(|op| {
+                let (parent_view, view) = views_before_and_after(&op)?;
+                let num_branches = changed_branch_names(&parent_view, &view).len();
+                let num_tags =
+                    refs::diff_named_ref_targets(parent_view.tags(), view.tags()).count();
+                let num_git_refs =
+                    refs::diff_named_ref_targets(parent_view.git_refs(), view.git_refs()).count();
+                Ok(i64::try_from(num_branches + num_tags + num_git_refs)?)
+            });
+            Ok(L::wrap_integer(out_property))
+        },
+    );
     map
 }
 
+/// Loads the views before and after the operation. The view before a merge
+/// operation is taken from its first parent, and the one before the root
+/// operation is empty.
+fn views_before_and_after(op: &Operation) -> Result<(View, View), TemplatePropertyError> {
+    let parent_view = match op.parents().next() {
+        Some(parent) => parent?.view()?,
+        None => View::new(op_store::View::default()),
+    };
+    Ok((parent_view, op.view()?))
+}
+
+/// Names of the local branches and `name@remote` of the remote branches which
+/// differ between the views.
+fn changed_branch_names(parent_view: &View, view: &View) -> Vec<String> {
+    let local_names =
+        refs::diff_named_ref_targets(parent_view.local_branches(), view.local_branches())
+            .map(|(name, _)| name.to_owned());
+    let remote_names = refs::diff_named_remote_refs(
+        parent_view.all_remote_branches(),
+        view.all_remote_branches(),
+    )
+    .map(|((name, remote_name), _)| format!("{name}@{remote_name}"));
+    local_names.chain(remote_names).collect()
+}
+
 impl Template for OperationId {
     fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
         write!(formatter, "{}", self.hex())
     }
 }
 
+impl ToJson for OperationId {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.hex().into())
+    }
+}
+
 fn builtin_operation_id_methods() -> OperationTemplateBuildMethodFnMap<OperationId> {
     type L = OperationTemplateLanguage;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -12,20 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
 
 use itertools::Itertools as _;
 use jj_lib::backend::{Signature, Timestamp};
+use jj_lib::footer;
+use jj_lib::time_util::DatePattern;
+use regex::Regex;
 
 use crate::template_parser::{
     self, BinaryOp, ExpressionKind, ExpressionNode, FunctionCallNode, TemplateAliasesMap,
     TemplateParseError, TemplateParseErrorKind, TemplateParseResult, UnaryOp,
 };
 use crate::templater::{
-    ConcatTemplate, ConditionalTemplate, IntoTemplate, LabelTemplate, ListPropertyTemplate,
-    ListTemplate, Literal, PlainTextFormattedProperty, PropertyPlaceholder, ReformatTemplate,
-    SeparateTemplate, Template, TemplateProperty, TemplatePropertyError, TemplatePropertyExt as _,
-    TemplateRenderer, TimestampRange,
+    CoalesceTemplate, ConcatTemplate, ConditionalTemplate, HyperlinkTemplate, IntoJson,
+    IntoTemplate, LabelTemplate, ListPropertyTemplate, ListTemplate, Literal,
+    PlainTextFormattedProperty, PropertyPlaceholder, ReformatTemplate, SeparateTemplate,
+    SerializableListTemplate, Template, TemplateProperty, TemplatePropertyError,
+    TemplatePropertyExt as _, TemplateRenderer, TimestampRange,
 };
 use crate::{text_util, time_util};
 
@@ -37,6 +42,9 @@ pub trait TemplateLanguage<'a> {
     fn wrap_string_list(
         property: impl TemplateProperty<Output = Vec<String>> + 'a,
     ) -> Self::Property;
+    fn wrap_string_map(
+        property: impl TemplateProperty<Output = BTreeMap<String, String>> + 'a,
+    ) -> Self::Property;
     fn wrap_boolean(property: impl TemplateProperty<Output = bool> + 'a) -> Self::Property;
     fn wrap_integer(property: impl TemplateProperty<Output = i64> + 'a) -> Self::Property;
     fn wrap_signature(property: impl TemplateProperty<Output = Signature> + 'a) -> Self::Property;
@@ -79,6 +87,7 @@ macro_rules! impl_core_wrap_property_fns {
             $a, $crate::template_builder::CoreTemplatePropertyKind, $outer, {
                 wrap_string(String) => String,
                 wrap_string_list(Vec<String>) => StringList,
+                wrap_string_map(std::collections::BTreeMap<String, String>) => StringMap,
                 wrap_boolean(bool) => Boolean,
                 wrap_integer(i64) => Integer,
                 wrap_signature(jj_lib::backend::Signature) => Signature,
@@ -123,11 +132,25 @@ pub trait IntoTemplateProperty<'a> {
 
     fn try_into_plain_text(self) -> Option<Box<dyn TemplateProperty<Output = String> + 'a>>;
     fn try_into_template(self) -> Option<Box<dyn Template + 'a>>;
+    /// Converts the property to a JSON value, or returns `None` if the type
+    /// isn't serializable.
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>>;
+
+    /// Annotates evaluation error of this property with the given `name`.
+    ///
+    /// The default implementation returns the property as is.
+    fn with_error_name(self, _name: &str) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
 }
 
 pub enum CoreTemplatePropertyKind<'a> {
     String(Box<dyn TemplateProperty<Output = String> + 'a>),
     StringList(Box<dyn TemplateProperty<Output = Vec<String>> + 'a>),
+    StringMap(Box<dyn TemplateProperty<Output = BTreeMap<String, String>> + 'a>),
     Boolean(Box<dyn TemplateProperty<Output = bool> + 'a>),
     Integer(Box<dyn TemplateProperty<Output = i64> + 'a>),
     Signature(Box<dyn TemplateProperty<Output = Signature> + 'a>),
@@ -157,6 +180,9 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::StringList(property) => {
                 Some(Box::new(property.map(|l| !l.is_empty())))
             }
+            CoreTemplatePropertyKind::StringMap(property) => {
+                Some(Box::new(property.map(|m| !m.is_empty())))
+            }
             CoreTemplatePropertyKind::Boolean(property) => Some(property),
             CoreTemplatePropertyKind::Integer(_) => None,
             CoreTemplatePropertyKind::Signature(_) => None,
@@ -199,6 +225,7 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
         match self {
             CoreTemplatePropertyKind::String(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::StringList(property) => Some(property.into_template()),
+            CoreTemplatePropertyKind::StringMap(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Boolean(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Integer(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Signature(property) => Some(property.into_template()),
@@ -208,6 +235,55 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::ListTemplate(template) => Some(template.into_template()),
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        match self {
+            CoreTemplatePropertyKind::String(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::StringList(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::StringMap(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Boolean(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Integer(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Signature(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Timestamp(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::TimestampRange(property) => Some(property.into_json()),
+            // Templates have no structure to serialize.
+            CoreTemplatePropertyKind::Template(_) => None,
+            CoreTemplatePropertyKind::ListTemplate(template) => template.try_into_json(),
+        }
+    }
+
+    fn with_error_name(self, name: &str) -> Self {
+        match self {
+            CoreTemplatePropertyKind::String(property) => {
+                CoreTemplatePropertyKind::String(Box::new(property.with_error_name(name)))
+            }
+            CoreTemplatePropertyKind::StringList(property) => {
+                CoreTemplatePropertyKind::StringList(Box::new(property.with_error_name(name)))
+            }
+            CoreTemplatePropertyKind::StringMap(property) => {
+                CoreTemplatePropertyKind::StringMap(Box::new(property.with_error_name(name)))
+            }
+            CoreTemplatePropertyKind::Boolean(property) => {
+                CoreTemplatePropertyKind::Boolean(Box::new(property.with_error_name(name)))
+            }
+            CoreTemplatePropertyKind::Integer(property) => {
+                CoreTemplatePropertyKind::Integer(Box::new(property.with_error_name(name)))
+            }
+            CoreTemplatePropertyKind::Signature(property) => {
+                CoreTemplatePropertyKind::Signature(Box::new(property.with_error_name(name)))
+            }
+            CoreTemplatePropertyKind::Timestamp(property) => {
+                CoreTemplatePropertyKind::Timestamp(Box::new(property.with_error_name(name)))
+            }
+            CoreTemplatePropertyKind::TimestampRange(property) => {
+                CoreTemplatePropertyKind::TimestampRange(Box::new(property.with_error_name(name)))
+            }
+            // Templates print errors of the inner properties, which are
+            // annotated separately.
+            kind @ (CoreTemplatePropertyKind::Template(_)
+            | CoreTemplatePropertyKind::ListTemplate(_)) => kind,
+        }
+    }
 }
 
 /// Function that translates global function call node.
@@ -243,6 +319,7 @@ pub type TemplateBuildMethodFnMap<'a, L, T> =
 pub struct CoreTemplateBuildFnTable<'a, L: TemplateLanguage<'a> + ?Sized> {
     pub functions: TemplateBuildFunctionFnMap<'a, L>,
     pub string_methods: TemplateBuildMethodFnMap<'a, L, String>,
+    pub string_map_methods: TemplateBuildMethodFnMap<'a, L, BTreeMap<String, String>>,
     pub boolean_methods: TemplateBuildMethodFnMap<'a, L, bool>,
     pub integer_methods: TemplateBuildMethodFnMap<'a, L, i64>,
     pub signature_methods: TemplateBuildMethodFnMap<'a, L, Signature>,
@@ -250,6 +327,13 @@ pub struct CoreTemplateBuildFnTable<'a, L: TemplateLanguage<'a> + ?Sized> {
     pub timestamp_range_methods: TemplateBuildMethodFnMap<'a, L, TimestampRange>,
 }
 
+/// Returns the names registered in the function table in sorted order.
+pub fn sorted_fn_names<'s, F>(table: &HashMap<&'s str, F>) -> Vec<&'s str> {
+    let mut names = table.keys().copied().collect_vec();
+    names.sort_unstable();
+    names
+}
+
 pub fn merge_fn_map<'s, F>(base: &mut HashMap<&'s str, F>, extension: HashMap<&'s str, F>) {
     for (name, function) in extension {
         if base.insert(name, function).is_some() {
@@ -264,8 +348,9 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
         CoreTemplateBuildFnTable {
             functions: builtin_functions(),
             string_methods: builtin_string_methods(),
+            string_map_methods: builtin_string_map_methods(),
             boolean_methods: HashMap::new(),
-            integer_methods: HashMap::new(),
+            integer_methods: builtin_integer_methods(),
             signature_methods: builtin_signature_methods(),
             timestamp_methods: builtin_timestamp_methods(),
             timestamp_range_methods: builtin_timestamp_range_methods(),
@@ -276,6 +361,7 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
         CoreTemplateBuildFnTable {
             functions: HashMap::new(),
             string_methods: HashMap::new(),
+            string_map_methods: HashMap::new(),
             boolean_methods: HashMap::new(),
             integer_methods: HashMap::new(),
             signature_methods: HashMap::new(),
@@ -288,6 +374,7 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
         let CoreTemplateBuildFnTable {
             functions,
             string_methods,
+            string_map_methods,
             boolean_methods,
             integer_methods,
             signature_methods,
@@ -297,6 +384,7 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
 
         merge_fn_map(&mut self.functions, functions);
         merge_fn_map(&mut self.string_methods, string_methods);
+        merge_fn_map(&mut self.string_map_methods, string_map_methods);
         merge_fn_map(&mut self.boolean_methods, boolean_methods);
         merge_fn_map(&mut self.integer_methods, integer_methods);
         merge_fn_map(&mut self.signature_methods, signature_methods);
@@ -304,6 +392,22 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
         merge_fn_map(&mut self.timestamp_range_methods, timestamp_range_methods);
     }
 
+    /// Returns the method names registered for each type.
+    pub fn method_names(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        vec![
+            ("String", sorted_fn_names(&self.string_methods)),
+            ("StringMap", sorted_fn_names(&self.string_map_methods)),
+            ("Boolean", sorted_fn_names(&self.boolean_methods)),
+            ("Integer", sorted_fn_names(&self.integer_methods)),
+            ("Signature", sorted_fn_names(&self.signature_methods)),
+            ("Timestamp", sorted_fn_names(&self.timestamp_methods)),
+            (
+                "TimestampRange",
+                sorted_fn_names(&self.timestamp_range_methods),
+            ),
+        ]
+    }
+
     /// Translates the function call node `function` by using this symbol table.
     pub fn build_function(
         &self,
@@ -337,6 +441,11 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
                     L::wrap_string(item)
                 })
             }
+            CoreTemplatePropertyKind::StringMap(property) => {
+                let table = &self.string_map_methods;
+                let build = template_parser::lookup_method("StringMap", table, function)?;
+                build(language, build_ctx, property, function)
+            }
             CoreTemplatePropertyKind::Boolean(property) => {
                 let table = &self.boolean_methods;
                 let build = template_parser::lookup_method("Boolean", table, function)?;
@@ -405,6 +514,12 @@ impl<'a, P: IntoTemplateProperty<'a>> Expression<P> {
         self.property.try_into_plain_text()
     }
 
+    pub fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        self.property.try_into_json()
+    }
+
     pub fn try_into_template(self) -> Option<Box<dyn Template + 'a>> {
         let template = self.property.try_into_template()?;
         if self.labels.is_empty() {
@@ -452,7 +567,8 @@ fn build_keyword<'a, L: TemplateLanguage<'a> + ?Sized>(
             }
             // Since keyword is a 0-ary method, any argument errors mean there's
             // no such keyword.
-            TemplateParseErrorKind::InvalidArguments { .. } => {
+            TemplateParseErrorKind::InvalidArguments { .. }
+            | TemplateParseErrorKind::InvalidArgumentCount { .. } => {
                 let kind = TemplateParseErrorKind::NoSuchKeyword {
                     name: name.to_owned(),
                     // TODO: might be better to phrase the error differently
@@ -506,9 +622,62 @@ fn build_binary_operation<'a, L: TemplateLanguage<'a> + ?Sized>(
             let out = lhs.and_then(move |l| Ok(l && rhs.extract()?));
             Ok(L::wrap_boolean(out))
         }
+        BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Ge | BinaryOp::Gt | BinaryOp::Le | BinaryOp::Lt => {
+            let lhs = expect_integer_expression(language, build_ctx, lhs_node)?;
+            let rhs = expect_integer_expression(language, build_ctx, rhs_node)?;
+            let out = (lhs, rhs).map(move |(l, r)| match op {
+                BinaryOp::Eq => l == r,
+                BinaryOp::Ne => l != r,
+                BinaryOp::Ge => l >= r,
+                BinaryOp::Gt => l > r,
+                BinaryOp::Le => l <= r,
+                BinaryOp::Lt => l < r,
+                _ => unreachable!(),
+            });
+            Ok(L::wrap_boolean(out))
+        }
+        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => {
+            let lhs = expect_integer_expression(language, build_ctx, lhs_node)?;
+            let rhs = expect_integer_expression(language, build_ctx, rhs_node)?;
+            let out = (lhs, rhs).and_then(move |(l, r)| {
+                let overflow = |verb: &str| {
+                    TemplatePropertyError(format!("Attempt to {verb} with overflow").into())
+                };
+                let divide_by_zero = || TemplatePropertyError("Attempt to divide by zero".into());
+                match op {
+                    BinaryOp::Add => l.checked_add(r).ok_or_else(|| overflow("add")),
+                    BinaryOp::Sub => l.checked_sub(r).ok_or_else(|| overflow("subtract")),
+                    BinaryOp::Mul => l.checked_mul(r).ok_or_else(|| overflow("multiply")),
+                    BinaryOp::Div if r == 0 => Err(divide_by_zero()),
+                    BinaryOp::Div => l.checked_div(r).ok_or_else(|| overflow("divide")),
+                    BinaryOp::Rem if r == 0 => Err(divide_by_zero()),
+                    BinaryOp::Rem => l
+                        .checked_rem(r)
+                        .ok_or_else(|| overflow("calculate the remainder")),
+                    _ => unreachable!(),
+                }
+            });
+            Ok(L::wrap_integer(out))
+        }
     }
 }
 
+fn builtin_integer_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
+) -> TemplateBuildMethodFnMap<'a, L, i64> {
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = TemplateBuildMethodFnMap::<L, i64>::new();
+    map.insert(
+        "to_string",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|i| i.to_string());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map
+}
+
 fn builtin_string_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
 ) -> TemplateBuildMethodFnMap<'a, L, String> {
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -519,6 +688,18 @@ fn builtin_string_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
         let out_property = self_property.and_then(|s| Ok(s.len().try_into()?));
         Ok(L::wrap_integer(out_property))
     });
+    map.insert(
+        "parse_int",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.and_then(|s| {
+                s.trim()
+                    .parse()
+                    .map_err(|_| TemplatePropertyError(format!("Invalid integer: {s:?}").into()))
+            });
+            Ok(L::wrap_integer(out_property))
+        },
+    );
     map.insert(
         "contains",
         |language, build_ctx, self_property, function| {
@@ -599,11 +780,118 @@ fn builtin_string_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
             Ok(L::wrap_string(out_property))
         },
     );
+    map.insert(
+        "shorten_description",
+        |language, build_ctx, self_property, function| {
+            let ([width_node], [strip_type_node]) = template_parser::expect_arguments(function)?;
+            let width_property = expect_usize_expression(language, build_ctx, width_node)?;
+            let strip_type_property = strip_type_node
+                .map(|node| expect_boolean_expression(language, build_ctx, node))
+                .transpose()?;
+            let out_property = (self_property, width_property, strip_type_property).map(
+                |(s, width, strip_type)| {
+                    shorten_description(&s, width, strip_type.unwrap_or(false))
+                },
+            );
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    // Alias of first_line(), paired with body().
+    map.insert("subject", map["first_line"]);
+    map.insert("body", |_language, _build_ctx, self_property, function| {
+        template_parser::expect_no_arguments(function)?;
+        let out_property = self_property.map(|s| {
+            let (message, _) = footer::split_footer(&s);
+            let (_, body) = message.split_once('\n').unwrap_or_default();
+            text_util::complete_newline(body.trim_matches('\n'))
+        });
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "footers",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|s| {
+                let (_, footer) = footer::split_footer(&s);
+                footer.lines().map(|l| l.to_owned()).collect()
+            });
+            Ok(L::wrap_string_list(out_property))
+        },
+    );
     map.insert("lines", |_language, _build_ctx, self_property, function| {
         template_parser::expect_no_arguments(function)?;
         let out_property = self_property.map(|s| s.lines().map(|l| l.to_owned()).collect());
         Ok(L::wrap_string_list(out_property))
     });
+    map.insert("match", |_language, _build_ctx, self_property, function| {
+        let [regex_node] = template_parser::expect_exact_arguments(function)?;
+        let regex = expect_regex_literal(regex_node)?;
+        let out_property = self_property.map(move |s| {
+            regex
+                .find(&s)
+                .map_or_else(String::new, |m| m.as_str().to_owned())
+        });
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert("replace", |language, build_ctx, self_property, function| {
+        let [regex_node, replacement_node] = template_parser::expect_exact_arguments(function)?;
+        let regex = expect_regex_literal(regex_node)?;
+        let replacement_property =
+            expect_plain_text_expression(language, build_ctx, replacement_node)?;
+        let out_property = (self_property, replacement_property)
+            .map(move |(s, replacement)| regex.replace_all(&s, replacement).into_owned());
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert("capture", |language, build_ctx, self_property, function| {
+        let [regex_node, index_node] = template_parser::expect_exact_arguments(function)?;
+        let regex = expect_regex_literal(regex_node)?;
+        let index_property = expect_usize_expression(language, build_ctx, index_node)?;
+        let out_property = (self_property, index_property).map(move |(s, index)| {
+            regex
+                .captures(&s)
+                .and_then(|captures| captures.get(index))
+                .map_or_else(String::new, |m| m.as_str().to_owned())
+        });
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "pad_start",
+        |language, build_ctx, self_property, function| {
+            let [width_node] = template_parser::expect_exact_arguments(function)?;
+            let width_property = expect_usize_expression(language, build_ctx, width_node)?;
+            let out_property =
+                (self_property, width_property).map(|(s, width)| text_util::pad_start(&s, width));
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert("pad_end", |language, build_ctx, self_property, function| {
+        let [width_node] = template_parser::expect_exact_arguments(function)?;
+        let width_property = expect_usize_expression(language, build_ctx, width_node)?;
+        let out_property =
+            (self_property, width_property).map(|(s, width)| text_util::pad_end(&s, width));
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "truncate_end",
+        |language, build_ctx, self_property, function| {
+            let [width_node] = template_parser::expect_exact_arguments(function)?;
+            let width_property = expect_usize_expression(language, build_ctx, width_node)?;
+            let out_property = (self_property, width_property)
+                .map(|(s, width)| text_util::elide_end(&s, "", width).0.into_owned());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert("wrap", |language, build_ctx, self_property, function| {
+        let [width_node] = template_parser::expect_exact_arguments(function)?;
+        let width_property = expect_usize_expression(language, build_ctx, width_node)?;
+        let out_property = (self_property, width_property).map(|(s, width)| {
+            text_util::wrap_bytes(s.as_bytes(), width)
+                .iter()
+                .map(|line| String::from_utf8_lossy(line))
+                .join("\n")
+        });
+        Ok(L::wrap_string(out_property))
+    });
     map.insert("upper", |_language, _build_ctx, self_property, function| {
         template_parser::expect_no_arguments(function)?;
         let out_property = self_property.map(|s| s.to_uppercase());
@@ -617,6 +905,55 @@ fn builtin_string_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
     map
 }
 
+/// Compiles the string literal `node` as a regular expression.
+fn expect_regex_literal(node: &ExpressionNode) -> TemplateParseResult<Regex> {
+    template_parser::expect_string_literal_with(node, |text, span| {
+        Regex::new(text).map_err(|err| {
+            TemplateParseError::unexpected_expression(format!("Invalid regex: {err}"), span)
+        })
+    })
+}
+
+/// Takes the first line of the `description`, and truncates it to `width` with
+/// an ellipsis. Control characters are escaped. If `strip_type` is set, the
+/// conventional-commit type prefix such as `fix(cli)!: ` is removed.
+fn shorten_description(description: &str, width: usize, strip_type: bool) -> String {
+    let mut line = description.lines().next().unwrap_or_default().trim();
+    if strip_type {
+        line = strip_conventional_commit_type(line);
+    }
+    let mut escaped = String::with_capacity(line.len());
+    for c in line.chars() {
+        if c.is_control() {
+            escaped.extend(c.escape_default());
+        } else {
+            escaped.push(c);
+        }
+    }
+    let (shortened, _) = text_util::elide_end(&escaped, "…", width);
+    shortened.into_owned()
+}
+
+/// Removes the `type(scope)!: ` prefix of a conventional-commit subject line.
+fn strip_conventional_commit_type(line: &str) -> &str {
+    let Some((prefix, rest)) = line.split_once(": ") else {
+        return line;
+    };
+    let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+    let type_name = match prefix.split_once('(') {
+        Some((type_name, scope)) => match scope.strip_suffix(')') {
+            Some(scope) if !scope.contains(['(', ')']) => type_name,
+            _ => return line,
+        },
+        None => prefix,
+    };
+    if !type_name.is_empty() && type_name.chars().all(|c| c.is_ascii_alphabetic()) {
+        rest.trim_start()
+    } else {
+        line
+    }
+}
+
 /// Clamps and aligns the given index `i` to char boundary.
 ///
 /// Negative index counts from the end. If the index isn't at a char boundary,
@@ -633,6 +970,59 @@ fn string_index_to_char_boundary(s: &str, i: isize) -> usize {
     }
 }
 
+fn builtin_string_map_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
+) -> TemplateBuildMethodFnMap<'a, L, BTreeMap<String, String>> {
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = TemplateBuildMethodFnMap::<L, BTreeMap<String, String>>::new();
+    map.insert("len", |_language, _build_ctx, self_property, function| {
+        template_parser::expect_no_arguments(function)?;
+        let out_property = self_property.and_then(|m| Ok(m.len().try_into()?));
+        Ok(L::wrap_integer(out_property))
+    });
+    map.insert(
+        "is_empty",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|m| m.is_empty());
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map.insert("get", |language, build_ctx, self_property, function| {
+        let [key_node] = template_parser::expect_exact_arguments(function)?;
+        let key_property = expect_plain_text_expression(language, build_ctx, key_node)?;
+        let out_property =
+            (self_property, key_property).map(|(m, key)| m.get(&key).cloned().unwrap_or_default());
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "contains_key",
+        |language, build_ctx, self_property, function| {
+            let [key_node] = template_parser::expect_exact_arguments(function)?;
+            let key_property = expect_plain_text_expression(language, build_ctx, key_node)?;
+            let out_property = (self_property, key_property).map(|(m, key)| m.contains_key(&key));
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map.insert("keys", |_language, _build_ctx, self_property, function| {
+        template_parser::expect_no_arguments(function)?;
+        let out_property = self_property.map(|m| m.into_keys().collect());
+        Ok(L::wrap_string_list(out_property))
+    });
+    map.insert(
+        "values",
+        |_language, _build_ctx, self_property, function| {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|m| m.into_values().collect());
+            Ok(L::wrap_string_list(out_property))
+        },
+    );
+    map.insert("map", |language, build_ctx, self_property, function| {
+        build_string_map_map_operation(language, build_ctx, self_property, function)
+    });
+    map
+}
+
 fn builtin_signature_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
 ) -> TemplateBuildMethodFnMap<'a, L, Signature> {
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -723,9 +1113,51 @@ fn builtin_timestamp_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
         });
         Ok(L::wrap_timestamp(out_property))
     });
+    map.insert("offset", |language, build_ctx, self_property, function| {
+        let [minutes_node] = template_parser::expect_exact_arguments(function)?;
+        let minutes_property = expect_integer_expression(language, build_ctx, minutes_node)?;
+        let out_property =
+            (self_property, minutes_property).and_then(|(mut timestamp, minutes)| {
+                // chrono::FixedOffset accepts offsets strictly less than a day.
+                if minutes.unsigned_abs() >= 24 * 60 {
+                    return Err(TemplatePropertyError(
+                        format!("Timezone offset out of range: {minutes} minutes").into(),
+                    ));
+                }
+                timestamp.tz_offset = minutes.try_into()?;
+                Ok(timestamp)
+            });
+        Ok(L::wrap_timestamp(out_property))
+    });
+    map.insert("after", |_language, _build_ctx, self_property, function| {
+        let [date_pattern_node] = template_parser::expect_exact_arguments(function)?;
+        let date_pattern = expect_date_pattern(date_pattern_node, "after")?;
+        let out_property = self_property.map(move |timestamp| date_pattern.matches(&timestamp));
+        Ok(L::wrap_boolean(out_property))
+    });
+    map.insert(
+        "before",
+        |_language, _build_ctx, self_property, function| {
+            let [date_pattern_node] = template_parser::expect_exact_arguments(function)?;
+            let date_pattern = expect_date_pattern(date_pattern_node, "before")?;
+            let out_property = self_property.map(move |timestamp| date_pattern.matches(&timestamp));
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
     map
 }
 
+/// Parses the string literal `node` as a date pattern of the given `kind`.
+/// Relative dates are resolved against the current time.
+fn expect_date_pattern(node: &ExpressionNode, kind: &str) -> TemplateParseResult<DatePattern> {
+    // No dynamic string is allowed since the current time is captured here.
+    template_parser::expect_string_literal_with(node, |date, span| {
+        let now = chrono::Local::now().fixed_offset();
+        DatePattern::from_str_kind(date, kind, now)
+            .map_err(|err| TemplateParseError::unexpected_expression(err.to_string(), span))
+    })
+}
+
 fn builtin_timestamp_range_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
 ) -> TemplateBuildMethodFnMap<'a, L, TimestampRange> {
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -760,9 +1192,13 @@ fn build_list_template_method<'a, L: TemplateLanguage<'a> + ?Sized>(
 ) -> TemplateParseResult<L::Property> {
     let property = match function.name {
         "join" => {
-            let [separator_node] = template_parser::expect_exact_arguments(function)?;
+            let ([separator_node], [last_separator_node]) =
+                template_parser::expect_arguments(function)?;
             let separator = expect_template_expression(language, build_ctx, separator_node)?;
-            L::wrap_template(self_template.join(separator))
+            let last_separator = last_separator_node
+                .map(|node| expect_template_expression(language, build_ctx, node))
+                .transpose()?;
+            L::wrap_template(self_template.join(separator, last_separator))
         }
         _ => return Err(TemplateParseError::no_such_method("ListTemplate", function)),
     };
@@ -789,13 +1225,23 @@ where
             let out_property = self_property.and_then(|items| Ok(items.len().try_into()?));
             L::wrap_integer(out_property)
         }
+        "is_empty" => {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|items| items.is_empty());
+            L::wrap_boolean(out_property)
+        }
         "join" => {
-            let [separator_node] = template_parser::expect_exact_arguments(function)?;
+            let ([separator_node], [last_separator_node]) =
+                template_parser::expect_arguments(function)?;
             let separator = expect_template_expression(language, build_ctx, separator_node)?;
+            let last_separator = last_separator_node
+                .map(|node| expect_template_expression(language, build_ctx, node))
+                .transpose()?;
             let template =
                 ListPropertyTemplate::new(self_property, separator, |formatter, item| {
                     item.format(formatter)
-                });
+                })
+                .with_last_separator(last_separator);
             L::wrap_template(Box::new(template))
         }
         "map" => build_map_operation(language, build_ctx, self_property, function, wrap_item)?,
@@ -821,6 +1267,11 @@ where
             let out_property = self_property.and_then(|items| Ok(items.len().try_into()?));
             L::wrap_integer(out_property)
         }
+        "is_empty" => {
+            template_parser::expect_no_arguments(function)?;
+            let out_property = self_property.map(|items| items.is_empty());
+            L::wrap_boolean(out_property)
+        }
         // No "join"
         "map" => build_map_operation(language, build_ctx, self_property, function, wrap_item)?,
         _ => return Err(TemplateParseError::no_such_method("List", function)),
@@ -849,14 +1300,72 @@ where
     // for each item.
     let [lambda_node] = template_parser::expect_exact_arguments(function)?;
     let item_placeholder = PropertyPlaceholder::new();
-    let item_template = template_parser::expect_lambda_with(lambda_node, |lambda, _span| {
-        let item_fn = || wrap_item(item_placeholder.clone());
+    let (item_template, item_json) =
+        template_parser::expect_lambda_with(lambda_node, |lambda, _span| {
+            let item_fn = || wrap_item(item_placeholder.clone());
+            let mut local_variables = build_ctx.local_variables.clone();
+            if let [name] = lambda.params.as_slice() {
+                local_variables.insert(name, &item_fn);
+            } else {
+                return Err(TemplateParseError::unexpected_expression(
+                    "Expected 1 lambda parameters",
+                    lambda.params_span,
+                ));
+            }
+            let inner_build_ctx = BuildContext {
+                local_variables,
+                self_variable: build_ctx.self_variable,
+            };
+            let item_template =
+                expect_template_expression(language, &inner_build_ctx, &lambda.body)?;
+            // The body is built again as a property so the list can be serialized.
+            let item_json =
+                build_expression(language, &inner_build_ctx, &lambda.body)?.try_into_json();
+            Ok((item_template, item_json))
+        })?;
+    let self_property = Rc::new(self_property);
+    let json_item_placeholder = item_placeholder.clone();
+    let list_template = ListPropertyTemplate::new(
+        self_property.clone(),
+        Literal(" "), // separator
+        move |formatter, item| {
+            item_placeholder.with_value(item, || item_template.format(formatter))
+        },
+    );
+    let Some(item_json) = item_json else {
+        return Ok(L::wrap_list_template(Box::new(list_template)));
+    };
+    let json_property = self_property.and_then(move |items| {
+        let values = items
+            .into_iter()
+            .map(|item| json_item_placeholder.with_value(item, || item_json.extract()))
+            .try_collect()?;
+        Ok(serde_json::Value::Array(values))
+    });
+    let list_template = SerializableListTemplate::new(list_template, json_property);
+    Ok(L::wrap_list_template(Box::new(list_template)))
+}
+
+/// Builds expression that applies template to each `(key, value)` entry of
+/// the map property, in key order.
+fn build_string_map_map_operation<'a, L: TemplateLanguage<'a> + ?Sized>(
+    language: &L,
+    build_ctx: &BuildContext<L::Property>,
+    self_property: impl TemplateProperty<Output = BTreeMap<String, String>> + 'a,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<L::Property> {
+    let [lambda_node] = template_parser::expect_exact_arguments(function)?;
+    let entry_placeholder = PropertyPlaceholder::<(String, String)>::new();
+    let entry_template = template_parser::expect_lambda_with(lambda_node, |lambda, _span| {
+        let key_fn = || L::wrap_string(entry_placeholder.clone().map(|(key, _)| key));
+        let value_fn = || L::wrap_string(entry_placeholder.clone().map(|(_, value)| value));
         let mut local_variables = build_ctx.local_variables.clone();
-        if let [name] = lambda.params.as_slice() {
-            local_variables.insert(name, &item_fn);
+        if let [key_name, value_name] = lambda.params.as_slice() {
+            local_variables.insert(key_name, &key_fn);
+            local_variables.insert(value_name, &value_fn);
         } else {
             return Err(TemplateParseError::unexpected_expression(
-                "Expected 1 lambda parameters",
+                "Expected 2 lambda parameters",
                 lambda.params_span,
             ));
         }
@@ -869,8 +1378,8 @@ where
     let list_template = ListPropertyTemplate::new(
         self_property,
         Literal(" "), // separator
-        move |formatter, item| {
-            item_placeholder.with_value(item, || item_template.format(formatter))
+        move |formatter, entry| {
+            entry_placeholder.with_value(entry, || entry_template.format(formatter))
         },
     );
     Ok(L::wrap_list_template(Box::new(list_template)))
@@ -910,6 +1419,15 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
             content, labels,
         ))))
     });
+    map.insert("hyperlink", |language, build_ctx, function| {
+        let [url_node, content_node] = template_parser::expect_exact_arguments(function)?;
+        let url_property = expect_plain_text_expression(language, build_ctx, url_node)?;
+        let content = expect_template_expression(language, build_ctx, content_node)?;
+        Ok(L::wrap_template(Box::new(HyperlinkTemplate::new(
+            content,
+            url_property,
+        ))))
+    });
     map.insert("if", |language, build_ctx, function| {
         let ([condition_node, true_node], [false_node]) =
             template_parser::expect_arguments(function)?;
@@ -929,6 +1447,22 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
             .try_collect()?;
         Ok(L::wrap_template(Box::new(ConcatTemplate(contents))))
     });
+    map.insert("json", |language, build_ctx, function| {
+        let [value_node] = template_parser::expect_exact_arguments(function)?;
+        let value_property = build_expression(language, build_ctx, value_node)?
+            .try_into_json()
+            .ok_or_else(|| TemplateParseError::expected_type("Serializable", value_node.span))?;
+        let out_property = value_property.and_then(|value| Ok(serde_json::to_string(&value)?));
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert("coalesce", |language, build_ctx, function| {
+        let contents = function
+            .args
+            .iter()
+            .map(|node| expect_template_expression(language, build_ctx, node))
+            .try_collect()?;
+        Ok(L::wrap_template(Box::new(CoalesceTemplate(contents))))
+    });
     map.insert("separate", |language, build_ctx, function| {
         let ([separator_node], content_nodes) = template_parser::expect_some_arguments(function)?;
         let separator = expect_template_expression(language, build_ctx, separator_node)?;
@@ -983,7 +1517,10 @@ pub fn build_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
                             ["self"],
                         ))
                     })?;
-                Ok(Expression::with_label(property, *name))
+                Ok(Expression::with_label(
+                    property.with_error_name(name),
+                    *name,
+                ))
             }
         }
         ExpressionKind::Boolean(value) => {
@@ -1023,6 +1560,9 @@ pub fn build_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
             expression.property =
                 language.build_method(build_ctx, expression.property, &method.function)?;
             expression.labels.push(method.function.name.to_owned());
+            expression.property = expression
+                .property
+                .with_error_name(&expression.labels.join("."));
             Ok(expression)
         }
         ExpressionKind::Lambda(_) => Err(TemplateParseError::unexpected_expression(
@@ -1192,7 +1732,8 @@ mod tests {
         fn render_ok(&self, template: &str) -> String {
             let template = self.parse(template).unwrap();
             let mut output = Vec::new();
-            let mut formatter = ColorFormatter::new(&mut output, self.color_rules.clone().into());
+            let mut formatter = ColorFormatter::new(&mut output, self.color_rules.clone().into())
+                .with_hyperlinks(true);
             template.format(&(), &mut formatter).unwrap();
             drop(formatter);
             String::from_utf8(output).unwrap()
@@ -1259,7 +1800,7 @@ mod tests {
         1 | description ()
           |             ^---
           |
-          = expected <EOI>, `++`, `||`, or `&&`
+          = expected <EOI>, `++`, `||`, `&&`, `==`, `!=`, `>=`, `>`, `<=`, `<`, `+`, `-`, `*`, `/`, or `%`
         "###);
 
         insta::assert_snapshot!(env.parse_err(r#"foo"#), @r###"
@@ -1486,10 +2027,68 @@ mod tests {
         insta::assert_snapshot!(env.render_ok(r#"--2"#), @"2");
         insta::assert_snapshot!(env.render_ok(r#"-(3)"#), @"-3");
 
+        insta::assert_snapshot!(env.render_ok(r#"1 + 2 * 3"#), @"7");
+        insta::assert_snapshot!(env.render_ok(r#"(1 + 2) * 3"#), @"9");
+        insta::assert_snapshot!(env.render_ok(r#"10 - 3 - 2"#), @"5");
+        insta::assert_snapshot!(env.render_ok(r#"7 / 2"#), @"3");
+        insta::assert_snapshot!(env.render_ok(r#"-7 % 3"#), @"-1");
+        insta::assert_snapshot!(env.render_ok(r#"2 * -3"#), @"-6");
+        insta::assert_snapshot!(env.render_ok(r#""foo".len() + 1"#), @"4");
+
+        insta::assert_snapshot!(env.render_ok(r#"1 == 1"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#"1 != 1"#), @"false");
+        insta::assert_snapshot!(env.render_ok(r#"1 < 2"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#"2 <= 2"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#"1 > 2"#), @"false");
+        insta::assert_snapshot!(env.render_ok(r#"1 >= 2"#), @"false");
+        insta::assert_snapshot!(env.render_ok(r#"1 + 1 == 2 && 3 > 2"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#"if(2 > 1, "yes", "no")"#), @"yes");
+
+        insta::assert_snapshot!(env.parse_err(r#""a" + 1"#), @r###"
+         --> 1:1
+          |
+        1 | "a" + 1
+          | ^-^
+          |
+          = Expected expression of type "Integer"
+        "###);
+        insta::assert_snapshot!(env.parse_err(r#"1 == true"#), @r###"
+         --> 1:6
+          |
+        1 | 1 == true
+          |      ^--^
+          |
+          = Expected expression of type "Integer"
+        "###);
+
         // No panic on integer overflow.
         insta::assert_snapshot!(
             env.render_ok(r#"-i64_min"#),
             @"<Error: Attempt to negate with overflow>");
+        insta::assert_snapshot!(
+            env.render_ok(r#"i64_min - 1"#),
+            @"<Error: Attempt to subtract with overflow>");
+        insta::assert_snapshot!(
+            env.render_ok(r#"i64_min * -1"#),
+            @"<Error: Attempt to multiply with overflow>");
+        insta::assert_snapshot!(
+            env.render_ok(r#"i64_min / -1"#),
+            @"<Error: Attempt to divide with overflow>");
+        insta::assert_snapshot!(
+            env.render_ok(r#"1 / 0"#),
+            @"<Error: Attempt to divide by zero>");
+        insta::assert_snapshot!(
+            env.render_ok(r#"1 % 0"#),
+            @"<Error: Attempt to divide by zero>");
+    }
+
+    #[test]
+    fn test_integer_method() {
+        let env = TestTemplateEnv::new();
+
+        insta::assert_snapshot!(env.render_ok(r#"42.to_string()"#), @"42");
+        insta::assert_snapshot!(env.render_ok(r#"(-42).to_string().len()"#), @"3");
+        insta::assert_snapshot!(env.render_ok(r#"(1 + 2).to_string() ++ "x""#), @"3x");
     }
 
     #[test]
@@ -1506,8 +2105,8 @@ mod tests {
         // Short-circuiting
         env.add_keyword("bad_bool", || L::wrap_boolean(new_error_property("Bad")));
         insta::assert_snapshot!(env.render_ok(r#"false && bad_bool"#), @"false");
-        insta::assert_snapshot!(env.render_ok(r#"true && bad_bool"#), @"<Error: Bad>");
-        insta::assert_snapshot!(env.render_ok(r#"false || bad_bool"#), @"<Error: Bad>");
+        insta::assert_snapshot!(env.render_ok(r#"true && bad_bool"#), @"<Error: Failed to evaluate bad_bool: Bad>");
+        insta::assert_snapshot!(env.render_ok(r#"false || bad_bool"#), @"<Error: Failed to evaluate bad_bool: Bad>");
         insta::assert_snapshot!(env.render_ok(r#"true || bad_bool"#), @"true");
     }
 
@@ -1528,6 +2127,18 @@ mod tests {
         insta::assert_snapshot!(
             env.render_ok(r#""a\nb\nc".lines().join(sep.upper())"#),
             @"aSEPbSEPc");
+        // Distinct last separator
+        insta::assert_snapshot!(
+            env.render_ok(r#""a\nb\nc".lines().join(", ", " and ")"#),
+            @"a, b and c");
+        insta::assert_snapshot!(env.render_ok(r#""a\nb".lines().join(", ", " and ")"#), @"a and b");
+        insta::assert_snapshot!(env.render_ok(r#""a".lines().join(", ", " and ")"#), @"a");
+        insta::assert_snapshot!(
+            env.render_ok(r#""a\nb\nc".lines().map(|s| s.upper()).join(", ", " or ")"#),
+            @"A, B or C");
+
+        insta::assert_snapshot!(env.render_ok(r#""".lines().is_empty()"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#""a".lines().is_empty()"#), @"false");
 
         insta::assert_snapshot!(
             env.render_ok(r#""a\nb\nc".lines().map(|s| s ++ s)"#),
@@ -1614,6 +2225,47 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_string_map_method() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("tags", || {
+            L::wrap_string_map(Literal(BTreeMap::from([
+                ("b".to_owned(), "2".to_owned()),
+                ("a".to_owned(), "1".to_owned()),
+            ])))
+        });
+        env.add_keyword("no_tags", || L::wrap_string_map(Literal(BTreeMap::new())));
+
+        insta::assert_snapshot!(env.render_ok(r#"tags"#), @r###"
+        a: 1
+        b: 2
+        "###);
+        insta::assert_snapshot!(env.render_ok(r#"if(tags, "y", "n")"#), @"y");
+        insta::assert_snapshot!(env.render_ok(r#"if(no_tags, "y", "n")"#), @"n");
+        insta::assert_snapshot!(env.render_ok(r#"tags.len()"#), @"2");
+        insta::assert_snapshot!(env.render_ok(r#"no_tags.is_empty()"#), @"true");
+
+        insta::assert_snapshot!(env.render_ok(r#"tags.get("a")"#), @"1");
+        insta::assert_snapshot!(env.render_ok(r#"tags.get("c")"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#"tags.contains_key("b")"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#"tags.contains_key("c")"#), @"false");
+        insta::assert_snapshot!(env.render_ok(r#"tags.keys().join(",")"#), @"a,b");
+        insta::assert_snapshot!(env.render_ok(r#"tags.values()"#), @"1 2");
+
+        insta::assert_snapshot!(
+            env.render_ok(r#"tags.map(|k, v| k ++ "=" ++ v).join("&")"#),
+            @"a=1&b=2");
+        insta::assert_snapshot!(env.render_ok(r#"no_tags.map(|k, v| k)"#), @"");
+        insta::assert_snapshot!(env.parse_err(r#"tags.map(|e| e)"#), @r###"
+         --> 1:11
+          |
+        1 | tags.map(|e| e)
+          |           ^
+          |
+          = Expected 2 lambda parameters
+        "###);
+    }
+
     #[test]
     fn test_string_method() {
         let mut env = TestTemplateEnv::new();
@@ -1625,6 +2277,12 @@ mod tests {
         insta::assert_snapshot!(env.render_ok(r#""foo".len()"#), @"3");
         insta::assert_snapshot!(env.render_ok(r#""💩".len()"#), @"4");
 
+        insta::assert_snapshot!(env.render_ok(r#""42".parse_int()"#), @"42");
+        insta::assert_snapshot!(env.render_ok(r#"" -7\n".parse_int() * 2"#), @"-14");
+        insta::assert_snapshot!(
+            env.render_ok(r#""4x".parse_int()"#),
+            @r###"<Error: Failed to evaluate parse_int: Invalid integer: "4x">"###);
+
         insta::assert_snapshot!(env.render_ok(r#""fooo".contains("foo")"#), @"true");
         insta::assert_snapshot!(env.render_ok(r#""foo".contains("fooo")"#), @"false");
         insta::assert_snapshot!(env.render_ok(r#"description.contains("description")"#), @"true");
@@ -1635,6 +2293,24 @@ mod tests {
         insta::assert_snapshot!(env.render_ok(r#""".first_line()"#), @"");
         insta::assert_snapshot!(env.render_ok(r#""foo\nbar".first_line()"#), @"foo");
 
+        insta::assert_snapshot!(env.render_ok(r#""".subject()"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#""foo\nbar".subject()"#), @"foo");
+
+        insta::assert_snapshot!(env.render_ok(r#""".body()"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#""foo\n".body()"#), @"");
+        insta::assert_snapshot!(
+            env.render_ok(r#""foo\n\nbar\nbaz\n\nKey: value\n".body().lines().join("|")"#),
+            @"bar|baz");
+        insta::assert_snapshot!(
+            env.render_ok(r#""foo\n\nbar\n\nnot: a\nfooter\n".body().lines().join("|")"#),
+            @"bar||not: a|footer");
+
+        insta::assert_snapshot!(env.render_ok(r#""".footers()"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#""Key: value\n".footers()"#), @"");
+        insta::assert_snapshot!(
+            env.render_ok(r#""foo\n\nKey: value\nOther-Key: other\n".footers().join("|")"#),
+            @"Key: value|Other-Key: other");
+
         insta::assert_snapshot!(env.render_ok(r#""".lines()"#), @"");
         insta::assert_snapshot!(env.render_ok(r#""a\nb\nc\n".lines()"#), @"a b c");
 
@@ -1689,6 +2365,109 @@ mod tests {
         // ranges with end > start are empty
         insta::assert_snapshot!(env.render_ok(r#""abcdef".substr(4, 2)"#), @"");
         insta::assert_snapshot!(env.render_ok(r#""abcdef".substr(-2, -4)"#), @"");
+
+        insta::assert_snapshot!(env.render_ok(r#""".shorten_description(10)"#), @"");
+        insta::assert_snapshot!(
+            env.render_ok(r#""short\nbody".shorten_description(10)"#),
+            @"short");
+        insta::assert_snapshot!(
+            env.render_ok(r#""a longer subject line".shorten_description(10)"#),
+            @"a longer …");
+        insta::assert_snapshot!(
+            env.render_ok(r#""a\tb\0".shorten_description(10)"#),
+            @r###"a\tb\u{0}"###);
+        insta::assert_snapshot!(
+            env.render_ok(r#""fix(cli)!: subject".shorten_description(10, true)"#),
+            @"subject");
+        insta::assert_snapshot!(
+            env.render_ok(r#""fix(cli)!: subject".shorten_description(10, false)"#),
+            @"fix(cli)!…");
+        insta::assert_snapshot!(
+            env.render_ok(r#""not a type: subject".shorten_description(20, true)"#),
+            @"not a type: subject");
+        insta::assert_snapshot!(
+            env.render_ok(r#""一二三四".shorten_description(6)"#),
+            @"一二…");
+    }
+
+    #[test]
+    fn test_string_pad_and_wrap_method() {
+        let env = TestTemplateEnv::new();
+
+        insta::assert_snapshot!(env.render_ok(r#""[" ++ "ab".pad_start(4) ++ "]""#), @"[  ab]");
+        insta::assert_snapshot!(env.render_ok(r#""[" ++ "ab".pad_end(4) ++ "]""#), @"[ab  ]");
+        insta::assert_snapshot!(env.render_ok(r#""[" ++ "abcdef".pad_end(4) ++ "]""#), @"[abcdef]");
+        insta::assert_snapshot!(env.render_ok(r#""[" ++ "一".pad_start(4) ++ "]""#), @"[  一]");
+
+        insta::assert_snapshot!(env.render_ok(r#""abcdef".truncate_end(4)"#), @"abcd");
+        insta::assert_snapshot!(env.render_ok(r#""ab".truncate_end(4)"#), @"ab");
+        insta::assert_snapshot!(env.render_ok(r#""一二三".truncate_end(5)"#), @"一二");
+        // Truncate and pad to a fixed-width column
+        insta::assert_snapshot!(
+            env.render_ok(r#""[" ++ "abcdef".truncate_end(4).pad_end(4) ++ "]""#),
+            @"[abcd]");
+
+        insta::assert_snapshot!(
+            env.render_ok(r#""The quick fox jumps over the lazy dog".wrap(10)"#),
+            @r###"
+        The quick
+        fox jumps
+        over the
+        lazy dog
+        "###);
+        insta::assert_snapshot!(env.render_ok(r#""a b
+
+c".wrap(1)"#), @r###"
+        a
+        b
+
+        c
+        "###);
+
+        // Negative width is an error
+        insta::assert_snapshot!(
+            env.render_ok(r#""ab".pad_start(-1)"#),
+            @"<Error: Failed to evaluate pad_start: out of range integral type conversion attempted>");
+    }
+
+    #[test]
+    fn test_string_regex_method() {
+        let env = TestTemplateEnv::new();
+
+        insta::assert_snapshot!(env.render_ok(r##""fix #123 and #45".match("#[0-9]+")"##), @"#123");
+        insta::assert_snapshot!(env.render_ok(r##""no issue".match("#[0-9]+")"##), @"");
+
+        insta::assert_snapshot!(
+            env.render_ok(r#""JJ-12: subject".replace("^[A-Z]+-[0-9]+: ", "")"#),
+            @"subject");
+        insta::assert_snapshot!(
+            env.render_ok(r#""a-b-c".replace("-", "+")"#),
+            @"a+b+c");
+        insta::assert_snapshot!(
+            env.render_ok(r#""2024-03-01".replace("([0-9]+)-([0-9]+)-([0-9]+)", "$3/$2/$1")"#),
+            @"01/03/2024");
+
+        insta::assert_snapshot!(
+            env.render_ok(r#""Fixes: #123".capture("Fixes: #([0-9]+)", 1)"#),
+            @"123");
+        insta::assert_snapshot!(
+            env.render_ok(r#""Fixes: #123".capture("Fixes: #([0-9]+)", 0)"#),
+            @"Fixes: #123");
+        insta::assert_snapshot!(
+            env.render_ok(r#""Fixes: #123".capture("Fixes: #([0-9]+)", 2)"#),
+            @"");
+        insta::assert_snapshot!(env.render_ok(r##""none".capture("#([0-9]+)", 1)"##), @"");
+
+        // The regex must be a valid string literal
+        insta::assert_snapshot!(env.parse_err(r#""a".match("a" ++ "b")"#), @r###"
+         --> 1:11
+          |
+        1 | "a".match("a" ++ "b")
+          |           ^--------^
+          |
+          = Expected string literal
+        "###);
+        assert!(env.parse_err(r#""a".match("(")"#).contains("Invalid regex"));
     }
 
     #[test]
@@ -1831,6 +2610,28 @@ mod tests {
           |
           = Invalid time format
         "###);
+
+        // Fixed timezone offset
+        insta::assert_snapshot!(env.render_ok(r#"t0.offset(90)"#), @"1970-01-01 01:30:00.000 +01:30");
+        insta::assert_snapshot!(env.render_ok(r#"t0.offset(-300).utc()"#), @"1970-01-01 00:00:00.000 +00:00");
+        insta::assert_snapshot!(env.render_ok(r#"t0.offset(1440)"#), @"<Error: Failed to evaluate t0.offset: Timezone offset out of range: 1440 minutes>");
+        insta::assert_snapshot!(env.render_ok(r#"t0.offset(-9223372036854775807 - 1)"#), @"<Error: Failed to evaluate t0.offset: Timezone offset out of range: -9223372036854775808 minutes>");
+
+        // Date comparison
+        insta::assert_snapshot!(
+            env.render_ok(r#"t0.after("1970-01-01T00:00:00Z") ++ " " ++ t0.before("1970-01-01T00:00:00Z")"#),
+            @"true false");
+        insta::assert_snapshot!(
+            env.render_ok(r#"t0.after("2 weeks ago") ++ " " ++ t0.before("yesterday")"#),
+            @"false true");
+        insta::assert_snapshot!(env.parse_err(r#"t0.after("soon")"#), @r###"
+         --> 1:10
+          |
+        1 | t0.after("soon")
+          |          ^----^
+          |
+          = Invalid date "soon"
+        "###);
     }
 
     #[test]
@@ -1993,6 +2794,31 @@ mod tests {
             @"[38;5;1mtext[39m");
     }
 
+    #[test]
+    fn test_hyperlink_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_color("error", crossterm::style::Color::DarkRed);
+
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("https://example.com/", "text")"#),
+            @r"]8;;https://example.com/\text]8;;\");
+
+        // Labeled content, and URL built from property
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("https://" ++ "example.com/", label("error", "text"))"#),
+            @r"]8;;https://example.com/\[38;5;1mtext[39m]8;;\");
+
+        // Nested hyperlink restarts the outer one
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("a", "1" ++ hyperlink("b", "2") ++ "3")"#),
+            @r"]8;;a\1]8;;b\2]8;;a\3]8;;\");
+
+        // URL with control characters isn't linked
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("a\tb", "text")"#),
+            @r"]8;;\text]8;;\");
+    }
+
     #[test]
     fn test_concat_function() {
         let mut env = TestTemplateEnv::new();
@@ -2011,6 +2837,91 @@ mod tests {
             @"[38;5;3ma[39mb");
     }
 
+    #[test]
+    fn test_if_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("bad_string", || L::wrap_string(new_error_property("Bad")));
+
+        insta::assert_snapshot!(env.render_ok(r#"if(true, "t")"#), @"t");
+        insta::assert_snapshot!(env.render_ok(r#"if(false, "t")"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#"if(false, "t", "f")"#), @"f");
+
+        // The branch which isn't rendered isn't evaluated
+        insta::assert_snapshot!(env.render_ok(r#"if(true, "t", bad_string)"#), @"t");
+        insta::assert_snapshot!(env.render_ok(r#"if(false, bad_string, "f")"#), @"f");
+        insta::assert_snapshot!(
+            env.render_ok(r#"if(false, "t", bad_string)"#),
+            @"<Error: Failed to evaluate bad_string: Bad>");
+    }
+
+    #[test]
+    fn test_coalesce_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("bad_string", || L::wrap_string(new_error_property("Bad")));
+        env.add_keyword("empty_string", || L::wrap_string(Literal("".to_owned())));
+        env.add_keyword("hidden", || L::wrap_boolean(Literal(false)));
+        env.add_color("warning", crossterm::style::Color::DarkYellow);
+
+        insta::assert_snapshot!(env.render_ok(r#"coalesce()"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#"coalesce("")"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#"coalesce("", "a", "b")"#), @"a");
+        insta::assert_snapshot!(env.render_ok(r#"coalesce(empty_string, "", "b")"#), @"b");
+        insta::assert_snapshot!(env.render_ok(r#"coalesce(hidden, "b")"#), @"false");
+        insta::assert_snapshot!(env.render_ok(r#"coalesce(if(hidden, "x"), "b")"#), @"b");
+
+        // Labeled
+        insta::assert_snapshot!(
+            env.render_ok(r#"coalesce(label("warning", ""), label("warning", "a"))"#),
+            @"[38;5;3ma[39m");
+
+        // Later contents aren't evaluated
+        insta::assert_snapshot!(env.render_ok(r#"coalesce("a", bad_string)"#), @"a");
+        insta::assert_snapshot!(
+            env.render_ok(r#"coalesce("", bad_string)"#),
+            @"<Error: Failed to evaluate bad_string: Bad>");
+    }
+
+    #[test]
+    fn test_json_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("bad_string", || L::wrap_string(new_error_property("Bad")));
+        env.add_keyword("description", || {
+            L::wrap_string(Literal("foo \"bar\"\n".to_owned()))
+        });
+        env.add_keyword("empty", || L::wrap_boolean(Literal(true)));
+        env.add_keyword("size", || L::wrap_integer(Literal(42)));
+
+        insta::assert_snapshot!(env.render_ok(r#"json("")"#), @r###""""###);
+        insta::assert_snapshot!(env.render_ok(r#"json(description)"#), @r###""foo \"bar\"\n""###);
+        insta::assert_snapshot!(env.render_ok(r#"json(empty)"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#"json(size)"#), @"42");
+        insta::assert_snapshot!(
+            env.render_ok(r#"json(description.lines())"#), @r###"["foo \"bar\""]"###);
+        insta::assert_snapshot!(
+            env.render_ok(r#"json(bad_string)"#),
+            @"<Error: Failed to evaluate bad_string: Bad>");
+        insta::assert_snapshot!(
+            env.render_ok(r#"json(description.lines().map(|s| s.len()))"#), @"[9]");
+
+        insta::assert_snapshot!(
+            env.parse_err(r#"json(description.lines().map(|s| label("foo", s)))"#), @r###"
+         --> 1:6
+          |
+        1 | json(description.lines().map(|s| label("foo", s)))
+          |      ^------------------------------------------^
+          |
+          = Expected expression of type "Serializable"
+        "###);
+        insta::assert_snapshot!(env.parse_err(r#"json(label("foo", "bar"))"#), @r###"
+         --> 1:6
+          |
+        1 | json(label("foo", "bar"))
+          |      ^-----------------^
+          |
+          = Expected expression of type "Serializable"
+        "###);
+    }
+
     #[test]
     fn test_separate_function() {
         let mut env = TestTemplateEnv::new();
@@ -44,6 +44,17 @@ impl Rule {
             Rule::logical_and_op => Some("&&"),
             Rule::logical_not_op => Some("!"),
             Rule::negate_op => Some("-"),
+            Rule::eq_op => Some("=="),
+            Rule::ne_op => Some("!="),
+            Rule::ge_op => Some(">="),
+            Rule::gt_op => Some(">"),
+            Rule::le_op => Some("<="),
+            Rule::lt_op => Some("<"),
+            Rule::add_op => Some("+"),
+            Rule::sub_op => Some("-"),
+            Rule::mul_op => Some("*"),
+            Rule::div_op => Some("/"),
+            Rule::rem_op => Some("%"),
             Rule::prefix_ops => None,
             Rule::infix_ops => None,
             Rule::function => None,
@@ -95,6 +106,12 @@ pub enum TemplateParseErrorKind {
     },
     #[error(r#"Function "{name}": {message}"#)]
     InvalidArguments { name: String, message: String },
+    #[error(r#"Function "{name}": {}"#, describe_argument_count(.min, .max))]
+    InvalidArgumentCount {
+        name: String,
+        min: usize,
+        max: Option<usize>,
+    },
     #[error("Redefinition of function parameter")]
     RedefinedFunctionParameter,
     #[error("{0}")]
@@ -161,6 +178,21 @@ impl TemplateParseError {
         )
     }
 
+    pub fn invalid_argument_count(
+        function: &FunctionCallNode,
+        min: usize,
+        max: Option<usize>,
+    ) -> Self {
+        TemplateParseError::with_span(
+            TemplateParseErrorKind::InvalidArgumentCount {
+                name: function.name.to_owned(),
+                min,
+                max,
+            },
+            function.args_span,
+        )
+    }
+
     pub fn expected_type(type_name: &str, span: pest::Span<'_>) -> Self {
         let message = format!(r#"Expected expression of type "{type_name}""#);
         TemplateParseError::unexpected_expression(message, span)
@@ -259,6 +291,14 @@ impl error::Error for TemplateParseError {
     }
 }
 
+fn describe_argument_count(min: &usize, max: &Option<usize>) -> String {
+    match max {
+        Some(max) if max == min => format!("Expected {min} arguments"),
+        Some(max) => format!("Expected {min} to {max} arguments"),
+        None => format!("Expected at least {min} arguments"),
+    }
+}
+
 fn rename_rules_in_pest_error(err: pest::error::Error<Rule>) -> pest::error::Error<Rule> {
     err.renamed_rules(|rule| {
         rule.to_symbol()
@@ -310,6 +350,28 @@ pub enum BinaryOp {
     LogicalOr,
     /// `&&`
     LogicalAnd,
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `>=`
+    Ge,
+    /// `>`
+    Gt,
+    /// `<=`
+    Le,
+    /// `<`
+    Lt,
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `%`
+    Rem,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -478,6 +540,15 @@ fn parse_expression_node(pair: Pair<Rule>) -> TemplateParseResult<ExpressionNode
         PrattParser::new()
             .op(Op::infix(Rule::logical_or_op, Assoc::Left))
             .op(Op::infix(Rule::logical_and_op, Assoc::Left))
+            .op(Op::infix(Rule::eq_op, Assoc::Left) | Op::infix(Rule::ne_op, Assoc::Left))
+            .op(Op::infix(Rule::ge_op, Assoc::Left)
+                | Op::infix(Rule::gt_op, Assoc::Left)
+                | Op::infix(Rule::le_op, Assoc::Left)
+                | Op::infix(Rule::lt_op, Assoc::Left))
+            .op(Op::infix(Rule::add_op, Assoc::Left) | Op::infix(Rule::sub_op, Assoc::Left))
+            .op(Op::infix(Rule::mul_op, Assoc::Left)
+                | Op::infix(Rule::div_op, Assoc::Left)
+                | Op::infix(Rule::rem_op, Assoc::Left))
             .op(Op::prefix(Rule::logical_not_op) | Op::prefix(Rule::negate_op))
     });
     PRATT
@@ -497,6 +568,17 @@ fn parse_expression_node(pair: Pair<Rule>) -> TemplateParseResult<ExpressionNode
             let op_kind = match op.as_rule() {
                 Rule::logical_or_op => BinaryOp::LogicalOr,
                 Rule::logical_and_op => BinaryOp::LogicalAnd,
+                Rule::eq_op => BinaryOp::Eq,
+                Rule::ne_op => BinaryOp::Ne,
+                Rule::ge_op => BinaryOp::Ge,
+                Rule::gt_op => BinaryOp::Gt,
+                Rule::le_op => BinaryOp::Le,
+                Rule::lt_op => BinaryOp::Lt,
+                Rule::add_op => BinaryOp::Add,
+                Rule::sub_op => BinaryOp::Sub,
+                Rule::mul_op => BinaryOp::Mul,
+                Rule::div_op => BinaryOp::Div,
+                Rule::rem_op => BinaryOp::Rem,
                 r => panic!("unexpected infix operator rule {r:?}"),
             };
             let lhs = Box::new(lhs?);
@@ -741,9 +823,10 @@ pub fn expand_aliases<'i>(
             ExpressionKind::FunctionCall(function) => {
                 if let Some((id, params, defn)) = state.aliases_map.get_function(function.name) {
                     if function.args.len() != params.len() {
-                        return Err(TemplateParseError::invalid_arguments(
+                        return Err(TemplateParseError::invalid_argument_count(
                             &function,
-                            format!("Expected {} arguments", params.len()),
+                            params.len(),
+                            Some(params.len()),
                         ));
                     }
                     // Resolve arguments in the current scope, and pass them in to the alias
@@ -804,9 +887,10 @@ pub fn expect_no_arguments(function: &FunctionCallNode) -> TemplateParseResult<(
     if function.args.is_empty() {
         Ok(())
     } else {
-        Err(TemplateParseError::invalid_arguments(
+        Err(TemplateParseError::invalid_argument_count(
             function,
-            "Expected 0 arguments",
+            0,
+            Some(0),
         ))
     }
 }
@@ -815,9 +899,11 @@ pub fn expect_no_arguments(function: &FunctionCallNode) -> TemplateParseResult<(
 pub fn expect_exact_arguments<'a, 'i, const N: usize>(
     function: &'a FunctionCallNode<'i>,
 ) -> TemplateParseResult<&'a [ExpressionNode<'i>; N]> {
-    function.args.as_slice().try_into().map_err(|_| {
-        TemplateParseError::invalid_arguments(function, format!("Expected {N} arguments"))
-    })
+    function
+        .args
+        .as_slice()
+        .try_into()
+        .map_err(|_| TemplateParseError::invalid_argument_count(function, N, Some(N)))
 }
 
 /// Extracts N required arguments and remainders.
@@ -828,9 +914,8 @@ pub fn expect_some_arguments<'a, 'i, const N: usize>(
         let (required, rest) = function.args.split_at(N);
         Ok((required.try_into().unwrap(), rest))
     } else {
-        Err(TemplateParseError::invalid_arguments(
-            function,
-            format!("Expected at least {N} arguments"),
+        Err(TemplateParseError::invalid_argument_count(
+            function, N, None,
         ))
     }
 }
@@ -849,9 +934,10 @@ pub fn expect_arguments<'a, 'i, const N: usize, const M: usize>(
         optional.resize(M, None);
         Ok((required.try_into().unwrap(), optional.try_into().unwrap()))
     } else {
-        Err(TemplateParseError::invalid_arguments(
+        Err(TemplateParseError::invalid_argument_count(
             function,
-            format!("Expected {min} to {max} arguments", min = N, max = N + M),
+            N,
+            Some(N + M),
         ))
     }
 }
@@ -1108,12 +1194,35 @@ mod tests {
             parse_normalized("x || y && z.h()").unwrap(),
             parse_normalized("x || (y && (z.h()))").unwrap(),
         );
+        assert_eq!(
+            parse_normalized("x && y == z || w").unwrap(),
+            parse_normalized("(x && (y == z)) || w").unwrap(),
+        );
+        assert_eq!(
+            parse_normalized("x == y < z").unwrap(),
+            parse_normalized("x == (y < z)").unwrap(),
+        );
+        assert_eq!(
+            parse_normalized("x <= y + z * w").unwrap(),
+            parse_normalized("x <= (y + (z * w))").unwrap(),
+        );
+        assert_eq!(
+            parse_normalized("x - y - z % w").unwrap(),
+            parse_normalized("(x - y) - (z % w)").unwrap(),
+        );
+        assert_eq!(
+            parse_normalized("-x * -y").unwrap(),
+            parse_normalized("(-x) * (-y)").unwrap(),
+        );
 
         // Top-level expression is allowed, but not in concatenation
         assert!(parse_template(r"x && y").is_ok());
         assert!(parse_template(r"f(x && y)").is_ok());
         assert!(parse_template(r"x && y ++ z").is_err());
         assert!(parse_template(r"(x && y) ++ z").is_ok());
+        assert!(parse_template(r"x + y ++ z").is_err());
+        assert!(parse_template(r"x ++ y + z").is_err());
+        assert!(parse_template(r"x++y").is_ok());
 
         // Expression span
         assert_eq!(parse_template(" ! x ").unwrap().span.as_str(), "! x");
@@ -1482,18 +1591,30 @@ mod tests {
         // Invalid number of arguments.
         assert_matches!(
             with_aliases([("F()", "x")]).parse("F(a)").unwrap_err().kind,
-            TemplateParseErrorKind::InvalidArguments { .. }
+            TemplateParseErrorKind::InvalidArgumentCount {
+                min: 0,
+                max: Some(0),
+                ..
+            }
         );
         assert_matches!(
             with_aliases([("F(x)", "x")]).parse("F()").unwrap_err().kind,
-            TemplateParseErrorKind::InvalidArguments { .. }
+            TemplateParseErrorKind::InvalidArgumentCount {
+                min: 1,
+                max: Some(1),
+                ..
+            }
         );
         assert_matches!(
             with_aliases([("F(x,y)", "x ++ y")])
                 .parse("F(a,b,c)")
                 .unwrap_err()
                 .kind,
-            TemplateParseErrorKind::InvalidArguments { .. }
+            TemplateParseErrorKind::InvalidArgumentCount {
+                min: 2,
+                max: Some(2),
+                ..
+            }
         );
 
         // Infinite recursion, where the top-level error isn't of RecursiveAlias kind.
@@ -13,10 +13,14 @@
 // limitations under the License.
 
 use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::rc::Rc;
 use std::{error, io, iter};
 
+use itertools::Itertools as _;
 use jj_lib::backend::{Signature, Timestamp};
+use thiserror::Error;
 
 use crate::formatter::{FormatRecorder, Formatter, PlainTextFormatter};
 use crate::time_util;
@@ -28,8 +32,13 @@ pub trait Template {
 
 /// Template that supports list-like behavior.
 pub trait ListTemplate: Template {
-    /// Concatenates items with the given separator.
-    fn join<'a>(self: Box<Self>, separator: Box<dyn Template + 'a>) -> Box<dyn Template + 'a>
+    /// Concatenates items with the given separator. If `last_separator` is
+    /// specified, it is used between the last two items instead.
+    fn join<'a>(
+        self: Box<Self>,
+        separator: Box<dyn Template + 'a>,
+        last_separator: Option<Box<dyn Template + 'a>>,
+    ) -> Box<dyn Template + 'a>
     where
         Self: 'a;
 
@@ -37,12 +46,35 @@ pub trait ListTemplate: Template {
     fn into_template<'a>(self: Box<Self>) -> Box<dyn Template + 'a>
     where
         Self: 'a;
+
+    /// Converts to a property serializing the items as a JSON array, if the
+    /// items are serializable.
+    fn try_into_json<'a>(
+        self: Box<Self>,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>>
+    where
+        Self: 'a,
+    {
+        None
+    }
 }
 
 pub trait IntoTemplate<'a> {
     fn into_template(self) -> Box<dyn Template + 'a>;
 }
 
+/// Value which can be serialized by the `json()` template function.
+///
+/// Object keys should be listed in sorted order, which is the order serde_json
+/// prints them in.
+pub trait ToJson {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError>;
+}
+
+pub trait IntoJson<'a> {
+    fn into_json(self) -> Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>;
+}
+
 impl<T: Template + ?Sized> Template for &T {
     fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
         <T as Template>::format(self, formatter)
@@ -135,6 +167,13 @@ impl Template for Vec<String> {
     }
 }
 
+impl Template for BTreeMap<String, String> {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        let entries = self.iter().map(|(key, value)| format!("{key}: {value}"));
+        format_joined(formatter, entries, "\n")
+    }
+}
+
 impl Template for bool {
     fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
         let repr = if *self { "true" } else { "false" };
@@ -148,6 +187,69 @@ impl Template for i64 {
     }
 }
 
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        self.as_ref()
+            .map_or(Ok(serde_json::Value::Null), |value| value.to_json())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let values = self.iter().map(|value| value.to_json()).try_collect()?;
+        Ok(serde_json::Value::Array(values))
+    }
+}
+
+impl ToJson for Signature {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "email": self.email,
+            "name": self.name,
+            "timestamp": self.timestamp.to_json()?,
+        }))
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.clone().into())
+    }
+}
+
+impl ToJson for Timestamp {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(time_util::format_rfc3339_timestamp(self)?.into())
+    }
+}
+
+impl ToJson for TimestampRange {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "end": self.end.to_json()?,
+            "start": self.start.to_json()?,
+        }))
+    }
+}
+
+impl ToJson for BTreeMap<String, String> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok((*self).into())
+    }
+}
+
+impl ToJson for i64 {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok((*self).into())
+    }
+}
+
 pub struct LabelTemplate<T, L> {
     content: T,
     labels: L,
@@ -184,6 +286,37 @@ where
     }
 }
 
+pub struct HyperlinkTemplate<T, U> {
+    content: T,
+    url: U,
+}
+
+impl<T, U> HyperlinkTemplate<T, U> {
+    pub fn new(content: T, url: U) -> Self
+    where
+        T: Template,
+        U: TemplateProperty<Output = String>,
+    {
+        HyperlinkTemplate { content, url }
+    }
+}
+
+impl<T, U> Template for HyperlinkTemplate<T, U>
+where
+    T: Template,
+    U: TemplateProperty<Output = String>,
+{
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        let url = match self.url.extract() {
+            Ok(url) => url,
+            Err(err) => return err.format(formatter),
+        };
+        formatter.push_hyperlink(&url)?;
+        self.content.format(formatter)?;
+        formatter.pop_hyperlink()
+    }
+}
+
 pub struct ConcatTemplate<T>(pub Vec<T>);
 
 impl<T: Template> Template for ConcatTemplate<T> {
@@ -217,12 +350,28 @@ where
     F: Fn(&mut dyn Formatter, &FormatRecorder) -> io::Result<()>,
 {
     fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
-        let mut recorder = FormatRecorder::new();
-        self.content.format(&mut recorder)?;
+        let recorder = record_template(&self.content, formatter.is_strict())?;
         (self.reformat)(formatter, &recorder)
     }
 }
 
+/// Renders the first of the `contents` which produces non-empty output. The
+/// remaining contents aren't evaluated.
+pub struct CoalesceTemplate<T>(pub Vec<T>);
+
+impl<T: Template> Template for CoalesceTemplate<T> {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        let strict = formatter.is_strict();
+        for template in &self.0 {
+            let recorder = record_template(template, strict)?;
+            if !recorder.data().is_empty() {
+                return recorder.replay(formatter);
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Like `ConcatTemplate`, but inserts a separator between non-empty templates.
 pub struct SeparateTemplate<S, T> {
     separator: S,
@@ -248,14 +397,14 @@ where
     T: Template,
 {
     fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        let strict = formatter.is_strict();
         let mut content_recorders = self
             .contents
             .iter()
             .filter_map(|template| {
-                let mut recorder = FormatRecorder::new();
-                match template.format(&mut recorder) {
-                    Ok(()) if recorder.data().is_empty() => None, // omit empty content
-                    Ok(()) => Some(Ok(recorder)),
+                match record_template(template, strict) {
+                    Ok(recorder) if recorder.data().is_empty() => None, // omit empty content
+                    Ok(recorder) => Some(Ok(recorder)),
                     Err(e) => Some(Err(e)),
                 }
             })
@@ -287,7 +436,16 @@ where
     }
 }
 
-/// Prints the evaluation error as inline template output.
+/// Evaluation error annotated with the name of the failed property.
+#[derive(Debug, Error)]
+#[error("Failed to evaluate {name}")]
+pub struct NamedPropertyError {
+    pub name: String,
+    pub source: Box<dyn error::Error + Send + Sync>,
+}
+
+/// Prints the evaluation error as inline template output, or aborts rendering
+/// if the formatter is strict.
 impl Template for TemplatePropertyError {
     fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
         format_error_inline(formatter, &*self.0)
@@ -308,6 +466,14 @@ impl<P: TemplateProperty + ?Sized> TemplateProperty for Box<P> {
     }
 }
 
+impl<P: TemplateProperty + ?Sized> TemplateProperty for Rc<P> {
+    type Output = <P as TemplateProperty>::Output;
+
+    fn extract(&self) -> Result<Self::Output, TemplatePropertyError> {
+        <P as TemplateProperty>::extract(self)
+    }
+}
+
 impl<P: TemplateProperty> TemplateProperty for Option<P> {
     type Output = Option<P::Output>;
 
@@ -359,6 +525,18 @@ pub trait TemplatePropertyExt: TemplateProperty {
     {
         TemplateFunction::new(self, move |value| Ok(function(value)))
     }
+
+    /// Translates to a property that will annotate evaluation error with the
+    /// given property `name`.
+    fn with_error_name(self, name: impl Into<String>) -> NamedErrorProperty<Self>
+    where
+        Self: Sized,
+    {
+        NamedErrorProperty {
+            property: self,
+            name: name.into(),
+        }
+    }
 }
 
 impl<P: TemplateProperty + ?Sized> TemplatePropertyExt for P {}
@@ -417,6 +595,15 @@ where
     }
 }
 
+impl<'a, O> IntoJson<'a> for Box<dyn TemplateProperty<Output = O> + 'a>
+where
+    O: ToJson + 'a,
+{
+    fn into_json(self) -> Box<dyn TemplateProperty<Output = serde_json::Value> + 'a> {
+        Box::new(self.and_then(|value| value.to_json()))
+    }
+}
+
 /// Adapter to turn template back to string property.
 pub struct PlainTextFormattedProperty<T> {
     template: T,
@@ -446,6 +633,7 @@ impl<T: Template> TemplateProperty for PlainTextFormattedProperty<T> {
 pub struct ListPropertyTemplate<P, S, F> {
     property: P,
     separator: S,
+    last_separator: Option<S>,
     format_item: F,
 }
 
@@ -460,9 +648,18 @@ impl<P, S, F> ListPropertyTemplate<P, S, F> {
         ListPropertyTemplate {
             property,
             separator,
+            last_separator: None,
             format_item,
         }
     }
+
+    /// Uses `last_separator` between the last two items.
+    pub fn with_last_separator(self, last_separator: Option<S>) -> Self {
+        ListPropertyTemplate {
+            last_separator,
+            ..self
+        }
+    }
 }
 
 impl<O, P, S, F> Template for ListPropertyTemplate<P, S, F>
@@ -477,7 +674,13 @@ where
             Ok(contents) => contents,
             Err(err) => return err.format(formatter),
         };
-        format_joined_with(formatter, contents, &self.separator, &self.format_item)
+        format_joined_with(
+            formatter,
+            contents,
+            &self.separator,
+            self.last_separator.as_ref(),
+            &self.format_item,
+        )
     }
 }
 
@@ -488,17 +691,20 @@ where
     S: Template,
     F: Fn(&mut dyn Formatter, O) -> io::Result<()>,
 {
-    fn join<'a>(self: Box<Self>, separator: Box<dyn Template + 'a>) -> Box<dyn Template + 'a>
+    fn join<'a>(
+        self: Box<Self>,
+        separator: Box<dyn Template + 'a>,
+        last_separator: Option<Box<dyn Template + 'a>>,
+    ) -> Box<dyn Template + 'a>
     where
         Self: 'a,
     {
         // Once join()-ed, list-like API should be dropped. This is guaranteed by
         // the return type.
-        Box::new(ListPropertyTemplate::new(
-            self.property,
-            separator,
-            self.format_item,
-        ))
+        Box::new(
+            ListPropertyTemplate::new(self.property, separator, self.format_item)
+                .with_last_separator(last_separator),
+        )
     }
 
     fn into_template<'a>(self: Box<Self>) -> Box<dyn Template + 'a>
@@ -509,6 +715,62 @@ where
     }
 }
 
+/// List template whose items can also be serialized, such as the result of
+/// `map()` with a serializable item expression.
+pub struct SerializableListTemplate<T, J> {
+    template: T,
+    json: J,
+}
+
+impl<T, J> SerializableListTemplate<T, J> {
+    pub fn new(template: T, json: J) -> Self
+    where
+        T: ListTemplate,
+        J: TemplateProperty<Output = serde_json::Value>,
+    {
+        SerializableListTemplate { template, json }
+    }
+}
+
+impl<T: Template, J> Template for SerializableListTemplate<T, J> {
+    fn format(&self, formatter: &mut dyn Formatter) -> io::Result<()> {
+        self.template.format(formatter)
+    }
+}
+
+impl<T, J> ListTemplate for SerializableListTemplate<T, J>
+where
+    T: ListTemplate,
+    J: TemplateProperty<Output = serde_json::Value>,
+{
+    fn join<'a>(
+        self: Box<Self>,
+        separator: Box<dyn Template + 'a>,
+        last_separator: Option<Box<dyn Template + 'a>>,
+    ) -> Box<dyn Template + 'a>
+    where
+        Self: 'a,
+    {
+        Box::new(self.template).join(separator, last_separator)
+    }
+
+    fn into_template<'a>(self: Box<Self>) -> Box<dyn Template + 'a>
+    where
+        Self: 'a,
+    {
+        self
+    }
+
+    fn try_into_json<'a>(
+        self: Box<Self>,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>>
+    where
+        Self: 'a,
+    {
+        Some(Box::new(self.json))
+    }
+}
+
 pub struct ConditionalTemplate<P, T, U> {
     pub condition: P,
     pub true_template: T,
@@ -580,6 +842,32 @@ where
     }
 }
 
+/// Adapter that annotates evaluation error with the property name.
+///
+/// This is usually created by `TemplatePropertyExt::with_error_name()`. If the
+/// error has already been annotated by an inner property, it is left untouched.
+pub struct NamedErrorProperty<P> {
+    property: P,
+    name: String,
+}
+
+impl<P: TemplateProperty> TemplateProperty for NamedErrorProperty<P> {
+    type Output = P::Output;
+
+    fn extract(&self) -> Result<Self::Output, TemplatePropertyError> {
+        self.property.extract().map_err(|err| {
+            if err.0.is::<NamedPropertyError>() {
+                err
+            } else {
+                TemplatePropertyError(Box::new(NamedPropertyError {
+                    name: self.name.clone(),
+                    source: err.0,
+                }))
+            }
+        })
+    }
+}
+
 /// Property which will be compiled into template once, and substituted later.
 #[derive(Clone, Debug)]
 pub struct PropertyPlaceholder<O> {
@@ -632,6 +920,7 @@ impl<O: Clone> TemplateProperty for PropertyPlaceholder<O> {
 pub struct TemplateRenderer<'a, C> {
     template: Box<dyn Template + 'a>,
     placeholder: PropertyPlaceholder<C>,
+    strict: bool,
 }
 
 impl<'a, C: Clone> TemplateRenderer<'a, C> {
@@ -639,15 +928,81 @@ impl<'a, C: Clone> TemplateRenderer<'a, C> {
         TemplateRenderer {
             template,
             placeholder,
+            strict: false,
         }
     }
 
+    /// Sets whether property evaluation error should abort rendering.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     pub fn format(&self, context: &C, formatter: &mut dyn Formatter) -> io::Result<()> {
-        self.placeholder
-            .with_value(context.clone(), || self.template.format(formatter))
+        self.placeholder.with_value(context.clone(), || {
+            if self.strict {
+                self.template.format(&mut StrictFormatter { formatter })
+            } else {
+                self.template.format(formatter)
+            }
+        })
     }
 }
 
+/// Formatter wrapper that turns property evaluation error into `io::Error`.
+struct StrictFormatter<'a> {
+    formatter: &'a mut dyn Formatter,
+}
+
+impl Write for StrictFormatter<'_> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.formatter.write(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.formatter.flush()
+    }
+}
+
+impl Formatter for StrictFormatter<'_> {
+    fn raw(&mut self) -> &mut dyn Write {
+        self.formatter.raw()
+    }
+
+    fn push_label(&mut self, label: &str) -> io::Result<()> {
+        self.formatter.push_label(label)
+    }
+
+    fn pop_label(&mut self) -> io::Result<()> {
+        self.formatter.pop_label()
+    }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        self.formatter.push_hyperlink(url)
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.formatter.pop_hyperlink()
+    }
+
+    fn is_strict(&self) -> bool {
+        true
+    }
+}
+
+/// Records the output of the `template`, which will be replayed later.
+fn record_template(template: &impl Template, strict: bool) -> io::Result<FormatRecorder> {
+    let mut recorder = FormatRecorder::new();
+    if strict {
+        template.format(&mut StrictFormatter {
+            formatter: &mut recorder,
+        })?;
+    } else {
+        template.format(&mut recorder)?;
+    }
+    Ok(recorder)
+}
+
 pub fn format_joined<I, S>(
     formatter: &mut dyn Formatter,
     contents: I,
@@ -658,7 +1013,7 @@ where
     I::Item: Template,
     S: Template,
 {
-    format_joined_with(formatter, contents, separator, |formatter, item| {
+    format_joined_with(formatter, contents, separator, None, |formatter, item| {
         item.format(formatter)
     })
 }
@@ -667,6 +1022,7 @@ fn format_joined_with<I, S, F>(
     formatter: &mut dyn Formatter,
     contents: I,
     separator: S,
+    last_separator: Option<S>,
     mut format_item: F,
 ) -> io::Result<()>
 where
@@ -674,18 +1030,27 @@ where
     S: Template,
     F: FnMut(&mut dyn Formatter, I::Item) -> io::Result<()>,
 {
-    let mut contents_iter = contents.into_iter().fuse();
+    let mut contents_iter = contents.into_iter().fuse().peekable();
     if let Some(item) = contents_iter.next() {
         format_item(formatter, item)?;
     }
-    for item in contents_iter {
-        separator.format(formatter)?;
+    while let Some(item) = contents_iter.next() {
+        match &last_separator {
+            Some(last_separator) if contents_iter.peek().is_none() => {
+                last_separator.format(formatter)?;
+            }
+            _ => separator.format(formatter)?,
+        }
         format_item(formatter, item)?;
     }
     Ok(())
 }
 
 fn format_error_inline(formatter: &mut dyn Formatter, err: &dyn error::Error) -> io::Result<()> {
+    if formatter.is_strict() {
+        let message = iter::successors(Some(err), |err| err.source()).join(": ");
+        return Err(io::Error::other(message));
+    }
     formatter.with_label("error", |formatter| {
         write!(formatter, "<")?;
         write!(formatter.labeled("heading"), "Error: ")?;
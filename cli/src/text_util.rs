@@ -64,6 +64,50 @@ pub fn elide_start<'a>(
     (Cow::Owned([ellipsis, text].concat()), concat_width)
 }
 
+/// Shortens `text` to `max_width` by removing trailing characters. `ellipsis`
+/// is added if the `text` gets truncated.
+///
+/// The returned string (including `ellipsis`) never exceeds the `max_width`.
+pub fn elide_end<'a>(text: &'a str, ellipsis: &'a str, max_width: usize) -> (Cow<'a, str>, usize) {
+    let (text_end, text_width) = truncate_end_pos(text, max_width);
+    if text_end == text.len() {
+        return (Cow::Borrowed(text), text_width);
+    }
+
+    let (ellipsis_end, ellipsis_width) = truncate_end_pos(ellipsis, max_width);
+    if ellipsis_end != ellipsis.len() {
+        return (Cow::Borrowed(&ellipsis[..ellipsis_end]), ellipsis_width);
+    }
+
+    let max_text_width = max_width - ellipsis_width;
+    let (text_end, text_width) = truncate_end_pos(text, max_text_width);
+    let concat_width = text_width + ellipsis_width;
+    assert!(concat_width <= max_width);
+    (
+        Cow::Owned([&text[..text_end], ellipsis].concat()),
+        concat_width,
+    )
+}
+
+/// Pads `text` with leading spaces so that it occupies at least `width`
+/// columns.
+pub fn pad_start(text: &str, width: usize) -> String {
+    let fill_width = width.saturating_sub(str_width(text));
+    [" ".repeat(fill_width).as_str(), text].concat()
+}
+
+/// Pads `text` with trailing spaces so that it occupies at least `width`
+/// columns.
+pub fn pad_end(text: &str, width: usize) -> String {
+    let fill_width = width.saturating_sub(str_width(text));
+    [text, " ".repeat(fill_width).as_str()].concat()
+}
+
+/// Returns the display width of `text`.
+fn str_width(text: &str) -> usize {
+    text.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
 /// Shortens `text` to `max_width` by removing leading characters, returning
 /// `(start_index, width)`.
 ///
@@ -81,6 +125,22 @@ fn truncate_start_pos(text: &str, max_width: usize) -> (usize, usize) {
     (0, acc_width)
 }
 
+/// Shortens `text` to `max_width` by removing trailing characters, returning
+/// `(end_index, width)`.
+///
+/// 0-width decomposed characters following the last kept character are kept.
+fn truncate_end_pos(text: &str, max_width: usize) -> (usize, usize) {
+    let mut acc_width = 0;
+    for (i, c) in text.char_indices() {
+        let new_width = acc_width + c.width().unwrap_or(0);
+        if new_width > max_width {
+            return (i, acc_width);
+        }
+        acc_width = new_width;
+    }
+    (text.len(), acc_width)
+}
+
 /// Skips `width` leading characters, returning `(start_index, skipped_width)`.
 ///
 /// The `skipped_width` may exceed the given `width` if `width` is not at
@@ -361,6 +421,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_elide_end() {
+        // Empty string
+        assert_eq!(elide_end("", "", 1), ("".into(), 0));
+
+        // Basic truncation
+        assert_eq!(elide_end("abcdef", "", 6), ("abcdef".into(), 6));
+        assert_eq!(elide_end("abcdef", "", 5), ("abcde".into(), 5));
+        assert_eq!(elide_end("abcdef", "", 1), ("a".into(), 1));
+        assert_eq!(elide_end("abcdef", "", 0), ("".into(), 0));
+        assert_eq!(elide_end("abcdef", "-=~", 6), ("abcdef".into(), 6));
+        assert_eq!(elide_end("abcdef", "-=~", 5), ("ab-=~".into(), 5));
+        assert_eq!(elide_end("abcdef", "-=~", 4), ("a-=~".into(), 4));
+        assert_eq!(elide_end("abcdef", "-=~", 3), ("-=~".into(), 3));
+        assert_eq!(elide_end("abcdef", "-=~", 2), ("-=".into(), 2));
+        assert_eq!(elide_end("abcdef", "-=~", 0), ("".into(), 0));
+
+        // East Asian characters (char.width() == 2)
+        assert_eq!(elide_end("一二三", "", 6), ("一二三".into(), 6));
+        assert_eq!(elide_end("一二三", "", 5), ("一二".into(), 4));
+        assert_eq!(elide_end("一二三", "", 1), ("".into(), 0));
+        assert_eq!(elide_end("一二三", "-=~", 5), ("一-=~".into(), 5));
+        assert_eq!(elide_end("一二三", "-=~", 4), ("-=~".into(), 3));
+        assert_eq!(elide_end("一二三", "略", 5), ("一略".into(), 4));
+        assert_eq!(elide_end("一二三", "略", 1), ("".into(), 0));
+
+        // Decomposed character at boundary
+        assert_eq!(
+            elide_end("a\u{300}bcde\u{300}", "", 5),
+            ("a\u{300}bcde\u{300}".into(), 5)
+        );
+        assert_eq!(
+            elide_end("a\u{300}bcde\u{300}", "", 1),
+            ("a\u{300}".into(), 1)
+        );
+        assert_eq!(
+            elide_end("a\u{300}bcde\u{300}", "E\u{300}", 2),
+            ("a\u{300}E\u{300}".into(), 2)
+        );
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad_start("", 3), "   ");
+        assert_eq!(pad_start("ab", 3), " ab");
+        assert_eq!(pad_start("abcd", 3), "abcd");
+        assert_eq!(pad_end("ab", 3), "ab ");
+        assert_eq!(pad_end("abcd", 0), "abcd");
+
+        // Wide and zero-width characters
+        assert_eq!(pad_start("\u{300}a", 2), " \u{300}a");
+        assert_eq!(pad_end("一", 3), "一 ");
+    }
+
     #[test]
     fn test_split_byte_line_to_words() {
         assert_eq!(split_byte_line_to_words(b""), vec![]);
@@ -72,6 +72,11 @@ pub fn format_absolute_timestamp(timestamp: &Timestamp) -> Result<String, Timest
     format_absolute_timestamp_with(timestamp, &DEFAULT_FORMAT)
 }
 
+/// Formats the timestamp in RFC 3339 format, e.g. `2001-02-03T04:05:06+07:00`.
+pub fn format_rfc3339_timestamp(timestamp: &Timestamp) -> Result<String, TimestampOutOfRange> {
+    Ok(datetime_from_timestamp(timestamp)?.to_rfc3339())
+}
+
 pub fn format_absolute_timestamp_with(
     timestamp: &Timestamp,
     format: &FormattingItems,
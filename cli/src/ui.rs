@@ -24,6 +24,7 @@ use tracing::instrument;
 use crate::command_error::{config_error_with_message, CommandError};
 use crate::config::CommandNameAndArgs;
 use crate::formatter::{Formatter, FormatterFactory, HeadingLabeledWriter, LabeledWriter};
+use crate::messages::MessageCatalog;
 
 const BUILTIN_PAGER_NAME: &str = ":builtin";
 
@@ -167,6 +168,7 @@ pub struct Ui {
     paginate: PaginationChoice,
     progress_indicator: bool,
     formatter_factory: FormatterFactory,
+    messages: MessageCatalog,
     output: UiOutput,
 }
 
@@ -256,11 +258,14 @@ impl Ui {
             pager_cmd: pager_setting(config)?,
             paginate: pagination_setting(config)?,
             progress_indicator,
+            messages: MessageCatalog::from_config(config)?,
             output: UiOutput::new_terminal(),
         })
     }
 
     pub fn reset(&mut self, config: &config::Config) -> Result<(), CommandError> {
+        // Load messages first so that they apply to errors in the other settings.
+        self.messages = MessageCatalog::from_config(config)?;
         self.color = use_color(color_setting(config));
         self.paginate = pagination_setting(config)?;
         self.pager_cmd = pager_setting(config)?;
@@ -309,6 +314,11 @@ impl Ui {
         self.color
     }
 
+    /// Catalog of messages customized by config.
+    pub fn messages(&self) -> &MessageCatalog {
+        &self.messages
+    }
+
     pub fn new_formatter<'output, W: Write + 'output>(
         &self,
         output: W,
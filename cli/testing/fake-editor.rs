@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::path::PathBuf;
-use std::process::exit;
+use std::process::{exit, Command, Stdio};
 use std::{env, fs};
 
 use clap::Parser;
@@ -50,6 +50,14 @@ fn main() {
                 let dest_path = edit_script_path.parent().unwrap().join(dest);
                 fs::copy(&args.file, dest_path).unwrap();
             }
+            ["dump-env", dest, names @ ..] => {
+                let dest_path = edit_script_path.parent().unwrap().join(dest);
+                let content = names
+                    .iter()
+                    .map(|name| format!("{name}={}\n", env::var(name).unwrap_or_default()))
+                    .join("");
+                fs::write(dest_path, content).unwrap();
+            }
             ["expect"] => {
                 let actual = String::from_utf8(fs::read(&args.file).unwrap()).unwrap();
                 if actual != payload {
@@ -71,6 +79,21 @@ fn main() {
                     panic!("Failed to write file {}", args.file.to_str().unwrap())
                 });
             }
+            ["run"] => {
+                // The payload is the program followed by its arguments, one per line
+                let mut lines = payload.lines();
+                let program = lines.next().unwrap();
+                let status = Command::new(program)
+                    .args(lines)
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .unwrap();
+                if !status.success() {
+                    eprintln!("fake-editor: `{program}` failed: {status}");
+                    exit(1)
+                }
+            }
             _ => {
                 eprintln!("fake-editor: unexpected command: {command}");
                 exit(1)
@@ -10,9 +10,11 @@ fn test_no_forgotten_test_files() {
 
 mod test_abandon_command;
 mod test_alias;
+mod test_backout_command;
 mod test_branch_command;
 mod test_builtin_aliases;
 mod test_cat_command;
+mod test_changes_command;
 mod test_checkout;
 mod test_chmod_command;
 mod test_commit_command;
@@ -25,6 +27,7 @@ mod test_diff_command;
 mod test_diffedit_command;
 mod test_duplicate_command;
 mod test_edit_command;
+mod test_files_command;
 mod test_generate_md_cli_help;
 mod test_git_clone;
 mod test_git_colocated;
@@ -53,6 +56,7 @@ mod test_revset_output;
 mod test_root;
 mod test_shell_completion;
 mod test_show_command;
+mod test_snapshot_command;
 mod test_sparse_command;
 mod test_split_command;
 mod test_squash_command;
@@ -359,6 +359,50 @@ fn test_double_abandon() {
     "###);
 }
 
+#[test]
+fn test_abandon_empty() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "empty"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "empty"]);
+    create_commit(&test_env, &repo_path, "b", &["empty"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    // Test the setup
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  [vru]
+    ◉  [roy] b
+    ◉  [zsu] empty
+    ◉  [rlv] a
+    ◉  [zzz]
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["abandon", "--empty", "-r", "all()"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Abandoned the following commits:
+      vruxwmqv 1f55fb6b (empty) (no description set)
+      zsuskuln 45cb2960 empty | (empty) empty
+    Rebased 1 descendant commits onto parents of abandoned commits
+    Working copy now at: znkkpsqq 3c39a08c (empty) (no description set)
+    Parent commit      : royxmykx c1dc47a8 b | b
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  [znk]
+    ◉  [roy] b
+    ◉  [rlv] a empty
+    ◉  [zzz]
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["abandon", "--empty", "a"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    No revisions to abandon.
+    "###);
+}
+
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     test_env.jj_cmd_success(
         repo_path,
@@ -0,0 +1,155 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn create_commit(test_env: &TestEnvironment, repo_path: &Path, name: &str, parents: &[&str]) {
+    if parents.is_empty() {
+        test_env.jj_cmd_ok(repo_path, &["new", "root()", "-m", name]);
+    } else {
+        let mut args = vec!["new", "-m", name];
+        args.extend(parents);
+        test_env.jj_cmd_ok(repo_path, &args);
+    }
+    std::fs::write(repo_path.join(name), format!("{name}\n")).unwrap();
+    test_env.jj_cmd_ok(repo_path, &["branch", "create", name]);
+}
+
+#[test]
+fn test_backout() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    create_commit(&test_env, &repo_path, "d", &["c"]);
+
+    // Back out a single commit
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["backout", "-r", "b", "-d", "d"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  backout of commit <commit> (change <change>)
+    @  d
+    ◉  c
+    ◉  b
+    ◉  a
+    │
+    ~
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "-r", "children(d)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    a
+    c
+    d
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+
+    // Nothing to back out
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["backout", "-r", "none()"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    No revisions to back out.
+    "###);
+}
+
+#[test]
+fn test_backout_multiple() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    create_commit(&test_env, &repo_path, "d", &["c"]);
+
+    // Back out the combined changes in one commit
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["backout", "-r", "b::c", "-d", "d"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  backout of 2 commits
+    @  d
+    ◉  c
+    ◉  b
+    ◉  a
+    │
+    ~
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "children(d)",
+            "-T",
+            &format!("{DESCRIPTION_TEMPLATE} ++ \"\\n\""),
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    backout of 2 commits
+
+    commit <commit> (change <change>)
+    commit <commit> (change <change>)
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "-r", "children(d)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    a
+    d
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+
+    // Back out each commit separately, newest first
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["backout", "--stack", "-r", "b::c", "-d", "d"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  backout of commit <commit> (change <change>)
+    ◉  backout of commit <commit> (change <change>)
+    @  d
+    ◉  c
+    ◉  b
+    ◉  a
+    │
+    ~
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "-r", "children(d)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    a
+    b
+    d
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "-r", "children(children(d))"]);
+    insta::assert_snapshot!(stdout, @r###"
+    a
+    d
+    "###);
+}
+
+// Backout descriptions refer to commit and change ids, which are replaced to
+// keep the snapshots readable.
+const DESCRIPTION_TEMPLATE: &str =
+    r#"description.replace("[0-9a-f]{40}", "<commit>").replace("[k-z]{32}", "<change>")"#;
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = format!("if(branches, branches, {DESCRIPTION_TEMPLATE}.first_line())");
+    test_env.jj_cmd_success(repo_path, &["log", "-r", "a::", "-T", &template])
+}
@@ -48,7 +48,7 @@ fn test_branch_multiple_names() {
         test_env.jj_cmd_ok(&repo_path, &["branch", "delete", "foo", "bar", "foo"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Deleted 2 branches.
+    Deleted 2 branches: bar, foo.
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     @   8bb159bc30a9
@@ -187,6 +187,64 @@ fn test_branch_move_conflicting() {
     "###);
 }
 
+#[test]
+fn test_branch_at_change() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(edit_script, "write\nfirst").unwrap();
+    let get_log = || {
+        let template = r#"separate(" ", description.first_line(), branches)"#;
+        let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["log", "-T", template]);
+        stdout
+    };
+    let get_branch_list = || {
+        let (stdout, _stderr) = test_env.jj_cmd_ok(
+            &repo_path,
+            &[
+                "branch",
+                "list",
+                "--config-toml=templates.commit_summary_no_branches='description.first_line()'",
+            ],
+        );
+        stdout
+    };
+
+    std::fs::write(repo_path.join("file1"), "").unwrap();
+    std::fs::write(repo_path.join("file2"), "").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "--at-change", "pinned"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "plain"]);
+
+    // The first part of the split keeps the change id, so the pinned branch
+    // stays on it, whereas the other branch moves to the second part.
+    test_env.jj_cmd_ok(&repo_path, &["split", "file1"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "plain", "-m", "second"]);
+    insta::assert_snapshot!(get_log(), @r###"
+    @  second plain
+    ◉  first pinned
+    ◉
+    "###);
+    insta::assert_snapshot!(get_branch_list(), @r###"
+    pinned (pinned to change qpvuntsmwlqt): first
+    plain: second
+    "###);
+
+    // The pin is carried over when renaming the branch
+    test_env.jj_cmd_ok(&repo_path, &["branch", "rename", "pinned", "renamed"]);
+    insta::assert_snapshot!(get_branch_list(), @r###"
+    plain: second
+    renamed (pinned to change qpvuntsmwlqt): first
+    "###);
+
+    // Setting the branch without --at-change removes the pin
+    test_env.jj_cmd_ok(&repo_path, &["branch", "set", "renamed", "-r@"]);
+    insta::assert_snapshot!(get_branch_list(), @r###"
+    plain: second
+    renamed: second
+    "###);
+}
+
 #[test]
 fn test_branch_rename() {
     let test_env = TestEnvironment::default();
@@ -252,13 +310,13 @@ fn test_branch_forget_glob() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     --glob has been deprecated. Please prefix the pattern with `glob:` instead.
-    Forgot 2 branches.
+    Forgot 2 branches: foo-1, foo-3.
     "###);
     test_env.jj_cmd_ok(&repo_path, &["undo"]);
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["branch", "forget", "glob:foo-[1-3]"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Forgot 2 branches.
+    Forgot 2 branches: foo-1, foo-3.
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     @  bar-2 foo-4 230dd059e1b0
@@ -334,13 +392,13 @@ fn test_branch_delete_glob() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     --glob has been deprecated. Please prefix the pattern with `glob:` instead.
-    Deleted 2 branches.
+    Deleted 2 branches: foo-1, foo-3.
     "###);
     test_env.jj_cmd_ok(&repo_path, &["undo"]);
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["branch", "delete", "glob:foo-[1-3]"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Deleted 2 branches.
+    Deleted 2 branches: foo-1, foo-3.
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     @  bar-2 foo-1@origin foo-3@origin foo-4 6fbf398c2d59
@@ -877,7 +935,7 @@ fn test_branch_track_untrack_patterns() {
     test_env.jj_cmd_ok(&repo_path, &["branch", "create", "main"]);
     insta::assert_snapshot!(
         test_env.jj_cmd_cli_error(&repo_path, &["branch", "track", "main"]), @r###"
-    error: invalid value 'main' for '<BRANCH@REMOTE>...': remote branch must be specified in branch@remote form
+    error: invalid value 'main' for '[BRANCH@REMOTE]...': remote branch must be specified in branch@remote form
 
     For more information, try '--help'.
     "###);
@@ -953,7 +1011,7 @@ fn test_branch_track_untrack_patterns() {
     // Track by pattern
     let (_, stderr) = test_env.jj_cmd_ok(&repo_path, &["branch", "track", "glob:feature?@origin"]);
     insta::assert_snapshot!(stderr, @r###"
-    Started tracking 2 remote branches.
+    Started tracking 2 remote branches: feature1@origin, feature2@origin.
     "###);
     insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
     feature1: omvolwpu 1336caed commit
@@ -964,6 +1022,134 @@ fn test_branch_track_untrack_patterns() {
     main: qpvuntsm 230dd059 (empty) (no description set)
       @git: qpvuntsm 230dd059 (empty) (no description set)
     "###);
+
+    // Track all remote branches
+    test_env.jj_cmd_ok(&repo_path, &["branch", "untrack", "glob:feature?@origin"]);
+    let (_, stderr) = test_env.jj_cmd_ok(&repo_path, &["branch", "track", "--all-remotes"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Started tracking 2 remote branches: feature1@origin, feature2@origin.
+    "###);
+    insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
+    feature1: omvolwpu 1336caed commit
+      @git: omvolwpu 1336caed commit
+      @origin: omvolwpu 1336caed commit
+    feature2: omvolwpu 1336caed commit
+      @origin: omvolwpu 1336caed commit
+    main: qpvuntsm 230dd059 (empty) (no description set)
+      @git: qpvuntsm 230dd059 (empty) (no description set)
+    "###);
+    let (_, stderr) = test_env.jj_cmd_ok(&repo_path, &["branch", "track", "--all"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+}
+
+#[test]
+fn test_branch_retarget() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    // Set up remote where "master" was renamed to "main"
+    let git_repo_path = test_env.env_root().join("git-repo");
+    let git_repo = git2::Repository::init(git_repo_path).unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "remote", "add", "origin", "../git-repo"],
+    );
+    let signature =
+        git2::Signature::new("Some One", "some.one@example.com", &git2::Time::new(0, 0)).unwrap();
+    let mut tree_builder = git_repo.treebuilder(None).unwrap();
+    let file_oid = git_repo.blob(b"content 1").unwrap();
+    tree_builder
+        .insert("file", file_oid, git2::FileMode::Blob.into())
+        .unwrap();
+    let tree_oid = tree_builder.write().unwrap();
+    let tree = git_repo.find_tree(tree_oid).unwrap();
+    let git_commit_oid = git_repo
+        .commit(None, &signature, &signature, "commit 1", &tree, &[])
+        .unwrap();
+    for name in ["refs/heads/master", "refs/heads/main"] {
+        git_repo.reference(name, git_commit_oid, true, "").unwrap();
+    }
+    test_env.add_config("git.auto-local-branch = false");
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "track", "master@origin"]);
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "branch",
+            "retarget",
+            "foo",
+            "--to-remote-branch",
+            "main@origin",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No such branch: foo
+    "###);
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "branch",
+            "retarget",
+            "master",
+            "--to-remote-branch",
+            "foo@origin",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No such remote branch: foo@origin
+    "###);
+
+    // The local branch diverged from the remote. It should keep its target,
+    // and its pin.
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "branch",
+            "set",
+            "master",
+            "-r@",
+            "--allow-backwards",
+            "--at-change",
+        ],
+    );
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "branch",
+            "retarget",
+            "master",
+            "--to-remote-branch",
+            "main@origin",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Renamed branch master to main.
+    Started tracking main@origin, which is ahead by 1 commits, behind by 1 commits.
+    "###);
+    insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
+    main (pinned to change qpvuntsmwlqt): qpvuntsm 230dd059 (empty) (no description set)
+      @origin (ahead by 1 commits, behind by 1 commits): sptzoqmo 7b33f629 commit 1
+    master@origin: sptzoqmo 7b33f629 commit 1
+    "###);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "branch",
+            "retarget",
+            "main",
+            "--to-remote-branch",
+            "main@origin",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Remote branch already tracked: main@origin
+    "###);
 }
 
 #[test]
@@ -1031,6 +1217,21 @@ fn test_branch_list() {
       @origin (ahead by 1 commits, behind by 1 commits): qpsqxpyq 38ef8af7 (empty) remote-unsync
     remote-untrack@origin: vmortlor 71a16b05 (empty) remote-untrack
     "###);
+
+    let template = r#"
+    self ++ " " ++ self.ahead_by("origin") ++ " " ++ self.behind_by("origin") ++ "\n"
+    "#;
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&local_path, &["branch", "list", "--all", "-T", template]), @r###"
+    local-only <Error: Failed to evaluate ahead_by: Branch local-only@origin doesn't exist> <Error: Failed to evaluate behind_by: Branch local-only@origin doesn't exist>
+    remote-delete* <Error: Failed to evaluate ahead_by: Branch remote-delete doesn't exist> <Error: Failed to evaluate behind_by: Branch remote-delete doesn't exist>
+    remote-delete@origin 0 0
+    remote-sync 0 0
+    remote-sync@origin 0 0
+    remote-unsync* 1 1
+    remote-unsync@origin 0 0
+    remote-untrack@origin 0 0
+    "###);
 }
 
 #[test]
@@ -1387,6 +1588,117 @@ fn test_branch_list_conflicted() {
     "###);
 }
 
+#[test]
+fn test_branch_list_sort() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"templates.commit_summary_no_branches = "description.first_line()""#);
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=old"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "c", "b"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=new"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "a"]);
+
+    let list = |args: &[&str]| {
+        let mut all_args = vec!["branch", "list"];
+        all_args.extend_from_slice(args);
+        test_env.jj_cmd_success(&repo_path, &all_args)
+    };
+    insta::assert_snapshot!(list(&[]), @r###"
+    a: new
+    b: old
+    c: old
+    "###);
+    insta::assert_snapshot!(list(&["--sort=name:desc"]), @r###"
+    c: old
+    b: old
+    a: new
+    "###);
+    // Branches pointing to the same commit stay in name order
+    insta::assert_snapshot!(list(&["--sort=committer-date"]), @r###"
+    b: old
+    c: old
+    a: new
+    "###);
+    insta::assert_snapshot!(list(&["--sort=committer-date:desc"]), @r###"
+    a: new
+    b: old
+    c: old
+    "###);
+
+    // Default from config
+    test_env.add_config(r#"ui.branch-list-sort = "committer-date""#);
+    insta::assert_snapshot!(list(&[]), @r###"
+    b: old
+    c: old
+    a: new
+    "###);
+    insta::assert_snapshot!(list(&["--sort=name"]), @r###"
+    a: new
+    b: old
+    c: old
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["branch", "list", "--sort=size"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value 'size' for '--sort <KEY[:desc]>': invalid sort key "size" (expected name, committer-date, or ahead)
+
+    For more information, try '--help'.
+    "###);
+    test_env.add_config(r#"ui.branch-list-sort = "name:up""#);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["branch", "list"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: Invalid `ui.branch-list-sort`
+    Caused by: invalid sort order "up" (expected asc or desc)
+    For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md.
+    "###);
+}
+
+#[test]
+fn test_branch_list_sort_ahead() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.auto-local-branch = true");
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "remote", "--git"]);
+    let remote_path = test_env.env_root().join("remote");
+    for branch in ["a", "b"] {
+        test_env.jj_cmd_ok(&remote_path, &["new", "root()", "-m", branch]);
+        test_env.jj_cmd_ok(&remote_path, &["branch", "create", branch]);
+    }
+    test_env.jj_cmd_ok(&remote_path, &["new"]);
+    test_env.jj_cmd_ok(&remote_path, &["git", "export"]);
+
+    let mut remote_git_path = remote_path;
+    remote_git_path.extend([".jj", "repo", "store", "git"]);
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["git", "clone", remote_git_path.to_str().unwrap(), "local"],
+    );
+    let local_path = test_env.env_root().join("local");
+    test_env.add_config(r#"templates.commit_summary_no_branches = "description.first_line()""#);
+    // "a" is 2 commits ahead of a@origin, "b" is 1 commit ahead of b@origin
+    test_env.jj_cmd_ok(&local_path, &["new", "a", "-m=a2"]);
+    test_env.jj_cmd_ok(&local_path, &["new", "-m=a3"]);
+    test_env.jj_cmd_ok(&local_path, &["branch", "set", "a"]);
+    test_env.jj_cmd_ok(&local_path, &["new", "b", "-m=b2"]);
+    test_env.jj_cmd_ok(&local_path, &["branch", "set", "b"]);
+
+    let stdout = test_env.jj_cmd_success(&local_path, &["branch", "list", "--sort=ahead"]);
+    insta::assert_snapshot!(stdout, @r###"
+    b: b2
+      @origin (behind by 1 commits): b
+    a: a3
+      @origin (behind by 2 commits): a
+    "###);
+    let stdout = test_env.jj_cmd_success(&local_path, &["branch", "list", "--sort=ahead:desc"]);
+    insta::assert_snapshot!(stdout, @r###"
+    a: a3
+      @origin (behind by 2 commits): a
+    b: b2
+      @origin (behind by 1 commits): b
+    "###);
+}
+
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
     let template = r#"branches ++ " " ++ commit_id.short()"#;
     test_env.jj_cmd_success(cwd, &["log", "-T", template])
@@ -0,0 +1,62 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_changes() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo_path = test_env.env_root().join("git-repo");
+    git2::Repository::init_bare(git_repo_path).unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "remote", "add", "origin", "../git-repo"],
+    );
+
+    std::fs::write(repo_path.join("file"), "pushed\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "pushed"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "feature"]);
+    test_env.jj_cmd_ok(&repo_path, &["git", "push", "--branch", "feature"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "other"]);
+    std::fs::write(repo_path.join("file"), "other\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "@", "feature", "-m", "conflicted"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "divergent 1"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["describe", "-m", "divergent 2", "--at-operation", "@-"],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["workspace", "add", "../secondary"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["changes"]);
+    insta::assert_snapshot!(stdout, @r###"
+    vzqnnsmr d1ccff58 secondary@ (empty) (no description set)
+    vruxwmqv?? a37b9d48 (divergent) (empty) divergent 2
+    vruxwmqv?? 40f430e9 default@ (divergent) (empty) divergent 1
+    yqosqzyt 456eebf9 (conflict) (empty) conflicted
+    royxmykx 452311a4 other
+    qpvuntsm 9bde510d feature@origin pushed
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["changes", "-r", "feature", "-T", r#"description ++ "\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    pushed
+
+    "###);
+}
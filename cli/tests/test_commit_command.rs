@@ -168,6 +168,44 @@ fn test_commit_without_working_copy() {
     "###);
 }
 
+#[test]
+fn test_commit_signoff() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-s", "-m=first"]);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["log", "--no-graph", "-r@-", "-Tdescription"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    first
+
+    Signed-off-by: Test User <test.user@example.com>
+    "###);
+
+    // The sign-off is in the editor, so it can be removed there
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(&edit_script, ["dump editor0", "write\nsecond"].join("\0")).unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-s"]);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor0")).unwrap(), @r###"
+
+
+    Signed-off-by: Test User <test.user@example.com>
+
+    JJ: Lines starting with "JJ: " (like this one) will be removed.
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["log", "--no-graph", "-r@-", "-Tdescription"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    "###);
+}
+
 #[test]
 fn test_commit_paths() {
     let test_env = TestEnvironment::default();
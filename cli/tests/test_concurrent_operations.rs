@@ -237,6 +237,136 @@ fn test_concurrent_snapshot_wc_reloadable() {
     "###);
 }
 
+#[test]
+fn test_concurrent_operations_merged() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "initial"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    // Another process creates a branch while the description is being edited
+    let jj_path = assert_cmd::cargo::cargo_bin("jj");
+    std::fs::write(
+        &edit_script,
+        [
+            &format!(
+                "run\n{}\nbranch\ncreate\nconcurrent\n-r\n@-",
+                jj_path.display()
+            ),
+            "write\ndescribed",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe"]);
+    assert!(
+        stderr.contains("Concurrent modification detected, merged 1 operations into this one."),
+        "{stderr}"
+    );
+
+    // The operations aren't divergent, so the next command has nothing to
+    // resolve
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", r#""op""#]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  op
+    ◉  op
+    ◉  op
+    ◉  op
+    ◉  op
+    ◉  op
+    ◉  op
+    "###);
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["log", "-T", "description ++ branches"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  described
+    ◉  initial
+    │  concurrent
+    ◉
+    "###);
+    insta::assert_snapshot!(stderr, @"");
+}
+
+#[test]
+fn test_concurrent_operations_merged_wc_moved() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+
+    // Another process checks out another commit while the description is
+    // being edited, which removes the file from disk
+    let jj_path = assert_cmd::cargo::cargo_bin("jj");
+    std::fs::write(
+        &edit_script,
+        [
+            &format!("run\n{}\nnew\nroot()", jj_path.display()),
+            "write\ndescribed",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe"]);
+    assert!(
+        stderr.contains("Concurrent modification detected, merged 1 operations into this one."),
+        "{stderr}"
+    );
+    assert!(stderr.contains("Working copy now at: "), "{stderr}");
+
+    // The described commit is checked out over the other one
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]);
+    assert!(stdout.contains("@  described"), "{stdout}");
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(),
+        "contents\n"
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status"]);
+    assert!(stdout.contains("A file"), "{stdout}");
+}
+
+#[test]
+fn test_concurrent_operations_merge_disabled() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("operation.merge-concurrent = false");
+    let edit_script = test_env.set_up_fake_editor();
+
+    // Another process creates a branch while the description is being edited
+    let jj_path = assert_cmd::cargo::cargo_bin("jj");
+    std::fs::write(
+        &edit_script,
+        [
+            &format!(
+                "run\n{}\nbranch\ncreate\nconcurrent\n-r\n@",
+                jj_path.display()
+            ),
+            "write\ndescribed",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe"]);
+    assert!(
+        !stderr.contains("Concurrent modification detected"),
+        "{stderr}"
+    );
+
+    // The operations are left divergent
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", r#""op""#]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  op
+    │ ◉  op
+    ├─╯
+    ◉  op
+    ◉  op
+    ◉  op
+    "###);
+}
+
 fn get_log_output_with_stderr(test_env: &TestEnvironment, cwd: &Path) -> (String, String) {
     let template = r#"commit_id ++ " " ++ description"#;
     test_env.jj_cmd_ok(cwd, &["log", "-T", template])
@@ -11,6 +11,7 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::ffi::OsStr;
 
 use insta::assert_snapshot;
 use regex::Regex;
@@ -54,6 +55,99 @@ fn test_debug_revset() {
     });
 }
 
+#[test]
+fn test_debug_revset_explain() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "debug",
+            "revset",
+            "--explain",
+            "heads(::@) | (::@ & root())",
+        ],
+    );
+    insta::with_settings!({filters => vec![
+        (r"(?m)(^    .*\n)+", "    ..\n"),
+    ]}, {
+        assert_snapshot!(stdout, @r###"
+        -- Parsed:
+        Union(
+            ..
+        )
+
+        -- Optimized by reorder_intersection:
+        Union(
+            ..
+        )
+
+        -- Optimized:
+        Union(
+            ..
+        )
+
+        -- Resolved:
+        Union(
+            ..
+        )
+
+        -- Evaluated once:
+        Ancestors {
+            ..
+        }
+
+        -- Evaluated:
+        RevsetImpl {
+            ..
+        }
+
+        -- Commit IDs:
+        230dd059e1b059aefc0da06a2e5a7dbf22362f22
+        0000000000000000000000000000000000000000
+        "###);
+    });
+}
+
+#[test]
+fn test_debug_revset_functions() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "revset-functions"]);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert!(lines.contains(&"all: 0 arguments"));
+    assert!(lines.contains(&"ancestors: 1 to 2 arguments"));
+    assert!(lines.contains(&"file: at least 1 argument"));
+}
+
+#[test]
+fn test_debug_template_functions() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    // Aliases don't hide the builtin functions.
+    test_env.add_config(r#"template-aliases.'fill(a)' = "a""#);
+
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "template-functions"]);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert!(lines.contains(&"Template functions:"));
+    assert!(lines.contains(&"  concat: any number of arguments"));
+    assert!(lines.contains(&"  fill: 2 arguments"));
+    assert!(lines.contains(&"  if: 2 to 3 arguments"));
+    assert!(lines.contains(&"String methods:"));
+    assert!(lines.contains(&"  substr: 2 arguments"));
+    assert!(lines.contains(&"RefName methods:"));
+    assert!(lines.contains(&"  remote: 0 arguments"));
+    assert!(lines.contains(&"TimestampRange methods:"));
+    assert!(lines.contains(&"  duration: 0 arguments"));
+    assert!(lines.contains(&"OperationId methods:"));
+    assert!(lines.contains(&"  short: 0 to 1 arguments"));
+}
+
 #[test]
 fn test_debug_index() {
     let test_env = TestEnvironment::default();
@@ -198,6 +292,206 @@ fn test_debug_tree() {
     );
 }
 
+#[test]
+fn test_debug_tree_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    std::fs::write(workspace_path.join("file"), "base\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m", "base"]);
+    std::fs::write(workspace_path.join("file"), "left\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "left"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "@-"]);
+    std::fs::write(workspace_path.join("file"), "right\n").unwrap();
+    std::fs::write(workspace_path.join("other"), "other\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "right"]);
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["new", "description(left)", "description(right)"],
+    );
+
+    // Conflicted entries list all of their terms
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "tree", "--json"]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"path":"file","value":{"adds":[{"executable":false,"id":"45cf141ba67d59203f02a54f03162f3fcef57830","type":"file"},{"executable":false,"id":"c376d892e8b105bd712d06ec5162b5f31ce949c3","type":"file"}],"removes":[{"executable":false,"id":"df967b96a579e45a18b8251732d16804b2e56a55","type":"file"}]}}
+    {"path":"other","value":{"adds":[{"executable":false,"id":"e45c9c2666d44e0327c1f9c239a74c508336053e","type":"file"}],"removes":[]}}
+    "###);
+}
+
+#[test]
+fn test_debug_object() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    std::fs::create_dir(workspace_path.join("dir")).unwrap();
+    std::fs::write(workspace_path.join("dir").join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "description"]);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["log", "--no-graph", "-r@", "-Tcommit_id"],
+    );
+    let commit_id = stdout.trim();
+
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["debug", "object", "commit", commit_id, "--json"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    {
+      "author": {
+        "email": "test.user@example.com",
+        "name": "Test User",
+        "timestamp": 981147907000,
+        "tz_offset": 420
+      },
+      "change_id": "qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu",
+      "committer": {
+        "email": "test.user@example.com",
+        "name": "Test User",
+        "timestamp": 981147908000,
+        "tz_offset": 420
+      },
+      "description": "description\n",
+      "extra": {},
+      "parents": [
+        "0000000000000000000000000000000000000000"
+      ],
+      "predecessors": [
+        "b5dcd9f63a04a52b6fdab3b2c58edad246e1793a"
+      ],
+      "root_tree": {
+        "adds": [
+          "b3ccf3ca84d576a677810704286eb7acb3001c3b"
+        ],
+        "removes": []
+      },
+      "secure_sig": null
+    }
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "debug",
+            "object",
+            "tree",
+            "b3ccf3ca84d576a677810704286eb7acb3001c3b",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    dir: Tree(TreeId("8add0d07efc6ba027407c82740a001cfcbc7b772"))
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "debug",
+            "object",
+            "file",
+            "12f00e90b6ef79117ce6e650416b8cf517099b78",
+            "--path=dir/file",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    contents
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "debug",
+            "object",
+            "file",
+            "12f00e90b6ef79117ce6e650416b8cf517099b78",
+            "--path=dir/file",
+            "--json",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    {
+      "content": "contents\n"
+    }
+    "###);
+
+    // Content which isn't valid UTF-8 is printed as hex
+    std::fs::write(workspace_path.join("binary"), b"\xff\xfe\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["status"]);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "debug",
+            "object",
+            "file",
+            "10024e7fc8861bf61ecd60cfc3cc89b74045b64e",
+            "--path=binary",
+            "--json",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    {
+      "content_hex": "fffe0a"
+    }
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&workspace_path, &["debug", "object", "commit", "xyz"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid commit id
+    "###);
+}
+
+#[test]
+fn test_debug_repack() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["init", "repo", "--config-toml=ui.allow-init-native=true"],
+    );
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file"), "modified contents\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["debug", "repack"]);
+    assert!(stdout.contains("Removed "), "{stdout}");
+    let loose_commits = repo_path.join(".jj/repo/store/commits");
+    assert_eq!(std::fs::read_dir(loose_commits).unwrap().count(), 0);
+
+    // The packed objects can be read, and new objects can be added
+    let stdout = test_env.jj_cmd_success(&repo_path, &["cat", "file", "-r", "@-"]);
+    assert_snapshot!(stdout, @r###"
+    contents
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["cat", "file"]);
+    assert_snapshot!(stdout, @r###"
+    modified contents
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    test_env.jj_cmd_ok(&repo_path, &["util", "gc"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["cat", "file"]);
+    assert_snapshot!(stdout, @r###"
+    modified contents
+    "###);
+
+    // gc only packs the new loose objects, whereas repack merges the packs
+    let count_packs = || {
+        std::fs::read_dir(repo_path.join(".jj/repo/store/packs"))
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().extension() == Some(OsStr::new("pack")))
+            .count()
+    };
+    assert_eq!(count_packs(), 2);
+    test_env.jj_cmd_ok(&repo_path, &["debug", "repack"]);
+    assert_eq!(count_packs(), 1);
+
+    // Repos using the Git backend can't be repacked
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "git-repo", "--git"]);
+    let stderr =
+        test_env.jj_cmd_failure(&test_env.env_root().join("git-repo"), &["debug", "repack"]);
+    assert_snapshot!(stderr, @r###"
+    Error: Only repositories using the local backend can be repacked
+    "###);
+}
+
 #[test]
 fn test_debug_operation_id() {
     let test_env = TestEnvironment::default();
@@ -211,7 +505,119 @@ fn test_debug_operation_id() {
     );
 }
 
+#[test]
+fn test_debug_cache_stats() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    for i in 0..3 {
+        std::fs::write(workspace_path.join(format!("file{i}")), "contents\n").unwrap();
+        test_env.jj_cmd_ok(&workspace_path, &["new"]);
+    }
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "debug",
+            "cache-stats",
+            "-r=all()",
+            "--config-toml=store.commit-cache-size=2",
+        ],
+    );
+    assert_snapshot!(stdout, @r###"
+    Commit cache:
+      Capacity: 2
+      Entries: 2
+      Hits: 5
+      Misses: 5
+      Hit rate: 50.0%
+    Tree cache:
+      Capacity: unlimited
+      Entries: 4
+      Hits: 8
+      Misses: 4
+      Hit rate: 66.7%
+    "###);
+}
+
+#[test]
+fn test_debug_watch() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "watched"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_path,
+        &[
+            "debug",
+            "watch",
+            "--count=1",
+            "--",
+            "log",
+            "--no-graph",
+            "-r@",
+            "-T",
+            "description",
+        ],
+    );
+    assert_snapshot!(stdout, @r###"
+    watched
+    "###);
+    assert_snapshot!(stderr, @"");
+
+    // A failing command is reported, but doesn't stop watching
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_path,
+        &[
+            "debug",
+            "watch",
+            "--count=1",
+            "--",
+            "log",
+            "-r",
+            "nonexistent",
+        ],
+    );
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("Revision \"nonexistent\" doesn't exist"));
+    assert!(stderr.contains("Warning: Command exited with"));
+}
+
 fn filter_index_stats(text: &str) -> String {
     let regex = Regex::new(r"    Name: [0-9a-z]+").unwrap();
     regex.replace_all(text, "    Name: [hash]").to_string()
 }
+
+#[test]
+fn test_debug_journal() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&workspace_path, &["branch", "create", "main"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "-m", "second"]);
+    test_env.jj_cmd_ok(&workspace_path, &["branch", "set", "main", "-r", "@"]);
+
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "journal"]);
+    let op_ids = test_env.jj_cmd_success(
+        &workspace_path,
+        &["op", "log", "--no-graph", "-T", r#"id ++ "\n""#],
+    );
+    let op_ids = op_ids.lines().rev().collect::<Vec<_>>();
+    // Every published operation except the root one has an entry, in order.
+    let journal_op_ids = stdout
+        .lines()
+        .map(|line| line.split('"').nth(3).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(journal_op_ids, op_ids[1..]);
+    insta::with_settings!({filters => vec![
+        (r#""op_id":"[0-9a-f]+""#, r#""op_id":"..""#),
+    ]}, {
+        assert_snapshot!(stdout, @r###"
+        {"op_id":"..","wc_commits":{}}
+        {"op_id":"..","wc_commits":{"default":"230dd059e1b059aefc0da06a2e5a7dbf22362f22"}}
+        {"op_id":"..","branches":["main"],"wc_commits":{"default":"230dd059e1b059aefc0da06a2e5a7dbf22362f22"}}
+        {"op_id":"..","wc_commits":{"default":"7e54d57844a7e96f084312cbb59cd2e6c539da00"}}
+        {"op_id":"..","branches":["main"],"wc_commits":{"default":"7e54d57844a7e96f084312cbb59cd2e6c539da00"}}
+        "###);
+    });
+}
@@ -323,3 +323,178 @@ fn test_describe_author() {
     ~
     "###);
 }
+
+#[test]
+fn test_describe_trailers() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+        [commit]
+        trailers = [
+          '"Signed-off-by: " ++ author.name() ++ " <" ++ author.email() ++ ">"',
+          'if(false, "Ignored: empty")',
+        ]
+        "#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "subject"]);
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @r###"
+    subject
+
+    Signed-off-by: Test User <test.user@example.com>
+    "###);
+
+    // Existing trailers are not duplicated
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe", "--no-edit"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    // Trailers are rendered against the commit being written
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "subject",
+            "--reset-author",
+            "--config-toml=user.name='Other User'",
+        ],
+    );
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @r###"
+    subject
+
+    Signed-off-by: Other User <test.user@example.com>
+    "###);
+
+    // Empty descriptions are left alone
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", ""]);
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Trailer templates must render to a footer line
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "subject",
+            r#"--config-toml=commit.trailers=['"not a trailer"']"#,
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Trailer template `"not a trailer"` rendered to `not a trailer`, which isn't a `Key: value` line
+    "###);
+}
+
+#[test]
+fn test_describe_signoff() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "--signoff",
+            "-m",
+            "subject\n\nAcked-by: Someone",
+        ],
+    );
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @r###"
+    subject
+
+    Acked-by: Someone
+    Signed-off-by: Test User <test.user@example.com>
+    "###);
+
+    // An existing sign-off isn't duplicated
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe", "--no-edit", "-s"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    // It can be enabled by config
+    test_env.add_config("commit.signoff = true");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "other",
+            "--config-toml=user.name='Other User'",
+        ],
+    );
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @r###"
+    other
+
+    Signed-off-by: Other User <test.user@example.com>
+    "###);
+
+    // Empty descriptions are left alone
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", ""]);
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @"");
+}
+
+#[test]
+fn test_describe_signoff_in_editor() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let edit_script = test_env.set_up_fake_editor();
+
+    // The sign-off is in the editor, after room for the message
+    std::fs::write(&edit_script, "dump editor0").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-s"]);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor0")).unwrap(), @r###"
+
+
+    Signed-off-by: Test User <test.user@example.com>
+
+    JJ: Lines starting with "JJ: " (like this one) will be removed.
+    "###);
+
+    // It's added to the footer of an existing description
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "subject"]);
+    std::fs::write(&edit_script, "dump editor1").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-s"]);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor1")).unwrap(), @r###"
+    subject
+
+    Signed-off-by: Test User <test.user@example.com>
+
+    JJ: Lines starting with "JJ: " (like this one) will be removed.
+    "###);
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @r###"
+    subject
+
+    Signed-off-by: Test User <test.user@example.com>
+    "###);
+
+    // Removing it in the editor isn't overridden
+    std::fs::write(&edit_script, "write\nsubject").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-s"]);
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @r###"
+    subject
+    "###);
+}
@@ -273,6 +273,57 @@ fn test_diff_bad_args() {
     "###);
 }
 
+#[test]
+fn test_diff_change_across_operations() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("base"), "base\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "change"]);
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "change"]);
+    let from_op = test_env.current_operation_id(&repo_path);
+    // Amend the change and modify its parent
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["edit", "@-"]);
+    std::fs::write(repo_path.join("base"), "base\nmodified\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["edit", "change"]);
+
+    // Only the amended content is shown, not the parent's change
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--change", "change", "--from-op", &from_op, "--git"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    index 7898192261...422c2b7ab3 100644
+    --- a/file
+    +++ b/file
+    @@ -1,1 +1,2 @@
+     a
+    +b
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["diff", "--change", "@-", "--from-op", "@---------"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Change qpvuntsmwlqt doesn't exist at operation 9a7d829846af
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["diff", "--from-op", "@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the following required arguments were not provided:
+      --change <CHANGE>
+
+    Usage: jj diff --from-op <FROM_OP> --change <CHANGE> [PATHS]...
+
+    For more information, try '--help'.
+    "###);
+}
+
 #[test]
 fn test_diff_relative_paths() {
     let test_env = TestEnvironment::default();
@@ -406,6 +457,65 @@ fn test_diff_relative_paths() {
     "###);
 }
 
+#[test]
+fn test_diff_file_patterns() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir_all(repo_path.join("dir1").join("subdir1")).unwrap();
+    std::fs::create_dir(repo_path.join("dir2")).unwrap();
+    std::fs::write(repo_path.join("file1"), "foo1\n").unwrap();
+    std::fs::write(repo_path.join("dir1").join("file2"), "foo2\n").unwrap();
+    std::fs::write(
+        repo_path.join("dir1").join("subdir1").join("file3"),
+        "foo3\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("dir2").join("file4"), "foo4\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path.join("dir1"), &["diff", "-s", "glob:*"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file2
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path.join("dir1"), &["diff", "-s", "glob:**/file3"]);
+    #[cfg(unix)]
+    insta::assert_snapshot!(stdout, @r###"
+    A subdir1/file3
+    "###);
+    #[cfg(windows)]
+    insta::assert_snapshot!(stdout, @r###"
+    A subdir1\file3
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path.join("dir1"),
+        &["diff", "-s", "root-glob:file?", "root:dir2"],
+    );
+    #[cfg(unix)]
+    insta::assert_snapshot!(stdout, @r###"
+    A ../dir2/file4
+    A ../file1
+    "###);
+    #[cfg(windows)]
+    insta::assert_snapshot!(stdout, @r###"
+    A ..\dir2\file4
+    A ..\file1
+    "###);
+
+    // Plain paths are literal
+    std::fs::write(repo_path.join("file["), "foo5\n").unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "file[", "*"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file[
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "-s", "glob:file["]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse fileset
+    Caused by: Pattern syntax error near position 4: invalid range pattern
+    "###);
+}
+
 #[test]
 fn test_diff_missing_newline() {
     let test_env = TestEnvironment::default();
@@ -740,6 +850,126 @@ fn test_diff_skipped_context_nondefault() {
     "###);
 }
 
+#[test]
+fn test_diff_algorithm() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\nb\nc\na\nb\nb\na\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file"), "c\nb\na\nb\na\nc\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    index 4eca417796...5640cf335e 100644
+    --- a/file
+    +++ b/file
+    @@ -1,7 +1,6 @@
+    -a
+    -b
+     c
+    +b
+     a
+    -b
+     b
+     a
+    +c
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--patience"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    index 4eca417796...5640cf335e 100644
+    --- a/file
+    +++ b/file
+    @@ -1,7 +1,6 @@
+    -a
+    -b
+     c
+    +b
+     a
+     b
+    -b
+     a
+    +c
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--myers"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    index 4eca417796...5640cf335e 100644
+    --- a/file
+    +++ b/file
+    @@ -1,7 +1,6 @@
+    -a
+    -b
+     c
+    +b
+     a
+     b
+    -b
+     a
+    +c
+    "###);
+
+    // The algorithm can be configured, and overridden by the arguments
+    test_env.add_config(r#"ui.diff.algorithm = "patience""#);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    index 4eca417796...5640cf335e 100644
+    --- a/file
+    +++ b/file
+    @@ -1,7 +1,6 @@
+    -a
+    -b
+     c
+    +b
+     a
+     b
+    -b
+     a
+    +c
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--histogram"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    index 4eca417796...5640cf335e 100644
+    --- a/file
+    +++ b/file
+    @@ -1,7 +1,6 @@
+    -a
+    -b
+     c
+    +b
+     a
+    -b
+     b
+     a
+    +c
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["diff", "--patience", "--myers"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the argument '--patience' cannot be used with '--myers'
+
+    Usage: jj diff --patience [PATHS]...
+
+    For more information, try '--help'.
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["diff", "--config-toml", r#"ui.diff.algorithm = "bogus""#],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: invalid diff algorithm: bogus
+    For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md.
+    "###);
+}
+
 #[test]
 fn test_diff_external_tool() {
     let mut test_env = TestEnvironment::default();
@@ -938,6 +1168,57 @@ fn test_diff_stat() {
     "###);
 }
 
+#[test]
+fn test_diff_dirstat() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::create_dir_all(repo_path.join("a").join("b").join("c")).unwrap();
+    std::fs::write(repo_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(repo_path.join("a").join("file2"), "foo\n").unwrap();
+    std::fs::write(
+        repo_path.join("a").join("b").join("c").join("file3"),
+        "foo\nbar\n",
+    )
+    .unwrap();
+
+    // The percentages are padded like `git diff --dirstat`, which insta
+    // snapshots can't represent. Directories are printed with the platform's
+    // path separator.
+    let sep = std::path::MAIN_SEPARATOR;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--dirstat"]);
+    assert_eq!(
+        stdout,
+        format!(" 25.0% .{sep}\n 25.0% a{sep}\n 50.0% a{sep}b{sep}c{sep}\n")
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--dirstat", "--dirstat-depth=1"]);
+    assert_eq!(stdout, format!(" 25.0% .{sep}\n 75.0% a{sep}\n"));
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--dirstat", "--dirstat-depth=0"]);
+    assert_eq!(stdout, format!("100.0% .{sep}\n"));
+
+    // Paths are relative to the current directory
+    let stdout = test_env.jj_cmd_success(&repo_path.join("a"), &["diff", "--dirstat"]);
+    assert_eq!(
+        stdout,
+        format!(" 25.0% ..{sep}\n 25.0% .{sep}\n 50.0% b{sep}c{sep}\n")
+    );
+
+    // Nothing is printed if no lines changed
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--dirstat"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["diff", "--dirstat-depth=1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the following required arguments were not provided:
+      --dirstat
+
+    Usage: jj diff --dirstat --dirstat-depth <DEPTH> [PATHS]...
+
+    For more information, try '--help'.
+    "###);
+}
+
 #[test]
 fn test_diff_stat_long_name_or_stat() {
     let mut test_env = TestEnvironment::default();
@@ -60,7 +60,7 @@ fn test_duplicate() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "a"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 2443ea76b0b1 as kpqxywon f5b1e687 a
+    Duplicated rlvkpnrz 2443ea76 a | a as kpqxywon f5b1e687 a
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     ◉  f5b1e68729d6   a
@@ -79,7 +79,7 @@ fn test_duplicate() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate" /* duplicates `c` */]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 17a00fc21654 as lylxulpl ef3b0f3d c
+    Duplicated royxmykx 17a00fc2 c | c as lylxulpl ef3b0f3d c
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     ◉    ef3b0f3d1046   c
@@ -119,8 +119,8 @@ fn test_duplicate_many() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "b::"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 1394f625cbbd as wqnwkozp 3b74d969 b
-    Duplicated 921dde6e55c0 as mouksmqu 8348ddce e
+    Duplicated zsuskuln 1394f625 b | b as wqnwkozp 3b74d969 b
+    Duplicated znkkpsqq 921dde6e e | e as mouksmqu 8348ddce e
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     ◉    8348ddcec733   e
@@ -142,7 +142,7 @@ fn test_duplicate_many() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "b", "b"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 1394f625cbbd as nkmrtpmo 0276d3d7 b
+    Duplicated zsuskuln 1394f625 b | b as nkmrtpmo 0276d3d7 b
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     ◉  0276d3d7c24d   b
@@ -162,9 +162,9 @@ fn test_duplicate_many() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "b::", "d::"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 1394f625cbbd as xtnwkqum fa167d18 b
-    Duplicated ebd06dba20ec as pqrnrkux 2181781b d
-    Duplicated 921dde6e55c0 as ztxkyksq 0f7430f2 e
+    Duplicated zsuskuln 1394f625 b | b as xtnwkqum fa167d18 b
+    Duplicated vruxwmqv ebd06dba d | d as pqrnrkux 2181781b d
+    Duplicated znkkpsqq 921dde6e e | e as ztxkyksq 0f7430f2 e
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     ◉    0f7430f2727a   e
@@ -198,9 +198,9 @@ fn test_duplicate_many() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "d::", "a"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 2443ea76b0b1 as nlrtlrxv c6f7f8c4 a
-    Duplicated ebd06dba20ec as plymsszl d94e4c55 d
-    Duplicated 921dde6e55c0 as urrlptpw 9bd4389f e
+    Duplicated rlvkpnrz 2443ea76 a | a as nlrtlrxv c6f7f8c4 a
+    Duplicated vruxwmqv ebd06dba d | d as plymsszl d94e4c55 d
+    Duplicated znkkpsqq 921dde6e e | e as urrlptpw 9bd4389f e
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     ◉    9bd4389f5d47   e
@@ -224,11 +224,11 @@ fn test_duplicate_many() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "a::"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 2443ea76b0b1 as uuuvxpvw 0fe67a05 a
-    Duplicated 1394f625cbbd as nmpuuozl e13ac0ad b
-    Duplicated c0cb3a0b73e7 as kzpokyyw df53fa58 c
-    Duplicated ebd06dba20ec as yxrlprzz 2f2442db d
-    Duplicated 921dde6e55c0 as mvkzkxrl ee8fe64e e
+    Duplicated rlvkpnrz 2443ea76 a | a as uuuvxpvw 0fe67a05 a
+    Duplicated zsuskuln 1394f625 b | b as nmpuuozl e13ac0ad b
+    Duplicated royxmykx c0cb3a0b c | c as kzpokyyw df53fa58 c
+    Duplicated vruxwmqv ebd06dba d | d as yxrlprzz 2f2442db d
+    Duplicated znkkpsqq 921dde6e e | e as mvkzkxrl ee8fe64e e
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     ◉    ee8fe64ed254   e
@@ -266,7 +266,7 @@ fn test_undo_after_duplicate() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "a"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 2443ea76b0b1 as mzvwutvl f5cefcbb a
+    Duplicated rlvkpnrz 2443ea76 a | a as mzvwutvl f5cefcbb a
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     ◉  f5cefcbb65a4   a
@@ -303,12 +303,12 @@ fn test_rebase_duplicates() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "b"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 1394f625cbbd as yqosqzyt fdaaf395 b
+    Duplicated zsuskuln 1394f625 b | b as yqosqzyt fdaaf395 b
     "###);
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "b"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    Duplicated 1394f625cbbd as vruxwmqv 870cf438 b
+    Duplicated zsuskuln 1394f625 b | b as vruxwmqv 870cf438 b
     "###);
     insta::assert_snapshot!(get_log_output_with_ts(&test_env, &repo_path), @r###"
     ◉  870cf438ccbb   b @ 2001-02-03 04:05:14.000 +07:00
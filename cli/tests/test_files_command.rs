@@ -0,0 +1,93 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_files() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::create_dir_all(repo_path.join("dir").join("sub")).unwrap();
+    std::fs::write(repo_path.join("dir").join("Cargo.toml"), "").unwrap();
+    std::fs::write(repo_path.join("dir").join("sub").join("Cargo.toml"), "x\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file1"), "bb\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "c\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["chmod", "x", "file2"]);
+
+    // Lists all files in the working-copy commit by default
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files"]);
+    insta::assert_snapshot!(stdout.replace('\\', "/"), @r###"
+    dir/Cargo.toml
+    dir/sub/Cargo.toml
+    file1
+    file2
+    "###);
+
+    // Can find files by glob
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "glob:**/Cargo.toml"]);
+    insta::assert_snapshot!(stdout.replace('\\', "/"), @r###"
+    dir/Cargo.toml
+    dir/sub/Cargo.toml
+    "###);
+
+    // Lists the files in each revision in turn
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "-r@", "-r@-", "glob:file*"]);
+    insta::assert_snapshot!(stdout, @r###"
+    file1
+    file2
+    file1
+    "###);
+
+    // Can render file metadata
+    let template = r#"path ++ " " ++ mode ++ " " ++ size ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "-T", template]);
+    insta::assert_snapshot!(stdout.replace('\\', "/"), @r###"
+    dir/Cargo.toml file 0
+    dir/sub/Cargo.toml file 2
+    file1 file 3
+    file2 executable 2
+    "###);
+
+    // Can render the commit which last modified each file
+    let commit_id = |revision: &str| {
+        test_env.jj_cmd_success(
+            &repo_path,
+            &["log", "--no-graph", "-r", revision, "-T", "commit_id"],
+        )
+    };
+    let (parent_id, child_id) = (commit_id("@-"), commit_id("@"));
+    let template = r#"path ++ " " ++ commit_id ++ " " ++ last_modified_commit_id ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "-T", template, "-r@", "file1"]);
+    assert_eq!(stdout, format!("file1 {child_id} {child_id}\n"));
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "-T", template, "dir/Cargo.toml"]);
+    assert_eq!(
+        stdout.replace('\\', "/"),
+        format!("dir/Cargo.toml {child_id} {parent_id}\n")
+    );
+
+    // Error if the template is invalid
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["files", "-T", "nonexistent"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:1
+      |
+    1 | nonexistent
+      | ^---------^
+      |
+      = Keyword "nonexistent" doesn't exist
+    "###);
+}
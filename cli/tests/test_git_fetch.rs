@@ -1120,6 +1120,38 @@ fn test_git_fetch_removed_branch() {
     ◉  000000000000
     "###);
 
+    // Fetch branch a2 without pruning, and check that it is kept
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &target_jj_repo_path,
+        &["git", "fetch", "--branch", "a2", "--no-prune"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &target_jj_repo_path), @r###"
+    ◉  c7d4bdcbc215 descr_for_b b
+    │ ◉  decaa3966c83 descr_for_a2 a2
+    ├─╯
+    │ ◉  359a9a02457d descr_for_a1 a1
+    ├─╯
+    ◉  ff36dc55760e descr_for_trunk1 trunk1
+    │ @  230dd059e1b0
+    ├─╯
+    ◉  000000000000
+    "###);
+    let stdout = test_env.jj_cmd_success(&target_jj_repo_path, &["branch", "list", "--all"]);
+    insta::assert_snapshot!(stdout, @r###"
+    a1: nknoxmzm 359a9a02 descr_for_a1
+      @origin: nknoxmzm 359a9a02 descr_for_a1
+    a2: qkvnknrk decaa396 descr_for_a2
+      @origin: qkvnknrk decaa396 descr_for_a2
+    b: vpupmnsl c7d4bdcb descr_for_b
+      @origin: vpupmnsl c7d4bdcb descr_for_b
+    trunk1: zowqyktl ff36dc55 descr_for_trunk1
+      @origin: zowqyktl ff36dc55 descr_for_trunk1
+    "###);
+
     // Fetch branches a2 from origin, and check that it has been removed locally
     let (stdout, stderr) =
         test_env.jj_cmd_ok(&target_jj_repo_path, &["git", "fetch", "--branch", "a2"]);
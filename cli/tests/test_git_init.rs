@@ -442,6 +442,81 @@ fn test_git_init_colocated_via_git_repo_path_imported_refs() {
     "###);
 }
 
+#[test]
+fn test_git_init_colocated_imports_reflog_and_stash() {
+    let test_env = TestEnvironment::default();
+    let workspace_root = test_env.env_root().join("repo");
+    let mut git_repo = init_git_repo(&workspace_root, false);
+    git_repo
+        .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+
+    // Amend the commit so the original one is only reachable from the reflog
+    let old_commit = git_repo.head().unwrap().peel_to_commit().unwrap();
+    let old_commit_hex = old_commit.id().to_string();
+    old_commit
+        .amend(
+            Some("HEAD"),
+            None,
+            None,
+            None,
+            Some("Amended message"),
+            None,
+        )
+        .unwrap();
+    drop(old_commit);
+
+    // Stash a modification of the tracked file
+    std::fs::write(workspace_root.join("some-file"), "stashed content").unwrap();
+    let git_signature = git2::Signature::new(
+        "Git User",
+        "git.user@example.com",
+        &git2::Time::new(456, 60),
+    )
+    .unwrap();
+    git_repo
+        .stash_save(&git_signature, "my stash", None)
+        .unwrap();
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &["git", "init", "--colocate", "--import-stash"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Imported 1 hidden commits from Git reflogs.
+    Imported 1 Git stash entries as commits.
+    Done importing changes from the underlying Git repo.
+    Initialized repo in "."
+    "###);
+
+    let template = r#"if(description, description.first_line(), "(no description)") ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &["log", "--no-graph", "-r", "all()", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    (no description)
+    On my-branch: my stash
+    Amended message
+    (no description)
+    "###);
+
+    // The original commit is hidden, but can still be looked up by id
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &["log", "--no-graph", "-r", &old_commit_hex, "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    My commit message
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &["cat", "-r", "description(\"my stash\")", "some-file"],
+    );
+    insta::assert_snapshot!(stdout, @"stashed content");
+}
+
 #[test]
 fn test_git_init_colocated_dirty_working_copy() {
     let test_env = TestEnvironment::default();
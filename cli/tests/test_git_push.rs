@@ -93,16 +93,16 @@ fn test_git_push_current_branch() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Move branch branch2 from 8476341eb395 to 10ee3363b259
-      Add branch my-branch to 10ee3363b259
+      update  branch2    8476341eb395 -> 10ee3363b259
+      create  my-branch  (none)       -> 10ee3363b259
     Dry-run requested, not pushing.
     "###);
     let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Move branch branch2 from 8476341eb395 to 10ee3363b259
-      Add branch my-branch to 10ee3363b259
+      update  branch2    8476341eb395 -> 10ee3363b259
+      create  my-branch  (none)       -> 10ee3363b259
     "###);
     let stdout = test_env.jj_cmd_success(&workspace_root, &["branch", "list", "--all"]);
     insta::assert_snapshot!(stdout, @r###"
@@ -130,7 +130,7 @@ fn test_git_push_parent_branch() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Force branch branch1 from 45a3aa29e907 to d47326d59ee1
+      force update  branch1  45a3aa29e907 -> d47326d59ee1
     "###);
 }
 
@@ -190,7 +190,7 @@ fn test_git_push_other_remote_has_branch() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Force branch branch1 from 45a3aa29e907 to 50421a29358a
+      force update  branch1  45a3aa29e907 -> 50421a29358a
     "###);
     // Since it's already pushed to origin, nothing will happen if push again
     let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push"]);
@@ -204,7 +204,7 @@ fn test_git_push_other_remote_has_branch() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to other:
-      Add branch branch1 to 50421a29358a
+      create  branch1  (none) -> 50421a29358a
     "###);
 }
 
@@ -232,7 +232,7 @@ fn test_git_push_not_fast_forward() {
     insta::assert_snapshot!(get_stdout_string(&assert), @"");
     insta::assert_snapshot!(get_stderr_string(&assert), @r###"
     Branch changes to push to origin:
-      Move branch branch1 from 45a3aa29e907 to c35839cb8e8c
+      update  branch1  45a3aa29e907 -> c35839cb8e8c
     Error: The push conflicts with changes made on the remote (it is not fast-forwardable).
     Hint: Try fetching from the remote, then make the branch point to where you want it to be, and push again.
     "###);
@@ -250,7 +250,7 @@ fn test_git_push_locally_created_and_rewritten() {
     let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push"]);
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Add branch my to fcc999921ce9
+      create  my  (none) -> fcc999921ce9
     "###);
 
     // Rewrite it and push again, which would fail if the pushed branch weren't
@@ -268,7 +268,36 @@ fn test_git_push_locally_created_and_rewritten() {
     let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push"]);
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Force branch my from fcc999921ce9 to bde1d2e44b2a
+      force update  my  fcc999921ce9 -> bde1d2e44b2a
+    "###);
+}
+
+#[test]
+fn test_git_push_forge_urls() {
+    let (test_env, workspace_root) = set_up();
+    test_env.add_config(
+        r#"git.forge-urls.origin = "https://example.com/compare/{branch}?remote={remote}""#,
+    );
+    test_env.jj_cmd_ok(&workspace_root, &["branch", "delete", "branch1"]);
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["branch", "create", "my/branch", "-r", "branch2"],
+    );
+    // No links are printed for a dry run or for deleted branches
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--all", "--dry-run"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Branch changes to push to origin:
+      delete  branch1    45a3aa29e907 -> (none)
+      create  my/branch  (none)       -> 8476341eb395
+    Dry-run requested, not pushing.
+    "###);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--all"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Branch changes to push to origin:
+      delete  branch1    45a3aa29e907 -> (none)
+      create  my/branch  (none)       -> 8476341eb395
+    Hint: my/branch@origin: https://example.com/compare/my%2Fbranch?remote=origin
     "###);
 }
 
@@ -298,9 +327,9 @@ fn test_git_push_multiple() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Delete branch branch1 from 45a3aa29e907
-      Force branch branch2 from 8476341eb395 to 15dcdaa4f12f
-      Add branch my-branch to 15dcdaa4f12f
+      delete        branch1    45a3aa29e907 -> (none)
+      force update  branch2    8476341eb395 -> 15dcdaa4f12f
+      create        my-branch  (none)       -> 15dcdaa4f12f
     Dry-run requested, not pushing.
     "###);
     // Dry run requesting two specific branches
@@ -311,8 +340,8 @@ fn test_git_push_multiple() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Delete branch branch1 from 45a3aa29e907
-      Add branch my-branch to 15dcdaa4f12f
+      delete  branch1    45a3aa29e907 -> (none)
+      create  my-branch  (none)       -> 15dcdaa4f12f
     Dry-run requested, not pushing.
     "###);
     // Dry run requesting two specific branches twice
@@ -331,8 +360,8 @@ fn test_git_push_multiple() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Delete branch branch1 from 45a3aa29e907
-      Add branch my-branch to 15dcdaa4f12f
+      delete  branch1    45a3aa29e907 -> (none)
+      create  my-branch  (none)       -> 15dcdaa4f12f
     Dry-run requested, not pushing.
     "###);
     // Dry run with glob pattern
@@ -343,8 +372,8 @@ fn test_git_push_multiple() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Delete branch branch1 from 45a3aa29e907
-      Force branch branch2 from 8476341eb395 to 15dcdaa4f12f
+      delete        branch1  45a3aa29e907 -> (none)
+      force update  branch2  8476341eb395 -> 15dcdaa4f12f
     Dry-run requested, not pushing.
     "###);
 
@@ -365,9 +394,9 @@ fn test_git_push_multiple() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Delete branch branch1 from 45a3aa29e907
-      Force branch branch2 from 8476341eb395 to 15dcdaa4f12f
-      Add branch my-branch to 15dcdaa4f12f
+      delete        branch1    45a3aa29e907 -> (none)
+      force update  branch2    8476341eb395 -> 15dcdaa4f12f
+      create        my-branch  (none)       -> 15dcdaa4f12f
     "###);
     let stdout = test_env.jj_cmd_success(&workspace_root, &["branch", "list", "--all"]);
     insta::assert_snapshot!(stdout, @r###"
@@ -401,7 +430,7 @@ fn test_git_push_changes() {
     insta::assert_snapshot!(stderr, @r###"
     Creating branch push-yostqsxwqrlt for revision @
     Branch changes to push to origin:
-      Add branch push-yostqsxwqrlt to 28d7620ea63a
+      create  push-yostqsxwqrlt  (none) -> 28d7620ea63a
     "###);
     // test pushing two changes at once
     std::fs::write(workspace_root.join("file"), "modified2").unwrap();
@@ -410,8 +439,8 @@ fn test_git_push_changes() {
     insta::assert_snapshot!(stderr, @r###"
     Creating branch push-yqosqzytrlsw for revision @-
     Branch changes to push to origin:
-      Force branch push-yostqsxwqrlt from 28d7620ea63a to 48d8c7948133
-      Add branch push-yqosqzytrlsw to fa16a14170fb
+      force update  push-yostqsxwqrlt  28d7620ea63a -> 48d8c7948133
+      create        push-yqosqzytrlsw  (none)       -> fa16a14170fb
     "###);
     // specifying the same change twice doesn't break things
     std::fs::write(workspace_root.join("file"), "modified3").unwrap();
@@ -419,7 +448,7 @@ fn test_git_push_changes() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Force branch push-yostqsxwqrlt from 48d8c7948133 to b5f030322b1d
+      force update  push-yostqsxwqrlt  48d8c7948133 -> b5f030322b1d
     "###);
 
     // specifying the same branch with --change/--branch doesn't break things
@@ -431,7 +460,7 @@ fn test_git_push_changes() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Force branch push-yostqsxwqrlt from b5f030322b1d to 4df62cec2ee4
+      force update  push-yostqsxwqrlt  b5f030322b1d -> 4df62cec2ee4
     "###);
 
     // try again with --change that moves the branch forward
@@ -460,7 +489,7 @@ fn test_git_push_changes() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Force branch push-yostqsxwqrlt from 4df62cec2ee4 to 3e2ce808759b
+      force update  push-yostqsxwqrlt  4df62cec2ee4 -> 3e2ce808759b
     "###);
     let stdout = test_env.jj_cmd_success(&workspace_root, &["status"]);
     insta::assert_snapshot!(stdout, @r###"
@@ -485,7 +514,7 @@ fn test_git_push_changes() {
     insta::assert_snapshot!(stderr, @r###"
     Creating branch test-yostqsxwqrlt for revision @
     Branch changes to push to origin:
-      Add branch test-yostqsxwqrlt to 3e2ce808759b
+      create  test-yostqsxwqrlt  (none) -> 3e2ce808759b
     "###);
 }
 
@@ -521,7 +550,7 @@ fn test_git_push_revisions() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Add branch branch-1 to 7decc7932d9c
+      create  branch-1  (none) -> 7decc7932d9c
     Dry-run requested, not pushing.
     "###);
     // Push multiple revisions of which some have branches
@@ -533,7 +562,7 @@ fn test_git_push_revisions() {
     insta::assert_snapshot!(stderr, @r###"
     No branches point to the specified revisions: @--
     Branch changes to push to origin:
-      Add branch branch-1 to 7decc7932d9c
+      create  branch-1  (none) -> 7decc7932d9c
     Dry-run requested, not pushing.
     "###);
     // Push a revision with a multiple branches
@@ -542,8 +571,8 @@ fn test_git_push_revisions() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Add branch branch-2a to 1b45449e18d0
-      Add branch branch-2b to 1b45449e18d0
+      create  branch-2a  (none) -> 1b45449e18d0
+      create  branch-2b  (none) -> 1b45449e18d0
     Dry-run requested, not pushing.
     "###);
     // Repeating a commit doesn't result in repeated messages about the branch
@@ -554,7 +583,7 @@ fn test_git_push_revisions() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Add branch branch-1 to 7decc7932d9c
+      create  branch-1  (none) -> 7decc7932d9c
     Dry-run requested, not pushing.
     "###);
 }
@@ -580,10 +609,10 @@ fn test_git_push_mixed() {
     insta::assert_snapshot!(stderr, @r###"
     Creating branch push-yqosqzytrlsw for revision @--
     Branch changes to push to origin:
-      Add branch push-yqosqzytrlsw to fa16a14170fb
-      Add branch branch-1 to 7decc7932d9c
-      Add branch branch-2a to 1b45449e18d0
-      Add branch branch-2b to 1b45449e18d0
+      create  push-yqosqzytrlsw  (none) -> fa16a14170fb
+      create  branch-1           (none) -> 7decc7932d9c
+      create  branch-2a          (none) -> 1b45449e18d0
+      create  branch-2b          (none) -> 1b45449e18d0
     "###);
 }
 
@@ -601,7 +630,7 @@ fn test_git_push_existing_long_branch() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Add branch push-19b790168e73f7a73a98deae21e807c0 to fa16a14170fb
+      create  push-19b790168e73f7a73a98deae21e807c0  (none) -> fa16a14170fb
     "###);
 }
 
@@ -717,7 +746,7 @@ fn test_git_push_deleted() {
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Delete branch branch1 from 45a3aa29e907
+      delete  branch1  45a3aa29e907 -> (none)
     "###);
     let stdout = test_env.jj_cmd_success(&workspace_root, &["log", "-rall()"]);
     insta::assert_snapshot!(stdout, @r###"
@@ -795,7 +824,7 @@ fn test_git_push_conflicting_branches() {
     Branch branch2 is conflicted
     Hint: Run `jj branch list` to inspect, and use `jj branch set` to fix it up.
     Branch changes to push to origin:
-      Move branch branch1 from 45a3aa29e907 to fd1d63e031ea
+      update  branch1  45a3aa29e907 -> fd1d63e031ea
     "###);
 
     // --revisions shouldn't be blocked by conflicting branch
@@ -806,7 +835,7 @@ fn test_git_push_conflicting_branches() {
     Branch branch2 is conflicted
     Hint: Run `jj branch list` to inspect, and use `jj branch set` to fix it up.
     Branch changes to push to origin:
-      Move branch branch1 from fd1d63e031ea to 8263cf992d33
+      update  branch1  fd1d63e031ea -> 8263cf992d33
     "###);
 }
 
@@ -853,7 +882,7 @@ fn test_git_push_tracked_vs_all() {
         test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--tracked", "--dry-run"]);
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to origin:
-      Delete branch branch2 from 8476341eb395
+      delete  branch2  8476341eb395 -> (none)
     Dry-run requested, not pushing.
     "###);
 
@@ -894,7 +923,7 @@ fn test_git_push_tracked_vs_all() {
     Non-tracking remote branch branch1@origin exists
     Hint: Run `jj branch track branch1@origin` to import the remote branch.
     Branch changes to push to origin:
-      Add branch branch3 to 998d6a7853d9
+      create  branch3  (none) -> 998d6a7853d9
     "###);
 }
 
@@ -945,8 +974,122 @@ fn test_git_push_to_remote_named_git() {
         test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--all", "--remote=git"]);
     insta::assert_snapshot!(stderr, @r###"
     Branch changes to push to git:
-      Add branch branch1 to 45a3aa29e907
-      Add branch branch2 to 8476341eb395
+      create  branch1  (none) -> 45a3aa29e907
+      create  branch2  (none) -> 8476341eb395
     Error: Git remote named 'git' is reserved for local Git repository
     "###);
 }
+
+#[test]
+fn test_git_push_multiple_remotes() {
+    let (test_env, workspace_root) = set_up();
+    let backup_path = test_env.env_root().join("backup");
+    git2::Repository::init_bare(&backup_path).unwrap();
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &[
+            "git",
+            "remote",
+            "add",
+            "backup",
+            backup_path.to_str().unwrap(),
+        ],
+    );
+    test_env.jj_cmd_ok(&workspace_root, &["new", "branch1", "-m", "modified"]);
+    test_env.jj_cmd_ok(&workspace_root, &["branch", "set", "branch1"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            "--remote=origin",
+            "--remote=backup",
+            "--branch=branch1",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Branch changes to push to origin:
+      update  branch1  45a3aa29e907 -> 00c114e55620
+    Branch changes to push to backup:
+      create  branch1  (none) -> 00c114e55620
+    Pushed to origin.
+    Pushed to backup.
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["branch", "list", "--all"]);
+    insta::assert_snapshot!(stdout, @r###"
+    branch1: yostqsxw 00c114e5 (empty) modified
+      @backup: yostqsxw 00c114e5 (empty) modified
+      @origin: yostqsxw 00c114e5 (empty) modified
+    branch2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &[
+            "op",
+            "log",
+            "--no-graph",
+            "--limit=1",
+            "-T",
+            r#"description ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    push branch branch1 to git remotes origin, backup
+    "###);
+
+    // Default remotes can be configured
+    test_env.add_config(r#"git.push = ["origin", "backup"]"#);
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--branch=branch2"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Branch branch2@origin already matches branch2
+    Nothing changed on origin.
+    Branch changes to push to backup:
+      create  branch2  (none) -> 8476341eb395
+    Pushed to backup.
+    "###);
+
+    // The pushes made before a failure are recorded
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["git", "remote", "add", "broken", "nonexistent"],
+    );
+    test_env.jj_cmd_ok(&workspace_root, &["branch", "create", "branch3"]);
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            "--remote=origin",
+            "--remote=broken",
+            "--remote=backup",
+            "--branch=branch3",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Branch changes to push to origin:
+      create  branch3  (none) -> 00c114e55620
+    Branch changes to push to broken:
+      create  branch3  (none) -> 00c114e55620
+    Branch changes to push to backup:
+      create  branch3  (none) -> 00c114e55620
+    Pushed to origin.
+    Error: unsupported URL protocol; class=Net (12)
+    Hint: Failed to push to broken. Branches were not pushed to: broken, backup
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["branch", "list", "--all"]);
+    insta::assert_snapshot!(stdout, @r###"
+    branch1: yostqsxw 00c114e5 (empty) modified
+      @backup: yostqsxw 00c114e5 (empty) modified
+      @origin: yostqsxw 00c114e5 (empty) modified
+    branch2: rlzusymt 8476341e (empty) description 2
+      @backup: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    branch3: yostqsxw 00c114e5 (empty) modified
+      @origin: yostqsxw 00c114e5 (empty) modified
+    "###);
+}
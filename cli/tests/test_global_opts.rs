@@ -14,6 +14,9 @@
 
 use std::ffi::OsString;
 
+use jj_lib::lock::FileLock;
+use regex::Regex;
+
 use crate::common::{get_stderr_string, strip_last_line, TestEnvironment};
 
 #[test]
@@ -294,6 +297,52 @@ fn test_broken_repo_structure() {
     "###);
 }
 
+#[test]
+fn test_wait_for_lock() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let lock_path = repo_path
+        .join(".jj")
+        .join("working_copy")
+        .join("working_copy.lock");
+
+    let _lock = FileLock::lock(lock_path).unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["status", "--wait-for-lock=0"]);
+    let stderr = Regex::new(r"held by process \d+@.*")
+        .unwrap()
+        .replace(&stderr, "held by process <pid>@<host>");
+    insta::assert_snapshot!(stderr.replace('\\', "/"), @r###"
+    Error: Failed to access working copy state
+    Caused by:
+    1: Failed to lock working copy
+    2: Timed out waiting for lock $TEST_ENV/repo/.jj/working_copy/working_copy.lock held by process <pid>@<host>
+    "###);
+
+    // The timeout can also be configured
+    let stderr =
+        test_env.jj_cmd_failure(&repo_path, &["status", "--config-toml=core.lock-timeout=0"]);
+    assert!(stderr.contains("Timed out waiting for lock"), "{stderr}");
+    drop(_lock);
+
+    // The operation isn't published if the operation heads can't be locked
+    let lock_path = repo_path
+        .join(".jj")
+        .join("repo")
+        .join("op_heads")
+        .join("heads")
+        .join("lock");
+    let _lock = FileLock::lock(lock_path).unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["describe", "-m", "x", "--wait-for-lock=0"]);
+    let stderr = Regex::new(r"held by process \d+@.*")
+        .unwrap()
+        .replace(&stderr, "held by process <pid>@<host>");
+    insta::assert_snapshot!(stderr.replace('\\', "/"), @r###"
+    Error: Failed to lock operation heads store
+    Caused by: Timed out waiting for lock $TEST_ENV/repo/.jj/repo/op_heads/heads/lock held by process <pid>@<host>
+    "###);
+}
+
 #[test]
 fn test_color_config() {
     let mut test_env = TestEnvironment::default();
@@ -419,7 +468,7 @@ fn test_color_ui_messages() {
     );
     insta::assert_snapshot!(stdout, @r###"
     [38;5;4m8bb159bc30a9859930e567eb9238a7c43ee6744d[39m
-    [38;5;1m<[1mError: [0m[38;5;1mNo commit available>[39m  [38;5;8m(elided revisions)[39m
+    [38;5;1m<[1mError: [0m[38;5;1mFailed to evaluate commit_id: No commit available>[39m  [38;5;8m(elided revisions)[39m
     [38;5;4m0000000000000000000000000000000000000000[39m
     "###);
 }
@@ -460,6 +509,31 @@ fn test_invalid_config() {
     "###);
 }
 
+#[test]
+fn test_customized_config_help() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"messages.config-help = "Run `our-jj-doctor` to fix your config.""#);
+
+    let stderr = test_env.jj_cmd_failure(test_env.env_root(), &["config", "get", "nonexistent"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: configuration property "nonexistent" not found
+    Run `our-jj-doctor` to fix your config.
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(
+        test_env.env_root(),
+        &[
+            "config",
+            "get",
+            "nonexistent",
+            "--config-toml=ui.hints.disable = ['config-help']",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: configuration property "nonexistent" not found
+    "###);
+}
+
 #[test]
 fn test_no_user_configured() {
     // Test that the user is reminded if they haven't configured their name or email
@@ -516,6 +590,9 @@ fn test_help() {
       -R, --repository <REPOSITORY>      Path to repository to operate on
           --ignore-working-copy          Don't snapshot the working copy, and don't update it
           --at-operation <AT_OPERATION>  Operation to load the repo at [default: @] [aliases: at-op]
+          --wait-for-lock <SECONDS>      Give up waiting for locks held by other processes after this
+                                         many seconds
+          --template-strict              Abort if a template property fails to evaluate
           --debug                        Enable debug logging
           --color <WHEN>                 When to colorize output (always, never, auto)
           --no-pager                     Disable the pager
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::common::{get_stdout_string, TestEnvironment};
+use crate::common::{get_stderr_string, get_stdout_string, TestEnvironment};
 
 #[test]
 fn test_log_with_empty_revision() {
@@ -41,6 +41,7 @@ fn test_log_with_no_template() {
     For more information, try '--help'.
     Hint: The following template aliases are defined:
     - builtin_change_id_with_hidden_and_divergent_info
+    - builtin_changes
     - builtin_log_comfortable
     - builtin_log_compact
     - builtin_log_detailed
@@ -415,6 +416,11 @@ fn test_log_shortest_accessors() {
     qpv[untsmwlqt] ba1[a30916d29]
     zzz[zzzzzzzzz] 00[0000000000]
     "###);
+    // The shorter prefixes are also accepted as revisions
+    insta::assert_snapshot!(render("f | z", r#"commit_id.short() ++ "\n""#), @r###"
+    f77fb1909080
+    38622e54e2e5
+    "###);
 
     // Can disable short prefixes by setting to empty string
     test_env.add_config(r#"revsets.short-prefixes = """#);
@@ -674,8 +680,8 @@ fn test_log_short_shortest_length_parameter() {
     "###);
     insta::assert_snapshot!(
         render(r#"commit_id.short(-100) ++ "|" ++ commit_id.shortest(-100)"#), @r###"
-    @  <Error: out of range integral type conversion attempted>|<Error: out of range integral type conversion attempted>
-    ◉  <Error: out of range integral type conversion attempted>|<Error: out of range integral type conversion attempted>
+    @  <Error: Failed to evaluate commit_id.short: out of range integral type conversion attempted>|<Error: Failed to evaluate commit_id.shortest: out of range integral type conversion attempted>
+    ◉  <Error: Failed to evaluate commit_id.short: out of range integral type conversion attempted>|<Error: Failed to evaluate commit_id.shortest: out of range integral type conversion attempted>
     "###);
     insta::assert_snapshot!(
         render(r#"commit_id.short(100) ++ "|" ++ commit_id.shortest(100)"#), @r###"
@@ -1478,3 +1484,80 @@ fn test_log_with_custom_symbols() {
     ^
     "###);
 }
+
+#[test]
+fn test_log_template_errors() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second"]);
+    let template = r#"separate(" ", description.first_line(), commit_id.short(-1)) ++ "\n""#;
+
+    // The failed property is rendered as a placeholder
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  second <Error: Failed to evaluate commit_id.short: out of range integral type conversion attempted>
+    ◉  first <Error: Failed to evaluate commit_id.short: out of range integral type conversion attempted>
+    ◉  <Error: Failed to evaluate commit_id.short: out of range integral type conversion attempted>
+    "###);
+
+    // --template-strict aborts on the first error
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", template, "--template-strict"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to evaluate commit_id.short: out of range integral type conversion attempted
+    "###);
+
+    // Unreadable commit is rendered as a placeholder
+    let commit_id = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r=description(first)", "-T=commit_id"],
+    );
+    let object_path = repo_path
+        .join(".jj")
+        .join("repo")
+        .join("store")
+        .join("git")
+        .join("objects")
+        .join(&commit_id[..2])
+        .join(&commit_id[2..]);
+    std::fs::remove_file(object_path).unwrap();
+    let template = r#"description.first_line() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-T", template, "--ignore-working-copy"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  second
+    ◌  <Error: Object 69542c1984c1f9d91f7c6c9c9e6941782c944bd9 of type commit not found: An object with id 69542c1984c1f9d91f7c6c9c9e6941782c944bd9 could not be found>
+    ◉
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-T", template, "--ignore-working-copy", "--no-graph"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    <Error: Object 69542c1984c1f9d91f7c6c9c9e6941782c944bd9 of type commit not found: An object with id 69542c1984c1f9d91f7c6c9c9e6941782c944bd9 could not be found>
+
+    "###);
+    let assert = test_env
+        .jj_cmd(
+            &repo_path,
+            &[
+                "log",
+                "-T",
+                template,
+                "--ignore-working-copy",
+                "--template-strict",
+            ],
+        )
+        .assert()
+        .code(255);
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    Internal error: Unexpected error from backend
+    Caused by:
+    1: Object 69542c1984c1f9d91f7c6c9c9e6941782c944bd9 of type commit not found
+    2: An object with id 69542c1984c1f9d91f7c6c9c9e6941782c944bd9 could not be found
+    "###);
+}
@@ -137,6 +137,7 @@ fn test_op_log_with_no_template() {
     For more information, try '--help'.
     Hint: The following template aliases are defined:
     - builtin_change_id_with_hidden_and_divergent_info
+    - builtin_changes
     - builtin_log_comfortable
     - builtin_log_compact
     - builtin_log_detailed
@@ -179,6 +180,103 @@ fn test_op_log_no_graph() {
     "###);
 }
 
+#[test]
+fn test_op_log_classification() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "it's a file"]);
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+
+    let template = r#"
+    separate(" ",
+      if(is_snapshot, "snapshot"),
+      if(is_undo, "undo"),
+      "[" ++ command.join("|") ++ "]",
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "--no-graph",
+            "--ignore-working-copy",
+            "-T",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    undo [jj|undo]
+    [jj|describe|-m|it's a file]
+    snapshot [jj|describe|-m|it's a file]
+    []
+    []
+    []
+    "###);
+}
+
+#[test]
+fn test_op_log_no_snapshot() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file1"), "").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+    std::fs::write(repo_path.join("file2"), "").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "two files"]);
+
+    let template = r#"description ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--ignore-working-copy", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  describe commit a027c12649835620b46e349141a602d7d84eb571
+    ◉  snapshot working copy
+    ◉  snapshot working copy
+    ◉  add workspace 'default'
+    ◉  initialize repo
+    ◉
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "--ignore-working-copy",
+            "--no-snapshot",
+            "-T",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  describe commit a027c12649835620b46e349141a602d7d84eb571
+    ◉  add workspace 'default'
+    ◉  initialize repo
+    ◉
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "--ignore-working-copy",
+            "--no-snapshot",
+            "--no-graph",
+            "--limit=2",
+            "-T",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    describe commit a027c12649835620b46e349141a602d7d84eb571
+    add workspace 'default'
+    "###);
+}
+
 #[test]
 fn test_op_log_no_graph_null_terminated() {
     let test_env = TestEnvironment::default();
@@ -220,11 +318,18 @@ fn test_op_log_template() {
     ◉  00000 false @ 1970-01-01 00:00:00.000 +00:00 1970-01-01 00:00:00.000 +00:00 less than a microsecond
     "###);
 
+    insta::assert_snapshot!(
+        render(r#"separate(" ", id.short(5), "parents:", parents.map(|op| op.id().short(5))) ++ "\n""#), @r###"
+    @  b5141 parents: 9a7d8
+    ◉  9a7d8 parents: 00000
+    ◉  00000 parents:
+    "###);
+
     // Negative length shouldn't cause panic.
     insta::assert_snapshot!(render(r#"id.short(-1) ++ "|""#), @r###"
-    @  <Error: out of range integral type conversion attempted>|
-    ◉  <Error: out of range integral type conversion attempted>|
-    ◉  <Error: out of range integral type conversion attempted>|
+    @  <Error: Failed to evaluate id.short: out of range integral type conversion attempted>|
+    ◉  <Error: Failed to evaluate id.short: out of range integral type conversion attempted>|
+    ◉  <Error: Failed to evaluate id.short: out of range integral type conversion attempted>|
     "###);
 
     // Test the default template, i.e. with relative start time and duration. We
@@ -245,6 +350,94 @@ fn test_op_log_template() {
     │  initialize repo
     ◉  000000000000 root()
     "###);
+
+    // Operations created by `jj init` aren't tagged with the command line.
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "description 0"]);
+    insta::assert_snapshot!(
+        render(r#"tags.get("args") ++ "|" ++ tags.keys().join(",") ++ "\n""#), @r###"
+    @  jj describe -m 'description 0'|args
+    ◉  |
+    ◉  |
+    ◉  |
+    "###);
+}
+
+#[test]
+fn test_op_log_changed_refs() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "foo"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "bar"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "delete", "foo"]);
+
+    let template = r#"
+        separate(" ",
+          coalesce(command.join(" "), description) ++ ":",
+          "branches=" ++ changed_branches.join(","),
+          "refs=" ++ changed_refs_count,
+        ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    jj branch delete foo: branches=foo refs=1
+    jj branch create bar: branches=bar refs=1
+    jj branch create foo: branches=foo refs=1
+    add workspace 'default': branches= refs=0
+    initialize repo: branches= refs=0
+    : branches= refs=0
+    "###);
+}
+
+#[test]
+fn test_op_description_template() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"templates.op_description = 'description ++ " (" ++ workspace ++ ": " ++ args.join(" ") ++ ")"'"#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "first"]);
+    // An empty rendering keeps the built-in description
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "new",
+            "-m",
+            "second",
+            "--config-toml=templates.op_description=''",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "--no-graph",
+            "-l2",
+            "-T",
+            r#"description ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    new empty commit
+    new empty commit (default: jj new -m first)
+    "###);
+
+    // Template errors are reported before the command runs
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["new", "--config-toml=templates.op_description='unknown'"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:1
+      |
+    1 | unknown
+      | ^-----^
+      |
+      = Keyword "unknown" doesn't exist
+    "###);
 }
 
 #[test]
@@ -453,6 +646,76 @@ fn test_op_abandon_ancestors() {
     "###);
 }
 
+#[test]
+fn test_op_log_deleted_and_reattach() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "one"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "two"]);
+
+    // Nothing has been deleted yet
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--deleted"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // The original operations become unreachable when abandoned
+    test_env.jj_cmd_ok(&repo_path, &["op", "abandon", "@-"]);
+    let template = r#"tags ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--deleted", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  args: jj new -m two
+    ◉  args: jj new -m one
+    │
+    ~
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "--deleted",
+            "--no-graph",
+            "-T",
+            r#"id.short() ++ "\n""#,
+        ],
+    );
+    let deleted_op_ids = stdout.lines().collect_vec();
+    assert_eq!(deleted_op_ids.len(), 2);
+
+    // Reachable operations can't be reattached
+    let current_op_id = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--no-graph", "-l1", "-T", "id.short()"],
+    );
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "reattach", "@"]);
+    assert_eq!(
+        stderr,
+        format!(
+            "Warning: Operation {current_op_id} is already reachable from the current \
+             operation\nNothing changed.\n"
+        )
+    );
+
+    // Reattaching the head brings back its ancestors as well
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "reattach", deleted_op_ids[0]]);
+    insta::assert_snapshot!(stderr, @r###"
+    Reattached 1 operations. They will be merged with the current operation by the next command.
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--deleted"]);
+    insta::assert_snapshot!(stdout, @"");
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["log", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  two
+    ◉  one
+    ◉
+    ◉
+    "###);
+    insta::assert_snapshot!(stderr, @r###"
+    Concurrent modification detected, resolving automatically.
+    "###);
+}
+
 #[test]
 fn test_op_abandon_without_updating_working_copy() {
     let test_env = TestEnvironment::default();
@@ -14,7 +14,7 @@
 
 use std::path::Path;
 
-use crate::common::TestEnvironment;
+use crate::common::{get_stderr_string, TestEnvironment};
 
 fn create_commit(test_env: &TestEnvironment, repo_path: &Path, name: &str, parents: &[&str]) {
     if parents.is_empty() {
@@ -54,7 +54,7 @@ fn test_rebase_invalid() {
     insta::assert_snapshot!(stderr, @r###"
     error: the argument '--revision <REVISION>' cannot be used with '--source <SOURCE>'
 
-    Usage: jj rebase --destination <DESTINATION> --revision <REVISION>
+    Usage: jj rebase --revision <REVISION> --destination <DESTINATION>
 
     For more information, try '--help'.
     "###);
@@ -65,7 +65,7 @@ fn test_rebase_invalid() {
     insta::assert_snapshot!(stderr, @r###"
     error: the argument '--branch <BRANCH>' cannot be used with '--source <SOURCE>'
 
-    Usage: jj rebase --destination <DESTINATION> --branch <BRANCH>
+    Usage: jj rebase --branch <BRANCH> --destination <DESTINATION>
 
     For more information, try '--help'.
     "###);
@@ -78,7 +78,7 @@ fn test_rebase_invalid() {
     insta::assert_snapshot!(stderr, @r###"
     error: the argument '--revision <REVISION>' cannot be used with '--skip-empty'
 
-    Usage: jj rebase --destination <DESTINATION> --revision <REVISION>
+    Usage: jj rebase --revision <REVISION> --destination <DESTINATION>
 
     For more information, try '--help'.
     "###);
@@ -1102,3 +1102,237 @@ fn test_rebase_skip_empty() {
     ◉
     "###);
 }
+
+#[test]
+fn test_rebase_empty_mode() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "a", "-m", "will become empty"]);
+    test_env.jj_cmd_ok(&repo_path, &["restore", "--from=b"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "already empty"]);
+    let setup_opid = test_env.current_operation_id(&repo_path);
+
+    // Without a terminal, the rebase is refused
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["rebase", "-d=b", "--empty=ask"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Rebasing would leave 1 commits empty
+    Hint: Use `--empty=keep` or `--empty=drop` to choose what to do with them.
+    "###);
+
+    // The commits are kept if the user declines
+    let assert = test_env
+        .jj_cmd_stdin(&repo_path, &["rebase", "-d=b", "--empty=ask"], "n\n")
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    These commits became empty:
+      royxmykx c5319668 (empty) will become empty
+    Rebased 2 commits
+    Working copy now at: vruxwmqv f90afa0c (empty) already empty
+    Parent commit      : royxmykx c5319668 (empty) will become empty
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]), @r###"
+    @  already empty
+    ◉  will become empty
+    ◉  b
+    ◉  a
+    ◉
+    "###);
+
+    // The newly empty commit is abandoned if the user agrees
+    test_env.jj_cmd_ok(&repo_path, &["op", "restore", &setup_opid]);
+    let assert = test_env
+        .jj_cmd_stdin(&repo_path, &["rebase", "-d=b", "--empty=ask"], "y\n")
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    These commits became empty:
+      royxmykx ab85bc76 (empty) will become empty
+    Abandoned 1 commits
+    Rebased 2 commits
+    Working copy now at: vruxwmqv 7c15362e (empty) already empty
+    Parent commit      : zsuskuln 1394f625 b | b
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]), @r###"
+    @  already empty
+    ◉  b
+    ◉  a
+    ◉
+    "###);
+
+    // The default can be set in the config
+    test_env.jj_cmd_ok(&repo_path, &["op", "restore", &setup_opid]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["rebase", "-d=b", "--config-toml=rebase.empty='drop'"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 2 commits
+    Working copy now at: vruxwmqv 67d7cfad (empty) already empty
+    Parent commit      : zsuskuln 1394f625 b | b
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]), @r###"
+    @  already empty
+    ◉  b
+    ◉  a
+    ◉
+    "###);
+}
+
+#[test]
+fn test_rebase_stop_on_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    create_commit(&test_env, &repo_path, "d", &["c"]);
+    create_commit(&test_env, &repo_path, "x", &["a"]);
+    std::fs::write(repo_path.join("file"), "x\n").unwrap();
+    // Test the setup
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  x
+    │ ◉  d
+    │ ◉  c
+    │ ◉  b
+    ├─╯
+    ◉  a
+    ◉
+    "###);
+
+    let get_change_hash = |rev: &str| {
+        test_env.jj_cmd_success(
+            &repo_path,
+            &["log", "--no-graph", "-r", rev, "-T", "change_id.short()"],
+        )
+    };
+    let expected_hint = format!(
+        "  jj rebase -s {} -d {}\n",
+        get_change_hash("b"),
+        get_change_hash("x")
+    );
+
+    // b would become conflicted, so it's left in place along with its
+    // descendants instead of being rewritten
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["rebase", "-s", "b", "-d", "x", "--stop-on-conflict"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    let (report, hint) = stderr.split_once("To rebase them anyway, run:\n").unwrap();
+    insta::assert_snapshot!(report, @r###"
+    Rebased 0 commits
+    Rebasing would create conflicts in these commits:
+      b
+    Left these commits and their descendants in place:
+      b
+    "###);
+    assert_eq!(hint, expected_hint);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  x
+    │ ◉  d
+    │ ◉  c
+    │ ◉  b
+    ├─╯
+    ◉  a
+    ◉
+    "###);
+    // No commit was made divergent
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-r", "all()", "-T", r#"if(divergent, "divergent")"#],
+    );
+    assert!(!stdout.contains("divergent"));
+
+    // Without the flag, b and its descendants are rebased
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["rebase", "-s", "b", "-d", "x"]);
+    assert!(stderr.starts_with("Rebased 3 commits\n"));
+}
+
+#[test]
+fn test_rebase_dry_run() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    create_commit(&test_env, &repo_path, "d", &["c"]);
+    create_commit(&test_env, &repo_path, "x", &["a"]);
+    std::fs::write(repo_path.join("file"), "x\n").unwrap();
+    let setup_log = get_log_output(&test_env, &repo_path);
+
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["rebase", "-s", "b", "-d", "x", "--dry-run"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Would create 3 new commits
+    These commits would become conflicted:
+      d
+      c
+      b
+    Dry run, nothing was changed.
+    "###);
+    assert_eq!(get_log_output(&test_env, &repo_path), setup_log);
+
+    // Commits that would become empty are reported too
+    test_env.jj_cmd_ok(&repo_path, &["new", "a", "-m", "y"]);
+    std::fs::write(repo_path.join("file"), "x\n").unwrap();
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["rebase", "-r", "@", "-d", "x", "--dry-run"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Would create 1 new commits
+    These commits would become empty:
+      y
+    Dry run, nothing was changed.
+    "###);
+}
+
+#[test]
+fn test_rebase_onto_workspace() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["workspace", "add", "--name", "secondary", "../secondary"],
+    );
+    test_env.jj_cmd_ok(&secondary_path, &["new", "a", "-m", "x"]);
+    std::fs::write(secondary_path.join("x"), "x\n").unwrap();
+    test_env.jj_cmd_ok(&secondary_path, &["branch", "create", "x"]);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["rebase", "--onto-workspace", "default"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Workspace default is the current workspace
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["rebase", "--onto-workspace", "unknown"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No such workspace: unknown
+    "###);
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["rebase", "-s", "b", "--onto-workspace", "secondary"],
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  b
+    ◉  x
+    ◉  a
+    ◉
+    "###);
+}
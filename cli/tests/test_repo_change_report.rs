@@ -96,6 +96,52 @@ fn test_report_conflicts() {
     "###);
 }
 
+#[test]
+fn test_report_conflicts_customized_hint() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"messages.resolve-conflicts = "See https://wiki.example.com/conflicts for help.""#,
+    );
+
+    std::fs::write(repo_path.join("file"), "A\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=A"]);
+    std::fs::write(repo_path.join("file"), "B\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=B"]);
+    std::fs::write(repo_path.join("file"), "C\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=C"]);
+
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["rebase", "-s=description(B)", "-d=root()"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 3 commits
+    New conflicts appeared in these commits:
+      kkmpptxz 9baab11e (conflict) C
+      rlvkpnrz de73196a (conflict) B
+    To resolve the conflicts, start by updating to the first one:
+      jj new rlvkpnrzqnoo
+    See https://wiki.example.com/conflicts for help.
+    Working copy now at: zsuskuln 7dc9bf15 (conflict) (empty) (no description set)
+    Parent commit      : kkmpptxz 9baab11e (conflict) C
+    Added 0 files, modified 1 files, removed 0 files
+    "###);
+
+    // The hint can also be turned off
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "rebase",
+            "-r=description(B)",
+            "-d=description(A)",
+            "--config-toml=ui.hints.disable = ['resolve-conflicts']",
+        ],
+    );
+    assert!(stderr.contains("There are still unresolved conflicts in rebased descendants."));
+    assert!(!stderr.contains("To resolve the conflicts"));
+}
+
 #[test]
 fn test_report_conflicts_with_divergent_commits() {
     let test_env = TestEnvironment::default();
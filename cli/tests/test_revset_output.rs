@@ -436,3 +436,50 @@ fn test_bad_alias_decl() {
       = Redefinition of function parameter
     "###);
 }
+
+#[test]
+fn test_mailmap() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "current"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "new",
+            "-m",
+            "old",
+            "--config-toml=user.email='old.user@example.com'",
+        ],
+    );
+    let template = r#"description.first_line() ++ " " ++ author.email() ++ "\n""#;
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r", "mine()", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  current test.user@example.com
+    │
+    ~
+    "###);
+
+    std::fs::write(
+        repo_path.join(".mailmap"),
+        "Test Person <test.user@example.com> <old.user@example.com>\n",
+    )
+    .unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r", "mine()", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  old old.user@example.com
+    ◉  current test.user@example.com
+    │
+    ~
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-r", r#"by(exact:"Test Person")"#, "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  old old.user@example.com
+    │
+    ~
+    "###);
+}
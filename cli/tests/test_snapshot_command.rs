@@ -0,0 +1,102 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_snapshot_auto_disabled() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+        snapshot.auto = false
+        templates.commit_summary = "description.first_line()"
+        "#,
+    );
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=wc"]);
+
+    // Changes aren't recorded implicitly
+    std::fs::write(repo_path.join("file"), "contents").unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["snapshot"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Working copy now at: wc
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["snapshot"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    // Unrecorded changes aren't overwritten by checking out another tree
+    std::fs::write(repo_path.join("file"), "modified").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["new", "root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The working copy has unrecorded changes, which would be overwritten.
+    Hint: `snapshot.auto` is disabled. Run `jj snapshot` to record the changes first.
+    "###);
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(),
+        "modified"
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r###"
+    wc
+    "###);
+
+    // Checking out the same tree leaves the files alone
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(),
+        "modified"
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["snapshot"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    assert!(!repo_path.join("file").exists());
+}
+
+#[test]
+fn test_snapshot_auto_enabled() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    // Other commands record changes implicitly
+    std::fs::write(repo_path.join("file"), "contents").unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file
+    "###);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["snapshot"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["snapshot", "--ignore-working-copy"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: This command must be able to update the working copy.
+    Hint: Don't use --ignore-working-copy.
+    "###);
+}
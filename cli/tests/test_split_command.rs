@@ -231,6 +231,187 @@ JJ: Lines starting with "JJ: " (like this one) will be removed.
     "###);
 }
 
+#[test]
+fn test_split_parallel() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "bar\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "test"]);
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        edit_script,
+        [
+            "dump editor1",
+            "write\npart 1",
+            "next invocation\n",
+            "dump editor2",
+            "write\npart 2",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["split", "--parallel", "file1"]);
+
+    assert_eq!(
+        std::fs::read_to_string(test_env.env_root().join("editor1")).unwrap(),
+        r#"JJ: Enter commit description for part 1 of 2.
+test
+
+JJ: This commit contains the following changes:
+JJ:     A file1
+
+JJ: Lines starting with "JJ: " (like this one) will be removed.
+"#
+    );
+    assert_eq!(
+        std::fs::read_to_string(test_env.env_root().join("editor2")).unwrap(),
+        r#"JJ: Enter commit description for part 2 of 2.
+test
+
+JJ: This commit contains the following changes:
+JJ:     A file2
+
+JJ: Lines starting with "JJ: " (like this one) will be removed.
+"#
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r###"
+    @  kkmpptxzrspx false part 2
+    │ ◉  qpvuntsmwlqt false part 1
+    ├─╯
+    ◉  zzzzzzzzzzzz true
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "-s", "-r", "description(1)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file1
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "-s", "-r", "description(2)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file2
+    "###);
+}
+
+#[test]
+fn test_split_parallel_with_children() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "bar\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "test"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "-m", "child"]);
+    std::fs::write(workspace_path.join("file3"), "baz\n").unwrap();
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        edit_script,
+        ["write\npart 1", "next invocation\n", "write\npart 2"].join("\0"),
+    )
+    .unwrap();
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["split", "--parallel", "-r", "@-", "file1"],
+    );
+
+    // The child is rebased onto both parts, and keeps its contents
+    let template = r#"separate(" ", empty, description)"#;
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @    false child
+    ├─╮
+    │ ◉  false part 2
+    ◉ │  false part 1
+    ├─╯
+    ◉  true
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file3
+    "###);
+}
+
+#[test]
+fn test_split_parallel_by_groups() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "bar\n").unwrap();
+    std::fs::write(workspace_path.join("file3"), "baz\n").unwrap();
+    std::fs::write(workspace_path.join("file4"), "qux\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "test"]);
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        edit_script,
+        [
+            "write\npart 1",
+            "next invocation\n",
+            "write\npart 2",
+            "next invocation\n",
+            "write\npart 3",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    // Paths matching several groups go to the first one
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &[
+            "split",
+            "--parallel",
+            "--group=file2",
+            "--group=glob:file[12]",
+        ],
+    );
+
+    let template = r#"separate(" ", empty, description)"#;
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  false part 3
+    │ ◉  false part 2
+    ├─╯
+    │ ◉  false part 1
+    ├─╯
+    ◉  true
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "-s", "-r", "description(1)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file2
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "-s", "-r", "description(2)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file1
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "-s", "-r", "description(3)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file3
+    A file4
+    "###);
+}
+
+#[test]
+fn test_split_parallel_dependent_changes() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    std::fs::write(workspace_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["new"]);
+    std::fs::write(workspace_path.join("file"), "b\n").unwrap();
+
+    // The remaining change from "c" to "b" can't be applied onto "a"
+    let edit_script = test_env.set_up_fake_diff_editor();
+    std::fs::write(edit_script, "write file\nc\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&workspace_path, &["split", "--parallel"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The changes can't be split into parallel commits because they depend on each other
+    Hint: These files would have conflicts: file. Select whole files for each part, or split without --parallel.
+    "###);
+}
+
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
     let template = r#"separate(" ", change_id.short(), empty, description)"#;
     test_env.jj_cmd_success(cwd, &["log", "-T", template])
@@ -974,3 +974,44 @@ fn get_description(test_env: &TestEnvironment, repo_path: &Path, rev: &str) -> S
         &["log", "--no-graph", "-T", "description", "-r", rev],
     )
 }
+
+#[test]
+fn test_squash_dry_run() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "a"]);
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "b"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "c"]);
+    std::fs::write(repo_path.join("file"), "c\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "d"]);
+    let log_template = r#"description.first_line() ++ " " ++ if(conflict, "conflict")"#;
+    let setup_log = test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "squash",
+            "--from",
+            "description(c)",
+            "--into",
+            "description(a)",
+            "-m",
+            "a",
+            "--dry-run",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Would create 3 new commits
+    These commits would become conflicted:
+      a
+    Dry run, nothing was changed.
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]);
+    assert_eq!(stdout, setup_log);
+}
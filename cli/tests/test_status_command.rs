@@ -62,3 +62,68 @@ fn test_status_ignored_gitignore() {
     Parent commit: zzzzzzzz 00000000 (empty) (no description set)
     "###);
 }
+
+#[test]
+fn test_status_template() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("a"), "a\n").unwrap();
+    std::fs::write(repo_path.join("b"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("a"), "a2\n").unwrap();
+    std::fs::remove_file(repo_path.join("b")).unwrap();
+    std::fs::write(repo_path.join("c"), "c\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status", "-T", r#"changes ++ "\n""#]);
+    insta::assert_snapshot!(stdout, @r###"
+    M a D b A c
+    "###);
+
+    let template = r#"
+    changes.map(|c| c.path() ++ ": " ++ c.status() ++ if(c.conflict(), " (conflict)")).join("\n")
+    ++ "\nconflicts: " ++ conflicted_files.len()
+    ++ "\nparents: " ++ parents.len() ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    a: modified
+    b: removed
+    c: added
+    conflicts: 0
+    parents: 1
+    "###);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_status_symlinks_disabled() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::os::unix::fs::symlink("target", repo_path.join("link")).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=link"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    assert!(repo_path.join("link").symlink_metadata().is_err());
+
+    // With `core.symlinks = false`, the symlink is checked out as a regular file
+    // containing the target, and is snapshotted back as a symlink
+    test_env.add_config("core.symlinks = false");
+    test_env.jj_cmd_ok(&repo_path, &["edit", "description(link)"]);
+    let metadata = repo_path.join("link").symlink_metadata().unwrap();
+    assert!(metadata.is_file());
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("link")).unwrap(),
+        "target"
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--types"]);
+    insta::assert_snapshot!(stdout, @r###"
+    -L link
+    "###);
+
+    // Status doesn't warn about the regular file since it was configured
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["status"]);
+    insta::assert_snapshot!(stderr, @"");
+}
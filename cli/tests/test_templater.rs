@@ -29,7 +29,7 @@ fn test_templater_parse_error() {
     1 | description ()
       |             ^---
       |
-      = expected <EOI>, `++`, `||`, or `&&`
+      = expected <EOI>, `++`, `||`, `&&`, `==`, `!=`, `>=`, `>`, `<=`, `<`, `+`, `-`, `*`, `/`, or `%`
     "###);
 
     // Typo
@@ -49,7 +49,7 @@ fn test_templater_parse_error() {
       | ^-------^
       |
       = Keyword "conflicts" doesn't exist
-    Hint: Did you mean "conflict", "conflicting"?
+    Hint: Did you mean "conflict", "conflicted_files", "conflicting"?
     "###);
     insta::assert_snapshot!(render_err(r#"commit_id.shorter()"#), @r###"
     Error: Failed to parse template:  --> 1:11
@@ -103,7 +103,7 @@ fn test_templater_parse_error() {
       | ^-----^
       |
       = Keyword "builtin" doesn't exist
-    Hint: Did you mean "builtin_change_id_with_hidden_and_divergent_info", "builtin_log_comfortable", "builtin_log_compact", "builtin_log_detailed", "builtin_log_oneline", "builtin_op_log_comfortable", "builtin_op_log_compact"?
+    Hint: Did you mean "builtin_change_id_with_hidden_and_divergent_info", "builtin_changes", "builtin_log_comfortable", "builtin_log_compact", "builtin_log_detailed", "builtin_log_oneline", "builtin_op_log_comfortable", "builtin_op_log_compact"?
     "###);
 }
 
@@ -93,6 +93,113 @@ fn test_gc_operation_log() {
     "###);
 }
 
+#[test]
+fn test_gc_compact_snapshots() {
+    let test_env = TestEnvironment::default();
+    // Use the local backend because GitBackend::gc() depends on the git CLI.
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["init", "repo", "--config-toml=ui.allow-init-native=true"],
+    );
+    let repo_path = test_env.env_root().join("repo");
+
+    // Create a few snapshot operations.
+    for i in 1..=3 {
+        std::fs::write(repo_path.join("file"), format!("change {i}\n")).unwrap();
+        test_env.jj_cmd_ok(&repo_path, &["status"]);
+    }
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "changes"]);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "gc", "--compact-snapshots"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Folded 2 snapshot operations and reparented 2 descendant operations.
+    "###);
+    let template = r#"description ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  describe commit 95b144abe80c6f0a71c1b495f5c08975e31b45b0b139a4ed560dc0584654271a9af52dfebcd8812534200b43764deb97a3eb92ef76cfe69c870a0a26a283c6a3
+    ◉  snapshot working copy
+    ◉  add workspace 'default'
+    ◉  initialize repo
+    ◉
+    "###);
+
+    // Nothing left to fold.
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "gc", "--compact-snapshots"]);
+    insta::assert_snapshot!(stderr, @"");
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+}
+
+#[test]
+fn test_util_exec() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let edit_script = test_env.set_up_fake_editor();
+    let editor_path = assert_cmd::cargo::cargo_bin("fake-editor");
+    let editor_path = editor_path.to_str().unwrap();
+
+    // The repo context is passed through environment variables
+    std::fs::write(
+        &edit_script,
+        "dump-env env JJ_ROOT JJ_CHANGE JJ_COMMIT JJ_OP\0write\nchanged\n",
+    )
+    .unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["util", "exec", editor_path, "file"]);
+    let env = std::fs::read_to_string(test_env.env_root().join("env")).unwrap();
+    insta::assert_snapshot!(test_env.normalize_output(&env), @r###"
+    JJ_ROOT=$TEST_ENV/repo
+    JJ_CHANGE=qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu
+    JJ_COMMIT=230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    JJ_OP=b51416386f2685fd5493f2b20e8eec3c24a1776d9e1a7cb5ed7e30d2d9c88c0c1e1fe71b0b7358cba60de42533d1228ed9878f2f89817d892c803395ccf9fe92
+    "###);
+    // Changes made by the program aren't recorded by default
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", "description", "--limit=2"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  add workspace 'default'
+    ◉  initialize repo
+    "###);
+
+    // The changes can be recorded with a description
+    std::fs::write(&edit_script, "write\nchanged again\n").unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["util", "exec", "-d", "format files", editor_path, "file"],
+    );
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", "description", "--limit=2"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  format files
+    ◉  snapshot working copy
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    new file mode 100644
+    index 0000000000..a177fcc70e
+    --- /dev/null
+    +++ b/file
+    @@ -1,0 +1,1 @@
+    +changed again
+    "###);
+
+    // Failure of the program is reported, and the changes aren't recorded
+    std::fs::write(&edit_script, "fail").unwrap();
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["util", "exec", "-d", "format files", editor_path, "file"],
+    );
+    insta::assert_snapshot!(stderr.replace(editor_path, "fake-editor"), @r###"
+    Error: Program 'fake-editor' exited with a non-zero code. Exit code: 1.
+    "###);
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", "description", "--limit=1"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  format files
+    "###);
+}
+
 #[test]
 fn test_shell_completions() {
     #[track_caller]
@@ -110,3 +217,73 @@ fn test_shell_completions() {
     test("nushell");
     test("zsh");
 }
+
+#[test]
+fn test_util_mangen_output_dir() {
+    let test_env = TestEnvironment::default();
+    let output_dir = test_env.env_root().join("man");
+    std::fs::create_dir(&output_dir).unwrap();
+    let (out, err) = test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &[
+            "util",
+            "mangen",
+            "--output-dir",
+            output_dir.to_str().unwrap(),
+        ],
+    );
+    assert!(out.is_empty());
+    assert!(err.is_empty());
+    assert!(output_dir.join("jj.1").is_file());
+    assert!(output_dir.join("jj-log.1").is_file());
+    assert!(output_dir.join("jj-branch-create.1").is_file());
+}
+
+#[test]
+fn test_util_markdown_help_function_reference() {
+    let test_env = TestEnvironment::default();
+    let stdout = test_env.jj_cmd_success(test_env.env_root(), &["util", "markdown-help"]);
+    assert!(stdout.contains("## Template functions"));
+    assert!(stdout.contains("`concat`"));
+    assert!(stdout.contains("* **Commit:** "));
+    assert!(stdout.contains("* **Operation:** "));
+    assert!(stdout.contains("## Revset functions"));
+    assert!(stdout.contains("`mine`"));
+}
+
+#[test]
+fn test_util_stats() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("small"), "a\n").unwrap();
+    std::fs::write(repo_path.join("large"), "abcdef\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "second"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["util", "stats", "--json"]);
+    let stats: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    // root, "first", and the working-copy commit
+    assert_eq!(stats["visible_commits"], 3);
+    // At least the working-copy commit before it was described
+    assert!(stats["hidden_commits"].as_u64().unwrap() >= 1);
+    assert_eq!(stats["conflicted_commits"], 0);
+    assert_eq!(stats["divergent_changes"], 0);
+    assert!(stats["operations"].as_u64().unwrap() >= 4);
+    assert!(!stats["index_levels"].as_array().unwrap().is_empty());
+    assert_eq!(stats["working_copy_files"], 2);
+    assert_eq!(
+        stats["largest_files"],
+        serde_json::json!([
+            {"path": "large", "size": 7},
+            {"path": "small", "size": 2},
+        ])
+    );
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["util", "stats", "--largest-files=1"]);
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&"Visible commits: 3"));
+    assert!(lines.contains(&"Working-copy files: 2"));
+    assert_eq!(lines[lines.len() - 2..], ["Largest files:", "  7 large"]);
+}
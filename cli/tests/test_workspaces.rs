@@ -128,6 +128,66 @@ fn test_workspaces_add_second_workspace_on_merge() {
     "###);
 }
 
+/// Test adding a workspace with the working copy as of an earlier operation
+#[test]
+fn test_workspaces_add_workspace_at_op() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "--git", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    std::fs::write(main_path.join("file"), "contents").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["describe", "-m", "lost"]);
+    test_env.jj_cmd_ok(&main_path, &["abandon"]);
+    assert!(!main_path.join("file").exists());
+
+    let stderr = test_env.jj_cmd_failure(
+        &main_path,
+        &[
+            "workspace",
+            "add",
+            "--at-op",
+            "@-",
+            "-r",
+            "root()",
+            "../secondary",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: --revision cannot be used with --at-op
+    "###);
+
+    // The working-copy commit of the operation before the abandon is recreated
+    test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "add", "--at-op", "@-", "../secondary"],
+    );
+    assert_eq!(
+        std::fs::read_to_string(secondary_path.join("file")).unwrap(),
+        "contents"
+    );
+    let stdout = test_env.jj_cmd_success(
+        &secondary_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "@",
+            "-T",
+            r#"description ++ parents.map(|c| c.commit_id().short()) ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    lost
+    000000000000
+    "###);
+
+    // The new workspace is added at the current operation
+    let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
+    assert!(stdout.contains("secondary: "), "{stdout}");
+    assert!(stdout.contains("default: "), "{stdout}");
+}
+
 /// Test adding a workspace, but at a specific revision using '-r'
 #[test]
 fn test_workspaces_add_workspace_at_revision() {
@@ -489,7 +549,7 @@ fn test_workspaces_current_op_discarded_by_other() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&secondary_path, &["workspace", "update-stale"]);
     insta::assert_snapshot!(stderr, @r###"
     Failed to read working copy's current operation; attempting recovery. Error message from read attempt: Object bb8aec2a1ca33ebafdfe8866bc4ad3464dffd25634fde19d1025625880791b141d35753e10737c41b2bc133ab84047312f3021d905bb711960253e7f430100fc of type operation not found
-    Created and checked out recovery commit 30ee0d1fbd7a
+    Created and checked out recovery commit kmkuslsw 30ee0d1f (empty) (no description set)
     "###);
     insta::assert_snapshot!(stdout, @"");
 
@@ -782,6 +842,120 @@ fn test_workspaces_root() {
     "###);
 }
 
+#[test]
+fn test_workspaces_template() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "--git", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+    let template = r#"
+    separate(" ",
+      workspaces.map(|w| "@" ++ w.name()),
+      "current=" ++ current_workspace().name(),
+    ) ++ "\n"
+    "#;
+
+    let stdout = test_env.jj_cmd_success(&main_path, &["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @default current=default
+    "###);
+
+    test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "add", "--name", "secondary", "../secondary"],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &secondary_path,
+        &["log", "--no-graph", "-r", "all()", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @secondary current=secondary
+    @default current=secondary
+    current=secondary
+    "###);
+
+    // The list prints the names with the "@" suffix, like working_copies
+    let stdout = test_env.jj_cmd_success(
+        &main_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "@",
+            "-T",
+            r#"workspaces ++ " " ++ working_copies ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    default@ default@
+    "###);
+}
+
+#[test]
+fn test_workspaces_move_change() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "--git", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    std::fs::write(main_path.join("file"), "base\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["commit", "-m", "base"]);
+    test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "add", "--name", "secondary", "../secondary"],
+    );
+    std::fs::write(main_path.join("file"), "change\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["describe", "-m", "change"]);
+
+    let template = r#"separate(" ", description.first_line(), working_copies) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&main_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    change default@
+    secondary@
+    base
+
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&main_path, &["workspace", "move-change", "default"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Workspace default is the current workspace
+    "###);
+    let stderr = test_env.jj_cmd_failure(&main_path, &["workspace", "move-change", "unknown"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No such workspace: unknown
+    "###);
+
+    // The secondary workspace's empty working-copy commit is abandoned, and the
+    // default workspace starts a new change on top of the same parent.
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&main_path, &["workspace", "move-change", "secondary"]);
+    assert!(
+        stderr.contains("Warning: The working copy of workspace secondary is now stale\n"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("Hint: Run `jj workspace update-stale` in workspace secondary"),
+        "{stderr}"
+    );
+    let stdout = test_env.jj_cmd_success(&main_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    default@
+    change secondary@
+    base
+
+    "###);
+    assert_eq!(
+        std::fs::read_to_string(main_path.join("file")).unwrap(),
+        "base\n"
+    );
+
+    test_env.jj_cmd_ok(&secondary_path, &["workspace", "update-stale"]);
+    assert_eq!(
+        std::fs::read_to_string(secondary_path.join("file")).unwrap(),
+        "change\n"
+    );
+}
+
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
     let template = r#"
     separate(" ",
@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use jj_lib::diff;
+use jj_lib::diff::{self, DiffAlgorithm};
 
 fn unchanged_lines(count: usize) -> (String, String) {
     let mut lines = vec![];
@@ -29,6 +29,20 @@ fn reversed_lines(count: usize) -> (String, String) {
     (left_lines.join(""), right_lines.join(""))
 }
 
+fn sparsely_modified_lines(count: usize) -> (String, String) {
+    let mut left_lines = vec![];
+    let mut right_lines = vec![];
+    for i in 0..count {
+        left_lines.push(format!("left line {i}\n"));
+        if i % 10 == 0 {
+            right_lines.push(format!("right line {i}\n"));
+        } else {
+            right_lines.push(format!("left line {i}\n"));
+        }
+    }
+    (left_lines.join(""), right_lines.join(""))
+}
+
 fn bench_diff_lines(c: &mut Criterion) {
     let mut group = c.benchmark_group("bench_diff_lines");
     for count in [1000, 10000] {
@@ -51,6 +65,24 @@ fn bench_diff_lines(c: &mut Criterion) {
     }
 }
 
+fn bench_diff_algorithms(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_diff_algorithms");
+    let inputs = sparsely_modified_lines(10000);
+    for algorithm in [
+        DiffAlgorithm::Histogram,
+        DiffAlgorithm::Patience,
+        DiffAlgorithm::Myers,
+    ] {
+        group.bench_with_input(
+            BenchmarkId::new("sparsely_modified_10k", format!("{algorithm:?}")),
+            &inputs,
+            |b, (left, right)| {
+                b.iter(|| diff::diff_with_algorithm(left.as_bytes(), right.as_bytes(), algorithm))
+            },
+        );
+    }
+}
+
 fn bench_diff_git_git_read_tree_c(c: &mut Criterion) {
     c.bench_function("bench_diff_git_git_read_tree_c", |b| {
         b.iter(|| {
@@ -198,5 +230,10 @@ int main(int argc, char **argv)
     });
 }
 
-criterion_group!(benches, bench_diff_lines, bench_diff_git_git_read_tree_c,);
+criterion_group!(
+    benches,
+    bench_diff_lines,
+    bench_diff_algorithms,
+    bench_diff_git_git_read_tree_c,
+);
 criterion_main!(benches);
@@ -0,0 +1,61 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use itertools::Itertools;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo_path::RepoPathBuf;
+use testutils::{create_single_tree, TestRepo};
+
+fn file_contents(num_lines: usize, first_line: &str, last_line: &str) -> String {
+    let mut lines = vec![first_line.to_owned()];
+    lines.extend((1..num_lines - 1).map(|i| format!("line {i}")));
+    lines.push(last_line.to_owned());
+    lines.join("\n") + "\n"
+}
+
+/// Merges trees where every file has been changed on both sides, so each file
+/// requires a content-level merge.
+fn bench_merge_trees(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_merge_trees");
+    group.sample_size(10);
+    for (num_dirs, files_per_dir) in [(10, 10), (100, 10)] {
+        let test_repo = TestRepo::init();
+        let repo = &test_repo.repo;
+        let paths = (0..num_dirs)
+            .cartesian_product(0..files_per_dir)
+            .map(|(dir, file)| RepoPathBuf::from_internal_string(format!("dir{dir}/file{file}")))
+            .collect_vec();
+        let create_tree = |contents: &str| {
+            let path_contents = paths
+                .iter()
+                .map(|path| (path.as_ref(), contents))
+                .collect_vec();
+            MergedTree::new(Merge::resolved(create_single_tree(repo, &path_contents)))
+        };
+        let base = create_tree(&file_contents(100, "base", "base"));
+        let side1 = create_tree(&file_contents(100, "side1", "base"));
+        let side2 = create_tree(&file_contents(100, "base", "side2"));
+        let label = format!("{num_dirs}x{files_per_dir}");
+        group.bench_with_input(
+            BenchmarkId::new("content_merge", &label),
+            &(base, side1, side2),
+            |b, (base, side1, side2)| b.iter(|| side1.merge(base, side2).unwrap()),
+        );
+    }
+}
+
+criterion_group!(benches, bench_merge_trees);
+criterion_main!(benches);
@@ -0,0 +1,160 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks of repo-level operations against synthesized repos.
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use jj_lib::commit::Commit;
+use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::mailmap::Mailmap;
+use jj_lib::repo::{ReadonlyRepo, Repo};
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::revset::{self, DefaultSymbolResolver, RevsetAliasesMap, RevsetParseContext};
+use testutils::{write_working_copy_file, CommitGraphBuilder, TestRepo, TestWorkspace};
+
+/// Creates `num_commits` commits on a main line, with a side branch forked off
+/// and merged back every 10 commits, roughly like a repo with feature
+/// branches.
+fn create_commit_graph(test_repo: &TestRepo, num_commits: usize) -> Arc<ReadonlyRepo> {
+    let settings = testutils::user_settings();
+    let mut tx = test_repo.repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.mut_repo());
+    let mut main_head = graph_builder.initial_commit();
+    let mut side_head: Option<Commit> = None;
+    for i in 1..num_commits {
+        if i % 10 == 0 {
+            let side = side_head.take().unwrap();
+            main_head = graph_builder.commit_with_parents(&[&main_head, &side]);
+        } else if i % 10 == 5 {
+            side_head = Some(graph_builder.commit_with_parents(&[&main_head]));
+        } else if let Some(side) = &side_head {
+            if i % 2 == 0 {
+                side_head = Some(graph_builder.commit_with_parents(&[side]));
+            } else {
+                main_head = graph_builder.commit_with_parents(&[&main_head]);
+            }
+        } else {
+            main_head = graph_builder.commit_with_parents(&[&main_head]);
+        }
+    }
+    tx.commit("create commit graph").unwrap()
+}
+
+/// Snapshots a working copy with many files, either unchanged since the last
+/// snapshot or all modified.
+fn bench_snapshot(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_snapshot");
+    group.sample_size(10);
+    for (num_dirs, files_per_dir) in [(10, 100), (100, 100)] {
+        let settings = testutils::user_settings();
+        let mut test_workspace = TestWorkspace::init(&settings);
+        let workspace_root = test_workspace.workspace.workspace_root().clone();
+        let paths: Vec<_> = (0..num_dirs)
+            .flat_map(|dir| {
+                (0..files_per_dir).map(move |file| {
+                    RepoPathBuf::from_internal_string(format!("dir{dir}/file{file}"))
+                })
+            })
+            .collect();
+        for path in &paths {
+            write_working_copy_file(&workspace_root, path, "initial contents\n");
+        }
+        test_workspace.snapshot().unwrap();
+
+        let label = format!("{num_dirs}x{files_per_dir}");
+        group.bench_function(BenchmarkId::new("unchanged", &label), |b| {
+            b.iter(|| test_workspace.snapshot().unwrap())
+        });
+        let mut generation = 0;
+        group.bench_function(BenchmarkId::new("all_modified", &label), |b| {
+            b.iter_batched(
+                || {
+                    generation += 1;
+                    for path in &paths {
+                        let contents = format!("modified contents {generation}\n");
+                        write_working_copy_file(&workspace_root, path, &contents);
+                    }
+                },
+                |()| test_workspace.snapshot().unwrap(),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+}
+
+/// Rebuilds the index of a synthesized commit graph from scratch.
+fn bench_index_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_index_build");
+    group.sample_size(10);
+    for num_commits in [1_000, 10_000] {
+        let test_repo = TestRepo::init();
+        let repo = create_commit_graph(&test_repo, num_commits);
+        let default_index_store: &DefaultIndexStore =
+            repo.index_store().as_any().downcast_ref().unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("reindex", num_commits),
+            &repo,
+            |b, repo| {
+                b.iter(|| {
+                    default_index_store.reinit().unwrap();
+                    default_index_store
+                        .build_index_at_operation(repo.operation(), repo.store())
+                        .unwrap()
+                })
+            },
+        );
+    }
+}
+
+/// Parses, resolves, and evaluates revsets against a synthesized commit graph.
+fn bench_revset(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_revset");
+    let test_repo = TestRepo::init();
+    let repo = create_commit_graph(&test_repo, 10_000);
+    let settings = testutils::user_settings();
+    let aliases_map = RevsetAliasesMap::new();
+    let mailmap = Mailmap::empty();
+    let context = RevsetParseContext {
+        aliases_map: &aliases_map,
+        user_email: settings.user_email(),
+        mailmap: &mailmap,
+        workspace: None,
+    };
+    for revset_str in [
+        "all()",
+        "heads(all())",
+        "roots(all())",
+        "merges()",
+        "::visible_heads() ~ ::root()+",
+        "latest(all(), 10)",
+        "description(random) & merges()",
+        "ancestors(visible_heads(), 100) & ~merges()",
+    ] {
+        group.bench_function(revset_str, |b| {
+            b.iter(|| {
+                let expression = revset::optimize(revset::parse(revset_str, &context).unwrap());
+                let symbol_resolver = DefaultSymbolResolver::new(repo.as_ref());
+                let expression = expression
+                    .resolve_user_expression(repo.as_ref(), &symbol_resolver)
+                    .unwrap();
+                expression.evaluate(repo.as_ref()).unwrap().iter().count()
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_snapshot, bench_index_build, bench_revset);
+criterion_main!(benches);
@@ -23,7 +23,8 @@ use std::time::SystemTime;
 use async_trait::async_trait;
 use thiserror::Error;
 
-use crate::content_hash::ContentHash;
+use crate::content_hash::{ContentHash, DigestUpdate};
+use crate::hex_util::to_reverse_hex;
 use crate::index::Index;
 use crate::merge::Merge;
 use crate::object_id::{id_type, ObjectId};
@@ -45,7 +46,15 @@ id_type!(pub FileId);
 id_type!(pub SymlinkId);
 id_type!(pub ConflictId);
 
-#[derive(ContentHash, Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+impl ChangeId {
+    /// Returns the hex string representation of this ID, which uses `z-k`
+    /// "digits" instead of `0-9a-f`.
+    pub fn reverse_hex(&self) -> String {
+        to_reverse_hex(&self.hex()).unwrap()
+    }
+}
+
+#[derive(ContentHash, Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
 pub struct MillisSinceEpoch(pub i64);
 
 #[derive(ContentHash, Debug, PartialEq, Eq, Clone, PartialOrd, Ord)]
@@ -125,7 +134,7 @@ impl MergedTreeId {
     }
 }
 
-#[derive(ContentHash, Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Commit {
     pub parents: Vec<CommitId>,
     pub predecessors: Vec<CommitId>,
@@ -134,9 +143,40 @@ pub struct Commit {
     pub description: String,
     pub author: Signature,
     pub committer: Signature,
+    /// Extra metadata keyed by namespaced names such as `gerrit.change-id`.
+    pub extra: BTreeMap<String, String>,
     pub secure_sig: Option<SecureSig>,
 }
 
+// Implemented by hand so that commits without extra metadata keep the ids they
+// had before the metadata was supported.
+impl ContentHash for Commit {
+    fn hash(&self, state: &mut impl DigestUpdate) {
+        let Commit {
+            parents,
+            predecessors,
+            root_tree,
+            change_id,
+            description,
+            author,
+            committer,
+            extra,
+            secure_sig,
+        } = self;
+        parents.hash(state);
+        predecessors.hash(state);
+        root_tree.hash(state);
+        change_id.hash(state);
+        description.hash(state);
+        author.hash(state);
+        committer.hash(state);
+        if !extra.is_empty() {
+            extra.hash(state);
+        }
+        secure_sig.hash(state);
+    }
+}
+
 #[derive(ContentHash, Debug, PartialEq, Eq, Clone)]
 pub struct ConflictTerm {
     pub value: TreeValue,
@@ -327,6 +367,7 @@ pub fn make_root_commit(root_change_id: ChangeId, empty_tree_id: TreeId) -> Comm
         description: String::new(),
         author: signature.clone(),
         committer: signature,
+        extra: BTreeMap::new(),
         secure_sig: None,
     }
 }
@@ -400,6 +441,13 @@ pub trait Backend: Send + Sync + Debug {
         sign_with: Option<&mut SigningFn>,
     ) -> BackendResult<(CommitId, Commit)>;
 
+    /// Flushes objects written so far to stable storage if the backend defers
+    /// syncing them. This is called before the operation of a transaction is
+    /// written.
+    fn flush(&self) -> BackendResult<()> {
+        Ok(())
+    }
+
     /// Perform garbage collection.
     ///
     /// All commits found in the `index` won't be removed. In addition to that,
@@ -15,15 +15,18 @@
 #![allow(missing_docs)]
 
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Error, Formatter};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
-use crate::backend;
 use crate::backend::{BackendError, ChangeId, CommitId, MergedTreeId, Signature};
 use crate::merged_tree::MergedTree;
+use crate::repo::Repo;
 use crate::signing::{SignResult, Verification};
 use crate::store::Store;
+use crate::tree::TreeMergeError;
+use crate::{backend, rewrite};
 
 #[derive(Clone)]
 pub struct Commit {
@@ -117,6 +120,16 @@ impl Commit {
         }
     }
 
+    /// Returns true if the commit doesn't change the merged tree of its
+    /// parents.
+    pub fn is_empty(&self, repo: &dyn Repo) -> Result<bool, TreeMergeError> {
+        if let [parent] = &self.parents()[..] {
+            return Ok(parent.tree_id() == self.tree_id());
+        }
+        let parent_tree = rewrite::merge_commit_trees(repo, &self.parents())?;
+        Ok(*self.tree_id() == parent_tree.id())
+    }
+
     pub fn change_id(&self) -> &ChangeId {
         &self.data.change_id
     }
@@ -137,6 +150,11 @@ impl Commit {
         &self.data.committer
     }
 
+    /// Extra metadata keyed by namespaced names such as `gerrit.change-id`.
+    pub fn extra(&self) -> &BTreeMap<String, String> {
+        &self.data.extra
+    }
+
     /// A commit is discardable if it has one parent, no change from its
     /// parent, and an empty description.
     pub fn is_discardable(&self) -> bool {
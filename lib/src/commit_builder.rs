@@ -14,6 +14,7 @@
 
 #![allow(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use crate::backend::{self, BackendResult, ChangeId, CommitId, MergedTreeId, Signature, SigningFn};
@@ -51,6 +52,7 @@ impl CommitBuilder<'_> {
             description: String::new(),
             author: signature.clone(),
             committer: signature,
+            extra: BTreeMap::new(),
             secure_sig: None,
         };
         CommitBuilder {
@@ -163,6 +165,21 @@ impl CommitBuilder<'_> {
         self
     }
 
+    /// Extra metadata keyed by namespaced names such as `gerrit.change-id`.
+    pub fn extra(&self) -> &BTreeMap<String, String> {
+        &self.commit.extra
+    }
+
+    pub fn set_extra(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.commit.extra.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn remove_extra(mut self, key: &str) -> Self {
+        self.commit.extra.remove(key);
+        self
+    }
+
     pub fn sign_settings(&self) -> &SignSettings {
         &self.sign_settings
     }
@@ -177,6 +194,15 @@ impl CommitBuilder<'_> {
         self
     }
 
+    /// Returns the commit as it would be written, e.g. to render a template
+    /// against it before writing it. Its id depends on the contents, so it
+    /// isn't known yet and is all zeros.
+    pub fn unwritten_commit(&self) -> Commit {
+        let store = self.mut_repo.store();
+        let id = CommitId::new(vec![0; store.commit_id_length()]);
+        Commit::new(store.clone(), id, Arc::new(self.commit.clone()))
+    }
+
     pub fn write(mut self) -> BackendResult<Commit> {
         let sign_settings = &self.sign_settings;
         let store = self.mut_repo.store();
@@ -16,7 +16,7 @@
 
 use std::cell::RefCell;
 use std::cmp::{Ordering, Reverse};
-use std::collections::{BTreeSet, BinaryHeap, HashSet};
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
 use std::ops::Range;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -31,7 +31,7 @@ use crate::default_index::{AsCompositeIndex, CompositeIndex, IndexEntry, IndexPo
 use crate::matchers::{EverythingMatcher, Matcher, PrefixMatcher, Visit};
 use crate::repo_path::RepoPath;
 use crate::revset::{
-    ResolvedExpression, ResolvedPredicateExpression, Revset, RevsetEvaluationError,
+    self, ResolvedExpression, ResolvedPredicateExpression, Revset, RevsetEvaluationError,
     RevsetFilterPredicate, GENERATION_RANGE_FULL,
 };
 use crate::revset_graph::RevsetGraphEdge;
@@ -282,6 +282,96 @@ impl ToPredicateFn for EagerRevset {
     }
 }
 
+/// Revset which pulls positions from the underlying walk on demand, and
+/// remembers them so that the set can be iterated again without re-evaluation.
+#[derive(Clone)]
+struct MemoizedRevset {
+    state: Rc<RefCell<MemoizedState>>,
+}
+
+struct MemoizedState {
+    walk: BoxedRevWalk<'static>,
+    positions: Vec<IndexPosition>,
+    exhausted: bool,
+}
+
+impl MemoizedRevset {
+    fn new(walk: BoxedRevWalk<'static>) -> Self {
+        let state = MemoizedState {
+            walk,
+            positions: Vec::new(),
+            exhausted: false,
+        };
+        MemoizedRevset {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    fn walk(&self) -> MemoizedRevWalk {
+        MemoizedRevWalk {
+            state: self.state.clone(),
+            next_index: 0,
+        }
+    }
+}
+
+impl fmt::Debug for MemoizedRevset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("MemoizedRevset")
+            .field("positions", &state.positions)
+            .field("exhausted", &state.exhausted)
+            .finish_non_exhaustive()
+    }
+}
+
+impl InternalRevset for MemoizedRevset {
+    fn positions<'a>(&self) -> BoxedRevWalk<'a>
+    where
+        Self: 'a,
+    {
+        Box::new(self.walk())
+    }
+
+    fn into_predicate<'a>(self: Box<Self>) -> Box<dyn ToPredicateFn + 'a>
+    where
+        Self: 'a,
+    {
+        self
+    }
+}
+
+impl ToPredicateFn for MemoizedRevset {
+    fn to_predicate_fn<'a>(&self) -> BoxedPredicateFn<'a>
+    where
+        Self: 'a,
+    {
+        predicate_fn_from_rev_walk(self.walk())
+    }
+}
+
+struct MemoizedRevWalk {
+    state: Rc<RefCell<MemoizedState>>,
+    next_index: usize,
+}
+
+impl RevWalk<CompositeIndex> for MemoizedRevWalk {
+    type Item = IndexPosition;
+
+    fn next(&mut self, index: &CompositeIndex) -> Option<Self::Item> {
+        let mut state = self.state.borrow_mut();
+        if self.next_index == state.positions.len() && !state.exhausted {
+            match state.walk.next(index) {
+                Some(pos) => state.positions.push(pos),
+                None => state.exhausted = true,
+            }
+        }
+        let pos = state.positions.get(self.next_index).copied()?;
+        self.next_index += 1;
+        Some(pos)
+    }
+}
+
 struct RevWalkRevset<W> {
     walk: W,
 }
@@ -701,9 +791,20 @@ pub fn evaluate<I: AsCompositeIndex + Clone>(
     store: &Arc<Store>,
     index: I,
 ) -> Result<RevsetImpl<I>, RevsetEvaluationError> {
+    let shared_slots = revset::common_subexpressions(expression)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(slot, group)| {
+            group
+                .into_iter()
+                .map(move |subexpression| (subexpression as *const ResolvedExpression, slot))
+        })
+        .collect();
     let context = EvaluationContext {
         store: store.clone(),
         index: index.as_composite(),
+        shared_slots,
+        shared_revsets: RefCell::new(HashMap::new()),
     };
     let internal_revset = context.evaluate(expression)?;
     Ok(RevsetImpl::new(internal_revset, index))
@@ -712,6 +813,10 @@ pub fn evaluate<I: AsCompositeIndex + Clone>(
 struct EvaluationContext<'index> {
     store: Arc<Store>,
     index: &'index CompositeIndex,
+    /// Maps each occurrence of a repeated subexpression to its shared slot.
+    shared_slots: HashMap<*const ResolvedExpression, usize>,
+    /// Revsets of repeated subexpressions which have been evaluated.
+    shared_revsets: RefCell<HashMap<usize, MemoizedRevset>>,
 }
 
 fn to_u32_generation_range(range: &Range<u64>) -> Result<Range<u32>, RevsetEvaluationError> {
@@ -729,6 +834,23 @@ impl<'index> EvaluationContext<'index> {
     fn evaluate(
         &self,
         expression: &ResolvedExpression,
+    ) -> Result<Box<dyn InternalRevset>, RevsetEvaluationError> {
+        let Some(&slot) = self.shared_slots.get(&(expression as *const _)) else {
+            return self.evaluate_uncached(expression);
+        };
+        if let Some(revset) = self.shared_revsets.borrow().get(&slot) {
+            return Ok(Box::new(revset.clone()));
+        }
+        let revset = MemoizedRevset::new(self.evaluate_uncached(expression)?.positions());
+        self.shared_revsets
+            .borrow_mut()
+            .insert(slot, revset.clone());
+        Ok(Box::new(revset))
+    }
+
+    fn evaluate_uncached(
+        &self,
+        expression: &ResolvedExpression,
     ) -> Result<Box<dyn InternalRevset>, RevsetEvaluationError> {
         let index = self.index;
         match expression {
@@ -874,7 +996,13 @@ impl<'index> EvaluationContext<'index> {
                 predicate: self.evaluate_predicate(predicate)?,
             })),
             ResolvedExpression::Intersection(expression1, expression2) => {
-                let set1 = self.evaluate(expression1)?;
+                // The optimizer puts the cheapest set first. If it's empty, the
+                // other set doesn't have to be evaluated. The set is memoized so
+                // the peeked position isn't computed again.
+                let set1 = MemoizedRevset::new(self.evaluate(expression1)?.positions());
+                if set1.positions().attach(index).next().is_none() {
+                    return Ok(Box::new(EagerRevset::empty()));
+                }
                 let set2 = self.evaluate(expression2)?;
                 Ok(Box::new(IntersectionRevset { set1, set2 }))
             }
@@ -1206,6 +1334,42 @@ mod tests {
         assert!(p(index, get_pos(&id_0)));
     }
 
+    #[test]
+    fn test_memoized_revset() {
+        let mut new_change_id = change_id_generator();
+        let mut index = DefaultMutableIndex::full(3, 16);
+        let id_0 = CommitId::from_hex("000000");
+        let id_1 = CommitId::from_hex("111111");
+        let id_2 = CommitId::from_hex("222222");
+        index.add_commit_data(id_0.clone(), new_change_id(), &[]);
+        index.add_commit_data(id_1.clone(), new_change_id(), &[id_0.clone()]);
+        index.add_commit_data(id_2.clone(), new_change_id(), &[id_1.clone()]);
+
+        let index = index.as_composite();
+        let get_pos = |id: &CommitId| index.commit_id_to_pos(id).unwrap();
+        let positions = vec![get_pos(&id_2), get_pos(&id_0)];
+        let pulled = Rc::new(std::cell::Cell::new(0));
+        let walk = EagerRevWalk::new(positions.clone().into_iter().inspect({
+            let pulled = pulled.clone();
+            move |_| pulled.set(pulled.get() + 1)
+        }));
+        let set = MemoizedRevset::new(Box::new(walk));
+
+        // Positions are pulled from the underlying walk on demand
+        assert_eq!(set.positions().attach(index).next(), Some(get_pos(&id_2)));
+        assert_eq!(pulled.get(), 1);
+        assert_eq!(set.positions().attach(index).collect_vec(), positions);
+        assert_eq!(pulled.get(), 2);
+
+        // Other walks and predicates reuse the pulled positions
+        assert_eq!(set.positions().attach(index).collect_vec(), positions);
+        let mut p = set.to_predicate_fn();
+        assert!(p(index, get_pos(&id_2)));
+        assert!(!p(index, get_pos(&id_1)));
+        assert!(p(index, get_pos(&id_0)));
+        assert_eq!(pulled.get(), 2);
+    }
+
     #[test]
     fn test_positions_accumulator() {
         let mut new_change_id = change_id_generator();
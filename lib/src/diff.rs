@@ -82,6 +82,21 @@ pub fn find_nonword_ranges(text: &[u8]) -> Vec<Range<usize>> {
     ranges
 }
 
+/// Algorithm used to find the unchanged regions between inputs.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DiffAlgorithm {
+    /// Matches up the least frequent tokens first, then recurses into the
+    /// regions between them.
+    #[default]
+    Histogram,
+    /// Matches up the tokens which occur exactly once on each side, then
+    /// recurses into the regions between them. Regions without such tokens are
+    /// diffed by the Myers algorithm.
+    Patience,
+    /// Finds the minimal number of insertions and deletions.
+    Myers,
+}
+
 struct Histogram<'a> {
     word_to_positions: HashMap<&'a [u8], Vec<usize>>,
     count_to_words: BTreeMap<usize, Vec<&'a [u8]>>,
@@ -300,6 +315,224 @@ pub(crate) fn unchanged_ranges(
     result
 }
 
+/// Finds unchanged ranges among the ones given as arguments by using the
+/// specified `algorithm`.
+fn unchanged_ranges_with_algorithm(
+    algorithm: DiffAlgorithm,
+    left: &[u8],
+    right: &[u8],
+    left_ranges: &[Range<usize>],
+    right_ranges: &[Range<usize>],
+) -> Vec<(Range<usize>, Range<usize>)> {
+    match algorithm {
+        DiffAlgorithm::Histogram => unchanged_ranges(left, right, left_ranges, right_ranges),
+        DiffAlgorithm::Patience => {
+            patience_unchanged_ranges(left, right, left_ranges, right_ranges)
+        }
+        DiffAlgorithm::Myers => myers_unchanged_ranges(left, right, left_ranges, right_ranges),
+    }
+}
+
+/// Finds unchanged ranges by matching up the tokens which are unique in both
+/// `left` and `right`. Falls back to the Myers algorithm if there are no such
+/// tokens.
+fn patience_unchanged_ranges(
+    left: &[u8],
+    right: &[u8],
+    left_ranges: &[Range<usize>],
+    right_ranges: &[Range<usize>],
+) -> Vec<(Range<usize>, Range<usize>)> {
+    if left_ranges.is_empty() || right_ranges.is_empty() {
+        return vec![];
+    }
+
+    // word => (left count, left position, right count, right position)
+    let mut word_positions: HashMap<&[u8], (usize, usize, usize, usize)> = HashMap::new();
+    for (i, range) in left_ranges.iter().enumerate() {
+        let entry = word_positions.entry(&left[range.clone()]).or_default();
+        entry.0 += 1;
+        entry.1 = i;
+    }
+    for (i, range) in right_ranges.iter().enumerate() {
+        if let Some(entry) = word_positions.get_mut(&right[range.clone()]) {
+            entry.2 += 1;
+            entry.3 = i;
+        }
+    }
+    // (left position, right position) of the unique shared words, sorted by
+    // right position
+    let unique_positions = word_positions
+        .into_values()
+        .filter(|&(left_count, _, right_count, _)| left_count == 1 && right_count == 1)
+        .map(|(_, left_position, _, right_position)| (left_position, right_position))
+        .sorted_by_key(|&(_, right_position)| right_position)
+        .collect_vec();
+    if unique_positions.is_empty() {
+        return myers_unchanged_ranges(left, right, left_ranges, right_ranges);
+    }
+
+    let sorted_left_positions = unique_positions
+        .iter()
+        .map(|&(left_position, _)| left_position)
+        .sorted()
+        .collect_vec();
+    let left_index_by_right_index = unique_positions
+        .iter()
+        .map(|(left_position, _)| sorted_left_positions.binary_search(left_position).unwrap())
+        .collect_vec();
+    let lcs = find_lcs(&left_index_by_right_index);
+
+    // Produce output ranges, recursing into the modified areas between the elements
+    // in the LCS.
+    let mut result = vec![];
+    let mut previous_left_position = 0;
+    let mut previous_right_position = 0;
+    let boundaries = lcs
+        .into_iter()
+        .map(|(left_index, right_index)| {
+            let left_position = sorted_left_positions[left_index];
+            let right_position = unique_positions[right_index].1;
+            Some((left_position, right_position))
+        })
+        .chain([None]);
+    for boundary in boundaries {
+        let (left_position, right_position) =
+            boundary.unwrap_or((left_ranges.len(), right_ranges.len()));
+        let skipped_left_positions = previous_left_position..left_position;
+        let skipped_right_positions = previous_right_position..right_position;
+        if !skipped_left_positions.is_empty() || !skipped_right_positions.is_empty() {
+            result.extend(patience_unchanged_ranges(
+                left,
+                right,
+                &left_ranges[skipped_left_positions],
+                &right_ranges[skipped_right_positions],
+            ));
+        }
+        if boundary.is_some() {
+            result.push((
+                left_ranges[left_position].clone(),
+                right_ranges[right_position].clone(),
+            ));
+            previous_left_position = left_position + 1;
+            previous_right_position = right_position + 1;
+        }
+    }
+    result
+}
+
+/// Maximum number of edits explored by the Myers algorithm. The memory usage
+/// grows quadratically with the number of edits, so larger diffs fall back to
+/// the histogram algorithm.
+const MAX_MYERS_EDIT_COST: usize = 2000;
+
+/// Finds unchanged ranges which form the shortest edit script between `left`
+/// and `right` by using the Myers O(ND) algorithm.
+fn myers_unchanged_ranges(
+    left: &[u8],
+    right: &[u8],
+    left_ranges: &[Range<usize>],
+    right_ranges: &[Range<usize>],
+) -> Vec<(Range<usize>, Range<usize>)> {
+    let left_words = left_ranges
+        .iter()
+        .map(|range| &left[range.clone()])
+        .collect_vec();
+    let right_words = right_ranges
+        .iter()
+        .map(|range| &right[range.clone()])
+        .collect_vec();
+    let Some(matches) = myers_matching_positions(&left_words, &right_words) else {
+        return unchanged_ranges(left, right, left_ranges, right_ranges);
+    };
+    matches
+        .into_iter()
+        .map(|(left_position, right_position)| {
+            (
+                left_ranges[left_position].clone(),
+                right_ranges[right_position].clone(),
+            )
+        })
+        .collect()
+}
+
+/// Returns `(left_position, right_position)` pairs of the matching words in the
+/// shortest edit script, or `None` if the edit cost exceeds
+/// `MAX_MYERS_EDIT_COST`.
+fn myers_matching_positions(left: &[&[u8]], right: &[&[u8]]) -> Option<Vec<(usize, usize)>> {
+    let n = left.len() as isize;
+    let m = right.len() as isize;
+    let follow_snake = |mut x: isize, mut y: isize| {
+        while x < n && y < m && left[x as usize] == right[y as usize] {
+            x += 1;
+            y += 1;
+        }
+        x
+    };
+    // Picks the furthest reaching move onto diagonal `k` after `d` edits.
+    // Returns the x position after the move, and the diagonal it came from.
+    // `previous[(k + d - 1)]` is the furthest x position on diagonal `k` after
+    // `d - 1` edits, or -1 if unreachable.
+    let choose_move = |previous: &[isize], d: isize, k: isize| {
+        let at = |k: isize| previous[(k + d - 1) as usize];
+        let down = (k < d && at(k + 1) >= 0 && at(k + 1) - k <= m).then(|| at(k + 1));
+        let right = (k > -d && at(k - 1) >= 0 && at(k - 1) < n).then(|| at(k - 1) + 1);
+        match (down, right) {
+            (Some(down_x), Some(right_x)) if right_x > down_x => Some((right_x, k - 1)),
+            (Some(down_x), _) => Some((down_x, k + 1)),
+            (None, Some(right_x)) => Some((right_x, k - 1)),
+            (None, None) => None,
+        }
+    };
+
+    // trace[d][k + d] is the furthest x position on diagonal k (= x - y) after
+    // d edits, or -1 if unreachable
+    let mut trace: Vec<Vec<isize>> = vec![];
+    let mut end_k = None;
+    for d in 0..=(n + m) {
+        if d as usize > MAX_MYERS_EDIT_COST {
+            return None;
+        }
+        let mut furthest = vec![-1; (2 * d + 1) as usize];
+        for k in (-d..=d).step_by(2) {
+            let x = if d == 0 {
+                0
+            } else if let Some((x, _)) = choose_move(&trace[(d - 1) as usize], d, k) {
+                x
+            } else {
+                continue;
+            };
+            let x = follow_snake(x, x - k);
+            furthest[(k + d) as usize] = x;
+            if x == n && x - k == m {
+                end_k = Some(k);
+                break;
+            }
+        }
+        trace.push(furthest);
+        if end_k.is_some() {
+            break;
+        }
+    }
+
+    // Walk back from the end, collecting the matching words along the path.
+    let mut k = end_k.unwrap();
+    let mut matches = vec![];
+    for d in (0..trace.len() as isize).rev() {
+        let end_x = trace[d as usize][(k + d) as usize];
+        let (start_x, previous_k) = if d == 0 {
+            (0, k)
+        } else {
+            choose_move(&trace[(d - 1) as usize], d, k).unwrap()
+        };
+        for x in (start_x..end_x).rev() {
+            matches.push((x as usize, (x - k) as usize));
+        }
+        k = previous_k;
+    }
+    matches.reverse();
+    Some(matches)
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 struct UnchangedRange {
     base_range: Range<usize>,
@@ -339,6 +572,7 @@ impl Ord for UnchangedRange {
 /// of them.
 #[derive(Clone, Debug)]
 pub struct Diff<'input> {
+    algorithm: DiffAlgorithm,
     base_input: &'input [u8],
     other_inputs: Vec<&'input [u8]>,
     // The key is a range in the base input. The value is the start of each non-base region
@@ -396,6 +630,14 @@ impl<'input> Diff<'input> {
     pub fn for_tokenizer(
         inputs: &[&'input [u8]],
         tokenizer: &impl Fn(&[u8]) -> Vec<Range<usize>>,
+    ) -> Self {
+        Diff::for_tokenizer_with_algorithm(inputs, tokenizer, DiffAlgorithm::default())
+    }
+
+    pub fn for_tokenizer_with_algorithm(
+        inputs: &[&'input [u8]],
+        tokenizer: &impl Fn(&[u8]) -> Vec<Range<usize>>,
+        algorithm: DiffAlgorithm,
     ) -> Self {
         assert!(!inputs.is_empty());
         let base_input = inputs[0];
@@ -416,7 +658,8 @@ impl<'input> Diff<'input> {
             offsets: vec![],
         }];
         for (i, other_token_ranges) in other_token_ranges.iter().enumerate() {
-            let unchanged_diff_ranges = unchanged_ranges(
+            let unchanged_diff_ranges = unchanged_ranges_with_algorithm(
+                algorithm,
                 base_input,
                 other_inputs[i],
                 &base_token_ranges,
@@ -435,6 +678,7 @@ impl<'input> Diff<'input> {
         });
 
         let mut diff = Self {
+            algorithm,
             base_input,
             other_inputs,
             unchanged_regions,
@@ -454,7 +698,14 @@ impl<'input> Diff<'input> {
     // probably mean that many callers repeat the same code. Perhaps it
     // should be possible to refine a whole diff *or* individual hunks.
     pub fn default_refinement(inputs: &[&'input [u8]]) -> Self {
-        let mut diff = Diff::for_tokenizer(inputs, &find_line_ranges);
+        Diff::default_refinement_with_algorithm(inputs, DiffAlgorithm::default())
+    }
+
+    pub fn default_refinement_with_algorithm(
+        inputs: &[&'input [u8]],
+        algorithm: DiffAlgorithm,
+    ) -> Self {
+        let mut diff = Diff::for_tokenizer_with_algorithm(inputs, &find_line_ranges, algorithm);
         diff.refine_changed_regions(&find_word_ranges);
         diff.refine_changed_regions(&find_nonword_ranges);
         diff
@@ -493,7 +744,8 @@ impl<'input> Diff<'input> {
                 slices.push(&self.other_inputs[i][changed_range]);
             }
 
-            let refined_diff = Diff::for_tokenizer(&slices, tokenizer);
+            let refined_diff =
+                Diff::for_tokenizer_with_algorithm(&slices, tokenizer, self.algorithm);
 
             for UnchangedRange {
                 base_range,
@@ -620,6 +872,16 @@ impl<'diff, 'input> Iterator for DiffHunkIterator<'diff, 'input> {
 /// algorithm correctly). It first diffs lines in the input and then refines
 /// the changed ranges at the word level.
 pub fn diff<'a>(left: &'a [u8], right: &'a [u8]) -> Vec<DiffHunk<'a>> {
+    diff_with_algorithm(left, right, DiffAlgorithm::default())
+}
+
+/// Diffs two slices of bytes by using the specified `algorithm`. See `diff()`
+/// for details.
+pub fn diff_with_algorithm<'a>(
+    left: &'a [u8],
+    right: &'a [u8],
+    algorithm: DiffAlgorithm,
+) -> Vec<DiffHunk<'a>> {
     if left == right {
         return vec![DiffHunk::Matching(left)];
     }
@@ -630,7 +892,7 @@ pub fn diff<'a>(left: &'a [u8], right: &'a [u8]) -> Vec<DiffHunk<'a>> {
         return vec![DiffHunk::Different(vec![left, b""])];
     }
 
-    Diff::default_refinement(&[left, right])
+    Diff::default_refinement_with_algorithm(&[left, right], algorithm)
         .hunks()
         .collect_vec()
 }
@@ -795,6 +1057,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unchanged_ranges_patience_unique_words() {
+        assert_eq!(
+            unchanged_ranges_with_algorithm(
+                DiffAlgorithm::Patience,
+                b"a b c z",
+                b"c a b z",
+                &[0..1, 2..3, 4..5, 6..7],
+                &[0..1, 2..3, 4..5, 6..7],
+            ),
+            vec![(0..1, 2..3), (2..3, 4..5), (6..7, 6..7)]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_ranges_patience_non_unique_in_middle() {
+        assert_eq!(
+            unchanged_ranges_with_algorithm(
+                DiffAlgorithm::Patience,
+                b"a b b c",
+                b"a b X b c",
+                &[0..1, 2..3, 4..5, 6..7],
+                &[0..1, 2..3, 4..5, 6..7, 8..9],
+            ),
+            vec![(0..1, 0..1), (2..3, 2..3), (4..5, 6..7), (6..7, 8..9)]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_ranges_myers_shortest_edit() {
+        // The example from the Myers paper, which has 5 edits
+        assert_eq!(
+            unchanged_ranges_with_algorithm(
+                DiffAlgorithm::Myers,
+                b"a b c a b b a",
+                b"c b a b a c",
+                &[0..1, 2..3, 4..5, 6..7, 8..9, 10..11, 12..13],
+                &[0..1, 2..3, 4..5, 6..7, 8..9, 10..11],
+            ),
+            vec![(4..5, 0..1), (6..7, 4..5), (8..9, 6..7), (12..13, 8..9)]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_ranges_myers_empty_side() {
+        assert_eq!(
+            unchanged_ranges_with_algorithm(DiffAlgorithm::Myers, b"", b"a b", &[], &[0..1, 2..3]),
+            vec![]
+        );
+        assert_eq!(
+            unchanged_ranges_with_algorithm(DiffAlgorithm::Myers, b"a b", b"", &[0..1, 2..3], &[]),
+            vec![]
+        );
+    }
+
     #[test]
     fn test_intersect_regions_existing_empty() {
         let actual = intersect_regions(vec![], &[(20..25, 55..60)]);
@@ -977,6 +1294,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_diff_with_algorithm() {
+        for algorithm in [
+            DiffAlgorithm::Histogram,
+            DiffAlgorithm::Patience,
+            DiffAlgorithm::Myers,
+        ] {
+            assert_eq!(
+                diff_with_algorithm(b"a\nb\nc\n", b"a\nX\nc\n", algorithm),
+                vec![
+                    DiffHunk::Matching(b"a\n"),
+                    DiffHunk::Different(vec![b"b", b"X"]),
+                    DiffHunk::Matching(b"\nc\n"),
+                ],
+                "{algorithm:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_diff_recursion_needed() {
         assert_eq!(
@@ -14,9 +14,12 @@
 
 #![allow(missing_docs)]
 
+use std::collections::BTreeSet;
 use std::fs::{self, File};
 use std::path::{Component, Path, PathBuf};
-use std::{io, iter};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::{io, iter, mem};
 
 use tempfile::{NamedTempFile, PersistError};
 use thiserror::Error;
@@ -148,8 +151,98 @@ pub fn persist_content_addressed_temp_file<P: AsRef<Path>>(
     }
 }
 
+/// When files written to a store are flushed to stable storage.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FsyncPolicy {
+    /// Every file is synced as soon as it has been written.
+    Always,
+    /// Files are synced together when the transaction is committed.
+    Batch,
+    /// Files are never synced explicitly. Data written shortly before a crash
+    /// or power loss may be lost.
+    #[default]
+    Never,
+}
+
+impl FromStr for FsyncPolicy {
+    type Err = config::ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(Self::Always),
+            "batch" => Ok(Self::Batch),
+            "never" => Ok(Self::Never),
+            other => Err(config::ConfigError::Message(format!(
+                "unknown fsync policy: {other}"
+            ))),
+        }
+    }
+}
+
+/// Keeps track of written files that still need to be synced according to the
+/// [`FsyncPolicy`].
+#[derive(Debug)]
+pub struct PendingSyncs {
+    policy: FsyncPolicy,
+    paths: Mutex<Vec<PathBuf>>,
+}
+
+impl PendingSyncs {
+    pub fn new(policy: FsyncPolicy) -> Self {
+        PendingSyncs {
+            policy,
+            paths: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn policy(&self) -> FsyncPolicy {
+        self.policy
+    }
+
+    /// Records that the file at `path` has been written. The file is synced
+    /// immediately if the policy is [`FsyncPolicy::Always`].
+    pub fn record(&self, path: PathBuf) -> Result<(), PathError> {
+        match self.policy {
+            FsyncPolicy::Always => sync_files([path]),
+            FsyncPolicy::Batch => {
+                self.paths.lock().unwrap().push(path);
+                Ok(())
+            }
+            FsyncPolicy::Never => Ok(()),
+        }
+    }
+
+    /// Syncs all files recorded since the last call, along with the
+    /// directories containing them.
+    pub fn flush(&self) -> Result<(), PathError> {
+        let paths = mem::take(&mut *self.paths.lock().unwrap());
+        sync_files(paths)
+    }
+}
+
+/// Syncs the given files and their parent directories. Files that no longer
+/// exist (e.g. because the object was already packed) are skipped.
+fn sync_files(paths: impl IntoIterator<Item = PathBuf>) -> Result<(), PathError> {
+    let mut dirs = BTreeSet::new();
+    for path in paths {
+        match File::open(&path) {
+            Ok(file) => file.sync_all().context(&path)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err).context(&path),
+        }
+        if let Some(dir) = path.parent() {
+            dirs.insert(dir.to_owned());
+        }
+    }
+    for dir in dirs {
+        sync_dir(&dir).context(&dir)?;
+    }
+    Ok(())
+}
+
 #[cfg(unix)]
 mod platform {
+    use std::fs::File;
     use std::io;
     use std::os::unix::fs::symlink;
     use std::path::Path;
@@ -162,6 +255,11 @@ mod platform {
     pub fn try_symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Result<()> {
         symlink(original, link)
     }
+
+    /// Flushes the directory entries (e.g. of renamed files) to disk.
+    pub(crate) fn sync_dir(path: &Path) -> io::Result<()> {
+        File::open(path)?.sync_all()
+    }
 }
 
 #[cfg(windows)]
@@ -192,6 +290,12 @@ mod platform {
 
         symlink_file(original, link)
     }
+
+    /// Directories can't be opened as files on Windows, and renames are
+    /// journaled by NTFS, so there's nothing to do.
+    pub(crate) fn sync_dir(_path: &Path) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -241,4 +345,43 @@ mod tests {
 
         assert!(persist_content_addressed_temp_file(temp_file, &target).is_ok());
     }
+
+    #[test]
+    fn test_pending_syncs_batch() {
+        let temp_dir = testutils::new_temp_dir();
+        let target = temp_dir.path().join("file");
+        fs::write(&target, b"contents").unwrap();
+
+        let syncs = PendingSyncs::new(FsyncPolicy::Batch);
+        syncs.record(target).unwrap();
+        // Files which have gone away in the meantime are ignored.
+        syncs.record(temp_dir.path().join("missing")).unwrap();
+        assert_eq!(syncs.paths.lock().unwrap().len(), 2);
+        syncs.flush().unwrap();
+        assert!(syncs.paths.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pending_syncs_not_batched() {
+        let temp_dir = testutils::new_temp_dir();
+        let target = temp_dir.path().join("file");
+        fs::write(&target, b"contents").unwrap();
+
+        for policy in [FsyncPolicy::Always, FsyncPolicy::Never] {
+            let syncs = PendingSyncs::new(policy);
+            syncs.record(target.clone()).unwrap();
+            assert!(syncs.paths.lock().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_fsync_policy() {
+        assert_eq!(
+            "always".parse::<FsyncPolicy>().unwrap(),
+            FsyncPolicy::Always
+        );
+        assert_eq!("batch".parse::<FsyncPolicy>().unwrap(), FsyncPolicy::Batch);
+        assert_eq!("never".parse::<FsyncPolicy>().unwrap(), FsyncPolicy::Never);
+        assert!("sometimes".parse::<FsyncPolicy>().is_err());
+    }
 }
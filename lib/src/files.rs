@@ -20,7 +20,7 @@ use std::fmt::{Debug, Error, Formatter};
 use itertools::Itertools;
 
 use crate::diff;
-use crate::diff::{Diff, DiffHunk};
+use crate::diff::{Diff, DiffAlgorithm, DiffHunk};
 use crate::merge::{trivial_merge, Merge};
 
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -47,7 +47,15 @@ impl DiffLine<'_> {
 }
 
 pub fn diff<'a>(left: &'a [u8], right: &'a [u8]) -> DiffLineIterator<'a> {
-    let diff_hunks = diff::diff(left, right);
+    diff_with_algorithm(left, right, DiffAlgorithm::default())
+}
+
+pub fn diff_with_algorithm<'a>(
+    left: &'a [u8],
+    right: &'a [u8],
+    algorithm: DiffAlgorithm,
+) -> DiffLineIterator<'a> {
+    let diff_hunks = diff::diff_with_algorithm(left, right, algorithm);
     DiffLineIterator::new(diff_hunks)
 }
 
@@ -0,0 +1,389 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing of fileset expressions which select files in the repo.
+//!
+//! A fileset expression is a union of file patterns separated by `|`. A
+//! pattern without a prefix is a literal path relative to the current
+//! directory, which selects the file or all files under the directory. Other
+//! kinds of patterns are selected by prefix: `cwd:` (the default), `root:` for
+//! a path relative to the workspace root, and `glob:` or `root-glob:` for a
+//! glob pattern relative to the current directory or the workspace root.
+//! `all()` selects all files.
+//!
+//! A pattern can be quoted by `"` to include `|` or surrounding spaces, in
+//! which `\"` and `\\` are escaped.
+
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::matchers::{
+    EverythingMatcher, FileGlobsMatcher, Matcher, NothingMatcher, PrefixMatcher, UnionMatcher,
+};
+use crate::repo_path::{FsPathParseError, RepoPathBuf, RepoPathUiConverter};
+
+/// Error occurred during fileset parsing.
+#[derive(Debug, Error)]
+pub enum FilesetParseError {
+    /// Pattern is empty.
+    #[error("Empty file pattern")]
+    EmptyPattern,
+    /// Quoted pattern isn't terminated.
+    #[error("Unterminated quoted pattern")]
+    UnterminatedQuote,
+    /// Quoted pattern is surrounded by other characters.
+    #[error("Unexpected characters around quoted pattern")]
+    UnexpectedCharacters,
+    /// Path can't be mapped to the repo.
+    #[error(transparent)]
+    InvalidPath(#[from] FsPathParseError),
+    /// Failed to parse glob pattern.
+    #[error(transparent)]
+    GlobPattern(#[from] glob::PatternError),
+    /// Pattern kind prefix is unknown.
+    #[error(r#"Invalid file pattern kind "{0}:""#)]
+    InvalidKind(String),
+}
+
+/// Result of fileset parsing.
+pub type FilesetParseResult<T> = Result<T, FilesetParseError>;
+
+/// Pattern that selects files by path.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilePattern {
+    /// Matches the file or files under the directory.
+    PrefixPath(RepoPathBuf),
+    /// Matches files under `dir` whose relative paths match the `pattern`.
+    FileGlob {
+        /// Directory the pattern is anchored to.
+        dir: RepoPathBuf,
+        /// Glob pattern relative to the `dir`.
+        pattern: glob::Pattern,
+    },
+}
+
+/// Parsed fileset expression.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilesetExpression {
+    /// Matches nothing.
+    None,
+    /// Matches everything.
+    All,
+    /// Matches files by pattern.
+    Pattern(FilePattern),
+    /// Matches files matched by any of the expressions.
+    UnionAll(Vec<FilesetExpression>),
+}
+
+impl FilesetExpression {
+    /// Expression that matches any of the given `expressions`.
+    pub fn union_all(expressions: Vec<FilesetExpression>) -> Self {
+        match expressions.len() {
+            0 => FilesetExpression::None,
+            1 => expressions.into_iter().next().unwrap(),
+            _ => FilesetExpression::UnionAll(expressions),
+        }
+    }
+
+    /// Transforms the expression tree to `Matcher` object.
+    pub fn to_matcher(&self) -> Box<dyn Matcher> {
+        let mut prefixes = vec![];
+        let mut globs = vec![];
+        if self.collect_patterns(&mut prefixes, &mut globs) {
+            return Box::new(EverythingMatcher);
+        }
+        match (prefixes.is_empty(), globs.is_empty()) {
+            (true, true) => Box::new(NothingMatcher),
+            (false, true) => Box::new(PrefixMatcher::new(prefixes)),
+            (true, false) => Box::new(FileGlobsMatcher::new(globs)),
+            (false, false) => Box::new(UnionMatcher::new(
+                PrefixMatcher::new(prefixes),
+                FileGlobsMatcher::new(globs),
+            )),
+        }
+    }
+
+    /// Collects patterns, and returns true if the expression matches all
+    /// files.
+    fn collect_patterns<'a>(
+        &'a self,
+        prefixes: &mut Vec<&'a RepoPathBuf>,
+        globs: &mut Vec<(&'a RepoPathBuf, glob::Pattern)>,
+    ) -> bool {
+        match self {
+            FilesetExpression::None => false,
+            FilesetExpression::All => true,
+            FilesetExpression::Pattern(FilePattern::PrefixPath(path)) => {
+                prefixes.push(path);
+                false
+            }
+            FilesetExpression::Pattern(FilePattern::FileGlob { dir, pattern }) => {
+                globs.push((dir, pattern.clone()));
+                false
+            }
+            FilesetExpression::UnionAll(expressions) => expressions
+                .iter()
+                .any(|expression| expression.collect_patterns(prefixes, globs)),
+        }
+    }
+}
+
+/// Parses the given `text` as fileset expression.
+pub fn parse(
+    text: &str,
+    path_converter: &RepoPathUiConverter,
+) -> FilesetParseResult<FilesetExpression> {
+    let expressions = split_union_terms(text)?
+        .into_iter()
+        .map(|term| match term {
+            UnionTerm::Bare("all()") => Ok(FilesetExpression::All),
+            UnionTerm::Bare(text) => parse_pattern(text, path_converter),
+            UnionTerm::Quoted(prefix, text) => parse_pattern_kind(prefix, &text, path_converter),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(FilesetExpression::union_all(expressions))
+}
+
+/// Term of a union expression.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum UnionTerm<'a> {
+    /// Unquoted text.
+    Bare(&'a str),
+    /// Pattern kind prefix (if any) followed by unescaped quoted text.
+    Quoted(Option<&'a str>, String),
+}
+
+/// Splits the `text` at `|`s which aren't quoted, and trims the terms.
+fn split_union_terms(text: &str) -> FilesetParseResult<Vec<UnionTerm<'_>>> {
+    let mut terms = vec![];
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        let term_len = rest.find(['|', '"']).unwrap_or(rest.len());
+        let term = if rest[term_len..].starts_with('"') {
+            let prefix = rest[..term_len].strip_suffix(':');
+            if prefix.is_none() && term_len != 0 {
+                return Err(FilesetParseError::UnexpectedCharacters);
+            }
+            let (quoted, quoted_len) = unquote(&rest[term_len + 1..])?;
+            rest = rest[term_len + 1 + quoted_len..].trim_start();
+            if !rest.is_empty() && !rest.starts_with('|') {
+                return Err(FilesetParseError::UnexpectedCharacters);
+            }
+            UnionTerm::Quoted(prefix, quoted)
+        } else {
+            let term = rest[..term_len].trim_end();
+            rest = &rest[term_len..];
+            UnionTerm::Bare(term)
+        };
+        terms.push(term);
+        match rest.strip_prefix('|') {
+            Some(next) => rest = next,
+            None => return Ok(terms),
+        }
+    }
+}
+
+/// Unescapes the quoted string up to the closing `"`. Returns the string and
+/// the length of the consumed text including the closing quote.
+fn unquote(text: &str) -> FilesetParseResult<(String, usize)> {
+    let mut unquoted = String::new();
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((unquoted, i + 1)),
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => unquoted.push(c),
+                Some((_, c)) => unquoted.extend(['\\', c]),
+                None => break,
+            },
+            c => unquoted.push(c),
+        }
+    }
+    Err(FilesetParseError::UnterminatedQuote)
+}
+
+/// Parses the given `text` as a single file pattern, which may be prefixed
+/// with the pattern kind such as `glob:`. Text without a known prefix is a
+/// path relative to the current directory.
+pub fn parse_pattern(
+    text: &str,
+    path_converter: &RepoPathUiConverter,
+) -> FilesetParseResult<FilesetExpression> {
+    match text.split_once(':') {
+        Some((kind, value)) if is_pattern_kind(kind) => {
+            parse_pattern_kind(Some(kind), value, path_converter)
+        }
+        _ => parse_pattern_kind(None, text, path_converter),
+    }
+}
+
+fn is_pattern_kind(kind: &str) -> bool {
+    matches!(kind, "cwd" | "root" | "glob" | "root-glob")
+}
+
+fn parse_pattern_kind(
+    kind: Option<&str>,
+    text: &str,
+    path_converter: &RepoPathUiConverter,
+) -> FilesetParseResult<FilesetExpression> {
+    if text.is_empty() {
+        return Err(FilesetParseError::EmptyPattern);
+    }
+    let RepoPathUiConverter::Fs { cwd, base } = path_converter;
+    let pattern = match kind {
+        None | Some("cwd") => FilePattern::PrefixPath(RepoPathBuf::parse_fs_path(cwd, base, text)?),
+        Some("root") => FilePattern::PrefixPath(RepoPathBuf::parse_fs_path(base, base, text)?),
+        Some("glob") => parse_glob(cwd, base, text)?,
+        Some("root-glob") => parse_glob(base, base, text)?,
+        Some(kind) => return Err(FilesetParseError::InvalidKind(kind.to_owned())),
+    };
+    Ok(FilesetExpression::Pattern(pattern))
+}
+
+/// Parses the glob pattern relative to `cwd`. Components before the first
+/// one containing any of `*`, `?`, or `[` are parsed as the base directory.
+fn parse_glob(cwd: &Path, base: &Path, text: &str) -> FilesetParseResult<FilePattern> {
+    let glob_start = text
+        .split_inclusive('/')
+        .scan(0, |pos, component| {
+            let start = *pos;
+            *pos += component.len();
+            Some((start, component))
+        })
+        .find(|(_, component)| component.contains(['*', '?', '[']))
+        .map_or(0, |(start, _)| start);
+    let (dir, pattern) = text.split_at(glob_start);
+    let dir = RepoPathBuf::parse_fs_path(cwd, base, if dir.is_empty() { "." } else { dir })?;
+    let pattern = glob::Pattern::new(pattern)?;
+    Ok(FilePattern::FileGlob { dir, pattern })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::repo_path::RepoPath;
+
+    fn repo_path_buf(value: &str) -> RepoPathBuf {
+        RepoPathBuf::from_internal_string(value)
+    }
+
+    fn converter() -> RepoPathUiConverter {
+        RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        }
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        let converter = converter();
+        let parse = |text| match parse_pattern(text, &converter)? {
+            FilesetExpression::Pattern(pattern) => Ok(pattern),
+            expression => panic!("unexpected expression {expression:?}"),
+        };
+        let prefix_path = |path| FilePattern::PrefixPath(repo_path_buf(path));
+        let file_glob = |dir, pattern| FilePattern::FileGlob {
+            dir: repo_path_buf(dir),
+            pattern: glob::Pattern::new(pattern).unwrap(),
+        };
+
+        // Plain paths are literal
+        assert_eq!(parse("foo").unwrap(), prefix_path("cur/foo"));
+        assert_eq!(parse("../foo").unwrap(), prefix_path("foo"));
+        assert_eq!(parse("*.rs").unwrap(), prefix_path("cur/*.rs"));
+        assert_eq!(parse("file[").unwrap(), prefix_path("cur/file["));
+        assert_eq!(parse("a|b").unwrap(), prefix_path("cur/a|b"));
+        assert_eq!(parse("foo:bar").unwrap(), prefix_path("cur/foo:bar"));
+
+        assert_eq!(parse("cwd:foo").unwrap(), prefix_path("cur/foo"));
+        assert_eq!(parse("root:foo/bar").unwrap(), prefix_path("foo/bar"));
+        assert_eq!(parse("glob:*.rs").unwrap(), file_glob("cur", "*.rs"));
+        assert_eq!(parse("glob:foo").unwrap(), file_glob("cur", "foo"));
+        assert_eq!(parse("glob:../*/foo").unwrap(), file_glob("", "*/foo"));
+        assert_eq!(
+            parse("root-glob:infra/**").unwrap(),
+            file_glob("infra", "**")
+        );
+        assert_eq!(parse("root-glob:*.md").unwrap(), file_glob("", "*.md"));
+
+        assert_matches!(parse(""), Err(FilesetParseError::EmptyPattern));
+        assert_matches!(parse("glob:"), Err(FilesetParseError::EmptyPattern));
+        assert_matches!(parse("../.."), Err(FilesetParseError::InvalidPath(_)));
+        assert_matches!(parse("glob:[a"), Err(FilesetParseError::GlobPattern(_)));
+    }
+
+    #[test]
+    fn test_split_union_terms() {
+        let split = |text| split_union_terms(text);
+        let quoted = |prefix, text: &str| UnionTerm::Quoted(prefix, text.to_owned());
+        assert_eq!(split("").unwrap(), [UnionTerm::Bare("")]);
+        assert_eq!(
+            split(" foo | bar baz ").unwrap(),
+            [UnionTerm::Bare("foo"), UnionTerm::Bare("bar baz")]
+        );
+        assert_eq!(
+            split(r#""a|b" | glob:" *.rs" | "q\"\\\n""#).unwrap(),
+            [
+                quoted(None, "a|b"),
+                quoted(Some("glob"), " *.rs"),
+                quoted(None, r#"q"\\n"#),
+            ]
+        );
+        assert_matches!(split(r#""foo"#), Err(FilesetParseError::UnterminatedQuote));
+        assert_matches!(
+            split(r#""foo" bar"#),
+            Err(FilesetParseError::UnexpectedCharacters)
+        );
+        assert_matches!(
+            split(r#"foo"bar""#),
+            Err(FilesetParseError::UnexpectedCharacters)
+        );
+    }
+
+    #[test]
+    fn test_parse_and_match() {
+        let converter = converter();
+        let matcher = |text| parse(text, &converter).unwrap().to_matcher();
+        let matches = |text, path| matcher(text).matches(RepoPath::from_internal_string(path));
+
+        assert!(matches("root-glob:infra/**", "infra/a/b.tf"));
+        assert!(!matches("root-glob:infra/**", "other/infra/b.tf"));
+        assert!(matches("root-glob:*.md", "README.md"));
+        assert!(!matches("root-glob:*.md", "docs/README.md"));
+        assert!(matches("foo | root:bar", "cur/foo/x"));
+        assert!(matches("foo | root:bar", "bar"));
+        assert!(!matches("foo | root:bar", "foo"));
+        assert!(matches("foo | all()", "anything"));
+        assert!(matches(r#""a|b""#, "cur/a|b"));
+        assert!(matches(r#"root:"all()""#, "all()"));
+        assert!(!matches(r#"root:"all()""#, "anything"));
+        assert_eq!(
+            parse("foo|bar", &converter).unwrap(),
+            FilesetExpression::UnionAll(vec![
+                FilesetExpression::Pattern(FilePattern::PrefixPath(repo_path_buf("cur/foo"))),
+                FilesetExpression::Pattern(FilePattern::PrefixPath(repo_path_buf("cur/bar"))),
+            ])
+        );
+        assert_matches!(
+            parse(r#"foo:"bar""#, &converter),
+            Err(FilesetParseError::InvalidKind(kind)) if kind == "foo"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cache of file ids keyed by file fingerprints, shared by the workspaces of a
+//! repo.
+//!
+//! When snapshotting, a file whose path, size, and mtime match a fingerprint
+//! recorded by any workspace is assumed to have the recorded content, so it
+//! doesn't have to be read and written to the backend again. This helps with
+//! large generated files which are identical across workspaces. Nothing
+//! specific to a workspace, such as the inode number, is part of the
+//! fingerprint, since it would never match in another workspace.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+use crate::backend::{FileId, MillisSinceEpoch};
+use crate::lock::FileLock;
+use crate::object_id::ObjectId;
+use crate::repo_path::RepoPathBuf;
+
+const CACHE_FILE_NAME: &str = "fingerprint_cache";
+const CACHE_LOCK_FILE_NAME: &str = "fingerprint_cache.lock";
+/// Maximum number of entries kept in the cache file.
+const MAX_ENTRIES: usize = 100_000;
+
+/// Identifies the content of a file on disk without reading it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct FileFingerprint {
+    /// Path of the file relative to the workspace root.
+    pub path: RepoPathBuf,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// Modification time of the file.
+    pub mtime: MillisSinceEpoch,
+}
+
+impl FileFingerprint {
+    /// Whether the file was changed at or after `time`, in which case it may
+    /// still be changed without its fingerprint changing.
+    fn changed_since(&self, time: MillisSinceEpoch) -> bool {
+        self.mtime >= time
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct CacheData {
+    entries: Vec<CacheEntry>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct CacheEntry {
+    path: String,
+    size: u64,
+    mtime: i64,
+    file_id: String,
+}
+
+/// File ids of previously snapshotted files, keyed by their fingerprints.
+#[derive(Debug)]
+pub struct FileFingerprintCache {
+    path: PathBuf,
+    snapshot_started_at: MillisSinceEpoch,
+    entries: Mutex<HashMap<FileFingerprint, FileId>>,
+    new_entries: Mutex<HashMap<FileFingerprint, FileId>>,
+}
+
+impl FileFingerprintCache {
+    /// Loads the cache of the repo at `repo_path`. A missing or unreadable
+    /// cache file results in an empty cache.
+    ///
+    /// The cache should be loaded right before snapshotting, since files
+    /// changed after it was loaded are considered dirty.
+    pub fn load(repo_path: &Path) -> Self {
+        let path = repo_path.join(CACHE_FILE_NAME);
+        let snapshot_started_at = MillisSinceEpoch(
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as i64,
+        );
+        FileFingerprintCache {
+            entries: Mutex::new(read_entries(&path)),
+            new_entries: Mutex::new(HashMap::new()),
+            path,
+            snapshot_started_at,
+        }
+    }
+
+    /// Returns the id of the file content recorded for the `fingerprint`.
+    ///
+    /// Files changed after the snapshot started are dirty, since they may be
+    /// changed again without changing their fingerprint.
+    pub fn get(&self, fingerprint: &FileFingerprint) -> Option<FileId> {
+        if fingerprint.changed_since(self.snapshot_started_at) {
+            return None;
+        }
+        self.entries.lock().unwrap().get(fingerprint).cloned()
+    }
+
+    /// Records that the file identified by `fingerprint` has the content
+    /// `file_id`.
+    ///
+    /// Files changed after the snapshot started are ignored since they may be
+    /// changed again without changing their fingerprint.
+    pub fn insert(&self, fingerprint: FileFingerprint, file_id: FileId) {
+        if fingerprint.changed_since(self.snapshot_started_at) {
+            return;
+        }
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(fingerprint.clone(), file_id.clone());
+        self.new_entries
+            .lock()
+            .unwrap()
+            .insert(fingerprint, file_id);
+    }
+
+    /// Writes the entries recorded by this process to the cache file, merged
+    /// with the entries written there by other processes in the meantime.
+    ///
+    /// The cache is shared by all workspaces, so the file is locked while the
+    /// entries are merged and the new file is written.
+    pub fn save(&self) -> io::Result<()> {
+        let new_entries = self.new_entries.lock().unwrap();
+        if new_entries.is_empty() {
+            return Ok(());
+        }
+        let dir = self.path.parent().unwrap();
+        let _lock = FileLock::lock(dir.join(CACHE_LOCK_FILE_NAME)).map_err(io::Error::other)?;
+        let mut entries = read_entries(&self.path);
+        if entries.len() + new_entries.len() > MAX_ENTRIES {
+            entries.clear();
+        }
+        entries.extend(
+            new_entries
+                .iter()
+                .map(|(fingerprint, file_id)| (fingerprint.clone(), file_id.clone())),
+        );
+        let data = CacheData {
+            entries: entries
+                .into_iter()
+                .map(|(fingerprint, file_id)| CacheEntry {
+                    path: fingerprint.path.into_internal_string(),
+                    size: fingerprint.size,
+                    mtime: fingerprint.mtime.0,
+                    file_id: file_id.hex(),
+                })
+                .collect(),
+        };
+        let mut temp_file = NamedTempFile::new_in(dir)?;
+        temp_file
+            .as_file_mut()
+            .write_all(&serde_json::to_vec(&data).unwrap())?;
+        temp_file.persist(&self.path).map_err(|err| err.error)?;
+        Ok(())
+    }
+
+    /// Removes the cache file of the repo at `repo_path`. This must be done
+    /// when unreachable file contents may have been removed from the backend.
+    pub fn clear(repo_path: &Path) -> io::Result<()> {
+        match fs::remove_file(repo_path.join(CACHE_FILE_NAME)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
+
+fn read_entries(path: &Path) -> HashMap<FileFingerprint, FileId> {
+    let Ok(content) = fs::read(path) else {
+        return HashMap::new();
+    };
+    let data: CacheData = serde_json::from_slice(&content).unwrap_or_default();
+    data.entries
+        .into_iter()
+        .filter_map(|entry| {
+            let fingerprint = FileFingerprint {
+                path: RepoPathBuf::from_internal_string(entry.path),
+                size: entry.size,
+                mtime: MillisSinceEpoch(entry.mtime),
+            };
+            let file_id = FileId::try_from_hex(&entry.file_id).ok()?;
+            Some((fingerprint, file_id))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(path: &str, mtime: i64) -> FileFingerprint {
+        FileFingerprint {
+            path: RepoPathBuf::from_internal_string(path),
+            size: 3,
+            mtime: MillisSinceEpoch(mtime),
+        }
+    }
+
+    #[test]
+    fn test_save_merges_entries_from_other_processes() {
+        let temp_dir = testutils::new_temp_dir();
+        let repo_path = temp_dir.path();
+        let cache1 = FileFingerprintCache::load(repo_path);
+        let cache2 = FileFingerprintCache::load(repo_path);
+        cache1.insert(fingerprint("a", 1), FileId::from_hex("aa"));
+        cache2.insert(fingerprint("b", 1), FileId::from_hex("bb"));
+        // Files modified after the cache was loaded aren't recorded.
+        cache2.insert(fingerprint("c", i64::MAX), FileId::from_hex("cc"));
+        assert_eq!(cache2.get(&fingerprint("c", i64::MAX)), None);
+        cache1.save().unwrap();
+        cache2.save().unwrap();
+
+        let cache = FileFingerprintCache::load(repo_path);
+        assert_eq!(
+            cache.get(&fingerprint("a", 1)),
+            Some(FileId::from_hex("aa"))
+        );
+        assert_eq!(
+            cache.get(&fingerprint("b", 1)),
+            Some(FileId::from_hex("bb"))
+        );
+        assert_eq!(cache.get(&fingerprint("a", 2)), None);
+        // Files changed after the snapshot started are dirty even if they have a
+        // recorded fingerprint.
+        let changed = fingerprint("d", i64::MAX);
+        cache1
+            .entries
+            .lock()
+            .unwrap()
+            .insert(changed.clone(), FileId::from_hex("dd"));
+        assert_eq!(cache1.get(&changed), None);
+
+        FileFingerprintCache::clear(repo_path).unwrap();
+        FileFingerprintCache::clear(repo_path).unwrap();
+        let cache = FileFingerprintCache::load(repo_path);
+        assert_eq!(cache.get(&fingerprint("a", 1)), None);
+    }
+}
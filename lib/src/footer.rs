@@ -0,0 +1,524 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and editing of footer lines (also known as trailers) in commit
+//! descriptions.
+//!
+//! The footer is the last paragraph of a description if every line in it
+//! looks like `Key: value`, or continues the value of the previous line by
+//! starting with whitespace. It may also contain other lines if it has a line
+//! generated by git, such as `Signed-off-by: `, and at least 25% of its lines
+//! are footer lines. The first paragraph is the subject, and is never
+//! considered a footer. These rules follow `git interpret-trailers`.
+
+use std::borrow::Cow;
+
+/// Characters that separate the key from the value by default. This matches
+/// the default of git's `trailer.separators` config.
+pub const DEFAULT_SEPARATORS: &str = ":";
+
+/// A `Key: value` entry in the footer of a description.
+///
+/// If the value is continued on the following lines, they are unfolded into a
+/// single line separated by spaces.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FooterEntry<'a> {
+    /// The key, as written in the description.
+    pub key: &'a str,
+    /// The value, with continuation lines unfolded.
+    pub value: Cow<'a, str>,
+}
+
+/// Parses a single `Key: value` footer line.
+///
+/// The key must be non-empty and consist of ASCII alphanumerics and `-`.
+/// Whitespace around the separator is ignored.
+pub fn parse_footer_line(line: &str) -> Option<FooterEntry<'_>> {
+    parse_footer_line_with_separators(line, DEFAULT_SEPARATORS)
+}
+
+/// Parses a single footer line whose key and value are separated by any of the
+/// `separators` characters, e.g. `":#"` to also accept `Bug #123`.
+pub fn parse_footer_line_with_separators<'a>(
+    line: &'a str,
+    separators: &str,
+) -> Option<FooterEntry<'a>> {
+    let (pos, separator) = line.char_indices().find(|&(_, c)| separators.contains(c))?;
+    let key = line[..pos].trim_end();
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    Some(FooterEntry {
+        key,
+        value: Cow::Borrowed(line[pos + separator.len_utf8()..].trim()),
+    })
+}
+
+/// Prefixes of the footer lines added by git itself, e.g. by `git commit
+/// --signoff` or `git cherry-pick -x`. A paragraph with one of them is a footer
+/// even if some of its lines aren't footer lines.
+const GIT_GENERATED_PREFIXES: &[&str] = &["Signed-off-by: ", "(cherry picked from commit "];
+
+fn is_continuation_line(line: &str) -> bool {
+    line.starts_with([' ', '\t'])
+}
+
+/// Whether the last `paragraph` of a description is a footer. Like git, lines
+/// are counted from the end so that continuation lines are attributed to the
+/// footer line before them.
+fn is_footer_paragraph(paragraph: &str, separators: &str) -> bool {
+    let mut footer_lines = 0;
+    let mut other_lines = 0;
+    let mut possible_continuation_lines = 0;
+    let mut has_git_generated_line = false;
+    for line in paragraph.lines().rev() {
+        if GIT_GENERATED_PREFIXES
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+        {
+            footer_lines += 1;
+            possible_continuation_lines = 0;
+            has_git_generated_line = true;
+        } else if parse_footer_line_with_separators(line, separators).is_some() {
+            footer_lines += 1;
+            possible_continuation_lines = 0;
+        } else if is_continuation_line(line) {
+            possible_continuation_lines += 1;
+        } else {
+            other_lines += 1 + possible_continuation_lines;
+            possible_continuation_lines = 0;
+        }
+    }
+    // Continuation lines at the start of the paragraph don't continue anything.
+    other_lines += possible_continuation_lines;
+    (has_git_generated_line && footer_lines * 3 >= other_lines)
+        || (footer_lines > 0 && other_lines == 0)
+}
+
+/// Splits the `description` into the text before the footer and the footer
+/// paragraph.
+///
+/// Trailing newlines are not included in either part. If the description has
+/// no footer, the footer part is empty.
+pub fn split_footer(description: &str) -> (&str, &str) {
+    split_footer_with_separators(description, DEFAULT_SEPARATORS)
+}
+
+/// Like [`split_footer()`], but footer lines may use any of the `separators`.
+pub fn split_footer_with_separators<'a>(
+    description: &'a str,
+    separators: &str,
+) -> (&'a str, &'a str) {
+    let description = description.trim_end_matches('\n');
+    let Some(pos) = description.rfind("\n\n") else {
+        return (description, "");
+    };
+    let paragraph = &description[pos + 2..];
+    if is_footer_paragraph(paragraph, separators) {
+        (description[..pos].trim_end_matches('\n'), paragraph)
+    } else {
+        (description, "")
+    }
+}
+
+/// Splits the `footer` paragraph into blocks, each of which is a line followed
+/// by its continuation lines. Blocks that aren't footer entries are skipped
+/// when parsing.
+fn footer_blocks(footer: &str) -> Vec<&str> {
+    let mut blocks = vec![];
+    let mut start = 0;
+    for (pos, _) in footer.match_indices('\n') {
+        if !is_continuation_line(&footer[pos + 1..]) {
+            blocks.push(&footer[start..pos]);
+            start = pos + 1;
+        }
+    }
+    if start < footer.len() {
+        blocks.push(&footer[start..]);
+    }
+    blocks
+}
+
+fn parse_footer_block<'a>(block: &'a str, separators: &str) -> Option<FooterEntry<'a>> {
+    let mut lines = block.lines();
+    let mut entry = parse_footer_line_with_separators(lines.next()?, separators)?;
+    for line in lines.map(str::trim).filter(|line| !line.is_empty()) {
+        let value = entry.value.to_mut();
+        if !value.is_empty() {
+            value.push(' ');
+        }
+        value.push_str(line);
+    }
+    Some(entry)
+}
+
+/// Returns the entries in the footer of the `description`, in order.
+pub fn parse_footers(description: &str) -> Vec<FooterEntry<'_>> {
+    parse_footers_with_separators(description, DEFAULT_SEPARATORS)
+}
+
+/// Like [`parse_footers()`], but footer lines may use any of the
+/// `separators`.
+pub fn parse_footers_with_separators<'a>(
+    description: &'a str,
+    separators: &str,
+) -> Vec<FooterEntry<'a>> {
+    let (_, footer) = split_footer_with_separators(description, separators);
+    footer_blocks(footer)
+        .into_iter()
+        .filter_map(|block| parse_footer_block(block, separators))
+        .collect()
+}
+
+/// Returns the value of the last footer entry whose key matches `key`
+/// case-insensitively.
+pub fn find_footer<'a>(description: &'a str, key: &str) -> Option<Cow<'a, str>> {
+    parse_footers(description)
+        .into_iter()
+        .rev()
+        .find(|entry| entry.key.eq_ignore_ascii_case(key))
+        .map(|entry| entry.value)
+}
+
+/// Returns the value of the Gerrit-style `Change-Id` footer, if any.
+pub fn find_change_id(description: &str) -> Option<Cow<'_, str>> {
+    find_footer(description, "Change-Id").filter(|change_id| !change_id.is_empty())
+}
+
+/// Appends a `key: value` line to the footer of the `description`, starting
+/// a new footer paragraph if there is none.
+///
+/// An empty description becomes just the footer line.
+pub fn add_footer_line(description: &str, key: &str, value: &str) -> String {
+    let (message, footer) = split_footer(description);
+    let new_line = format!("{key}: {value}");
+    join_footer(
+        message,
+        footer_blocks(footer).into_iter().chain([new_line.as_str()]),
+    )
+}
+
+/// Removes all footer entries whose key matches `key` case-insensitively,
+/// including their continuation lines. The footer paragraph is removed if it
+/// becomes empty.
+pub fn remove_footer(description: &str, key: &str) -> String {
+    let (message, footer) = split_footer(description);
+    let blocks = footer_blocks(footer);
+    if !blocks.iter().any(|block| footer_block_has_key(block, key)) {
+        return description.to_owned();
+    }
+    join_footer(
+        message,
+        blocks
+            .into_iter()
+            .filter(|block| !footer_block_has_key(block, key)),
+    )
+}
+
+/// Sets the value of the footer `key`. The first matching entry is updated in
+/// place and any other matching entries are removed. If there is no matching
+/// entry, one is appended as by [`add_footer_line()`].
+pub fn set_footer(description: &str, key: &str, value: &str) -> String {
+    let (message, footer) = split_footer(description);
+    let blocks = footer_blocks(footer);
+    let Some(pos) = blocks
+        .iter()
+        .position(|block| footer_block_has_key(block, key))
+    else {
+        return add_footer_line(description, key, value);
+    };
+    let existing_key = parse_footer_line(blocks[pos]).unwrap().key;
+    let new_line = format!("{existing_key}: {value}");
+    let lines = blocks.iter().enumerate().filter_map(|(i, &block)| {
+        if i == pos {
+            Some(new_line.as_str())
+        } else if footer_block_has_key(block, key) {
+            None
+        } else {
+            Some(block)
+        }
+    });
+    join_footer(message, lines)
+}
+
+fn footer_block_has_key(block: &str, key: &str) -> bool {
+    parse_footer_line(block).is_some_and(|entry| entry.key.eq_ignore_ascii_case(key))
+}
+
+/// Builds a description from the `message` without footer and the footer
+/// `lines`, ending with a newline.
+fn join_footer<'a>(message: &str, lines: impl IntoIterator<Item = &'a str>) -> String {
+    let mut description = message.to_owned();
+    let mut lines = lines.into_iter().peekable();
+    if lines.peek().is_some() && !description.is_empty() {
+        description.push_str("\n\n");
+    } else if !description.is_empty() {
+        description.push('\n');
+    }
+    for line in lines {
+        description.push_str(line);
+        description.push('\n');
+    }
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_footer_line() {
+        assert_eq!(
+            parse_footer_line("Signed-off-by: Foo <foo@example.com>"),
+            Some(FooterEntry {
+                key: "Signed-off-by",
+                value: "Foo <foo@example.com>".into(),
+            })
+        );
+        assert_eq!(
+            parse_footer_line("Key:"),
+            Some(FooterEntry {
+                key: "Key",
+                value: "".into(),
+            })
+        );
+        assert_eq!(parse_footer_line("no colon"), None);
+        assert_eq!(parse_footer_line(": value"), None);
+        assert_eq!(parse_footer_line("two words: value"), None);
+    }
+
+    #[test]
+    fn test_parse_footer_line_with_separators() {
+        assert_eq!(
+            parse_footer_line_with_separators("Bug #123", ":#"),
+            Some(FooterEntry {
+                key: "Bug",
+                value: "123".into(),
+            })
+        );
+        assert_eq!(parse_footer_line_with_separators("Bug #123", ":"), None);
+        // The first separator character wins
+        assert_eq!(
+            parse_footer_line_with_separators("Key: a#b", ":#"),
+            Some(FooterEntry {
+                key: "Key",
+                value: "a#b".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_split_footer() {
+        assert_eq!(split_footer(""), ("", ""));
+        assert_eq!(split_footer("subject\n"), ("subject", ""));
+        // The subject is never a footer
+        assert_eq!(split_footer("Key: value\n"), ("Key: value", ""));
+        assert_eq!(
+            split_footer("subject\n\nbody\n\nKey: value\nOther-Key: other\n"),
+            ("subject\n\nbody", "Key: value\nOther-Key: other")
+        );
+        assert_eq!(
+            split_footer("subject\n\n\nKey: value\n\n"),
+            ("subject", "Key: value")
+        );
+        // Mixed paragraph isn't a footer
+        assert_eq!(
+            split_footer("subject\n\nKey: value\nnot a footer\n"),
+            ("subject\n\nKey: value\nnot a footer", "")
+        );
+    }
+
+    #[test]
+    fn test_find_footer() {
+        let description = "subject\n\nChange-Id: I1\nfoo: bar\nchange-id: I2\n";
+        assert_eq!(find_footer(description, "foo").as_deref(), Some("bar"));
+        assert_eq!(find_footer(description, "Change-Id").as_deref(), Some("I2"));
+        assert_eq!(find_footer(description, "missing").as_deref(), None);
+        assert_eq!(find_change_id(description).as_deref(), Some("I2"));
+        assert_eq!(find_change_id("subject\n\nChange-Id:\n").as_deref(), None);
+        assert_eq!(
+            find_change_id("subject\n\nChange-Id: I1\n\nbody\n").as_deref(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_add_footer_line() {
+        assert_eq!(add_footer_line("", "Key", "value"), "Key: value\n");
+        assert_eq!(
+            add_footer_line("subject\n", "Key", "value"),
+            "subject\n\nKey: value\n"
+        );
+        assert_eq!(
+            add_footer_line("subject\n\nbody\n  indented\n", "Key", "value"),
+            "subject\n\nbody\n  indented\n\nKey: value\n"
+        );
+        // Existing footer is extended, and its order is kept
+        assert_eq!(
+            add_footer_line("subject\n\nB: 1\nA: 2\n", "A", "3"),
+            "subject\n\nB: 1\nA: 2\nA: 3\n"
+        );
+        // Mixed last paragraph isn't a footer
+        assert_eq!(
+            add_footer_line("subject\n\nA: 1\ntext\n", "A", "2"),
+            "subject\n\nA: 1\ntext\n\nA: 2\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_footer() {
+        // Unchanged if there's nothing to remove
+        assert_eq!(remove_footer("", "Key"), "");
+        assert_eq!(remove_footer("subject", "Key"), "subject");
+        assert_eq!(
+            remove_footer("subject\n\nOther: value\n\n", "Key"),
+            "subject\n\nOther: value\n\n"
+        );
+        assert_eq!(
+            remove_footer("subject\n\nA: 1\nkey: 2\nB: 3\nKey: 4\n", "Key"),
+            "subject\n\nA: 1\nB: 3\n"
+        );
+        // The footer paragraph is removed with its last line
+        assert_eq!(
+            remove_footer("subject\n\nbody\n\nKey: value\n", "Key"),
+            "subject\n\nbody\n"
+        );
+    }
+
+    #[test]
+    fn test_set_footer() {
+        assert_eq!(
+            set_footer("subject\n", "Change-Id", "I1"),
+            "subject\n\nChange-Id: I1\n"
+        );
+        // The first matching line is updated in place, keeping its key
+        assert_eq!(
+            set_footer(
+                "subject\n\nA: 1\nchange-id: I1\nB: 2\nChange-Id: I2\n",
+                "Change-Id",
+                "I3"
+            ),
+            "subject\n\nA: 1\nchange-id: I3\nB: 2\n"
+        );
+        assert_eq!(
+            set_footer("subject\n\nA: 1\n", "B", "2"),
+            "subject\n\nA: 1\nB: 2\n"
+        );
+    }
+
+    // The expected values below are what `git interpret-trailers --parse`
+    // prints for the same input.
+    #[test]
+    fn test_git_trailer_compatibility() {
+        let footers = |description| {
+            parse_footers(description)
+                .iter()
+                .map(|entry| format!("{}: {}", entry.key, entry.value))
+                .collect::<Vec<_>>()
+        };
+        // Whitespace around the separator
+        assert_eq!(
+            footers("subject\n\nKey : value\nOther:value\n"),
+            ["Key: value", "Other: value"]
+        );
+        // Empty value
+        assert_eq!(footers("subject\n\nKey:\n"), ["Key: "]);
+        // Continuation lines are unfolded with a single space
+        assert_eq!(
+            footers("subject\n\nSigned-off-by: A\n  continued\n\there\nB: 1\n"),
+            ["Signed-off-by: A continued here", "B: 1"]
+        );
+        assert_eq!(footers("subject\n\nKey: a\n   \n"), ["Key: a"]);
+        // A paragraph can't start with a continuation line
+        assert!(footers("subject\n\n  indented\nKey: value\n").is_empty());
+        // Keys can't contain whitespace
+        assert!(footers("subject\n\ntwo words: x\n").is_empty());
+        // Only the last paragraph is the footer
+        assert_eq!(footers("subject\n\nA: 1\n\nB: 2\n"), ["B: 2"]);
+        assert!(footers("subject\n\nA: 1\n\nbody\n").is_empty());
+        // Configured separators
+        let entries = parse_footers_with_separators("subject\n\nBug #123\nKey: v\n", ":#");
+        assert_eq!(
+            entries,
+            [
+                FooterEntry {
+                    key: "Bug",
+                    value: "123".into(),
+                },
+                FooterEntry {
+                    key: "Key",
+                    value: "v".into(),
+                },
+            ]
+        );
+        assert!(parse_footers("subject\n\nBug #123\n").is_empty());
+    }
+
+    #[test]
+    fn test_git_trailer_compatibility_git_generated() {
+        let footers = |description| {
+            parse_footers(description)
+                .iter()
+                .map(|entry| format!("{}: {}", entry.key, entry.value))
+                .collect::<Vec<_>>()
+        };
+        // A paragraph with a git-generated line can have other lines if at
+        // least 25% of its lines are footer lines
+        assert_eq!(
+            footers("subject\n\nSigned-off-by: A\ntext\n  more\nKey: v\n"),
+            ["Signed-off-by: A", "Key: v"]
+        );
+        assert_eq!(
+            footers("subject\n\ntext 1\ntext 2\ntext 3\nSigned-off-by: A\n"),
+            ["Signed-off-by: A"]
+        );
+        assert!(
+            footers("subject\n\ntext 1\ntext 2\ntext 3\ntext 4\nSigned-off-by: A\n").is_empty()
+        );
+        // Other keys don't allow other lines
+        assert!(footers("subject\n\nKey: v\ntext\n").is_empty());
+        // The cherry-pick line isn't an entry, but makes the paragraph a footer
+        assert_eq!(
+            footers("subject\n\n(cherry picked from commit abc)\ntext\nKey: v\n"),
+            ["Key: v"]
+        );
+        assert_eq!(
+            split_footer("subject\n\ntext\nSigned-off-by: A\n"),
+            ("subject", "text\nSigned-off-by: A")
+        );
+        // The footer is extended even if it has other lines
+        assert_eq!(
+            add_footer_line("subject\n\ntext\nSigned-off-by: A\n", "Key", "v"),
+            "subject\n\ntext\nSigned-off-by: A\nKey: v\n"
+        );
+    }
+
+    #[test]
+    fn test_edit_footer_with_continuation() {
+        let description = "subject\n\nA: 1\nKey: long\n  value\nB: 2\n";
+        assert_eq!(
+            find_footer(description, "key").as_deref(),
+            Some("long value")
+        );
+        assert_eq!(remove_footer(description, "Key"), "subject\n\nA: 1\nB: 2\n");
+        assert_eq!(
+            set_footer(description, "Key", "short"),
+            "subject\n\nA: 1\nKey: short\nB: 2\n"
+        );
+        assert_eq!(
+            add_footer_line(description, "C", "3"),
+            "subject\n\nA: 1\nKey: long\n  value\nB: 2\nC: 3\n"
+        );
+    }
+}
@@ -26,15 +26,16 @@ use itertools::Itertools;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
-use crate::backend::{BackendError, CommitId};
+use crate::backend::{BackendError, ChangeId, CommitId};
 use crate::commit::Commit;
+use crate::footer;
 use crate::git_backend::GitBackend;
 use crate::object_id::ObjectId;
 use crate::op_store::{RefTarget, RefTargetOptionExt, RemoteRef, RemoteRefState};
 use crate::refs::BranchPushUpdate;
 use crate::repo::{MutableRepo, Repo};
-use crate::revset::RevsetExpression;
-use crate::settings::GitSettings;
+use crate::revset::{RevsetEvaluationError, RevsetExpression};
+use crate::settings::{GitSettings, UserSettings};
 use crate::store::Store;
 use crate::str_util::StringPattern;
 use crate::view::View;
@@ -205,6 +206,9 @@ impl GitImportError {
 pub struct GitImportStats {
     /// Commits superseded by newly imported commits.
     pub abandoned_commits: Vec<CommitId>,
+    /// Local `(old_commit, new_commit)`s superseded by newly imported commits
+    /// with the same change id or `Change-Id` footer.
+    pub reassociated_commits: Vec<(CommitId, CommitId)>,
     /// Remote `(ref_name, (old_remote_ref, new_target))`s to be merged in to
     /// the local refs.
     pub changed_remote_refs: BTreeMap<RefName, (RemoteRef, RefTarget)>,
@@ -260,6 +264,7 @@ pub fn import_some_refs(
         .flat_map(|(_, new_target)| new_target.added_ids())
         .filter(|&id| !index.has_id(id));
     let heads_imported = git_backend.import_head_commits(missing_head_ids).is_ok();
+    let old_heads = mut_repo.view().heads().iter().cloned().collect_vec();
 
     // Import new remote heads
     let mut head_commits = Vec::new();
@@ -324,6 +329,8 @@ pub fn import_some_refs(
         }
     }
 
+    let reassociated_commits =
+        reassociate_rewritten_commits(mut_repo, git_settings, &old_heads, &changed_remote_refs)?;
     let abandoned_commits = if git_settings.abandon_unreachable_commits {
         abandon_unreachable_commits(mut_repo, &changed_remote_refs)
     } else {
@@ -331,11 +338,107 @@ pub fn import_some_refs(
     };
     let stats = GitImportStats {
         abandoned_commits,
+        reassociated_commits,
         changed_remote_refs,
     };
     Ok(stats)
 }
 
+/// Finds local commits which were rewritten on the remote, and records them as
+/// rewritten to the newly imported commits.
+///
+/// A local commit is considered rewritten if it isn't reachable from any remote
+/// branch or tag, and it has the same change id or `Change-Id` footer as a
+/// commit imported from one of the remotes listed in
+/// `git_settings.reassociate_rewrites`. This is the case if the remote amended
+/// or rebased the commit, as Gerrit does.
+fn reassociate_rewritten_commits(
+    mut_repo: &mut MutableRepo,
+    git_settings: &GitSettings,
+    old_heads: &[CommitId],
+    changed_remote_refs: &BTreeMap<RefName, (RemoteRef, RefTarget)>,
+) -> Result<Vec<(CommitId, CommitId)>, GitImportError> {
+    let new_heads = changed_remote_refs
+        .iter()
+        .filter(|(ref_name, _)| match ref_name {
+            RefName::RemoteBranch { remote, .. } => {
+                git_settings.reassociate_rewrites.contains(remote)
+            }
+            RefName::LocalBranch(_) | RefName::Tag(_) => false,
+        })
+        .flat_map(|(_, (_, new_target))| new_target.added_ids())
+        .cloned()
+        .collect_vec();
+    if new_heads.is_empty() {
+        return Ok(vec![]);
+    }
+    let store = mut_repo.store().clone();
+
+    let imported_ids = RevsetExpression::commits(old_heads.to_vec())
+        .range(&RevsetExpression::commits(new_heads))
+        .evaluate_programmatic(mut_repo)
+        .map_err(revset_evaluation_to_import_error)?
+        .iter()
+        .collect_vec();
+    // Descendants come first. If the remote has multiple commits with the same
+    // change id or Change-Id footer, the newest one should win.
+    let mut imported_by_change_id: HashMap<ChangeId, CommitId> = HashMap::new();
+    let mut imported_by_footer: HashMap<String, CommitId> = HashMap::new();
+    for id in imported_ids {
+        let commit = store
+            .get_commit(&id)
+            .map_err(GitImportError::InternalBackend)?;
+        if let Some(change_id) = footer::find_change_id(commit.description()) {
+            imported_by_footer
+                .entry(change_id.into_owned())
+                .or_insert(id.clone());
+        }
+        imported_by_change_id
+            .entry(commit.change_id().clone())
+            .or_insert(id);
+    }
+
+    let local_ids =
+        RevsetExpression::remote_branches(StringPattern::everything(), StringPattern::everything())
+            .union(&RevsetExpression::tags())
+            .union(&RevsetExpression::root())
+            .range(&RevsetExpression::visible_heads())
+            .evaluate_programmatic(mut_repo)
+            .map_err(revset_evaluation_to_import_error)?
+            .iter()
+            .collect_vec();
+    let mut reassociated_commits = vec![];
+    for old_id in local_ids {
+        let commit = store
+            .get_commit(&old_id)
+            .map_err(GitImportError::InternalBackend)?;
+        let Some(new_id) = imported_by_change_id.get(commit.change_id()).or_else(|| {
+            footer::find_change_id(commit.description())
+                .and_then(|change_id| imported_by_footer.get(change_id.as_ref()))
+        }) else {
+            continue;
+        };
+        // Rewriting a commit to itself or its ancestor would drop its changes,
+        // and to its descendant would create a cycle.
+        let index = mut_repo.index();
+        if index.is_ancestor(&old_id, new_id) || index.is_ancestor(new_id, &old_id) {
+            continue;
+        }
+        reassociated_commits.push((old_id, new_id.clone()));
+    }
+    for (old_id, new_id) in &reassociated_commits {
+        mut_repo.set_rewritten_commit(old_id.clone(), new_id.clone());
+    }
+    Ok(reassociated_commits)
+}
+
+fn revset_evaluation_to_import_error(err: RevsetEvaluationError) -> GitImportError {
+    match err {
+        RevsetEvaluationError::StoreError(err) => GitImportError::InternalBackend(err),
+        RevsetEvaluationError::Other(message) => GitImportError::from_git(message),
+    }
+}
+
 /// Finds commits that used to be reachable in git that no longer are reachable.
 /// Those commits will be recorded as abandoned in the `MutableRepo`.
 fn abandon_unreachable_commits(
@@ -554,6 +657,113 @@ pub fn import_head(mut_repo: &mut MutableRepo) -> Result<(), GitImportError> {
     Ok(())
 }
 
+/// Imports commits recorded in the reflogs of the underlying Git repo.
+///
+/// Commits which aren't reachable from the current refs are indexed as hidden
+/// commits, so they can still be looked up by id. Returns the ids of the
+/// reflog entries which weren't indexed before.
+pub fn import_reflog(
+    mut_repo: &mut MutableRepo,
+    git_repo: &git2::Repository,
+) -> Result<Vec<CommitId>, GitImportError> {
+    let store = mut_repo.store().clone();
+    let git_backend = get_git_backend(&store).ok_or(GitImportError::UnexpectedBackend)?;
+
+    let mut git_ref_names = vec!["HEAD".to_owned()];
+    for git_ref in git_repo.references().map_err(GitImportError::from_git)? {
+        let git_ref = git_ref.map_err(GitImportError::from_git)?;
+        // Refs created by jj to keep imported commits alive have no reflog.
+        // Stash entries are imported by import_stash().
+        match git_ref.name() {
+            Some(name) if !name.starts_with("refs/jj/") && name != "refs/stash" => {
+                git_ref_names.push(name.to_owned());
+            }
+            _ => {}
+        }
+    }
+
+    let index = mut_repo.index();
+    let mut seen_ids = HashSet::new();
+    let mut new_ids = Vec::new();
+    for git_ref_name in &git_ref_names {
+        let reflog = git_repo
+            .reflog(git_ref_name)
+            .map_err(GitImportError::from_git)?;
+        for entry in reflog.iter() {
+            for oid in [entry.id_old(), entry.id_new()] {
+                let id = CommitId::from_bytes(oid.as_bytes());
+                if oid.is_zero() || index.has_id(&id) || !seen_ids.insert(id.clone()) {
+                    continue;
+                }
+                // Old entries may point to pruned objects, and reflogs of tags
+                // may point to non-commit objects.
+                if git_repo.find_commit(oid).is_ok() {
+                    new_ids.push(id);
+                }
+            }
+        }
+    }
+
+    git_backend
+        .import_head_commits(&new_ids)
+        .map_err(GitImportError::InternalBackend)?;
+    let commits: Vec<_> = new_ids
+        .iter()
+        .map(|id| store.get_commit(id))
+        .try_collect()
+        .map_err(GitImportError::InternalBackend)?;
+    mut_repo
+        .add_hidden_commits(&commits)
+        .map_err(GitImportError::InternalBackend)?;
+    Ok(new_ids)
+}
+
+/// Creates a commit for each entry of the Git stash, oldest first.
+///
+/// Each commit is a child of the commit the changes were stashed on, and
+/// contains the stashed working-copy changes. The stashed index and untracked
+/// files aren't imported.
+pub fn import_stash(
+    mut_repo: &mut MutableRepo,
+    git_repo: &git2::Repository,
+    settings: &UserSettings,
+) -> Result<Vec<Commit>, GitImportError> {
+    let store = mut_repo.store().clone();
+    let git_backend = get_git_backend(&store).ok_or(GitImportError::UnexpectedBackend)?;
+
+    let reflog = git_repo
+        .reflog("refs/stash")
+        .map_err(GitImportError::from_git)?;
+    // The reflog lists the latest stash entry first.
+    let stash_entries = reflog
+        .iter()
+        .map(|entry| {
+            let id = CommitId::from_bytes(entry.id_new().as_bytes());
+            let message = entry.message().unwrap_or_default().to_owned();
+            (id, message)
+        })
+        .collect_vec();
+    git_backend
+        .import_head_commits(stash_entries.iter().map(|(id, _)| id))
+        .map_err(GitImportError::InternalBackend)?;
+
+    let mut commits = Vec::new();
+    for (id, message) in stash_entries.iter().rev() {
+        let stash_commit = store
+            .get_commit(id)
+            .map_err(GitImportError::InternalBackend)?;
+        let base_ids = stash_commit.parent_ids()[..1].to_vec();
+        let commit = mut_repo
+            .new_commit(settings, base_ids, stash_commit.tree_id().clone())
+            .set_author(stash_commit.author().clone())
+            .set_description(format!("{message}\n"))
+            .write()
+            .map_err(GitImportError::InternalBackend)?;
+        commits.push(commit);
+    }
+    Ok(commits)
+}
+
 #[derive(Error, Debug)]
 pub enum GitExportError {
     #[error("Git error")]
@@ -1146,8 +1356,10 @@ pub fn fetch(
     }
     tracing::debug!("remote.download");
     remote.download(&refspecs, Some(&mut fetch_options))?;
-    tracing::debug!("remote.prune");
-    remote.prune(None)?;
+    if git_settings.fetch_prune {
+        tracing::debug!("remote.prune");
+        remote.prune(None)?;
+    }
     tracing::debug!("remote.update_tips");
     remote.update_tips(None, false, git2::AutotagOption::Unspecified, None)?;
     // TODO: We could make it optional to get the default branch since we only care
@@ -1193,14 +1405,25 @@ pub enum GitPushError {
     RemoteReservedForLocalGitRepo,
     #[error("Push is not fast-forwardable")]
     NotFastForward,
-    #[error("Remote rejected the update of some refs (do you have permission to push to {0:?}?)")]
-    RefUpdateRejected(Vec<String>),
+    #[error(
+        "Remote rejected the update of some refs (do you have permission to push to {:?}?)",
+        .0.iter().map(|update| &update.qualified_name).collect_vec()
+    )]
+    RefUpdateRejected(Vec<RejectedRefUpdate>),
     // TODO: I'm sure there are other errors possible, such as transport-level errors,
     // and errors caused by the remote rejecting the push.
     #[error("Unexpected git error when pushing")]
     InternalGitError(#[from] git2::Error),
 }
 
+/// Ref update which the remote didn't accept.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RejectedRefUpdate {
+    pub qualified_name: String,
+    /// Reason reported by the remote, if any.
+    pub reason: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct GitBranchPushTargets {
     pub branch_updates: Vec<(String, BranchPushUpdate)>,
@@ -1322,6 +1545,7 @@ fn push_refs(
         }
     })?;
     let mut remaining_remote_refs: HashSet<_> = qualified_remote_refs.iter().copied().collect();
+    let mut rejection_reasons: HashMap<String, String> = HashMap::new();
     let mut push_options = git2::PushOptions::new();
     let mut proxy_options = git2::ProxyOptions::new();
     proxy_options.auto();
@@ -1329,7 +1553,9 @@ fn push_refs(
     let mut callbacks = callbacks.into_git();
     callbacks.push_update_reference(|refname, status| {
         // The status is Some if the ref update was rejected
-        if status.is_none() {
+        if let Some(reason) = status {
+            rejection_reasons.insert(refname.to_owned(), reason.to_owned());
+        } else {
             remaining_remote_refs.remove(refname);
         }
         Ok(())
@@ -1351,7 +1577,10 @@ fn push_refs(
             remaining_remote_refs
                 .iter()
                 .sorted()
-                .map(|name| name.to_string())
+                .map(|name| RejectedRefUpdate {
+                    qualified_name: name.to_string(),
+                    reason: rejection_reasons.remove(*name),
+                })
                 .collect(),
         ))
     }
@@ -15,7 +15,7 @@
 #![allow(missing_docs)]
 
 use std::any::Any;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Debug, Error, Formatter};
 use std::io::{Cursor, Read};
 use std::path::{Path, PathBuf};
@@ -38,7 +38,7 @@ use crate::backend::{
     MillisSinceEpoch, SecureSig, Signature, SigningFn, SymlinkId, Timestamp, Tree, TreeId,
     TreeValue,
 };
-use crate::file_util::{IoResultExt as _, PathError};
+use crate::file_util::{FsyncPolicy, IoResultExt as _, PathError, PendingSyncs};
 use crate::index::Index;
 use crate::lock::FileLock;
 use crate::merge::{Merge, MergeBuilder};
@@ -63,6 +63,8 @@ pub enum GitBackendInitError {
     InitRepository(#[source] gix::init::Error),
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
+    #[error("Invalid store configuration")]
+    Config(#[source] config::ConfigError),
     #[error(transparent)]
     Path(PathError),
 }
@@ -77,6 +79,8 @@ impl From<Box<GitBackendInitError>> for BackendInitError {
 pub enum GitBackendLoadError {
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
+    #[error("Invalid store configuration")]
+    Config(#[source] config::ConfigError),
     #[error(transparent)]
     Path(PathError),
 }
@@ -124,6 +128,8 @@ pub struct GitBackend {
     cached_extra_metadata: Mutex<Option<Arc<ReadonlyTable>>>,
     /// Whether tree of imported commit should be promoted to non-legacy format.
     imported_commit_uses_tree_conflict_format: bool,
+    /// Loose objects written by this backend which may need to be synced.
+    pending_syncs: PendingSyncs,
 }
 
 impl GitBackend {
@@ -135,6 +141,7 @@ impl GitBackend {
         base_repo: gix::ThreadSafeRepository,
         extra_metadata_store: TableStore,
         imported_commit_uses_tree_conflict_format: bool,
+        fsync_policy: FsyncPolicy,
     ) -> Self {
         let repo = Mutex::new(base_repo.to_thread_local());
         let root_commit_id = CommitId::from_bytes(&[0; HASH_LENGTH]);
@@ -149,6 +156,7 @@ impl GitBackend {
             extra_metadata_store,
             cached_extra_metadata: Mutex::new(None),
             imported_commit_uses_tree_conflict_format,
+            pending_syncs: PendingSyncs::new(fsync_policy),
         }
     }
 
@@ -241,11 +249,19 @@ impl GitBackend {
                 .context(&target_path)
                 .map_err(GitBackendInitError::Path)?;
         };
-        let extra_metadata_store = TableStore::init(extra_path, HASH_LENGTH);
+        let lock_timeout = settings
+            .lock_timeout()
+            .map_err(GitBackendInitError::Config)?;
+        let extra_metadata_store =
+            TableStore::init(extra_path, HASH_LENGTH).with_lock_timeout(lock_timeout);
+        let fsync_policy = settings
+            .fsync_policy()
+            .map_err(GitBackendInitError::Config)?;
         Ok(GitBackend::new(
             git_repo,
             extra_metadata_store,
             settings.use_tree_conflict_format(),
+            fsync_policy,
         ))
     }
 
@@ -268,11 +284,19 @@ impl GitBackend {
             gix_open_opts_from_settings(settings),
         )
         .map_err(GitBackendLoadError::OpenRepository)?;
-        let extra_metadata_store = TableStore::load(store_path.join("extra"), HASH_LENGTH);
+        let lock_timeout = settings
+            .lock_timeout()
+            .map_err(GitBackendLoadError::Config)?;
+        let extra_metadata_store =
+            TableStore::load(store_path.join("extra"), HASH_LENGTH).with_lock_timeout(lock_timeout);
+        let fsync_policy = settings
+            .fsync_policy()
+            .map_err(GitBackendLoadError::Config)?;
         Ok(GitBackend::new(
             repo,
             extra_metadata_store,
             settings.use_tree_conflict_format(),
+            fsync_policy,
         ))
     }
 
@@ -300,6 +324,23 @@ impl GitBackend {
         self.base_repo.work_dir()
     }
 
+    /// Records that the object `oid` has been written to the loose object
+    /// store so that it will be synced according to the fsync policy.
+    fn record_written_object(&self, oid: &gix::oid) -> BackendResult<()> {
+        if self.pending_syncs.policy() == FsyncPolicy::Never {
+            return Ok(());
+        }
+        let hex = oid.to_hex().to_string();
+        let path = self
+            .git_repo_path()
+            .join("objects")
+            .join(&hex[..2])
+            .join(&hex[2..]);
+        self.pending_syncs
+            .record(path)
+            .map_err(|err| BackendError::Other(err.into()))
+    }
+
     fn cached_extra_metadata_table(&self) -> BackendResult<Arc<ReadonlyTable>> {
         let mut locked_head = self.cached_extra_metadata.lock().unwrap();
         match locked_head.as_ref() {
@@ -518,6 +559,7 @@ fn commit_from_git_without_root_parent(
         description,
         author,
         committer,
+        extra: BTreeMap::new(),
         secure_sig,
     })
 }
@@ -586,6 +628,7 @@ fn serialize_extras(commit: &Commit) -> Vec<u8> {
     for predecessor in &commit.predecessors {
         proto.predecessors.push(predecessor.to_bytes());
     }
+    proto.extra = commit.extra.clone();
     proto.encode_to_vec()
 }
 
@@ -619,6 +662,7 @@ fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
     for predecessor in &proto.predecessors {
         commit.predecessors.push(CommitId::from_bytes(predecessor));
     }
+    commit.extra = proto.extra;
 }
 
 /// Returns `RefEdit` that will create a ref in `refs/jj/keep` if not exist.
@@ -869,6 +913,7 @@ impl Backend for GitBackend {
                 object_type: "file",
                 source: Box::new(err),
             })?;
+        self.record_written_object(&oid)?;
         Ok(FileId::new(oid.as_bytes().to_vec()))
     }
 
@@ -895,6 +940,7 @@ impl Backend for GitBackend {
                     object_type: "symlink",
                     source: Box::new(err),
                 })?;
+        self.record_written_object(&oid)?;
         Ok(SymlinkId::new(oid.as_bytes().to_vec()))
     }
 
@@ -1016,6 +1062,7 @@ impl Backend for GitBackend {
                 object_type: "tree",
                 source: Box::new(err),
             })?;
+        self.record_written_object(&oid)?;
         Ok(TreeId::from_bytes(oid.as_bytes()))
     }
 
@@ -1049,6 +1096,7 @@ impl Backend for GitBackend {
                 object_type: "conflict",
                 source: Box::new(err),
             })?;
+        self.record_written_object(&oid)?;
         Ok(ConflictId::from_bytes(oid.as_bytes()))
     }
 
@@ -1102,7 +1150,11 @@ impl Backend for GitBackend {
             MergedTreeId::Legacy(tree_id) => validate_git_object_id(tree_id)?,
             MergedTreeId::Merge(tree_ids) => match tree_ids.as_resolved() {
                 Some(tree_id) => validate_git_object_id(tree_id)?,
-                None => write_tree_conflict(&locked_repo, tree_ids)?,
+                None => {
+                    let tree_id = write_tree_conflict(&locked_repo, tree_ids)?;
+                    self.record_written_object(&tree_id)?;
+                    tree_id
+                }
             },
         };
         let author = signature_to_git(&contents.author);
@@ -1190,7 +1242,10 @@ impl Backend for GitBackend {
                     // change id. Adjust the timestamp until this is no longer the case.
                     committer.time.seconds -= 1;
                 }
-                _ => break CommitId::from_bytes(git_id.as_bytes()),
+                _ => {
+                    self.record_written_object(&git_id)?;
+                    break CommitId::from_bytes(git_id.as_bytes());
+                }
             }
         };
 
@@ -1209,6 +1264,12 @@ impl Backend for GitBackend {
         Ok((id, contents))
     }
 
+    fn flush(&self) -> BackendResult<()> {
+        self.pending_syncs
+            .flush()
+            .map_err(|err| BackendError::Other(err.into()))
+    }
+
     #[tracing::instrument(skip(self, index))]
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         let git_repo = self.lock_git_repo();
@@ -1658,6 +1719,7 @@ mod tests {
             description: "".to_string(),
             author: create_signature(),
             committer: create_signature(),
+            extra: Default::default(),
             secure_sig: None,
         };
 
@@ -1736,6 +1798,7 @@ mod tests {
             description: "".to_string(),
             author: create_signature(),
             committer: create_signature(),
+            extra: Default::default(),
             secure_sig: None,
         };
 
@@ -1819,6 +1882,7 @@ mod tests {
             description: "initial".to_string(),
             author: signature.clone(),
             committer: signature,
+            extra: Default::default(),
             secure_sig: None,
         };
         let commit_id = backend.write_commit(commit, None).unwrap().0;
@@ -1896,6 +1960,7 @@ mod tests {
             description: "initial".to_string(),
             author: create_signature(),
             committer: create_signature(),
+            extra: Default::default(),
             secure_sig: None,
         };
         // libgit2 doesn't seem to preserve negative timestamps, so set it to at least 1
@@ -1937,6 +2002,7 @@ mod tests {
             description: "initial".to_string(),
             author: create_signature(),
             committer: create_signature(),
+            extra: Default::default(),
             secure_sig: None,
         };
 
@@ -0,0 +1,105 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Queries on the commit graph of a repo.
+//!
+//! These functions answer common questions about the commit graph without
+//! having to build revset expressions or access the [`Index`] directly. The
+//! results reflect the commits indexed in the given repo, whether or not they
+//! are visible.
+//!
+//! [`Index`]: crate::index::Index
+
+use crate::backend::CommitId;
+use crate::repo::Repo;
+use crate::repo_path::RepoPath;
+use crate::revset::{RevsetEvaluationError, RevsetExpression, RevsetFilterPredicate};
+
+/// Returns true if `ancestor_id` is an ancestor of `descendant_id`, or if they
+/// are the same commit.
+pub fn is_ancestor(repo: &dyn Repo, ancestor_id: &CommitId, descendant_id: &CommitId) -> bool {
+    repo.index().is_ancestor(ancestor_id, descendant_id)
+}
+
+/// Returns the commits in `ids` which are not ancestors of other commits in
+/// `ids`. Duplicates are removed. The order is unspecified.
+pub fn heads<'a>(repo: &dyn Repo, ids: impl IntoIterator<Item = &'a CommitId>) -> Vec<CommitId> {
+    repo.index().heads(&mut ids.into_iter())
+}
+
+/// Returns the best common ancestors of the commits in `set1` and `set2`. A
+/// best common ancestor has no descendants that are also common ancestors.
+pub fn common_ancestors(repo: &dyn Repo, set1: &[CommitId], set2: &[CommitId]) -> Vec<CommitId> {
+    repo.index().common_ancestors(set1, set2)
+}
+
+/// Iterates over the `heads` and all their ancestors in topological order,
+/// with children before parents.
+pub fn topo_order<'index>(
+    repo: &'index dyn Repo,
+    heads: &[CommitId],
+) -> Result<impl Iterator<Item = CommitId> + 'index, RevsetEvaluationError> {
+    let revset = RevsetExpression::commits(heads.to_vec())
+        .ancestors()
+        .evaluate_programmatic(repo)?;
+    Ok(revset.iter())
+}
+
+/// Iterates over the commits which are ancestors of `heads` but not of
+/// `roots` (i.e. `roots..heads`) in topological order, with children before
+/// parents.
+pub fn range<'index>(
+    repo: &'index dyn Repo,
+    roots: &[CommitId],
+    heads: &[CommitId],
+) -> Result<impl Iterator<Item = CommitId> + 'index, RevsetEvaluationError> {
+    let revset = RevsetExpression::commits(roots.to_vec())
+        .range(&RevsetExpression::commits(heads.to_vec()))
+        .evaluate_programmatic(repo)?;
+    Ok(revset.iter())
+}
+
+/// Counts the commits which are ancestors of `heads` but not of `other_heads`
+/// (ahead), and the commits which are ancestors of `other_heads` but not of
+/// `heads` (behind).
+pub fn ahead_behind(
+    repo: &dyn Repo,
+    heads: &[CommitId],
+    other_heads: &[CommitId],
+) -> Result<(usize, usize), RevsetEvaluationError> {
+    let ahead = range(repo, other_heads, heads)?.count();
+    let behind = range(repo, heads, other_heads)?.count();
+    Ok((ahead, behind))
+}
+
+/// Returns the closest ancestor of `head` (including `head` itself) which
+/// modified the file or directory at `path`, or `None` if no such commit
+/// exists.
+///
+/// If the history has merged branches which both modified the path, the one
+/// which comes first in topological order is returned.
+pub fn last_modifying_commit(
+    repo: &dyn Repo,
+    head: &CommitId,
+    path: &RepoPath,
+) -> Result<Option<CommitId>, RevsetEvaluationError> {
+    let revset = RevsetExpression::commit(head.clone())
+        .ancestors()
+        .intersection(&RevsetExpression::filter(RevsetFilterPredicate::File(
+            Some(vec![path.to_owned()]),
+        )))
+        .evaluate_programmatic(repo)?;
+    let id = revset.iter().next();
+    Ok(id)
+}
@@ -0,0 +1,443 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Append-only journal of published operations.
+//!
+//! Every published operation appends a line of JSON to `<repo>/journal`
+//! describing the operation id, the refs it changed, and the working-copy
+//! commits in the resulting view. External tools can tail the file to learn
+//! when to refresh instead of polling the operation log.
+//!
+//! `jj util gc` bounds the journal by replacing it with a file containing only
+//! the newest entries. A marker line is then appended to the replaced file so
+//! readers which are following it know to reopen the journal.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read as _, Write};
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools as _;
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::backend::CommitId;
+use crate::object_id::ObjectId;
+use crate::op_store::{OperationId, RefTarget};
+use crate::view::View;
+
+const JOURNAL_FILE_NAME: &str = "journal";
+/// Line appended to a journal file after it was replaced by `truncate()`.
+const REPLACED_MARKER: &str = r#"{"replaced":true}"#;
+
+/// Error that may occur while reading the journal.
+#[derive(Debug, Error)]
+pub enum JournalError {
+    /// Failed to read the journal file.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Line that couldn't be parsed as an entry.
+    #[error("Invalid journal entry: {0}")]
+    InvalidEntry(String),
+}
+
+/// Summary of a published operation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JournalEntry {
+    /// Id of the published operation.
+    pub op_id: OperationId,
+    /// Local and remote branches (as `name@remote`) whose targets changed.
+    pub branches: Vec<String>,
+    /// Tags whose targets changed.
+    pub tags: Vec<String>,
+    /// Git refs whose targets changed, including `HEAD` for the Git HEAD.
+    pub git_refs: Vec<String>,
+    /// Working-copy commits in the new view, keyed by workspace name.
+    pub wc_commits: BTreeMap<String, CommitId>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct JournalLine {
+    op_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    branches: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    git_refs: Vec<String>,
+    #[serde(default)]
+    wc_commits: BTreeMap<String, String>,
+}
+
+impl JournalEntry {
+    /// Builds an entry for the operation `op_id` which changed `old_view` to
+    /// `new_view`.
+    pub fn new(op_id: OperationId, old_view: &View, new_view: &View) -> Self {
+        let branches = changed_names(
+            old_view.local_branches(),
+            new_view.local_branches(),
+            |name| name.to_owned(),
+        );
+        let remote_branches = changed_names(
+            old_view
+                .all_remote_branches()
+                .map(|(symbol, remote_ref)| (symbol, &remote_ref.target)),
+            new_view
+                .all_remote_branches()
+                .map(|(symbol, remote_ref)| (symbol, &remote_ref.target)),
+            |(name, remote)| format!("{name}@{remote}"),
+        );
+        let branches = [branches, remote_branches].concat();
+        let tags = changed_names(
+            old_view
+                .tags()
+                .iter()
+                .map(|(name, target)| (name.as_str(), target)),
+            new_view
+                .tags()
+                .iter()
+                .map(|(name, target)| (name.as_str(), target)),
+            |name| name.to_owned(),
+        );
+        let mut git_refs = changed_names(
+            old_view
+                .git_refs()
+                .iter()
+                .map(|(name, target)| (name.as_str(), target)),
+            new_view
+                .git_refs()
+                .iter()
+                .map(|(name, target)| (name.as_str(), target)),
+            |name| name.to_owned(),
+        );
+        if old_view.git_head() != new_view.git_head() {
+            git_refs.push("HEAD".to_owned());
+        }
+        let wc_commits = new_view
+            .wc_commit_ids()
+            .iter()
+            .map(|(workspace_id, commit_id)| (workspace_id.as_str().to_owned(), commit_id.clone()))
+            .collect();
+        JournalEntry {
+            op_id,
+            branches,
+            tags,
+            git_refs,
+            wc_commits,
+        }
+    }
+
+    /// Serializes the entry as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        let line = JournalLine {
+            op_id: self.op_id.hex(),
+            branches: self.branches.clone(),
+            tags: self.tags.clone(),
+            git_refs: self.git_refs.clone(),
+            wc_commits: self
+                .wc_commits
+                .iter()
+                .map(|(name, commit_id)| (name.clone(), commit_id.hex()))
+                .collect(),
+        };
+        serde_json::to_string(&line).unwrap()
+    }
+
+    fn from_json(text: &str) -> Result<Self, JournalError> {
+        let invalid = || JournalError::InvalidEntry(text.to_owned());
+        let line: JournalLine = serde_json::from_str(text).map_err(|_| invalid())?;
+        let wc_commits = line
+            .wc_commits
+            .into_iter()
+            .map(|(name, hex)| {
+                let commit_id = CommitId::try_from_hex(&hex).map_err(|_| invalid())?;
+                Ok::<_, JournalError>((name, commit_id))
+            })
+            .try_collect()?;
+        Ok(JournalEntry {
+            op_id: OperationId::try_from_hex(&line.op_id).map_err(|_| invalid())?,
+            branches: line.branches,
+            tags: line.tags,
+            git_refs: line.git_refs,
+            wc_commits,
+        })
+    }
+}
+
+/// Yields the names of refs that were added, removed, or moved. Both inputs
+/// must be sorted by name.
+fn changed_names<'a, K: Ord + Copy + 'a>(
+    old: impl Iterator<Item = (K, &'a RefTarget)>,
+    new: impl Iterator<Item = (K, &'a RefTarget)>,
+    to_name: impl Fn(K) -> String,
+) -> Vec<String> {
+    old.merge_join_by(new, |(old_key, _), (new_key, _)| old_key.cmp(new_key))
+        .filter_map(move |item| {
+            let (key, changed) = match item {
+                itertools::EitherOrBoth::Both((key, old), (_, new)) => (key, old != new),
+                itertools::EitherOrBoth::Left((key, _))
+                | itertools::EitherOrBoth::Right((key, _)) => (key, true),
+            };
+            changed.then(|| to_name(key))
+        })
+        .collect()
+}
+
+/// Returns the path of the journal file in the repo at `repo_path`.
+pub fn journal_path(repo_path: &Path) -> PathBuf {
+    repo_path.join(JOURNAL_FILE_NAME)
+}
+
+/// Appends `entry` to the journal of the repo at `repo_path`.
+///
+/// The caller should hold the op heads lock so concurrent processes append
+/// entries in publication order.
+pub fn append(repo_path: &Path, entry: &JournalEntry) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(journal_path(repo_path))?;
+    // Write the entry with a single call so readers never see a partially
+    // written line followed by another entry.
+    file.write_all(format!("{}\n", entry.to_json()).as_bytes())
+}
+
+/// Drops all but the newest `keep` entries from the journal of the repo at
+/// `repo_path`.
+///
+/// The caller should hold the op heads lock so no entry is appended to the
+/// journal while it's being replaced.
+pub fn truncate(repo_path: &Path, keep: usize) -> io::Result<()> {
+    let path = journal_path(repo_path);
+    let mut old_file = match OpenOptions::new().read(true).append(true).open(&path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let mut content = String::new();
+    old_file.read_to_string(&mut content)?;
+    let lines = content
+        .lines()
+        .filter(|line| !line.is_empty() && *line != REPLACED_MARKER)
+        .collect_vec();
+    if lines.len() <= keep {
+        return Ok(());
+    }
+    let mut temp_file = NamedTempFile::new_in(repo_path)?;
+    for line in &lines[lines.len() - keep..] {
+        writeln!(temp_file, "{line}")?;
+    }
+    temp_file.persist(&path).map_err(|err| err.error)?;
+    // Readers still following the old file reopen the journal when they reach
+    // the marker, which is only written once the new file is in place.
+    old_file.write_all(format!("{REPLACED_MARKER}\n").as_bytes())
+}
+
+/// Reads journal entries, and can be polled for entries appended later.
+pub struct JournalReader {
+    path: PathBuf,
+    // `None` until the journal file exists.
+    reader: Option<BufReader<File>>,
+    partial_line: String,
+    last_op_id: Option<OperationId>,
+    // Set after reopening a replaced journal, until the last entry read from
+    // the old file has been skipped in the new file.
+    skip_until: Option<OperationId>,
+}
+
+impl JournalReader {
+    /// Prepares to read the journal of the repo at `repo_path`. The journal
+    /// file isn't created if it doesn't exist yet, but entries are read from it
+    /// once it's created.
+    pub fn open(repo_path: &Path) -> io::Result<Self> {
+        let mut reader = JournalReader {
+            path: journal_path(repo_path),
+            reader: None,
+            partial_line: String::new(),
+            last_op_id: None,
+            skip_until: None,
+        };
+        reader.try_open_file()?;
+        Ok(reader)
+    }
+
+    fn try_open_file(&mut self) -> io::Result<()> {
+        match File::open(&self.path) {
+            Ok(file) => {
+                self.reader = Some(BufReader::new(file));
+                Ok(())
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the next entry, or `None` if the end of the journal has been
+    /// reached. Entries appended after that can be read by calling this
+    /// function again.
+    pub fn next_entry(&mut self) -> Result<Option<JournalEntry>, JournalError> {
+        loop {
+            if self.reader.is_none() {
+                self.try_open_file()?;
+            }
+            let Some(reader) = &mut self.reader else {
+                return Ok(None);
+            };
+            if reader.read_line(&mut self.partial_line)? == 0 {
+                return Ok(None);
+            }
+            if !self.partial_line.ends_with('\n') {
+                // The rest of the line hasn't been written yet.
+                return Ok(None);
+            }
+            let line = std::mem::take(&mut self.partial_line);
+            let line = line.trim_end();
+            if line == REPLACED_MARKER {
+                self.reader = None;
+                self.skip_until = self.last_op_id.clone();
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let entry = JournalEntry::from_json(line)?;
+            if let Some(op_id) = &self.skip_until {
+                // The entries up to the last one read were kept from the old
+                // file.
+                if *op_id == entry.op_id {
+                    self.skip_until = None;
+                }
+                continue;
+            }
+            self.last_op_id = Some(entry.op_id.clone());
+            return Ok(Some(entry));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use assert_matches::assert_matches;
+    use maplit::btreemap;
+
+    use super::*;
+
+    #[test]
+    fn test_reader_follows_appended_entries() {
+        let temp_dir = testutils::new_temp_dir();
+        let repo_path = temp_dir.path();
+        let entry = JournalEntry {
+            op_id: OperationId::from_hex("0123"),
+            branches: vec!["main".to_owned(), "main@origin".to_owned()],
+            tags: vec![],
+            git_refs: vec!["HEAD".to_owned()],
+            wc_commits: btreemap! {"default".to_owned() => CommitId::from_hex("abcd")},
+        };
+
+        let mut reader = JournalReader::open(repo_path).unwrap();
+        assert_eq!(reader.next_entry().unwrap(), None);
+        append(repo_path, &entry).unwrap();
+        assert_eq!(reader.next_entry().unwrap(), Some(entry.clone()));
+        assert_eq!(reader.next_entry().unwrap(), None);
+
+        // A partially written line isn't returned until it is complete.
+        let line = format!("{}\n", entry.to_json());
+        let (head, tail) = line.split_at(10);
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(journal_path(repo_path))
+            .unwrap();
+        file.write_all(head.as_bytes()).unwrap();
+        assert_eq!(reader.next_entry().unwrap(), None);
+        file.write_all(tail.as_bytes()).unwrap();
+        assert_eq!(reader.next_entry().unwrap(), Some(entry));
+
+        fs::write(journal_path(repo_path), "{}\n").unwrap();
+        let mut reader = JournalReader::open(repo_path).unwrap();
+        assert_matches!(reader.next_entry(), Err(JournalError::InvalidEntry(_)));
+    }
+
+    #[test]
+    fn test_reader_does_not_create_journal() {
+        let temp_dir = testutils::new_temp_dir();
+        let repo_path = temp_dir.path();
+
+        let mut reader = JournalReader::open(repo_path).unwrap();
+        assert_eq!(reader.next_entry().unwrap(), None);
+        assert!(!journal_path(repo_path).exists());
+
+        // Entries are read once the journal is created.
+        let entry = new_entry("0123");
+        append(repo_path, &entry).unwrap();
+        assert_eq!(reader.next_entry().unwrap(), Some(entry));
+    }
+
+    #[test]
+    fn test_truncate() {
+        let temp_dir = testutils::new_temp_dir();
+        let repo_path = temp_dir.path();
+        let entries = ["01", "02", "03", "04"].map(new_entry);
+
+        // Missing journal
+        truncate(repo_path, 2).unwrap();
+        assert!(!journal_path(repo_path).exists());
+
+        for entry in &entries[..3] {
+            append(repo_path, entry).unwrap();
+        }
+        let mut following_reader = JournalReader::open(repo_path).unwrap();
+        assert_eq!(
+            following_reader.next_entry().unwrap(),
+            Some(entries[0].clone())
+        );
+        assert_eq!(
+            following_reader.next_entry().unwrap(),
+            Some(entries[1].clone())
+        );
+        assert_eq!(
+            following_reader.next_entry().unwrap(),
+            Some(entries[2].clone())
+        );
+        assert_eq!(following_reader.next_entry().unwrap(), None);
+
+        truncate(repo_path, 3).unwrap();
+        truncate(repo_path, 2).unwrap();
+        append(repo_path, &entries[3]).unwrap();
+        let mut new_reader = JournalReader::open(repo_path).unwrap();
+        assert_eq!(new_reader.next_entry().unwrap(), Some(entries[1].clone()));
+        assert_eq!(new_reader.next_entry().unwrap(), Some(entries[2].clone()));
+        assert_eq!(new_reader.next_entry().unwrap(), Some(entries[3].clone()));
+        assert_eq!(new_reader.next_entry().unwrap(), None);
+
+        // The reader which was following the old file only sees the new entry.
+        assert_eq!(
+            following_reader.next_entry().unwrap(),
+            Some(entries[3].clone())
+        );
+        assert_eq!(following_reader.next_entry().unwrap(), None);
+    }
+
+    fn new_entry(op_id_hex: &'static str) -> JournalEntry {
+        JournalEntry {
+            op_id: OperationId::from_hex(op_id_hex),
+            branches: vec![],
+            tags: vec![],
+            git_refs: vec![],
+            wc_commits: btreemap! {},
+        }
+    }
+}
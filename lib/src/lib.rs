@@ -39,18 +39,25 @@ pub mod diff;
 pub mod extensions_map;
 pub mod file_util;
 pub mod files;
+pub mod fileset;
+pub mod fingerprint_cache;
 pub mod fmt_util;
+pub mod footer;
 pub mod fsmonitor;
 pub mod git;
 pub mod git_backend;
 pub mod gitignore;
 pub mod gpg_signing;
+pub mod graph;
 pub mod hex_util;
 pub mod id_prefix;
 pub mod index;
+pub mod journal;
 pub mod local_backend;
+pub mod local_pack;
 pub mod local_working_copy;
 pub mod lock;
+pub mod mailmap;
 pub mod matchers;
 pub mod merge;
 pub mod merged_tree;
@@ -76,6 +83,7 @@ pub mod stacked_table;
 pub mod store;
 pub mod str_util;
 pub mod submodule_store;
+pub mod time_util;
 pub mod transaction;
 pub mod tree;
 pub mod tree_builder;
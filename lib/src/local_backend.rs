@@ -18,8 +18,9 @@ use std::any::Any;
 use std::fmt::Debug;
 use std::fs;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{Cursor, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
@@ -28,13 +29,14 @@ use prost::Message;
 use tempfile::NamedTempFile;
 
 use crate::backend::{
-    make_root_commit, Backend, BackendError, BackendResult, ChangeId, Commit, CommitId, Conflict,
-    ConflictId, ConflictTerm, FileId, MergedTreeId, MillisSinceEpoch, SecureSig, Signature,
-    SigningFn, SymlinkId, Timestamp, Tree, TreeId, TreeValue,
+    make_root_commit, Backend, BackendError, BackendLoadError, BackendResult, ChangeId, Commit,
+    CommitId, Conflict, ConflictId, ConflictTerm, FileId, MergedTreeId, MillisSinceEpoch,
+    SecureSig, Signature, SigningFn, SymlinkId, Timestamp, Tree, TreeId, TreeValue,
 };
 use crate::content_hash::blake2b_hash;
-use crate::file_util::persist_content_addressed_temp_file;
+use crate::file_util::{persist_content_addressed_temp_file, FsyncPolicy, PendingSyncs};
 use crate::index::Index;
+use crate::local_pack::{self, ObjectKind, PackSet, RepackScope, RepackStats};
 use crate::merge::MergeBuilder;
 use crate::object_id::ObjectId;
 use crate::repo_path::{RepoPath, RepoPathComponentBuf};
@@ -68,6 +70,10 @@ pub struct LocalBackend {
     root_commit_id: CommitId,
     root_change_id: ChangeId,
     empty_tree_id: TreeId,
+    pending_syncs: PendingSyncs,
+    /// Packed objects. Reloaded when an object can't be found, since another
+    /// process may have repacked the loose objects in the meantime.
+    packs: Mutex<PackSet>,
 }
 
 impl LocalBackend {
@@ -81,7 +87,7 @@ impl LocalBackend {
         fs::create_dir(store_path.join("files")).unwrap();
         fs::create_dir(store_path.join("symlinks")).unwrap();
         fs::create_dir(store_path.join("conflicts")).unwrap();
-        let backend = Self::load(store_path);
+        let backend = Self::load(store_path).unwrap();
         let empty_tree_id = backend
             .write_tree(RepoPath::root(), &Tree::default())
             .unwrap();
@@ -89,16 +95,89 @@ impl LocalBackend {
         backend
     }
 
-    pub fn load(store_path: &Path) -> Self {
+    pub fn load(store_path: &Path) -> Result<Self, BackendLoadError> {
         let root_commit_id = CommitId::from_bytes(&[0; COMMIT_ID_LENGTH]);
         let root_change_id = ChangeId::from_bytes(&[0; CHANGE_ID_LENGTH]);
         let empty_tree_id = TreeId::from_hex("482ae5a29fbe856c7272f2071b8b0f0359ee2d89ff392b8a900643fbd0836eccd067b8bf41909e206c90d45d6e7d8b6686b93ecaee5fe1a9060d87b672101310");
-        LocalBackend {
+        let packs = PackSet::load(store_path).map_err(|err| BackendLoadError(err.into()))?;
+        Ok(LocalBackend {
             path: store_path.to_path_buf(),
             root_commit_id,
             root_change_id,
             empty_tree_id,
+            pending_syncs: PendingSyncs::new(FsyncPolicy::default()),
+            packs: Mutex::new(packs),
+        })
+    }
+
+    /// Sets when written objects are synced to disk.
+    pub fn with_fsync_policy(mut self, policy: FsyncPolicy) -> Self {
+        self.pending_syncs = PendingSyncs::new(policy);
+        self
+    }
+
+    /// Moves the loose objects into a new pack. With [`RepackScope::All`], the
+    /// existing packs are merged into it too.
+    pub fn repack(&self, scope: RepackScope) -> BackendResult<RepackStats> {
+        let mut packs = self.packs.lock().unwrap();
+        // Objects written before the repack must be on disk before their loose
+        // files are removed.
+        self.pending_syncs.flush().map_err(to_other_err)?;
+        *packs = PackSet::load(&self.path).map_err(to_other_err)?;
+        let stats = local_pack::repack(&self.path, &packs, scope).map_err(to_other_err)?;
+        *packs = PackSet::load(&self.path).map_err(to_other_err)?;
+        Ok(stats)
+    }
+
+    fn persist_object(
+        &self,
+        temp_file: NamedTempFile,
+        kind: ObjectKind,
+        id: &impl ObjectId,
+        path: PathBuf,
+    ) -> BackendResult<()> {
+        // Identical content is already stored if the object is in a pack.
+        if self.packs.lock().unwrap().contains(kind, id.as_bytes()) {
+            return Ok(());
         }
+        persist_content_addressed_temp_file(temp_file, &path).map_err(to_other_err)?;
+        self.pending_syncs.record(path).map_err(to_other_err)
+    }
+
+    /// Reads the content of an object which couldn't be found as a loose file.
+    /// `err` is the error which occurred while reading the loose file.
+    fn read_packed_object(
+        &self,
+        kind: ObjectKind,
+        id: &impl ObjectId,
+        err: std::io::Error,
+    ) -> BackendResult<Vec<u8>> {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            return Err(map_not_found_err(err, id));
+        }
+        let mut packs = self.packs.lock().unwrap();
+        match packs.read(kind, id.as_bytes()) {
+            Ok(Some(data)) => return Ok(data),
+            Ok(None) => {}
+            // The pack may have been replaced by a concurrent repack.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(map_not_found_err(err, id)),
+        }
+        *packs = PackSet::load(&self.path).map_err(to_other_err)?;
+        match packs.read(kind, id.as_bytes()) {
+            Ok(Some(data)) => Ok(data),
+            Ok(None) => Err(map_not_found_err(err, id)),
+            Err(err) => Err(map_not_found_err(err, id)),
+        }
+    }
+
+    fn read_object(
+        &self,
+        kind: ObjectKind,
+        id: &impl ObjectId,
+        path: PathBuf,
+    ) -> BackendResult<Vec<u8>> {
+        fs::read(path).or_else(|err| self.read_packed_object(kind, id, err))
     }
 
     fn file_path(&self, id: &FileId) -> PathBuf {
@@ -158,8 +237,13 @@ impl Backend for LocalBackend {
 
     async fn read_file(&self, _path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
         let path = self.file_path(id);
-        let file = File::open(path).map_err(|err| map_not_found_err(err, id))?;
-        Ok(Box::new(zstd::Decoder::new(file).map_err(to_other_err)?))
+        match File::open(path) {
+            Ok(file) => Ok(Box::new(zstd::Decoder::new(file).map_err(to_other_err)?)),
+            Err(err) => {
+                let data = self.read_packed_object(ObjectKind::File, id, err)?;
+                Ok(Box::new(Cursor::new(data)))
+            }
+        }
     }
 
     fn write_file(&self, _path: &RepoPath, contents: &mut dyn Read) -> BackendResult<FileId> {
@@ -179,15 +263,18 @@ impl Backend for LocalBackend {
         encoder.finish().map_err(to_other_err)?;
         let id = FileId::new(hasher.finalize().to_vec());
 
-        persist_content_addressed_temp_file(temp_file, self.file_path(&id))
-            .map_err(to_other_err)?;
+        self.persist_object(temp_file, ObjectKind::File, &id, self.file_path(&id))?;
         Ok(id)
     }
 
     async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> Result<String, BackendError> {
         let path = self.symlink_path(id);
-        let target = fs::read_to_string(path).map_err(|err| map_not_found_err(err, id))?;
-        Ok(target)
+        let buf = self.read_object(ObjectKind::Symlink, id, path)?;
+        String::from_utf8(buf).map_err(|err| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: Box::new(err),
+        })
     }
 
     fn write_symlink(&self, _path: &RepoPath, target: &str) -> Result<SymlinkId, BackendError> {
@@ -199,14 +286,13 @@ impl Backend for LocalBackend {
         hasher.update(target.as_bytes());
         let id = SymlinkId::new(hasher.finalize().to_vec());
 
-        persist_content_addressed_temp_file(temp_file, self.symlink_path(&id))
-            .map_err(to_other_err)?;
+        self.persist_object(temp_file, ObjectKind::Symlink, &id, self.symlink_path(&id))?;
         Ok(id)
     }
 
     async fn read_tree(&self, _path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
         let path = self.tree_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object(ObjectKind::Tree, id, path)?;
 
         let proto = crate::protos::local_store::Tree::decode(&*buf).map_err(to_other_err)?;
         Ok(tree_from_proto(proto))
@@ -223,14 +309,13 @@ impl Backend for LocalBackend {
 
         let id = TreeId::new(blake2b_hash(tree).to_vec());
 
-        persist_content_addressed_temp_file(temp_file, self.tree_path(&id))
-            .map_err(to_other_err)?;
+        self.persist_object(temp_file, ObjectKind::Tree, &id, self.tree_path(&id))?;
         Ok(id)
     }
 
     fn read_conflict(&self, _path: &RepoPath, id: &ConflictId) -> BackendResult<Conflict> {
         let path = self.conflict_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object(ObjectKind::Conflict, id, path)?;
 
         let proto = crate::protos::local_store::Conflict::decode(&*buf).map_err(to_other_err)?;
         Ok(conflict_from_proto(proto))
@@ -247,8 +332,12 @@ impl Backend for LocalBackend {
 
         let id = ConflictId::new(blake2b_hash(conflict).to_vec());
 
-        persist_content_addressed_temp_file(temp_file, self.conflict_path(&id))
-            .map_err(to_other_err)?;
+        self.persist_object(
+            temp_file,
+            ObjectKind::Conflict,
+            &id,
+            self.conflict_path(&id),
+        )?;
         Ok(id)
     }
 
@@ -261,7 +350,7 @@ impl Backend for LocalBackend {
         }
 
         let path = self.commit_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object(ObjectKind::Commit, id, path)?;
 
         let proto = crate::protos::local_store::Commit::decode(&*buf).map_err(to_other_err)?;
         Ok(commit_from_proto(proto))
@@ -296,12 +385,19 @@ impl Backend for LocalBackend {
 
         let id = CommitId::new(blake2b_hash(&commit).to_vec());
 
-        persist_content_addressed_temp_file(temp_file, self.commit_path(&id))
-            .map_err(to_other_err)?;
+        self.persist_object(temp_file, ObjectKind::Commit, &id, self.commit_path(&id))?;
         Ok((id, commit))
     }
 
+    fn flush(&self) -> BackendResult<()> {
+        self.pending_syncs.flush().map_err(to_other_err)
+    }
+
     fn gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
+        // Unreachable objects aren't removed yet, but packing them saves space.
+        // Existing packs are left alone so that gc doesn't rewrite the whole
+        // store every time; `jj debug repack` merges them.
+        self.repack(RepackScope::Loose)?;
         Ok(())
     }
 }
@@ -327,6 +423,7 @@ pub fn commit_to_proto(commit: &Commit) -> crate::protos::local_store::Commit {
     proto.description = commit.description.clone();
     proto.author = Some(signature_to_proto(&commit.author));
     proto.committer = Some(signature_to_proto(&commit.committer));
+    proto.extra = commit.extra.clone();
     proto
 }
 
@@ -356,6 +453,7 @@ fn commit_from_proto(mut proto: crate::protos::local_store::Commit) -> Commit {
         description: proto.description,
         author: signature_from_proto(proto.author.unwrap_or_default()),
         committer: signature_from_proto(proto.committer.unwrap_or_default()),
+        extra: proto.extra,
         secure_sig,
     }
 }
@@ -512,6 +610,7 @@ mod tests {
             description: "".to_string(),
             author: create_signature(),
             committer: create_signature(),
+            extra: Default::default(),
             secure_sig: None,
         };
 
@@ -547,6 +646,83 @@ mod tests {
         assert_eq!(root_merge_commit, commit);
     }
 
+    #[test]
+    fn read_objects_after_repack() {
+        let temp_dir = testutils::new_temp_dir();
+        let store_path = temp_dir.path();
+
+        let backend = LocalBackend::init(store_path);
+        let file_path = RepoPath::from_internal_string("file");
+        let file_id = backend
+            .write_file(file_path, &mut "content".as_bytes())
+            .unwrap();
+        let symlink_id = backend.write_symlink(file_path, "target").unwrap();
+        let commit = Commit {
+            parents: vec![backend.root_commit_id().clone()],
+            predecessors: vec![],
+            root_tree: MergedTreeId::resolved(backend.empty_tree_id().clone()),
+            change_id: ChangeId::from_hex("abc123"),
+            description: "description".to_string(),
+            author: create_signature(),
+            committer: create_signature(),
+            extra: Default::default(),
+            secure_sig: None,
+        };
+        let commit_id = backend.write_commit(commit.clone(), None).unwrap().0;
+
+        let stats = backend.repack(RepackScope::All).unwrap();
+        assert_eq!(stats.num_objects, 4);
+        assert!(!backend.file_path(&file_id).exists());
+        assert!(!backend.commit_path(&commit_id).exists());
+
+        // Objects can be read from the pack, also by a newly loaded backend
+        for backend in [backend, LocalBackend::load(store_path).unwrap()] {
+            let mut content = String::new();
+            backend
+                .read_file(file_path, &file_id)
+                .block_on()
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            assert_eq!(content, "content");
+            assert_eq!(
+                backend
+                    .read_symlink(file_path, &symlink_id)
+                    .block_on()
+                    .unwrap(),
+                "target"
+            );
+            assert_eq!(backend.read_commit(&commit_id).block_on().unwrap(), commit);
+            backend
+                .read_tree(RepoPath::root(), backend.empty_tree_id())
+                .block_on()
+                .unwrap();
+
+            // Writing a packed object again doesn't create a loose copy
+            backend
+                .write_file(file_path, &mut "content".as_bytes())
+                .unwrap();
+            assert!(!backend.file_path(&file_id).exists());
+        }
+
+        // Objects packed by another backend instance are found after reloading
+        let backend = LocalBackend::load(store_path).unwrap();
+        let other_backend = LocalBackend::load(store_path).unwrap();
+        let file_id = backend
+            .write_file(file_path, &mut "new content".as_bytes())
+            .unwrap();
+        other_backend.repack(RepackScope::Loose).unwrap();
+        assert!(!backend.file_path(&file_id).exists());
+        let mut content = String::new();
+        backend
+            .read_file(file_path, &file_id)
+            .block_on()
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "new content");
+    }
+
     fn create_signature() -> Signature {
         Signature {
             name: "Someone".to_string(),
@@ -0,0 +1,722 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pack files of the local backend.
+//!
+//! Objects written by the local backend are first stored as individual
+//! ("loose") files. Repacking moves them into a single pack file, in which each
+//! object is compressed with zstd. Objects of the same kind and similar size
+//! are tried as delta bases for each other: an object can be compressed using
+//! an earlier object in the pack as the zstd dictionary, which makes successive
+//! versions of a file or tree cheap to store.
+//!
+//! A pack file starts with a magic number, followed by the entries until the
+//! end of the file. Each entry consists of the object kind (1 byte), the length
+//! of the object id (1 byte), the object id, the index of the delta base within
+//! the pack (4 bytes, all ones if none), the size of the uncompressed object
+//! (8 bytes), the size of the compressed data (8 bytes), and the compressed
+//! data. Integers are little-endian.
+//!
+//! Each pack has an index file with the same name and the `idx` extension, so
+//! that the pack doesn't have to be scanned when it's loaded. It starts with a
+//! magic number, followed by one record per entry, sorted by object kind and
+//! id. A record consists of the object kind, the length of the object id, the
+//! object id, the index of the entry within the pack (4 bytes), the index of
+//! its delta base (4 bytes), the size of the uncompressed object (8 bytes), and
+//! the offset and size of the compressed data in the pack (8 bytes each). The
+//! index file is written before the pack, so every pack has one.
+
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use blake2::{Blake2b512, Digest};
+use tempfile::NamedTempFile;
+
+use crate::file_util::{persist_content_addressed_temp_file, sync_dir};
+
+const PACKS_DIR: &str = "packs";
+const PACK_EXTENSION: &str = "pack";
+const INDEX_EXTENSION: &str = "idx";
+const PACK_MAGIC: &[u8; 8] = b"JJPACK01";
+const INDEX_MAGIC: &[u8; 8] = b"JJIDX001";
+const NO_BASE: u32 = u32::MAX;
+const COMPRESSION_LEVEL: i32 = 3;
+/// Number of preceding objects tried as delta base for each object.
+const DELTA_WINDOW: usize = 10;
+/// Maximum length of a chain of deltas, which bounds the cost of reading an
+/// object.
+const MAX_DELTA_DEPTH: usize = 10;
+
+/// Kind of object stored by the local backend.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ObjectKind {
+    /// A commit.
+    Commit,
+    /// A tree, listing the entries of a directory.
+    Tree,
+    /// The contents of a file.
+    File,
+    /// The target of a symlink.
+    Symlink,
+    /// A legacy conflict object.
+    Conflict,
+}
+
+impl ObjectKind {
+    /// All kinds of objects.
+    pub const ALL: [ObjectKind; 5] = [
+        ObjectKind::Commit,
+        ObjectKind::Tree,
+        ObjectKind::File,
+        ObjectKind::Symlink,
+        ObjectKind::Conflict,
+    ];
+
+    /// Name of the directory in which loose objects of this kind are stored.
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            ObjectKind::Commit => "commits",
+            ObjectKind::Tree => "trees",
+            ObjectKind::File => "files",
+            ObjectKind::Symlink => "symlinks",
+            ObjectKind::Conflict => "conflicts",
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            ObjectKind::Commit => 0,
+            ObjectKind::Tree => 1,
+            ObjectKind::File => 2,
+            ObjectKind::Symlink => 3,
+            ObjectKind::Conflict => 4,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        ObjectKind::ALL
+            .into_iter()
+            .find(|kind| kind.to_byte() == byte)
+    }
+}
+
+/// Which objects are written to the new pack by [`repack()`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RepackScope {
+    /// Only the loose objects. Existing packs are kept as they are.
+    Loose,
+    /// The loose objects and the objects in the existing packs, which are
+    /// replaced by the new pack.
+    All,
+}
+
+/// Statistics about a repacking.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RepackStats {
+    /// Number of objects in the new pack.
+    pub num_objects: usize,
+    /// Number of objects stored as deltas against another object.
+    pub num_deltas: usize,
+    /// Size of the new pack file in bytes.
+    pub pack_size: u64,
+    /// Number of loose object files which were removed.
+    pub num_loose_removed: usize,
+    /// Number of old pack files which were removed.
+    pub num_packs_removed: usize,
+}
+
+#[derive(Debug)]
+struct PackEntry {
+    kind: ObjectKind,
+    id: Vec<u8>,
+    /// Index of the entry within the pack.
+    position: usize,
+    base: Option<usize>,
+    raw_size: u64,
+    data_offset: u64,
+    data_len: u64,
+}
+
+#[derive(Debug)]
+struct Pack {
+    path: PathBuf,
+    /// Entries sorted by kind and id.
+    entries: Vec<PackEntry>,
+    /// Maps the index of an entry within the pack to its index in `entries`.
+    positions: Vec<usize>,
+}
+
+impl Pack {
+    /// Loads the index of the pack at `path`.
+    fn load(path: &Path) -> io::Result<Self> {
+        let index_path = path.with_extension(INDEX_EXTENSION);
+        let mut reader = BufReader::new(File::open(&index_path)?);
+        let mut magic = [0; INDEX_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != INDEX_MAGIC {
+            return Err(invalid_data(format!(
+                "{} is not a pack index",
+                index_path.display()
+            )));
+        }
+        let mut entries = vec![];
+        loop {
+            let mut kind_byte = [0; 1];
+            if reader.read(&mut kind_byte)? == 0 {
+                break;
+            }
+            let kind = ObjectKind::from_byte(kind_byte[0])
+                .ok_or_else(|| invalid_data("unknown object kind in pack index"))?;
+            let mut id_len = [0; 1];
+            reader.read_exact(&mut id_len)?;
+            let mut id = vec![0; id_len[0].into()];
+            reader.read_exact(&mut id)?;
+            let position = usize::try_from(read_u32(&mut reader)?).map_err(invalid_data)?;
+            let base = match read_u32(&mut reader)? {
+                NO_BASE => None,
+                index => Some(usize::try_from(index).map_err(invalid_data)?),
+            };
+            if base.is_some_and(|base| base >= position) {
+                return Err(invalid_data("delta base must precede the object in pack"));
+            }
+            entries.push(PackEntry {
+                kind,
+                id,
+                position,
+                base,
+                raw_size: read_u64(&mut reader)?,
+                data_offset: read_u64(&mut reader)?,
+                data_len: read_u64(&mut reader)?,
+            });
+        }
+        if !entries
+            .windows(2)
+            .all(|pair| (pair[0].kind, &pair[0].id) < (pair[1].kind, &pair[1].id))
+        {
+            return Err(invalid_data("pack index isn't sorted"));
+        }
+        let mut positions = vec![usize::MAX; entries.len()];
+        for (index, entry) in entries.iter().enumerate() {
+            match positions.get_mut(entry.position) {
+                Some(slot) if *slot == usize::MAX => *slot = index,
+                _ => return Err(invalid_data("invalid entry position in pack index")),
+            }
+        }
+        Ok(Pack {
+            path: path.to_owned(),
+            entries,
+            positions,
+        })
+    }
+
+    /// Returns the index of the object in `entries`.
+    fn find(&self, kind: ObjectKind, id: &[u8]) -> Option<usize> {
+        self.entries
+            .binary_search_by(|entry| (entry.kind, entry.id.as_slice()).cmp(&(kind, id)))
+            .ok()
+    }
+
+    fn read_entry(&self, index: usize) -> io::Result<Vec<u8>> {
+        let entry = &self.entries[index];
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(entry.data_offset))?;
+        let mut data = vec![0; usize::try_from(entry.data_len).map_err(invalid_data)?];
+        file.read_exact(&mut data)?;
+        let capacity = usize::try_from(entry.raw_size).map_err(invalid_data)?;
+        match entry.base {
+            None => zstd::bulk::decompress(&data, capacity),
+            Some(base) => {
+                let dictionary = self.read_entry(self.positions[base])?;
+                zstd::bulk::Decompressor::with_dictionary(&dictionary)?.decompress(&data, capacity)
+            }
+        }
+    }
+}
+
+/// The pack files of a local backend store.
+#[derive(Debug, Default)]
+pub struct PackSet {
+    packs: Vec<Pack>,
+}
+
+impl PackSet {
+    /// Loads the indexes of the pack files of the store at `store_path`. A
+    /// store without packs results in an empty set.
+    pub fn load(store_path: &Path) -> io::Result<Self> {
+        let dir_entries = match fs::read_dir(store_path.join(PACKS_DIR)) {
+            Ok(dir_entries) => dir_entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(PackSet::default()),
+            Err(err) => return Err(err),
+        };
+        let mut paths = vec![];
+        for dir_entry in dir_entries {
+            let path = dir_entry?.path();
+            if path.extension() == Some(OsStr::new(PACK_EXTENSION)) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        let packs = paths
+            .iter()
+            .map(|path| Pack::load(path))
+            .collect::<io::Result<_>>()?;
+        Ok(PackSet { packs })
+    }
+
+    /// Number of distinct objects in the packs.
+    pub fn num_objects(&self) -> usize {
+        self.packs
+            .iter()
+            .flat_map(|pack| &pack.entries)
+            .map(|entry| (entry.kind, &entry.id))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Returns true if the object is in any of the packs.
+    pub fn contains(&self, kind: ObjectKind, id: &[u8]) -> bool {
+        self.packs.iter().any(|pack| pack.find(kind, id).is_some())
+    }
+
+    /// Reads the uncompressed content of the object, or returns `None` if it
+    /// isn't in any of the packs.
+    pub fn read(&self, kind: ObjectKind, id: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        for pack in &self.packs {
+            if let Some(index) = pack.find(kind, id) {
+                return pack.read_entry(index).map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+enum ObjectSource {
+    Loose(PathBuf),
+    Packed,
+}
+
+struct ObjectToPack {
+    kind: ObjectKind,
+    id: Vec<u8>,
+    source: ObjectSource,
+    size_hint: u64,
+}
+
+/// Writes the loose objects of the store at `store_path` into a new pack, then
+/// removes them. With [`RepackScope::All`], the objects in `pack_set` (which
+/// must be the current packs of the store) are written to the new pack too, and
+/// the old packs are removed.
+pub fn repack(
+    store_path: &Path,
+    pack_set: &PackSet,
+    scope: RepackScope,
+) -> io::Result<RepackStats> {
+    let mut stats = RepackStats::default();
+    let mut objects = vec![];
+    let mut seen = HashSet::new();
+    let mut loose_paths = vec![];
+    for kind in ObjectKind::ALL {
+        for dir_entry in fs::read_dir(store_path.join(kind.dir_name()))? {
+            let dir_entry = dir_entry?;
+            let Ok(id) = hex::decode(dir_entry.file_name().as_encoded_bytes()) else {
+                continue;
+            };
+            let path = dir_entry.path();
+            loose_paths.push(path.clone());
+            if seen.insert((kind, id.clone())) {
+                objects.push(ObjectToPack {
+                    kind,
+                    id,
+                    source: ObjectSource::Loose(path),
+                    size_hint: dir_entry.metadata()?.len(),
+                });
+            }
+        }
+    }
+    let old_packs = match scope {
+        RepackScope::Loose => &[][..],
+        RepackScope::All => &pack_set.packs[..],
+    };
+    for pack in old_packs {
+        for entry in &pack.entries {
+            if seen.insert((entry.kind, entry.id.clone())) {
+                objects.push(ObjectToPack {
+                    kind: entry.kind,
+                    id: entry.id.clone(),
+                    source: ObjectSource::Packed,
+                    size_hint: entry.raw_size,
+                });
+            }
+        }
+    }
+    if objects.is_empty() {
+        return Ok(stats);
+    }
+    // Like Git, consider objects of similar size as deltas of each other,
+    // larger ones first so that deltas mostly remove data.
+    objects.sort_by_key(|object| (object.kind, Reverse(object.size_hint)));
+
+    let packs_dir = store_path.join(PACKS_DIR);
+    fs::create_dir_all(&packs_dir)?;
+    let temp_file = NamedTempFile::new_in(&packs_dir)?;
+    let mut writer = BufWriter::new(temp_file.as_file());
+    writer.write_all(PACK_MAGIC)?;
+    let mut offset = PACK_MAGIC.len() as u64;
+    let mut index_entries = vec![];
+    // Recently written objects of the current kind, and their delta depths
+    let mut window: VecDeque<(usize, Vec<u8>, usize)> = VecDeque::new();
+    for (index, object) in objects.iter().enumerate() {
+        let raw = match &object.source {
+            ObjectSource::Loose(path) => read_loose_object(object.kind, path)?,
+            ObjectSource::Packed => pack_set
+                .read(object.kind, &object.id)?
+                .ok_or_else(|| invalid_data("packed object disappeared"))?,
+        };
+        if index > 0 && objects[index - 1].kind != object.kind {
+            window.clear();
+        }
+        let mut base = None;
+        let mut data = zstd::bulk::compress(&raw, COMPRESSION_LEVEL)?;
+        let mut depth = 0;
+        for (base_index, base_raw, base_depth) in &window {
+            if *base_depth >= MAX_DELTA_DEPTH {
+                continue;
+            }
+            let delta = zstd::bulk::Compressor::with_dictionary(COMPRESSION_LEVEL, base_raw)?
+                .compress(&raw)?;
+            if delta.len() < data.len() {
+                base = Some(*base_index);
+                data = delta;
+                depth = base_depth + 1;
+            }
+        }
+        writer.write_all(&[
+            object.kind.to_byte(),
+            u8::try_from(object.id.len()).map_err(invalid_data)?,
+        ])?;
+        writer.write_all(&object.id)?;
+        let base_index = base
+            .map_or(Ok(NO_BASE), u32::try_from)
+            .map_err(invalid_data)?;
+        writer.write_all(&base_index.to_le_bytes())?;
+        writer.write_all(&(raw.len() as u64).to_le_bytes())?;
+        writer.write_all(&(data.len() as u64).to_le_bytes())?;
+        writer.write_all(&data)?;
+        let data_offset = offset + 2 + object.id.len() as u64 + 4 + 8 + 8;
+        offset = data_offset + data.len() as u64;
+        index_entries.push(PackEntry {
+            kind: object.kind,
+            id: object.id.clone(),
+            position: index,
+            base,
+            raw_size: raw.len() as u64,
+            data_offset,
+            data_len: data.len() as u64,
+        });
+        stats.num_objects += 1;
+        if base.is_some() {
+            stats.num_deltas += 1;
+        }
+        window.push_back((index, raw, depth));
+        if window.len() > DELTA_WINDOW {
+            window.pop_front();
+        }
+    }
+    writer.flush()?;
+    drop(writer);
+    temp_file.as_file().sync_all()?;
+    stats.pack_size = temp_file.as_file().metadata()?.len();
+    // The pack is named after the set of objects, not the order they were
+    // written in, so repacking the same objects keeps the same pack.
+    index_entries.sort_by(|a, b| (a.kind, &a.id).cmp(&(b.kind, &b.id)));
+    let mut hasher = Blake2b512::new();
+    for entry in &index_entries {
+        hasher.update([entry.kind.to_byte()]);
+        hasher.update(&entry.id);
+    }
+    let pack_name = hex::encode(&hasher.finalize()[..16]);
+    let pack_path = packs_dir.join(format!("{pack_name}.{PACK_EXTENSION}"));
+    let index_path = pack_path.with_extension(INDEX_EXTENSION);
+    // An existing pack of the same objects may have chosen other deltas, so it's
+    // kept together with its index rather than mixed with the new ones.
+    if !(pack_path.exists() && index_path.exists()) {
+        // The index is persisted first so that a pack is never without one.
+        write_index(&index_path, &index_entries)?;
+        persist_content_addressed_temp_file(temp_file, &pack_path)?;
+        // The new pack must survive a crash before the other copies are removed.
+        sync_dir(&packs_dir)?;
+    }
+
+    // Now that the objects are safely in the new pack, remove the other copies.
+    // Objects written concurrently after we listed them are left loose.
+    for path in loose_paths {
+        remove_file_if_exists(&path)?;
+        stats.num_loose_removed += 1;
+    }
+    for pack in old_packs {
+        if pack.path != pack_path {
+            remove_file_if_exists(&pack.path)?;
+            remove_file_if_exists(&pack.path.with_extension(INDEX_EXTENSION))?;
+            stats.num_packs_removed += 1;
+        }
+    }
+    Ok(stats)
+}
+
+/// Writes the index of a pack to `path`. The `entries` must be sorted by kind
+/// and id.
+fn write_index(path: &Path, entries: &[PackEntry]) -> io::Result<()> {
+    let temp_file = NamedTempFile::new_in(path.parent().unwrap())?;
+    let mut writer = BufWriter::new(temp_file.as_file());
+    writer.write_all(INDEX_MAGIC)?;
+    for entry in entries {
+        writer.write_all(&[
+            entry.kind.to_byte(),
+            u8::try_from(entry.id.len()).map_err(invalid_data)?,
+        ])?;
+        writer.write_all(&entry.id)?;
+        let position = u32::try_from(entry.position).map_err(invalid_data)?;
+        let base = entry
+            .base
+            .map_or(Ok(NO_BASE), u32::try_from)
+            .map_err(invalid_data)?;
+        writer.write_all(&position.to_le_bytes())?;
+        writer.write_all(&base.to_le_bytes())?;
+        writer.write_all(&entry.raw_size.to_le_bytes())?;
+        writer.write_all(&entry.data_offset.to_le_bytes())?;
+        writer.write_all(&entry.data_len.to_le_bytes())?;
+    }
+    writer.flush()?;
+    drop(writer);
+    temp_file.as_file().sync_all()?;
+    persist_content_addressed_temp_file(temp_file, path)?;
+    Ok(())
+}
+
+/// Reads the uncompressed content of a loose object. Only file contents are
+/// compressed when stored loose.
+fn read_loose_object(kind: ObjectKind, path: &Path) -> io::Result<Vec<u8>> {
+    match kind {
+        ObjectKind::File => zstd::decode_all(File::open(path)?),
+        _ => fs::read(path),
+    }
+}
+
+fn remove_file_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_loose(store_path: &Path, kind: ObjectKind, id: &[u8], content: &[u8]) {
+        let dir = store_path.join(kind.dir_name());
+        fs::create_dir_all(&dir).unwrap();
+        let data = match kind {
+            ObjectKind::File => zstd::encode_all(content, 0).unwrap(),
+            _ => content.to_vec(),
+        };
+        fs::write(dir.join(hex::encode(id)), data).unwrap();
+    }
+
+    fn init_store(store_path: &Path) {
+        for kind in ObjectKind::ALL {
+            fs::create_dir_all(store_path.join(kind.dir_name())).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_repack_roundtrip() {
+        let temp_dir = testutils::new_temp_dir();
+        let store_path = temp_dir.path();
+        init_store(store_path);
+        let base_content = (0..2000).map(|i| format!("line {i}\n")).collect::<String>();
+        let modified_content = base_content.replace("line 1000\n", "changed\n");
+        write_loose(
+            store_path,
+            ObjectKind::File,
+            b"\x01",
+            base_content.as_bytes(),
+        );
+        write_loose(
+            store_path,
+            ObjectKind::File,
+            b"\x02",
+            modified_content.as_bytes(),
+        );
+        write_loose(store_path, ObjectKind::Tree, b"\x01", b"tree");
+        assert_eq!(PackSet::load(store_path).unwrap().num_objects(), 0);
+
+        let stats = repack(store_path, &PackSet::default(), RepackScope::All).unwrap();
+        assert_eq!(stats.num_objects, 3);
+        assert_eq!(stats.num_deltas, 1);
+        assert_eq!(stats.num_loose_removed, 3);
+        assert_eq!(stats.num_packs_removed, 0);
+        assert!(!store_path.join("files").join("01").exists());
+
+        let pack_set = PackSet::load(store_path).unwrap();
+        assert_eq!(pack_set.num_objects(), 3);
+        assert!(pack_set.contains(ObjectKind::Tree, b"\x01"));
+        // Kinds are distinct namespaces
+        assert!(!pack_set.contains(ObjectKind::Commit, b"\x01"));
+        assert_eq!(
+            pack_set.read(ObjectKind::File, b"\x01").unwrap().unwrap(),
+            base_content.as_bytes()
+        );
+        assert_eq!(
+            pack_set.read(ObjectKind::File, b"\x02").unwrap().unwrap(),
+            modified_content.as_bytes()
+        );
+        assert_eq!(
+            pack_set.read(ObjectKind::Tree, b"\x01").unwrap().unwrap(),
+            b"tree"
+        );
+        assert_eq!(pack_set.read(ObjectKind::Tree, b"\x02").unwrap(), None);
+    }
+
+    #[test]
+    fn test_repack_merges_packs() {
+        let temp_dir = testutils::new_temp_dir();
+        let store_path = temp_dir.path();
+        init_store(store_path);
+        write_loose(store_path, ObjectKind::Commit, b"\x01", b"commit 1");
+        repack(
+            store_path,
+            &PackSet::load(store_path).unwrap(),
+            RepackScope::All,
+        )
+        .unwrap();
+        write_loose(store_path, ObjectKind::Commit, b"\x02", b"commit 2");
+        let stats = repack(
+            store_path,
+            &PackSet::load(store_path).unwrap(),
+            RepackScope::All,
+        )
+        .unwrap();
+        assert_eq!(stats.num_objects, 2);
+        assert_eq!(stats.num_loose_removed, 1);
+        assert_eq!(stats.num_packs_removed, 1);
+        // The pack and its index
+        assert_eq!(fs::read_dir(store_path.join(PACKS_DIR)).unwrap().count(), 2);
+
+        // Repacking without changes keeps the same pack
+        let stats = repack(
+            store_path,
+            &PackSet::load(store_path).unwrap(),
+            RepackScope::All,
+        )
+        .unwrap();
+        assert_eq!(stats.num_objects, 2);
+        assert_eq!(stats.num_packs_removed, 0);
+        let pack_set = PackSet::load(store_path).unwrap();
+        assert_eq!(
+            pack_set.read(ObjectKind::Commit, b"\x01").unwrap().unwrap(),
+            b"commit 1"
+        );
+        assert_eq!(
+            pack_set.read(ObjectKind::Commit, b"\x02").unwrap().unwrap(),
+            b"commit 2"
+        );
+    }
+
+    #[test]
+    fn test_repack_loose_only() {
+        let temp_dir = testutils::new_temp_dir();
+        let store_path = temp_dir.path();
+        init_store(store_path);
+        write_loose(store_path, ObjectKind::Commit, b"\x01", b"commit 1");
+        repack(store_path, &PackSet::default(), RepackScope::Loose).unwrap();
+        write_loose(store_path, ObjectKind::Commit, b"\x02", b"commit 2");
+        let pack_set = PackSet::load(store_path).unwrap();
+        let stats = repack(store_path, &pack_set, RepackScope::Loose).unwrap();
+        assert_eq!(stats.num_objects, 1);
+        assert_eq!(stats.num_loose_removed, 1);
+        assert_eq!(stats.num_packs_removed, 0);
+
+        // Each pack has an index next to it
+        let mut file_names = fs::read_dir(store_path.join(PACKS_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .map(|path| path.extension().unwrap().to_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        file_names.sort();
+        assert_eq!(file_names, ["idx", "idx", "pack", "pack"]);
+
+        let pack_set = PackSet::load(store_path).unwrap();
+        assert_eq!(pack_set.num_objects(), 2);
+        assert_eq!(
+            pack_set.read(ObjectKind::Commit, b"\x01").unwrap().unwrap(),
+            b"commit 1"
+        );
+        assert_eq!(
+            pack_set.read(ObjectKind::Commit, b"\x02").unwrap().unwrap(),
+            b"commit 2"
+        );
+
+        // Nothing to do without loose objects
+        let stats = repack(store_path, &pack_set, RepackScope::Loose).unwrap();
+        assert_eq!(stats, RepackStats::default());
+    }
+
+    #[test]
+    fn test_pack_index_lookup() {
+        let temp_dir = testutils::new_temp_dir();
+        let store_path = temp_dir.path();
+        init_store(store_path);
+        // Written in an order different from the sorted one
+        for id in [b"\x03", b"\x01", b"\x02"] {
+            write_loose(store_path, ObjectKind::Tree, id, &[b'x'; 100]);
+        }
+        write_loose(store_path, ObjectKind::Commit, b"\x02", b"commit");
+        repack(store_path, &PackSet::default(), RepackScope::All).unwrap();
+
+        let pack_set = PackSet::load(store_path).unwrap();
+        for id in [b"\x01", b"\x02", b"\x03"] {
+            assert_eq!(
+                pack_set.read(ObjectKind::Tree, id).unwrap().unwrap(),
+                [b'x'; 100]
+            );
+        }
+        assert_eq!(
+            pack_set.read(ObjectKind::Commit, b"\x02").unwrap().unwrap(),
+            b"commit"
+        );
+        assert!(!pack_set.contains(ObjectKind::Commit, b"\x01"));
+        assert!(!pack_set.contains(ObjectKind::Tree, b"\x04"));
+    }
+}
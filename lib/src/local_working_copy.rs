@@ -25,7 +25,7 @@ use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Sender};
 use std::sync::Arc;
-use std::time::UNIX_EPOCH;
+use std::time::{Duration, UNIX_EPOCH};
 use std::{fs, iter, mem, slice};
 
 use futures::StreamExt;
@@ -45,6 +45,7 @@ use crate::backend::{
 use crate::commit::Commit;
 use crate::conflicts::{self, materialize_tree_value, MaterializedTreeValue};
 use crate::file_util::{check_symlink_support, try_symlink};
+use crate::fingerprint_cache::{FileFingerprint, FileFingerprintCache};
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::watchman;
 use crate::fsmonitor::FsmonitorKind;
@@ -58,7 +59,7 @@ use crate::merged_tree::{MergedTree, MergedTreeBuilder};
 use crate::object_id::ObjectId;
 use crate::op_store::{OperationId, WorkspaceId};
 use crate::repo_path::{RepoPath, RepoPathBuf, RepoPathComponent};
-use crate::settings::HumanByteSize;
+use crate::settings::{HumanByteSize, UserSettings};
 use crate::store::Store;
 use crate::tree::Tree;
 use crate::working_copy::{
@@ -438,6 +439,14 @@ fn mtime_from_metadata(metadata: &Metadata) -> MillisSinceEpoch {
     )
 }
 
+fn file_fingerprint(path: &RepoPath, metadata: &Metadata) -> FileFingerprint {
+    FileFingerprint {
+        path: path.to_owned(),
+        size: metadata.len(),
+        mtime: mtime_from_metadata(metadata),
+    }
+}
+
 fn file_state(metadata: &Metadata) -> Option<FileState> {
     let metadata_file_type = metadata.file_type();
     let file_type = if metadata_file_type.is_dir() {
@@ -467,6 +476,25 @@ fn file_state(metadata: &Metadata) -> Option<FileState> {
     })
 }
 
+/// Replaces the executable bit read from disk with the one recorded in the
+/// `tree`, so filesystems which don't preserve the bit don't produce spurious
+/// changes.
+fn keep_executable_bit_from_tree(file_state: &mut FileState, tree: &MergedTree, path: &RepoPath) {
+    #[cfg(unix)]
+    if let FileType::Normal { executable } = &mut file_state.file_type {
+        *executable = matches!(
+            tree.path_value(path).as_normal(),
+            Some(TreeValue::File {
+                executable: true,
+                ..
+            })
+        );
+    }
+    // The executable bit is never read from disk on Windows.
+    #[cfg(windows)]
+    let _ = (file_state, tree, path);
+}
+
 struct FsmonitorMatcher {
     matcher: Option<Box<dyn Matcher>>,
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
@@ -669,12 +697,33 @@ impl TreeState {
         &self,
         path: &RepoPath,
         disk_path: &Path,
+        fingerprint_cache: Option<&FileFingerprintCache>,
     ) -> Result<FileId, SnapshotError> {
         let mut file = File::open(disk_path).map_err(|err| SnapshotError::Other {
             message: format!("Failed to open file {}", disk_path.display()),
             err: err.into(),
         })?;
-        Ok(self.store.write_file(path, &mut file)?)
+        // Fingerprint the opened file rather than the path so the fingerprint
+        // describes the content which is read below.
+        let fingerprint = match fingerprint_cache {
+            Some(cache) => {
+                let metadata = file.metadata().map_err(|err| SnapshotError::Other {
+                    message: format!("Failed to stat file {}", disk_path.display()),
+                    err: err.into(),
+                })?;
+                let fingerprint = file_fingerprint(path, &metadata);
+                if let Some(id) = cache.get(&fingerprint) {
+                    return Ok(id);
+                }
+                Some(fingerprint)
+            }
+            None => None,
+        };
+        let id = self.store.write_file(path, &mut file)?;
+        if let (Some(cache), Some(fingerprint)) = (fingerprint_cache, fingerprint) {
+            cache.insert(fingerprint, id.clone());
+        }
+        Ok(id)
     }
 
     fn write_symlink_to_store(
@@ -737,6 +786,8 @@ impl TreeState {
             fsmonitor_kind,
             progress,
             max_new_file_size,
+            ignore_executable_bit,
+            fingerprint_cache,
         } = options;
 
         let sparse_matcher = self.sparse_matcher();
@@ -780,6 +831,8 @@ impl TreeState {
                 directory_to_visit,
                 progress,
                 max_new_file_size,
+                ignore_executable_bit,
+                fingerprint_cache,
             )
         })?;
 
@@ -853,6 +906,8 @@ impl TreeState {
         directory_to_visit: DirectoryToVisit,
         progress: Option<&SnapshotProgress>,
         max_new_file_size: u64,
+        ignore_executable_bit: bool,
+        fingerprint_cache: Option<&FileFingerprintCache>,
     ) -> Result<(), SnapshotError> {
         let DirectoryToVisit {
             dir,
@@ -933,6 +988,7 @@ impl TreeState {
                                     Some(&current_file_state),
                                     current_tree,
                                     &new_file_state,
+                                    fingerprint_cache,
                                 )?;
                                 if let Some(tree_value) = update {
                                     tree_entries_tx
@@ -962,6 +1018,8 @@ impl TreeState {
                             directory_to_visit,
                             progress,
                             max_new_file_size,
+                            ignore_executable_bit,
+                            fingerprint_cache,
                         )?;
                     }
                 } else if matcher.matches(&path) {
@@ -987,7 +1045,14 @@ impl TreeState {
                                 max_size: HumanByteSize(max_new_file_size),
                             });
                         }
-                        if let Some(new_file_state) = file_state(&metadata) {
+                        if let Some(mut new_file_state) = file_state(&metadata) {
+                            if ignore_executable_bit {
+                                keep_executable_bit_from_tree(
+                                    &mut new_file_state,
+                                    current_tree,
+                                    &path,
+                                );
+                            }
                             present_files_tx.send(path.clone()).ok();
                             let update = self.get_updated_tree_value(
                                 &path,
@@ -995,6 +1060,7 @@ impl TreeState {
                                 maybe_current_file_state.as_ref(),
                                 current_tree,
                                 &new_file_state,
+                                fingerprint_cache,
                             )?;
                             if let Some(tree_value) = update {
                                 tree_entries_tx.send((path.clone(), tree_value)).ok();
@@ -1063,6 +1129,7 @@ impl TreeState {
         maybe_current_file_state: Option<&FileState>,
         current_tree: &MergedTree,
         new_file_state: &FileState,
+        fingerprint_cache: Option<&FileFingerprintCache>,
     ) -> Result<Option<MergedTreeValue>, SnapshotError> {
         let clean = match maybe_current_file_state {
             None => {
@@ -1096,6 +1163,7 @@ impl TreeState {
                     &disk_path,
                     &current_tree_values,
                     executable,
+                    fingerprint_cache,
                 )?,
                 FileType::Symlink => {
                     let id = self.write_symlink_to_store(repo_path, &disk_path)?;
@@ -1117,13 +1185,14 @@ impl TreeState {
         disk_path: &Path,
         current_tree_values: &MergedTreeValue,
         executable: FileExecutableFlag,
+        fingerprint_cache: Option<&FileFingerprintCache>,
     ) -> Result<MergedTreeValue, SnapshotError> {
         // If the file contained a conflict before and is now a normal file on disk, we
         // try to parse any conflict markers in the file into a conflict.
         if let Some(current_tree_value) = current_tree_values.as_resolved() {
             #[cfg(unix)]
             let _ = current_tree_value; // use the variable
-            let id = self.write_file_to_store(repo_path, disk_path)?;
+            let id = self.write_file_to_store(repo_path, disk_path, fingerprint_cache)?;
             // On Windows, we preserve the executable bit from the current tree.
             #[cfg(windows)]
             let executable = {
@@ -1484,6 +1553,8 @@ pub struct LocalWorkingCopy {
     state_path: PathBuf,
     checkout_state: OnceCell<CheckoutState>,
     tree_state: OnceCell<TreeState>,
+    lock_timeout: Option<Duration>,
+    symlinks_enabled: bool,
 }
 
 impl WorkingCopy for LocalWorkingCopy {
@@ -1517,7 +1588,12 @@ impl WorkingCopy for LocalWorkingCopy {
 
     fn start_mutation(&self) -> Result<Box<dyn LockedWorkingCopy>, WorkingCopyStateError> {
         let lock_path = self.state_path.join("working_copy.lock");
-        let lock = FileLock::lock(lock_path);
+        let lock = FileLock::lock_with_timeout(lock_path, self.lock_timeout).map_err(|err| {
+            WorkingCopyStateError {
+                message: "Failed to lock working copy".to_owned(),
+                err: err.into(),
+            }
+        })?;
 
         let wc = LocalWorkingCopy {
             store: self.store.clone(),
@@ -1528,6 +1604,8 @@ impl WorkingCopy for LocalWorkingCopy {
             // TODO: It's expensive to reload the whole tree. We should copy it from `self` if it
             // hasn't changed.
             tree_state: OnceCell::new(),
+            lock_timeout: self.lock_timeout,
+            symlinks_enabled: self.symlinks_enabled,
         };
         let old_operation_id = wc.operation_id().clone();
         let old_tree_id = wc.tree_id()?.clone();
@@ -1579,6 +1657,8 @@ impl LocalWorkingCopy {
             state_path,
             checkout_state: OnceCell::new(),
             tree_state: OnceCell::with_value(tree_state),
+            lock_timeout: None,
+            symlinks_enabled: true,
         })
     }
 
@@ -1593,9 +1673,28 @@ impl LocalWorkingCopy {
             state_path,
             checkout_state: OnceCell::new(),
             tree_state: OnceCell::new(),
+            lock_timeout: None,
+            symlinks_enabled: true,
         }
     }
 
+    /// Sets how long `start_mutation()` waits for another process to release
+    /// the working-copy lock. Waits indefinitely if `None`.
+    pub fn with_lock_timeout(mut self, lock_timeout: Option<Duration>) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+
+    /// If `symlinks_enabled` is false, symlinks are checked out as regular
+    /// files containing the link target even if the platform supports symlinks.
+    pub fn with_symlinks_enabled(mut self, symlinks_enabled: bool) -> Self {
+        self.symlinks_enabled = symlinks_enabled;
+        if let Some(tree_state) = self.tree_state.get_mut() {
+            tree_state.symlink_support &= symlinks_enabled;
+        }
+        self
+    }
+
     pub fn state_path(&self) -> &Path {
         &self.state_path
     }
@@ -1637,11 +1736,13 @@ impl LocalWorkingCopy {
     fn tree_state(&self) -> Result<&TreeState, WorkingCopyStateError> {
         self.tree_state
             .get_or_try_init(|| {
-                TreeState::load(
+                let mut tree_state = TreeState::load(
                     self.store.clone(),
                     self.working_copy_path.clone(),
                     self.state_path.clone(),
-                )
+                )?;
+                tree_state.symlink_support &= self.symlinks_enabled;
+                Ok::<_, TreeStateError>(tree_state)
             })
             .map_err(|err| WorkingCopyStateError {
                 message: "Failed to read working copy state".to_string(),
@@ -1689,14 +1790,16 @@ impl WorkingCopyFactory for LocalWorkingCopyFactory {
         state_path: PathBuf,
         operation_id: OperationId,
         workspace_id: WorkspaceId,
+        settings: &UserSettings,
     ) -> Result<Box<dyn WorkingCopy>, WorkingCopyStateError> {
-        Ok(Box::new(LocalWorkingCopy::init(
+        let wc = LocalWorkingCopy::init(
             store,
             working_copy_path,
             state_path,
             operation_id,
             workspace_id,
-        )?))
+        )?;
+        Ok(Box::new(configure_from_settings(wc, settings)?))
     }
 
     fn load_working_copy(
@@ -1704,11 +1807,28 @@ impl WorkingCopyFactory for LocalWorkingCopyFactory {
         store: Arc<Store>,
         working_copy_path: PathBuf,
         state_path: PathBuf,
-    ) -> Box<dyn WorkingCopy> {
-        Box::new(LocalWorkingCopy::load(store, working_copy_path, state_path))
+        settings: &UserSettings,
+    ) -> Result<Box<dyn WorkingCopy>, WorkingCopyStateError> {
+        let wc = LocalWorkingCopy::load(store, working_copy_path, state_path);
+        Ok(Box::new(configure_from_settings(wc, settings)?))
     }
 }
 
+fn configure_from_settings(
+    wc: LocalWorkingCopy,
+    settings: &UserSettings,
+) -> Result<LocalWorkingCopy, WorkingCopyStateError> {
+    let to_state_err = |err: config::ConfigError| WorkingCopyStateError {
+        message: "Invalid working copy configuration".to_owned(),
+        err: err.into(),
+    };
+    let lock_timeout = settings.lock_timeout().map_err(to_state_err)?;
+    let symlinks_enabled = settings.symlinks_enabled().map_err(to_state_err)?;
+    Ok(wc
+        .with_lock_timeout(lock_timeout)
+        .with_symlinks_enabled(symlinks_enabled))
+}
+
 /// A working copy that's locked on disk. The lock is held until you call
 /// `finish()` or `discard()`.
 pub struct LockedLocalWorkingCopy {
@@ -18,7 +18,104 @@
 #[cfg_attr(not(unix), path = "lock/fallback.rs")]
 mod platform;
 
+use std::fmt;
+use std::path::{Path, PathBuf};
+
 pub use platform::FileLock;
+use thiserror::Error;
+
+/// The process holding a lock, as recorded in the lock file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub hostname: String,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        LockOwner {
+            pid: std::process::id(),
+            hostname: whoami::fallible::hostname().unwrap_or_default(),
+        }
+    }
+
+    fn parse(contents: &str) -> Option<Self> {
+        let (pid, hostname) = contents.trim_end().split_once('@')?;
+        Some(LockOwner {
+            pid: pid.parse().ok()?,
+            hostname: hostname.to_owned(),
+        })
+    }
+
+    fn read_from(path: &Path) -> Option<Self> {
+        Self::parse(&std::fs::read_to_string(path).ok()?)
+    }
+
+    /// Returns true if the owner ran on this host and is known to have exited,
+    /// which means the lock was left behind (or inherited by a child process).
+    fn is_stale(&self) -> bool {
+        self.hostname == whoami::fallible::hostname().unwrap_or_default()
+            && !process_exists(self.pid)
+    }
+}
+
+#[cfg(unix)]
+fn process_exists(pid: u32) -> bool {
+    let Some(pid) = i32::try_from(pid)
+        .ok()
+        .and_then(rustix::process::Pid::from_raw)
+    else {
+        return true;
+    };
+    rustix::process::test_kill_process(pid) != Err(rustix::io::Errno::SRCH)
+}
+
+#[cfg(not(unix))]
+fn process_exists(_pid: u32) -> bool {
+    // Not implemented; assume the owner is alive.
+    true
+}
+
+impl fmt::Display for LockOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.pid, self.hostname)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FileLockError {
+    #[error(
+        "Timed out waiting for lock {}{}",
+        path.display(),
+        owner.as_ref().map(|owner| format!(" held by process {owner}")).unwrap_or_default()
+    )]
+    Timeout {
+        path: PathBuf,
+        owner: Option<LockOwner>,
+    },
+    #[error(
+        "Timed out waiting for lock {} held by process {owner}, which is no longer running",
+        path.display()
+    )]
+    Stale { path: PathBuf, owner: LockOwner },
+    #[error("Failed to lock {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        err: std::io::Error,
+    },
+}
+
+impl FileLockError {
+    /// Creates the error for a lock at `path` that couldn't be acquired in
+    /// time, reporting its owner if it's recorded in the lock file.
+    fn timeout(path: PathBuf) -> Self {
+        match LockOwner::read_from(&path) {
+            Some(owner) if owner.is_stale() => FileLockError::Stale { path, owner },
+            owner => FileLockError::Timeout { path, owner },
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -26,6 +123,8 @@ mod tests {
     use std::time::Duration;
     use std::{fs, thread};
 
+    use assert_matches::assert_matches;
+
     use super::*;
 
     #[test]
@@ -34,7 +133,7 @@ mod tests {
         let lock_path = temp_dir.path().join("test.lock");
         assert!(!lock_path.exists());
         {
-            let _lock = FileLock::lock(lock_path.clone());
+            let _lock = FileLock::lock(lock_path.clone()).unwrap();
             assert!(lock_path.exists());
         }
         assert!(!lock_path.exists());
@@ -50,7 +149,7 @@ mod tests {
         thread::scope(|s| {
             for _ in 0..num_threads {
                 s.spawn(|| {
-                    let _lock = FileLock::lock(lock_path.clone());
+                    let _lock = FileLock::lock(lock_path.clone()).unwrap();
                     let data = fs::read(&data_path).unwrap();
                     let value = u32::from_le_bytes(data.try_into().unwrap());
                     thread::sleep(Duration::from_millis(1));
@@ -62,4 +161,40 @@ mod tests {
         let value = u32::from_le_bytes(data.try_into().unwrap());
         assert_eq!(value, num_threads as u32);
     }
+
+    #[test]
+    fn lock_timeout_when_held() {
+        let temp_dir = testutils::new_temp_dir();
+        let lock_path = temp_dir.path().join("test.lock");
+        let _lock = FileLock::lock(lock_path.clone()).unwrap();
+        let err = FileLock::lock_with_timeout(lock_path.clone(), Some(Duration::from_millis(10)))
+            .err()
+            .unwrap();
+        assert_matches!(
+            err,
+            FileLockError::Timeout { path, owner } if path == lock_path
+                && owner == Some(LockOwner::current())
+        );
+    }
+
+    #[test]
+    fn lock_owner_parse() {
+        let owner = LockOwner {
+            pid: 123,
+            hostname: "host@example.com".to_owned(),
+        };
+        assert_eq!(LockOwner::parse(&owner.to_string()), Some(owner));
+        assert_eq!(LockOwner::parse("123"), None);
+        assert_eq!(LockOwner::parse("abc@host"), None);
+    }
+
+    #[test]
+    fn lock_owner_is_stale() {
+        assert!(!LockOwner::current().is_stale());
+        let other_host = LockOwner {
+            hostname: format!("not-{}", LockOwner::current().hostname),
+            ..LockOwner::current()
+        };
+        assert!(!other_host.is_stale());
+    }
 }
@@ -13,27 +13,47 @@
 // limitations under the License.
 
 use std::fs::{File, OpenOptions};
+use std::io::Write as _;
 use std::path::PathBuf;
 use std::time::Duration;
 
 use backoff::{retry, ExponentialBackoff};
 use tracing::instrument;
 
+use super::{FileLockError, LockOwner};
+
 pub struct FileLock {
     path: PathBuf,
     _file: File,
 }
 
 impl FileLock {
-    pub fn lock(path: PathBuf) -> FileLock {
+    /// Locks the file at `path`, waiting up to 10 seconds if it's locked by
+    /// someone else.
+    pub fn lock(path: PathBuf) -> Result<FileLock, FileLockError> {
+        Self::lock_with_timeout(path, None)
+    }
+
+    /// Locks the file at `path`, waiting at most `timeout` if it's locked by
+    /// someone else. Waits 10 seconds if `timeout` is `None`.
+    pub fn lock_with_timeout(
+        path: PathBuf,
+        timeout: Option<Duration>,
+    ) -> Result<FileLock, FileLockError> {
         let mut options = OpenOptions::new();
         options.create_new(true);
         options.write(true);
         let try_write_lock_file = || match options.open(&path) {
-            Ok(file) => Ok(FileLock {
-                path: path.clone(),
-                _file: file,
-            }),
+            Ok(mut file) => {
+                // Record the owner so processes waiting for the lock can report it. This
+                // is informational only.
+                file.write_all(LockOwner::current().to_string().as_bytes())
+                    .map_err(backoff::Error::Permanent)?;
+                Ok(FileLock {
+                    path: path.clone(),
+                    _file: file,
+                })
+            }
             Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
                 Err(backoff::Error::Transient {
                     err,
@@ -50,16 +70,13 @@ impl FileLock {
         };
         let backoff = ExponentialBackoff {
             initial_interval: Duration::from_millis(1),
-            max_elapsed_time: Some(Duration::from_secs(10)),
+            max_elapsed_time: Some(timeout.unwrap_or(Duration::from_secs(10))),
             ..Default::default()
         };
         match retry(backoff, try_write_lock_file) {
-            Err(err) => panic!(
-                "failed to create lock file {}: {}",
-                path.to_string_lossy(),
-                err
-            ),
-            Ok(file_lock) => file_lock,
+            Ok(file_lock) => Ok(file_lock),
+            Err(backoff::Error::Transient { .. }) => Err(FileLockError::timeout(path)),
+            Err(backoff::Error::Permanent(err)) => Err(FileLockError::Io { path, err }),
         }
     }
 }
@@ -14,27 +14,60 @@
 
 #![allow(missing_docs)]
 
-use std::fs::File;
+use std::fs::{File, OpenOptions};
+use std::io::Write as _;
 use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use rustix::fs::FlockOperation;
 use tracing::instrument;
 
+use super::{FileLockError, LockOwner};
+
 pub struct FileLock {
     path: PathBuf,
     file: File,
 }
 
 impl FileLock {
-    pub fn lock(path: PathBuf) -> FileLock {
+    /// Locks the file at `path`, waiting indefinitely if it's locked by
+    /// someone else.
+    pub fn lock(path: PathBuf) -> Result<FileLock, FileLockError> {
+        Self::lock_with_timeout(path, None)
+    }
+
+    /// Locks the file at `path`, waiting at most `timeout` if it's locked by
+    /// someone else. Waits indefinitely if `timeout` is `None`.
+    pub fn lock_with_timeout(
+        path: PathBuf,
+        timeout: Option<Duration>,
+    ) -> Result<FileLock, FileLockError> {
+        let to_io_err = |err: std::io::Error| FileLockError::Io {
+            path: path.clone(),
+            err,
+        };
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
         loop {
-            // Create lockfile, or open pre-existing one
-            let file = File::create(&path).expect("failed to open lockfile");
+            // Create lockfile, or open pre-existing one. Don't truncate it since the
+            // current holder has recorded itself in it.
+            let mut file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)
+                .map_err(to_io_err)?;
             // If the lock was already held, wait for it to be released
-            rustix::fs::flock(&file, FlockOperation::LockExclusive)
-                .expect("failed to lock lockfile");
+            if let Some(deadline) = deadline {
+                if !flock_until(&file, deadline).map_err(to_io_err)? {
+                    return Err(FileLockError::timeout(path));
+                }
+            } else {
+                rustix::fs::flock(&file, FlockOperation::LockExclusive)
+                    .map_err(|err| to_io_err(err.into()))?;
+            }
 
-            let stat = rustix::fs::fstat(&file).expect("failed to stat lockfile");
+            let stat = rustix::fs::fstat(&file).map_err(|err| to_io_err(err.into()))?;
             if stat.st_nlink == 0 {
                 // Lockfile was deleted, probably by the previous holder's `Drop` impl; create a
                 // new one so our ownership is visible, rather than hidden in an
@@ -43,8 +76,32 @@ impl FileLock {
                 continue;
             }
 
-            return Self { path, file };
+            // Record the owner so processes waiting for the lock can report it. This is
+            // informational only.
+            file.set_len(0).map_err(to_io_err)?;
+            file.write_all(LockOwner::current().to_string().as_bytes())
+                .map_err(to_io_err)?;
+            return Ok(Self { path, file });
+        }
+    }
+}
+
+/// Tries to lock `file` until `deadline`. Returns false if the lock couldn't be
+/// acquired in time.
+fn flock_until(file: &File, deadline: Instant) -> std::io::Result<bool> {
+    let mut interval = Duration::from_millis(1);
+    loop {
+        match rustix::fs::flock(file, FlockOperation::NonBlockingLockExclusive) {
+            Ok(()) => return Ok(true),
+            Err(rustix::io::Errno::WOULDBLOCK) => {}
+            Err(err) => return Err(err.into()),
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(false);
         }
+        thread::sleep(interval.min(deadline - now));
+        interval = (interval * 2).min(Duration::from_millis(100));
     }
 }
 
@@ -0,0 +1,227 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mapping of author/committer identities as in Git's `.mailmap` file.
+
+use std::io;
+use std::path::Path;
+
+use itertools::Itertools as _;
+
+/// One line of the mailmap file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct MailmapEntry {
+    proper_name: Option<String>,
+    proper_email: Option<String>,
+    commit_name: Option<String>,
+    commit_email: String,
+}
+
+/// Table that maps identities recorded in commits to canonical identities.
+///
+/// Email addresses are compared case-insensitively as Git does.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Mailmap {
+    entries: Vec<MailmapEntry>,
+}
+
+impl Mailmap {
+    /// Creates an empty mailmap which maps every identity to itself.
+    pub fn empty() -> Self {
+        Mailmap::default()
+    }
+
+    /// Parses the content of a mailmap file. Malformed lines are ignored.
+    pub fn parse(text: &str) -> Self {
+        let entries = text.lines().filter_map(parse_line).collect();
+        Mailmap { entries }
+    }
+
+    /// Loads mailmap file at the given `path`. Missing file is treated as
+    /// empty.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(Self::parse(&text)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::empty()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns true if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Resolves the given identity to the canonical `(name, email)`.
+    pub fn resolve(&self, name: &str, email: &str) -> (String, String) {
+        // Entries with a commit name take precedence over email-only entries.
+        let entry = self
+            .entries
+            .iter()
+            .filter(|entry| entry.commit_email.eq_ignore_ascii_case(email))
+            .filter(|entry| entry.commit_name.as_ref().map_or(true, |n| n == name))
+            .max_by_key(|entry| entry.commit_name.is_some());
+        let Some(entry) = entry else {
+            return (name.to_owned(), email.to_owned());
+        };
+        (
+            entry.proper_name.as_deref().unwrap_or(name).to_owned(),
+            entry.proper_email.as_deref().unwrap_or(email).to_owned(),
+        )
+    }
+
+    /// Returns the emails recorded in commits which are mapped to a canonical
+    /// identity whose name or email satisfies `predicate`.
+    ///
+    /// Entries restricted to a commit name are matched by email only.
+    pub fn aliases_matching(&self, predicate: impl Fn(&str) -> bool) -> Vec<&str> {
+        let mut emails: Vec<&str> = self
+            .entries
+            .iter()
+            .filter(|entry| {
+                let proper_email = entry.proper_email.as_ref().unwrap_or(&entry.commit_email);
+                entry.proper_name.as_deref().is_some_and(&predicate) || predicate(proper_email)
+            })
+            .map(|entry| entry.commit_email.as_str())
+            .collect();
+        emails.sort_unstable_by_key(|email| email.to_ascii_lowercase());
+        emails.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        emails
+    }
+
+    /// Returns the emails which are mapped to the same canonical email as
+    /// `email`, including `email` itself and the canonical email.
+    pub fn aliases_of_email(&self, email: &str) -> Vec<String> {
+        let (_, canonical) = self.resolve("", email);
+        let mut emails = self
+            .aliases_matching(|s| s.eq_ignore_ascii_case(&canonical))
+            .into_iter()
+            .map(|s| s.to_owned())
+            .collect_vec();
+        emails.push(email.to_owned());
+        emails.push(canonical);
+        emails.sort_unstable_by_key(|email| email.to_ascii_lowercase());
+        emails.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        emails
+    }
+}
+
+/// Parses `[proper name] [<proper email>] [commit name] <commit email>`.
+fn parse_line(line: &str) -> Option<MailmapEntry> {
+    let line = line.split('#').next().unwrap();
+    let (name1, email1, rest) = split_name_email(line)?;
+    match split_name_email(rest) {
+        Some((name2, email2, _)) => Some(MailmapEntry {
+            proper_name: name1,
+            proper_email: Some(email1),
+            commit_name: name2,
+            commit_email: email2,
+        }),
+        None => Some(MailmapEntry {
+            proper_name: name1,
+            proper_email: None,
+            commit_name: None,
+            commit_email: email1,
+        }),
+    }
+}
+
+fn split_name_email(text: &str) -> Option<(Option<String>, String, &str)> {
+    let (name, rest) = text.split_once('<')?;
+    let (email, rest) = rest.split_once('>')?;
+    let name = name.trim();
+    let name = (!name.is_empty()).then(|| name.to_owned());
+    Some((name, email.trim().to_owned(), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_resolve() {
+        let mailmap = Mailmap::parse(
+            "# comment\nAlice Example <alice@example.com>\n<alice@example.com> \
+             <alice@old.example.com>\nAlice Example <alice@example.com> alice \
+             <root@localhost>\nBob <bob@example.com> <BOB@Laptop>  # trailing comment\nmalformed \
+             line\n",
+        );
+        assert_eq!(mailmap.entries.len(), 4);
+        assert_eq!(
+            mailmap.resolve("alice", "alice@example.com"),
+            ("Alice Example".to_owned(), "alice@example.com".to_owned())
+        );
+        assert_eq!(
+            mailmap.resolve("Alice", "alice@old.example.com"),
+            ("Alice".to_owned(), "alice@example.com".to_owned())
+        );
+        assert_eq!(
+            mailmap.resolve("alice", "root@localhost"),
+            ("Alice Example".to_owned(), "alice@example.com".to_owned())
+        );
+        assert_eq!(
+            mailmap.resolve("someone", "root@localhost"),
+            ("someone".to_owned(), "root@localhost".to_owned())
+        );
+        assert_eq!(
+            mailmap.resolve("bob", "bob@laptop"),
+            ("Bob".to_owned(), "bob@example.com".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_aliases() {
+        let mailmap = Mailmap::parse(
+            "<alice@example.com> <alice@old.example.com>\nAlice Example <alice@example.com> alice \
+             <root@localhost>\nBob <bob@example.com> <bob@laptop>\n",
+        );
+        assert_eq!(
+            mailmap.aliases_of_email("alice@example.com"),
+            [
+                "alice@example.com",
+                "alice@old.example.com",
+                "root@localhost"
+            ]
+        );
+        assert_eq!(
+            mailmap.aliases_of_email("alice@old.example.com"),
+            [
+                "alice@example.com",
+                "alice@old.example.com",
+                "root@localhost"
+            ]
+        );
+        assert_eq!(
+            mailmap.aliases_of_email("carol@example.com"),
+            ["carol@example.com"]
+        );
+        // Emails are compared case-insensitively
+        assert_eq!(
+            mailmap.aliases_of_email("ALICE@example.com"),
+            [
+                "ALICE@example.com",
+                "alice@old.example.com",
+                "root@localhost"
+            ]
+        );
+        assert_eq!(
+            mailmap.aliases_matching(|s| s.starts_with("Alice")),
+            ["root@localhost"]
+        );
+        assert_eq!(
+            mailmap.aliases_matching(|s| s == "bob@example.com"),
+            ["bob@laptop"]
+        );
+    }
+}
@@ -19,7 +19,7 @@ use std::iter;
 
 use tracing::instrument;
 
-use crate::repo_path::{RepoPath, RepoPathComponentBuf};
+use crate::repo_path::{RepoPath, RepoPathBuf, RepoPathComponentBuf};
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum Visit {
@@ -259,6 +259,120 @@ impl Matcher for IntersectionMatcher<'_> {
     }
 }
 
+/// Matches file paths with glob patterns.
+///
+/// Patterns are matched against the path relative to the directory they were
+/// anchored to. `*` and `?` don't match path separators, `**` does.
+#[derive(Clone, Debug)]
+pub struct FileGlobsMatcher {
+    globs: Vec<(RepoPathBuf, glob::Pattern)>,
+}
+
+impl FileGlobsMatcher {
+    pub fn new<D: AsRef<RepoPath>>(
+        dir_patterns: impl IntoIterator<Item = (D, glob::Pattern)>,
+    ) -> Self {
+        let globs = dir_patterns
+            .into_iter()
+            .map(|(dir, pattern)| (dir.as_ref().to_owned(), pattern))
+            .collect();
+        FileGlobsMatcher { globs }
+    }
+}
+
+impl Matcher for FileGlobsMatcher {
+    fn matches(&self, file: &RepoPath) -> bool {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        self.globs.iter().any(|(dir, pattern)| {
+            file.strip_prefix(dir)
+                .is_some_and(|tail| pattern.matches_with(tail.as_internal_file_string(), options))
+        })
+    }
+
+    fn visit(&self, dir: &RepoPath) -> Visit {
+        let mut dirs = HashSet::new();
+        for (glob_dir, _) in &self.globs {
+            if dir.starts_with(glob_dir) {
+                return Visit::Specific {
+                    dirs: VisitDirs::All,
+                    files: VisitFiles::All,
+                };
+            }
+            if let Some(tail) = glob_dir.strip_prefix(dir) {
+                dirs.extend(tail.components().next().map(|name| name.to_owned()));
+            }
+        }
+        Visit::sets(dirs, HashSet::new())
+    }
+}
+
+/// Matches paths that are matched by any of the input matchers.
+#[derive(Clone, Debug)]
+pub struct UnionMatcher<M1, M2> {
+    input1: M1,
+    input2: M2,
+}
+
+impl<M1: Matcher, M2: Matcher> UnionMatcher<M1, M2> {
+    pub fn new(input1: M1, input2: M2) -> Self {
+        Self { input1, input2 }
+    }
+}
+
+impl<M1: Matcher, M2: Matcher> Matcher for UnionMatcher<M1, M2> {
+    fn matches(&self, file: &RepoPath) -> bool {
+        self.input1.matches(file) || self.input2.matches(file)
+    }
+
+    fn visit(&self, dir: &RepoPath) -> Visit {
+        match self.input1.visit(dir) {
+            Visit::AllRecursively => Visit::AllRecursively,
+            Visit::Nothing => self.input2.visit(dir),
+            Visit::Specific {
+                dirs: dirs1,
+                files: files1,
+            } => match self.input2.visit(dir) {
+                Visit::AllRecursively => Visit::AllRecursively,
+                Visit::Nothing => Visit::Specific {
+                    dirs: dirs1,
+                    files: files1,
+                },
+                Visit::Specific {
+                    dirs: dirs2,
+                    files: files2,
+                } => {
+                    let dirs = match (dirs1, dirs2) {
+                        (VisitDirs::Set(dirs1), VisitDirs::Set(dirs2)) => {
+                            VisitDirs::Set(dirs1.union(&dirs2).cloned().collect())
+                        }
+                        _ => VisitDirs::All,
+                    };
+                    let files = match (files1, files2) {
+                        (VisitFiles::Set(files1), VisitFiles::Set(files2)) => {
+                            VisitFiles::Set(files1.union(&files2).cloned().collect())
+                        }
+                        _ => VisitFiles::All,
+                    };
+                    Visit::Specific { dirs, files }
+                }
+            },
+        }
+    }
+}
+
+impl<M: Matcher + ?Sized> Matcher for Box<M> {
+    fn matches(&self, file: &RepoPath) -> bool {
+        <M as Matcher>::matches(self, file)
+    }
+
+    fn visit(&self, dir: &RepoPath) -> Visit {
+        <M as Matcher>::visit(self, dir)
+    }
+}
+
 /// Keeps track of which subdirectories and files of each directory need to be
 /// visited.
 #[derive(PartialEq, Eq, Debug)]
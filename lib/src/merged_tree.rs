@@ -25,6 +25,7 @@ use std::{iter, vec};
 use futures::stream::StreamExt;
 use futures::{Future, Stream, TryStreamExt};
 use itertools::Itertools;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::backend::{BackendError, BackendResult, ConflictId, MergedTreeId, TreeId, TreeValue};
 use crate::matchers::{EverythingMatcher, Matcher};
@@ -488,10 +489,33 @@ fn merge_trees(merge: &Merge<Tree>) -> Result<Merge<Tree>, TreeMergeError> {
     // any conflicts.
     let mut new_tree = backend::Tree::default();
     let mut conflicts = vec![];
+    // Trivially resolvable entries are handled inline. The remaining entries
+    // may require merging subtrees recursively or merging file contents, which
+    // is where the CPU time goes, so they are merged on the rayon thread pool.
+    // The merged objects are still written to the backend one at a time as
+    // they're produced; only their syncing can be deferred (`store.fsync`).
+    let mut pending = vec![];
     for basename in all_tree_basenames(merge) {
         let path_merge = merge.map(|tree| tree.value(basename).cloned());
-        let path = dir.join(basename);
-        let path_merge = merge_tree_values(store, &path, path_merge)?;
+        if let Some(value) = path_merge.resolve_trivial() {
+            new_tree.set_or_remove(basename, value.clone());
+        } else {
+            pending.push((basename, path_merge));
+        }
+    }
+    let merge_entry = |(basename, path_merge): (&RepoPathComponent, MergedTreeValue)| {
+        merge_tree_values(store, &dir.join(basename), path_merge)
+    };
+    let basenames = pending.iter().map(|(basename, _)| *basename).collect_vec();
+    let merged_values: Vec<_> = if pending.len() > 1 {
+        pending
+            .into_par_iter()
+            .map(merge_entry)
+            .collect::<Result<_, _>>()?
+    } else {
+        pending.into_iter().map(merge_entry).try_collect()?
+    };
+    for (basename, path_merge) in zip(basenames, merged_values) {
         match path_merge.into_resolved() {
             Ok(value) => {
                 new_tree.set_or_remove(basename, value);
@@ -30,6 +30,14 @@ use crate::operation::Operation;
 pub enum OpHeadResolutionError {
     #[error("Operation log has no heads")]
     NoHeads,
+    #[error(transparent)]
+    OpHeadsStore(#[from] OpHeadsStoreError),
+}
+
+#[derive(Debug, Error)]
+pub enum OpHeadsStoreError {
+    #[error("Failed to lock operation heads store")]
+    Lock(#[source] Box<dyn std::error::Error + Send + Sync>),
 }
 
 pub trait OpHeadsStoreLock {}
@@ -51,7 +59,7 @@ pub trait OpHeadsStore: Send + Sync + Debug {
     /// is to prevent concurrent processes from resolving the same divergent
     /// operations. It is not needed for correctness; implementations are free
     /// to return a type that doesn't hold.
-    fn lock(&self) -> Box<dyn OpHeadsStoreLock + '_>;
+    fn lock(&self) -> Result<Box<dyn OpHeadsStoreLock + '_>, OpHeadsStoreError>;
 }
 
 // Given an OpHeadsStore, fetch and resolve its op heads down to one under a
@@ -87,7 +95,9 @@ where
     // Note that the locking isn't necessary for correctness; we take the lock
     // only to prevent other concurrent processes from doing the same work (and
     // producing another set of divergent heads).
-    let _lock = op_heads_store.lock();
+    let _lock = op_heads_store
+        .lock()
+        .map_err(OpHeadResolutionError::OpHeadsStore)?;
     let op_head_ids = op_heads_store.get_op_heads();
 
     if op_head_ids.is_empty() {
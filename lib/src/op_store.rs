@@ -24,8 +24,9 @@ use itertools::Itertools as _;
 use once_cell::sync::Lazy;
 use thiserror::Error;
 
-use crate::backend::{CommitId, MillisSinceEpoch, Timestamp};
-use crate::content_hash::ContentHash;
+use crate::backend::{ChangeId, CommitId, MillisSinceEpoch, Timestamp};
+use crate::content_hash::{ContentHash, DigestUpdate};
+use crate::dag_walk;
 use crate::merge::Merge;
 use crate::object_id::{id_type, HexPrefix, ObjectId, PrefixResolution};
 
@@ -255,7 +256,7 @@ pub struct BranchTarget<'a> {
 
 /// Represents the way the repo looks at a given time, just like how a Tree
 /// object represents how the file system looks at a given time.
-#[derive(ContentHash, PartialEq, Eq, Clone, Debug, Default)]
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
 pub struct View {
     /// All head commits
     pub head_ids: HashSet<CommitId>,
@@ -271,6 +272,36 @@ pub struct View {
     // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
     // precise: the commit to which we most recently completed an update to).
     pub wc_commit_ids: HashMap<WorkspaceId, CommitId>,
+    /// Local branches which follow a change rather than a commit. When the
+    /// change is rewritten, the branch is moved to the new commit.
+    pub pinned_branches: BTreeMap<String, ChangeId>,
+}
+
+// Implemented by hand so that views without pinned branches keep the ids they
+// had before pinning was supported.
+impl ContentHash for View {
+    fn hash(&self, state: &mut impl DigestUpdate) {
+        let View {
+            head_ids,
+            local_branches,
+            tags,
+            remote_views,
+            git_refs,
+            git_head,
+            wc_commit_ids,
+            pinned_branches,
+        } = self;
+        head_ids.hash(state);
+        local_branches.hash(state);
+        tags.hash(state);
+        remote_views.hash(state);
+        git_refs.hash(state);
+        git_head.hash(state);
+        wc_commit_ids.hash(state);
+        if !pinned_branches.is_empty() {
+            pinned_branches.hash(state);
+        }
+    }
 }
 
 /// Represents the state of the remote repo.
@@ -432,6 +463,23 @@ pub trait OpStore: Send + Sync + Debug {
 
     fn write_operation(&self, contents: &Operation) -> OpStoreResult<OperationId>;
 
+    /// Returns the ids of all operations in the store, including the ones no
+    /// longer reachable from the operation heads.
+    ///
+    /// The default implementation can't list the store, so it only returns the
+    /// operations reachable from `head_ids`.
+    fn all_operation_ids(&self, head_ids: &[OperationId]) -> OpStoreResult<Vec<OperationId>> {
+        dag_walk::dfs_ok(
+            head_ids.iter().cloned().map(Ok),
+            |id: &OperationId| id.clone(),
+            |id: &OperationId| match self.read_operation(id) {
+                Ok(operation) => operation.parents.into_iter().map(Ok).collect_vec(),
+                Err(err) => vec![Err(err)],
+            },
+        )
+        .try_collect()
+    }
+
     /// Resolves an unambiguous operation ID prefix.
     fn resolve_operation_id_prefix(
         &self,
@@ -27,7 +27,7 @@ use crate::op_heads_store::{OpHeadResolutionError, OpHeadsStore};
 use crate::op_store::{OpStore, OpStoreError, OpStoreResult, OperationId};
 use crate::operation::Operation;
 use crate::repo::{ReadonlyRepo, Repo as _, RepoLoader};
-use crate::{dag_walk, op_heads_store};
+use crate::{dag_walk, op_heads_store, op_store};
 
 /// Error that may occur during evaluation of operation set expression.
 #[derive(Debug, Error)]
@@ -224,6 +224,53 @@ pub fn walk_ancestors(head_ops: &[Operation]) -> impl Iterator<Item = OpStoreRes
     .map_ok(|OperationByEndTime(op)| op)
 }
 
+/// Looks up operations which exist in the store but aren't reachable from the
+/// `head_ops`, and returns them in reverse topological order.
+///
+/// Such operations are left behind by `jj op abandon`, or when operation heads
+/// were lost. They stay in the store until garbage collected.
+pub fn find_unreachable_operations(
+    op_store: &Arc<dyn OpStore>,
+    head_ops: &[Operation],
+) -> OpStoreResult<Vec<Operation>> {
+    let reachable_ids: HashSet<OperationId> = walk_ancestors(head_ops)
+        .map_ok(|op| op.id().clone())
+        .try_collect()?;
+    let head_ids = head_ops.iter().map(|op| op.id().clone()).collect_vec();
+    let unreachable_ops: HashMap<OperationId, Operation> = op_store
+        .all_operation_ids(&head_ids)?
+        .into_iter()
+        .filter(|id| !reachable_ids.contains(id))
+        .map(|id| {
+            let data = op_store.read_operation(&id)?;
+            Ok::<_, OpStoreError>((id.clone(), Operation::new(op_store.clone(), id, data)))
+        })
+        .try_collect()?;
+    // Emit the latest operation first to stabilize the order.
+    let mut start_ops = unreachable_ops
+        .values()
+        .cloned()
+        .map(OperationByEndTime)
+        .collect_vec();
+    start_ops.sort_unstable_by(|op1, op2| op1.cmp(op2).reverse());
+    let sorted_ops = dag_walk::topo_order_reverse(
+        start_ops,
+        |OperationByEndTime(op)| op.id().clone(),
+        |OperationByEndTime(op)| {
+            op.parent_ids()
+                .iter()
+                .filter_map(|id| unreachable_ops.get(id))
+                .cloned()
+                .map(OperationByEndTime)
+                .collect_vec()
+        },
+    );
+    Ok(sorted_ops
+        .into_iter()
+        .map(|OperationByEndTime(op)| op)
+        .collect())
+}
+
 /// Stats about `reparent_range()`.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ReparentStats {
@@ -296,3 +343,76 @@ pub fn reparent_range(
         unreachable_count: unreachable_ids.len(),
     })
 }
+
+/// Folds runs of consecutive snapshot operations into the last operation of
+/// each run.
+///
+/// A snapshot operation is folded into its child if the child is also a
+/// snapshot operation, and the history is linear between them. The folded
+/// operation keeps the view and metadata of the child, but inherits the start
+/// time and the parents of the first operation of the run. Head operations are
+/// never folded.
+///
+/// Returns the new head operation ids as well as some stats. If the old
+/// operation heads are remapped to the new heads, the folded operations become
+/// unreachable.
+pub fn compact_snapshots(
+    op_store: &dyn OpStore,
+    head_ops: &[Operation],
+) -> OpStoreResult<ReparentStats> {
+    let ops: Vec<_> = walk_ancestors(head_ops).try_collect()?;
+    let mut children_map: HashMap<&OperationId, Vec<&Operation>> = HashMap::new();
+    for op in &ops {
+        for id in op.parent_ids() {
+            children_map.entry(id).or_default().push(op);
+        }
+    }
+    let is_linear_snapshot =
+        |op: &Operation| op.metadata().is_snapshot && op.parent_ids().len() == 1;
+    let folded_ids: HashSet<&OperationId> = ops
+        .iter()
+        .filter(|op| is_linear_snapshot(op))
+        .filter(|op| match children_map.get(op.id()).map(Vec::as_slice) {
+            Some([child]) => is_linear_snapshot(child),
+            _ => false,
+        })
+        .filter(|op| head_ops.iter().all(|head| head.id() != op.id()))
+        .map(|op| op.id())
+        .collect();
+
+    let mut rewritten_ids: HashMap<OperationId, OperationId> = HashMap::new();
+    let mut folded_data: HashMap<&OperationId, op_store::Operation> = HashMap::new();
+    for old_op in ops.iter().rev() {
+        let mut data = old_op.store_operation().clone();
+        for id in &mut data.parents {
+            if let Some(new_id) = rewritten_ids.get(id) {
+                *id = new_id.clone();
+            }
+        }
+        // Folded operations are never rewritten, so the parent id can be
+        // looked up without remapping.
+        if let [parent_id] = &*data.parents {
+            if let Some(parent_data) = folded_data.remove(parent_id) {
+                data.parents = parent_data.parents;
+                data.metadata.start_time = parent_data.metadata.start_time;
+            }
+        }
+        if folded_ids.contains(old_op.id()) {
+            folded_data.insert(old_op.id(), data);
+        } else if &data != old_op.store_operation() {
+            let new_id = op_store.write_operation(&data)?;
+            rewritten_ids.insert(old_op.id().clone(), new_id);
+        }
+    }
+    assert!(folded_data.is_empty());
+
+    let new_head_ids = head_ops
+        .iter()
+        .map(|op| rewritten_ids.get(op.id()).unwrap_or(op.id()).clone())
+        .collect();
+    Ok(ReparentStats {
+        new_head_ids,
+        rewritten_count: rewritten_ids.len(),
+        unreachable_count: folded_ids.len(),
+    })
+}
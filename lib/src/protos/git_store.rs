@@ -12,6 +12,11 @@ pub struct Commit {
     /// TODO(#1624): delete when we assume that all commits use this format
     #[prost(bool, tag = "10")]
     pub uses_tree_conflict_format: bool,
+    #[prost(btree_map = "string, string", tag = "11")]
+    pub extra: ::prost::alloc::collections::BTreeMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
     #[deprecated]
     #[prost(bool, tag = "8")]
     pub is_open: bool,
@@ -65,6 +65,11 @@ pub struct Commit {
     pub author: ::core::option::Option<commit::Signature>,
     #[prost(message, optional, tag = "7")]
     pub committer: ::core::option::Option<commit::Signature>,
+    #[prost(btree_map = "string, string", tag = "10")]
+    pub extra: ::prost::alloc::collections::BTreeMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
     #[prost(bytes = "vec", optional, tag = "9")]
     pub secure_sig: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
 }
@@ -124,6 +124,12 @@ pub struct View {
     /// Whether "@git" branches have been migrated to remote_targets.
     #[prost(bool, tag = "10")]
     pub has_git_refs_migrated_to_remote: bool,
+    /// Change ids of local branches which follow a change.
+    #[prost(map = "string, bytes", tag = "11")]
+    pub pinned_branches: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::vec::Vec<u8>,
+    >,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -14,7 +14,7 @@
 
 #![allow(missing_docs)]
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::io::ErrorKind;
 use std::ops::Deref;
@@ -125,9 +125,12 @@ impl ReadonlyRepo {
     }
 
     pub fn default_op_heads_store_initializer() -> &'static OpHeadsStoreInitializer<'static> {
-        &|_settings, store_path| {
-            let store = SimpleOpHeadsStore::init(store_path);
-            Box::new(store)
+        &|settings, store_path| {
+            let lock_timeout = settings
+                .lock_timeout()
+                .map_err(|err| BackendInitError(err.into()))?;
+            let store = SimpleOpHeadsStore::init(store_path).with_lock_timeout(lock_timeout);
+            Ok(Box::new(store))
         }
     }
 
@@ -157,7 +160,14 @@ impl ReadonlyRepo {
         let backend = backend_initializer(user_settings, &store_path)?;
         let backend_path = store_path.join("type");
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
-        let store = Store::new(backend, signer, user_settings.use_tree_conflict_format());
+        let store = Store::new(
+            backend,
+            signer,
+            user_settings.use_tree_conflict_format(),
+            &user_settings
+                .object_cache_settings()
+                .map_err(|err| BackendInitError(err.into()))?,
+        );
         let repo_settings = user_settings.with_repo(&repo_path).unwrap();
 
         let op_store_path = repo_path.join("op_store");
@@ -169,7 +179,7 @@ impl ReadonlyRepo {
 
         let op_heads_path = repo_path.join("op_heads");
         fs::create_dir(&op_heads_path).context(&op_heads_path)?;
-        let op_heads_store = op_heads_store_initializer(user_settings, &op_heads_path);
+        let op_heads_store = op_heads_store_initializer(user_settings, &op_heads_path)?;
         let op_heads_type_path = op_heads_path.join("type");
         fs::write(&op_heads_type_path, op_heads_store.name()).context(&op_heads_type_path)?;
         op_heads_store.update_op_heads(&[], op_store.root_operation_id());
@@ -216,7 +226,10 @@ impl ReadonlyRepo {
         tx.mut_repo()
             .add_head(&repo.store().root_commit())
             .expect("failed to add root commit as head");
-        Ok(tx.commit("initialize repo"))
+        let repo = tx
+            .commit("initialize repo")
+            .map_err(|err| BackendInitError(err.into()))?;
+        Ok(repo)
     }
 
     pub fn loader(&self) -> RepoLoader {
@@ -334,7 +347,8 @@ impl Repo for ReadonlyRepo {
 pub type BackendInitializer<'a> =
     dyn Fn(&UserSettings, &Path) -> Result<Box<dyn Backend>, BackendInitError> + 'a;
 pub type OpStoreInitializer<'a> = dyn Fn(&UserSettings, &Path) -> Box<dyn OpStore> + 'a;
-pub type OpHeadsStoreInitializer<'a> = dyn Fn(&UserSettings, &Path) -> Box<dyn OpHeadsStore> + 'a;
+pub type OpHeadsStoreInitializer<'a> =
+    dyn Fn(&UserSettings, &Path) -> Result<Box<dyn OpHeadsStore>, BackendInitError> + 'a;
 pub type IndexStoreInitializer<'a> =
     dyn Fn(&UserSettings, &Path) -> Result<Box<dyn IndexStore>, BackendInitError> + 'a;
 pub type SubmoduleStoreInitializer<'a> =
@@ -343,7 +357,8 @@ pub type SubmoduleStoreInitializer<'a> =
 type BackendFactory =
     Box<dyn Fn(&UserSettings, &Path) -> Result<Box<dyn Backend>, BackendLoadError>>;
 type OpStoreFactory = Box<dyn Fn(&UserSettings, &Path) -> Box<dyn OpStore>>;
-type OpHeadsStoreFactory = Box<dyn Fn(&UserSettings, &Path) -> Box<dyn OpHeadsStore>>;
+type OpHeadsStoreFactory =
+    Box<dyn Fn(&UserSettings, &Path) -> Result<Box<dyn OpHeadsStore>, BackendLoadError>>;
 type IndexStoreFactory =
     Box<dyn Fn(&UserSettings, &Path) -> Result<Box<dyn IndexStore>, BackendLoadError>>;
 type SubmoduleStoreFactory = Box<dyn Fn(&UserSettings, &Path) -> Box<dyn SubmoduleStore>>;
@@ -363,7 +378,14 @@ impl Default for StoreFactories {
         // Backends
         factories.add_backend(
             LocalBackend::name(),
-            Box::new(|_settings, store_path| Ok(Box::new(LocalBackend::load(store_path)))),
+            Box::new(|settings, store_path| {
+                let fsync_policy = settings
+                    .fsync_policy()
+                    .map_err(|err| BackendLoadError(err.into()))?;
+                Ok(Box::new(
+                    LocalBackend::load(store_path)?.with_fsync_policy(fsync_policy),
+                ))
+            }),
         );
         factories.add_backend(
             GitBackend::name(),
@@ -379,7 +401,14 @@ impl Default for StoreFactories {
         // OpHeadsStores
         factories.add_op_heads_store(
             SimpleOpHeadsStore::name(),
-            Box::new(|_settings, store_path| Box::new(SimpleOpHeadsStore::load(store_path))),
+            Box::new(|settings, store_path| {
+                let lock_timeout = settings
+                    .lock_timeout()
+                    .map_err(|err| BackendLoadError(err.into()))?;
+                Ok(Box::new(
+                    SimpleOpHeadsStore::load(store_path).with_lock_timeout(lock_timeout),
+                ))
+            }),
         );
 
         // Index
@@ -502,7 +531,7 @@ impl StoreFactories {
                 store: "operation heads",
                 store_type: op_heads_store_type.to_string(),
             })?;
-        Ok(op_heads_store_factory(settings, store_path))
+        Ok(op_heads_store_factory(settings, store_path)?)
     }
 
     pub fn add_index_store(&mut self, name: &str, factory: IndexStoreFactory) {
@@ -585,6 +614,8 @@ pub enum RepoLoaderError {
     OpHeadResolution(#[from] OpHeadResolutionError),
     #[error(transparent)]
     OpStore(#[from] OpStoreError),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
 }
 
 #[derive(Clone)]
@@ -608,6 +639,9 @@ impl RepoLoader {
             store_factories.load_backend(user_settings, &repo_path.join("store"))?,
             Signer::from_settings(user_settings)?,
             user_settings.use_tree_conflict_format(),
+            &user_settings
+                .object_cache_settings()
+                .map_err(|err| BackendLoadError(err.into()))?,
         );
         let repo_settings = user_settings.with_repo(repo_path).unwrap();
         let op_store =
@@ -705,7 +739,7 @@ impl RepoLoader {
             tx.mut_repo().rebase_descendants(user_settings)?;
         }
         let merged_repo = tx
-            .write("resolve concurrent operations")
+            .write("resolve concurrent operations")?
             .leave_unpublished();
         Ok(merged_repo.operation().clone())
     }
@@ -924,6 +958,24 @@ impl MutableRepo {
         result
     }
 
+    /// Like `rebase_descendants_with_options()`, but also returns the commits
+    /// that were left in place because of `options.stop_on_conflict`. Each of
+    /// them is mapped to the parents it would have been rebased onto.
+    pub fn rebase_descendants_with_options_return_stopped(
+        &mut self,
+        settings: &UserSettings,
+        options: RebaseOptions,
+    ) -> Result<(usize, HashMap<CommitId, Vec<CommitId>>), TreeMergeError> {
+        let result = self
+            .rebase_descendants_return_rebaser(settings, options)?
+            .map_or((0, HashMap::new()), |rebaser| {
+                let stopped = rebaser.stopped().clone();
+                (rebaser.into_map().len(), stopped)
+            });
+        self.clear_descendant_rebaser_plans();
+        Ok(result)
+    }
+
     pub fn rebase_descendants(&mut self, settings: &UserSettings) -> Result<usize, TreeMergeError> {
         self.rebase_descendants_with_options(settings, Default::default())
     }
@@ -1051,26 +1103,7 @@ impl MutableRepo {
                 }
             }
             _ => {
-                let missing_commits = dag_walk::topo_order_reverse_ord_ok(
-                    heads
-                        .iter()
-                        .cloned()
-                        .map(CommitByCommitterTimestamp)
-                        .map(Ok),
-                    |CommitByCommitterTimestamp(commit)| commit.id().clone(),
-                    |CommitByCommitterTimestamp(commit)| {
-                        commit
-                            .parent_ids()
-                            .iter()
-                            .filter(|id| !self.index().has_id(id))
-                            .map(|id| self.store().get_commit(id))
-                            .map_ok(CommitByCommitterTimestamp)
-                            .collect_vec()
-                    },
-                )?;
-                for CommitByCommitterTimestamp(missing_commit) in missing_commits.iter().rev() {
-                    self.index.add_commit(missing_commit);
-                }
+                self.add_missing_commits_to_index(heads)?;
                 for head in heads {
                     self.view.get_mut().add_head(head.id());
                 }
@@ -1080,6 +1113,39 @@ impl MutableRepo {
         Ok(())
     }
 
+    /// Adds the given `commits` and ancestors to the index without making them
+    /// visible.
+    ///
+    /// Hidden commits can still be looked up by id, which is useful for
+    /// preserving history that isn't reachable from any ref.
+    pub fn add_hidden_commits(&mut self, commits: &[Commit]) -> BackendResult<()> {
+        self.add_missing_commits_to_index(commits)
+    }
+
+    fn add_missing_commits_to_index(&mut self, heads: &[Commit]) -> BackendResult<()> {
+        let missing_commits = dag_walk::topo_order_reverse_ord_ok(
+            heads
+                .iter()
+                .cloned()
+                .map(CommitByCommitterTimestamp)
+                .map(Ok),
+            |CommitByCommitterTimestamp(commit)| commit.id().clone(),
+            |CommitByCommitterTimestamp(commit)| {
+                commit
+                    .parent_ids()
+                    .iter()
+                    .filter(|id| !self.index().has_id(id))
+                    .map(|id| self.store().get_commit(id))
+                    .map_ok(CommitByCommitterTimestamp)
+                    .collect_vec()
+            },
+        )?;
+        for CommitByCommitterTimestamp(missing_commit) in missing_commits.iter().rev() {
+            self.index.add_commit(missing_commit);
+        }
+        Ok(())
+    }
+
     pub fn remove_head(&mut self, head: &CommitId) {
         self.view_mut().remove_head(head);
         self.view.mark_dirty();
@@ -1116,6 +1182,17 @@ impl MutableRepo {
         view.set_local_branch_target(name, new_target);
     }
 
+    pub fn get_branch_pin(&self, name: &str) -> Option<ChangeId> {
+        self.view.with_ref(|v| v.get_branch_pin(name).cloned())
+    }
+
+    /// Pins the local branch to follow the given change, or unpins it if
+    /// `change_id` is `None`. The branch should already point to a commit of
+    /// the change.
+    pub fn set_branch_pin(&mut self, name: &str, change_id: Option<ChangeId>) {
+        self.view_mut().set_branch_pin(name, change_id);
+    }
+
     pub fn get_remote_branch(&self, name: &str, remote_name: &str) -> RemoteRef {
         self.view
             .with_ref(|v| v.get_remote_branch(name, remote_name).clone())
@@ -1268,6 +1345,22 @@ impl MutableRepo {
         for (name, (base_target, other_target)) in changed_local_branches {
             self.merge_local_branch(name, base_target, other_target);
         }
+        let pinned_branch_names: BTreeSet<&str> = base
+            .pinned_branches()
+            .chain(other.pinned_branches())
+            .map(|(name, _)| name)
+            .collect();
+        for name in pinned_branch_names {
+            // If both sides changed the pin, we keep the self side.
+            let base_pin = base.get_branch_pin(name);
+            let other_pin = other.get_branch_pin(name);
+            if other_pin != base_pin
+                && self.view().get_branch_pin(name) == base_pin
+                && self.view().get_local_branch(name).is_present()
+            {
+                self.view_mut().set_branch_pin(name, other_pin.cloned());
+            }
+        }
 
         let changed_tags = diff_named_ref_targets(base.tags(), other.tags());
         for (name, (base_target, other_target)) in changed_tags {
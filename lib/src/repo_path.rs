@@ -441,6 +441,34 @@ pub struct FsPathParseError {
     source: RelativePathParseError,
 }
 
+/// Converts `RepoPath`s to file paths presented to the user.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RepoPathUiConverter {
+    /// Formats paths relative to `cwd`, where `base` is the workspace root.
+    Fs { cwd: PathBuf, base: PathBuf },
+}
+
+impl RepoPathUiConverter {
+    /// Formats the given `file` path for display.
+    pub fn format_file_path(&self, file: &RepoPath) -> String {
+        match self {
+            RepoPathUiConverter::Fs { cwd, base } => {
+                file_util::relative_path(cwd, &file.to_fs_path(base))
+                    .to_str()
+                    .unwrap()
+                    .to_owned()
+            }
+        }
+    }
+
+    /// Parses a path from the UI input.
+    pub fn parse_file_path(&self, input: &str) -> Result<RepoPathBuf, FsPathParseError> {
+        match self {
+            RepoPathUiConverter::Fs { cwd, base } => RepoPathBuf::parse_fs_path(cwd, base, input),
+        }
+    }
+}
+
 fn is_valid_repo_path_component_str(value: &str) -> bool {
     !value.is_empty() && !value.contains('/')
 }
@@ -661,6 +689,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ui_converter_format_file_path() {
+        let temp_dir = testutils::new_temp_dir();
+        let base = temp_dir.path().join("repo");
+        let converter = RepoPathUiConverter::Fs {
+            cwd: base.join("dir"),
+            base,
+        };
+        assert_eq!(
+            converter.format_file_path(repo_path("dir/file")),
+            Path::new("file").to_str().unwrap()
+        );
+        assert_eq!(
+            converter.format_file_path(repo_path("other/file")),
+            Path::new("..").join("other").join("file").to_str().unwrap()
+        );
+        assert_eq!(
+            converter.format_file_path(repo_path("dir/sub/file")),
+            Path::new("sub").join("file").to_str().unwrap()
+        );
+    }
+
     #[test]
     fn parse_fs_path_wc_in_cwd() {
         let temp_dir = testutils::new_temp_dir();
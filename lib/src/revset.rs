@@ -35,6 +35,7 @@ use crate::backend::{BackendError, BackendResult, ChangeId, CommitId};
 use crate::commit::Commit;
 use crate::git;
 use crate::hex_util::to_forward_hex;
+use crate::mailmap::Mailmap;
 use crate::object_id::{HexPrefix, PrefixResolution};
 use crate::op_store::WorkspaceId;
 use crate::repo::Repo;
@@ -177,6 +178,15 @@ pub enum RevsetParseErrorKind {
     },
     #[error("Invalid arguments to revset function \"{name}\": {message}")]
     InvalidFunctionArguments { name: String, message: String },
+    #[error(
+        "Invalid arguments to revset function \"{name}\": {}",
+        describe_argument_count(.min, .max)
+    )]
+    InvalidFunctionArgumentCount {
+        name: String,
+        min: usize,
+        max: Option<usize>,
+    },
     #[error("Invalid file pattern")]
     FsPathParseError(#[source] FsPathParseError),
     #[error("Cannot resolve file pattern without workspace")]
@@ -191,6 +201,15 @@ pub enum RevsetParseErrorKind {
     RecursiveAlias(String),
 }
 
+fn describe_argument_count(min: &usize, max: &Option<usize>) -> String {
+    match max {
+        Some(max) if max == min => format!("Expected {min} arguments"),
+        Some(max) => format!("Expected {min} to {max} arguments"),
+        None if *min == 1 => "Expected at least 1 argument".to_owned(),
+        None => format!("Expected at least {min} arguments"),
+    }
+}
+
 impl RevsetParseError {
     fn new(kind: RevsetParseErrorKind) -> Self {
         RevsetParseError {
@@ -807,6 +826,7 @@ struct ParseState<'a> {
     aliases_expanding: &'a [RevsetAliasId<'a>],
     locals: &'a HashMap<&'a str, Rc<RevsetExpression>>,
     user_email: &'a str,
+    mailmap: &'a Mailmap,
     workspace_ctx: &'a Option<RevsetWorkspaceContext<'a>>,
     /// Whether or not `kind:"pattern"` syntax is allowed.
     allow_string_pattern: bool,
@@ -834,6 +854,7 @@ impl ParseState<'_> {
             aliases_expanding: &aliases_expanding,
             locals,
             user_email: self.user_email,
+            mailmap: self.mailmap,
             workspace_ctx: self.workspace_ctx,
             allow_string_pattern: self.allow_string_pattern,
         };
@@ -1122,6 +1143,13 @@ fn parse_function_expression(
     }
 }
 
+/// Returns the names of the builtin revset functions in sorted order.
+pub fn builtin_function_names() -> Vec<&'static str> {
+    let mut names = BUILTIN_FUNCTION_MAP.keys().copied().collect_vec();
+    names.sort_unstable();
+    names
+}
+
 fn collect_function_names(aliases_map: &RevsetAliasesMap) -> Vec<String> {
     let mut names = BUILTIN_FUNCTION_MAP
         .keys()
@@ -1279,9 +1307,37 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
     });
     map.insert("mine", |name, arguments_pair, state| {
         expect_no_arguments(name, arguments_pair)?;
-        Ok(RevsetExpression::filter(RevsetFilterPredicate::Author(
-            StringPattern::Exact(state.user_email.to_owned()),
-        )))
+        let expressions = state
+            .mailmap
+            .aliases_of_email(state.user_email)
+            .into_iter()
+            .map(|email| {
+                RevsetExpression::filter(RevsetFilterPredicate::Author(StringPattern::ExactI(
+                    email,
+                )))
+            })
+            .collect_vec();
+        Ok(RevsetExpression::union_all(&expressions))
+    });
+    map.insert("by", |name, arguments_pair, state| {
+        let arg = expect_one_argument(name, arguments_pair)?;
+        let pattern = parse_function_argument_to_string_pattern(name, arg, state)?;
+        // Commits recorded under an alias of a matching canonical identity
+        let alias_patterns = state
+            .mailmap
+            .aliases_matching(|s| pattern.matches(s))
+            .into_iter()
+            .map(StringPattern::exact_i);
+        let expressions = iter::once(pattern.clone())
+            .chain(alias_patterns)
+            .flat_map(|pattern| {
+                [
+                    RevsetExpression::filter(RevsetFilterPredicate::Author(pattern.clone())),
+                    RevsetExpression::filter(RevsetFilterPredicate::Committer(pattern)),
+                ]
+            })
+            .collect_vec();
+        Ok(RevsetExpression::union_all(&expressions))
     });
     map.insert("committer", |name, arguments_pair, state| {
         let arg = expect_one_argument(name, arguments_pair)?;
@@ -1314,9 +1370,10 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
                 .try_collect()?;
             if paths.is_empty() {
                 Err(RevsetParseError::with_span(
-                    RevsetParseErrorKind::InvalidFunctionArguments {
+                    RevsetParseErrorKind::InvalidFunctionArgumentCount {
                         name: name.to_owned(),
-                        message: "Expected at least 1 argument".to_string(),
+                        min: 1,
+                        max: None,
                     },
                     arguments_span,
                 ))
@@ -1401,12 +1458,14 @@ fn expect_named_arguments_vec<'i>(
         )
     };
     let make_count_error = || {
-        let message = if min_arg_count == max_arg_count {
-            format!("Expected {min_arg_count} arguments")
-        } else {
-            format!("Expected {min_arg_count} to {max_arg_count} arguments")
-        };
-        make_error(message, arguments_span)
+        RevsetParseError::with_span(
+            RevsetParseErrorKind::InvalidFunctionArgumentCount {
+                name: function_name.to_owned(),
+                min: min_arg_count,
+                max: Some(max_arg_count),
+            },
+            arguments_span,
+        )
     };
 
     let mut pos_iter = Some(0..max_arg_count);
@@ -1547,6 +1606,7 @@ pub fn parse(
         aliases_expanding: &[],
         locals: &HashMap::new(),
         user_email: &context.user_email,
+        mailmap: context.mailmap,
         workspace_ctx: &context.workspace,
         allow_string_pattern: false,
     };
@@ -1963,15 +2023,202 @@ fn fold_generation(expression: &Rc<RevsetExpression>) -> TransformedExpression {
     })
 }
 
+/// Rough rank of the cost to evaluate the `expression`. Smaller sets are
+/// usually cheaper to evaluate and to intersect with.
+///
+/// The generation bounds of ancestor sets are the only size hints available.
+/// The index has no per-commit path filters (such as Bloom filters), so file
+/// filters always rank last.
+fn estimate_cost(expression: &RevsetExpression) -> u8 {
+    match expression {
+        RevsetExpression::None => 0,
+        RevsetExpression::Commits(_)
+        | RevsetExpression::CommitRef(_)
+        | RevsetExpression::StringPattern { .. } => 1,
+        // Walking a bounded number of generations visits a few commits per head.
+        RevsetExpression::Ancestors { heads, generation } if generation.end != u64::MAX => {
+            estimate_cost(heads).max(2)
+        }
+        RevsetExpression::Heads(candidates)
+        | RevsetExpression::Roots(candidates)
+        | RevsetExpression::Latest { candidates, .. }
+        | RevsetExpression::Present(candidates) => estimate_cost(candidates),
+        RevsetExpression::Range { .. } | RevsetExpression::DagRange { .. } => 4,
+        RevsetExpression::Ancestors { .. } | RevsetExpression::Descendants { .. } => 5,
+        RevsetExpression::All => 6,
+        RevsetExpression::NotIn(_) => 7,
+        RevsetExpression::Filter(_) | RevsetExpression::AsFilter(_) => 8,
+        RevsetExpression::Union(expression1, expression2) => {
+            estimate_cost(expression1).max(estimate_cost(expression2))
+        }
+        RevsetExpression::Intersection(expression1, expression2) => {
+            estimate_cost(expression1).min(estimate_cost(expression2))
+        }
+        RevsetExpression::Difference(expression1, _) => estimate_cost(expression1),
+    }
+}
+
+/// Reorders operands of nested intersections so the cheapest set is evaluated
+/// first. The sort is stable, so operands of equal cost keep their order.
+/// Negated sets and filters are the most costly, so they end up at the right,
+/// negated sets before filters.
+fn reorder_intersection(expression: &Rc<RevsetExpression>) -> TransformedExpression {
+    fn collect_operands<'a>(
+        expression: &'a Rc<RevsetExpression>,
+        operands: &mut Vec<&'a Rc<RevsetExpression>>,
+    ) {
+        if let RevsetExpression::Intersection(expression1, expression2) = expression.as_ref() {
+            collect_operands(expression1, operands);
+            collect_operands(expression2, operands);
+        } else {
+            operands.push(expression);
+        }
+    }
+
+    transform_expression_bottom_up(expression, |expression| {
+        if !matches!(expression.as_ref(), RevsetExpression::Intersection(..)) {
+            return None;
+        }
+        let mut operands = vec![];
+        collect_operands(expression, &mut operands);
+        let costs = operands
+            .iter()
+            .map(|operand| estimate_cost(operand))
+            .collect_vec();
+        if costs.windows(2).all(|pair| pair[0] <= pair[1]) {
+            return None;
+        }
+        let mut sorted_operands = operands
+            .into_iter()
+            .zip(costs)
+            .sorted_by_key(|(_, cost)| *cost)
+            .map(|(operand, _)| operand);
+        let first = sorted_operands.next().unwrap().clone();
+        Some(sorted_operands.fold(first, |acc, operand| acc.intersection(operand)))
+    })
+}
+
+type OptimizationPass = fn(&Rc<RevsetExpression>) -> TransformedExpression;
+
+/// Transformations applied by `optimize()`, in order.
+const OPTIMIZATION_PASSES: [(&str, OptimizationPass); 7] = [
+    ("unfold_difference", unfold_difference),
+    ("fold_redundant_expression", fold_redundant_expression),
+    ("fold_generation", fold_generation),
+    ("reorder_intersection", reorder_intersection),
+    ("internalize_filter", internalize_filter),
+    ("fold_difference", fold_difference),
+    ("fold_not_in_ancestors", fold_not_in_ancestors),
+];
+
 /// Rewrites the given `expression` tree to reduce evaluation cost. Returns new
 /// tree.
 pub fn optimize(expression: Rc<RevsetExpression>) -> Rc<RevsetExpression> {
-    let expression = unfold_difference(&expression).unwrap_or(expression);
-    let expression = fold_redundant_expression(&expression).unwrap_or(expression);
-    let expression = fold_generation(&expression).unwrap_or(expression);
-    let expression = internalize_filter(&expression).unwrap_or(expression);
-    let expression = fold_difference(&expression).unwrap_or(expression);
-    fold_not_in_ancestors(&expression).unwrap_or(expression)
+    optimize_explained(expression).0
+}
+
+/// Like `optimize()`, but also returns the name of each transformation that
+/// rewrote the tree along with the tree it produced.
+pub fn optimize_explained(
+    expression: Rc<RevsetExpression>,
+) -> (
+    Rc<RevsetExpression>,
+    Vec<(&'static str, Rc<RevsetExpression>)>,
+) {
+    let mut steps = vec![];
+    let mut expression = expression;
+    for (name, pass) in OPTIMIZATION_PASSES {
+        if let Some(new_expression) = pass(&expression) {
+            steps.push((name, new_expression.clone()));
+            expression = new_expression;
+        }
+    }
+    (expression, steps)
+}
+
+/// Finds set expressions that appear more than once in the `expression` tree,
+/// typically because an alias was expanded several times. Evaluation engines
+/// can evaluate each of them once.
+///
+/// Returns the occurrences of each repeated set, outermost sets first. Only
+/// sets which are costly to compute are considered.
+pub fn common_subexpressions(expression: &ResolvedExpression) -> Vec<Vec<&ResolvedExpression>> {
+    fn is_costly(expression: &ResolvedExpression) -> bool {
+        matches!(
+            expression,
+            ResolvedExpression::Ancestors { .. }
+                | ResolvedExpression::Range { .. }
+                | ResolvedExpression::DagRange { .. }
+                | ResolvedExpression::Heads(_)
+                | ResolvedExpression::Roots(_)
+                | ResolvedExpression::Latest { .. }
+        )
+    }
+
+    fn collect_rec<'a>(
+        expression: &'a ResolvedExpression,
+        subexpressions: &mut Vec<&'a ResolvedExpression>,
+    ) {
+        if is_costly(expression) {
+            subexpressions.push(expression);
+        }
+        match expression {
+            ResolvedExpression::Commits(_) => {}
+            ResolvedExpression::Ancestors { heads, .. } => collect_rec(heads, subexpressions),
+            ResolvedExpression::Range { roots, heads, .. }
+            | ResolvedExpression::DagRange { roots, heads, .. } => {
+                collect_rec(roots, subexpressions);
+                collect_rec(heads, subexpressions);
+            }
+            ResolvedExpression::Heads(candidates)
+            | ResolvedExpression::Roots(candidates)
+            | ResolvedExpression::Latest { candidates, .. } => {
+                collect_rec(candidates, subexpressions);
+            }
+            ResolvedExpression::FilterWithin {
+                candidates,
+                predicate,
+            } => {
+                collect_rec(candidates, subexpressions);
+                collect_predicate_rec(predicate, subexpressions);
+            }
+            ResolvedExpression::Union(expression1, expression2)
+            | ResolvedExpression::Intersection(expression1, expression2)
+            | ResolvedExpression::Difference(expression1, expression2) => {
+                collect_rec(expression1, subexpressions);
+                collect_rec(expression2, subexpressions);
+            }
+        }
+    }
+
+    fn collect_predicate_rec<'a>(
+        expression: &'a ResolvedPredicateExpression,
+        subexpressions: &mut Vec<&'a ResolvedExpression>,
+    ) {
+        match expression {
+            ResolvedPredicateExpression::Filter(_) => {}
+            ResolvedPredicateExpression::Set(expression) => collect_rec(expression, subexpressions),
+            ResolvedPredicateExpression::NotIn(complement) => {
+                collect_predicate_rec(complement, subexpressions);
+            }
+            ResolvedPredicateExpression::Union(expression1, expression2) => {
+                collect_predicate_rec(expression1, subexpressions);
+                collect_predicate_rec(expression2, subexpressions);
+            }
+        }
+    }
+
+    let mut subexpressions = vec![];
+    collect_rec(expression, &mut subexpressions);
+    let mut groups: Vec<Vec<&ResolvedExpression>> = vec![];
+    for subexpression in subexpressions {
+        match groups.iter_mut().find(|group| *group[0] == *subexpression) {
+            Some(group) => group.push(subexpression),
+            None => groups.push(vec![subexpression]),
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
 }
 
 // TODO: find better place to host this function (or add compile-time revset
@@ -2525,6 +2772,7 @@ impl Iterator for ReverseRevsetIterator {
 pub struct RevsetParseContext<'a> {
     pub aliases_map: &'a RevsetAliasesMap,
     pub user_email: String,
+    pub mailmap: &'a Mailmap,
     pub workspace: Option<RevsetWorkspaceContext<'a>>,
 }
 
@@ -2564,6 +2812,7 @@ mod tests {
         let context = RevsetParseContext {
             aliases_map: &aliases_map,
             user_email: "test.user@example.com".to_string(),
+            mailmap: &Mailmap::empty(),
             workspace: None,
         };
         // Map error to comparable object
@@ -2588,6 +2837,7 @@ mod tests {
         let context = RevsetParseContext {
             aliases_map: &aliases_map,
             user_email: "test.user@example.com".to_string(),
+            mailmap: &Mailmap::empty(),
             workspace: Some(workspace_ctx),
         };
         // Map error to comparable object
@@ -3134,9 +3384,10 @@ mod tests {
         assert_eq!(parse("parents(foo"), Err(RevsetParseErrorKind::SyntaxError));
         assert_eq!(
             parse("parents(foo,foo)"),
-            Err(RevsetParseErrorKind::InvalidFunctionArguments {
+            Err(RevsetParseErrorKind::InvalidFunctionArgumentCount {
                 name: "parents".to_string(),
-                message: "Expected 1 arguments".to_string()
+                min: 1,
+                max: Some(1),
             })
         );
         assert_eq!(
@@ -3179,7 +3430,7 @@ mod tests {
         assert_eq!(
             parse("mine()"),
             Ok(RevsetExpression::filter(RevsetFilterPredicate::Author(
-                StringPattern::Exact("test.user@example.com".to_string())
+                StringPattern::ExactI("test.user@example.com".to_string())
             )))
         );
         assert_eq!(
@@ -3397,23 +3648,26 @@ mod tests {
         // Invalid number of arguments.
         assert_eq!(
             parse_with_aliases("F(a)", [("F()", "x")]),
-            Err(RevsetParseErrorKind::InvalidFunctionArguments {
+            Err(RevsetParseErrorKind::InvalidFunctionArgumentCount {
                 name: "F".to_owned(),
-                message: "Expected 0 arguments".to_owned()
+                min: 0,
+                max: Some(0),
             })
         );
         assert_eq!(
             parse_with_aliases("F()", [("F(x)", "x")]),
-            Err(RevsetParseErrorKind::InvalidFunctionArguments {
+            Err(RevsetParseErrorKind::InvalidFunctionArgumentCount {
                 name: "F".to_owned(),
-                message: "Expected 1 arguments".to_owned()
+                min: 1,
+                max: Some(1),
             })
         );
         assert_eq!(
             parse_with_aliases("F(a,b,c)", [("F(x,y)", "x|y")]),
-            Err(RevsetParseErrorKind::InvalidFunctionArguments {
+            Err(RevsetParseErrorKind::InvalidFunctionArgumentCount {
                 name: "F".to_owned(),
-                message: "Expected 2 arguments".to_owned()
+                min: 2,
+                max: Some(2),
             })
         );
 
@@ -4224,22 +4478,22 @@ mod tests {
                             "foo",
                         ),
                     ),
-                    Ancestors {
-                        heads: Filter(
-                            Author(
-                                Substring(
-                                    "baz",
-                                ),
-                            ),
+                    CommitRef(
+                        Symbol(
+                            "qux",
                         ),
-                        generation: 1..2,
-                    },
-                ),
-                CommitRef(
-                    Symbol(
-                        "qux",
                     ),
                 ),
+                Ancestors {
+                    heads: Filter(
+                        Author(
+                            Substring(
+                                "baz",
+                            ),
+                        ),
+                    ),
+                    generation: 1..2,
+                },
             ),
             Filter(
                 Description(
@@ -4345,23 +4599,23 @@ mod tests {
                 Intersection(
                     Intersection(
                         Intersection(
-                            CommitRef(
-                                Symbol(
-                                    "a",
-                                ),
-                            ),
                             Intersection(
                                 CommitRef(
                                     Symbol(
-                                        "b",
+                                        "a",
                                     ),
                                 ),
                                 CommitRef(
                                     Symbol(
-                                        "c",
+                                        "b",
                                     ),
                                 ),
                             ),
+                            CommitRef(
+                                Symbol(
+                                    "c",
+                                ),
+                            ),
                         ),
                         CommitRef(
                             Symbol(
@@ -4613,6 +4867,105 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_optimize_reorder_intersection() {
+        // Cheaper sets are moved to the left, negated sets and filters stay at
+        // the right
+        insta::assert_debug_snapshot!(
+            optimize(parse("::foo & bar- & baz & author(qux)").unwrap()), @r###"
+        Intersection(
+            Intersection(
+                Intersection(
+                    CommitRef(
+                        Symbol(
+                            "baz",
+                        ),
+                    ),
+                    Ancestors {
+                        heads: CommitRef(
+                            Symbol(
+                                "bar",
+                            ),
+                        ),
+                        generation: 1..2,
+                    },
+                ),
+                Ancestors {
+                    heads: CommitRef(
+                        Symbol(
+                            "foo",
+                        ),
+                    ),
+                    generation: 0..18446744073709551615,
+                },
+            ),
+            Filter(
+                Author(
+                    Substring(
+                        "qux",
+                    ),
+                ),
+            ),
+        )
+        "###);
+        // Already sorted
+        insta::assert_debug_snapshot!(optimize(parse("foo & ::bar").unwrap()), @r###"
+        Intersection(
+            CommitRef(
+                Symbol(
+                    "foo",
+                ),
+            ),
+            Ancestors {
+                heads: CommitRef(
+                    Symbol(
+                        "bar",
+                    ),
+                ),
+                generation: 0..18446744073709551615,
+            },
+        )
+        "###);
+
+        let (_, steps) = optimize_explained(parse("::foo & bar").unwrap());
+        assert_eq!(
+            steps.iter().map(|(name, _)| *name).collect_vec(),
+            ["reorder_intersection"]
+        );
+    }
+
+    #[test]
+    fn test_common_subexpressions() {
+        let commits = |hex: &'static str| {
+            Box::new(ResolvedExpression::Commits(vec![CommitId::from_hex(hex)]))
+        };
+        let ancestors = |heads| ResolvedExpression::Ancestors {
+            heads,
+            generation: GENERATION_RANGE_FULL,
+        };
+        let heads = |candidates| ResolvedExpression::Heads(Box::new(candidates));
+        // Repeated ancestors sets, including one within a predicate
+        let expression = ResolvedExpression::Union(
+            Box::new(heads(ancestors(commits("aa")))),
+            Box::new(ResolvedExpression::FilterWithin {
+                candidates: Box::new(ancestors(commits("aa"))),
+                predicate: ResolvedPredicateExpression::NotIn(Box::new(
+                    ResolvedPredicateExpression::Set(Box::new(heads(ancestors(commits("bb"))))),
+                )),
+            }),
+        );
+        assert_eq!(
+            common_subexpressions(&expression),
+            vec![vec![&ancestors(commits("aa")), &ancestors(commits("aa"))]]
+        );
+        // Commits are cheap to evaluate again
+        let expression = ResolvedExpression::Union(commits("aa"), commits("aa"));
+        assert_eq!(
+            common_subexpressions(&expression),
+            Vec::<Vec<&ResolvedExpression>>::new()
+        );
+    }
+
     #[test]
     fn test_optimize_ancestors() {
         // Typical scenario: fold nested parents()
@@ -16,19 +16,20 @@
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::{mem, slice};
 
 use futures::StreamExt;
 use itertools::Itertools;
 use pollster::FutureExt;
 use tracing::instrument;
 
-use crate::backend::{BackendError, BackendResult, CommitId, MergedTreeId};
+use crate::backend::{BackendError, BackendResult, ChangeId, CommitId, MergedTreeId};
 use crate::commit::Commit;
 use crate::dag_walk;
 use crate::index::Index;
 use crate::matchers::{Matcher, Visit};
 use crate::merged_tree::{MergedTree, MergedTreeBuilder};
-use crate::object_id::ObjectId;
+use crate::object_id::{HexPrefix, ObjectId, PrefixResolution};
 use crate::op_store::RefTarget;
 use crate::repo::{MutableRepo, Repo};
 use crate::repo_path::RepoPath;
@@ -115,12 +116,18 @@ pub fn rebase_commit(
     match rebased_commit {
         RebasedCommit::Rewritten(new_commit) => Ok(new_commit),
         RebasedCommit::Abandoned { parent: _ } => panic!("Commit was unexpectedly abandoned"),
+        RebasedCommit::Stopped => panic!("Commit was unexpectedly stopped"),
     }
 }
 
 pub enum RebasedCommit {
     Rewritten(Commit),
-    Abandoned { parent: Commit },
+    Abandoned {
+        parent: Commit,
+    },
+    /// The commit would have become conflicted, so it was left in place
+    /// because `RebaseOptions::stop_on_conflict` is set.
+    Stopped,
 }
 
 pub fn rebase_commit_with_options(
@@ -176,6 +183,12 @@ pub fn rebase_commit_with_options(
             new_base_tree.merge(&old_base_tree, &old_tree)?.id(),
         )
     };
+    if options.stop_on_conflict
+        && !old_commit.has_conflict()?
+        && mut_repo.store().get_root_tree(&new_tree_id)?.has_conflict()
+    {
+        return Ok(RebasedCommit::Stopped);
+    }
     // Ensure we don't abandon commits with multiple parents (merge commits), even
     // if they're empty.
     if let [parent] = new_parents {
@@ -231,21 +244,78 @@ pub fn back_out_commit(
     old_commit: &Commit,
     new_parents: &[Commit],
 ) -> Result<Commit, TreeMergeError> {
-    let old_base_tree = merge_commit_trees(mut_repo, &old_commit.parents())?;
-    let new_base_tree = merge_commit_trees(mut_repo, new_parents)?;
-    let old_tree = old_commit.tree()?;
-    let new_tree = new_base_tree.merge(&old_tree, &old_base_tree)?;
+    back_out_commits(settings, mut_repo, slice::from_ref(old_commit), new_parents)
+}
+
+/// Creates a single commit on top of `new_parents` which reverts the combined
+/// changes of `old_commits`.
+///
+/// The `old_commits` are reverted in the given order, so descendants should
+/// come before their ancestors.
+pub fn back_out_commits(
+    settings: &UserSettings,
+    mut_repo: &mut MutableRepo,
+    old_commits: &[Commit],
+    new_parents: &[Commit],
+) -> Result<Commit, TreeMergeError> {
+    let mut new_tree = merge_commit_trees(mut_repo, new_parents)?;
+    for old_commit in old_commits {
+        let old_base_tree = merge_commit_trees(mut_repo, &old_commit.parents())?;
+        let old_tree = old_commit.tree()?;
+        new_tree = new_tree.merge(&old_tree, &old_base_tree)?;
+    }
     let new_parent_ids = new_parents
         .iter()
         .map(|commit| commit.id().clone())
         .collect();
     // TODO: i18n the description based on repo language
+    let description = match old_commits {
+        [old_commit] => format!("backout of {}", describe_backed_out_commit(old_commit)),
+        _ => {
+            let mut description = format!("backout of {} commits\n", old_commits.len());
+            for old_commit in old_commits {
+                description.push('\n');
+                description.push_str(&describe_backed_out_commit(old_commit));
+            }
+            description
+        }
+    };
     Ok(mut_repo
         .new_commit(settings, new_parent_ids, new_tree.id())
-        .set_description(format!("backout of commit {}", &old_commit.id().hex()))
+        .set_description(description)
         .write()?)
 }
 
+/// Creates a stack of commits on top of `new_parents`, each of which reverts
+/// one of the `old_commits` in the given order. Returns the new commits from
+/// the bottom of the stack.
+///
+/// Descendants should come before their ancestors in `old_commits` so the
+/// stack mirrors the original one.
+pub fn back_out_commits_as_stack(
+    settings: &UserSettings,
+    mut_repo: &mut MutableRepo,
+    old_commits: &[Commit],
+    new_parents: &[Commit],
+) -> Result<Vec<Commit>, TreeMergeError> {
+    let mut parents = new_parents.to_vec();
+    let mut new_commits = Vec::with_capacity(old_commits.len());
+    for old_commit in old_commits {
+        let new_commit = back_out_commit(settings, mut_repo, old_commit, &parents)?;
+        parents = vec![new_commit.clone()];
+        new_commits.push(new_commit);
+    }
+    Ok(new_commits)
+}
+
+fn describe_backed_out_commit(commit: &Commit) -> String {
+    format!(
+        "commit {} (change {})",
+        commit.id().hex(),
+        commit.change_id().reverse_hex()
+    )
+}
+
 #[derive(Clone, Default, PartialEq, Eq, Debug)]
 pub enum EmptyBehaviour {
     /// Always keep empty commits
@@ -273,6 +343,9 @@ pub struct RebaseOptions {
     /// If a merge commit would end up with one parent being an ancestor of the
     /// other, then filter out the ancestor.
     pub simplify_ancestor_merge: bool,
+    /// If a rebased commit would become conflicted, leave it and its
+    /// descendants in place instead of rewriting them.
+    pub stop_on_conflict: bool,
 }
 
 pub(crate) struct DescendantRebaser<'settings, 'repo> {
@@ -294,10 +367,16 @@ pub(crate) struct DescendantRebaser<'settings, 'repo> {
     rebased: HashMap<CommitId, CommitId>,
     // Names of branches where local target includes the commit id in the key.
     branches: HashMap<CommitId, HashSet<String>>,
+    // Pinned branches with the change they're pinned to and the commit they
+    // pointed to before rebasing.
+    pinned_branches: Vec<(String, ChangeId, CommitId)>,
     // Parents of rebased/abandoned commit that should become new heads once their descendants
     // have been rebased.
     heads_to_add: HashSet<CommitId>,
     heads_to_remove: Vec<CommitId>,
+    // Commits that were left in place because they would have become
+    // conflicted, mapped to the parents they would have been rebased onto.
+    stopped: HashMap<CommitId, Vec<CommitId>>,
 
     // Options to apply during a rebase.
     options: RebaseOptions,
@@ -389,6 +468,15 @@ impl<'settings, 'repo> DescendantRebaser<'settings, 'repo> {
                     .insert(branch_name.to_owned());
             }
         }
+        let pinned_branches = mut_repo
+            .view()
+            .pinned_branches()
+            .filter_map(|(branch_name, change_id)| {
+                let target = mut_repo.view().get_local_branch(branch_name);
+                let commit_id = target.as_normal()?;
+                Some((branch_name.to_owned(), change_id.clone(), commit_id.clone()))
+            })
+            .collect();
 
         DescendantRebaser {
             settings,
@@ -400,8 +488,10 @@ impl<'settings, 'repo> DescendantRebaser<'settings, 'repo> {
             new_commits,
             rebased: Default::default(),
             branches,
+            pinned_branches,
             heads_to_add,
             heads_to_remove: Default::default(),
+            stopped: Default::default(),
             options: Default::default(),
         }
     }
@@ -418,6 +508,14 @@ impl<'settings, 'repo> DescendantRebaser<'settings, 'repo> {
         self.rebased
     }
 
+    /// Returns the commits that were left in place because they would have
+    /// become conflicted, mapped to the parents they would have been rebased
+    /// onto. Their descendants are left in place too. Only populated if
+    /// `RebaseOptions::stop_on_conflict` is set.
+    pub fn stopped(&self) -> &HashMap<CommitId, Vec<CommitId>> {
+        &self.stopped
+    }
+
     /// Panics if `parent_mapping` contains cycles
     fn new_parents(&self, old_ids: &[CommitId]) -> Vec<CommitId> {
         fn single_substitution_round(
@@ -598,6 +696,12 @@ impl<'settings, 'repo> DescendantRebaser<'settings, 'repo> {
                 self.abandoned.insert(old_commit.id().clone());
                 parent
             }
+            RebasedCommit::Stopped => {
+                // The descendants stay on top of the original commit, which
+                // isn't rewritten.
+                self.stopped.insert(old_commit_id, new_parent_ids);
+                return Ok(());
+            }
         };
         let previous_rebased_value = self
             .rebased
@@ -628,6 +732,85 @@ impl<'settings, 'repo> DescendantRebaser<'settings, 'repo> {
         self.heads_to_remove.clear();
         self.heads_to_add.clear();
         self.mut_repo.set_view(view);
+        self.update_pinned_branches()?;
         Ok(())
     }
+
+    /// Moves pinned branches whose commit was rewritten to the commit their
+    /// change was rewritten to. If the change doesn't have a single visible
+    /// commit rewritten from the branch's old commit (e.g. because the branch
+    /// was moved to another change, or the change was abandoned or became
+    /// divergent), the pin is dropped and the branch is left where the rewrite
+    /// moved it.
+    fn update_pinned_branches(&mut self) -> BackendResult<()> {
+        let pinned_branches = mem::take(&mut self.pinned_branches)
+            .into_iter()
+            .filter(|(_, _, old_commit_id)| self.parent_mapping.contains_key(old_commit_id))
+            .collect_vec();
+        if pinned_branches.is_empty() {
+            return Ok(());
+        }
+        // Build the index once, and only if a pinned branch was rewritten.
+        let change_id_index = self
+            .mut_repo
+            .mutable_index()
+            .change_id_index(&mut self.mut_repo.view().heads().iter());
+        let pinned_branches = pinned_branches
+            .into_iter()
+            .map(|(branch_name, change_id, old_commit_id)| {
+                let prefix = HexPrefix::from_bytes(change_id.as_bytes());
+                let new_commit_ids = match change_id_index.resolve_prefix(&prefix) {
+                    PrefixResolution::SingleMatch(commit_ids) => commit_ids,
+                    PrefixResolution::NoMatch | PrefixResolution::AmbiguousMatch => vec![],
+                };
+                (branch_name, old_commit_id, new_commit_ids)
+            })
+            .collect_vec();
+        drop(change_id_index);
+
+        let store = self.mut_repo.store().clone();
+        for (branch_name, old_commit_id, new_commit_ids) in pinned_branches {
+            let new_commit_id = match &*new_commit_ids {
+                [new_commit_id] => Some(new_commit_id),
+                _ => None,
+            };
+            let new_commit = new_commit_id.map(|id| store.get_commit(id)).transpose()?;
+            match new_commit {
+                Some(new_commit) if is_rewritten_from(&new_commit, &old_commit_id)? => {
+                    self.mut_repo.set_local_branch_target(
+                        &branch_name,
+                        RefTarget::normal(new_commit.id().clone()),
+                    );
+                }
+                _ => self.mut_repo.set_branch_pin(&branch_name, None),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `old_commit_id` is a predecessor of `commit`, directly or through
+/// other predecessors.
+fn is_rewritten_from(commit: &Commit, old_commit_id: &CommitId) -> BackendResult<bool> {
+    let store = commit.store();
+    let predecessors = dag_walk::dfs_ok(
+        commit
+            .predecessor_ids()
+            .iter()
+            .map(|id| store.get_commit(id)),
+        |commit: &Commit| commit.id().clone(),
+        |commit: &Commit| {
+            commit
+                .predecessor_ids()
+                .iter()
+                .map(|id| store.get_commit(id))
+                .collect_vec()
+        },
+    );
+    for predecessor in predecessors {
+        if predecessor?.id() == old_commit_id {
+            return Ok(true);
+        }
+    }
+    Ok(false)
 }
@@ -14,14 +14,17 @@
 
 #![allow(missing_docs)]
 
+use std::num::NonZeroUsize;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use chrono::DateTime;
 use rand::prelude::*;
 use rand_chacha::ChaCha20Rng;
 
 use crate::backend::{ChangeId, Commit, Signature, Timestamp};
+use crate::file_util::FsyncPolicy;
 use crate::fmt_util::binary_prefix;
 use crate::fsmonitor::FsmonitorKind;
 use crate::signing::SignBehavior;
@@ -42,16 +45,25 @@ pub struct RepoSettings {
 pub struct GitSettings {
     pub auto_local_branch: bool,
     pub abandon_unreachable_commits: bool,
+    pub fetch_prune: bool,
+    /// Remotes whose fetched commits supersede local commits with the same
+    /// change id or `Change-Id` footer.
+    pub reassociate_rewrites: Vec<String>,
 }
 
 impl GitSettings {
-    pub fn from_config(config: &config::Config) -> Self {
-        GitSettings {
+    pub fn from_config(config: &config::Config) -> Result<Self, config::ConfigError> {
+        Ok(GitSettings {
             auto_local_branch: config.get_bool("git.auto-local-branch").unwrap_or(false),
             abandon_unreachable_commits: config
                 .get_bool("git.abandon-unreachable-commits")
                 .unwrap_or(true),
-        }
+            fetch_prune: config.get_bool("git.fetch-prune").unwrap_or(true),
+            reassociate_rewrites: config
+                .get::<Vec<String>>("git.reassociate-rewrites")
+                .optional()?
+                .unwrap_or_default(),
+        })
     }
 }
 
@@ -60,10 +72,31 @@ impl Default for GitSettings {
         GitSettings {
             auto_local_branch: false,
             abandon_unreachable_commits: true,
+            fetch_prune: true,
+            reassociate_rewrites: vec![],
         }
     }
 }
 
+/// Capacities of the in-memory object caches of the `Store`. The caches are
+/// unbounded by default.
+#[derive(Debug, Clone, Default)]
+pub struct ObjectCacheSettings {
+    /// Maximum number of commits kept in memory, or `None` if unbounded.
+    pub commit_capacity: Option<NonZeroUsize>,
+    /// Maximum number of trees kept in memory, or `None` if unbounded.
+    pub tree_capacity: Option<NonZeroUsize>,
+}
+
+impl ObjectCacheSettings {
+    pub fn from_config(config: &config::Config) -> Result<Self, config::ConfigError> {
+        Ok(ObjectCacheSettings {
+            commit_capacity: config.get("store.commit-cache-size").optional()?,
+            tree_capacity: config.get("store.tree-cache-size").optional()?,
+        })
+    }
+}
+
 /// Commit signing settings, describes how to and if to sign commits.
 #[derive(Debug, Clone, Default)]
 pub struct SignSettings {
@@ -172,6 +205,38 @@ impl UserSettings {
         }
     }
 
+    /// When objects written by the commit backend are synced to disk
+    /// (`store.fsync`).
+    pub fn fsync_policy(&self) -> Result<FsyncPolicy, config::ConfigError> {
+        match self.config.get_string("store.fsync") {
+            Ok(policy) => Ok(policy.parse()?),
+            Err(config::ConfigError::NotFound(_)) => Ok(FsyncPolicy::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Whether the executable bit of files on disk should be ignored when
+    /// snapshotting the working copy (`core.filemode = false`).
+    pub fn ignore_executable_bit(&self) -> Result<bool, config::ConfigError> {
+        let filemode = self.config.get_bool("core.filemode").optional()?;
+        Ok(!filemode.unwrap_or(true))
+    }
+
+    /// Whether symlinks should be checked out as symlinks if the platform
+    /// supports them (`core.symlinks`). If `false`, they're checked out as
+    /// regular files containing the link target.
+    pub fn symlinks_enabled(&self) -> Result<bool, config::ConfigError> {
+        let symlinks = self.config.get_bool("core.symlinks").optional()?;
+        Ok(symlinks.unwrap_or(true))
+    }
+
+    /// How long to wait for locks held by other processes before giving up
+    /// (`core.lock-timeout`, in seconds). `None` means the platform default.
+    pub fn lock_timeout(&self) -> Result<Option<Duration>, config::ConfigError> {
+        let seconds = self.config.get::<u64>("core.lock-timeout").optional()?;
+        Ok(seconds.map(Duration::from_secs))
+    }
+
     // Must not be changed to avoid git pushing older commits with no set email
     // address
     pub const USER_EMAIL_PLACEHOLDER: &'static str = "(no email configured)";
@@ -192,6 +257,14 @@ impl UserSettings {
             .unwrap_or_else(|_| whoami::username())
     }
 
+    pub fn merge_concurrent_operations(&self) -> Result<bool, config::ConfigError> {
+        let enabled = self
+            .config
+            .get_bool("operation.merge-concurrent")
+            .optional()?;
+        Ok(enabled.unwrap_or(true))
+    }
+
     pub fn push_branch_prefix(&self) -> String {
         self.config
             .get_string("git.push-branch-prefix")
@@ -232,10 +305,14 @@ impl UserSettings {
         &self.config
     }
 
-    pub fn git_settings(&self) -> GitSettings {
+    pub fn git_settings(&self) -> Result<GitSettings, config::ConfigError> {
         GitSettings::from_config(&self.config)
     }
 
+    pub fn object_cache_settings(&self) -> Result<ObjectCacheSettings, config::ConfigError> {
+        ObjectCacheSettings::from_config(&self.config)
+    }
+
     pub fn graph_style(&self) -> String {
         self.config
             .get_string("ui.graph.style")
@@ -258,6 +335,19 @@ impl UserSettings {
         )
     }
 
+    pub fn auto_snapshot(&self) -> Result<bool, config::ConfigError> {
+        let enabled = self.config.get_bool("snapshot.auto").optional()?;
+        Ok(enabled.unwrap_or(true))
+    }
+
+    pub fn use_fingerprint_cache(&self) -> Result<bool, config::ConfigError> {
+        let enabled = self
+            .config
+            .get_bool("snapshot.fingerprint-cache")
+            .optional()?;
+        Ok(enabled.unwrap_or(false))
+    }
+
     pub fn max_new_file_size(&self) -> Result<u64, config::ConfigError> {
         let cfg = self
             .config
@@ -410,6 +500,26 @@ fn parse_human_byte_size(v: &str) -> Result<u64, &str> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn object_cache_settings_from_config() {
+        let settings_from_toml = |text: &str| {
+            let config = config::Config::builder()
+                .add_source(config::File::from_str(text, config::FileFormat::Toml))
+                .build()
+                .unwrap();
+            UserSettings::from_config(config).object_cache_settings()
+        };
+        let settings = settings_from_toml("").unwrap();
+        assert_eq!(settings.commit_capacity, None);
+        assert_eq!(settings.tree_capacity, None);
+        let settings = settings_from_toml("store.commit-cache-size = 10").unwrap();
+        assert_eq!(settings.commit_capacity, NonZeroUsize::new(10));
+        assert_eq!(settings.tree_capacity, None);
+        assert!(settings_from_toml("store.commit-cache-size = 0").is_err());
+        assert!(settings_from_toml("store.tree-cache-size = -1").is_err());
+        assert!(settings_from_toml(r#"store.tree-cache-size = "many""#).is_err());
+    }
+
     #[test]
     fn byte_size_parse() {
         assert_eq!(parse_human_byte_size("0"), Ok(0));
@@ -18,14 +18,16 @@ use std::any::Any;
 use std::fmt::{Debug, Formatter};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::lock::FileLock;
 use crate::object_id::ObjectId;
-use crate::op_heads_store::{OpHeadsStore, OpHeadsStoreLock};
+use crate::op_heads_store::{OpHeadsStore, OpHeadsStoreError, OpHeadsStoreLock};
 use crate::op_store::OperationId;
 
 pub struct SimpleOpHeadsStore {
     dir: PathBuf,
+    lock_timeout: Option<Duration>,
 }
 
 impl Debug for SimpleOpHeadsStore {
@@ -44,12 +46,25 @@ impl SimpleOpHeadsStore {
     pub fn init(dir: &Path) -> Self {
         let op_heads_dir = dir.join("heads");
         fs::create_dir(&op_heads_dir).unwrap();
-        Self { dir: op_heads_dir }
+        Self {
+            dir: op_heads_dir,
+            lock_timeout: None,
+        }
     }
 
     pub fn load(dir: &Path) -> Self {
         let op_heads_dir = dir.join("heads");
-        Self { dir: op_heads_dir }
+        Self {
+            dir: op_heads_dir,
+            lock_timeout: None,
+        }
+    }
+
+    /// Sets how long `lock()` waits for another process to release the lock.
+    /// Waits indefinitely if `None`.
+    pub fn with_lock_timeout(mut self, lock_timeout: Option<Duration>) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
     }
 
     fn add_op_head(&self, id: &OperationId) {
@@ -100,9 +115,9 @@ impl OpHeadsStore for SimpleOpHeadsStore {
         op_heads
     }
 
-    fn lock(&self) -> Box<dyn OpHeadsStoreLock + '_> {
-        Box::new(SimpleOpHeadsStoreLock {
-            _lock: FileLock::lock(self.dir.join("lock")),
-        })
+    fn lock(&self) -> Result<Box<dyn OpHeadsStoreLock + '_>, OpHeadsStoreError> {
+        let lock = FileLock::lock_with_timeout(self.dir.join("lock"), self.lock_timeout)
+            .map_err(|err| OpHeadsStoreError::Lock(err.into()))?;
+        Ok(Box::new(SimpleOpHeadsStoreLock { _lock: lock }))
     }
 }
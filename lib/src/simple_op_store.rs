@@ -27,7 +27,7 @@ use prost::Message;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
-use crate::backend::{CommitId, MillisSinceEpoch, Timestamp};
+use crate::backend::{ChangeId, CommitId, MillisSinceEpoch, Timestamp};
 use crate::content_hash::blake2b_hash;
 use crate::file_util::{persist_content_addressed_temp_file, IoResultExt as _, PathError};
 use crate::merge::Merge;
@@ -181,6 +181,26 @@ impl OpStore for SimpleOpStore {
         Ok(id)
     }
 
+    fn all_operation_ids(&self, _head_ids: &[OperationId]) -> OpStoreResult<Vec<OperationId>> {
+        let op_dir = self.path.join("operations");
+        let list = || -> io::Result<_> {
+            let mut ids = vec![self.root_operation_id.clone()];
+            for entry in op_dir.read_dir()? {
+                let Ok(name) = entry?.file_name().into_string() else {
+                    continue; // Skip invalid UTF-8
+                };
+                let Ok(id) = OperationId::try_from_hex(&name) else {
+                    continue; // Skip invalid hex
+                };
+                ids.push(id);
+            }
+            Ok(ids)
+        };
+        list()
+            .context(&op_dir)
+            .map_err(|err| OpStoreError::Other(err.into()))
+    }
+
     fn resolve_operation_id_prefix(
         &self,
         prefix: &HexPrefix,
@@ -434,6 +454,12 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
 
     proto.git_head = ref_target_to_proto(&view.git_head);
 
+    for (name, change_id) in &view.pinned_branches {
+        proto
+            .pinned_branches
+            .insert(name.clone(), change_id.to_bytes());
+    }
+
     proto
 }
 
@@ -480,6 +506,10 @@ fn view_from_proto(proto: crate::protos::op_store::View) -> View {
         view.git_head = RefTarget::normal(CommitId::new(proto.git_head_legacy));
     }
 
+    for (name, change_id) in proto.pinned_branches {
+        view.pinned_branches.insert(name, ChangeId::new(change_id));
+    }
+
     if !proto.has_git_refs_migrated_to_remote {
         migrate_git_refs_to_remote(&mut view);
     }
@@ -733,6 +763,7 @@ mod tests {
                 WorkspaceId::default() => default_wc_commit_id,
                 WorkspaceId::new("test".to_string()) => test_wc_commit_id,
             },
+            pinned_branches: btreemap! {},
         }
     }
 
@@ -792,6 +823,20 @@ mod tests {
         assert_eq!(read_view, view);
     }
 
+    #[test]
+    fn test_read_write_view_with_pinned_branches() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = SimpleOpStore::init(temp_dir.path());
+        let mut view = create_view();
+        let view_id_without_pins = store.write_view(&view).unwrap();
+        view.pinned_branches
+            .insert("main".to_string(), ChangeId::from_hex("eee111"));
+        let view_id = store.write_view(&view).unwrap();
+        assert_ne!(view_id, view_id_without_pins);
+        let read_view = store.read_view(&view_id).unwrap();
+        assert_eq!(read_view, view);
+    }
+
     #[test]
     fn test_read_write_operation() {
         let temp_dir = testutils::new_temp_dir();
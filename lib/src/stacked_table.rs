@@ -27,6 +27,7 @@ use std::io;
 use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use blake2::{Blake2b512, Digest};
 use tempfile::NamedTempFile;
@@ -367,6 +368,7 @@ pub struct TableStore {
     dir: PathBuf,
     key_size: usize,
     cached_tables: RwLock<HashMap<String, Arc<ReadonlyTable>>>,
+    lock_timeout: Option<Duration>,
 }
 
 impl TableStore {
@@ -376,6 +378,7 @@ impl TableStore {
             dir,
             key_size,
             cached_tables: Default::default(),
+            lock_timeout: None,
         }
     }
 
@@ -393,9 +396,17 @@ impl TableStore {
             dir,
             key_size,
             cached_tables: Default::default(),
+            lock_timeout: None,
         }
     }
 
+    /// Sets how long to wait for another process to release the lock on the
+    /// table heads. Waits indefinitely if `None`.
+    pub fn with_lock_timeout(mut self, lock_timeout: Option<Duration>) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+
     pub fn save_table(&self, mut_table: MutableTable) -> TableStoreResult<Arc<ReadonlyTable>> {
         let maybe_parent_table = mut_table.parent_file.clone();
         let table = mut_table.save_in(self)?;
@@ -424,8 +435,9 @@ impl TableStore {
         std::fs::remove_file(self.dir.join("heads").join(&table.name)).ok();
     }
 
-    fn lock(&self) -> FileLock {
-        FileLock::lock(self.dir.join("lock"))
+    fn lock(&self) -> TableStoreResult<FileLock> {
+        FileLock::lock_with_timeout(self.dir.join("lock"), self.lock_timeout)
+            .map_err(|err| TableStoreError(io::Error::other(err)))
     }
 
     fn load_table(&self, name: String) -> TableStoreResult<Arc<ReadonlyTable>> {
@@ -477,7 +489,7 @@ impl TableStore {
     }
 
     pub fn get_head_locked(&self) -> TableStoreResult<(Arc<ReadonlyTable>, FileLock)> {
-        let lock = self.lock();
+        let lock = self.lock()?;
         let mut tables = self.get_head_tables()?;
 
         if tables.is_empty() {
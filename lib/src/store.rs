@@ -15,12 +15,16 @@
 #![allow(missing_docs)]
 
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
 use std::fmt::{Debug, Formatter};
+use std::hash::{BuildHasher as _, Hash};
 use std::io::Read;
-use std::sync::{Arc, RwLock};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 
+use clru::CLruCache;
 use pollster::FutureExt;
 
 use crate::backend::{
@@ -32,6 +36,7 @@ use crate::index::Index;
 use crate::merge::{Merge, MergedTreeValue};
 use crate::merged_tree::MergedTree;
 use crate::repo_path::{RepoPath, RepoPathBuf};
+use crate::settings::ObjectCacheSettings;
 use crate::signing::Signer;
 use crate::tree::Tree;
 use crate::tree_builder::TreeBuilder;
@@ -41,11 +46,118 @@ use crate::tree_builder::TreeBuilder;
 pub struct Store {
     backend: Box<dyn Backend>,
     signer: Signer,
-    commit_cache: RwLock<HashMap<CommitId, Arc<backend::Commit>>>,
-    tree_cache: RwLock<HashMap<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
+    commit_cache: ObjectCache<CommitId, Arc<backend::Commit>>,
+    tree_cache: ObjectCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>,
     use_tree_conflict_format: bool,
 }
 
+/// Statistics about the usage of an object cache.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Maximum number of objects kept in the cache, or `None` if unbounded.
+    pub capacity: Option<usize>,
+    /// Number of objects currently in the cache.
+    pub len: usize,
+    /// Number of lookups that were served from the cache.
+    pub hits: u64,
+    /// Number of lookups that had to be read from the backend.
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Ratio of lookups that were served from the cache, or `None` if there
+    /// were no lookups.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// Statistics about the object caches of a `Store`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoreCacheStats {
+    pub commits: CacheStats,
+    pub trees: CacheStats,
+}
+
+/// Number of independently locked parts of an object cache.
+const NUM_CACHE_SHARDS: usize = 16;
+
+/// Least-recently-used cache which counts hits and misses.
+///
+/// The cache is split into shards by key hash, each with its own lock, so that
+/// threads merging trees in parallel don't serialize on a single lock. Objects
+/// are evicted in least-recently-used order within each shard.
+struct ObjectCache<K, V> {
+    shards: Vec<Mutex<CLruCache<K, V>>>,
+    hash_builder: RandomState,
+    capacity: Option<NonZeroUsize>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> ObjectCache<K, V> {
+    fn new(capacity: Option<NonZeroUsize>) -> Self {
+        let shards = if let Some(capacity) = capacity {
+            // Split the capacity so that the shards add up to it exactly.
+            let num_shards = NUM_CACHE_SHARDS.min(capacity.get());
+            (0..num_shards)
+                .map(|i| {
+                    let shard_capacity =
+                        capacity.get() / num_shards + usize::from(i < capacity.get() % num_shards);
+                    NonZeroUsize::new(shard_capacity).unwrap()
+                })
+                .map(|shard_capacity| Mutex::new(CLruCache::new(shard_capacity)))
+                .collect()
+        } else {
+            // An unbounded cache doesn't allocate its capacity upfront.
+            (0..NUM_CACHE_SHARDS)
+                .map(|_| Mutex::new(CLruCache::new(NonZeroUsize::MAX)))
+                .collect()
+        };
+        ObjectCache {
+            shards,
+            hash_builder: RandomState::new(),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn shard(&self, key: &K) -> &Mutex<CLruCache<K, V>> {
+        let hash = self.hash_builder.hash_one(key);
+        &self.shards[(hash % self.shards.len() as u64) as usize]
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        let value = self.shard(key).lock().unwrap().get(key).cloned();
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    fn insert(&self, key: K, value: V) {
+        self.shard(&key).lock().unwrap().put(key, value);
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            capacity: self.capacity.map(NonZeroUsize::get),
+            len: self
+                .shards
+                .iter()
+                .map(|shard| shard.lock().unwrap().len())
+                .sum(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
 impl Debug for Store {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
         f.debug_struct("Store")
@@ -59,12 +171,13 @@ impl Store {
         backend: Box<dyn Backend>,
         signer: Signer,
         use_tree_conflict_format: bool,
+        cache_settings: &ObjectCacheSettings,
     ) -> Arc<Self> {
         Arc::new(Store {
             backend,
             signer,
-            commit_cache: Default::default(),
-            tree_cache: Default::default(),
+            commit_cache: ObjectCache::new(cache_settings.commit_capacity),
+            tree_cache: ObjectCache::new(cache_settings.tree_capacity),
             use_tree_conflict_format,
         })
     }
@@ -106,6 +219,14 @@ impl Store {
         self.backend.concurrency()
     }
 
+    /// Returns statistics about the commit and tree caches.
+    pub fn cache_stats(&self) -> StoreCacheStats {
+        StoreCacheStats {
+            commits: self.commit_cache.stats(),
+            trees: self.tree_cache.stats(),
+        }
+    }
+
     pub fn empty_merged_tree_id(&self) -> MergedTreeId {
         MergedTreeId::Legacy(self.backend.empty_tree_id().clone())
     }
@@ -124,16 +245,12 @@ impl Store {
     }
 
     async fn get_backend_commit(&self, id: &CommitId) -> BackendResult<Arc<backend::Commit>> {
-        {
-            let read_locked_cached = self.commit_cache.read().unwrap();
-            if let Some(data) = read_locked_cached.get(id).cloned() {
-                return Ok(data);
-            }
+        if let Some(data) = self.commit_cache.get(id) {
+            return Ok(data);
         }
         let commit = self.backend.read_commit(id).await?;
         let data = Arc::new(commit);
-        let mut write_locked_cache = self.commit_cache.write().unwrap();
-        write_locked_cache.insert(id.clone(), data.clone());
+        self.commit_cache.insert(id.clone(), data.clone());
         Ok(data)
     }
 
@@ -146,10 +263,7 @@ impl Store {
 
         let (commit_id, commit) = self.backend.write_commit(commit, sign_with)?;
         let data = Arc::new(commit);
-        {
-            let mut write_locked_cache = self.commit_cache.write().unwrap();
-            write_locked_cache.insert(commit_id.clone(), data.clone());
-        }
+        self.commit_cache.insert(commit_id.clone(), data.clone());
 
         Ok(Commit::new(self.clone(), commit_id, data))
     }
@@ -173,16 +287,12 @@ impl Store {
         id: &TreeId,
     ) -> BackendResult<Arc<backend::Tree>> {
         let key = (dir.to_owned(), id.clone());
-        {
-            let read_locked_cache = self.tree_cache.read().unwrap();
-            if let Some(data) = read_locked_cache.get(&key).cloned() {
-                return Ok(data);
-            }
+        if let Some(data) = self.tree_cache.get(&key) {
+            return Ok(data);
         }
         let data = self.backend.read_tree(dir, id).await?;
         let data = Arc::new(data);
-        let mut write_locked_cache = self.tree_cache.write().unwrap();
-        write_locked_cache.insert(key, data.clone());
+        self.tree_cache.insert(key, data.clone());
         Ok(data)
     }
 
@@ -206,10 +316,8 @@ impl Store {
     ) -> BackendResult<Tree> {
         let tree_id = self.backend.write_tree(path, &tree)?;
         let data = Arc::new(tree);
-        {
-            let mut write_locked_cache = self.tree_cache.write().unwrap();
-            write_locked_cache.insert((path.to_owned(), tree_id.clone()), data.clone());
-        }
+        self.tree_cache
+            .insert((path.to_owned(), tree_id.clone()), data.clone());
 
         Ok(Tree::new(self.clone(), path.to_owned(), tree_id, data))
     }
@@ -268,7 +376,62 @@ impl Store {
         TreeBuilder::new(self.clone(), base_tree_id)
     }
 
+    pub fn flush(&self) -> BackendResult<()> {
+        self.backend.flush()
+    }
+
     pub fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         self.backend.gc(index, keep_newer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_object_cache_capacity() {
+        let cache = ObjectCache::new(NonZeroUsize::new(20));
+        for i in 0..100 {
+            cache.insert(i, i);
+        }
+        let stats = cache.stats();
+        assert_eq!(stats.capacity, Some(20));
+        assert!(stats.len <= 20, "{stats:?}");
+
+        let cache = ObjectCache::new(NonZeroUsize::new(1));
+        cache.insert(1, 1);
+        cache.insert(2, 2);
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), Some(2));
+        assert_eq!(cache.stats().len, 1);
+    }
+
+    #[test]
+    fn test_object_cache_parallel() {
+        let cache = ObjectCache::new(None);
+        let num_threads = 8;
+        let num_keys = 1000;
+        thread::scope(|scope| {
+            for _ in 0..num_threads {
+                scope.spawn(|| {
+                    for i in 0..num_keys {
+                        if cache.get(&i).is_none() {
+                            cache.insert(i, i * 2);
+                        }
+                        assert_eq!(cache.get(&i), Some(i * 2));
+                    }
+                });
+            }
+        });
+        let stats = cache.stats();
+        assert_eq!(stats.capacity, None);
+        assert_eq!(stats.len, num_keys as usize);
+        // Every thread looks up each key at least once after inserting it
+        assert_eq!(stats.hits + stats.misses, 2 * num_threads * num_keys);
+        assert!(stats.hits >= num_threads * num_keys, "{stats:?}");
+        assert!(stats.misses <= num_threads * num_keys, "{stats:?}");
+    }
+}
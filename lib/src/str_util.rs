@@ -38,6 +38,8 @@ pub enum StringPatternParseError {
 pub enum StringPattern {
     /// Matches strings exactly equal to `string`.
     Exact(String),
+    /// Matches strings equal to `string` ignoring ASCII case.
+    ExactI(String),
     /// Unix-style shell wildcard pattern.
     Glob(glob::Pattern),
     /// Matches strings that contain `substring`.
@@ -55,6 +57,11 @@ impl StringPattern {
         StringPattern::Exact(src.into())
     }
 
+    /// Creates pattern that matches exactly, ignoring ASCII case.
+    pub fn exact_i(src: impl Into<String>) -> Self {
+        StringPattern::ExactI(src.into())
+    }
+
     /// Parses the given string as glob pattern.
     pub fn glob(src: &str) -> Result<Self, StringPatternParseError> {
         // TODO: might be better to do parsing and compilation separately since
@@ -85,14 +92,14 @@ impl StringPattern {
     pub fn as_exact(&self) -> Option<&str> {
         match self {
             StringPattern::Exact(literal) => Some(literal),
-            StringPattern::Glob(_) | StringPattern::Substring(_) => None,
+            StringPattern::ExactI(_) | StringPattern::Glob(_) | StringPattern::Substring(_) => None,
         }
     }
 
     /// Returns the original string of this pattern.
     pub fn as_str(&self) -> &str {
         match self {
-            StringPattern::Exact(literal) => literal,
+            StringPattern::Exact(literal) | StringPattern::ExactI(literal) => literal,
             StringPattern::Glob(pattern) => pattern.as_str(),
             StringPattern::Substring(needle) => needle,
         }
@@ -104,6 +111,7 @@ impl StringPattern {
         // TODO: If we add Regex pattern, it will return None.
         match self {
             StringPattern::Exact(literal) => Some(glob::Pattern::escape(literal).into()),
+            StringPattern::ExactI(_) => None,
             StringPattern::Glob(pattern) => Some(pattern.as_str().into()),
             StringPattern::Substring(needle) if needle.is_empty() => Some("*".into()),
             StringPattern::Substring(needle) => {
@@ -116,6 +124,7 @@ impl StringPattern {
     pub fn matches(&self, haystack: &str) -> bool {
         match self {
             StringPattern::Exact(literal) => haystack == literal,
+            StringPattern::ExactI(literal) => haystack.eq_ignore_ascii_case(literal),
             StringPattern::Glob(pattern) => pattern.matches(haystack),
             StringPattern::Substring(needle) => haystack.contains(needle),
         }
@@ -162,5 +171,14 @@ mod tests {
             StringPattern::Substring("*".into()).to_glob(),
             Some("*[*]*".into())
         );
+        assert_eq!(StringPattern::exact_i("a").to_glob(), None);
+    }
+
+    #[test]
+    fn test_string_pattern_matches() {
+        assert!(StringPattern::exact("a@b").matches("a@b"));
+        assert!(!StringPattern::exact("a@b").matches("A@b"));
+        assert!(StringPattern::exact_i("a@b").matches("A@b"));
+        assert!(!StringPattern::exact_i("a@b").matches("a@bc"));
     }
 }
@@ -0,0 +1,199 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and matching of date patterns.
+
+use chrono::{DateTime, Days, FixedOffset, Months, NaiveDate, NaiveDateTime, TimeDelta};
+use thiserror::Error;
+
+use crate::backend::{MillisSinceEpoch, Timestamp};
+
+/// Error occurred during date pattern parsing.
+#[derive(Debug, Error)]
+pub enum DatePatternParseError {
+    /// Unknown pattern kind is specified.
+    #[error(
+        r#"Invalid date pattern kind "{0}:", try prefixing with one of `after:` or `before:`"#
+    )]
+    InvalidKind(String),
+    /// The date couldn't be parsed.
+    #[error(r#"Invalid date "{0}""#)]
+    InvalidDate(String),
+}
+
+/// Pattern to be tested against timestamps like commit author or committer
+/// date.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DatePattern {
+    /// Matches timestamps at or after the given instant.
+    AtOrAfter(MillisSinceEpoch),
+    /// Matches timestamps before, but not including, the given instant.
+    Before(MillisSinceEpoch),
+}
+
+impl DatePattern {
+    /// Parses the given string as pattern of the specified `kind`. Relative
+    /// dates such as "2 weeks ago" are resolved against `now`, and absolute
+    /// dates without time zone are interpreted in the time zone of `now`.
+    pub fn from_str_kind(
+        src: &str,
+        kind: &str,
+        now: DateTime<FixedOffset>,
+    ) -> Result<Self, DatePatternParseError> {
+        let millis_since_epoch =
+            || parse_date(src, now).map(|date| MillisSinceEpoch(date.timestamp_millis()));
+        match kind {
+            "after" => Ok(DatePattern::AtOrAfter(millis_since_epoch()?)),
+            "before" => Ok(DatePattern::Before(millis_since_epoch()?)),
+            _ => Err(DatePatternParseError::InvalidKind(kind.to_owned())),
+        }
+    }
+
+    /// Returns true if this pattern matches the `timestamp`.
+    pub fn matches(&self, timestamp: &Timestamp) -> bool {
+        match self {
+            DatePattern::AtOrAfter(earliest) => *earliest <= timestamp.timestamp,
+            DatePattern::Before(latest) => timestamp.timestamp < *latest,
+        }
+    }
+}
+
+/// Parses an absolute date such as "2024-02-01" or "2024-02-01T12:00:00Z", or
+/// a relative date such as "yesterday" or "2 weeks ago".
+fn parse_date(
+    src: &str,
+    now: DateTime<FixedOffset>,
+) -> Result<DateTime<FixedOffset>, DatePatternParseError> {
+    let invalid_date = || DatePatternParseError::InvalidDate(src.to_owned());
+    let text = src.trim();
+    let lowercase_text = text.to_ascii_lowercase();
+    let start_of_today = || {
+        now.date_naive()
+            .and_time(Default::default())
+            .and_local_timezone(*now.offset())
+            .single()
+    };
+    match lowercase_text.as_str() {
+        "now" => return Ok(now),
+        "today" => return start_of_today().ok_or_else(invalid_date),
+        "yesterday" => {
+            return start_of_today()
+                .and_then(|today| today.checked_sub_days(Days::new(1)))
+                .ok_or_else(invalid_date);
+        }
+        _ => {}
+    }
+    if let Some(amount_and_unit) = lowercase_text.strip_suffix(" ago") {
+        let (amount, unit) = amount_and_unit.split_once(' ').ok_or_else(invalid_date)?;
+        let amount: u32 = amount.parse().map_err(|_| invalid_date())?;
+        let unit = unit.strip_suffix('s').unwrap_or(unit);
+        let date = match unit {
+            "second" | "sec" => TimeDelta::try_seconds(amount.into())
+                .and_then(|delta| now.checked_sub_signed(delta)),
+            "minute" | "min" => TimeDelta::try_minutes(amount.into())
+                .and_then(|delta| now.checked_sub_signed(delta)),
+            "hour" => {
+                TimeDelta::try_hours(amount.into()).and_then(|delta| now.checked_sub_signed(delta))
+            }
+            "day" => now.checked_sub_days(Days::new(amount.into())),
+            "week" => now.checked_sub_days(Days::new(u64::from(amount) * 7)),
+            "month" => now.checked_sub_months(Months::new(amount)),
+            "year" => amount
+                .checked_mul(12)
+                .and_then(|months| now.checked_sub_months(Months::new(months))),
+            _ => None,
+        };
+        return date.ok_or_else(invalid_date);
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+        return Ok(date);
+    }
+    let naive_date_time = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+            Some(date.and_time(Default::default()))
+        })
+        .ok_or_else(invalid_date)?;
+    naive_date_time
+        .and_local_timezone(*now.offset())
+        .single()
+        .ok_or_else(invalid_date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-03-15T12:30:00+02:00").unwrap()
+    }
+
+    fn parse(src: &str) -> String {
+        parse_date(src, now()).unwrap().to_rfc3339()
+    }
+
+    #[test]
+    fn test_parse_date_relative() {
+        assert_eq!(parse("now"), "2024-03-15T12:30:00+02:00");
+        assert_eq!(parse("today"), "2024-03-15T00:00:00+02:00");
+        assert_eq!(parse("Yesterday"), "2024-03-14T00:00:00+02:00");
+        assert_eq!(parse("30 seconds ago"), "2024-03-15T12:29:30+02:00");
+        assert_eq!(parse("1 hour ago"), "2024-03-15T11:30:00+02:00");
+        assert_eq!(parse("2 weeks ago"), "2024-03-01T12:30:00+02:00");
+        assert_eq!(parse("1 month ago"), "2024-02-15T12:30:00+02:00");
+        assert_eq!(parse("2 years ago"), "2022-03-15T12:30:00+02:00");
+    }
+
+    #[test]
+    fn test_parse_date_absolute() {
+        assert_eq!(parse("2024-01-31"), "2024-01-31T00:00:00+02:00");
+        assert_eq!(parse("2024-01-31 08:15"), "2024-01-31T08:15:00+02:00");
+        assert_eq!(parse("2024-01-31 08:15:30"), "2024-01-31T08:15:30+02:00");
+        assert_eq!(
+            parse("2024-01-31T08:15:30-05:00"),
+            "2024-01-31T08:15:30-05:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_date_invalid() {
+        for src in ["", "soon", "two days ago", "3 fortnights ago", "2024-13-01"] {
+            assert!(
+                matches!(
+                    parse_date(src, now()),
+                    Err(DatePatternParseError::InvalidDate(_))
+                ),
+                "{src:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_date_pattern_matches() {
+        let timestamp = |src: &str| Timestamp::from_datetime(parse_date(src, now()).unwrap());
+        let after = DatePattern::from_str_kind("2024-01-31", "after", now()).unwrap();
+        assert!(after.matches(&timestamp("2024-01-31")));
+        assert!(after.matches(&timestamp("2024-02-01")));
+        assert!(!after.matches(&timestamp("2024-01-30 23:59")));
+        let before = DatePattern::from_str_kind("2024-01-31", "before", now()).unwrap();
+        assert!(!before.matches(&timestamp("2024-01-31")));
+        assert!(before.matches(&timestamp("2024-01-30 23:59")));
+        assert!(matches!(
+            DatePattern::from_str_kind("2024-01-31", "during", now()),
+            Err(DatePatternParseError::InvalidKind(_))
+        ));
+    }
+}
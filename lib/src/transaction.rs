@@ -14,20 +14,32 @@
 
 #![allow(missing_docs)]
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use itertools::Itertools as _;
+use thiserror::Error;
 
-use crate::backend::Timestamp;
+use crate::backend::{BackendError, BackendResult, Timestamp};
 use crate::index::ReadonlyIndex;
-use crate::op_heads_store::OpHeadsStore;
-use crate::op_store::OperationMetadata;
+use crate::journal::{self, JournalEntry};
+use crate::op_heads_store::{OpHeadsStore, OpHeadsStoreError};
+use crate::op_store::{OpStoreError, OperationMetadata};
 use crate::operation::Operation;
 use crate::repo::{MutableRepo, ReadonlyRepo, Repo, RepoLoader, RepoLoaderError};
 use crate::settings::UserSettings;
 use crate::view::View;
 use crate::{dag_walk, op_store};
 
+/// Error from attempts to commit a transaction.
+#[derive(Debug, Error)]
+pub enum TransactionCommitError {
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    #[error(transparent)]
+    OpHeadsStore(#[from] OpHeadsStoreError),
+}
+
 /// An in-memory representation of a repo and any changes being made to it.
 ///
 /// Within the scope of a transaction, changes to the repository are made
@@ -92,19 +104,93 @@ impl Transaction {
         Ok(())
     }
 
+    /// Merges operations which were published since this transaction started
+    /// into it, so the transaction can be committed on top of them instead of
+    /// creating divergent operation heads.
+    ///
+    /// Operations published while merging are picked up by another attempt,
+    /// up to `max_attempts` times. Any operations landing after that are left
+    /// to be resolved when the repo is next loaded at head. Returns the merged
+    /// operations.
+    pub fn merge_concurrent_operations(
+        &mut self,
+        settings: &UserSettings,
+        max_attempts: usize,
+    ) -> Result<Vec<Operation>, RepoLoaderError> {
+        let mut merged_ops = vec![];
+        for _ in 0..max_attempts {
+            let concurrent_ops = self.concurrent_operations()?;
+            if concurrent_ops.is_empty() {
+                break;
+            }
+            for other_op in concurrent_ops {
+                self.merge_operation(other_op.clone())?;
+                self.mut_repo.rebase_descendants(settings)?;
+                merged_ops.push(other_op);
+            }
+            // The base operation is usually an ancestor of the merged ones, in
+            // which case the new operation shouldn't be a merge.
+            let parent_heads = dag_walk::heads_ok(
+                self.parent_ops.iter().cloned().map(Ok),
+                |op: &Operation| op.id().clone(),
+                |op: &Operation| op.parents().collect_vec(),
+            )?;
+            self.parent_ops.retain(|op| parent_heads.contains(op));
+        }
+        Ok(merged_ops)
+    }
+
+    /// Returns the current operation heads which are neither parents of this
+    /// transaction nor their ancestors, oldest first.
+    fn concurrent_operations(&self) -> Result<Vec<Operation>, OpStoreError> {
+        let op_store = self.base_repo().op_store();
+        let parent_ids: HashSet<_> = self.parent_ops.iter().map(|op| op.id().clone()).collect();
+        let other_ops: Vec<_> = self
+            .base_repo()
+            .op_heads_store()
+            .get_op_heads()
+            .into_iter()
+            .filter(|id| !parent_ids.contains(id))
+            .map(|id| -> Result<Operation, OpStoreError> {
+                let data = op_store.read_operation(&id)?;
+                Ok(Operation::new(op_store.clone(), id, data))
+            })
+            .try_collect()?;
+        if other_ops.is_empty() {
+            return Ok(vec![]);
+        }
+        let heads = dag_walk::heads_ok(
+            self.parent_ops.iter().cloned().chain(other_ops).map(Ok),
+            |op: &Operation| op.id().clone(),
+            |op: &Operation| op.parents().collect_vec(),
+        )?;
+        let concurrent_ops = heads
+            .into_iter()
+            .filter(|op| !parent_ids.contains(op.id()))
+            .sorted_by_key(|op| op.metadata().end_time.timestamp)
+            .collect_vec();
+        Ok(concurrent_ops)
+    }
+
     pub fn set_is_snapshot(&mut self, is_snapshot: bool) {
         self.op_metadata.is_snapshot = is_snapshot;
     }
 
     /// Writes the transaction to the operation store and publishes it.
-    pub fn commit(self, description: impl Into<String>) -> Arc<ReadonlyRepo> {
-        self.write(description).publish()
+    pub fn commit(
+        self,
+        description: impl Into<String>,
+    ) -> Result<Arc<ReadonlyRepo>, TransactionCommitError> {
+        Ok(self.write(description)?.publish()?)
     }
 
     /// Writes the transaction to the operation store, but does not publish it.
     /// That means that a repo can be loaded at the operation, but the
     /// operation will not be seen when loading the repo at head.
-    pub fn write(mut self, description: impl Into<String>) -> UnpublishedOperation {
+    ///
+    /// Objects written by the transaction are flushed to disk first if the
+    /// store defers syncing them, and an error is returned if that fails.
+    pub fn write(mut self, description: impl Into<String>) -> BackendResult<UnpublishedOperation> {
         let mut_repo = self.mut_repo;
         // TODO: Should we instead just do the rebasing here if necessary?
         assert!(
@@ -114,6 +200,9 @@ impl Transaction {
         let base_repo = mut_repo.base_repo().clone();
         let (mut_index, view) = mut_repo.consume();
 
+        // Make sure the objects referenced by the new view are on disk before
+        // the operation pointing to them is.
+        base_repo.store().flush()?;
         let view_id = base_repo.op_store().write_view(view.store_view()).unwrap();
         self.op_metadata.description = description.into();
         self.op_metadata.end_time = self.end_time.unwrap_or_else(Timestamp::now);
@@ -133,7 +222,14 @@ impl Transaction {
             .index_store()
             .write_index(mut_index, operation.id())
             .unwrap();
-        UnpublishedOperation::new(&base_repo.loader(), operation, view, index)
+        let journal_entry = JournalEntry::new(operation.id().clone(), base_repo.view(), &view);
+        Ok(UnpublishedOperation::new(
+            &base_repo.loader(),
+            operation,
+            view,
+            index,
+            journal_entry,
+        ))
     }
 }
 
@@ -169,6 +265,7 @@ pub fn create_op_metadata(
 pub struct UnpublishedOperation {
     op_heads_store: Arc<dyn OpHeadsStore>,
     repo: Arc<ReadonlyRepo>,
+    journal_entry: JournalEntry,
 }
 
 impl UnpublishedOperation {
@@ -177,10 +274,12 @@ impl UnpublishedOperation {
         operation: Operation,
         view: View,
         index: Box<dyn ReadonlyIndex>,
+        journal_entry: JournalEntry,
     ) -> Self {
         UnpublishedOperation {
             op_heads_store: repo_loader.op_heads_store().clone(),
             repo: repo_loader.create_from(operation, view, index),
+            journal_entry,
         }
     }
 
@@ -188,11 +287,17 @@ impl UnpublishedOperation {
         self.repo.operation()
     }
 
-    pub fn publish(self) -> Arc<ReadonlyRepo> {
-        let _lock = self.op_heads_store.lock();
+    /// Makes the operation visible as an operation head. Fails if the
+    /// operation heads can't be locked, in which case the operation stays
+    /// written but unpublished.
+    pub fn publish(self) -> Result<Arc<ReadonlyRepo>, OpHeadsStoreError> {
+        let _lock = self.op_heads_store.lock()?;
         self.op_heads_store
             .update_op_heads(self.operation().parent_ids(), self.operation().id());
-        self.repo
+        // The journal is only a hint for external tools, so failing to update it
+        // shouldn't fail the operation.
+        journal::append(self.repo.repo_path(), &self.journal_entry).ok();
+        Ok(self.repo)
     }
 
     pub fn leave_unpublished(self) -> Arc<ReadonlyRepo> {
@@ -18,7 +18,7 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 
 use itertools::Itertools;
 
-use crate::backend::CommitId;
+use crate::backend::{ChangeId, CommitId};
 use crate::op_store::{BranchTarget, RefTarget, RefTargetOptionExt as _, RemoteRef, WorkspaceId};
 use crate::refs::LocalAndRemoteRef;
 use crate::str_util::StringPattern;
@@ -110,6 +110,7 @@ impl View {
     // TODO: maybe rename to forget_branch() because this seems unusual operation?
     pub fn remove_branch(&mut self, name: &str) {
         self.data.local_branches.remove(name);
+        self.data.pinned_branches.remove(name);
         for remote_view in self.data.remote_views.values_mut() {
             remote_view.branches.remove(name);
         }
@@ -145,6 +146,31 @@ impl View {
             self.data.local_branches.insert(name.to_owned(), target);
         } else {
             self.data.local_branches.remove(name);
+            self.data.pinned_branches.remove(name);
+        }
+    }
+
+    /// Iterates `(name, change_id)`s of local branches which are pinned to a
+    /// change, in lexicographical order.
+    pub fn pinned_branches(&self) -> impl Iterator<Item = (&str, &ChangeId)> {
+        self.data
+            .pinned_branches
+            .iter()
+            .map(|(name, change_id)| (name.as_ref(), change_id))
+    }
+
+    /// Returns the change the local branch is pinned to, if any.
+    pub fn get_branch_pin(&self, name: &str) -> Option<&ChangeId> {
+        self.data.pinned_branches.get(name)
+    }
+
+    /// Pins the local branch to follow the given change, or unpins it if
+    /// `change_id` is `None`.
+    pub fn set_branch_pin(&mut self, name: &str, change_id: Option<ChangeId>) {
+        if let Some(change_id) = change_id {
+            self.data.pinned_branches.insert(name.to_owned(), change_id);
+        } else {
+            self.data.pinned_branches.remove(name);
         }
     }
 
@@ -338,6 +364,7 @@ impl View {
             git_refs,
             git_head,
             wc_commit_ids,
+            pinned_branches: _,
         } = &self.data;
         itertools::chain!(
             head_ids,
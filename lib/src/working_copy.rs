@@ -24,11 +24,12 @@ use thiserror::Error;
 
 use crate::backend::{BackendError, MergedTreeId};
 use crate::commit::Commit;
+use crate::fingerprint_cache::FileFingerprintCache;
 use crate::fsmonitor::FsmonitorKind;
 use crate::gitignore::{GitIgnoreError, GitIgnoreFile};
 use crate::op_store::{OperationId, WorkspaceId};
 use crate::repo_path::{RepoPath, RepoPathBuf};
-use crate::settings::HumanByteSize;
+use crate::settings::{HumanByteSize, UserSettings};
 use crate::store::Store;
 
 /// The trait all working-copy implementations must implement.
@@ -73,6 +74,7 @@ pub trait WorkingCopyFactory {
         state_path: PathBuf,
         operation_id: OperationId,
         workspace_id: WorkspaceId,
+        settings: &UserSettings,
     ) -> Result<Box<dyn WorkingCopy>, WorkingCopyStateError>;
 
     /// Load an existing working copy.
@@ -81,7 +83,8 @@ pub trait WorkingCopyFactory {
         store: Arc<Store>,
         working_copy_path: PathBuf,
         state_path: PathBuf,
-    ) -> Box<dyn WorkingCopy>;
+        settings: &UserSettings,
+    ) -> Result<Box<dyn WorkingCopy>, WorkingCopyStateError>;
 }
 
 /// A working copy that's being modified.
@@ -198,6 +201,14 @@ pub struct SnapshotOptions<'a> {
     /// (depending on implementation)
     /// return `SnapshotError::NewFileTooLarge`.
     pub max_new_file_size: u64,
+    /// Whether to ignore changes to the executable bit of files on disk, and
+    /// keep the bit recorded in the tree instead. This is useful on
+    /// filesystems which don't preserve the executable bit.
+    pub ignore_executable_bit: bool,
+    /// Cache of file ids keyed by file size and mtime, shared with the other
+    /// workspaces of the repo. Files matching a cached fingerprint aren't read
+    /// again.
+    pub fingerprint_cache: Option<&'a FileFingerprintCache>,
 }
 
 impl SnapshotOptions<'_> {
@@ -208,6 +219,8 @@ impl SnapshotOptions<'_> {
             fsmonitor_kind: FsmonitorKind::None,
             progress: None,
             max_new_file_size: u64::MAX,
+            ignore_executable_bit: false,
+            fingerprint_cache: None,
         }
     }
 }
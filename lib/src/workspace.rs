@@ -72,6 +72,8 @@ pub enum WorkspaceLoadError {
     #[error("Repo path could not be interpreted as Unicode text")]
     NonUnicodePath,
     #[error(transparent)]
+    WorkingCopyState(#[from] WorkingCopyStateError),
+    #[error(transparent)]
     Path(#[from] PathError),
 }
 
@@ -115,7 +117,9 @@ fn init_working_copy(
         user_settings,
         &repo.store().root_commit(),
     )?;
-    let repo = tx.commit(format!("add workspace '{}'", workspace_id.as_str()));
+    let repo = tx
+        .commit(format!("add workspace '{}'", workspace_id.as_str()))
+        .map_err(|err| BackendInitError(err.into()))?;
 
     let working_copy = working_copy_factory.init_working_copy(
         repo.store().clone(),
@@ -123,6 +127,7 @@ fn init_working_copy(
         working_copy_state_path.clone(),
         repo.op_id().clone(),
         workspace_id,
+        user_settings,
     )?;
     let working_copy_type_path = working_copy_state_path.join("type");
     fs::write(&working_copy_type_path, working_copy.name()).context(&working_copy_type_path)?;
@@ -471,7 +476,8 @@ impl WorkspaceLoader {
         working_copy_factories: &HashMap<String, Box<dyn WorkingCopyFactory>>,
     ) -> Result<Workspace, WorkspaceLoadError> {
         let repo_loader = RepoLoader::init(user_settings, &self.repo_dir, store_factories)?;
-        let working_copy = self.load_working_copy(repo_loader.store(), working_copy_factories)?;
+        let working_copy =
+            self.load_working_copy(user_settings, repo_loader.store(), working_copy_factories)?;
         let workspace = Workspace::new(&self.workspace_root, working_copy, repo_loader)?;
         Ok(workspace)
     }
@@ -499,15 +505,18 @@ impl WorkspaceLoader {
 
     fn load_working_copy(
         &self,
+        user_settings: &UserSettings,
         store: &Arc<Store>,
         working_copy_factories: &HashMap<String, Box<dyn WorkingCopyFactory>>,
-    ) -> Result<Box<dyn WorkingCopy>, StoreLoadError> {
+    ) -> Result<Box<dyn WorkingCopy>, WorkspaceLoadError> {
         let working_copy_factory = self.get_working_copy_factory(working_copy_factories)?;
-        Ok(working_copy_factory.load_working_copy(
+        let working_copy = working_copy_factory.load_working_copy(
             store.clone(),
             self.workspace_root.to_owned(),
             self.working_copy_state_path.to_owned(),
-        ))
+            user_settings,
+        )?;
+        Ok(working_copy)
     }
 }
 
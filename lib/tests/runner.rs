@@ -15,6 +15,7 @@ mod test_diff_summary;
 mod test_git;
 mod test_git_backend;
 mod test_gpg;
+mod test_graph;
 mod test_id_prefix;
 mod test_index;
 mod test_init;
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+
 use jj_lib::backend::{ChangeId, MillisSinceEpoch, Signature, Timestamp};
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::merged_tree::DiffSummary;
@@ -19,7 +21,10 @@ use jj_lib::repo::Repo;
 use jj_lib::repo_path::{RepoPath, RepoPathBuf};
 use jj_lib::settings::UserSettings;
 use test_case::test_case;
-use testutils::{assert_rebased_onto, create_tree, CommitGraphBuilder, TestRepo, TestRepoBackend};
+use testutils::{
+    assert_rebased_onto, create_tree, load_repo_at_head, CommitGraphBuilder, TestRepo,
+    TestRepoBackend,
+};
 
 fn to_owned_path_vec(paths: &[&RepoPath]) -> Vec<RepoPathBuf> {
     paths.iter().map(|&path| path.to_owned()).collect()
@@ -75,7 +80,7 @@ fn test_initial(backend: TestRepoBackend) {
     assert_eq!(builder.author(), &author_signature);
     assert_eq!(builder.committer(), &committer_signature);
     let commit = builder.write().unwrap();
-    tx.commit("test");
+    tx.commit("test").unwrap();
 
     assert_eq!(commit.parents(), vec![store.root_commit()]);
     assert_eq!(commit.predecessors(), vec![]);
@@ -125,7 +130,7 @@ fn test_rewrite(backend: TestRepoBackend) {
         )
         .write()
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let rewritten_tree = create_tree(
         &repo,
@@ -151,7 +156,7 @@ fn test_rewrite(backend: TestRepoBackend) {
         .write()
         .unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    tx.commit("test");
+    tx.commit("test").unwrap();
     assert_eq!(rewritten_commit.parents(), vec![store.root_commit()]);
     assert_eq!(
         rewritten_commit.predecessors(),
@@ -244,6 +249,66 @@ fn test_rewrite_update_missing_user(backend: TestRepoBackend) {
     );
 }
 
+#[test_case(TestRepoBackend::Local ; "local backend")]
+#[test_case(TestRepoBackend::Git ; "git backend")]
+fn test_extra(backend: TestRepoBackend) {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(backend);
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let root_commit_id = repo.store().root_commit_id();
+    let empty_tree_id = repo.store().empty_merged_tree_id();
+    let plain_commit = tx
+        .mut_repo()
+        .new_commit(
+            &settings,
+            vec![root_commit_id.clone()],
+            empty_tree_id.clone(),
+        )
+        .write()
+        .unwrap();
+    let builder = tx
+        .mut_repo()
+        .new_commit(&settings, vec![root_commit_id.clone()], empty_tree_id)
+        .set_change_id(plain_commit.change_id().clone())
+        .set_committer(plain_commit.committer().clone())
+        .set_extra("ci.run-id", "123")
+        .set_extra("gerrit.change-id", "I0123");
+    assert_eq!(builder.extra().len(), 2);
+    let commit = builder.write().unwrap();
+    // Commits which only differ in extra metadata shouldn't be conflated
+    assert_ne!(commit.id(), plain_commit.id());
+    assert!(plain_commit.extra().is_empty());
+
+    // Extra metadata is carried over by rewrites
+    let rewritten_commit = tx
+        .mut_repo()
+        .rewrite_commit(&settings, &commit)
+        .set_description("rewritten")
+        .remove_extra("ci.run-id")
+        .write()
+        .unwrap();
+    tx.mut_repo().rebase_descendants(&settings).unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    // Extra metadata is persisted by the backend
+    let repo = load_repo_at_head(&settings, repo.repo_path());
+    let commit = repo.store().get_commit(commit.id()).unwrap();
+    assert_eq!(
+        commit.extra(),
+        &BTreeMap::from([
+            ("ci.run-id".to_owned(), "123".to_owned()),
+            ("gerrit.change-id".to_owned(), "I0123".to_owned()),
+        ])
+    );
+    let rewritten_commit = repo.store().get_commit(rewritten_commit.id()).unwrap();
+    assert_eq!(
+        rewritten_commit.extra(),
+        &BTreeMap::from([("gerrit.change-id".to_owned(), "I0123".to_owned())])
+    );
+}
+
 #[test_case(TestRepoBackend::Local ; "local backend")]
 // #[test_case(TestRepoBackend::Git ; "git backend")]
 fn test_commit_builder_descendants(backend: TestRepoBackend) {
@@ -257,7 +322,7 @@ fn test_commit_builder_descendants(backend: TestRepoBackend) {
     let commit1 = graph_builder.initial_commit();
     let commit2 = graph_builder.commit_with_parents(&[&commit1]);
     let commit3 = graph_builder.commit_with_parents(&[&commit2]);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // Test with for_new_commit()
     let mut tx = repo.start_transaction(&settings);
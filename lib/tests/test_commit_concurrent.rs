@@ -56,7 +56,7 @@ fn test_commit_parallel(backend: TestRepoBackend) {
             s.spawn(move || {
                 let mut tx = repo.start_transaction(&settings);
                 write_random_commit(tx.mut_repo(), &settings);
-                tx.commit("test");
+                tx.commit("test").unwrap();
             });
         }
     });
@@ -87,7 +87,7 @@ fn test_commit_parallel_instances(backend: TestRepoBackend) {
             s.spawn(move || {
                 let mut tx = repo.start_transaction(&settings);
                 write_random_commit(tx.mut_repo(), &settings);
-                tx.commit("test");
+                tx.commit("test").unwrap();
             });
         }
     });
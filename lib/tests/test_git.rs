@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::collections::{BTreeMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{mpsc, Arc, Barrier};
 use std::{fs, iter, thread};
 
@@ -112,7 +112,7 @@ fn test_import_refs() {
     git::import_head(tx.mut_repo()).unwrap();
     let stats = git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     let view = repo.view();
 
     assert!(stats.abandoned_commits.is_empty());
@@ -234,7 +234,7 @@ fn test_import_refs_reimport() {
     let mut tx = repo.start_transaction(&settings);
     let stats = git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     assert!(stats.abandoned_commits.is_empty());
     let expected_heads = hashset! {
@@ -257,12 +257,12 @@ fn test_import_refs_reimport() {
         .unwrap();
     tx.mut_repo()
         .set_local_branch_target("feature2", RefTarget::normal(commit6.id().clone()));
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let stats = git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     assert_eq!(
         // The order is unstable just because we import heads from Git repo.
@@ -487,7 +487,7 @@ fn test_import_refs_reimport_with_deleted_remote_ref() {
     let mut tx = repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let expected_heads = hashset! {
             jj_id(&commit_main),
@@ -543,7 +543,7 @@ fn test_import_refs_reimport_with_deleted_remote_ref() {
     let mut tx = repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let view = repo.view();
     // The local branches were indeed deleted
@@ -606,7 +606,7 @@ fn test_import_refs_reimport_with_moved_remote_ref() {
     let mut tx = repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let expected_heads = hashset! {
             jj_id(&commit_main),
@@ -672,7 +672,7 @@ fn test_import_refs_reimport_with_moved_remote_ref() {
     let mut tx = repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let view = repo.view();
     assert_eq!(view.branches().count(), 3);
@@ -739,7 +739,7 @@ fn test_import_refs_reimport_with_moved_untracked_remote_ref() {
     let mut tx = repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     let view = repo.view();
 
     assert_eq!(*view.heads(), hashset! { jj_id(&commit_remote_t0) });
@@ -759,7 +759,7 @@ fn test_import_refs_reimport_with_moved_untracked_remote_ref() {
     let mut tx = repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     let view = repo.view();
 
     // commit_remote_t0 should be abandoned, but commit_base shouldn't because
@@ -871,6 +871,116 @@ fn test_import_refs_reimport_abandoning_disabled() {
     assert!(tx.mut_repo().view().heads().contains(&jj_id(&commit2)));
 }
 
+#[test]
+fn test_import_refs_reassociate_rewritten_commits() {
+    // Test that local commits are replaced by the commits with the same
+    // Change-Id fetched from the configured remote
+    let settings = testutils::user_settings();
+    let git_settings = GitSettings {
+        reassociate_rewrites: vec!["origin".to_owned()],
+        ..Default::default()
+    };
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+
+    let commit1 = empty_git_commit(&git_repo, "refs/remotes/origin/main", &[]);
+    let mut tx = repo.start_transaction(&settings);
+    git::import_refs(tx.mut_repo(), &git_settings).unwrap();
+    let local_commit = create_random_commit(tx.mut_repo(), &settings)
+        .set_parents(vec![jj_id(&commit1)])
+        .set_description("local\n\nChange-Id: I0123456789abcdef\n")
+        .write()
+        .unwrap();
+    let local_child = create_random_commit(tx.mut_repo(), &settings)
+        .set_parents(vec![local_commit.id().clone()])
+        .write()
+        .unwrap();
+    let unrelated_commit = create_random_commit(tx.mut_repo(), &settings)
+        .set_parents(vec![jj_id(&commit1)])
+        .set_description("unrelated\n\nChange-Id: Ifedcba9876543210\n")
+        .write()
+        .unwrap();
+
+    // The remote amends the local commit and merges it
+    let signature = git2::Signature::now("Someone", "someone@example.com").unwrap();
+    let empty_tree = git_repo.find_tree(commit1.tree_id()).unwrap();
+    let amended_oid = git_repo
+        .commit(
+            Some("refs/remotes/origin/main"),
+            &signature,
+            &signature,
+            "amended\n\nChange-Id: I0123456789abcdef\n",
+            &empty_tree,
+            &[&commit1],
+        )
+        .unwrap();
+    let amended_id = CommitId::from_bytes(amended_oid.as_bytes());
+    let stats = git::import_refs(tx.mut_repo(), &git_settings).unwrap();
+    assert_eq!(
+        stats.reassociated_commits,
+        vec![(local_commit.id().clone(), amended_id.clone())]
+    );
+    tx.mut_repo().rebase_descendants(&settings).unwrap();
+    let mut_repo = tx.mut_repo();
+    let heads = mut_repo.view().heads();
+    assert!(!heads.contains(local_commit.id()));
+    assert!(heads.contains(unrelated_commit.id()));
+    assert!(!heads.contains(local_child.id()));
+    let rebased_child = heads
+        .iter()
+        .map(|id| mut_repo.store().get_commit(id).unwrap())
+        .find(|commit| commit.change_id() == local_child.change_id())
+        .unwrap();
+    assert_eq!(rebased_child.parent_ids(), &[amended_id.clone()]);
+
+    // A local commit is also replaced by the imported commit with the same
+    // change id, even without a Change-Id footer
+    let amended_commit = git_repo.find_commit(amended_oid).unwrap();
+    let rewritten_oid = git_repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "rewritten",
+            &empty_tree,
+            &[&amended_commit],
+        )
+        .unwrap();
+    let rewritten_id = CommitId::from_bytes(rewritten_oid.as_bytes());
+    let rewritten_change_id = mut_repo
+        .store()
+        .get_commit(&rewritten_id)
+        .unwrap()
+        .change_id()
+        .clone();
+    let local_commit = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![amended_id])
+        .set_change_id(rewritten_change_id)
+        .set_description("local")
+        .write()
+        .unwrap();
+    git_repo
+        .reference("refs/remotes/origin/main", rewritten_oid, true, "rewritten")
+        .unwrap();
+    let stats = git::import_refs(mut_repo, &git_settings).unwrap();
+    assert_eq!(
+        stats.reassociated_commits,
+        vec![(local_commit.id().clone(), rewritten_id)]
+    );
+
+    // Nothing is replaced if the remote isn't configured
+    let mut tx = repo.start_transaction(&settings);
+    let local_commit = create_random_commit(tx.mut_repo(), &settings)
+        .set_parents(vec![jj_id(&commit1)])
+        .set_description("local\n\nChange-Id: I0123456789abcdef\n")
+        .write()
+        .unwrap();
+    let stats = git::import_refs(tx.mut_repo(), &GitSettings::default()).unwrap();
+    assert!(stats.reassociated_commits.is_empty());
+    assert!(tx.mut_repo().view().heads().contains(local_commit.id()));
+}
+
 #[test]
 fn test_import_refs_reimport_conflicted_remote_branch() {
     let settings = testutils::user_settings();
@@ -909,7 +1019,7 @@ fn test_import_refs_reimport_conflicted_remote_branch() {
     // The conflict can be resolved by importing the current Git state
     let mut tx = repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &git_settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     assert_eq!(
         repo.view().get_git_ref("refs/remotes/origin/main"),
         &RefTarget::normal(jj_id(&commit2)),
@@ -977,7 +1087,7 @@ fn test_import_some_refs() {
     })
     .unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // There are two heads, feature2 and feature4.
     let view = repo.view();
@@ -1054,7 +1164,7 @@ fn test_import_some_refs() {
     })
     .unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // feature2 and feature4 will still be heads, and all four branches should be
     // present.
@@ -1071,7 +1181,7 @@ fn test_import_some_refs() {
     .unwrap();
     // No descendant should be rewritten.
     assert_eq!(tx.mut_repo().rebase_descendants(&settings).unwrap(), 0);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // feature2 and feature4 should still be the heads, and all three branches
     // feature2, feature3, and feature3 should exist.
@@ -1088,7 +1198,7 @@ fn test_import_some_refs() {
     .unwrap();
     // No descendant should be rewritten
     assert_eq!(tx.mut_repo().rebase_descendants(&settings).unwrap(), 0);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // feature2 and feature4 should still be the heads, and both branches
     // should exist.
@@ -1104,7 +1214,7 @@ fn test_import_some_refs() {
     .unwrap();
     // No descendant should be rewritten
     assert_eq!(tx.mut_repo().rebase_descendants(&settings).unwrap(), 0);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // feature2 should now be the only head and only branch.
     let view = repo.view();
@@ -1179,7 +1289,7 @@ fn test_import_refs_empty_git_repo() {
     tx.mut_repo()
         .rebase_descendants(&test_data.settings)
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     assert_eq!(*repo.view().heads(), heads_before);
     assert_eq!(repo.view().branches().count(), 0);
     assert_eq!(repo.view().tags().len(), 0);
@@ -1282,7 +1392,7 @@ fn test_import_refs_detached_head() {
     tx.mut_repo()
         .rebase_descendants(&test_data.settings)
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let expected_heads = hashset! { jj_id(&commit1) };
     assert_eq!(*repo.view().heads(), expected_heads);
@@ -1290,6 +1400,70 @@ fn test_import_refs_detached_head() {
     assert_eq!(repo.view().git_head(), &RefTarget::normal(jj_id(&commit1)));
 }
 
+#[test]
+fn test_import_reflog() {
+    let test_data = GitRepoData::create();
+    let git_settings = GitSettings::default();
+    let commit1 = empty_git_commit(&test_data.git_repo, "refs/heads/main", &[]);
+    let commit2 = empty_git_commit(&test_data.git_repo, "refs/heads/feature", &[]);
+    // commit1 is now only reachable from the reflog of "main"
+    git_ref(&test_data.git_repo, "refs/heads/main", commit2.id());
+    delete_git_ref(&test_data.git_repo, "refs/heads/feature");
+
+    let mut tx = test_data.repo.start_transaction(&test_data.settings);
+    git::import_refs(tx.mut_repo(), &git_settings).unwrap();
+    let hidden_ids = git::import_reflog(tx.mut_repo(), &test_data.git_repo).unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    assert_eq!(hidden_ids, vec![jj_id(&commit1)]);
+    assert!(repo.index().has_id(&jj_id(&commit1)));
+    let expected_heads = hashset! { jj_id(&commit2) };
+    assert_eq!(*repo.view().heads(), expected_heads);
+
+    // Indexed commits aren't reported again
+    let mut tx = repo.start_transaction(&test_data.settings);
+    let hidden_ids = git::import_reflog(tx.mut_repo(), &test_data.git_repo).unwrap();
+    assert_eq!(hidden_ids, vec![]);
+}
+
+#[test]
+fn test_import_stash() {
+    let mut test_data = GitRepoData::create();
+    let git_repo = &mut test_data.git_repo;
+    let workdir = git_repo.workdir().unwrap().to_owned();
+    let signature = git2::Signature::now("Someone", "someone@example.com").unwrap();
+    fs::write(workdir.join("file"), "base").unwrap();
+    let mut git_index = git_repo.index().unwrap();
+    git_index.add_path(Path::new("file")).unwrap();
+    git_index.write().unwrap();
+    let git_tree = git_repo.find_tree(git_index.write_tree().unwrap()).unwrap();
+    let base_oid = git_repo
+        .commit(Some("HEAD"), &signature, &signature, "base", &git_tree, &[])
+        .unwrap();
+    drop(git_tree);
+    fs::write(workdir.join("file"), "stashed").unwrap();
+    let stash_oid = git_repo.stash_save(&signature, "my stash", None).unwrap();
+
+    let mut tx = test_data.repo.start_transaction(&test_data.settings);
+    let commits =
+        git::import_stash(tx.mut_repo(), &test_data.git_repo, &test_data.settings).unwrap();
+    assert_eq!(commits.len(), 1);
+    let stash_commit = tx
+        .repo()
+        .store()
+        .get_commit(&CommitId::from_bytes(stash_oid.as_bytes()))
+        .unwrap();
+    assert_eq!(
+        commits[0].parent_ids(),
+        [CommitId::from_bytes(base_oid.as_bytes())]
+    );
+    assert_eq!(commits[0].tree_id(), stash_commit.tree_id());
+    assert_eq!(commits[0].author().name, "Someone");
+    assert!(commits[0].description().ends_with(": my stash\n"));
+    let repo = tx.commit("test").unwrap();
+    assert!(repo.view().heads().contains(commits[0].id()));
+}
+
 #[test]
 fn test_export_refs_no_detach() {
     // When exporting the branch that's current checked out, don't detach HEAD if
@@ -2049,7 +2223,7 @@ fn test_fetch_initial_commit() {
     // No default branch because the origin repo's HEAD wasn't set
     assert_eq!(stats.default_branch, None);
     assert!(stats.import_stats.abandoned_commits.is_empty());
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     // The initial commit is visible after git::fetch().
     let view = repo.view();
     assert!(view.heads().contains(&jj_id(&initial_git_commit)));
@@ -2096,7 +2270,7 @@ fn test_fetch_success() {
         &git_settings,
     )
     .unwrap();
-    test_data.repo = tx.commit("test");
+    test_data.repo = tx.commit("test").unwrap();
 
     test_data.origin_repo.set_head("refs/heads/main").unwrap();
     let new_git_commit = empty_git_commit(
@@ -2122,7 +2296,7 @@ fn test_fetch_success() {
     // The default branch is "main"
     assert_eq!(stats.default_branch, Some("main".to_string()));
     assert!(stats.import_stats.abandoned_commits.is_empty());
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     // The new commit is visible after we fetch again
     let view = repo.view();
     assert!(view.heads().contains(&jj_id(&new_git_commit)));
@@ -2349,7 +2523,7 @@ fn set_up_push_repos(settings: &UserSettings, temp_dir: &TempDir) -> PushTestSet
             state: RemoteRefState::Tracking,
         },
     );
-    let jj_repo = tx.commit("test");
+    let jj_repo = tx.commit("test").unwrap();
     PushTestSetup {
         source_repo_dir,
         jj_repo,
@@ -2418,7 +2592,7 @@ fn test_push_branches_success() {
     );
 
     // Check that the repo view reflects the changes in the Git repo
-    setup.jj_repo = tx.commit("test");
+    setup.jj_repo = tx.commit("test").unwrap();
     let mut tx = setup.jj_repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &GitSettings::default()).unwrap();
     assert!(!tx.mut_repo().has_changes());
@@ -2471,7 +2645,7 @@ fn test_push_branches_deletion() {
     assert!(view.get_remote_branch("main", "origin").is_absent());
 
     // Check that the repo view reflects the changes in the Git repo
-    setup.jj_repo = tx.commit("test");
+    setup.jj_repo = tx.commit("test").unwrap();
     let mut tx = setup.jj_repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &GitSettings::default()).unwrap();
     assert!(!tx.mut_repo().has_changes());
@@ -2541,7 +2715,7 @@ fn test_push_branches_mixed_deletion_and_addition() {
     );
 
     // Check that the repo view reflects the changes in the Git repo
-    setup.jj_repo = tx.commit("test");
+    setup.jj_repo = tx.commit("test").unwrap();
     let mut tx = setup.jj_repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &GitSettings::default()).unwrap();
     assert!(!tx.mut_repo().has_changes());
@@ -2554,7 +2728,7 @@ fn test_push_branches_not_fast_forward() {
     let mut setup = set_up_push_repos(&settings, &temp_dir);
     let mut tx = setup.jj_repo.start_transaction(&settings);
     let new_commit = write_random_commit(tx.mut_repo(), &settings);
-    setup.jj_repo = tx.commit("test");
+    setup.jj_repo = tx.commit("test").unwrap();
     let mut tx = setup.jj_repo.start_transaction(&settings);
 
     let targets = GitBranchPushTargets {
@@ -2584,7 +2758,7 @@ fn test_push_branches_not_fast_forward_with_force() {
     let mut setup = set_up_push_repos(&settings, &temp_dir);
     let mut tx = setup.jj_repo.start_transaction(&settings);
     let new_commit = write_random_commit(tx.mut_repo(), &settings);
-    setup.jj_repo = tx.commit("test");
+    setup.jj_repo = tx.commit("test").unwrap();
     let mut tx = setup.jj_repo.start_transaction(&settings);
 
     let targets = GitBranchPushTargets {
@@ -2710,7 +2884,7 @@ fn test_bulk_update_extra_on_import_refs() {
         let mut tx = repo.start_transaction(&settings);
         git::import_refs(tx.mut_repo(), &git_settings).unwrap();
         tx.mut_repo().rebase_descendants(&settings).unwrap();
-        tx.commit("test")
+        tx.commit("test").unwrap()
     };
 
     // Extra metadata table shouldn't be created per read_commit() call. The number
@@ -2749,7 +2923,7 @@ fn test_rewrite_imported_commit() {
     let mut tx = repo.start_transaction(&settings);
     git::import_refs(tx.mut_repo(), &git_settings).unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     let imported_commit = repo.store().get_commit(&jj_id(&git_commit)).unwrap();
 
     // Try to create identical commit with different change id.
@@ -2766,7 +2940,7 @@ fn test_rewrite_imported_commit() {
         .set_description(imported_commit.description())
         .write()
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // Imported commit shouldn't be reused, and the timestamp of the authored
     // commit should be adjusted to create new commit.
@@ -2810,7 +2984,7 @@ fn test_concurrent_write_commit() {
                     .set_description("racy commit")
                     .write()
                     .unwrap();
-                tx.commit(format!("writer {i}"));
+                tx.commit(format!("writer {i}")).unwrap();
                 sender
                     .send((commit.id().clone(), commit.change_id().clone()))
                     .unwrap();
@@ -2895,7 +3069,7 @@ fn test_concurrent_read_write_commit() {
                     .set_description(format!("commit {i}"))
                     .write()
                     .unwrap();
-                tx.commit(format!("writer {i}"));
+                tx.commit(format!("writer {i}")).unwrap();
                 assert_eq!(commit.id(), commit_id);
             });
         }
@@ -2943,7 +3117,7 @@ fn test_concurrent_read_write_commit() {
                         })
                         .collect_vec();
                     if tx.mut_repo().has_changes() {
-                        tx.commit(format!("reader {i}"));
+                        tx.commit(format!("reader {i}")).unwrap();
                     }
                     thread::yield_now();
                 }
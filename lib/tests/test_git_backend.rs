@@ -82,7 +82,7 @@ fn test_gc() {
         .set_predecessors(vec![commit_d.id().clone()])
         .write()
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     assert_eq!(
         *repo.view().heads(),
         hashset! {
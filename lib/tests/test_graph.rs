@@ -0,0 +1,161 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools as _;
+use jj_lib::graph;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use testutils::{create_tree, CommitGraphBuilder, TestRepo};
+
+#[test]
+fn test_graph_queries() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // o E
+    // |\
+    // o | D
+    // | o C
+    // o | B
+    // |/
+    // o A
+    // o root
+
+    let root_commit_id = repo.store().root_commit_id().clone();
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.mut_repo());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_b]);
+    let commit_e = graph_builder.commit_with_parents(&[&commit_d, &commit_c]);
+    let repo = tx.commit("test").unwrap();
+    let repo = repo.as_ref();
+
+    assert!(graph::is_ancestor(repo, commit_a.id(), commit_e.id()));
+    assert!(graph::is_ancestor(repo, commit_b.id(), commit_b.id()));
+    assert!(!graph::is_ancestor(repo, commit_c.id(), commit_d.id()));
+
+    let heads = graph::heads(repo, [commit_b.id(), commit_c.id(), commit_a.id()]);
+    assert_eq!(
+        heads.into_iter().sorted().collect_vec(),
+        [commit_b.id().clone(), commit_c.id().clone()]
+            .into_iter()
+            .sorted()
+            .collect_vec()
+    );
+    assert_eq!(
+        graph::heads(repo, [commit_e.id(), commit_e.id(), commit_c.id()]),
+        vec![commit_e.id().clone()]
+    );
+
+    assert_eq!(
+        graph::common_ancestors(repo, &[commit_d.id().clone()], &[commit_c.id().clone()]),
+        vec![commit_a.id().clone()]
+    );
+
+    // Children are emitted before parents
+    let order = graph::topo_order(repo, &[commit_e.id().clone()])
+        .unwrap()
+        .collect_vec();
+    assert_eq!(order.len(), 6);
+    assert_eq!(order.first(), Some(commit_e.id()));
+    assert_eq!(order.last(), Some(&root_commit_id));
+    let position = |id| order.iter().position(|x| x == id).unwrap();
+    assert!(position(commit_d.id()) < position(commit_b.id()));
+    assert!(position(commit_b.id()) < position(commit_a.id()));
+    assert!(position(commit_c.id()) < position(commit_a.id()));
+
+    assert_eq!(
+        graph::range(repo, &[commit_b.id().clone()], &[commit_e.id().clone()])
+            .unwrap()
+            .sorted()
+            .collect_vec(),
+        [
+            commit_c.id().clone(),
+            commit_d.id().clone(),
+            commit_e.id().clone()
+        ]
+        .into_iter()
+        .sorted()
+        .collect_vec()
+    );
+    assert_eq!(
+        graph::range(repo, &[commit_e.id().clone()], &[commit_c.id().clone()])
+            .unwrap()
+            .count(),
+        0
+    );
+
+    assert_eq!(
+        graph::ahead_behind(repo, &[commit_d.id().clone()], &[commit_c.id().clone()]).unwrap(),
+        (2, 1)
+    );
+    assert_eq!(
+        graph::ahead_behind(repo, &[commit_a.id().clone()], &[commit_e.id().clone()]).unwrap(),
+        (0, 4)
+    );
+}
+
+#[test]
+fn test_last_modifying_commit() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let file1 = RepoPath::from_internal_string("file1");
+    let file2 = RepoPath::from_internal_string("file2");
+    let missing = RepoPath::from_internal_string("missing");
+    let tree1 = create_tree(repo, &[(file1, "1"), (file2, "1")]);
+    let tree2 = create_tree(repo, &[(file1, "2"), (file2, "1")]);
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+    let commit1 = mut_repo
+        .new_commit(
+            &settings,
+            vec![repo.store().root_commit_id().clone()],
+            tree1.id(),
+        )
+        .write()
+        .unwrap();
+    let commit2 = mut_repo
+        .new_commit(&settings, vec![commit1.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let commit3 = mut_repo
+        .new_commit(&settings, vec![commit2.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+    let repo = repo.as_ref();
+
+    assert_eq!(
+        graph::last_modifying_commit(repo, commit3.id(), file1).unwrap(),
+        Some(commit2.id().clone())
+    );
+    assert_eq!(
+        graph::last_modifying_commit(repo, commit3.id(), file2).unwrap(),
+        Some(commit1.id().clone())
+    );
+    assert_eq!(
+        graph::last_modifying_commit(repo, commit1.id(), file1).unwrap(),
+        Some(commit1.id().clone())
+    );
+    assert_eq!(
+        graph::last_modifying_commit(repo, commit3.id(), missing).unwrap(),
+        None
+    );
+}
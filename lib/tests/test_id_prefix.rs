@@ -54,7 +54,7 @@ fn test_id_prefix() {
     for _ in 0..25 {
         commits.push(create_commit(commits.last().unwrap().id()));
     }
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // Print the commit IDs and change IDs for reference
     let commit_prefixes = commits
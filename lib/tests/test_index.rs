@@ -94,7 +94,7 @@ fn test_index_commits_standard_cases() {
     let commit_f = graph_builder.commit_with_parents(&[&commit_b, &commit_e]);
     let commit_g = graph_builder.commit_with_parents(&[&commit_f]);
     let commit_h = graph_builder.commit_with_parents(&[&commit_e]);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let index = as_readonly_composite(&repo);
     // There should be the root commit, plus 8 more
@@ -153,7 +153,7 @@ fn test_index_commits_criss_cross() {
         left_commits.push(new_left);
         right_commits.push(new_right);
     }
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let index = as_readonly_composite(&repo);
     // There should the root commit, plus 2 for each generation
@@ -300,11 +300,11 @@ fn test_index_commits_previous_operations() {
     let commit_a = graph_builder.initial_commit();
     let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
     let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     tx.mut_repo().remove_head(commit_c.id());
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // Delete index from disk
     let default_index_store: &DefaultIndexStore =
@@ -355,7 +355,7 @@ fn test_index_commits_hidden_but_referenced() {
             state: jj_lib::op_store::RemoteRefState::New,
         },
     );
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // All commits should be indexed
     assert!(repo.index().has_id(commit_a.id()));
@@ -394,7 +394,7 @@ fn test_index_commits_incremental() {
     let commit_a = child_commit(tx.mut_repo(), &settings, &root_commit)
         .write()
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let index = as_readonly_composite(&repo);
     // There should be the root commit, plus 1 more
@@ -407,7 +407,7 @@ fn test_index_commits_incremental() {
     let commit_c = child_commit(tx.mut_repo(), &settings, &commit_b)
         .write()
         .unwrap();
-    tx.commit("test");
+    tx.commit("test").unwrap();
 
     let repo = load_repo_at_head(&settings, repo.repo_path());
     let index = as_readonly_composite(&repo);
@@ -445,13 +445,13 @@ fn test_index_commits_incremental_empty_transaction() {
     let commit_a = child_commit(tx.mut_repo(), &settings, &root_commit)
         .write()
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let index = as_readonly_composite(&repo);
     // There should be the root commit, plus 1 more
     assert_eq!(index.num_commits(), 1 + 1);
 
-    repo.start_transaction(&settings).commit("test");
+    repo.start_transaction(&settings).commit("test").unwrap();
 
     let repo = load_repo_at_head(&settings, repo.repo_path());
     let index = as_readonly_composite(&repo);
@@ -487,7 +487,7 @@ fn test_index_commits_incremental_already_indexed() {
     let commit_a = child_commit(tx.mut_repo(), &settings, &root_commit)
         .write()
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     assert!(repo.index().has_id(commit_a.id()));
     assert_eq!(as_readonly_composite(&repo).num_commits(), 1 + 1);
@@ -507,7 +507,7 @@ fn create_n_commits(
     for _ in 0..num_commits {
         write_random_commit(tx.mut_repo(), settings);
     }
-    tx.commit("test")
+    tx.commit("test").unwrap()
 }
 
 fn as_readonly_composite(repo: &Arc<ReadonlyRepo>) -> &CompositeIndex {
@@ -606,7 +606,7 @@ fn test_reindex_no_segments_dir() {
 
     let mut tx = repo.start_transaction(&settings);
     let commit_a = write_random_commit(tx.mut_repo(), &settings);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     assert!(repo.index().has_id(commit_a.id()));
 
     // jj <= 0.14 doesn't have "segments" directory
@@ -626,7 +626,7 @@ fn test_reindex_corrupt_segment_files() {
 
     let mut tx = repo.start_transaction(&settings);
     let commit_a = write_random_commit(tx.mut_repo(), &settings);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     assert!(repo.index().has_id(commit_a.id()));
 
     // Corrupt the index files
@@ -664,7 +664,7 @@ fn test_reindex_from_merged_operation() {
     for _ in 0..2 {
         let mut tx = repo.start_transaction(&settings);
         let commit = write_random_commit(tx.mut_repo(), &settings);
-        let repo = tx.commit("test");
+        let repo = tx.commit("test").unwrap();
         let mut tx = repo.start_transaction(&settings);
         tx.mut_repo().remove_head(commit.id());
         txs.push(tx);
@@ -674,7 +674,7 @@ fn test_reindex_from_merged_operation() {
     op_ids_to_delete.push(repo.op_id());
     let mut tx = repo.start_transaction(&settings);
     write_random_commit(tx.mut_repo(), &settings);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     op_ids_to_delete.push(repo.op_id());
     let operation_to_reload = repo.operation();
 
@@ -703,12 +703,12 @@ fn test_reindex_missing_commit() {
 
     let mut tx = repo.start_transaction(&settings);
     let missing_commit = write_random_commit(tx.mut_repo(), &settings);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
     let bad_op_id = repo.op_id();
 
     let mut tx = repo.start_transaction(&settings);
     tx.mut_repo().remove_head(missing_commit.id());
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // Remove historical head commit to simulate bad GC.
     let test_backend: &TestBackend = repo.store().backend_impl().downcast_ref().unwrap();
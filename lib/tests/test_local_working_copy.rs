@@ -18,10 +18,12 @@ use std::os::unix::fs::PermissionsExt;
 use std::os::unix::net::UnixListener;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use itertools::Itertools;
-use jj_lib::backend::{MergedTreeId, TreeId, TreeValue};
+use jj_lib::backend::{MergedTreeId, MillisSinceEpoch, TreeId, TreeValue};
 use jj_lib::file_util::{check_symlink_support, try_symlink};
+use jj_lib::fingerprint_cache::{FileFingerprint, FileFingerprintCache};
 use jj_lib::fsmonitor::FsmonitorKind;
 use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::merge::Merge;
@@ -31,7 +33,7 @@ use jj_lib::repo::{ReadonlyRepo, Repo};
 use jj_lib::repo_path::{RepoPath, RepoPathBuf, RepoPathComponent};
 use jj_lib::settings::UserSettings;
 use jj_lib::working_copy::{CheckoutStats, SnapshotError, SnapshotOptions};
-use jj_lib::workspace::LockedWorkspace;
+use jj_lib::workspace::{default_working_copy_factory, LockedWorkspace, Workspace};
 use test_case::test_case;
 use testutils::{
     commit_with_tree, create_tree, write_random_commit, TestRepoBackend, TestWorkspace,
@@ -155,7 +157,7 @@ fn test_checkout_file_transitions(backend: TestRepoBackend) {
             Kind::GitSubmodule => {
                 let mut tx = repo.start_transaction(settings);
                 let id = write_random_commit(tx.mut_repo(), settings).id().clone();
-                tx.commit("test");
+                tx.commit("test").unwrap();
                 Merge::normal(TreeValue::GitSubmodule(id))
             }
         };
@@ -528,6 +530,142 @@ fn test_snapshot_racy_timestamps() {
     }
 }
 
+#[cfg(unix)]
+#[test]
+fn test_snapshot_ignore_executable_bit() {
+    // Tests that changes to the executable bit on disk can be ignored.
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let workspace_root = test_workspace.workspace.workspace_root().clone();
+    let file_path = RepoPath::from_internal_string("file");
+    let disk_path = file_path.to_fs_path(&workspace_root);
+    std::fs::write(&disk_path, "contents").unwrap();
+    let tree = test_workspace.snapshot().unwrap();
+
+    let mut snapshot = |ignore_executable_bit: bool| {
+        let mut locked_ws = test_workspace
+            .workspace
+            .start_working_copy_mutation()
+            .unwrap();
+        let tree_id = locked_ws
+            .locked_wc()
+            .snapshot(SnapshotOptions {
+                ignore_executable_bit,
+                ..SnapshotOptions::empty_for_test()
+            })
+            .unwrap();
+        locked_ws.finish(OperationId::from_hex("abc123")).unwrap();
+        tree_id
+    };
+    std::fs::set_permissions(&disk_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    assert_eq!(snapshot(true), tree.id());
+    assert_ne!(snapshot(false), tree.id());
+}
+
+#[test]
+fn test_snapshot_fingerprint_cache() {
+    // Tests that files matching a cached fingerprint aren't read again.
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let workspace_root = test_workspace.workspace.workspace_root().clone();
+    let repo_path = test_workspace.workspace.repo_path().clone();
+    let store = test_workspace.repo.store().clone();
+    let file_path = RepoPath::from_internal_string("file");
+    let disk_path = file_path.to_fs_path(&workspace_root);
+    std::fs::write(&disk_path, "contents").unwrap();
+    let mtime = disk_path.metadata().unwrap().modified().unwrap();
+    let mtime = mtime.duration_since(std::time::UNIX_EPOCH).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(10));
+
+    // Pretend that another workspace recorded different contents for the file
+    let cached_id = testutils::write_file(&store, file_path, "cached contents");
+    let fingerprint = FileFingerprint {
+        path: file_path.to_owned(),
+        size: 8,
+        mtime: MillisSinceEpoch(mtime.as_millis() as i64),
+    };
+    let cache = FileFingerprintCache::load(&repo_path);
+    cache.insert(fingerprint, cached_id.clone());
+    cache.save().unwrap();
+
+    let cache = FileFingerprintCache::load(&repo_path);
+    let mut locked_ws = test_workspace
+        .workspace
+        .start_working_copy_mutation()
+        .unwrap();
+    let tree_id = locked_ws
+        .locked_wc()
+        .snapshot(SnapshotOptions {
+            fingerprint_cache: Some(&cache),
+            ..SnapshotOptions::empty_for_test()
+        })
+        .unwrap();
+    locked_ws.finish(OperationId::from_hex("abc123")).unwrap();
+    let tree = store.get_root_tree(&tree_id).unwrap();
+    assert_eq!(
+        tree.path_value(file_path).into_resolved(),
+        Ok(Some(TreeValue::File {
+            id: cached_id,
+            executable: false
+        }))
+    );
+}
+
+#[test]
+fn test_snapshot_fingerprint_cache_across_workspaces() {
+    // Tests that a file recorded by one workspace isn't read again when it
+    // appears in another workspace with the same path, size, and mtime.
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let repo_path = test_workspace.workspace.repo_path().clone();
+    let workspace1_root = test_workspace.workspace.workspace_root().clone();
+    let workspace2_root = test_workspace.root_dir().join("workspace2");
+    std::fs::create_dir(&workspace2_root).unwrap();
+    let (mut workspace2, _repo) = Workspace::init_workspace_with_existing_repo(
+        &settings,
+        &workspace2_root,
+        &test_workspace.repo,
+        &*default_working_copy_factory(),
+        WorkspaceId::new("workspace2".to_string()),
+    )
+    .unwrap();
+
+    let snapshot = |workspace: &mut Workspace| {
+        let cache = FileFingerprintCache::load(&repo_path);
+        let mut locked_ws = workspace.start_working_copy_mutation().unwrap();
+        let tree_id = locked_ws
+            .locked_wc()
+            .snapshot(SnapshotOptions {
+                fingerprint_cache: Some(&cache),
+                ..SnapshotOptions::empty_for_test()
+            })
+            .unwrap();
+        locked_ws.finish(OperationId::from_hex("abc123")).unwrap();
+        cache.save().unwrap();
+        tree_id
+    };
+    // The contents differ so we can tell that the second workspace used the
+    // cached file id instead of reading the file.
+    let file_path = RepoPath::from_internal_string("file");
+    let mtime = SystemTime::now() - Duration::from_secs(3600);
+    for (root, contents) in [
+        (&workspace1_root, "contents 1"),
+        (&workspace2_root, "contents 2"),
+    ] {
+        let disk_path = file_path.to_fs_path(root);
+        std::fs::write(&disk_path, contents).unwrap();
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&disk_path)
+            .unwrap();
+        file.set_modified(mtime).unwrap();
+    }
+
+    let tree_id1 = snapshot(&mut test_workspace.workspace);
+    let tree_id2 = snapshot(&mut workspace2);
+    assert_eq!(tree_id2, tree_id1);
+}
+
 #[cfg(unix)]
 #[test]
 fn test_snapshot_special_file() {
@@ -888,7 +1026,7 @@ fn test_gitsubmodule() {
 
     let mut tx = repo.start_transaction(&settings);
     let submodule_id = write_random_commit(tx.mut_repo(), &settings).id().clone();
-    tx.commit("create submodule commit");
+    tx.commit("create submodule commit").unwrap();
 
     tree_builder.set(
         submodule_path.to_owned(),
@@ -1236,6 +1236,58 @@ fn test_merge_partial_resolution() {
     assert_eq!(merged, expected_merged);
 }
 
+/// Merge 3 resolved trees with many independently changed subtrees, some of
+/// which conflict
+#[test]
+fn test_merge_many_subtrees() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let paths = (0..20).map(|i| format!("dir{i}/subdir/file")).collect_vec();
+    let paths = paths
+        .iter()
+        .map(|path| RepoPath::from_internal_string(path))
+        .collect_vec();
+    let tree_with = |content_fn: &dyn Fn(usize) -> &'static str| {
+        let path_contents = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| (*path, content_fn(i)))
+            .collect_vec();
+        create_single_tree(repo, &path_contents)
+    };
+    // Every third file is changed on both sides, the others only on one side
+    let base1 = tree_with(&|_| "base");
+    let side1 = tree_with(&|i| {
+        if i % 2 == 0 || i % 3 == 0 {
+            "side1"
+        } else {
+            "base"
+        }
+    });
+    let side2 = tree_with(&|i| {
+        if i % 2 == 1 || i % 3 == 0 {
+            "side2"
+        } else {
+            "base"
+        }
+    });
+    let resolved = |i: usize| if i % 2 == 0 { "side1" } else { "side2" };
+    let expected_base1 = tree_with(&|i| if i % 3 == 0 { "base" } else { resolved(i) });
+    let expected_side1 = tree_with(&|i| if i % 3 == 0 { "side1" } else { resolved(i) });
+    let expected_side2 = tree_with(&|i| if i % 3 == 0 { "side2" } else { resolved(i) });
+    let base1_merged = MergedTree::new(Merge::resolved(base1));
+    let side1_merged = MergedTree::new(Merge::resolved(side1));
+    let side2_merged = MergedTree::new(Merge::resolved(side2));
+    let expected_merged = MergedTree::new(Merge::from_removes_adds(
+        vec![expected_base1],
+        vec![expected_side1, expected_side2],
+    ));
+
+    let merged = side1_merged.merge(&base1_merged, &side2_merged).unwrap();
+    assert_eq!(merged, expected_merged);
+}
+
 /// Merge 3 resolved trees, including one empty legacy tree
 #[test]
 fn test_merge_with_empty_legacy_tree() {
@@ -48,11 +48,11 @@ fn test_unpublished_operation() {
 
     let mut tx1 = repo.start_transaction(&settings);
     write_random_commit(tx1.mut_repo(), &settings);
-    let unpublished_op = tx1.write("transaction 1");
+    let unpublished_op = tx1.write("transaction 1").unwrap();
     let op_id1 = unpublished_op.operation().id().clone();
     assert_ne!(op_id1, op_id0);
     assert_eq!(list_dir(&op_heads_dir), vec![op_id0.hex()]);
-    unpublished_op.publish();
+    unpublished_op.publish().unwrap();
     assert_eq!(list_dir(&op_heads_dir), vec![op_id1.hex()]);
 }
 
@@ -70,14 +70,24 @@ fn test_consecutive_operations() {
 
     let mut tx1 = repo.start_transaction(&settings);
     write_random_commit(tx1.mut_repo(), &settings);
-    let op_id1 = tx1.commit("transaction 1").operation().id().clone();
+    let op_id1 = tx1
+        .commit("transaction 1")
+        .unwrap()
+        .operation()
+        .id()
+        .clone();
     assert_ne!(op_id1, op_id0);
     assert_eq!(list_dir(&op_heads_dir), vec![op_id1.hex()]);
 
     let repo = repo.reload_at_head(&settings).unwrap();
     let mut tx2 = repo.start_transaction(&settings);
     write_random_commit(tx2.mut_repo(), &settings);
-    let op_id2 = tx2.commit("transaction 2").operation().id().clone();
+    let op_id2 = tx2
+        .commit("transaction 2")
+        .unwrap()
+        .operation()
+        .id()
+        .clone();
     assert_ne!(op_id2, op_id0);
     assert_ne!(op_id2, op_id1);
     assert_eq!(list_dir(&op_heads_dir), vec![op_id2.hex()]);
@@ -102,7 +112,12 @@ fn test_concurrent_operations() {
 
     let mut tx1 = repo.start_transaction(&settings);
     write_random_commit(tx1.mut_repo(), &settings);
-    let op_id1 = tx1.commit("transaction 1").operation().id().clone();
+    let op_id1 = tx1
+        .commit("transaction 1")
+        .unwrap()
+        .operation()
+        .id()
+        .clone();
     assert_ne!(op_id1, op_id0);
     assert_eq!(list_dir(&op_heads_dir), vec![op_id1.hex()]);
 
@@ -110,7 +125,12 @@ fn test_concurrent_operations() {
     // since they were run in parallel.
     let mut tx2 = repo.start_transaction(&settings);
     write_random_commit(tx2.mut_repo(), &settings);
-    let op_id2 = tx2.commit("transaction 2").operation().id().clone();
+    let op_id2 = tx2
+        .commit("transaction 2")
+        .unwrap()
+        .operation()
+        .id()
+        .clone();
     assert_ne!(op_id2, op_id0);
     assert_ne!(op_id2, op_id1);
     let mut actual_heads_on_disk = list_dir(&op_heads_dir);
@@ -128,6 +148,58 @@ fn test_concurrent_operations() {
     assert_eq!(list_dir(&op_heads_dir), vec![merged_op_id.hex()]);
 }
 
+#[test]
+fn test_merge_concurrent_operations() {
+    // Test that a transaction can be committed on top of an operation which was
+    // published after it started, instead of creating divergent op-heads.
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let op_heads_dir = repo.repo_path().join("op_heads").join("heads");
+    let op_id0 = repo.op_id().clone();
+
+    let mut tx1 = repo.start_transaction(&settings);
+    let commit1 = write_random_commit(tx1.mut_repo(), &settings);
+    let mut tx2 = repo.start_transaction(&settings);
+    let commit2 = write_random_commit(tx2.mut_repo(), &settings);
+    let op_id1 = tx1
+        .commit("transaction 1")
+        .unwrap()
+        .operation()
+        .id()
+        .clone();
+
+    let merged_ops = tx2.merge_concurrent_operations(&settings, 3).unwrap();
+    assert_eq!(merged_ops.iter().map(|op| op.id()).collect_vec(), [&op_id1]);
+    // Nothing new to merge
+    assert!(tx2
+        .merge_concurrent_operations(&settings, 3)
+        .unwrap()
+        .is_empty());
+    let repo2 = tx2.commit("transaction 2").unwrap();
+    assert_eq!(repo2.operation().parent_ids(), [op_id1.clone()]);
+    assert_eq!(list_dir(&op_heads_dir), vec![repo2.op_id().hex()]);
+    assert_heads(repo2.as_ref(), vec![commit1.id(), commit2.id()]);
+
+    // No attempts means the operations are left divergent
+    let mut tx3 = repo.start_transaction(&settings);
+    write_random_commit(tx3.mut_repo(), &settings);
+    assert!(tx3
+        .merge_concurrent_operations(&settings, 0)
+        .unwrap()
+        .is_empty());
+    let repo3 = tx3.commit("transaction 3").unwrap();
+    assert_eq!(repo3.operation().parent_ids(), [op_id0]);
+    assert_eq!(
+        list_dir(&op_heads_dir),
+        [repo2.op_id().hex(), repo3.op_id().hex()]
+            .into_iter()
+            .sorted()
+            .collect_vec()
+    );
+}
+
 fn assert_heads(repo: &dyn Repo, expected: Vec<&CommitId>) {
     let expected = expected.iter().cloned().cloned().collect();
     assert_eq!(*repo.view().heads(), expected);
@@ -145,7 +217,7 @@ fn test_isolation() {
         .set_parents(vec![repo.store().root_commit_id().clone()])
         .write()
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     let mut_repo1 = tx1.mut_repo();
@@ -176,12 +248,12 @@ fn test_isolation() {
     assert_heads(mut_repo2, vec![rewrite2.id()]);
 
     // The base repo and tx2 don't see the commits from tx1.
-    tx1.commit("transaction 1");
+    tx1.commit("transaction 1").unwrap();
     assert_heads(repo.as_ref(), vec![initial.id()]);
     assert_heads(mut_repo2, vec![rewrite2.id()]);
 
     // The base repo still doesn't see the commits after both transactions commit.
-    tx2.commit("transaction 2");
+    tx2.commit("transaction 2").unwrap();
     assert_heads(repo.as_ref(), vec![initial.id()]);
     // After reload, the base repo sees both rewrites.
     let repo = repo.reload_at_head(&settings).unwrap();
@@ -216,10 +288,10 @@ fn test_reparent_range_linear() {
         write_random_commit(tx.mut_repo(), &settings);
         tx
     };
-    let repo_a = random_tx(&repo_0).commit("op A");
-    let repo_b = random_tx(&repo_a).commit("op B");
-    let repo_c = random_tx(&repo_b).commit("op C");
-    let repo_d = random_tx(&repo_c).commit("op D");
+    let repo_a = random_tx(&repo_0).commit("op A").unwrap();
+    let repo_b = random_tx(&repo_a).commit("op B").unwrap();
+    let repo_c = random_tx(&repo_b).commit("op C").unwrap();
+    let repo_d = random_tx(&repo_c).commit("op D").unwrap();
 
     // Reparent B..D (=C|D) onto A:
     // D'
@@ -290,10 +362,10 @@ fn test_reparent_range_branchy() {
         write_random_commit(tx.mut_repo(), &settings);
         tx
     };
-    let repo_a = random_tx(&repo_0).commit("op A");
-    let repo_b = random_tx(&repo_a).commit("op B");
-    let repo_c = random_tx(&repo_b).commit("op C");
-    let repo_d = random_tx(&repo_c).commit("op D");
+    let repo_a = random_tx(&repo_0).commit("op A").unwrap();
+    let repo_b = random_tx(&repo_a).commit("op B").unwrap();
+    let repo_c = random_tx(&repo_b).commit("op C").unwrap();
+    let repo_d = random_tx(&repo_c).commit("op D").unwrap();
     let tx_e = random_tx(&repo_d);
     let tx_f = random_tx(&repo_c);
     let repo_g = testutils::commit_transactions(&settings, vec![tx_e, tx_f]);
@@ -399,6 +471,79 @@ fn test_reparent_range_branchy() {
     assert_eq!(new_op_f.parent_ids(), slice::from_ref(repo_d.op_id()));
 }
 
+#[test]
+fn test_compact_snapshots() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo_0 = test_repo.repo;
+    let op_store = repo_0.op_store();
+
+    let read_op = |id| {
+        let data = op_store.read_operation(id).unwrap();
+        Operation::new(op_store.clone(), id.clone(), data)
+    };
+
+    fn op_parents<const N: usize>(op: &Operation) -> [Operation; N] {
+        let parents: Vec<_> = op.parents().try_collect().unwrap();
+        parents.try_into().unwrap()
+    }
+
+    // Set up linear operation graph (S* are snapshot operations):
+    // S4
+    // B
+    // S3
+    // S2
+    // S1
+    // A
+    // 0 (initial)
+    let random_tx = |repo: &Arc<ReadonlyRepo>, is_snapshot: bool| {
+        let mut tx = repo.start_transaction(&settings);
+        tx.set_is_snapshot(is_snapshot);
+        write_random_commit(tx.mut_repo(), &settings);
+        tx
+    };
+    let repo_a = random_tx(&repo_0, false).commit("op A").unwrap();
+    let repo_s1 = random_tx(&repo_a, true).commit("snapshot 1").unwrap();
+    let repo_s2 = random_tx(&repo_s1, true).commit("snapshot 2").unwrap();
+    let repo_s3 = random_tx(&repo_s2, true).commit("snapshot 3").unwrap();
+    let repo_b = random_tx(&repo_s3, false).commit("op B").unwrap();
+    let repo_s4 = random_tx(&repo_b, true).commit("snapshot 4").unwrap();
+
+    // Fold S1|S2 into S3:
+    // S4'
+    // B'
+    // S3'
+    // A
+    // 0 (initial)
+    let stats = op_walk::compact_snapshots(op_store.as_ref(), slice::from_ref(repo_s4.operation()))
+        .unwrap();
+    assert_eq!(stats.new_head_ids.len(), 1);
+    assert_eq!(stats.rewritten_count, 3);
+    assert_eq!(stats.unreachable_count, 2);
+    let new_op_s4 = read_op(&stats.new_head_ids[0]);
+    assert_eq!(new_op_s4.metadata(), repo_s4.operation().metadata());
+    assert_eq!(new_op_s4.view_id(), repo_s4.operation().view_id());
+    let [new_op_b] = op_parents(&new_op_s4);
+    assert_eq!(new_op_b.metadata(), repo_b.operation().metadata());
+    let [new_op_s3] = op_parents(&new_op_b);
+    assert_eq!(new_op_s3.view_id(), repo_s3.operation().view_id());
+    assert_eq!(
+        new_op_s3.metadata().description,
+        repo_s3.operation().metadata().description
+    );
+    assert_eq!(
+        new_op_s3.metadata().start_time,
+        repo_s1.operation().metadata().start_time
+    );
+    assert_eq!(new_op_s3.parent_ids(), slice::from_ref(repo_a.op_id()));
+
+    // Nothing to fold
+    let stats = op_walk::compact_snapshots(op_store.as_ref(), slice::from_ref(&new_op_s4)).unwrap();
+    assert_eq!(stats.new_head_ids, vec![new_op_s4.id().clone()]);
+    assert_eq!(stats.rewritten_count, 0);
+    assert_eq!(stats.unreachable_count, 0);
+}
+
 fn stable_op_id_settings() -> UserSettings {
     UserSettings::from_config(
         testutils::base_config()
@@ -423,7 +568,7 @@ fn test_resolve_op_id() {
     // up with hashes with ambiguous prefixes.
     for i in (1..7).chain([16]) {
         let tx = repo.start_transaction(&settings);
-        let repo = tx.commit(format!("transaction {i}"));
+        let repo = tx.commit(format!("transaction {i}")).unwrap();
         operations.push(repo.operation().clone());
     }
     // "2" and "0" are ambiguous
@@ -514,7 +659,7 @@ fn test_resolve_op_parents_children() {
     let mut repos = Vec::new();
     for _ in 0..3 {
         let tx = repo.start_transaction(&settings);
-        repos.push(tx.commit("test"));
+        repos.push(tx.commit("test").unwrap());
         repo = repos.last().unwrap();
     }
     let operations = repos.iter().map(|repo| repo.operation()).collect_vec();
@@ -592,6 +737,70 @@ fn test_resolve_op_parents_children() {
     );
 }
 
+#[test]
+fn test_find_unreachable_operations() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo_0 = test_repo.repo;
+    let op_store = repo_0.op_store();
+
+    // Set up operation graph:
+    //
+    //   E
+    // D |
+    // C |
+    // |/
+    // B
+    // A
+    // 0 (initial)
+    let random_tx = |repo: &Arc<ReadonlyRepo>| {
+        let mut tx = repo.start_transaction(&settings);
+        write_random_commit(tx.mut_repo(), &settings);
+        tx
+    };
+    let repo_a = random_tx(&repo_0).commit("op A").unwrap();
+    let repo_b = random_tx(&repo_a).commit("op B").unwrap();
+    let repo_c = random_tx(&repo_b).commit("op C").unwrap();
+    let repo_d = random_tx(&repo_c).commit("op D").unwrap();
+    let repo_e = random_tx(&repo_b).commit("op E").unwrap();
+
+    let find_unreachable_ids = |head_ops: &[Operation]| {
+        op_walk::find_unreachable_operations(op_store, head_ops)
+            .unwrap()
+            .iter()
+            .map(|op| op.id().clone())
+            .collect_vec()
+    };
+
+    // Everything is reachable from the heads
+    assert_eq!(
+        find_unreachable_ids(&[repo_d.operation().clone(), repo_e.operation().clone()]),
+        vec![]
+    );
+
+    // C|D are no longer reachable, children first
+    assert_eq!(
+        find_unreachable_ids(slice::from_ref(repo_e.operation())),
+        vec![repo_d.op_id().clone(), repo_c.op_id().clone()]
+    );
+
+    // B|C|D|E are no longer reachable, and B is emitted after its children
+    let unreachable_ids = find_unreachable_ids(slice::from_ref(repo_a.operation()));
+    assert_eq!(
+        unreachable_ids.iter().sorted().collect_vec(),
+        [
+            repo_b.op_id(),
+            repo_c.op_id(),
+            repo_d.op_id(),
+            repo_e.op_id()
+        ]
+        .into_iter()
+        .sorted()
+        .collect_vec()
+    );
+    assert_eq!(unreachable_ids.last(), Some(repo_b.op_id()));
+}
+
 #[test]
 fn test_gc() {
     let settings = stable_op_id_settings();
@@ -617,12 +826,12 @@ fn test_gc() {
         write_random_commit(tx.mut_repo(), &settings);
         tx
     };
-    let repo_a = random_tx(&repo_0).commit("op A");
-    let repo_b = random_tx(&repo_a).commit("op B");
-    let repo_c = random_tx(&repo_b).commit("op C");
-    let repo_d = random_tx(&repo_c).commit("op D");
-    let repo_e = empty_tx(&repo_b).commit("op E");
-    let repo_f = random_tx(&repo_e).commit("op F");
+    let repo_a = random_tx(&repo_0).commit("op A").unwrap();
+    let repo_b = random_tx(&repo_a).commit("op B").unwrap();
+    let repo_c = random_tx(&repo_b).commit("op C").unwrap();
+    let repo_d = random_tx(&repo_c).commit("op D").unwrap();
+    let repo_e = empty_tx(&repo_b).commit("op E").unwrap();
+    let repo_f = random_tx(&repo_e).commit("op F").unwrap();
 
     // Sanity check for the original state
     let mut expected_op_entries = list_dir(&op_dir);
@@ -41,7 +41,7 @@ fn test_merge_ref_targets() {
     let commit5 = graph_builder.commit_with_parents(&[&commit1]);
     let commit6 = graph_builder.commit_with_parents(&[&commit5]);
     let commit7 = graph_builder.commit_with_parents(&[&commit5]);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let target1 = RefTarget::normal(commit1.id().clone());
     let target2 = RefTarget::normal(commit2.id().clone());
@@ -20,6 +20,7 @@ use jj_lib::backend::{CommitId, MillisSinceEpoch, Signature, Timestamp};
 use jj_lib::commit::Commit;
 use jj_lib::git;
 use jj_lib::git_backend::GitBackend;
+use jj_lib::mailmap::Mailmap;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::{RefTarget, RemoteRef, RemoteRefState, WorkspaceId};
 use jj_lib::repo::Repo;
@@ -42,6 +43,7 @@ fn resolve_symbol(repo: &dyn Repo, symbol: &str) -> Result<Vec<CommitId>, Revset
     let context = RevsetParseContext {
         aliases_map: &RevsetAliasesMap::new(),
         user_email: String::new(),
+        mailmap: &Mailmap::empty(),
         workspace: None,
     };
     let expression = parse(symbol, &context).unwrap();
@@ -109,7 +111,7 @@ fn test_resolve_symbol_commit_id() {
             .unwrap();
         commits.push(commit);
     }
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // Test the test setup
     assert_eq!(
@@ -170,6 +172,7 @@ fn test_resolve_symbol_commit_id() {
     let context = RevsetParseContext {
         aliases_map: &RevsetAliasesMap::new(),
         user_email: settings.user_email(),
+        mailmap: &Mailmap::empty(),
         workspace: None,
     };
     assert_matches!(
@@ -255,7 +258,7 @@ fn test_resolve_symbol_change_id(readonly: bool) {
 
     let _readonly_repo;
     let repo: &dyn Repo = if readonly {
-        _readonly_repo = tx.commit("test");
+        _readonly_repo = tx.commit("test").unwrap();
         _readonly_repo.as_ref()
     } else {
         tx.mut_repo()
@@ -788,10 +791,19 @@ fn test_resolve_symbol_git_refs() {
 }
 
 fn resolve_commit_ids(repo: &dyn Repo, revset_str: &str) -> Vec<CommitId> {
+    resolve_commit_ids_with_mailmap(repo, revset_str, &Mailmap::empty())
+}
+
+fn resolve_commit_ids_with_mailmap(
+    repo: &dyn Repo,
+    revset_str: &str,
+    mailmap: &Mailmap,
+) -> Vec<CommitId> {
     let settings = testutils::user_settings();
     let context = RevsetParseContext {
         aliases_map: &RevsetAliasesMap::new(),
         user_email: settings.user_email(),
+        mailmap,
         workspace: None,
     };
     let expression = optimize(parse(revset_str, &context).unwrap());
@@ -817,6 +829,7 @@ fn resolve_commit_ids_in_workspace(
     let context = RevsetParseContext {
         aliases_map: &RevsetAliasesMap::new(),
         user_email: settings.user_email(),
+        mailmap: &Mailmap::empty(),
         workspace: Some(workspace_ctx),
     };
     let expression = optimize(parse(revset_str, &context).unwrap());
@@ -2252,6 +2265,82 @@ fn test_evaluate_expression_mine() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_by() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+
+    let signature = |name: &str, email: &str| Signature {
+        name: name.to_string(),
+        email: email.to_string(),
+        timestamp: Timestamp {
+            timestamp: MillisSinceEpoch(0),
+            tz_offset: 0,
+        },
+    };
+    let commit1 = create_random_commit(mut_repo, &settings)
+        .set_author(signature("alice", "alice@example.com"))
+        .set_committer(signature("bob", "bob@example.com"))
+        .write()
+        .unwrap();
+    let commit2 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit1.id().clone()])
+        .set_author(signature("bob", "bob@example.com"))
+        .set_committer(signature("alice", "alice@example.com"))
+        .write()
+        .unwrap();
+    let commit3 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit2.id().clone()])
+        .set_author(signature("root", "root@localhost"))
+        .set_committer(signature("root", "root@localhost"))
+        .write()
+        .unwrap();
+    let commit4 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit3.id().clone()])
+        .set_author(signature("test", "old.test.user@example.com"))
+        .set_committer(signature("bob", "bob@example.com"))
+        .write()
+        .unwrap();
+
+    // Matches author or committer
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "by(alice)"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "by(glob:\"alice@*\")"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(resolve_commit_ids(mut_repo, "mine()"), vec![]);
+
+    // Aliases in mailmap are resolved to the canonical identity. Emails are
+    // compared case-insensitively.
+    let mailmap = Mailmap::parse(
+        "Alice <alice@example.com> <Root@LocalHost>\n<Test.User@example.com> \
+         <OLD.test.user@example.com>\n",
+    );
+    assert_eq!(
+        resolve_commit_ids_with_mailmap(mut_repo, "by(glob:\"alice@*\")", &mailmap),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone()
+        ]
+    );
+    assert_eq!(
+        resolve_commit_ids_with_mailmap(mut_repo, "by(exact:Alice)", &mailmap),
+        vec![commit3.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids_with_mailmap(mut_repo, "mine()", &mailmap),
+        vec![commit4.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_committer() {
     let settings = testutils::user_settings();
@@ -2428,6 +2517,57 @@ fn test_evaluate_expression_intersection() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_common_subexpressions() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+    let mut graph_builder = CommitGraphBuilder::new(&settings, mut_repo);
+    let commit1 = graph_builder.initial_commit();
+    let commit2 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit3 = graph_builder.commit_with_parents(&[&commit2]);
+    let commit4 = graph_builder.commit_with_parents(&[&commit1]);
+
+    // The repeated ancestors set is evaluated once, and reused by the
+    // intersection, the difference, and the filter.
+    let ancestors = format!("::{}", commit3.id().hex());
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "heads({ancestors}) | ({ancestors} & {}::)",
+                commit2.id().hex()
+            )
+        ),
+        vec![commit3.id().clone(), commit2.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "({ancestors} | {}) ~ (({ancestors}) & description(nothing))",
+                commit4.id().hex()
+            )
+        ),
+        vec![
+            commit4.id().clone(),
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone(),
+            repo.store().root_commit_id().clone(),
+        ]
+    );
+
+    // The cheaper empty set is evaluated first
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("none() & {ancestors}")),
+        vec![]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_difference() {
     let settings = testutils::user_settings();
@@ -2750,7 +2890,7 @@ fn test_reverse_graph_iterator() {
     let commit_d = graph_builder.commit_with_parents(&[&commit_c]);
     let commit_e = graph_builder.commit_with_parents(&[&commit_c]);
     let commit_f = graph_builder.commit_with_parents(&[&commit_d, &commit_e]);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let revset = revset_for_commits(
         repo.as_ref(),
@@ -2819,7 +2959,7 @@ fn test_revset_containing_fn() {
     let commit_b = write_random_commit(mut_repo, &settings);
     let commit_c = write_random_commit(mut_repo, &settings);
     let commit_d = write_random_commit(mut_repo, &settings);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let revset = revset_for_commits(repo.as_ref(), &[&commit_b, &commit_d]);
 
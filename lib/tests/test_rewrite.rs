@@ -19,7 +19,9 @@ use jj_lib::merged_tree::MergedTree;
 use jj_lib::op_store::{RefTarget, RemoteRef, RemoteRefState, WorkspaceId};
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
-use jj_lib::rewrite::{rebase_commit_with_options, restore_tree, EmptyBehaviour, RebaseOptions};
+use jj_lib::rewrite::{
+    rebase_commit_with_options, restore_tree, EmptyBehaviour, RebaseOptions, RebasedCommit,
+};
 use maplit::{hashmap, hashset};
 use test_case::test_case;
 use testutils::{
@@ -979,7 +981,7 @@ fn test_rebase_descendants_basic_branch_update() {
     let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
     tx.mut_repo()
         .set_local_branch_target("main", RefTarget::normal(commit_b.id().clone()));
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let commit_b2 = tx
@@ -1022,7 +1024,7 @@ fn test_rebase_descendants_branch_move_two_steps() {
     let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
     tx.mut_repo()
         .set_local_branch_target("main", RefTarget::normal(commit_c.id().clone()));
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let commit_b2 = tx
@@ -1076,7 +1078,7 @@ fn test_rebase_descendants_basic_branch_update_with_non_local_branch() {
         .set_remote_branch("main", "origin", commit_b_remote_ref.clone());
     tx.mut_repo()
         .set_tag_target("v1", RefTarget::normal(commit_b.id().clone()));
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let commit_b2 = tx
@@ -1125,7 +1127,7 @@ fn test_rebase_descendants_update_branch_after_abandon() {
     let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
     tx.mut_repo()
         .set_local_branch_target("main", RefTarget::normal(commit_b.id().clone()));
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     tx.mut_repo().record_abandoned_commit(commit_b.id().clone());
@@ -1141,6 +1143,111 @@ fn test_rebase_descendants_update_branch_after_abandon() {
     );
 }
 
+#[test]
+fn test_rebase_descendants_update_pinned_branch_after_split() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Branches "main" and "pinned" point to commit B, and "pinned" is pinned to
+    // B's change. B is then split into B2 (which keeps the change id) and C,
+    // and the rewrite is recorded as B => C like `jj split` does. Branch main
+    // should be updated to point to C, and branch pinned should follow the
+    // change to B2.
+    //
+    //                C main
+    // B main pinned  |
+    // |         =>   B2 pinned
+    // A              |
+    //                A
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.mut_repo());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    tx.mut_repo()
+        .set_local_branch_target("main", RefTarget::normal(commit_b.id().clone()));
+    tx.mut_repo()
+        .set_local_branch_target("pinned", RefTarget::normal(commit_b.id().clone()));
+    tx.mut_repo()
+        .set_branch_pin("pinned", Some(commit_b.change_id().clone()));
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx = repo.start_transaction(&settings);
+    let commit_b2 = tx
+        .mut_repo()
+        .rewrite_commit(&settings, &commit_b)
+        .set_description("first part")
+        .write()
+        .unwrap();
+    let commit_c = tx
+        .mut_repo()
+        .rewrite_commit(&settings, &commit_b)
+        .set_parents(vec![commit_b2.id().clone()])
+        .generate_new_change_id()
+        .write()
+        .unwrap();
+    tx.mut_repo()
+        .set_rewritten_commit(commit_b.id().clone(), commit_c.id().clone());
+    tx.mut_repo().rebase_descendants(&settings).unwrap();
+    assert_eq!(
+        tx.mut_repo().get_local_branch("main"),
+        RefTarget::normal(commit_c.id().clone())
+    );
+    assert_eq!(
+        tx.mut_repo().get_local_branch("pinned"),
+        RefTarget::normal(commit_b2.id().clone())
+    );
+    assert_eq!(tx.mut_repo().get_branch_pin("main"), None);
+    assert_eq!(
+        tx.mut_repo().get_branch_pin("pinned"),
+        Some(commit_b.change_id().clone())
+    );
+
+    // Deleting the branch removes the pin
+    tx.mut_repo()
+        .set_local_branch_target("pinned", RefTarget::absent());
+    assert_eq!(tx.mut_repo().get_branch_pin("pinned"), None);
+}
+
+#[test]
+fn test_rebase_descendants_drop_pin_of_moved_branch() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Branch "pinned" is pinned to B's change, but has since been moved to A
+    // without updating the pin. When A is rewritten, B is only rebased, so
+    // it isn't rewritten from the branch's commit. The branch should follow A
+    // like an unpinned branch, and the pin should be dropped.
+    //
+    // B                  B2
+    // |                  |
+    // A pinned   =>      A2 pinned
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.mut_repo());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    tx.mut_repo()
+        .set_local_branch_target("pinned", RefTarget::normal(commit_a.id().clone()));
+    tx.mut_repo()
+        .set_branch_pin("pinned", Some(commit_b.change_id().clone()));
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx = repo.start_transaction(&settings);
+    let commit_a2 = tx
+        .mut_repo()
+        .rewrite_commit(&settings, &commit_a)
+        .set_description("rewritten")
+        .write()
+        .unwrap();
+    tx.mut_repo().rebase_descendants(&settings).unwrap();
+    assert_eq!(
+        tx.mut_repo().get_local_branch("pinned"),
+        RefTarget::normal(commit_a2.id().clone())
+    );
+    assert_eq!(tx.mut_repo().get_branch_pin("pinned"), None);
+}
+
 #[test]
 fn test_rebase_descendants_update_branches_after_divergent_rewrite() {
     let settings = testutils::user_settings();
@@ -1165,7 +1272,7 @@ fn test_rebase_descendants_update_branches_after_divergent_rewrite() {
         .set_local_branch_target("main", RefTarget::normal(commit_b.id().clone()));
     tx.mut_repo()
         .set_local_branch_target("other", RefTarget::normal(commit_c.id().clone()));
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let commit_b2 = tx
@@ -1247,7 +1354,7 @@ fn test_rebase_descendants_rewrite_updates_branch_conflict() {
             [commit_b.id().clone(), commit_c.id().clone()],
         ),
     );
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let commit_a2 = tx
@@ -1336,7 +1443,7 @@ fn test_rebase_descendants_rewrite_resolves_branch_conflict() {
             [commit_b.id().clone(), commit_c.id().clone()],
         ),
     );
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let commit_b2 = tx
@@ -1376,7 +1483,7 @@ fn test_rebase_descendants_branch_delete_modify_abandon() {
         "main",
         RefTarget::from_legacy_form([commit_a.id().clone()], [commit_b.id().clone()]),
     );
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     tx.mut_repo().record_abandoned_commit(commit_b.id().clone());
@@ -1414,7 +1521,7 @@ fn test_rebase_descendants_update_checkout() {
     tx.mut_repo()
         .set_wc_commit(ws3_id.clone(), commit_a.id().clone())
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let commit_c = tx
@@ -1424,7 +1531,7 @@ fn test_rebase_descendants_update_checkout() {
         .write()
         .unwrap();
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // Workspaces 1 and 2 had B checked out, so they get updated to C. Workspace 3
     // had A checked out, so it doesn't get updated.
@@ -1463,12 +1570,12 @@ fn test_rebase_descendants_update_checkout_abandoned() {
     tx.mut_repo()
         .set_wc_commit(ws3_id.clone(), commit_a.id().clone())
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     tx.mut_repo().record_abandoned_commit(commit_b.id().clone());
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     // Workspaces 1 and 2 had B checked out, so they get updated to the same new
     // commit on top of C. Workspace 3 had A checked out, so it doesn't get updated.
@@ -1516,12 +1623,12 @@ fn test_rebase_descendants_update_checkout_abandoned_merge() {
     tx.mut_repo()
         .set_wc_commit(workspace_id.clone(), commit_d.id().clone())
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     tx.mut_repo().record_abandoned_commit(commit_d.id().clone());
     tx.mut_repo().rebase_descendants(&settings).unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let new_checkout_id = repo.view().get_wc_commit_id(&workspace_id).unwrap();
     let checkout = repo.store().get_commit(new_checkout_id).unwrap();
@@ -1600,6 +1707,7 @@ fn test_empty_commit_option(empty_behavior: EmptyBehaviour) {
             RebaseOptions {
                 empty: empty_behavior.clone(),
                 simplify_ancestor_merge: true,
+                stop_on_conflict: false,
             },
         )
         .unwrap();
@@ -1734,6 +1842,7 @@ fn test_rebase_abandoning_empty() {
     let rebase_options = RebaseOptions {
         empty: EmptyBehaviour::AbandonAllEmpty,
         simplify_ancestor_merge: true,
+        stop_on_conflict: false,
     };
     rebase_commit_with_options(
         &settings,
@@ -1770,3 +1879,104 @@ fn test_rebase_abandoning_empty() {
         hashset! {new_commit_f.id().clone(), new_wc_commit_id.clone()}
     );
 }
+
+#[test]
+fn test_rebase_descendants_stop_on_conflict() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Commit B is rebased onto X, which modifies the same file, so B would
+    // become conflicted. B and its descendants C and D should be left in place.
+    //
+    // D
+    // |
+    // C
+    // |
+    // B X
+    // |/
+    // A
+    let path = RepoPath::from_internal_string("file");
+    let tree_a = create_tree(repo, &[(path, "a")]);
+    let tree_a2 = create_tree(repo, &[(path, "a2")]);
+    let tree_b = create_tree(repo, &[(path, "b")]);
+    let tree_x = create_tree(repo, &[(path, "x")]);
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+    let mut create_commit = |parents: &[&Commit], tree: &MergedTree| {
+        create_random_commit(mut_repo, &settings)
+            .set_parents(
+                parents
+                    .iter()
+                    .map(|commit| commit.id().clone())
+                    .collect_vec(),
+            )
+            .set_tree_id(tree.id())
+            .write()
+            .unwrap()
+    };
+    let root_commit = repo.store().root_commit();
+    let commit_a = create_commit(&[&root_commit], &tree_a);
+    let commit_b = create_commit(&[&commit_a], &tree_b);
+    let commit_c = create_commit(&[&commit_b], &tree_b);
+    let commit_d = create_commit(&[&commit_c], &tree_b);
+    let commit_x = create_commit(&[&commit_a], &tree_x);
+    let repo = tx.commit("test").unwrap();
+
+    let rebase_options = RebaseOptions {
+        stop_on_conflict: true,
+        ..Default::default()
+    };
+    let mut tx = repo.start_transaction(&settings);
+    let rebased_commit = rebase_commit_with_options(
+        &settings,
+        tx.mut_repo(),
+        &commit_b,
+        &[commit_x.clone()],
+        &rebase_options,
+    )
+    .unwrap();
+    assert!(matches!(rebased_commit, RebasedCommit::Stopped));
+    let (num_rebased, stopped) = tx
+        .mut_repo()
+        .rebase_descendants_with_options_return_stopped(&settings, rebase_options.clone())
+        .unwrap();
+    assert_eq!(num_rebased, 0);
+    assert!(stopped.is_empty());
+    assert_eq!(
+        *tx.mut_repo().view().heads(),
+        hashset! {commit_d.id().clone(), commit_x.id().clone()}
+    );
+
+    // When A is rewritten so that B and X would become conflicted, they are left
+    // in place along with their descendants, and reported with the parents they
+    // would have been rebased onto.
+    let mut tx = repo.start_transaction(&settings);
+    let commit_a2 = tx
+        .mut_repo()
+        .rewrite_commit(&settings, &commit_a)
+        .set_tree_id(tree_a2.id())
+        .write()
+        .unwrap();
+    let (num_rebased, stopped) = tx
+        .mut_repo()
+        .rebase_descendants_with_options_return_stopped(&settings, rebase_options)
+        .unwrap();
+    assert_eq!(num_rebased, 0);
+    assert_eq!(
+        stopped,
+        hashmap! {
+            commit_b.id().clone() => vec![commit_a2.id().clone()],
+            commit_x.id().clone() => vec![commit_a2.id().clone()],
+        }
+    );
+    assert_eq!(
+        *tx.mut_repo().view().heads(),
+        hashset! {
+            commit_a2.id().clone(),
+            commit_d.id().clone(),
+            commit_x.id().clone(),
+        }
+    );
+}
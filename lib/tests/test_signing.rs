@@ -63,7 +63,7 @@ fn manual(backend: TestRepoBackend) {
         .set_author(someone_else())
         .write()
         .unwrap();
-    tx.commit("test");
+    tx.commit("test").unwrap();
 
     let commit1 = repo.store().get_commit(commit1.id()).unwrap();
     assert_eq!(commit1.verification().unwrap(), good_verification());
@@ -88,7 +88,7 @@ fn keep_on_rewrite(backend: TestRepoBackend) {
         .set_sign_behavior(SignBehavior::Own)
         .write()
         .unwrap();
-    tx.commit("test");
+    tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let mut_repo = tx.mut_repo();
@@ -114,7 +114,7 @@ fn manual_drop_on_rewrite(backend: TestRepoBackend) {
         .set_sign_behavior(SignBehavior::Own)
         .write()
         .unwrap();
-    tx.commit("test");
+    tx.commit("test").unwrap();
 
     let mut tx = repo.start_transaction(&settings);
     let mut_repo = tx.mut_repo();
@@ -145,7 +145,7 @@ fn forced(backend: TestRepoBackend) {
         .set_author(someone_else())
         .write()
         .unwrap();
-    tx.commit("test");
+    tx.commit("test").unwrap();
 
     let commit = repo.store().get_commit(commit.id()).unwrap();
     assert_eq!(commit.verification().unwrap(), good_verification());
@@ -164,7 +164,7 @@ fn configured(backend: TestRepoBackend) {
     let repo = repo.clone();
     let mut tx = repo.start_transaction(&settings);
     let commit = write_random_commit(tx.mut_repo(), &settings);
-    tx.commit("test");
+    tx.commit("test").unwrap();
 
     let commit = repo.store().get_commit(commit.id()).unwrap();
     assert_eq!(commit.verification().unwrap(), good_verification());
@@ -44,7 +44,7 @@ fn test_heads_fork() {
     let initial = graph_builder.initial_commit();
     let child1 = graph_builder.commit_with_parents(&[&initial]);
     let child2 = graph_builder.commit_with_parents(&[&initial]);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     assert_eq!(
         *repo.view().heads(),
@@ -67,7 +67,7 @@ fn test_heads_merge() {
     let child1 = graph_builder.commit_with_parents(&[&initial]);
     let child2 = graph_builder.commit_with_parents(&[&initial]);
     let merge = graph_builder.commit_with_parents(&[&child1, &child2]);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     assert_eq!(*repo.view().heads(), hashset! {merge.id().clone()});
 }
@@ -84,7 +84,7 @@ fn test_merge_views_heads() {
     let head_unchanged = write_random_commit(mut_repo, &settings);
     let head_remove_tx1 = write_random_commit(mut_repo, &settings);
     let head_remove_tx2 = write_random_commit(mut_repo, &settings);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     tx1.mut_repo().remove_head(head_remove_tx1.id());
@@ -149,7 +149,7 @@ fn test_merge_views_checkout() {
         .mut_repo()
         .set_wc_commit(ws5_id.clone(), commit1.id().clone())
         .unwrap();
-    let repo = initial_tx.commit("test");
+    let repo = initial_tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     tx1.mut_repo()
@@ -230,7 +230,7 @@ fn test_merge_views_branches() {
         "feature",
         RefTarget::normal(feature_branch_local_tx0.id().clone()),
     );
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     let main_branch_local_tx1 = write_random_commit(tx1.mut_repo(), &settings);
@@ -300,7 +300,7 @@ fn test_merge_views_tags() {
     mut_repo.set_tag_target("v1.0", RefTarget::normal(v1_tx0.id().clone()));
     let v2_tx0 = write_random_commit(mut_repo, &settings);
     mut_repo.set_tag_target("v2.0", RefTarget::normal(v2_tx0.id().clone()));
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     let v1_tx1 = write_random_commit(tx1.mut_repo(), &settings);
@@ -350,7 +350,7 @@ fn test_merge_views_git_refs() {
         "refs/heads/feature",
         RefTarget::normal(feature_branch_tx0.id().clone()),
     );
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     let main_branch_tx1 = write_random_commit(tx1.mut_repo(), &settings);
@@ -398,7 +398,7 @@ fn test_merge_views_git_heads() {
     let tx0_head = write_random_commit(tx0.mut_repo(), &settings);
     tx0.mut_repo()
         .set_git_head_target(RefTarget::normal(tx0_head.id().clone()));
-    let repo = tx0.commit("test");
+    let repo = tx0.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     let tx1_head = write_random_commit(tx1.mut_repo(), &settings);
@@ -427,7 +427,7 @@ fn test_merge_views_divergent() {
 
     let mut tx = test_repo.repo.start_transaction(&settings);
     let commit_a = write_random_commit(tx.mut_repo(), &settings);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     let commit_a2 = tx1
@@ -466,7 +466,7 @@ fn test_merge_views_child_on_rewritten(child_first: bool) {
 
     let mut tx = test_repo.repo.start_transaction(&settings);
     let commit_a = write_random_commit(tx.mut_repo(), &settings);
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     let commit_b = create_random_commit(tx1.mut_repo(), &settings)
@@ -516,7 +516,7 @@ fn test_merge_views_child_on_rewritten_divergent(on_rewritten: bool, child_first
         .set_change_id(commit_a2.change_id().clone())
         .write()
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     let parent = if on_rewritten { &commit_a2 } else { &commit_a3 };
@@ -572,7 +572,7 @@ fn test_merge_views_child_on_abandoned(child_first: bool) {
         .set_parents(vec![commit_a.id().clone()])
         .write()
         .unwrap();
-    let repo = tx.commit("test");
+    let repo = tx.commit("test").unwrap();
 
     let mut tx1 = repo.start_transaction(&settings);
     let commit_c = create_random_commit(tx1.mut_repo(), &settings)
@@ -252,7 +252,7 @@ pub fn commit_transactions(settings: &UserSettings, txs: Vec<Transaction>) -> Ar
     let repo_loader = txs[0].base_repo().loader();
     let mut op_ids = vec![];
     for tx in txs {
-        op_ids.push(tx.commit("test").op_id().clone());
+        op_ids.push(tx.commit("test").unwrap().op_id().clone());
         std::thread::sleep(std::time::Duration::from_millis(1));
     }
     let repo = repo_loader.load_at_head(settings).unwrap();
@@ -361,6 +361,7 @@ pub fn commit_with_tree(store: &Arc<Store>, tree_id: MergedTreeId) -> Commit {
         description: "description".to_string(),
         author: signature.clone(),
         committer: signature,
+        extra: Default::default(),
         secure_sig: None,
     };
     store.write_commit(commit, None).unwrap()